@@ -0,0 +1,86 @@
+//! Benchmarks for triangulating shapes
+//!
+//! Most faces of typical models lie in axis-aligned planes, and the kernel has
+//! a fast path for those. Each model is triangulated as is, and rotated, which
+//! forces it onto the general path, without changing the amount of work that
+//! is done otherwise. Run them using `cargo bench`, and compare the results of
+//! both variants.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use fj::enclosure::{Enclosure, Standoff};
+use fj_host::{
+    debug::DebugInfo,
+    kernel::{algorithms::tolerance::Tolerance, shapes::ToShape as _},
+};
+
+fn triangulation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("triangulation");
+
+    for (name, model) in models() {
+        // Both variants use the same tolerance, or the curved faces of the
+        // rotated one might be approximated differently.
+        let tolerance = Tolerance::from_bounding_box(&model.bounding_volume());
+
+        for (variant, model) in
+            [("axis-aligned", model.clone()), ("rotated", rotated(model))]
+        {
+            let mut shape = model
+                .to_shape(tolerance, &mut DebugInfo::new())
+                .expect("Failed to build model");
+
+            group.bench_function(BenchmarkId::new(name, variant), |b| {
+                b.iter(|| {
+                    let mut triangles = Vec::new();
+                    shape.topology().triangles(
+                        tolerance,
+                        &mut triangles,
+                        &mut DebugInfo::new(),
+                    );
+                    triangles
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Models that are typical for hobbyist use
+fn models() -> [(&'static str, fj::Shape3d); 2] {
+    let sketch = fj::Sketch::from_points(vec![
+        [0., 0.],
+        [40., 0.],
+        [40., 10.],
+        [30., 10.],
+        [30., 20.],
+        [10., 20.],
+        [10., 10.],
+        [0., 10.],
+    ]);
+    let block = fj::Sweep {
+        shape: sketch.into(),
+        length: 15.,
+    }
+    .into();
+
+    let mut enclosure = Enclosure::new([60., 40., 25.], 2.)
+        .with_standoff(Standoff::new([10., 10.]))
+        .with_standoff(Standoff::new([50., 30.]));
+    enclosure.corner_radius = 0.;
+
+    [("block", block), ("enclosure", enclosure.base())]
+}
+
+/// Rotate a model, so none of its faces are axis-aligned anymore
+fn rotated(model: fj::Shape3d) -> fj::Shape3d {
+    fj::Transform {
+        shape: model,
+        axis: [1., 2., 3.],
+        angle: 0.1,
+        offset: [0.; 3],
+    }
+    .into()
+}
+
+criterion_group!(benches, triangulation);
+criterion_main!(benches);
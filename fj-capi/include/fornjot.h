@@ -0,0 +1,117 @@
+/*
+ * C API for Fornjot
+ *
+ * Build shapes, triangulate them, and read the resulting meshes. Link against
+ * the `fornjot` library, which is built from the `fj-capi` crate.
+ *
+ * Shapes and meshes are owned by the library, and must be freed using
+ * `fj_shape_free` and `fj_mesh_free`. Functions that fail return NULL, and the
+ * reason can be read using `fj_last_error`.
+ */
+
+#ifndef FORNJOT_H
+#define FORNJOT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A shape. Opaque; only ever handled through pointers. */
+typedef struct FjShape FjShape;
+
+/* A triangle mesh. Opaque; read using the `fj_mesh_*` functions. */
+typedef struct FjMesh FjMesh;
+
+/*
+ * Access the message of the last error that occurred on this thread
+ *
+ * Returns NULL, if no error occurred yet. The message stays valid until the
+ * next error occurs on the same thread.
+ *
+ * Functions that take a shape fail, if they are passed NULL. They don't
+ * replace the last error in that case, so calls can be nested, and the error
+ * of the innermost call that failed is reported.
+ */
+const char *fj_last_error(void);
+
+/*
+ * 2-dimensional shapes
+ *
+ * Shapes are only checked for validity once they are triangulated.
+ */
+
+FjShape *fj_circle(double x, double y, double radius);
+FjShape *fj_ellipse(double x, double y, double radius_x, double radius_y);
+
+/* `points` contains `num_points` points, each made up of x and y. */
+FjShape *fj_sketch(const double *points, size_t num_points);
+
+/* Takes ownership of `a` and `b`, even if the call fails. */
+FjShape *fj_difference_2d(FjShape *a, FjShape *b);
+
+/*
+ * 3-dimensional shapes
+ *
+ * All of these take ownership of the shapes that are passed to them, even if
+ * the call fails.
+ */
+
+/* Sweep a 2-dimensional shape along the z-axis. */
+FjShape *fj_sweep(FjShape *shape, double length);
+
+/*
+ * Rotate a shape around `axis` by `angle` (in radians), then translate it by
+ * `offset`. `axis` and `offset` point to 3 values each.
+ */
+FjShape *fj_transform(
+    FjShape *shape,
+    const double *axis,
+    double angle,
+    const double *offset
+);
+
+FjShape *fj_union(FjShape *a, FjShape *b);
+
+/*
+ * Create a shape from a JSON description, in the same format as the shape
+ * descriptions that the host application loads.
+ */
+FjShape *fj_shape_from_json(const char *json);
+
+/* Free a shape. Does nothing, if `shape` is NULL. */
+void fj_shape_free(FjShape *shape);
+
+/*
+ * Triangulation
+ */
+
+/*
+ * Triangulate a shape
+ *
+ * If `tolerance` is not positive, a default tolerance that is based on the
+ * size of the shape is used. Returns null, if `tolerance` is infinite.
+ * Doesn't take ownership of `shape`.
+ */
+FjMesh *fj_triangulate(const FjShape *shape, double tolerance);
+
+size_t fj_mesh_num_vertices(const FjMesh *mesh);
+
+/* 3 coordinates (x, y, z) per vertex. Valid until the mesh is freed. */
+const double *fj_mesh_vertices(const FjMesh *mesh);
+
+size_t fj_mesh_num_triangles(const FjMesh *mesh);
+
+/* 3 vertex indices per triangle. Valid until the mesh is freed. */
+const uint32_t *fj_mesh_indices(const FjMesh *mesh);
+
+/* Free a mesh. Does nothing, if `mesh` is NULL. */
+void fj_mesh_free(FjMesh *mesh);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* FORNJOT_H */
@@ -0,0 +1,44 @@
+use std::{cell::RefCell, ffi::CString, os::raw::c_char, ptr};
+
+use crate::Error;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::default();
+}
+
+/// Remember an error, so it can be read using [`fj_last_error`]
+///
+/// [`Error::Null`] doesn't replace an earlier error, as the null pointer is
+/// most likely the result of that earlier error.
+pub fn set(error: Error) {
+    if matches!(error, Error::Null) && is_set() {
+        return;
+    }
+
+    // Interior null bytes can't be represented in a C string. They are
+    // unlikely to show up in error messages, so just drop them.
+    let message = error.to_string().replace('\0', "");
+    let message = CString::new(message).expect("Removed all null bytes");
+
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+/// Access the message of the last error that occurred on this thread
+///
+/// Returns null, if no error occurred yet. The message is owned by the
+/// library, and stays valid until the next error occurs on the same thread.
+///
+/// Functions that take a shape fail, if they are passed null. They don't
+/// replace the last error in that case, so calls can be nested, and the
+/// error of the innermost call that failed is reported.
+#[no_mangle]
+pub extern "C" fn fj_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| match &*last_error.borrow() {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+fn is_set() -> bool {
+    LAST_ERROR.with(|last_error| last_error.borrow().is_some())
+}
@@ -0,0 +1,142 @@
+//! C API for Fornjot
+//!
+//! This library makes it possible to build shapes and triangulate them from C
+//! or C++, so the Fornjot kernel can be embedded into existing tools. It is
+//! built as a dynamic library. The functions it exports are declared in
+//! `include/fornjot.h`.
+//!
+//! Shapes and meshes are passed to C as pointers to opaque types, which are
+//! freed using [`fj_shape_free`] and [`fj_mesh_free`]. Functions that fail
+//! return null, and the reason can be read using [`fj_last_error`].
+
+mod error;
+mod mesh;
+mod shape;
+
+pub use self::{
+    error::fj_last_error,
+    mesh::{
+        fj_mesh_free, fj_mesh_indices, fj_mesh_num_triangles,
+        fj_mesh_num_vertices, fj_mesh_vertices, fj_triangulate, FjMesh,
+    },
+    shape::{
+        fj_circle, fj_difference_2d, fj_ellipse, fj_shape_free,
+        fj_shape_from_json, fj_sketch, fj_sweep, fj_transform, fj_union,
+        FjShape,
+    },
+};
+
+use thiserror::Error;
+
+/// An error that can occur in a call to the C API
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Expected a shape, but got null")]
+    Null,
+
+    #[error("Only 2-dimensional shapes can be used in a {0}")]
+    Not2d(&'static str),
+
+    #[error("Only 3-dimensional shapes can be used in a {0}")]
+    Not3d(&'static str),
+
+    #[error("Error parsing shape description: {0}")]
+    Json(serde_json::Error),
+
+    #[error("Invalid shape: {0}")]
+    InvalidShape(#[from] fj::ShapeError),
+
+    #[error("{0}")]
+    InvalidTolerance(
+        #[from] fj_host::kernel::algorithms::tolerance::InvalidTolerance,
+    ),
+
+    #[error("Error building shape: {0}")]
+    Kernel(#[from] fj_host::kernel::Error),
+
+    #[error("Error building mesh: {0}")]
+    IndexOverflow(#[from] fj_host::mesh::IndexOverflow),
+
+    #[error("Unexpected panic while triangulating shape")]
+    Panic,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::CStr, ptr, slice};
+
+    use super::*;
+
+    unsafe fn last_error() -> String {
+        CStr::from_ptr(fj_last_error())
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn triangulate_sweep() {
+        unsafe {
+            let points = [0., 0., 2., 0., 2., 1., 0., 1.];
+            let sketch = fj_sketch(points.as_ptr(), 4);
+            let sweep = fj_sweep(sketch, 3.);
+
+            let axis = [0., 0., 1.];
+            let offset = [1., 1., 1.];
+            let shape = fj_transform(sweep, axis.as_ptr(), 0., offset.as_ptr());
+            assert!(!shape.is_null());
+
+            let mesh = fj_triangulate(shape, 0.);
+            assert!(!mesh.is_null());
+            fj_shape_free(shape);
+
+            // A box has 8 corners and 6 sides with 2 triangles each.
+            assert_eq!(fj_mesh_num_vertices(mesh), 8);
+            assert_eq!(fj_mesh_num_triangles(mesh), 12);
+
+            let vertices = slice::from_raw_parts(fj_mesh_vertices(mesh), 8 * 3);
+            let indices = slice::from_raw_parts(fj_mesh_indices(mesh), 12 * 3);
+            assert!(indices.iter().all(|&index| index < 8));
+
+            for vertex in vertices.chunks(3) {
+                assert!([1., 3.].contains(&vertex[0]));
+                assert!([1., 2.].contains(&vertex[1]));
+                assert!([1., 4.].contains(&vertex[2]));
+            }
+
+            fj_mesh_free(mesh);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        unsafe {
+            // Sweeping a 3-dimensional shape fails, and the error is kept by
+            // the call that is passed the resulting null pointer.
+            let sweep = fj_sweep(fj_circle(0., 0., 1.), 1.);
+            let shape = fj_union(fj_sweep(sweep, 1.), ptr::null_mut());
+            assert!(shape.is_null());
+            assert_eq!(
+                last_error(),
+                "Only 2-dimensional shapes can be used in a sweep"
+            );
+
+            // Invalid shapes are rejected when they are triangulated.
+            let circle = fj_circle(0., 0., -1.);
+            assert!(fj_triangulate(circle, 0.).is_null());
+            assert_eq!(
+                last_error(),
+                "Invalid shape: Circle radius must be positive, but is -1"
+            );
+            fj_shape_free(circle);
+
+            let circle = fj_circle(0., 0., 1.);
+            assert!(fj_triangulate(circle, f64::INFINITY).is_null());
+            assert!(last_error().starts_with("Invalid tolerance"));
+            fj_shape_free(circle);
+
+            let json = b"{\"Shape2d\":{}}\0";
+            assert!(fj_shape_from_json(json.as_ptr().cast()).is_null());
+            assert!(last_error().starts_with("Error parsing shape description"));
+        }
+    }
+}
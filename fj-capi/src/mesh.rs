@@ -0,0 +1,149 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use fj_host::{
+    debug::DebugInfo,
+    evaluation::triangulate,
+    kernel::{algorithms::tolerance::Tolerance, shapes::ToShape as _},
+    mesh::MeshMaker,
+};
+
+use crate::{error, shape::FjShape, Error};
+
+/// A triangle mesh, as it is passed through the C API
+///
+/// This is an opaque type. C code only ever handles pointers to it, and reads
+/// its contents using the accessor functions.
+pub struct FjMesh {
+    vertices: Vec<f64>,
+    indices: Vec<u32>,
+}
+
+/// Triangulate a shape
+///
+/// If `tolerance` is not positive, a default tolerance that is based on the
+/// size of the shape is used. Returns null, if `tolerance` is infinite, if the
+/// shape is invalid, or if the kernel can't build it. Doesn't take ownership
+/// of `shape`.
+///
+/// # Safety
+///
+/// `shape` must be null, or have been returned by this library and not been
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn fj_triangulate(
+    shape: *const FjShape,
+    tolerance: f64,
+) -> *mut FjMesh {
+    let shape = match shape.as_ref() {
+        Some(FjShape(shape)) => shape,
+        None => {
+            error::set(Error::Null);
+            return ptr::null_mut();
+        }
+    };
+
+    // A panic must not unwind into C code.
+    let mesh = panic::catch_unwind(AssertUnwindSafe(|| mesh(shape, tolerance)))
+        .unwrap_or(Err(Error::Panic));
+
+    match mesh {
+        Ok(mesh) => Box::into_raw(Box::new(mesh)),
+        Err(err) => {
+            error::set(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Access the number of vertices in a mesh
+///
+/// # Safety
+///
+/// `mesh` must have been returned by [`fj_triangulate`] and not been freed
+/// yet.
+#[no_mangle]
+pub unsafe extern "C" fn fj_mesh_num_vertices(mesh: *const FjMesh) -> usize {
+    (*mesh).vertices.len() / 3
+}
+
+/// Access the vertices of a mesh
+///
+/// Each vertex is made up of an x, a y, and a z coordinate. The vertices are
+/// owned by the mesh and are valid until it is freed.
+///
+/// # Safety
+///
+/// `mesh` must have been returned by [`fj_triangulate`] and not been freed
+/// yet.
+#[no_mangle]
+pub unsafe extern "C" fn fj_mesh_vertices(mesh: *const FjMesh) -> *const f64 {
+    (*mesh).vertices.as_ptr()
+}
+
+/// Access the number of triangles in a mesh
+///
+/// # Safety
+///
+/// `mesh` must have been returned by [`fj_triangulate`] and not been freed
+/// yet.
+#[no_mangle]
+pub unsafe extern "C" fn fj_mesh_num_triangles(mesh: *const FjMesh) -> usize {
+    (*mesh).indices.len() / 3
+}
+
+/// Access the triangles of a mesh
+///
+/// Each triangle is made up of the indices of its 3 vertices. The indices are
+/// owned by the mesh and are valid until it is freed.
+///
+/// # Safety
+///
+/// `mesh` must have been returned by [`fj_triangulate`] and not been freed
+/// yet.
+#[no_mangle]
+pub unsafe extern "C" fn fj_mesh_indices(mesh: *const FjMesh) -> *const u32 {
+    (*mesh).indices.as_ptr()
+}
+
+/// Free a mesh
+///
+/// Does nothing, if `mesh` is null.
+///
+/// # Safety
+///
+/// `mesh` must be null, or have been returned by [`fj_triangulate`] and not
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn fj_mesh_free(mesh: *mut FjMesh) {
+    if !mesh.is_null() {
+        drop(Box::from_raw(mesh));
+    }
+}
+
+fn mesh(shape: &fj::Shape, tolerance: f64) -> Result<FjMesh, Error> {
+    shape.check()?;
+
+    let tolerance = if tolerance > 0. {
+        Tolerance::from_scalar(tolerance)?
+    } else {
+        Tolerance::from_bounding_box(&shape.bounding_volume())
+    };
+
+    let mut triangles = Vec::new();
+    triangulate(shape, tolerance, &mut triangles, &mut DebugInfo::new())?;
+
+    let mut mesh_maker = MeshMaker::new();
+    for triangle in triangles {
+        for vertex in triangle.points() {
+            mesh_maker.push(vertex)?;
+        }
+    }
+
+    let vertices = mesh_maker.vertices().flat_map(<[f64; 3]>::from).collect();
+    let indices = mesh_maker.indices().collect();
+
+    Ok(FjMesh { vertices, indices })
+}
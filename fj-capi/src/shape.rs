@@ -0,0 +1,243 @@
+use std::{ffi::CStr, os::raw::c_char, ptr, slice};
+
+use crate::{error, Error};
+
+/// A shape, as it is passed through the C API
+///
+/// This is an opaque type. C code only ever handles pointers to it.
+pub struct FjShape(pub fj::Shape);
+
+/// Create a circle
+#[no_mangle]
+pub extern "C" fn fj_circle(x: f64, y: f64, radius: f64) -> *mut FjShape {
+    output(Ok(fj::Circle {
+        center: [x, y],
+        radius,
+    }
+    .into()))
+}
+
+/// Create an ellipse
+#[no_mangle]
+pub extern "C" fn fj_ellipse(
+    x: f64,
+    y: f64,
+    radius_x: f64,
+    radius_y: f64,
+) -> *mut FjShape {
+    output(Ok(fj::Ellipse {
+        center: [x, y],
+        radii: [radius_x, radius_y],
+    }
+    .into()))
+}
+
+/// Create a sketch from its points
+///
+/// `points` contains `num_points` points, each made up of an x and a y
+/// coordinate, so it must be valid for reading `2 * num_points` values.
+///
+/// # Safety
+///
+/// `points` must point to `2 * num_points` valid `double`s.
+#[no_mangle]
+pub unsafe extern "C" fn fj_sketch(
+    points: *const f64,
+    num_points: usize,
+) -> *mut FjShape {
+    if points.is_null() {
+        return output(Err(Error::Null));
+    }
+
+    let points = slice::from_raw_parts(points, num_points * 2)
+        .chunks(2)
+        .map(|point| [point[0], point[1]])
+        .collect();
+
+    output(Ok(fj::Sketch::from_points(points).into()))
+}
+
+/// Create the difference of two 2-dimensional shapes
+///
+/// Takes ownership of `a` and `b`, even if the call fails.
+///
+/// # Safety
+///
+/// `a` and `b` must be null, or have been returned by this library and not
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn fj_difference_2d(
+    a: *mut FjShape,
+    b: *mut FjShape,
+) -> *mut FjShape {
+    let (a, b) = (take(a), take(b));
+
+    output((|| {
+        let a = shape_2d(a?, "difference")?;
+        let b = shape_2d(b?, "difference")?;
+
+        Ok(fj::Difference2d { a, b }.into())
+    })())
+}
+
+/// Sweep a 2-dimensional shape along the z-axis
+///
+/// Takes ownership of `shape`, even if the call fails.
+///
+/// # Safety
+///
+/// `shape` must be null, or have been returned by this library and not been
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn fj_sweep(
+    shape: *mut FjShape,
+    length: f64,
+) -> *mut FjShape {
+    let shape = take(shape);
+
+    output((|| {
+        let shape = shape_2d(shape?, "sweep")?;
+        Ok(fj::Sweep { shape, length }.into())
+    })())
+}
+
+/// Rotate a 3-dimensional shape around an axis, then translate it
+///
+/// `axis` and `offset` each point to 3 values. `angle` is in radians. Takes
+/// ownership of `shape`, even if the call fails.
+///
+/// # Safety
+///
+/// `shape` must be null, or have been returned by this library and not been
+/// freed yet. `axis` and `offset` must each point to 3 valid `double`s.
+#[no_mangle]
+pub unsafe extern "C" fn fj_transform(
+    shape: *mut FjShape,
+    axis: *const f64,
+    angle: f64,
+    offset: *const f64,
+) -> *mut FjShape {
+    let shape = take(shape);
+
+    output((|| {
+        let shape = shape_3d(shape?, "transform")?;
+        let axis = vector(axis)?;
+        let offset = vector(offset)?;
+
+        Ok(fj::Transform {
+            shape,
+            axis,
+            angle,
+            offset,
+        }
+        .into())
+    })())
+}
+
+/// Create the union of two 3-dimensional shapes
+///
+/// Takes ownership of `a` and `b`, even if the call fails.
+///
+/// # Safety
+///
+/// `a` and `b` must be null, or have been returned by this library and not
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn fj_union(
+    a: *mut FjShape,
+    b: *mut FjShape,
+) -> *mut FjShape {
+    let (a, b) = (take(a), take(b));
+
+    output((|| {
+        let a = shape_3d(a?, "union")?;
+        let b = shape_3d(b?, "union")?;
+
+        Ok(fj::Union { a, b }.into())
+    })())
+}
+
+/// Create a shape from its JSON description
+///
+/// Uses the same format as shape descriptions that are loaded by the host
+/// application.
+///
+/// # Safety
+///
+/// `json` must be null, or point to a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn fj_shape_from_json(
+    json: *const c_char,
+) -> *mut FjShape {
+    if json.is_null() {
+        return output(Err(Error::Null));
+    }
+
+    let json = CStr::from_ptr(json).to_bytes();
+    output(serde_json::from_slice(json).map_err(Error::Json))
+}
+
+/// Free a shape
+///
+/// Does nothing, if `shape` is null.
+///
+/// # Safety
+///
+/// `shape` must be null, or have been returned by this library and not been
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn fj_shape_free(shape: *mut FjShape) {
+    let _ = take(shape);
+}
+
+/// Take ownership of a shape that was passed in from C
+///
+/// Returns [`Error::Null`], if the pointer is null.
+unsafe fn take(shape: *mut FjShape) -> Result<fj::Shape, Error> {
+    if shape.is_null() {
+        return Err(Error::Null);
+    }
+
+    Ok(Box::from_raw(shape).0)
+}
+
+/// Pass a shape to C, or remember the error and pass null instead
+fn output(shape: Result<fj::Shape, Error>) -> *mut FjShape {
+    match shape {
+        Ok(shape) => Box::into_raw(Box::new(FjShape(shape))),
+        Err(err) => {
+            error::set(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+fn shape_2d(
+    shape: fj::Shape,
+    operation: &'static str,
+) -> Result<fj::Shape2d, Error> {
+    match shape {
+        fj::Shape::Shape2d(shape) => Ok(shape),
+        fj::Shape::Shape3d(_) => Err(Error::Not2d(operation)),
+    }
+}
+
+fn shape_3d(
+    shape: fj::Shape,
+    operation: &'static str,
+) -> Result<fj::Shape3d, Error> {
+    match shape {
+        fj::Shape::Shape3d(shape) => Ok(shape),
+        fj::Shape::Shape2d(_) => Err(Error::Not3d(operation)),
+    }
+}
+
+unsafe fn vector(components: *const f64) -> Result<[f64; 3], Error> {
+    if components.is_null() {
+        return Err(Error::Null);
+    }
+
+    let mut vector = [0.; 3];
+    vector.copy_from_slice(slice::from_raw_parts(components, 3));
+    Ok(vector)
+}
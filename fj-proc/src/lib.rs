@@ -0,0 +1,152 @@
+//! Procedural macros for the Fornjot modeling library
+//!
+//! This crate is an implementation detail of [`fj`], which re-exports its
+//! macros. Please use them from there.
+//!
+//! [`fj`]: https://crates.io/crates/fj
+
+mod parameter;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned as _, ItemFn, ReturnType};
+
+use self::parameter::Parameter;
+
+/// Define a model
+///
+/// Turns a function into a model, that can be loaded by the host application.
+/// The function returns the shape of the model, and each of its arguments is a
+/// parameter of the model, which is parsed from the value that is passed to
+/// the host application.
+///
+/// Parameters are configured using the `#[param]` attribute, which accepts
+/// the following settings:
+///
+/// - `default`: The value that is used, if no value is passed. Parameters
+///   without a default value are required.
+/// - `min` and `max`: The range of valid values for numeric parameters. The
+///   host application rejects values outside of it.
+/// - `step`: The increment by which a numeric parameter is meant to be
+///   changed, for example using a slider.
+///
+/// ```
+/// #[fj::model]
+/// pub fn cuboid(
+///     #[param(default = 3.0, min = 0.5, step = 0.5)] x: f64,
+///     #[param(default = 2.0)] y: f64,
+///     #[param(default = 1.0, min = 0.1, max = 10.0)] z: f64,
+/// ) -> fj::Shape {
+///     let rectangle = fj::Sketch::from_points(vec![
+///         [-x / 2., -y / 2.],
+///         [x / 2., -y / 2.],
+///         [x / 2., y / 2.],
+///         [-x / 2., y / 2.],
+///     ]);
+///
+///     fj::Sweep {
+///         shape: rectangle.into(),
+///         length: z,
+///     }
+///     .into()
+/// }
+/// # fn main() {}
+/// ```
+///
+/// Besides the function that evaluates the model, the macro generates the
+/// metadata that the host application uses to check that the model is
+/// compatible, and to validate the parameters before evaluating it. The shape
+/// is checked for degenerate input (see `fj::Shape::check`), before it is
+/// returned to the host application. There can only be one model per crate.
+#[proc_macro_attribute]
+pub fn model(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        let attr = proc_macro2::TokenStream::from(attr);
+        return syn::Error::new(
+            attr.span(),
+            "`#[fj::model]` doesn't take any arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let function = parse_macro_input!(item as ItemFn);
+
+    match expand(function) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(mut function: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let sig = &function.sig;
+    if let Some(asyncness) = sig.asyncness {
+        return Err(syn::Error::new(
+            asyncness.span(),
+            "model function can't be `async`",
+        ));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            sig.generics.span(),
+            "model function can't be generic",
+        ));
+    }
+    if let ReturnType::Default = sig.output {
+        return Err(syn::Error::new(
+            sig.span(),
+            "model function must return a shape",
+        ));
+    }
+
+    let parameters = function
+        .sig
+        .inputs
+        .iter_mut()
+        .map(Parameter::parse)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let ident = &function.sig.ident;
+    let name = ident.to_string();
+    let reads = parameters.iter().map(Parameter::read);
+    let metadata = parameters.iter().map(Parameter::metadata);
+
+    Ok(quote! {
+        #function
+
+        #[doc(hidden)]
+        mod __fj_model {
+            #[allow(unused_imports)]
+            use super::*;
+
+            #[no_mangle]
+            #[allow(improper_ctypes_definitions)]
+            pub extern "C" fn model(
+                args: &::std::collections::HashMap<
+                    ::std::string::String,
+                    ::std::string::String,
+                >,
+            ) -> ::std::result::Result<::fj::Shape, ::fj::ShapeError> {
+                let shape: ::fj::Shape = super::#ident(#(#reads),*).into();
+                shape.checked()
+            }
+
+            #[no_mangle]
+            pub extern "C" fn fj_abi_version() -> u32 {
+                ::fj::abi::VERSION
+            }
+
+            #[no_mangle]
+            #[allow(improper_ctypes_definitions)]
+            pub extern "C" fn fj_metadata() -> &'static ::fj::abi::Metadata {
+                static METADATA: ::fj::abi::Metadata = ::fj::abi::Metadata {
+                    name: #name,
+                    fj_version: ::fj::abi::FJ_VERSION,
+                    parameters: &[#(#metadata),*],
+                };
+
+                &METADATA
+            }
+        }
+    })
+}
@@ -0,0 +1,180 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens as _};
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned as _,
+    Expr, FnArg, Ident, Pat, Token, Type,
+};
+
+/// A parameter of a model function
+pub struct Parameter {
+    pub ident: Ident,
+    pub ty: Type,
+    pub default: Option<Expr>,
+    pub min: Option<Expr>,
+    pub max: Option<Expr>,
+    pub step: Option<Expr>,
+}
+
+impl Parameter {
+    /// Parse a parameter from an argument of the model function
+    ///
+    /// Removes the `#[param]` attributes from the argument, as they are not
+    /// valid outside of the macro.
+    pub fn parse(arg: &mut FnArg) -> syn::Result<Self> {
+        let arg = match arg {
+            FnArg::Typed(arg) => arg,
+            FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new(
+                    receiver.span(),
+                    "model function can't take `self`",
+                ));
+            }
+        };
+
+        let ident = match &*arg.pat {
+            Pat::Ident(pat) => pat.ident.clone(),
+            pat => {
+                return Err(syn::Error::new(
+                    pat.span(),
+                    "model parameters must be plain identifiers",
+                ));
+            }
+        };
+
+        let mut parameter = Self {
+            ident,
+            ty: (*arg.ty).clone(),
+            default: None,
+            min: None,
+            max: None,
+            step: None,
+        };
+
+        let (params, attrs) = arg
+            .attrs
+            .drain(..)
+            .partition(|attr| attr.path.is_ident("param"));
+        arg.attrs = attrs;
+
+        for attr in params {
+            let settings = attr.parse_args_with(
+                Punctuated::<Setting, Token![,]>::parse_terminated,
+            )?;
+
+            for setting in settings {
+                parameter.apply(setting)?;
+            }
+        }
+
+        Ok(parameter)
+    }
+
+    /// The name of the parameter, as used in the model arguments
+    pub fn name(&self) -> String {
+        self.ident.to_string()
+    }
+
+    /// Generate an expression that reads the parameter from `args`
+    pub fn read(&self) -> TokenStream {
+        let ty = &self.ty;
+        let name = self.name();
+        let default = match &self.default {
+            Some(default) => quote!(::std::option::Option::Some(#default)),
+            None => quote!(::std::option::Option::None),
+        };
+
+        quote! {
+            ::fj::abi::parameter::<#ty>(args, #name, #default)
+        }
+    }
+
+    /// Generate the metadata of the parameter
+    pub fn metadata(&self) -> TokenStream {
+        let name = self.name();
+        let ty = self.ty.to_token_stream().to_string();
+        let default = match &self.default {
+            Some(default) => {
+                let default = default.to_token_stream().to_string();
+                quote!(::std::option::Option::Some(#default))
+            }
+            None => quote!(::std::option::Option::None),
+        };
+
+        // Only numeric parameters have a range, so the default of those can
+        // be converted to a number.
+        let ranged = self.min.is_some() || self.max.is_some();
+        let default_value = match &self.default {
+            Some(default) if ranged => {
+                quote!(::std::option::Option::Some((#default) as f64))
+            }
+            _ => quote!(::std::option::Option::None),
+        };
+
+        let [min, max, step] =
+            [&self.min, &self.max, &self.step].map(|value| match value {
+                Some(value) => {
+                    quote!(::std::option::Option::Some((#value) as f64))
+                }
+                None => quote!(::std::option::Option::None),
+            });
+
+        quote! {
+            ::fj::abi::Parameter {
+                name: #name,
+                ty: #ty,
+                default: #default,
+                default_value: #default_value,
+                min: #min,
+                max: #max,
+                step: #step,
+            }
+        }
+    }
+
+    fn apply(&mut self, setting: Setting) -> syn::Result<()> {
+        match setting.key.to_string().as_str() {
+            "default" => set(&mut self.default, setting),
+            "min" => set(&mut self.min, setting),
+            "max" => set(&mut self.max, setting),
+            "step" => set(&mut self.step, setting),
+            key => Err(syn::Error::new(
+                setting.key.span(),
+                format!(
+                    "unknown parameter setting `{}`; expected one of \
+                    `default`, `min`, `max`, or `step`",
+                    key
+                ),
+            )),
+        }
+    }
+}
+
+/// A setting of a parameter, in the form `key = value`
+struct Setting {
+    key: Ident,
+    value: Expr,
+}
+
+impl Parse for Setting {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+
+        Ok(Self { key, value })
+    }
+}
+
+fn set(target: &mut Option<Expr>, setting: Setting) -> syn::Result<()> {
+    if target.is_some() {
+        return Err(syn::Error::new(
+            setting.key.span(),
+            format!("`{}` is specified more than once", setting.key),
+        ));
+    }
+
+    *target = Some(setting.value);
+    Ok(())
+}
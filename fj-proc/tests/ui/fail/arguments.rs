@@ -0,0 +1,6 @@
+#[fj::model(cuboid)]
+pub fn model() -> fj::Shape {
+    fj::Circle { center: [0., 0.], radius: 1. }.into()
+}
+
+fn main() {}
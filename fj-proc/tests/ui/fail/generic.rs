@@ -0,0 +1,6 @@
+#[fj::model]
+pub fn model<T: Into<fj::Shape>>(shape: T) -> fj::Shape {
+    shape.into()
+}
+
+fn main() {}
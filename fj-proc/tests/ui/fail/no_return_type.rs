@@ -0,0 +1,6 @@
+#[fj::model]
+pub fn model(#[param(default = 1.0)] radius: f64) {
+    let _ = radius;
+}
+
+fn main() {}
@@ -0,0 +1,4 @@
+#[fj::model]
+pub struct Model;
+
+fn main() {}
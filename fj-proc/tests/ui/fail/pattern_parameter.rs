@@ -0,0 +1,6 @@
+#[fj::model]
+pub fn model((a, b): (f64, f64)) -> fj::Shape {
+    fj::Circle { center: [0., 0.], radius: a + b }.into()
+}
+
+fn main() {}
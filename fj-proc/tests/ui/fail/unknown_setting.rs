@@ -0,0 +1,6 @@
+#[fj::model]
+pub fn model(#[param(fallback = 1.0)] radius: f64) -> fj::Shape {
+    fj::Circle { center: [0., 0.], radius }.into()
+}
+
+fn main() {}
@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+#[fj::model]
+pub fn model(
+    #[param(default = 2.0)] radius: f64,
+    args: u64,
+    #[param(default = 2, min = 1, max = 8, step = 1)] count: u64,
+    #[param(default = -1.0, min = -2.0, max = 0.0)] offset: f64,
+) -> fj::Circle {
+    fj::Circle {
+        center: [offset, 0.],
+        radius: radius * (args * count) as f64,
+    }
+}
+
+fn main() {
+    let mut args = HashMap::new();
+    args.insert("args".to_owned(), "3".to_owned());
+
+    match __fj_model::model(&args) {
+        fj::Shape::Shape2d(fj::Shape2d::Circle(circle)) => {
+            assert_eq!(circle.center, [-1., 0.]);
+            assert_eq!(circle.radius, 12.);
+        }
+        _ => panic!("Unexpected shape"),
+    }
+
+    let metadata = __fj_model::fj_metadata();
+    assert_eq!(metadata.name, "model");
+    assert_eq!(metadata.parameters.len(), 4);
+    assert_eq!(metadata.parameters[0].default, Some("2.0"));
+    assert_eq!(metadata.parameters[0].default_value, None);
+    assert_eq!(metadata.parameters[1].ty, "u64");
+    assert_eq!(metadata.parameters[1].default, None);
+    assert_eq!(metadata.parameters[1].min, None);
+    assert_eq!(metadata.parameters[2].min, Some(1.));
+    assert_eq!(metadata.parameters[2].max, Some(8.));
+    assert_eq!(metadata.parameters[2].step, Some(1.));
+    assert_eq!(metadata.parameters[2].default_value, Some(2.));
+    assert_eq!(metadata.parameters[3].default_value, Some(-1.));
+}
@@ -0,0 +1,26 @@
+// Demo of the web viewer
+//
+// Loads the module that `wasm-pack` generates in `pkg/`, and displays one of
+// the built-in models. Which model is displayed can be chosen from a list, or
+// by adding `?model=<name>` to the URL.
+
+import init, { model_names, view } from "./pkg/fj_web.js";
+
+await init();
+
+const select = document.getElementById("models");
+for (const name of model_names()) {
+    select.add(new Option(name, name));
+}
+
+const params = new URLSearchParams(window.location.search);
+select.value = params.get("model") ?? "star";
+
+// The event loop of the viewer runs until the page is left, so switching to
+// another model means loading the page again.
+select.addEventListener("change", () => {
+    params.set("model", select.value);
+    window.location.search = params.toString();
+});
+
+view(select.value, "viewer");
@@ -0,0 +1,211 @@
+//! Web viewer for Fornjot
+//!
+//! Displays one of the models in [`models`] in the browser, using WebGL. The
+//! viewer is started from JavaScript by calling [`view`]. See `index.html` for
+//! a demo.
+//!
+//! Models can't be loaded from dynamic libraries on the web, which is why only
+//! built-in models can be displayed. Everything else, from triangulating the
+//! model to rendering it, is done by the same code as in the host application.
+
+#![cfg(target_arch = "wasm32")]
+
+pub mod models;
+
+use wasm_bindgen::prelude::*;
+use winit::{
+    dpi::LogicalSize,
+    event::{Event, WindowEvent},
+    event_loop::EventLoop,
+    platform::web::WindowExtWebSys as _,
+};
+
+use fj_host::{
+    camera::Camera,
+    evaluation::{ProcessedShape, ShapeProcessor},
+    graphics::{DrawConfig, Renderer, ThemeConfig, Vertices},
+    input,
+    mesh::Silhouette,
+    platform::Instant,
+    window::Window,
+};
+
+/// Display a built-in model in the element with the given ID
+///
+/// Fails, if there is no model named `model`, if the element doesn't exist,
+/// or if the model can't be triangulated. Initializing the renderer happens
+/// afterwards, so errors from that are logged to the console instead.
+#[wasm_bindgen]
+pub fn view(model: &str, element_id: &str) -> Result<(), JsValue> {
+    console_error_panic_hook::set_once();
+
+    let shape = models::get(model)
+        .ok_or_else(|| format!("Unknown model: {}", model))?;
+    let shape = ShapeProcessor {
+        edges: true,
+        ..ShapeProcessor::new()
+    }
+    .process(&shape)
+    .map_err(|err| format!("Error processing model: {}", err))?;
+
+    let element = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id(element_id))
+        .ok_or_else(|| format!("No element with ID `{}`", element_id))?;
+
+    let event_loop = EventLoop::new();
+    let window = Window::new(&event_loop, model);
+
+    // The canvas fills the element it is added to.
+    window.inner().set_inner_size(LogicalSize::new(
+        element.client_width(),
+        element.client_height(),
+    ));
+    element.append_child(&window.inner().canvas())?;
+
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(err) = run(event_loop, window, shape).await {
+            web_sys::console::error_1(&err);
+        }
+    });
+
+    Ok(())
+}
+
+/// The names of the built-in models, which can be passed to [`view`]
+#[wasm_bindgen]
+pub fn model_names() -> Box<[JsValue]> {
+    models::NAMES.iter().map(|&name| name.into()).collect()
+}
+
+async fn run(
+    event_loop: EventLoop<()>,
+    window: Window,
+    shape: ProcessedShape,
+) -> Result<(), JsValue> {
+    let theme_config = ThemeConfig::default();
+    let mut theme = theme_config.variant;
+
+    let mut renderer = Renderer::new(&window, theme_config.theme(theme))
+        .await
+        .map_err(|err| format!("Error initializing renderer: {}", err))?;
+    renderer.update_geometry(
+        Vertices::mesh(&shape.triangles, &shape.faces),
+        Vertices::edges(shape.edges.iter().map(|edge| edge.points())),
+        Silhouette::new(&shape.triangles),
+        (&shape.debug_info).into(),
+        Vertices::validation_errors(&shape.debug_info, &shape.aabb),
+        shape.aabb,
+    );
+
+    let mut previous_time = Instant::now();
+
+    let mut input_handler = input::Handler::new(previous_time);
+    let mut draw_config = DrawConfig::default();
+    let mut camera = Camera::new(&shape.aabb);
+
+    // On the web, this doesn't return. The browser keeps calling the closure
+    // after this function has been left.
+    event_loop.run(move |event, _, _| {
+        let mut actions = input::Actions::new();
+
+        let now = Instant::now();
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::Resized(size),
+                ..
+            } => {
+                renderer.handle_resize(size);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput { input, .. },
+                ..
+            } => {
+                input_handler.handle_keyboard_input(input, &mut actions);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CursorMoved { position, .. },
+                ..
+            } => {
+                input_handler.handle_cursor_moved(
+                    position,
+                    &mut camera,
+                    &window,
+                    &shape.aabb,
+                );
+            }
+            Event::WindowEvent {
+                event: WindowEvent::MouseInput { state, button, .. },
+                ..
+            } => {
+                let focus_point = camera.focus_point(
+                    &window,
+                    input_handler.cursor(),
+                    &shape.triangles,
+                );
+
+                input_handler.handle_mouse_input(button, state, focus_point);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::MouseWheel { delta, .. },
+                ..
+            } => {
+                input_handler.handle_mouse_wheel(delta, now);
+            }
+            Event::MainEventsCleared => {
+                let delta_t = now.duration_since(previous_time);
+                previous_time = now;
+
+                input_handler.update(
+                    delta_t.as_secs_f64(),
+                    now,
+                    &mut camera,
+                    &window,
+                    &shape.triangles,
+                    &shape.aabb,
+                );
+
+                window.inner().request_redraw();
+            }
+            Event::RedrawRequested(_) => {
+                camera.update_planes(&shape.aabb);
+
+                if let Err(err) = renderer.draw(&camera, &draw_config) {
+                    web_sys::console::error_1(
+                        &format!("Draw error: {}", err).into(),
+                    );
+                }
+            }
+            _ => {}
+        }
+
+        // There's nothing to exit to in the browser, and no toolpath to show,
+        // so those actions are ignored.
+        if actions.toggle_model {
+            draw_config.draw_model = !draw_config.draw_model;
+        }
+        if actions.toggle_mesh {
+            draw_config.draw_mesh = !draw_config.draw_mesh;
+        }
+        if actions.toggle_debug {
+            draw_config.draw_debug = !draw_config.draw_debug;
+        }
+        if actions.toggle_validation {
+            draw_config.draw_validation = !draw_config.draw_validation;
+        }
+        if actions.toggle_edges {
+            draw_config.draw_edges = !draw_config.draw_edges;
+        }
+        if actions.toggle_xray {
+            draw_config.draw_xray = !draw_config.draw_xray;
+        }
+        if actions.zoom_to_fit {
+            camera.fit(&shape.aabb);
+        }
+        if actions.toggle_theme {
+            theme = theme.toggled();
+            renderer.set_theme(theme_config.theme(theme));
+        }
+    });
+}
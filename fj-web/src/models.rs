@@ -0,0 +1,79 @@
+//! The models that the web viewer can display
+//!
+//! These are the same as the example models in the `models/` directory, with
+//! their default parameters.
+
+use std::f64::consts::PI;
+
+/// The names of the built-in models
+pub const NAMES: &[&str] = &["spacer", "star"];
+
+/// Create the built-in model with the given name
+///
+/// Returns `None`, if there is no model with that name.
+pub fn get(name: &str) -> Option<fj::Shape> {
+    match name {
+        "spacer" => Some(spacer(1.0, 0.5, 1.0)),
+        "star" => Some(star(5, 1.0, 2.0, 1.0)),
+        _ => None,
+    }
+}
+
+/// See `models/spacer`
+pub fn spacer(outer: f64, inner: f64, height: f64) -> fj::Shape {
+    let outer_edge = fj::Circle {
+        center: [0., 0.],
+        radius: outer,
+    };
+    let inner_edge = fj::Circle {
+        center: [0., 0.],
+        radius: inner,
+    };
+
+    let footprint = fj::Difference2d {
+        a: outer_edge.into(),
+        b: inner_edge.into(),
+    };
+
+    let spacer = fj::Sweep {
+        shape: footprint.into(),
+        length: height,
+    };
+
+    spacer.into()
+}
+
+/// See `models/star`
+pub fn star(num_points: u64, r1: f64, r2: f64, h: f64) -> fj::Shape {
+    let num_vertices = num_points * 2;
+
+    let mut outer = Vec::new();
+    let mut inner = Vec::new();
+    for i in 0..num_vertices {
+        let angle = 2. * PI / num_vertices as f64 * i as f64;
+        let radius = if i % 2 == 0 { r1 } else { r2 };
+
+        let (sin, cos) = angle.sin_cos();
+
+        let x = cos * radius;
+        let y = sin * radius;
+
+        outer.push([x, y]);
+        inner.push([x / 2., y / 2.]);
+    }
+
+    let outer = fj::Sketch::from_points(outer);
+    let inner = fj::Sketch::from_points(inner);
+
+    let footprint = fj::Difference2d {
+        a: outer.into(),
+        b: inner.into(),
+    };
+
+    let star = fj::Sweep {
+        shape: footprint.into(),
+        length: h,
+    };
+
+    star.into()
+}
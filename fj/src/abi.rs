@@ -0,0 +1,112 @@
+//! The interface between models and the host application
+//!
+//! Models don't use this module directly. The code that uses it is generated
+//! by the [`model`](crate::model) macro.
+
+use std::{collections::HashMap, str::FromStr};
+
+/// The version of the interface between models and the host application
+///
+/// This is incremented whenever the interface changes in an incompatible way.
+/// The host application refuses to load models that were built against a
+/// different version.
+pub const VERSION: u32 = 9;
+
+/// The version of the `fj` crate
+pub const FJ_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Metadata about a model
+///
+/// The host application reads this before evaluating the model.
+#[derive(Debug)]
+#[repr(C)]
+pub struct Metadata {
+    /// The name of the model function
+    pub name: &'static str,
+
+    /// The version of the `fj` crate that the model was built against
+    pub fj_version: &'static str,
+
+    /// The parameters of the model
+    pub parameters: &'static [Parameter],
+}
+
+/// A parameter of a model
+#[derive(Debug)]
+#[repr(C)]
+pub struct Parameter {
+    /// The name of the parameter
+    pub name: &'static str,
+
+    /// The type of the parameter, as written in the model's source code
+    pub ty: &'static str,
+
+    /// The default value of the parameter, as written in the model's source
+    /// code
+    ///
+    /// The parameter is required, if this is `None`.
+    pub default: Option<&'static str>,
+
+    /// The default value of the parameter, evaluated as a number
+    ///
+    /// Only parameters with a limited range have this, so the host application
+    /// can check their default against the range. `default` can't be used for
+    /// that, as it might not be a plain number (like `2. * PI`, or `-1.0`,
+    /// which is written as `- 1.0`).
+    pub default_value: Option<f64>,
+
+    /// The minimum value of the parameter, if it is limited
+    pub min: Option<f64>,
+
+    /// The maximum value of the parameter, if it is limited
+    pub max: Option<f64>,
+
+    /// The increment by which the parameter is meant to be changed
+    ///
+    /// This is a hint for user interfaces, for example for the step size of a
+    /// slider. Values that aren't a multiple of it are still valid.
+    pub step: Option<f64>,
+}
+
+/// Read the value of a parameter from the model arguments
+///
+/// Returns `default`, if no value was provided for the parameter.
+///
+/// # Panics
+///
+/// Panics, if the value can't be parsed, or if no value was provided for a
+/// parameter without a default. The host application checks the latter before
+/// evaluating the model.
+pub fn parameter<T>(
+    args: &HashMap<String, String>,
+    name: &str,
+    default: Option<T>,
+) -> T
+where
+    T: FromStr,
+{
+    match (args.get(name), default) {
+        (Some(value), _) => value.parse().unwrap_or_else(|_| {
+            panic!("Invalid value `{}` for parameter `{}`", value, name)
+        }),
+        (None, Some(default)) => default,
+        (None, None) => panic!("Missing value for parameter `{}`", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::parameter;
+
+    #[test]
+    fn parameter_should_fall_back_to_default() {
+        let mut args = HashMap::new();
+        args.insert("x".to_owned(), "2.5".to_owned());
+
+        assert_eq!(parameter(&args, "x", Some(1.)), 2.5);
+        assert_eq!(parameter(&args, "y", Some(1.)), 1.);
+        assert_eq!(parameter::<u64>(&args, "z", Some(5)), 5);
+    }
+}
@@ -0,0 +1,369 @@
+//! Cable management features
+//!
+//! Small features that keep cables in place, on the parts of a device:
+//!
+//! - [`ZiptieAnchor`]: A bridge that a ziptie is threaded through, to tie
+//!   cables down onto a face.
+//! - [`CableChannel`]: A channel that runs along a path, with lips at its top
+//!   that leave an opening narrower than the cable, so the cable clips into
+//!   it.
+//!
+//! Like the features in [`snap_fit`](crate::snap_fit), these are built in a
+//! local coordinate system, standing on the x-y plane, and placed on a face
+//! of a part using a [`Placement`].
+//!
+//! The channel is built from straight sweeps of the regions between lines that
+//! are offset from its path, which are combined using [`Union`]. See its
+//! documentation for the consequences.
+
+use std::{error::Error, fmt};
+
+use crate::{
+    placement::sweep_side_profile, Placement, Shape3d, Sketch, Sweep,
+    Transform, Union,
+};
+
+/// An anchor for a ziptie
+///
+/// In its local coordinate system, the anchor is a bridge that stands on the
+/// x-y plane, centered on the origin. The tunnel below the bridge runs along
+/// the y-axis, so the ziptie goes around the cables along the x-axis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZiptieAnchor {
+    /// The width of the ziptie's strap
+    pub strap_width: f64,
+
+    /// The thickness of the ziptie's strap
+    pub strap_thickness: f64,
+
+    /// The thickness of the bridge, and of the posts that carry it
+    pub wall: f64,
+
+    /// The length of the tunnel
+    pub length: f64,
+
+    /// The gap between the strap and the tunnel, on all sides
+    pub clearance: f64,
+}
+
+impl ZiptieAnchor {
+    /// Create an anchor for a ziptie
+    ///
+    /// The wall thickness is initialized to `1.5`, the length of the tunnel to
+    /// the width of the strap, and the clearance to `0.3`.
+    pub fn new(strap_width: f64, strap_thickness: f64) -> Self {
+        Self {
+            strap_width,
+            strap_thickness,
+            wall: 1.5,
+            length: strap_width,
+            clearance: 0.3,
+        }
+    }
+
+    /// The width and height of the tunnel that the strap goes through
+    pub fn tunnel(&self) -> [f64; 2] {
+        [
+            self.strap_width + self.clearance * 2.,
+            self.strap_thickness + self.clearance * 2.,
+        ]
+    }
+
+    /// Create the shape of the anchor, in its local coordinate system
+    pub fn shape(&self) -> Shape3d {
+        let [width, height] = self.tunnel();
+        let inner = width / 2.;
+        let outer = inner + self.wall;
+        let top = height + self.wall;
+
+        // The side profile of the bridge. Its x-axis is the local x-axis, its
+        // y-axis is the local z-axis.
+        let profile = Sketch::from_points(vec![
+            [-outer, 0.],
+            [-inner, 0.],
+            [-inner, height],
+            [inner, height],
+            [inner, 0.],
+            [outer, 0.],
+            [outer, top],
+            [-outer, top],
+        ]);
+
+        sweep_side_profile(profile, self.length).into()
+    }
+
+    /// Create the anchor, and move it to the placement
+    pub fn place(&self, placement: &Placement) -> Transform {
+        placement.place(self.shape())
+    }
+}
+
+/// A channel that cables clip into
+///
+/// In its local coordinate system, the channel stands on the x-y plane, and
+/// follows a path in that plane. It has a floor, a wall on either side, and a
+/// lip at the top of each wall. The lips leave an opening that is narrower
+/// than the cable, so the cable needs to be pushed through it, and is held in
+/// place once it's in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CableChannel {
+    /// The path that the center of the channel follows
+    ///
+    /// The path is open, meaning it doesn't return to its first point. Its
+    /// corners are mitered, so it must not turn by more than 90° at any point,
+    /// and consecutive points must not be the same.
+    pub path: Vec<[f64; 2]>,
+
+    /// The diameter of the cable, or of the bundle of cables
+    pub cable_diameter: f64,
+
+    /// The width of the opening between the lips
+    ///
+    /// Must be smaller than the inner width of the channel (see
+    /// [`CableChannel::inner_width`]). The smaller it is, the harder it is to
+    /// push the cable in, and to pull it out again.
+    pub opening: f64,
+
+    /// The thickness of the floor, the walls, and the lips
+    pub wall: f64,
+
+    /// The gap between the cable and the channel
+    pub clearance: f64,
+}
+
+impl CableChannel {
+    /// Create a channel along a path
+    ///
+    /// The opening is initialized to 80% of the cable diameter, the wall
+    /// thickness to `1.5`, and the clearance to `0.3`.
+    pub fn new(path: Vec<[f64; 2]>, cable_diameter: f64) -> Self {
+        Self {
+            path,
+            cable_diameter,
+            opening: cable_diameter * 0.8,
+            wall: 1.5,
+            clearance: 0.3,
+        }
+    }
+
+    /// The width of the space between the walls
+    pub fn inner_width(&self) -> f64 {
+        self.cable_diameter + self.clearance * 2.
+    }
+
+    /// The height of the channel, from its floor to the top of its lips
+    pub fn height(&self) -> f64 {
+        self.cable_diameter + self.clearance + self.wall * 2.
+    }
+
+    /// Create the shape of the channel, in its local coordinate system
+    ///
+    /// Returns an error, if the channel can't follow the path.
+    pub fn shape(&self) -> Result<Shape3d, PathError> {
+        if self.path.len() < 2 {
+            return Err(PathError::TooFewPoints(self.path.len()));
+        }
+
+        let inner = self.inner_width() / 2.;
+        let outer = inner + self.wall;
+        let opening = self.opening / 2.;
+        let [floor, lips] = [self.wall, self.height() - self.wall];
+
+        // Each part of the cross-section, as the distances of its sides from
+        // the path, and the heights of its bottom and top.
+        let parts = [
+            ([-outer, outer], [0., floor]),
+            ([-outer, -inner], [floor, lips]),
+            ([inner, outer], [floor, lips]),
+            ([-outer, -opening], [lips, self.height()]),
+            ([opening, outer], [lips, self.height()]),
+        ];
+
+        let parts = parts
+            .into_iter()
+            .map(|([right, left], [bottom, top])| {
+                let mut outline = offset(&self.path, right)?;
+                outline.extend(offset(&self.path, left)?.into_iter().rev());
+
+                Ok(Transform {
+                    shape: Sweep {
+                        shape: Sketch::from_points(outline).into(),
+                        length: top - bottom,
+                    }
+                    .into(),
+                    axis: [0., 0., 1.],
+                    angle: 0.,
+                    offset: [0., 0., bottom],
+                }
+                .into())
+            })
+            .collect::<Result<Vec<Shape3d>, _>>()?;
+
+        Ok(parts
+            .into_iter()
+            .reduce(|a, b| Union { a, b }.into())
+            .expect("Cross-section has parts"))
+    }
+
+    /// Create the channel, and move it to the placement
+    ///
+    /// Returns an error, if the channel can't follow the path.
+    pub fn place(&self, placement: &Placement) -> Result<Transform, PathError> {
+        Ok(placement.place(self.shape()?))
+    }
+}
+
+/// A path that a [`CableChannel`] can't follow
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathError {
+    /// The path has fewer than 2 points
+    TooFewPoints(usize),
+
+    /// Two consecutive points of the path are the same
+    ///
+    /// Contains the index of the first of them.
+    ZeroLengthSegment(usize),
+
+    /// The path turns by more than 90° at a point
+    ///
+    /// Contains the index of the point.
+    SharpTurn(usize),
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::TooFewPoints(num_points) => {
+                write!(
+                    f,
+                    "Channel path needs at least 2 points, but has {}",
+                    num_points
+                )
+            }
+            Self::ZeroLengthSegment(index) => {
+                write!(f, "Channel path repeats its point at index {}", index)
+            }
+            Self::SharpTurn(index) => {
+                write!(
+                    f,
+                    "Channel path turns by more than 90° at index {}",
+                    index
+                )
+            }
+        }
+    }
+}
+
+impl Error for PathError {}
+
+/// Offset an open path to its left side, mitering its corners
+///
+/// Negative distances offset the path to its right side. The path must have at
+/// least 2 points.
+///
+/// Returns an error, if a segment has a length of zero, or if the path turns
+/// by more than 90°.
+fn offset(
+    path: &[[f64; 2]],
+    distance: f64,
+) -> Result<Vec<[f64; 2]>, PathError> {
+    // The left-hand normal of each segment of the path
+    let mut normals = Vec::new();
+    for (i, segment) in path.windows(2).enumerate() {
+        let [[ax, ay], [bx, by]] = [segment[0], segment[1]];
+        let length = (bx - ax).hypot(by - ay);
+        if length == 0. {
+            return Err(PathError::ZeroLengthSegment(i));
+        }
+        normals.push([(ay - by) / length, (bx - ax) / length]);
+    }
+
+    path.iter()
+        .enumerate()
+        .map(|(i, &[x, y])| {
+            let before = normals[i.saturating_sub(1)];
+            let after = normals[i.min(normals.len() - 1)];
+
+            // The normals are the same, where the path doesn't turn, and
+            // opposite, where it turns back onto itself, which would make the
+            // miter infinitely long. Allow for rounding errors at right
+            // angles.
+            let cos = before[0] * after[0] + before[1] * after[1];
+            if cos < -1e-9 {
+                return Err(PathError::SharpTurn(i));
+            }
+
+            // The miter is as long as it needs to be, so its end is at the
+            // given distance from both segments.
+            let [nx, ny] = [before[0] + after[0], before[1] + after[1]];
+            let scale = distance / (1. + cos);
+
+            Ok([x + nx * scale, y + ny * scale])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Placement, Shape, Shape3d};
+
+    use super::{offset, CableChannel, PathError, ZiptieAnchor};
+
+    #[test]
+    fn offset_should_miter_corners() {
+        let path = [[0., 0.], [10., 0.], [10., 10.]];
+
+        assert_eq!(offset(&path, 1.).unwrap(), [[0., 1.], [9., 1.], [9., 10.]]);
+        assert_eq!(
+            offset(&path, -1.).unwrap(),
+            [[0., -1.], [11., -1.], [11., 10.]]
+        );
+    }
+
+    #[test]
+    fn offset_should_reject_degenerate_paths() {
+        let repeated = [[0., 0.], [10., 0.], [10., 0.], [20., 0.]];
+        assert_eq!(offset(&repeated, 1.), Err(PathError::ZeroLengthSegment(1)));
+
+        let reversed = [[0., 0.], [10., 0.], [5., 0.]];
+        assert_eq!(offset(&reversed, 1.), Err(PathError::SharpTurn(1)));
+
+        let channel = CableChannel::new(vec![[0., 0.]], 5.);
+        assert_eq!(channel.shape().err(), Some(PathError::TooFewPoints(1)));
+    }
+
+    #[test]
+    fn ziptie_anchor_should_leave_tunnel_for_strap() {
+        let anchor = ZiptieAnchor::new(4., 1.);
+
+        let [width, height] = anchor.tunnel();
+        assert!((width - 4.6).abs() < 1e-12);
+        assert!((height - 1.6).abs() < 1e-12);
+
+        let shape = anchor.place(&Placement::new(
+            [0., 0., 5.],
+            [1., 0., 0.],
+            [0., 0., 1.],
+        ));
+        assert!(Shape::from(shape).check().is_ok());
+    }
+
+    #[test]
+    fn cable_channel_should_consist_of_floor_walls_and_lips() {
+        let channel =
+            CableChannel::new(vec![[0., 0.], [20., 0.], [30., 10.]], 5.);
+        assert!((channel.inner_width() - 5.6).abs() < 1e-12);
+        assert!((channel.height() - 8.3).abs() < 1e-12);
+
+        let mut parts = Vec::new();
+        let mut shape = channel.shape().unwrap();
+        while let Shape3d::Union(union) = shape {
+            parts.push(union.b);
+            shape = union.a;
+        }
+        parts.push(shape);
+        assert_eq!(parts.len(), 5);
+
+        assert!(Shape::from(channel.shape().unwrap()).check().is_ok());
+    }
+}
@@ -0,0 +1,220 @@
+//! Test prints for calibrating clearances
+//!
+//! How much clearance two printed parts need, to fit into each other, depends
+//! on the printer, the material, and the slicer settings. Features like the
+//! ones in [`snap_fit`](crate::snap_fit) or [`cable`](crate::cable) have a
+//! `clearance` parameter for that reason, which is best determined by printing
+//! a test coupon.
+//!
+//! A [`ClearanceCoupon`] consists of a plate with a row of holes, and a strip
+//! with a row of pegs. All pegs have the same diameter, while the holes get
+//! larger along the x-axis, by the clearances that are to be tested. Try each
+//! peg in the hole at the same position. The smallest clearance, where the peg
+//! goes in, is the one for a tight fit, the one where it drops in freely is
+//! the one for a loose fit.
+
+use crate::{
+    placement::translate_z, Circle, Difference2d, Shape3d, Sketch, Sweep,
+    Transform, Union,
+};
+
+/// A test coupon for calibrating clearances
+///
+/// In its local coordinate system, both parts of the coupon stand on the x-y
+/// plane, and start at the origin. The holes and pegs are lined up along the
+/// x-axis, in the order of [`ClearanceCoupon::clearances`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClearanceCoupon {
+    /// The diameter of the pegs
+    pub diameter: f64,
+
+    /// The clearances that are tested, one for each hole
+    ///
+    /// The clearance is the difference between the radius of the hole and the
+    /// radius of the peg, the same as for the other features in this library.
+    pub clearances: Vec<f64>,
+
+    /// The depth of the holes, and the height of the pegs
+    pub depth: f64,
+
+    /// The minimum thickness of the plate between the holes, and the
+    /// thickness of the base of both parts
+    pub wall: f64,
+}
+
+impl ClearanceCoupon {
+    /// Create a coupon for a series of clearances
+    ///
+    /// The clearances start at `first`, and increase by `step` from one hole
+    /// to the next. The depth is initialized to the diameter of the pegs, and
+    /// the wall thickness to `2`.
+    pub fn new(diameter: f64, first: f64, step: f64, count: usize) -> Self {
+        Self {
+            diameter,
+            clearances: (0..count).map(|i| first + step * i as f64).collect(),
+            depth: diameter,
+            wall: 2.,
+        }
+    }
+
+    /// The distance between the centers of neighboring holes, or pegs
+    pub fn pitch(&self) -> f64 {
+        let max_clearance = self.clearances.iter().cloned().fold(0., f64::max);
+        self.diameter + max_clearance * 2. + self.wall
+    }
+
+    /// The size of each part along the x- and y-axes
+    pub fn size(&self) -> [f64; 2] {
+        [self.pitch() * self.clearances.len() as f64, self.pitch()]
+    }
+
+    /// Create the plate with the holes, in its local coordinate system
+    ///
+    /// # Panics
+    ///
+    /// Panics, if there are no clearances.
+    pub fn holes(&self) -> Shape3d {
+        let pitch = self.pitch();
+
+        // A plate with multiple holes can't be created by a single difference
+        // yet, so each hole gets its own tile.
+        let tiles = self.clearances.iter().enumerate().map(|(i, clearance)| {
+            let x = pitch * i as f64;
+            let tile = Difference2d {
+                a: rectangle([x, 0.], [x + pitch, pitch]).into(),
+                b: Circle {
+                    center: self.center(i),
+                    radius: self.diameter / 2. + clearance,
+                }
+                .into(),
+            };
+
+            translate_z(
+                Sweep {
+                    shape: tile.into(),
+                    length: self.depth,
+                },
+                self.wall,
+            )
+        });
+
+        self.on_base(tiles)
+    }
+
+    /// Create the strip with the pegs, in its local coordinate system
+    ///
+    /// # Panics
+    ///
+    /// Panics, if there are no clearances.
+    pub fn pegs(&self) -> Shape3d {
+        let pegs = (0..self.clearances.len()).map(|i| {
+            translate_z(
+                Sweep {
+                    shape: Circle {
+                        center: self.center(i),
+                        radius: self.diameter / 2.,
+                    }
+                    .into(),
+                    length: self.depth,
+                },
+                self.wall,
+            )
+        });
+
+        self.on_base(pegs)
+    }
+
+    /// Create both parts, next to each other, ready to be printed
+    ///
+    /// The strip with the pegs is placed behind the plate with the holes,
+    /// along the y-axis, with a gap as wide as the wall between them.
+    pub fn shape(&self) -> Shape3d {
+        let pegs = Transform {
+            shape: self.pegs(),
+            axis: [0., 0., 1.],
+            angle: 0.,
+            offset: [0., self.pitch() + self.wall, 0.],
+        };
+
+        Union {
+            a: self.holes(),
+            b: pegs.into(),
+        }
+        .into()
+    }
+
+    /// The center of the hole, or peg, at the given index
+    fn center(&self, index: usize) -> [f64; 2] {
+        let pitch = self.pitch();
+        [pitch * (index as f64 + 0.5), pitch / 2.]
+    }
+
+    /// Put the features on a base, that spans the whole part
+    fn on_base(&self, features: impl Iterator<Item = Shape3d>) -> Shape3d {
+        assert!(
+            !self.clearances.is_empty(),
+            "Coupon needs at least one clearance"
+        );
+
+        let base = Sweep {
+            shape: rectangle([0., 0.], self.size()).into(),
+            length: self.wall,
+        };
+
+        features.fold(base.into(), |a, b| Union { a, b }.into())
+    }
+}
+
+fn rectangle([min_x, min_y]: [f64; 2], [max_x, max_y]: [f64; 2]) -> Sketch {
+    Sketch::from_points(vec![
+        [min_x, min_y],
+        [max_x, min_y],
+        [max_x, max_y],
+        [min_x, max_y],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Shape, Shape2d, Shape3d};
+
+    use super::ClearanceCoupon;
+
+    #[test]
+    fn coupon_should_have_growing_holes() {
+        let coupon = ClearanceCoupon::new(5., 0.1, 0.05, 5);
+
+        let expected = [0.1, 0.15, 0.2, 0.25, 0.3];
+        assert_eq!(coupon.clearances.len(), expected.len());
+        for (clearance, expected) in coupon.clearances.iter().zip(expected) {
+            assert!((clearance - expected).abs() < 1e-12);
+        }
+        assert!((coupon.pitch() - 7.6).abs() < 1e-12);
+
+        // Collect the radii of the holes, which are the second operand of the
+        // difference in each tile. Unions nest to the left, so the last hole
+        // is found first.
+        let mut radii = Vec::new();
+        let mut shape = coupon.holes();
+        while let Shape3d::Union(union) = shape {
+            if let Shape3d::Transform(transform) = union.b {
+                if let Shape3d::Sweep(sweep) = transform.shape {
+                    if let Shape2d::Difference(difference) = sweep.shape {
+                        if let Shape2d::Circle(circle) = difference.b {
+                            radii.push(circle.radius);
+                        }
+                    }
+                }
+            }
+            shape = union.a;
+        }
+        radii.reverse();
+
+        assert_eq!(radii.len(), 5);
+        for (radius, clearance) in radii.iter().zip(&coupon.clearances) {
+            assert!((radius - 2.5 - clearance).abs() < 1e-12);
+        }
+
+        assert!(Shape::from(coupon.shape()).check().is_ok());
+    }
+}
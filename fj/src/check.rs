@@ -0,0 +1,698 @@
+use std::{error::Error, fmt};
+
+use crate::{
+    Anchor, Circle, Deform, Deformation, Difference2d, Ellipse, Shape, Shape2d,
+    Shape3d, Sketch, Sweep, Texture, TextureFaces, Transform, Union,
+};
+
+impl Shape {
+    /// Check the shape for degenerate input
+    ///
+    /// Degenerate input, like a sweep with a length of zero, or a sketch that
+    /// contains the same point twice, can't be turned into valid geometry.
+    /// Every shape that a model returns is checked (see [`Shape::checked`]),
+    /// but models can run the check themselves too, for example in their
+    /// tests.
+    ///
+    /// Returns the first problem that is found.
+    pub fn check(&self) -> Result<(), ShapeError> {
+        match self {
+            Self::Shape2d(shape) => shape.check(),
+            Self::Shape3d(shape) => shape.check(),
+        }
+    }
+
+    /// Check the shape for degenerate input, and return it, if there is none
+    ///
+    /// The code that the [`model`](crate::model) macro generates passes the
+    /// shape of the model through this, so the host application receives the
+    /// [`ShapeError`] instead of the degenerate shape.
+    pub fn checked(self) -> Result<Self, ShapeError> {
+        self.check()?;
+        Ok(self)
+    }
+}
+
+impl Shape2d {
+    /// Check the shape for degenerate input
+    ///
+    /// See [`Shape::check`].
+    pub fn check(&self) -> Result<(), ShapeError> {
+        match self {
+            Self::Circle(shape) => shape.check(),
+            Self::Difference(shape) => shape.check(),
+            Self::Ellipse(shape) => shape.check(),
+            Self::Sketch(shape) => shape.check(),
+        }
+    }
+}
+
+impl Shape3d {
+    /// Check the shape for degenerate input
+    ///
+    /// See [`Shape::check`].
+    pub fn check(&self) -> Result<(), ShapeError> {
+        match self {
+            Self::Sweep(shape) => shape.check(),
+            Self::Transform(shape) => shape.check(),
+            Self::Union(shape) => shape.check(),
+            Self::Deform(shape) => shape.check(),
+            Self::Anchor(shape) => shape.check(),
+            Self::Material(shape) => shape.shape.check(),
+            Self::Texture(shape) => shape.check(),
+        }
+    }
+}
+
+impl Circle {
+    /// Check the circle for degenerate input
+    ///
+    /// See [`Shape::check`].
+    pub fn check(&self) -> Result<(), ShapeError> {
+        finite("circle center", self.center)?;
+        finite("circle radius", [self.radius])?;
+
+        if self.radius <= 0. {
+            return Err(ShapeError::InvalidRadius(self.radius));
+        }
+
+        Ok(())
+    }
+}
+
+impl Ellipse {
+    /// Check the ellipse for degenerate input
+    ///
+    /// See [`Shape::check`].
+    pub fn check(&self) -> Result<(), ShapeError> {
+        finite("ellipse center", self.center)?;
+        finite("ellipse radii", self.radii)?;
+
+        for radius in self.radii {
+            if radius <= 0. {
+                return Err(ShapeError::InvalidEllipseRadius(radius));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Difference2d {
+    /// Check the difference for degenerate input
+    ///
+    /// See [`Shape::check`].
+    pub fn check(&self) -> Result<(), ShapeError> {
+        self.a.check()?;
+        self.b.check()
+    }
+}
+
+impl Sketch {
+    /// Check the sketch for degenerate input
+    ///
+    /// See [`Shape::check`].
+    pub fn check(&self) -> Result<(), ShapeError> {
+        let points = self.to_points();
+
+        for point in &points {
+            finite("sketch point", *point)?;
+        }
+
+        if self.is_open() {
+            if let (Some(&start), Some(&end)) = (points.first(), points.last())
+            {
+                return Err(ShapeError::OpenSketch { start, end });
+            }
+        }
+
+        // Consecutive duplicates are merged into one point, when the sketch is
+        // converted into a shape, so they only count once.
+        let n = points.len();
+        let is_run = |i: usize, j: usize| {
+            let inner = points[i..=j].iter().all(|point| *point == points[i]);
+            let around = points[j..]
+                .iter()
+                .chain(&points[..=i])
+                .all(|point| *point == points[i]);
+            inner || around
+        };
+
+        let distinct = (0..n).filter(|&i| points[i] != points[(i + 1) % n]);
+        let distinct = distinct.count().max(n.min(1));
+        if distinct < 3 {
+            return Err(ShapeError::TooFewPoints(distinct));
+        }
+
+        for (i, a) in points.iter().enumerate() {
+            for (j, b) in points.iter().enumerate().skip(i + 1) {
+                if a == b && !is_run(i, j) {
+                    return Err(ShapeError::RepeatedPoint {
+                        point: *a,
+                        indices: [i, j],
+                    });
+                }
+            }
+        }
+
+        // If all points are on a line, the sketch has no area.
+        let [ax, ay] = points[0];
+        let has_area = points.windows(2).any(|window| {
+            let [[bx, by], [cx, cy]] = [window[0], window[1]];
+            (bx - ax) * (cy - ay) - (cx - ax) * (by - ay) != 0.
+        });
+        if !has_area {
+            return Err(ShapeError::ZeroArea);
+        }
+
+        Ok(())
+    }
+}
+
+impl Sweep {
+    /// Check the sweep for degenerate input
+    ///
+    /// See [`Shape::check`].
+    pub fn check(&self) -> Result<(), ShapeError> {
+        finite("sweep length", [self.length])?;
+
+        if self.length == 0. {
+            return Err(ShapeError::ZeroLengthSweep);
+        }
+
+        self.shape.check()
+    }
+}
+
+impl Transform {
+    /// Check the transform for degenerate input
+    ///
+    /// See [`Shape::check`].
+    pub fn check(&self) -> Result<(), ShapeError> {
+        finite("rotation axis", self.axis)?;
+        finite("rotation angle", [self.angle])?;
+        finite("translation offset", self.offset)?;
+
+        if self.axis == [0.; 3] {
+            return Err(ShapeError::ZeroRotationAxis);
+        }
+
+        self.shape.check()
+    }
+}
+
+impl Union {
+    /// Check the union for degenerate input
+    ///
+    /// See [`Shape::check`].
+    pub fn check(&self) -> Result<(), ShapeError> {
+        self.a.check()?;
+        self.b.check()
+    }
+}
+
+impl Deform {
+    /// Check the deformation for degenerate input
+    ///
+    /// See [`Shape::check`].
+    pub fn check(&self) -> Result<(), ShapeError> {
+        match self.deformation {
+            Deformation::Bend { radius } => {
+                finite("bend radius", [radius])?;
+
+                if radius == 0. {
+                    return Err(ShapeError::ZeroBendRadius);
+                }
+            }
+            Deformation::Twist { rate } => {
+                finite("twist rate", [rate])?;
+            }
+            Deformation::Wrap { radius } => {
+                finite("wrap radius", [radius])?;
+
+                if radius <= 0. {
+                    return Err(ShapeError::InvalidWrapRadius(radius));
+                }
+            }
+        }
+
+        self.shape.check()
+    }
+}
+
+impl Anchor {
+    /// Check the anchor for degenerate input
+    ///
+    /// See [`Shape::check`].
+    pub fn check(&self) -> Result<(), ShapeError> {
+        finite("anchor origin", self.origin)?;
+        finite("anchor x-axis", self.x_axis)?;
+        finite("anchor z-axis", self.z_axis)?;
+
+        let [x, z] = [self.x_axis, self.z_axis];
+        let cross = [
+            x[1] * z[2] - x[2] * z[1],
+            x[2] * z[0] - x[0] * z[2],
+            x[0] * z[1] - x[1] * z[0],
+        ];
+        if cross == [0.; 3] {
+            return Err(ShapeError::DegenerateAnchor(self.name.clone()));
+        }
+
+        self.shape.check()
+    }
+}
+
+impl Texture {
+    /// Check the texture for degenerate input
+    ///
+    /// See [`Shape::check`].
+    pub fn check(&self) -> Result<(), ShapeError> {
+        finite("texture pitch", [self.pitch])?;
+        finite("texture depth", [self.depth])?;
+
+        if self.pitch <= 0. {
+            return Err(ShapeError::InvalidTexturePitch(self.pitch));
+        }
+
+        if let TextureFaces::Facing(direction) = self.faces {
+            finite("texture direction", direction)?;
+
+            if direction == [0.; 3] {
+                return Err(ShapeError::ZeroTextureDirection);
+            }
+        }
+
+        self.shape.check()
+    }
+}
+
+/// Degenerate input, that can't be turned into valid geometry
+///
+/// See [`Shape::check`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShapeError {
+    /// A value is NaN or infinite
+    ///
+    /// Contains a description of the value.
+    NotFinite(&'static str),
+
+    /// A circle has a radius that is zero or negative
+    InvalidRadius(f64),
+
+    /// An ellipse has a radius that is zero or negative
+    InvalidEllipseRadius(f64),
+
+    /// A sketch has fewer than 3 points
+    TooFewPoints(usize),
+
+    /// A sketch contains the same point more than once
+    ///
+    /// Consecutive duplicates, including the last point being the same as the
+    /// first, are allowed. They are merged into a single point.
+    RepeatedPoint {
+        /// The point that is repeated
+        point: [f64; 2],
+
+        /// The indices of two occurrences of the point
+        indices: [usize; 2],
+    },
+
+    /// All points of a sketch are on a line, so it doesn't have any area
+    ZeroArea,
+
+    /// The path of a sketch doesn't return to where it started
+    ///
+    /// See [`Sketch::from_path`].
+    OpenSketch {
+        /// The first point of the path
+        start: [f64; 2],
+
+        /// The last point of the path
+        end: [f64; 2],
+    },
+
+    /// A sweep has a length of zero
+    ZeroLengthSweep,
+
+    /// A transform has a rotation axis with a length of zero
+    ZeroRotationAxis,
+
+    /// A bend has a radius of zero
+    ZeroBendRadius,
+
+    /// A wrap has a radius that is zero or negative
+    InvalidWrapRadius(f64),
+
+    /// A texture has a pitch that is zero or negative
+    InvalidTexturePitch(f64),
+
+    /// A texture is applied to the faces facing a direction of length zero
+    ZeroTextureDirection,
+
+    /// The axes of an anchor have a length of zero, or are parallel
+    ///
+    /// Contains the name of the anchor.
+    DegenerateAnchor(String),
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotFinite(value) => {
+                write!(f, "The {} is not a finite number", value)
+            }
+            Self::InvalidRadius(radius) => {
+                write!(f, "Circle radius must be positive, but is {}", radius)
+            }
+            Self::InvalidEllipseRadius(radius) => {
+                write!(f, "Ellipse radius must be positive, but is {}", radius)
+            }
+            Self::TooFewPoints(points) => {
+                write!(f, "Sketch needs at least 3 points, but has {}", points)
+            }
+            Self::RepeatedPoint { point, indices } => {
+                write!(
+                    f,
+                    "Sketch contains point {:?} twice (at indices {} and {})",
+                    point, indices[0], indices[1]
+                )
+            }
+            Self::ZeroArea => {
+                write!(f, "All points of sketch are on a line")
+            }
+            Self::OpenSketch { start, end } => {
+                write!(
+                    f,
+                    "Sketch outline starts at {:?}, but ends at {:?}; close it \
+                    with `Sketch::with_auto_close`, if that is intended",
+                    start, end
+                )
+            }
+            Self::ZeroLengthSweep => write!(f, "Sweep has a length of zero"),
+            Self::ZeroRotationAxis => {
+                write!(f, "Rotation axis has a length of zero")
+            }
+            Self::ZeroBendRadius => write!(f, "Bend has a radius of zero"),
+            Self::InvalidWrapRadius(radius) => {
+                write!(f, "Wrap radius must be positive, but is {}", radius)
+            }
+            Self::InvalidTexturePitch(pitch) => {
+                write!(f, "Texture pitch must be positive, but is {}", pitch)
+            }
+            Self::ZeroTextureDirection => {
+                write!(f, "Texture direction has a length of zero")
+            }
+            Self::DegenerateAnchor(name) => {
+                write!(
+                    f,
+                    "Axes of anchor `{}` are zero, or parallel to each other",
+                    name
+                )
+            }
+        }
+    }
+}
+
+impl Error for ShapeError {}
+
+fn finite<const D: usize>(
+    value: &'static str,
+    components: [f64; D],
+) -> Result<(), ShapeError> {
+    if components.iter().all(|component| component.is_finite()) {
+        Ok(())
+    } else {
+        Err(ShapeError::NotFinite(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Anchor, Circle, Deform, Deformation, Difference2d, Ellipse, Shape,
+        Shape2d, Shape3d, Sketch, Sweep, Texture, TextureFaces, TexturePattern,
+        Transform, Union,
+    };
+
+    use super::ShapeError;
+
+    #[test]
+    fn valid_shapes_should_pass() {
+        let shape = union(sweep(square(), 1.), sweep(ring(), 2.));
+        assert_eq!(shape.check(), Ok(()));
+    }
+
+    #[test]
+    fn circle_with_non_positive_radius() {
+        for radius in [0., -1.] {
+            assert_eq!(
+                Circle {
+                    center: [0., 0.],
+                    radius
+                }
+                .check(),
+                Err(ShapeError::InvalidRadius(radius))
+            );
+        }
+        assert_eq!(
+            Circle {
+                center: [0., 0.],
+                radius: f64::NAN
+            }
+            .check(),
+            Err(ShapeError::NotFinite("circle radius"))
+        );
+    }
+
+    #[test]
+    fn ellipse_with_non_positive_radius() {
+        let ellipse = |radii| Ellipse {
+            center: [1., 2.],
+            radii,
+        };
+
+        assert_eq!(ellipse([2., 1.]).check(), Ok(()));
+        assert_eq!(
+            ellipse([2., 0.]).check(),
+            Err(ShapeError::InvalidEllipseRadius(0.))
+        );
+        assert_eq!(
+            ellipse([f64::INFINITY, 1.]).check(),
+            Err(ShapeError::NotFinite("ellipse radii"))
+        );
+    }
+
+    #[test]
+    fn sketch_with_too_few_points() {
+        let sketch = Sketch::from_points(vec![[0., 0.], [1., 0.]]);
+        assert_eq!(sketch.check(), Err(ShapeError::TooFewPoints(2)));
+    }
+
+    #[test]
+    fn sketch_with_repeated_point() {
+        let sketch = Sketch::from_points(vec![
+            [0., 0.],
+            [1., 0.],
+            [1., 1.],
+            [1., 0.],
+            [0., 1.],
+        ]);
+        assert_eq!(
+            sketch.check(),
+            Err(ShapeError::RepeatedPoint {
+                point: [1., 0.],
+                indices: [1, 3]
+            })
+        );
+    }
+
+    #[test]
+    fn sketch_with_consecutive_duplicates() {
+        // These are merged, when the sketch is converted into a shape.
+        let sketch = Sketch::from_points(vec![
+            [0., 0.],
+            [1., 0.],
+            [1., 0.],
+            [1., 0.],
+            [0., 1.],
+        ]);
+        assert_eq!(sketch.check(), Ok(()));
+
+        // Closing the cycle explicitly repeats the first point at the end.
+        let sketch =
+            Sketch::from_points(vec![[0., 0.], [1., 0.], [0., 1.], [0., 0.]]);
+        assert_eq!(sketch.check(), Ok(()));
+
+        // Merging the duplicates leaves too few points.
+        let sketch =
+            Sketch::from_points(vec![[0., 0.], [1., 0.], [1., 0.], [0., 0.]]);
+        assert_eq!(sketch.check(), Err(ShapeError::TooFewPoints(2)));
+    }
+
+    #[test]
+    fn open_sketch() {
+        let path = vec![[0., 0.], [1., 0.], [0., 1.], [0.01, 0.]];
+
+        let sketch = Sketch::from_path(path.clone(), 0.1);
+        assert_eq!(sketch.check(), Ok(()));
+        assert_eq!(sketch.to_points().len(), 3);
+
+        let sketch = Sketch::from_path(path, 0.001);
+        assert_eq!(
+            sketch.check(),
+            Err(ShapeError::OpenSketch {
+                start: [0., 0.],
+                end: [0.01, 0.]
+            })
+        );
+        assert_eq!(sketch.with_auto_close().check(), Ok(()));
+    }
+
+    #[test]
+    fn sketch_without_area() {
+        let sketch = Sketch::from_points(vec![[0., 0.], [1., 1.], [2., 2.]]);
+        assert_eq!(sketch.check(), Err(ShapeError::ZeroArea));
+    }
+
+    #[test]
+    fn sketch_with_non_finite_point() {
+        let sketch =
+            Sketch::from_points(vec![[0., 0.], [f64::INFINITY, 0.], [0., 1.]]);
+        assert_eq!(sketch.check(), Err(ShapeError::NotFinite("sketch point")));
+    }
+
+    #[test]
+    fn zero_length_sweep() {
+        let shape = sweep(square(), 0.);
+        assert_eq!(shape.check(), Err(ShapeError::ZeroLengthSweep));
+    }
+
+    #[test]
+    fn transform_with_zero_rotation_axis() {
+        let transform = Transform {
+            shape: sweep(square(), 1.),
+            axis: [0., 0., 0.],
+            angle: 1.,
+            offset: [0., 0., 0.],
+        };
+        assert_eq!(transform.check(), Err(ShapeError::ZeroRotationAxis));
+    }
+
+    #[test]
+    fn bend_with_zero_radius() {
+        let deform = Deform {
+            shape: sweep(square(), 1.),
+            deformation: Deformation::Bend { radius: 0. },
+        };
+        assert_eq!(deform.check(), Err(ShapeError::ZeroBendRadius));
+    }
+
+    #[test]
+    fn wrap_with_negative_radius() {
+        let deform = Deform {
+            shape: sweep(square(), 1.),
+            deformation: Deformation::Wrap { radius: -1. },
+        };
+        assert_eq!(deform.check(), Err(ShapeError::InvalidWrapRadius(-1.)));
+    }
+
+    #[test]
+    fn texture_with_zero_pitch() {
+        let texture = Texture {
+            shape: sweep(square(), 1.),
+            pattern: TexturePattern::Knurl,
+            faces: TextureFaces::Sides,
+            pitch: 0.,
+            depth: 0.1,
+        };
+        assert_eq!(texture.check(), Err(ShapeError::InvalidTexturePitch(0.)));
+    }
+
+    #[test]
+    fn texture_with_zero_direction() {
+        let texture = Texture {
+            shape: sweep(square(), 1.),
+            pattern: TexturePattern::Ribs,
+            faces: TextureFaces::Facing([0.; 3]),
+            pitch: 1.,
+            depth: 0.1,
+        };
+        assert_eq!(texture.check(), Err(ShapeError::ZeroTextureDirection));
+    }
+
+    #[test]
+    fn anchor_with_parallel_axes() {
+        let anchor = Anchor {
+            shape: sweep(square(), 1.),
+            name: "mount".to_owned(),
+            origin: [0., 0., 0.],
+            x_axis: [0., 0., 2.],
+            z_axis: [0., 0., 1.],
+        };
+        assert_eq!(
+            anchor.check(),
+            Err(ShapeError::DegenerateAnchor("mount".to_owned()))
+        );
+    }
+
+    #[test]
+    fn checked_should_return_shape_without_degenerate_input() {
+        let shape = union(sweep(square(), 1.), sweep(ring(), 2.));
+        assert!(shape.checked().is_ok());
+
+        let shape = union(sweep(square(), 1.), sweep(square(), 0.));
+        assert!(matches!(shape.checked(), Err(ShapeError::ZeroLengthSweep)));
+    }
+
+    #[test]
+    fn degenerate_input_should_be_found_in_nested_shapes() {
+        let shape = union(sweep(square(), 1.), sweep(square(), 0.));
+        assert_eq!(shape.check(), Err(ShapeError::ZeroLengthSweep));
+
+        let ring = Difference2d {
+            a: Circle {
+                center: [0., 0.],
+                radius: 1.,
+            }
+            .into(),
+            b: Circle {
+                center: [0., 0.],
+                radius: 0.,
+            }
+            .into(),
+        };
+        assert_eq!(ring.check(), Err(ShapeError::InvalidRadius(0.)));
+    }
+
+    fn square() -> Sketch {
+        Sketch::from_points(vec![[0., 0.], [1., 0.], [1., 1.], [0., 1.]])
+    }
+
+    fn ring() -> Difference2d {
+        Difference2d {
+            a: Circle {
+                center: [0., 0.],
+                radius: 2.,
+            }
+            .into(),
+            b: Circle {
+                center: [0., 0.],
+                radius: 1.,
+            }
+            .into(),
+        }
+    }
+
+    fn sweep(shape: impl Into<Shape2d>, length: f64) -> Shape3d {
+        Sweep {
+            shape: shape.into(),
+            length,
+        }
+        .into()
+    }
+
+    fn union(a: Shape3d, b: Shape3d) -> Shape {
+        Union { a, b }.into()
+    }
+}
@@ -0,0 +1,403 @@
+//! Enclosures
+//!
+//! An enclosure is a box with a lid, as it is used to house electronics. Its
+//! floor can carry standoffs, which a circuit board is screwed onto. This
+//! module generates both parts of the box from a few parameters, using only
+//! regular shapes:
+//!
+//! - The floor and the lid are sweeps of the outline of the box.
+//! - The walls are a sweep of the difference between the outline and the
+//!   cavity inside of it.
+//! - Each standoff is a sweep of a ring, whose hole takes the screw.
+//!
+//! The vertical edges of the box are rounded, by replacing the corners of the
+//! outline with arcs. Those are approximated by straight edges, as sketches
+//! can't contain arcs yet.
+//!
+//! The parts are combined using [`Union`], which doesn't merge the faces where
+//! they touch. See its documentation for the consequences.
+
+use std::f64::consts::FRAC_PI_2;
+
+use crate::{
+    placement::translate_z, Circle, Difference2d, Shape3d, Sketch, Sweep, Union,
+};
+
+/// An enclosure
+///
+/// The enclosure is an axis-aligned box, with its outside extending from the
+/// origin to [`Enclosure::size`]. The lid is the topmost part of it, and has
+/// the same thickness as the walls.
+#[derive(Clone, Debug)]
+pub struct Enclosure {
+    /// The outer dimensions of the enclosure, including the lid
+    pub size: [f64; 3],
+
+    /// The thickness of the walls, the floor, and the lid
+    pub wall: f64,
+
+    /// The radius of the vertical edges on the outside of the enclosure
+    ///
+    /// The edges on the inside are rounded too, with a radius that is smaller
+    /// by the wall thickness. If the radius is not bigger than the wall
+    /// thickness, the edges on the inside are sharp.
+    ///
+    /// Must not be negative, and must be less than half the width and depth
+    /// of the enclosure.
+    pub corner_radius: f64,
+
+    /// The number of straight edges that approximate each rounded corner
+    ///
+    /// Must be at least 1.
+    pub corner_segments: usize,
+
+    /// How the lid closes the enclosure
+    pub lid: Lid,
+
+    /// The standoffs on the floor of the enclosure
+    pub standoffs: Vec<Standoff>,
+}
+
+impl Enclosure {
+    /// Create an enclosure with a flat lid, and without any standoffs
+    ///
+    /// The corner radius is initialized to twice the wall thickness, and the
+    /// number of corner segments to `8`.
+    pub fn new(size: [f64; 3], wall: f64) -> Self {
+        Self {
+            size,
+            wall,
+            corner_radius: wall * 2.,
+            corner_segments: 8,
+            lid: Lid::Flat,
+            standoffs: Vec::new(),
+        }
+    }
+
+    /// Add a standoff to the floor of the enclosure
+    pub fn with_standoff(mut self, standoff: Standoff) -> Self {
+        self.standoffs.push(standoff);
+        self
+    }
+
+    /// The height of the base, without the lid
+    pub fn base_height(&self) -> f64 {
+        self.size[2] - self.wall
+    }
+
+    /// Create the base of the enclosure
+    ///
+    /// The base consists of the floor, the walls, and the standoffs.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the dimensions of the enclosure are invalid. See
+    /// [`Enclosure::outline`].
+    pub fn base(&self) -> Shape3d {
+        let floor = Sweep {
+            shape: self.outline().into(),
+            length: self.wall,
+        };
+        let walls = Difference2d {
+            a: self.outline().into(),
+            b: self.cavity(0.).into(),
+        };
+        let walls = translate_z(
+            Sweep {
+                shape: walls.into(),
+                length: self.base_height() - self.wall,
+            },
+            self.wall,
+        );
+
+        let mut base = Union {
+            a: floor.into(),
+            b: walls,
+        }
+        .into();
+
+        for standoff in &self.standoffs {
+            base = Union {
+                a: base,
+                b: translate_z(standoff.shape(), self.wall),
+            }
+            .into();
+        }
+
+        base
+    }
+
+    /// Create the lid of the enclosure
+    ///
+    /// The lid is positioned on top of the base, as it is when the enclosure
+    /// is closed.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the dimensions of the enclosure are invalid (see
+    /// [`Enclosure::outline`]), if the depth of an inset lid isn't positive or
+    /// reaches the floor, or if its clearance is negative or leaves no room
+    /// for the lip.
+    pub fn lid(&self) -> Shape3d {
+        let plate = translate_z(
+            Sweep {
+                shape: self.outline().into(),
+                length: self.wall,
+            },
+            self.base_height(),
+        );
+
+        match self.lid {
+            Lid::Flat => plate,
+            Lid::Inset { depth, clearance } => {
+                assert!(
+                    depth > 0. && depth < self.base_height() - self.wall,
+                    "Lid depth must be positive, and less than the height of \
+                    the cavity"
+                );
+                assert!(clearance >= 0., "Lid clearance must not be negative");
+
+                let [x, y, _] = self.size;
+                let inset = (self.wall * 2. + clearance) * 2.;
+                assert!(
+                    x > inset && y > inset,
+                    "Lid clearance leaves no room for the lip"
+                );
+
+                let lip = Difference2d {
+                    a: self.cavity(clearance).into(),
+                    b: self.cavity(clearance + self.wall).into(),
+                };
+                let lip = translate_z(
+                    Sweep {
+                        shape: lip.into(),
+                        length: depth,
+                    },
+                    self.base_height() - depth,
+                );
+
+                Union { a: plate, b: lip }.into()
+            }
+        }
+    }
+
+    /// The outline of the enclosure, as seen from above
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the wall thickness isn't positive, if the enclosure isn't
+    /// larger than two walls in every direction, if the corner radius is
+    /// negative or not less than half the width and depth, or if there are
+    /// no corner segments.
+    pub fn outline(&self) -> Sketch {
+        let [x, y, z] = self.size;
+
+        assert!(self.wall > 0., "Enclosure wall must be thicker than zero");
+        assert!(
+            x > self.wall * 2. && y > self.wall * 2. && z > self.wall * 2.,
+            "Enclosure is too small for its walls"
+        );
+        assert!(
+            self.corner_radius >= 0.
+                && self.corner_radius < x / 2.
+                && self.corner_radius < y / 2.,
+            "Enclosure corner radius must not be negative, and less than half \
+            the width and depth"
+        );
+        assert!(
+            self.corner_segments > 0,
+            "Enclosure corners need at least one segment"
+        );
+
+        rounded_rectangle(
+            [0., 0.],
+            [x, y],
+            self.corner_radius,
+            self.corner_segments,
+        )
+    }
+
+    /// The outline of the cavity inside of the walls, shrunk by `inset`
+    fn cavity(&self, inset: f64) -> Sketch {
+        let [x, y, _] = self.size;
+        let offset = self.wall + inset;
+
+        rounded_rectangle(
+            [offset, offset],
+            [x - offset, y - offset],
+            self.corner_radius - offset,
+            self.corner_segments,
+        )
+    }
+}
+
+/// How the lid closes an [`Enclosure`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Lid {
+    /// A flat plate that sits on top of the walls
+    Flat,
+
+    /// A flat plate with a lip below it, that fits inside the walls
+    ///
+    /// The lip keeps the lid from sliding off, and holds it in place by
+    /// friction, if the clearance is small enough.
+    Inset {
+        /// How far the lip reaches into the enclosure
+        depth: f64,
+
+        /// The gap between the lip and the walls
+        clearance: f64,
+    },
+}
+
+/// A standoff on the floor of an [`Enclosure`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Standoff {
+    /// The position of the center of the standoff, in the x-y plane
+    pub position: [f64; 2],
+
+    /// The height of the standoff, above the floor
+    pub height: f64,
+
+    /// The outer radius of the standoff
+    pub radius: f64,
+
+    /// The radius of the hole for the screw
+    ///
+    /// Screws that form their own thread need a hole that is a bit smaller
+    /// than the screw.
+    pub hole_radius: f64,
+}
+
+impl Standoff {
+    /// Create a standoff for an M2.5 thread-forming screw
+    ///
+    /// The height is initialized to `5.`, the radius to `3.`, and the hole
+    /// radius to `1.1`.
+    pub fn new(position: [f64; 2]) -> Self {
+        Self {
+            position,
+            height: 5.,
+            radius: 3.,
+            hole_radius: 1.1,
+        }
+    }
+
+    fn shape(&self) -> Sweep {
+        let ring = Difference2d {
+            a: Circle {
+                center: self.position,
+                radius: self.radius,
+            }
+            .into(),
+            b: Circle {
+                center: self.position,
+                radius: self.hole_radius,
+            }
+            .into(),
+        };
+
+        Sweep {
+            shape: ring.into(),
+            length: self.height,
+        }
+    }
+}
+
+/// Create a rectangle with rounded corners
+///
+/// Each corner is approximated by `segments` straight edges. A radius that is
+/// not bigger than zero results in sharp corners.
+fn rounded_rectangle(
+    min: [f64; 2],
+    max: [f64; 2],
+    radius: f64,
+    segments: usize,
+) -> Sketch {
+    if radius <= 0. {
+        return Sketch::from_points(vec![
+            min,
+            [max[0], min[1]],
+            max,
+            [min[0], max[1]],
+        ]);
+    }
+
+    // The centers of the corner arcs, counter-clockwise, starting with the
+    // corner at `min`.
+    let centers = [
+        [min[0] + radius, min[1] + radius],
+        [max[0] - radius, min[1] + radius],
+        [max[0] - radius, max[1] - radius],
+        [min[0] + radius, max[1] - radius],
+    ];
+
+    let mut points = Vec::new();
+    for (i, [x, y]) in centers.into_iter().enumerate() {
+        let start = FRAC_PI_2 * (i as f64 + 2.);
+
+        for j in 0..=segments {
+            let angle = start + FRAC_PI_2 * j as f64 / segments as f64;
+            let (sin, cos) = angle.sin_cos();
+            points.push([x + cos * radius, y + sin * radius]);
+        }
+    }
+
+    Sketch::from_points(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Shape;
+
+    use super::{rounded_rectangle, Enclosure, Lid, Standoff};
+
+    #[test]
+    fn rounded_rectangle_should_stay_within_bounds() {
+        let points = rounded_rectangle([1., 2.], [5., 4.], 0.5, 8).to_points();
+
+        assert_eq!(points.len(), 36);
+        for [x, y] in points {
+            assert!((1. - 1e-12..=5. + 1e-12).contains(&x));
+            assert!((2. - 1e-12..=4. + 1e-12).contains(&y));
+        }
+
+        let sharp = rounded_rectangle([1., 2.], [5., 4.], 0., 8).to_points();
+        assert_eq!(sharp, [[1., 2.], [5., 2.], [5., 4.], [1., 4.]]);
+    }
+
+    #[test]
+    fn enclosure_should_pass_check() {
+        let mut enclosure = Enclosure::new([60., 40., 25.], 2.)
+            .with_standoff(Standoff::new([10., 10.]))
+            .with_standoff(Standoff::new([50., 30.]));
+        assert_eq!(enclosure.base_height(), 23.);
+
+        for lid in [
+            Lid::Flat,
+            Lid::Inset {
+                depth: 3.,
+                clearance: 0.2,
+            },
+        ] {
+            enclosure.lid = lid;
+
+            assert_eq!(Shape::from(enclosure.base()).check(), Ok(()));
+            assert_eq!(Shape::from(enclosure.lid()).check(), Ok(()));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "thicker than zero")]
+    fn enclosure_should_panic_on_negative_wall() {
+        Enclosure::new([60., 40., 25.], -2.).base();
+    }
+
+    #[test]
+    #[should_panic(expected = "corner radius")]
+    fn enclosure_should_panic_on_large_corner_radius() {
+        let mut enclosure = Enclosure::new([60., 40., 25.], 2.);
+        enclosure.corner_radius = 20.;
+        enclosure.base();
+    }
+}
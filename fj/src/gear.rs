@@ -0,0 +1,303 @@
+//! Involute spur gears
+//!
+//! A [`SpurGear`] is defined by its module, which is the size of the teeth,
+//! its number of teeth, and its pressure angle. Two gears mesh, if they have
+//! the same module and pressure angle, and are placed at the distance that
+//! [`SpurGear::center_distance`] returns.
+//!
+//! The flanks of the teeth are involutes of the base circle. Sketches consist
+//! of straight edges, so the involutes, and the root circle between the teeth,
+//! are approximated by points. The teeth have the standard proportions: the
+//! addendum is one module, and the dedendum one and a quarter. Undercut, which
+//! happens in real gears with few teeth, isn't modeled. Below the base circle,
+//! the flanks continue radially to the root circle instead.
+//!
+//! Like the other features, gears are built in a local coordinate system,
+//! standing on the x-y plane, centered on the z-axis, and placed using a
+//! [`Placement`].
+
+use std::f64::consts::{PI, TAU};
+
+use crate::{
+    Circle, Difference2d, Placement, Shape2d, Shape3d, Sketch, Sweep, Transform,
+};
+
+/// An involute spur gear
+///
+/// In its local coordinate system, the gear stands on the x-y plane, centered
+/// on the z-axis. One of its teeth is centered on the positive x-axis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpurGear {
+    /// The module, which is the pitch diameter divided by the number of teeth
+    pub module: f64,
+
+    /// The number of teeth
+    pub teeth: usize,
+
+    /// The pressure angle, in degrees
+    ///
+    /// 20 degrees is the standard for most gears.
+    pub pressure_angle: f64,
+
+    /// The thickness of the gear, along the z-axis
+    pub thickness: f64,
+
+    /// The diameter of the bore in the center of the gear
+    ///
+    /// Zero means there is no bore. Must be smaller than the root diameter.
+    pub bore: f64,
+
+    /// How much thinner each tooth is made, measured along the pitch circle
+    ///
+    /// Meshing gears need some backlash, so they don't bind. Printed gears
+    /// need more than machined ones.
+    pub backlash: f64,
+
+    /// The number of points on each flank of a tooth
+    pub segments: usize,
+}
+
+impl SpurGear {
+    /// Create a gear with the given module and number of teeth
+    ///
+    /// The pressure angle is initialized to 20 degrees, and the thickness to
+    /// eight times the module, which is typical for spur gears. The gear has
+    /// no bore and no backlash.
+    pub fn new(module: f64, teeth: usize) -> Self {
+        Self {
+            module,
+            teeth,
+            pressure_angle: 20.,
+            thickness: module * 8.,
+            bore: 0.,
+            backlash: 0.,
+            segments: 8,
+        }
+    }
+
+    /// Change the diameter of the bore
+    pub fn with_bore(mut self, bore: f64) -> Self {
+        self.bore = bore;
+        self
+    }
+
+    /// Change the backlash
+    pub fn with_backlash(mut self, backlash: f64) -> Self {
+        self.backlash = backlash;
+        self
+    }
+
+    /// The diameter of the pitch circle, on which meshing gears roll
+    pub fn pitch_diameter(&self) -> f64 {
+        self.module * self.teeth as f64
+    }
+
+    /// The diameter of the base circle, which the involutes unwind from
+    pub fn base_diameter(&self) -> f64 {
+        self.pitch_diameter() * self.pressure_angle.to_radians().cos()
+    }
+
+    /// The diameter of the tips of the teeth
+    pub fn outside_diameter(&self) -> f64 {
+        self.pitch_diameter() + 2. * self.module
+    }
+
+    /// The diameter of the root circle, between the teeth
+    pub fn root_diameter(&self) -> f64 {
+        self.pitch_diameter() - 2.5 * self.module
+    }
+
+    /// The distance between the centers of this gear and another, to mesh
+    pub fn center_distance(&self, other: &Self) -> f64 {
+        (self.pitch_diameter() + other.pitch_diameter()) / 2.
+    }
+
+    /// The outline of the gear, without the bore
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the gear has fewer than 4 teeth, as the teeth would end in a
+    /// point, or intersect each other.
+    pub fn profile(&self) -> Sketch {
+        assert!(self.teeth >= 4, "A gear needs at least 4 teeth");
+
+        let z = self.teeth as f64;
+        let base = self.base_diameter() / 2.;
+        let root = self.root_diameter() / 2.;
+        let tip = self.outside_diameter() / 2.;
+        let foot = base.max(root);
+
+        // The angle between the center of a tooth and the points where its
+        // involutes start, on the base circle. At the pitch circle, the flanks
+        // are half the thickness of the tooth from its center.
+        let pressure_angle = self.pressure_angle.to_radians();
+        let half_thickness = PI / (2. * z) - self.backlash / self.module / z;
+        let half_angle = half_thickness + involute(pressure_angle);
+
+        // The involute is parameterized by the angle that its tangent to the
+        // base circle has unrolled, which is the tangent of the pressure
+        // angle at a given radius.
+        let unrolled = |radius: f64| ((radius / base).powi(2) - 1.).sqrt();
+        let [start, end] = [unrolled(foot), unrolled(tip)];
+        let flank: Vec<_> = (0..=self.segments)
+            .map(|i| {
+                let t = start + (end - start) * i as f64 / self.segments as f64;
+                let radius = base * (1. + t * t).sqrt();
+                let angle = half_angle - (t - t.atan());
+                [radius, angle]
+            })
+            .collect();
+
+        // Points on the root circle between the teeth, not including the
+        // ones at the feet of the flanks
+        let [_, foot_angle] = flank[0];
+        let gap = [foot_angle, TAU / z - foot_angle];
+        let root_points = self.segments / 2;
+
+        let polar = |[radius, angle]: [f64; 2], offset: f64| {
+            let angle = angle + offset;
+            [radius * angle.cos(), radius * angle.sin()]
+        };
+
+        let mut points = Vec::new();
+        for i in 0..self.teeth {
+            let center = TAU * i as f64 / z;
+
+            if root < base {
+                points.push(polar([root, -foot_angle], center));
+            }
+            points.extend(
+                flank.iter().map(|&[r, angle]| polar([r, -angle], center)),
+            );
+            points.extend(flank.iter().rev().map(|&p| polar(p, center)));
+            if root < base {
+                points.push(polar([root, foot_angle], center));
+            }
+
+            points.extend((1..=root_points).map(|j| {
+                let t = j as f64 / (root_points + 1) as f64;
+                let angle = gap[0] + (gap[1] - gap[0]) * t;
+                polar([root, angle], center)
+            }));
+        }
+
+        Sketch::from_points(points)
+    }
+
+    /// Create the shape of the gear, in its local coordinate system
+    pub fn shape(&self) -> Shape3d {
+        let mut section: Shape2d = self.profile().into();
+
+        if self.bore > 0. {
+            section = Difference2d {
+                a: section,
+                b: Circle {
+                    center: [0., 0.],
+                    radius: self.bore / 2.,
+                }
+                .into(),
+            }
+            .into();
+        }
+
+        Sweep {
+            shape: section,
+            length: self.thickness,
+        }
+        .into()
+    }
+
+    /// Create the gear, and move it to the placement
+    pub fn place(&self, placement: &Placement) -> Transform {
+        placement.place(self.shape())
+    }
+}
+
+/// The involute function, which is the polar angle of an involute's point
+///
+/// The point is the one where the involute's pressure angle is `angle`.
+fn involute(angle: f64) -> f64 {
+    angle.tan() - angle
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Shape, Shape3d};
+
+    use super::SpurGear;
+
+    #[test]
+    fn gear_should_have_standard_dimensions() {
+        let gear = SpurGear::new(2., 20);
+
+        assert_eq!(gear.pitch_diameter(), 40.);
+        assert_eq!(gear.outside_diameter(), 44.);
+        assert_eq!(gear.root_diameter(), 35.);
+        assert!((gear.base_diameter() - 37.5877).abs() < 1e-4);
+        assert_eq!(gear.center_distance(&SpurGear::new(2., 10)), 30.);
+    }
+
+    #[test]
+    fn profile_should_reach_from_root_to_tip() {
+        for teeth in [8, 20, 60] {
+            let gear = SpurGear::new(1., teeth);
+
+            let radii: Vec<_> = gear
+                .profile()
+                .to_points()
+                .into_iter()
+                .map(|[x, y]| x.hypot(y))
+                .collect();
+            let max = radii.iter().copied().fold(f64::MIN, f64::max);
+            let min = radii.iter().copied().fold(f64::MAX, f64::min);
+
+            assert!((max - gear.outside_diameter() / 2.).abs() < 1e-9);
+            assert!((min - gear.root_diameter() / 2.).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn tooth_should_be_half_the_circular_pitch_thick() {
+        let gear = SpurGear::new(1., 20);
+
+        // Points on the first tooth's flanks, in the order of the outline:
+        // the right flank outwards, then the left flank inwards.
+        let points = gear.profile().to_points();
+        let flank = &points[1..=gear.segments + 1];
+
+        // Find where the right flank crosses the pitch circle, and measure the
+        // tooth thickness there, assuming the tooth is symmetric.
+        let pitch = gear.pitch_diameter() / 2.;
+        let crossing = flank
+            .windows(2)
+            .find_map(|pair| {
+                let [a, b] = [pair[0], pair[1]].map(|[x, y]| x.hypot(y));
+                if a <= pitch && pitch <= b {
+                    let t = (pitch - a) / (b - a);
+                    let [x0, y0] = pair[0];
+                    let [x1, y1] = pair[1];
+                    Some([x0 + (x1 - x0) * t, y0 + (y1 - y0) * t])
+                } else {
+                    None
+                }
+            })
+            .unwrap();
+        let thickness = 2. * pitch * (-crossing[1]).atan2(crossing[0]);
+
+        // The flanks are approximated by straight lines, so there's some
+        // error.
+        let circular_pitch = std::f64::consts::PI * gear.module;
+        assert!((thickness - circular_pitch / 2.).abs() < 0.01);
+    }
+
+    #[test]
+    fn gear_with_bore_should_be_valid() {
+        let gear = SpurGear::new(1.5, 12).with_bore(5.).with_backlash(0.1);
+
+        match gear.shape() {
+            Shape3d::Sweep(sweep) => assert_eq!(sweep.length, 12.),
+            shape => panic!("Expected gear to be a sweep, got {:?}", shape),
+        }
+        assert!(Shape::from(gear.shape()).check().is_ok());
+    }
+}
@@ -0,0 +1,286 @@
+//! Bosses for heat-set threaded inserts
+//!
+//! Printed parts can't hold a thread that is screwed in and out repeatedly. A
+//! heat-set insert is a brass sleeve with a thread on the inside, that is
+//! pressed into a hole in the part using a soldering iron. The hole needs to
+//! be a bit smaller than the insert, and the plastic around it needs to be
+//! thick enough to hold the insert.
+//!
+//! An [`InsertBoss`] is a cylinder with such a hole, optionally braced by
+//! gussets. Its dimensions can be derived from one of the [`InsertSize`]
+//! presets. Like the features in [`snap_fit`](crate::snap_fit), bosses are
+//! built in a local coordinate system, standing on the x-y plane, and placed
+//! on a face of a part using a [`Placement`].
+
+use std::f64::consts::TAU;
+
+use crate::{
+    placement::{sweep_side_profile, translate_z},
+    Circle, Difference2d, Placement, Shape3d, Sketch, Sweep, Transform, Union,
+};
+
+/// Common sizes of heat-set inserts
+///
+/// The dimensions are typical for the short inserts, which are sold for 3D
+/// printing. They vary between manufacturers, so please check the datasheet of
+/// the inserts that you use.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InsertSize {
+    /// An insert for M2 screws
+    M2,
+
+    /// An insert for M2.5 screws
+    M2_5,
+
+    /// An insert for M3 screws
+    M3,
+
+    /// An insert for M4 screws
+    M4,
+
+    /// An insert for M5 screws
+    M5,
+}
+
+impl InsertSize {
+    /// The diameter of the hole that the insert is pressed into
+    pub fn hole_diameter(&self) -> f64 {
+        match self {
+            Self::M2 => 3.2,
+            Self::M2_5 => 3.6,
+            Self::M3 => 4.,
+            Self::M4 => 5.6,
+            Self::M5 => 6.4,
+        }
+    }
+
+    /// The length of the insert
+    pub fn length(&self) -> f64 {
+        match self {
+            Self::M2 => 4.,
+            Self::M2_5 => 5.7,
+            Self::M3 => 5.7,
+            Self::M4 => 8.1,
+            Self::M5 => 9.5,
+        }
+    }
+}
+
+/// A boss for a heat-set insert
+///
+/// In its local coordinate system, the boss stands on the x-y plane, centered
+/// on the z-axis. The hole is at its top.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InsertBoss {
+    /// The diameter of the hole
+    pub hole_diameter: f64,
+
+    /// The depth of the hole, from the top of the boss
+    ///
+    /// Should be a bit longer than the insert, to leave room for the plastic
+    /// that the insert displaces. If it's not smaller than the height of the
+    /// boss, the hole goes all the way down to the face that the boss stands
+    /// on.
+    pub hole_depth: f64,
+
+    /// The thickness of the wall around the hole
+    pub wall: f64,
+
+    /// The height of the boss
+    pub height: f64,
+
+    /// The number of gussets around the boss
+    ///
+    /// The gussets are evenly spaced, with the first one pointing towards
+    /// positive x.
+    pub gussets: usize,
+
+    /// The thickness of each gusset
+    pub gusset_thickness: f64,
+
+    /// How far each gusset reaches out from the boss, at its foot
+    pub gusset_length: f64,
+}
+
+impl InsertBoss {
+    /// Create a boss for an insert of the given size
+    ///
+    /// The hole is made 1 longer than the insert, and the boss as high as the
+    /// hole is deep. The wall thickness is initialized to half the diameter of
+    /// the hole, making the boss twice as wide as the hole, which is a common
+    /// rule of thumb. The boss has no gussets, but if any are added, they are
+    /// as thick as the wall, and reach as far as the boss is high.
+    pub fn new(size: InsertSize) -> Self {
+        let hole_depth = size.length() + 1.;
+        let wall = size.hole_diameter() / 2.;
+
+        Self {
+            hole_diameter: size.hole_diameter(),
+            hole_depth,
+            wall,
+            height: hole_depth,
+            gussets: 0,
+            gusset_thickness: wall,
+            gusset_length: hole_depth,
+        }
+    }
+
+    /// Brace the boss with the given number of gussets
+    pub fn with_gussets(mut self, gussets: usize) -> Self {
+        self.gussets = gussets;
+        self
+    }
+
+    /// The outer radius of the boss
+    pub fn outer_radius(&self) -> f64 {
+        self.hole_diameter / 2. + self.wall
+    }
+
+    /// Create the shape of the boss, in its local coordinate system
+    pub fn shape(&self) -> Shape3d {
+        let hole_depth = self.hole_depth.min(self.height);
+        let floor = self.height - hole_depth;
+
+        let circle = |radius| Circle {
+            center: [0., 0.],
+            radius,
+        };
+        let ring = Difference2d {
+            a: circle(self.outer_radius()).into(),
+            b: circle(self.hole_diameter / 2.).into(),
+        };
+        let mut boss = translate_z(
+            Sweep {
+                shape: ring.into(),
+                length: hole_depth,
+            },
+            floor,
+        );
+
+        if floor > 0. {
+            let base = Sweep {
+                shape: circle(self.outer_radius()).into(),
+                length: floor,
+            };
+            boss = Union {
+                a: base.into(),
+                b: boss,
+            }
+            .into();
+        }
+
+        for i in 0..self.gussets {
+            let angle = TAU * i as f64 / self.gussets as f64;
+            boss = Union {
+                a: boss,
+                b: self.gusset(angle),
+            }
+            .into();
+        }
+
+        boss
+    }
+
+    /// Create the boss, and move it to the placement
+    pub fn place(&self, placement: &Placement) -> Transform {
+        placement.place(self.shape())
+    }
+
+    /// Create a boss at each of the positions, and move them to the placement
+    ///
+    /// The positions are in the x-y plane of the placement's local coordinate
+    /// system.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if there are no positions.
+    pub fn place_at(
+        &self,
+        placement: &Placement,
+        positions: &[[f64; 2]],
+    ) -> Transform {
+        let bosses = positions
+            .iter()
+            .map(|&[x, y]| {
+                Transform {
+                    shape: self.shape(),
+                    axis: [0., 0., 1.],
+                    angle: 0.,
+                    offset: [x, y, 0.],
+                }
+                .into()
+            })
+            .reduce(|a: Shape3d, b| Union { a, b }.into())
+            .expect("No positions to place bosses at");
+
+        placement.place(bosses)
+    }
+
+    /// Create a gusset that points in the direction of the angle
+    fn gusset(&self, angle: f64) -> Shape3d {
+        // The gusset starts in the middle of the wall, not on its outside.
+        // The outside of the wall is curved, so a flat gusset that starts
+        // there would only touch it along a line, leaving a gap to either side.
+        let foot = self.hole_diameter / 2. + self.wall / 2.;
+
+        // The side profile of the gusset. Its x-axis is the local x-axis, its
+        // y-axis is the local z-axis.
+        let profile = Sketch::from_points(vec![
+            [foot, 0.],
+            [self.outer_radius() + self.gusset_length, 0.],
+            [foot, self.height],
+        ]);
+
+        let gusset = sweep_side_profile(profile, self.gusset_thickness);
+
+        Transform {
+            shape: gusset.into(),
+            axis: [0., 0., 1.],
+            angle,
+            offset: [0., 0., 0.],
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Placement, Shape, Shape3d};
+
+    use super::{InsertBoss, InsertSize};
+
+    #[test]
+    fn boss_should_be_derived_from_insert_size() {
+        let boss = InsertBoss::new(InsertSize::M3);
+
+        assert_eq!(boss.hole_diameter, 4.);
+        assert!((boss.hole_depth - 6.7).abs() < 1e-12);
+        assert_eq!(boss.height, boss.hole_depth);
+        assert_eq!(boss.outer_radius(), 4.);
+
+        // Without a floor or gussets, the boss is just the swept ring.
+        assert!(matches!(boss.shape(), Shape3d::Transform(_)));
+        assert!(Shape::from(boss.shape()).check().is_ok());
+    }
+
+    #[test]
+    fn boss_should_have_floor_and_gussets() {
+        let mut boss = InsertBoss::new(InsertSize::M2).with_gussets(4);
+        boss.height = 8.;
+
+        // The floor, the ring, and the 4 gussets
+        let mut parts = 1;
+        let mut shape = boss.shape();
+        while let Shape3d::Union(union) = shape {
+            parts += 1;
+            shape = union.a;
+        }
+        assert_eq!(parts, 6);
+
+        let placed = boss.place_at(
+            &Placement::new([0., 0., 2.], [1., 0., 0.], [0., 0., 1.]),
+            &[[5., 5.], [25., 5.]],
+        );
+        assert!(Shape::from(placed).check().is_ok());
+    }
+}
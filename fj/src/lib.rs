@@ -9,23 +9,116 @@
 //! usage examples.
 //!
 //! [Fornjot repository]: https://github.com/hannobraun/Fornjot
+//!
+//! # Features
+//!
+//! The shape types, which are passed from the model to the host application,
+//! the rest of the interface between them (see [`abi`]), [`Placement`], and
+//! the densities of materials (see [`material`]) are always available.
+//! Everything else can be disabled, to reduce the time it takes to compile a
+//! model:
+//!
+//! - `calibration` (default): Test prints for calibrating clearances, in
+//!   [`calibration`].
+//! - `cable` (default): Cable management features, in [`cable`].
+//! - `enclosure` (default): Boxes with a lid and standoffs, in [`enclosure`].
+//! - `gear` (default): Involute spur gears, in [`gear`].
+//! - `insert` (default): Bosses for heat-set threaded inserts, in [`insert`].
+//! - `living-hinge` (default): Living hinge cut patterns, in [`living_hinge`].
+//! - `macros` (default): The [`model`] macro, which is the recommended way
+//!   to define a model.
+//! - `sheet-metal` (default): Sheet metal parts, in [`sheet_metal`].
+//! - `snap-fit` (default): Snap-fit features, in [`snap_fit`].
+//! - `syntax` (default): Extension traits for a more concise syntax, in
+//!   [`prelude`].
+//! - `thread` (default): Metric screw threads, in [`thread`].
+//! - `serde`: Implementations of Serde's `Serialize` and `Deserialize` for
+//!   the shape types.
+//!
+//! Features never change the layout of the shape types, so a model and the
+//! host application don't need to enable the same features.
+
+use std::collections::HashMap;
 
+pub mod abi;
+pub mod material;
+
+mod check;
+mod placement;
 mod shape_2d;
 mod shape_3d;
+
+#[cfg(feature = "syntax")]
 mod syntax;
 
+#[cfg(feature = "cable")]
+pub mod cable;
+
+#[cfg(feature = "calibration")]
+pub mod calibration;
+
+#[cfg(feature = "enclosure")]
+pub mod enclosure;
+
+#[cfg(feature = "gear")]
+pub mod gear;
+
+#[cfg(feature = "insert")]
+pub mod insert;
+
+#[cfg(feature = "living-hinge")]
+pub mod living_hinge;
+
+#[cfg(feature = "sheet-metal")]
+pub mod sheet_metal;
+
+#[cfg(feature = "snap-fit")]
+pub mod snap_fit;
+
+#[cfg(feature = "thread")]
+pub mod thread;
+
+#[cfg(feature = "syntax")]
 pub mod prelude {
     pub use crate::syntax::{
-        Rotate as _, Sketch as _, Sweep as _, Translate as _, Union as _,
+        Anchor as _, Bend as _, Material as _, Rotate as _, Sketch as _,
+        Sweep as _, Texture as _, Translate as _, Twist as _, Union as _,
+        Wrap as _,
     };
 }
 
-pub use self::{shape_2d::*, shape_3d::*};
+pub use self::{
+    check::ShapeError, placement::Placement, shape_2d::*, shape_3d::*,
+};
+
+#[cfg(feature = "macros")]
+pub use fj_proc::model;
 
 /// A shape
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub enum Shape {
     Shape2d(Shape2d),
     Shape3d(Shape3d),
 }
+
+/// The name of the model parameter that holds the animation time
+///
+/// To animate a model, the host application evaluates it repeatedly, passing
+/// the normalized time in this parameter. The time goes from `0.0` at the
+/// start of the animation, to `1.0` at its end.
+pub const TIME: &str = "time";
+
+/// Read the animation time from the model parameters
+///
+/// Returns `0.0`, if the model is not being animated.
+///
+/// # Panics
+///
+/// Panics, if the time parameter is not a valid number.
+pub fn time(args: &HashMap<String, String>) -> f64 {
+    args.get(TIME)
+        .map(|time| time.parse().expect("Invalid animation time"))
+        .unwrap_or(0.)
+}
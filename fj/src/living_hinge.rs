@@ -0,0 +1,178 @@
+//! Living hinges
+//!
+//! A living hinge makes a section of a rigid sheet flexible, by cutting a
+//! pattern of slits into it. The slits are arranged in columns, and the
+//! columns are offset against each other, so the sheet turns into a lattice of
+//! thin beams that twist, when it's bent. This works for laser-cut plywood and
+//! acrylic, as well as for 3D-printed parts.
+//!
+//! The slits are provided as [`Sketch`]es, one for each slit, which are cut out
+//! of the outline of the part.
+//!
+//! # Limitations
+//!
+//! The 2-dimensional difference operation currently only supports a single
+//! cut (see [`Difference2d`](crate::Difference2d)). Until that's resolved,
+//! the cuts can only be subtracted from the part one at a time, or exported for
+//! a laser cutter, alongside the outline of the part.
+
+use crate::Sketch;
+
+/// A living hinge pattern
+///
+/// The pattern fills an axis-aligned rectangle in the x-y plane. The slits are
+/// parallel to the y-axis, so the sheet bends around that axis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LivingHinge {
+    /// The corner of the region with the smallest coordinates
+    pub min: [f64; 2],
+
+    /// The corner of the region with the largest coordinates
+    pub max: [f64; 2],
+
+    /// The length of each slit
+    pub slit_length: f64,
+
+    /// The width of each slit
+    ///
+    /// For a laser cutter, this is the width of the kerf.
+    pub slit_width: f64,
+
+    /// The distance between neighboring columns of slits
+    ///
+    /// Smaller distances make for thinner beams between the slits, which makes
+    /// the hinge more flexible, but also more fragile.
+    pub spacing: f64,
+
+    /// The length of the material between two slits in the same column
+    pub bridge: f64,
+}
+
+impl LivingHinge {
+    /// Create a living hinge pattern
+    ///
+    /// The slit width is initialized to `0.2`, which is a typical kerf of a
+    /// laser cutter.
+    pub fn new(
+        min: [f64; 2],
+        max: [f64; 2],
+        slit_length: f64,
+        spacing: f64,
+        bridge: f64,
+    ) -> Self {
+        Self {
+            min,
+            max,
+            slit_length,
+            slit_width: 0.2,
+            spacing,
+            bridge,
+        }
+    }
+
+    /// Create the slits that make up the pattern
+    ///
+    /// The columns are centered in the region, and so are the slits within
+    /// every other column. The slits of the remaining columns are offset by
+    /// half a period, and cut short where they would leave the region. Slits
+    /// that would end up shorter than a bridge are left out.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the region isn't finite, if the spacing or the slit length
+    /// isn't positive, or if the bridge is negative. The pattern would repeat
+    /// endlessly in those cases.
+    pub fn cuts(&self) -> Vec<Sketch> {
+        let [min_x, min_y] = self.min;
+        let [max_x, max_y] = self.max;
+
+        assert!(
+            self.min.iter().chain(&self.max).all(|c| c.is_finite()),
+            "Living hinge region must be finite"
+        );
+        assert!(self.spacing > 0., "Living hinge spacing must be positive");
+        assert!(
+            self.slit_length > 0. && self.bridge >= 0.,
+            "Living hinge needs positive slit length and non-negative bridge"
+        );
+        let [center_x, center_y] = [(min_x + max_x) / 2., (min_y + max_y) / 2.];
+
+        let columns = ((max_x - min_x) / self.spacing).floor() as i64;
+        let period = self.slit_length + self.bridge;
+        let half_width = self.slit_width / 2.;
+
+        let mut cuts = Vec::new();
+
+        for column in 0..columns {
+            let x = center_x
+                + (column as f64 - (columns - 1) as f64 / 2.) * self.spacing;
+            let offset = if column % 2 == 0 { 0. } else { period / 2. };
+
+            // The range of slits that could overlap the region
+            let first = ((min_y - center_y - offset) / period).floor() as i64;
+            let last = ((max_y - center_y - offset) / period).ceil() as i64;
+
+            for slit in first..=last {
+                let center = center_y + offset + slit as f64 * period;
+                let start = (center - self.slit_length / 2.).max(min_y);
+                let end = (center + self.slit_length / 2.).min(max_y);
+
+                if end - start < self.bridge {
+                    continue;
+                }
+
+                cuts.push(Sketch::from_points(vec![
+                    [x - half_width, start],
+                    [x + half_width, start],
+                    [x + half_width, end],
+                    [x - half_width, end],
+                ]));
+            }
+        }
+
+        cuts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LivingHinge;
+
+    #[test]
+    fn cuts_should_alternate_between_columns() {
+        let hinge = LivingHinge::new([0., 0.], [10., 50.], 20., 2., 3.);
+        let cuts: Vec<_> =
+            hinge.cuts().iter().map(|cut| cut.to_points()).collect();
+
+        // Columns at 1, 3, 5, 7, and 9. Even columns have a slit centered in
+        // the region, and two that are cut short at the edges. Odd columns
+        // have two full slits, which leave a bridge at the center.
+        let column = |x: f64| {
+            cuts.iter()
+                .filter(|cut| (cut[0][0] + 0.1 - x).abs() < 1e-9)
+                .map(|cut| [cut[0][1], cut[2][1]])
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(column(1.), [[0., 12.], [15., 35.], [38., 50.]]);
+        assert_eq!(column(3.), [[3.5, 23.5], [26.5, 46.5]]);
+        assert_eq!(column(9.), column(1.));
+        assert_eq!(cuts.len(), 3 * 3 + 2 * 2);
+
+        for cut in cuts.iter().flatten() {
+            assert!((0. ..=10.).contains(&cut[0]));
+            assert!((0. ..=50.).contains(&cut[1]));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "spacing must be positive")]
+    fn cuts_should_panic_on_zero_spacing() {
+        LivingHinge::new([0., 0.], [10., 50.], 20., 0., 3.).cuts();
+    }
+
+    #[test]
+    #[should_panic(expected = "positive slit length")]
+    fn cuts_should_panic_on_empty_period() {
+        LivingHinge::new([0., 0.], [10., 50.], 2., 2., -2.).cuts();
+    }
+}
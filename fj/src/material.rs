@@ -0,0 +1,42 @@
+//! Materials, and their densities
+//!
+//! Shapes are assigned a material by name, using [`crate::Material`]. The host
+//! application knows the materials in [`DENSITIES`], and can be configured to
+//! know more. Models can use [`density`] to compute the mass of a part
+//! themselves, for example to size a counterweight.
+//!
+//! ```
+//! let density = fj::material::density("aluminum").unwrap();
+//!
+//! // The mass of a 10 mm cube, in grams
+//! let mass = 10. * 10. * 10. / 1000. * density;
+//! assert!((mass - 2.7).abs() < 1e-9);
+//! ```
+
+/// The densities of common materials, in grams per cubic centimeter
+///
+/// The values are typical for each material. The density of a specific alloy
+/// or filament can differ by a few percent.
+pub const DENSITIES: &[(&str, f64)] = &[
+    ("abs", 1.04),
+    ("aluminum", 2.70),
+    ("brass", 8.50),
+    ("copper", 8.96),
+    ("nylon", 1.14),
+    ("petg", 1.27),
+    ("pla", 1.24),
+    ("stainless-steel", 8.00),
+    ("steel", 7.85),
+    ("titanium", 4.43),
+    ("wood", 0.70),
+];
+
+/// Look up the density of a material, in grams per cubic centimeter
+///
+/// Returns `None`, if the material is not in [`DENSITIES`].
+pub fn density(name: &str) -> Option<f64> {
+    DENSITIES
+        .iter()
+        .find(|(material, _)| *material == name)
+        .map(|&(_, density)| density)
+}
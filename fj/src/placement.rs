@@ -0,0 +1,219 @@
+#[cfg(any(feature = "cable", feature = "insert", feature = "snap-fit"))]
+use std::f64::consts::FRAC_PI_2;
+
+#[cfg(any(feature = "cable", feature = "insert", feature = "snap-fit"))]
+use crate::{Shape2d, Sweep};
+use crate::{Shape3d, Transform};
+
+/// The position and orientation of a feature on a face
+///
+/// Features, like the snap-fits in [`snap_fit`](crate::snap_fit), are built
+/// in a local coordinate system, whose x-y plane is the face they are placed
+/// on, and whose z-axis points out of the face. A placement moves that
+/// coordinate system to where the face actually is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Placement {
+    /// The point on the face that the feature's origin is moved to
+    pub origin: [f64; 3],
+
+    /// The direction that the feature's x-axis points in
+    ///
+    /// Doesn't need to be perpendicular to `z_axis`. Only its component that
+    /// is perpendicular to `z_axis` is used.
+    pub x_axis: [f64; 3],
+
+    /// The direction that the feature's z-axis points in
+    ///
+    /// This is usually the outward normal of the face.
+    pub z_axis: [f64; 3],
+}
+
+impl Placement {
+    /// Create a placement
+    pub fn new(origin: [f64; 3], x_axis: [f64; 3], z_axis: [f64; 3]) -> Self {
+        Self {
+            origin,
+            x_axis,
+            z_axis,
+        }
+    }
+
+    /// Move a shape from the local coordinate system to the placement
+    ///
+    /// # Panics
+    ///
+    /// Panics, if either axis has a length of zero, or if they are parallel.
+    pub fn place(&self, shape: impl Into<Shape3d>) -> Transform {
+        let z = normalize(self.z_axis).expect("Placement has zero z-axis");
+        let x = sub(self.x_axis, scale(z, dot(self.x_axis, z)));
+        let x = normalize(x).expect("Placement axes are parallel");
+        let y = cross(z, x);
+
+        let (axis, angle) = axis_angle([x, y, z]);
+
+        Transform {
+            shape: shape.into(),
+            axis,
+            angle,
+            offset: self.origin,
+        }
+    }
+}
+
+/// Sweep a side profile of a feature along the local y-axis
+///
+/// The profile's x-axis is the local x-axis, its y-axis is the local z-axis.
+/// Sweeping extends the profile along the local z-axis, so the sweep is
+/// rotated, to extend along the y-axis instead, centered on the x-z plane.
+#[cfg(any(feature = "cable", feature = "insert", feature = "snap-fit"))]
+pub(crate) fn sweep_side_profile(
+    profile: impl Into<Shape2d>,
+    width: f64,
+) -> Transform {
+    Transform {
+        shape: Sweep {
+            shape: profile.into(),
+            length: width,
+        }
+        .into(),
+        axis: [1., 0., 0.],
+        angle: FRAC_PI_2,
+        offset: [0., width / 2., 0.],
+    }
+}
+
+/// Move a shape along the z-axis
+#[cfg(any(feature = "calibration", feature = "enclosure", feature = "insert"))]
+pub(crate) fn translate_z(shape: impl Into<Shape3d>, z: f64) -> Shape3d {
+    Transform {
+        shape: shape.into(),
+        axis: [0., 0., 1.],
+        angle: 0.,
+        offset: [0., 0., z],
+    }
+    .into()
+}
+
+/// Convert a rotation, given by the images of the unit vectors, to an axis and
+/// an angle
+///
+/// Goes through a quaternion, which works for any angle, including a half
+/// turn, where the axis can't be read off the rotation matrix directly.
+fn axis_angle([x, y, z]: [[f64; 3]; 3]) -> ([f64; 3], f64) {
+    // The columns of the rotation matrix are the rotated unit vectors.
+    let m = |row: usize, column: usize| [x, y, z][column][row];
+
+    let trace = m(0, 0) + m(1, 1) + m(2, 2);
+    let [w, i, j, k] = if trace > 0. {
+        let s = (trace + 1.).sqrt() * 2.;
+        [
+            s / 4.,
+            (m(2, 1) - m(1, 2)) / s,
+            (m(0, 2) - m(2, 0)) / s,
+            (m(1, 0) - m(0, 1)) / s,
+        ]
+    } else if m(0, 0) > m(1, 1) && m(0, 0) > m(2, 2) {
+        let s = (1. + m(0, 0) - m(1, 1) - m(2, 2)).sqrt() * 2.;
+        [
+            (m(2, 1) - m(1, 2)) / s,
+            s / 4.,
+            (m(0, 1) + m(1, 0)) / s,
+            (m(0, 2) + m(2, 0)) / s,
+        ]
+    } else if m(1, 1) > m(2, 2) {
+        let s = (1. + m(1, 1) - m(0, 0) - m(2, 2)).sqrt() * 2.;
+        [
+            (m(0, 2) - m(2, 0)) / s,
+            (m(0, 1) + m(1, 0)) / s,
+            s / 4.,
+            (m(1, 2) + m(2, 1)) / s,
+        ]
+    } else {
+        let s = (1. + m(2, 2) - m(0, 0) - m(1, 1)).sqrt() * 2.;
+        [
+            (m(1, 0) - m(0, 1)) / s,
+            (m(0, 2) + m(2, 0)) / s,
+            (m(1, 2) + m(2, 1)) / s,
+            s / 4.,
+        ]
+    };
+
+    let angle = 2. * dot([i, j, k], [i, j, k]).sqrt().atan2(w);
+    match normalize([i, j, k]) {
+        Some(axis) => (axis, angle),
+
+        // No rotation. The axis doesn't matter, but must not be zero.
+        None => ([0., 0., 1.], 0.),
+    }
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(a: [f64; 3], factor: f64) -> [f64; 3] {
+    a.map(|component| component * factor)
+}
+
+fn normalize(a: [f64; 3]) -> Option<[f64; 3]> {
+    let length = dot(a, a).sqrt();
+    if length == 0. {
+        return None;
+    }
+
+    Some(scale(a, 1. / length))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    use crate::{Sketch, Sweep};
+
+    use super::Placement;
+
+    #[test]
+    fn place_should_rotate_local_axes_onto_placement() {
+        let shape = Sweep {
+            shape: Sketch::from_points(vec![[0., 0.], [1., 0.], [0., 1.]])
+                .into(),
+            length: 1.,
+        };
+        let axis_angle = |x_axis, z_axis| {
+            let transform = Placement::new([1., 2., 3.], x_axis, z_axis)
+                .place(shape.clone());
+            assert_eq!(transform.offset, [1., 2., 3.]);
+
+            (transform.axis.map(round), round(transform.angle))
+        };
+
+        assert_eq!(axis_angle([1., 0., 0.], [0., 0., 2.]), ([0., 0., 1.], 0.));
+        assert_eq!(
+            axis_angle([0., 1., 0.], [0., 0., 1.]),
+            ([0., 0., 1.], round(FRAC_PI_2))
+        );
+
+        // A face that points down is a half turn around the x-axis, which is
+        // where the axis can't be read off the rotation matrix.
+        let (axis, angle) = axis_angle([1., 0., 0.], [0., 0., -1.]);
+        assert_eq!(axis.map(f64::abs), [1., 0., 0.]);
+        assert_eq!(angle, round(PI));
+    }
+
+    /// Round away floating-point noise, to compare results exactly
+    fn round(value: f64) -> f64 {
+        (value * 1e9).round() / 1e9
+    }
+}
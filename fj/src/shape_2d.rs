@@ -4,6 +4,7 @@ use crate::Shape;
 
 /// A 2-dimensional shape
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub enum Shape2d {
     /// A circle
@@ -12,14 +13,21 @@ pub enum Shape2d {
     /// A difference between two shapes
     Difference(Box<Difference2d>),
 
+    /// An ellipse
+    Ellipse(Ellipse),
+
     /// A sketch
     Sketch(Sketch),
 }
 
 /// A circle
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Circle {
+    /// The center of the circle
+    pub center: [f64; 2],
+
     /// The radius of the circle
     pub radius: f64,
 }
@@ -36,8 +44,36 @@ impl From<Circle> for Shape2d {
     }
 }
 
+/// An ellipse
+///
+/// The axes of the ellipse are aligned with the x- and y-axes. To get an
+/// ellipse that is rotated, sweep it and rotate the result.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct Ellipse {
+    /// The center of the ellipse
+    pub center: [f64; 2],
+
+    /// The radii of the ellipse, along the x- and y-axes
+    pub radii: [f64; 2],
+}
+
+impl From<Ellipse> for Shape {
+    fn from(shape: Ellipse) -> Self {
+        Self::Shape2d(Shape2d::Ellipse(shape))
+    }
+}
+
+impl From<Ellipse> for Shape2d {
+    fn from(shape: Ellipse) -> Self {
+        Self::Ellipse(shape)
+    }
+}
+
 /// A difference between two shapes
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Difference2d {
     /// The original shape
@@ -65,10 +101,22 @@ impl From<Difference2d> for Shape2d {
 /// represented by a number of points. For example, if the points a, b, and c
 /// are provided, the edges ab, bc, and ca are assumed.
 ///
-/// Nothing about these edges is checked right now, but algorithms might assume
-/// that the edges are non-overlapping. If you create a `Sketch` with
+/// Outlines that are traced as a path, which returns to where it started, can
+/// be turned into a sketch using [`Sketch::from_path`]. Such a sketch is
+/// rejected by [`Sketch::check`], if the path doesn't actually return to its
+/// start, unless the gap is closed using [`Sketch::with_auto_close`].
+///
+/// Degenerate sketches, with fewer than 3 points, repeated points, or no area,
+/// are rejected by [`Sketch::check`]. Consecutive duplicates of a point are
+/// merged into one, instead of being rejected. Beyond that, algorithms might
+/// assume that the edges are non-overlapping. If you create a `Sketch` with
 /// overlapping edges, you're on your own.
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "SketchData", into = "SketchData")
+)]
 #[repr(C)]
 pub struct Sketch {
     // The fields are the raw parts of a `Vec`. `Sketch` needs to be FFI-safe,
@@ -76,6 +124,12 @@ pub struct Sketch {
     ptr: *mut [f64; 2],
     length: usize,
     capacity: usize,
+
+    tag: Tag,
+
+    // Whether the points are the path of an outline that doesn't return to
+    // its start. See `Sketch::from_path`.
+    open: bool,
 }
 
 impl Sketch {
@@ -94,9 +148,128 @@ impl Sketch {
             ptr,
             length,
             capacity,
+            tag: Tag::Cut,
+            open: false,
         }
     }
 
+    /// Create a sketch from a path that returns to where it started
+    ///
+    /// Unlike with [`Sketch::from_points`], the last point of `points` is
+    /// expected to be the same as the first, closing the outline. It may be
+    /// off by up to `tolerance`, which is common for paths that were traced
+    /// or converted from other formats. The last point is then left out, and
+    /// the outline ends at the first point instead.
+    ///
+    /// If the last point is farther than `tolerance` from the first, the
+    /// outline is open, and [`Sketch::check`] rejects the sketch. Use
+    /// [`Sketch::with_auto_close`] to close it with an additional edge
+    /// instead.
+    ///
+    /// ```
+    /// let triangle = vec![[0., 0.], [2., 0.], [1., 2.], [1e-9, 0.]];
+    /// let sketch = fj::Sketch::from_path(triangle, 1e-6);
+    /// assert_eq!(sketch.to_points(), [[0., 0.], [2., 0.], [1., 2.]]);
+    ///
+    /// let open = vec![[0., 0.], [2., 0.], [1., 2.]];
+    /// let sketch = fj::Sketch::from_path(open, 1e-6);
+    /// assert!(sketch.check().is_err());
+    /// assert!(sketch.with_auto_close().check().is_ok());
+    /// ```
+    pub fn from_path(mut points: Vec<[f64; 2]>, tolerance: f64) -> Self {
+        let closed = match (points.first(), points.last()) {
+            (Some(&[ax, ay]), Some(&[bx, by])) if points.len() > 1 => {
+                (bx - ax).hypot(by - ay) <= tolerance
+            }
+            _ => false,
+        };
+
+        if closed {
+            points.pop();
+        }
+
+        let mut sketch = Self::from_points(points);
+        sketch.open = !closed;
+        sketch
+    }
+
+    /// Create a symmetric sketch from half of its outline
+    ///
+    /// `half` is mirrored across `axis`, and the mirrored points are appended
+    /// in reverse order, completing the outline. Typically, the first and the
+    /// last point of `half` are on the axis. Such points are not repeated in
+    /// the mirrored half. If they are not on the axis, they are connected to
+    /// their mirror images by edges that cross the axis.
+    ///
+    /// Mirroring the points manually tends to introduce tiny asymmetries, due
+    /// to rounding errors. Points that are on the axis might end up slightly
+    /// off it, resulting in nearly identical points, that cause trouble later
+    /// on. This method avoids that, as mirroring across an axis-parallel line
+    /// leaves points on the line exactly where they are.
+    ///
+    /// ```
+    /// let sketch = fj::Sketch::symmetric(
+    ///     vec![[0., 0.], [2., 0.], [1., 3.], [0., 3.]],
+    ///     fj::MirrorAxis::Vertical(0.),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     sketch.to_points(),
+    ///     [[0., 0.], [2., 0.], [1., 3.], [0., 3.], [-1., 3.], [-2., 0.]],
+    /// );
+    /// ```
+    pub fn symmetric(mut half: Vec<[f64; 2]>, axis: MirrorAxis) -> Self {
+        let mut mirrored: Vec<_> =
+            half.iter().rev().map(|&point| axis.mirror(point)).collect();
+
+        // Points on the axis are their own mirror images. Repeating them
+        // would result in a degenerate sketch.
+        if let (Some(first), Some(last)) = (half.first(), mirrored.last()) {
+            if first == last {
+                mirrored.pop();
+            }
+        }
+        if let (Some(last), Some(first)) = (half.last(), mirrored.first()) {
+            if last == first {
+                mirrored.remove(0);
+            }
+        }
+
+        half.extend(mirrored);
+        Self::from_points(half)
+    }
+
+    /// Tag the sketch
+    ///
+    /// Sketches are tagged with [`Tag::Cut`] by default.
+    pub fn with_tag(mut self, tag: Tag) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    /// Return the tag of the sketch
+    pub fn tag(&self) -> Tag {
+        self.tag
+    }
+
+    /// Close the outline of the sketch with an additional edge, if necessary
+    ///
+    /// Only makes a difference for sketches created with
+    /// [`Sketch::from_path`], whose path doesn't return to its start. The last
+    /// point is then connected to the first, like for sketches created with
+    /// [`Sketch::from_points`].
+    pub fn with_auto_close(mut self) -> Self {
+        self.open = false;
+        self
+    }
+
+    /// Indicate whether the outline of the sketch is open
+    ///
+    /// See [`Sketch::from_path`].
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
     /// Return the points of the sketch
     pub fn to_points(&self) -> Vec<[f64; 2]> {
         // This is sound. All invariants are automatically kept, as the raw
@@ -131,6 +304,125 @@ impl From<Sketch> for Shape2d {
     }
 }
 
+/// A line that a sketch can be mirrored across
+///
+/// See [`Sketch::symmetric`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MirrorAxis {
+    /// The line parallel to the y-axis, at the given x coordinate
+    ///
+    /// Mirrors points from left to right, and vice versa.
+    Vertical(f64),
+
+    /// The line parallel to the x-axis, at the given y coordinate
+    ///
+    /// Mirrors points from top to bottom, and vice versa.
+    Horizontal(f64),
+}
+
+impl MirrorAxis {
+    /// Mirror a point across the axis
+    ///
+    /// Points on the axis are returned unchanged.
+    pub fn mirror(&self, [x, y]: [f64; 2]) -> [f64; 2] {
+        // `2. * c - c` is exactly `c`, so points on the axis stay on it.
+        match *self {
+            Self::Vertical(c) => [2. * c - x, y],
+            Self::Horizontal(c) => [x, 2. * c - y],
+        }
+    }
+}
+
+/// The serialized form of [`Sketch`]
+///
+/// `Sketch` stores the raw parts of a `Vec`, which can't be serialized as-is.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SketchData {
+    points: Vec<[f64; 2]>,
+    tag: Tag,
+    #[serde(default)]
+    open: bool,
+}
+
+#[cfg(feature = "serde")]
+impl From<SketchData> for Sketch {
+    fn from(data: SketchData) -> Self {
+        let mut sketch = Self::from_points(data.points).with_tag(data.tag);
+        sketch.open = data.open;
+        sketch
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Sketch> for SketchData {
+    fn from(sketch: Sketch) -> Self {
+        Self {
+            points: sketch.to_points(),
+            tag: sketch.tag(),
+            open: sketch.is_open(),
+        }
+    }
+}
+
+/// Marks what the outline of a sketch is used for, when it is manufactured
+///
+/// Tags don't affect the geometry of a sketch. They are used when exporting
+/// 2-dimensional shapes for a laser cutter, which cuts through the material
+/// along some lines, but only marks the surface along others.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub enum Tag {
+    /// Cut through the material along the outline
+    Cut,
+
+    /// Engrave the outline into the surface of the material
+    Engrave,
+
+    /// Score the outline, marking it with a light, shallow line
+    Score,
+}
+
 // `Sketch` can be `Send`, because it encapsulates the raw pointer it contains,
 // making sure memory ownership rules are observed.
 unsafe impl Send for Sketch {}
+
+// `Sketch` can be `Sync`, because the points behind the raw pointer are never
+// modified after it was created. Shared references only ever read them.
+unsafe impl Sync for Sketch {}
+
+#[cfg(test)]
+mod tests {
+    use super::{MirrorAxis, Sketch};
+
+    #[test]
+    fn symmetric() {
+        // Points on an axis that isn't at zero stay exactly on it.
+        let sketch = Sketch::symmetric(
+            vec![[0.3, 0.1], [0.9, 0.7], [0.3, 1.3]],
+            MirrorAxis::Vertical(0.3),
+        );
+        let points = sketch.to_points();
+        assert_eq!(points.len(), 4);
+        assert_eq!(points[3], [2. * 0.3 - 0.9, 0.7]);
+        assert!(sketch.check().is_ok());
+
+        // Points that aren't on the axis are connected to their mirror images.
+        let sketch = Sketch::symmetric(
+            vec![[1., 0.], [2., 1.], [1., 2.]],
+            MirrorAxis::Vertical(0.),
+        );
+        assert_eq!(
+            sketch.to_points(),
+            [
+                [1., 0.],
+                [2., 1.],
+                [1., 2.],
+                [-1., 2.],
+                [-2., 1.],
+                [-1., 0.]
+            ]
+        );
+    }
+}
@@ -2,6 +2,7 @@ use crate::{Shape, Shape2d};
 
 /// A 3-dimensional shape
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub enum Shape3d {
     /// A sweep of 2-dimensional shape along the z-axis
@@ -12,6 +13,18 @@ pub enum Shape3d {
 
     /// The union of two 3-dimensional shapes
     Union(Box<Union>),
+
+    /// A deformed 3-dimensional shape
+    Deform(Box<Deform>),
+
+    /// A 3-dimensional shape with a named anchor
+    Anchor(Box<Anchor>),
+
+    /// A 3-dimensional shape that is made of a material
+    Material(Box<Material>),
+
+    /// A 3-dimensional shape with a texture on some of its faces
+    Texture(Box<Texture>),
 }
 
 impl From<Shape3d> for Shape {
@@ -30,6 +43,7 @@ impl From<Shape3d> for Shape {
 /// See issue:
 /// <https://github.com/hannobraun/Fornjot/issues/101>
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Transform {
     /// The shape being rotated
@@ -59,6 +73,7 @@ impl From<Transform> for Shape3d {
 
 /// A sweep of a 2-dimensional shape along the z-axis
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Sweep {
     /// The 2-dimensional shape being swept
@@ -95,6 +110,7 @@ impl From<Sweep> for Shape3d {
 /// See issue:
 /// <https://github.com/hannobraun/Fornjot/issues/42>
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Union {
     /// The first of the shapes
@@ -115,3 +131,260 @@ impl From<Union> for Shape3d {
         Self::Union(Box::new(shape))
     }
 }
+
+/// A deformed 3-dimensional shape
+///
+/// Unlike [`Transform`], a deformation can change the form of a shape, for
+/// example by bending or twisting it.
+///
+/// # Limitations
+///
+/// The deformation is applied to a triangle mesh that approximates the shape.
+/// This means that the deformed shape consists only of triangles, and can't be
+/// used with operations that require the exact geometry. The faces of the
+/// shape are kept, but their surfaces and edges are lost.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct Deform {
+    /// The shape being deformed
+    pub shape: Shape3d,
+
+    /// The deformation that is applied to the shape
+    pub deformation: Deformation,
+}
+
+impl From<Deform> for Shape {
+    fn from(shape: Deform) -> Self {
+        Self::Shape3d(Shape3d::Deform(Box::new(shape)))
+    }
+}
+
+impl From<Deform> for Shape3d {
+    fn from(shape: Deform) -> Self {
+        Self::Deform(Box::new(shape))
+    }
+}
+
+/// A deformation of a 3-dimensional shape
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub enum Deformation {
+    /// Bend the shape around an axis that is parallel to the y-axis
+    ///
+    /// The z-axis is bent into a circle with the given radius, whose center is
+    /// at `x = radius`. A positive radius bends the shape towards positive x, a
+    /// negative one towards negative x. The cross-section at `z = 0` stays
+    /// where it is.
+    ///
+    /// The shape must not extend beyond the center of the circle.
+    Bend {
+        /// The radius of the bend, measured at the z-axis
+        radius: f64,
+    },
+
+    /// Twist the shape around the z-axis
+    ///
+    /// Each cross-section of the shape is rotated around the z-axis, by an
+    /// angle that is proportional to its height. The cross-section at `z = 0`
+    /// stays where it is.
+    Twist {
+        /// The angle of the rotation per unit of height, in radians
+        ///
+        /// Positive values rotate counter-clockwise, when looking down on the
+        /// shape from positive z.
+        rate: f64,
+    },
+
+    /// Wrap the shape around a cylinder, whose axis is the z-axis
+    ///
+    /// The xy-plane is wrapped around the cylinder without being stretched,
+    /// meaning that distances along the x-axis become arc lengths along the
+    /// circumference of the cylinder. The y-axis becomes the axis of the
+    /// cylinder, and the z-axis points away from it. The origin ends up at
+    /// `[radius, 0, 0]`.
+    ///
+    /// This is typically used to put a sketch onto a round part: Sweep the
+    /// sketch to the height of the embossing, then wrap it around a cylinder
+    /// with the radius of the part.
+    ///
+    /// The shape must not extend below `z = -radius`, and it must be shorter
+    /// than the circumference of the cylinder along the x-axis, or it overlaps
+    /// itself.
+    Wrap {
+        /// The radius of the cylinder
+        radius: f64,
+    },
+}
+
+/// A 3-dimensional shape with a named anchor
+///
+/// An anchor is a coordinate frame on a part, like the center of a mounting
+/// hole, that other parts can be attached to. It doesn't change the shape, but
+/// moves along with it, when the shape is transformed or deformed. The host
+/// application can export the anchors of a model, so assembly tools and
+/// scripts can position parts relative to each other.
+///
+/// To define more than one anchor, wrap a shape in an anchor repeatedly.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct Anchor {
+    /// The shape that the anchor is defined on
+    pub shape: Shape3d,
+
+    /// The name of the anchor
+    pub name: String,
+
+    /// The origin of the anchor's frame
+    pub origin: [f64; 3],
+
+    /// The direction of the frame's x-axis
+    ///
+    /// Doesn't need to be perpendicular to `z_axis`. Only its component that
+    /// is perpendicular to `z_axis` is used.
+    pub x_axis: [f64; 3],
+
+    /// The direction of the frame's z-axis
+    ///
+    /// The y-axis is perpendicular to both other axes, making the frame
+    /// right-handed.
+    pub z_axis: [f64; 3],
+}
+
+impl From<Anchor> for Shape {
+    fn from(shape: Anchor) -> Self {
+        Self::Shape3d(Shape3d::Anchor(Box::new(shape)))
+    }
+}
+
+impl From<Anchor> for Shape3d {
+    fn from(shape: Anchor) -> Self {
+        Self::Anchor(Box::new(shape))
+    }
+}
+
+/// A 3-dimensional shape that is made of a material
+///
+/// The material doesn't change the shape. The host application uses it to
+/// compute the mass and moments of inertia of the shape, from the density of
+/// the material. Materials are referred to by name. See [`crate::material`]
+/// for the materials that are known by default.
+///
+/// If parts of `shape` are wrapped in a material of their own, they keep it.
+/// This way, a default material can be assigned to an assembly, and be
+/// overridden for individual parts.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct Material {
+    /// The shape that is made of the material
+    pub shape: Shape3d,
+
+    /// The name of the material
+    pub name: String,
+}
+
+impl From<Material> for Shape {
+    fn from(shape: Material) -> Self {
+        Self::Shape3d(Shape3d::Material(Box::new(shape)))
+    }
+}
+
+impl From<Material> for Shape3d {
+    fn from(shape: Material) -> Self {
+        Self::Material(Box::new(shape))
+    }
+}
+
+/// A 3-dimensional shape with a texture on some of its faces
+///
+/// A texture is a repeating pattern of shallow grooves, like the knurling or
+/// the ribs on a grip. Such features are too small and too many to be modeled
+/// as shapes of their own, so the grooves are cut into the surface of the
+/// shape instead.
+///
+/// # Limitations
+///
+/// Like a [`Deform`], the texture is applied to a triangle mesh that
+/// approximates the shape. The whole mesh is subdivided, until its triangles
+/// are small enough to follow the pattern, so textured shapes consist of many
+/// triangles. The texture fades out towards the edges of the textured faces,
+/// where they meet faces that aren't textured.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct Texture {
+    /// The shape that the texture is applied to
+    pub shape: Shape3d,
+
+    /// The pattern of the texture
+    pub pattern: TexturePattern,
+
+    /// The faces that the texture is applied to
+    pub faces: TextureFaces,
+
+    /// The distance between neighboring grooves
+    pub pitch: f64,
+
+    /// How deep the grooves are cut into the surface
+    ///
+    /// Negative values raise the pattern above the surface instead.
+    pub depth: f64,
+}
+
+impl From<Texture> for Shape {
+    fn from(shape: Texture) -> Self {
+        Self::Shape3d(Shape3d::Texture(Box::new(shape)))
+    }
+}
+
+impl From<Texture> for Shape3d {
+    fn from(shape: Texture) -> Self {
+        Self::Texture(Box::new(shape))
+    }
+}
+
+/// The pattern of a [`Texture`]
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub enum TexturePattern {
+    /// Straight grooves with a V-shaped profile
+    ///
+    /// On the sides of a shape, the grooves are parallel to the z-axis. On its
+    /// top, they are parallel to the y-axis.
+    Ribs,
+
+    /// Two sets of grooves that cross each other diagonally
+    ///
+    /// This leaves a pattern of small pyramids, like a diamond knurl.
+    Knurl,
+}
+
+/// The faces of a shape that a [`Texture`] is applied to
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub enum TextureFaces {
+    /// The faces around the z-axis, like the sides of a swept shape
+    ///
+    /// These are the faces that are parallel to the z-axis. The pattern runs
+    /// around the z-axis. Its pitch is adjusted, so that a whole number of
+    /// grooves fits around the shape, at the average distance of the faces
+    /// from the axis.
+    Sides,
+
+    /// The faces that face towards positive z, like the top of a swept shape
+    Top,
+
+    /// The faces that face in the given direction
+    ///
+    /// This selects any set of planar faces, like the bottom or one side of a
+    /// box. The ribs of [`TexturePattern::Ribs`] run along the direction that
+    /// is closest to the y-axis in the plane of the faces, or along the z-axis,
+    /// if the faces face along the y-axis. `Facing([0., 0., 1.])` is the same
+    /// as [`TextureFaces::Top`].
+    Facing([f64; 3]),
+}
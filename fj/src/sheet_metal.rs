@@ -0,0 +1,252 @@
+//! Sheet metal parts
+//!
+//! A sheet metal part is made from a flat sheet of uniform thickness, which is
+//! cut to shape and then bent along straight lines. This module describes such
+//! a part in terms of its bends, and computes the flat pattern that needs to be
+//! cut from the sheet, before bending it.
+//!
+//! The flat pattern is a regular [`Sketch`], so it can be used like any other
+//! 2-dimensional shape. For example, it can be exported to SVG for laser
+//! cutting, or used to generate toolpaths for a CNC mill.
+
+use std::f64::consts::PI;
+
+use crate::Sketch;
+
+/// A sheet metal part
+///
+/// The part consists of a flat base face, and flanges that are bent up or down
+/// along the edges of the base face.
+#[derive(Clone, Debug)]
+pub struct SheetMetal {
+    /// The outline of the base face, in the x-y plane
+    ///
+    /// The edges of the base face are where the bends start. Edge `i` goes from
+    /// point `i` to point `i + 1` (and the last edge back to the first point).
+    pub base: Vec<[f64; 2]>,
+
+    /// The thickness of the sheet
+    pub thickness: f64,
+
+    /// The position of the neutral axis, as a fraction of the thickness
+    ///
+    /// When the sheet is bent, the material on the inside of the bend is
+    /// compressed, and the material on the outside is stretched. The neutral
+    /// axis is where the material keeps its length. Its position depends on
+    /// the material and the bending process, and is usually between `0.3` and
+    /// `0.5`.
+    pub k_factor: f64,
+
+    /// The flanges that are bent along the edges of the base face
+    pub flanges: Vec<Flange>,
+}
+
+impl SheetMetal {
+    /// Create a sheet metal part without any flanges
+    ///
+    /// The K-factor is initialized to `0.44`, which is a common value for air
+    /// bending of steel.
+    pub fn new(base: Vec<[f64; 2]>, thickness: f64) -> Self {
+        Self {
+            base,
+            thickness,
+            k_factor: 0.44,
+            flanges: Vec::new(),
+        }
+    }
+
+    /// Add a flange to the part
+    pub fn with_flange(mut self, flange: Flange) -> Self {
+        self.flanges.push(flange);
+        self
+    }
+
+    /// Compute the flat pattern of the part
+    ///
+    /// Each flange extends the base face outward from its edge, by the bend
+    /// allowance of its bend (see [`Flange::bend_allowance`]), plus its
+    /// length.
+    ///
+    /// Flanges on adjacent edges are not trimmed against each other, so if
+    /// both are bent, their corner needs to be relieved by the model.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if a flange refers to an edge that doesn't exist, or if there
+    /// is more than one flange on the same edge.
+    pub fn unfold(&self) -> Sketch {
+        let widths = self.flange_widths();
+        let outward = self.outward();
+
+        let mut points = Vec::new();
+        for (i, &a) in self.base.iter().enumerate() {
+            points.push(a);
+
+            if let Some(width) = widths[i] {
+                let b = self.base[(i + 1) % self.base.len()];
+                let [nx, ny] = outward(a, b);
+
+                points.push([a[0] + nx * width, a[1] + ny * width]);
+                points.push([b[0] + nx * width, b[1] + ny * width]);
+            }
+        }
+
+        Sketch::from_points(points)
+    }
+
+    /// Compute the bend lines of the flat pattern
+    ///
+    /// Returns two lines per flange, which mark where the bend starts and ends.
+    /// The returned lines are in the same order as [`SheetMetal::flanges`].
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`SheetMetal::unfold`].
+    pub fn bend_lines(&self) -> Vec<[[[f64; 2]; 2]; 2]> {
+        // Validate the flanges, even though we don't need their widths.
+        self.flange_widths();
+        let outward = self.outward();
+
+        self.flanges
+            .iter()
+            .map(|flange| {
+                let a = self.base[flange.edge];
+                let b = self.base[(flange.edge + 1) % self.base.len()];
+                let [nx, ny] = outward(a, b);
+
+                let offset = |[x, y]: [f64; 2], distance: f64| {
+                    [x + nx * distance, y + ny * distance]
+                };
+                let allowance = flange.bend_allowance(self);
+
+                [[a, b], [offset(a, allowance), offset(b, allowance)]]
+            })
+            .collect()
+    }
+
+    /// The width of the flange on each edge, in the flat pattern
+    fn flange_widths(&self) -> Vec<Option<f64>> {
+        let mut widths = vec![None; self.base.len()];
+
+        for flange in &self.flanges {
+            let width = widths
+                .get_mut(flange.edge)
+                .expect("Flange refers to edge that doesn't exist");
+            assert!(width.is_none(), "More than one flange on the same edge");
+
+            *width = Some(flange.bend_allowance(self) + flange.length);
+        }
+
+        widths
+    }
+
+    /// Returns a function that computes the outward normal of an edge
+    fn outward(&self) -> impl Fn([f64; 2], [f64; 2]) -> [f64; 2] {
+        let mut area = 0.;
+        for (i, [ax, ay]) in self.base.iter().enumerate() {
+            let [bx, by] = self.base[(i + 1) % self.base.len()];
+            area += ax * by - bx * ay;
+        }
+
+        // Outward is to the right of a counter-clockwise edge.
+        let sign = if area >= 0. { 1. } else { -1. };
+
+        move |[ax, ay], [bx, by]| {
+            let [dx, dy] = [bx - ax, by - ay];
+            let length = (dx * dx + dy * dy).sqrt();
+            [sign * dy / length, -sign * dx / length]
+        }
+    }
+}
+
+/// A flange of a sheet metal part
+///
+/// A flange is bent along a whole edge of the base face. It consists of the
+/// bend itself, and a flat section after the bend.
+#[derive(Clone, Debug)]
+pub struct Flange {
+    /// The index of the edge of the base face that the flange is bent along
+    pub edge: usize,
+
+    /// The angle of the bend, in radians
+    ///
+    /// Positive angles bend the flange up, negative angles bend it down. An
+    /// angle of `0` leaves the flange flat.
+    pub angle: f64,
+
+    /// The radius of the inside of the bend
+    pub radius: f64,
+
+    /// The length of the flat section of the flange, after the bend
+    pub length: f64,
+}
+
+impl Flange {
+    /// Create a flange with a 90° bend upwards
+    pub fn new(edge: usize, radius: f64, length: f64) -> Self {
+        Self {
+            edge,
+            angle: PI / 2.,
+            radius,
+            length,
+        }
+    }
+
+    /// Compute the bend allowance of the flange
+    ///
+    /// This is the length of the neutral axis within the bend, which is the
+    /// length that the bend takes up in the flat pattern.
+    pub fn bend_allowance(&self, part: &SheetMetal) -> f64 {
+        self.angle.abs() * (self.radius + part.k_factor * part.thickness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::{Flange, SheetMetal};
+
+    #[test]
+    fn unfold_should_add_bend_allowance_and_flange_length() {
+        let part = SheetMetal {
+            base: vec![[0., 0.], [10., 0.], [10., 5.], [0., 5.]],
+            thickness: 1.,
+            k_factor: 0.5,
+            flanges: vec![Flange::new(1, 1.5, 3.)],
+        };
+
+        // The bend allowance is `π/2 * (1.5 + 0.5 * 1)`, which is `π`.
+        let width = 10. + PI + 3.;
+        assert_eq!(
+            part.unfold().to_points(),
+            [
+                [0., 0.],
+                [10., 0.],
+                [width, 0.],
+                [width, 5.],
+                [10., 5.],
+                [0., 5.],
+            ]
+        );
+
+        let [start, end] = part.bend_lines()[0];
+        assert_eq!(start, [[10., 0.], [10., 5.]]);
+        assert_eq!(end, [[10. + PI, 0.], [10. + PI, 5.]]);
+    }
+
+    #[test]
+    fn unfold_should_handle_clockwise_base_and_bends_down() {
+        let part = SheetMetal::new(vec![[0., 0.], [0., 5.], [10., 5.]], 1.)
+            .with_flange(Flange {
+                angle: -PI / 4.,
+                ..Flange::new(0, 1., 2.)
+            });
+
+        // The first edge is the left one, so the flange extends to the left.
+        let points = part.unfold().to_points();
+        let width = PI / 4. * 1.44 + 2.;
+        assert_eq!(points[1], [-width, 0.]);
+        assert_eq!(points[2], [-width, 5.]);
+    }
+}
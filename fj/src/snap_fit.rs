@@ -0,0 +1,319 @@
+//! Snap-fit features
+//!
+//! Snap-fits join two parts without fasteners. A flexible feature on one part
+//! deflects while the parts are pushed together, and snaps back behind an edge
+//! of the other part, once they are in place. This module generates the most
+//! common kinds:
+//!
+//! - [`Cantilever`]: A beam with a hook at its end, which catches behind the
+//!   edge of a window in the mating part.
+//! - [`Annular`]: A bead around a shaft, which snaps into a groove or behind
+//!   the edge of a bore in the mating part.
+//!
+//! Features are built in a local coordinate system, and placed on a face of a
+//! part using a [`Placement`]. The mating geometry, like the window that a
+//! hook catches behind, is provided as a [`Sketch`], which the mating part can
+//! subtract from its outline.
+
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_6, TAU};
+
+use crate::{
+    placement::sweep_side_profile, Deform, Deformation, Placement, Shape3d,
+    Sketch, Sweep, Transform,
+};
+
+/// A cantilever snap-fit
+///
+/// In its local coordinate system, the beam stands on the x-y plane, and
+/// extends along the z-axis. Its root is centered on the y-axis, and the hook
+/// at its tip points towards positive x, which is the direction the beam
+/// deflects away from, when the parts are pushed together.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cantilever {
+    /// The length of the beam, from the face it stands on to the hook
+    pub length: f64,
+
+    /// The thickness of the beam, in the direction of deflection
+    pub thickness: f64,
+
+    /// The width of the beam
+    pub width: f64,
+
+    /// How far the hook reaches behind the edge of the mating part
+    ///
+    /// This is also how far the beam deflects, when the parts are pushed
+    /// together.
+    pub engagement: f64,
+
+    /// The angle between the insertion face of the hook and the beam, in
+    /// radians
+    ///
+    /// Smaller angles make the parts easier to push together.
+    pub insertion_angle: f64,
+
+    /// The gap between the hook and the window in the mating part
+    pub clearance: f64,
+}
+
+impl Cantilever {
+    /// Create a cantilever snap-fit
+    ///
+    /// The insertion angle is initialized to 30°, and the clearance to `0.2`.
+    pub fn new(
+        length: f64,
+        thickness: f64,
+        width: f64,
+        engagement: f64,
+    ) -> Self {
+        Self {
+            length,
+            thickness,
+            width,
+            engagement,
+            insertion_angle: FRAC_PI_6,
+            clearance: 0.2,
+        }
+    }
+
+    /// The length of the hook, along the beam
+    pub fn hook_length(&self) -> f64 {
+        self.engagement / self.insertion_angle.tan()
+    }
+
+    /// Compute the strain at the root of the beam, when it is fully deflected
+    ///
+    /// This uses the common approximation for a beam with a constant,
+    /// rectangular cross-section. If the result is bigger than the permissible
+    /// strain of the material, the beam breaks, and needs to be longer or
+    /// thinner.
+    pub fn strain(&self) -> f64 {
+        1.5 * self.thickness * self.engagement / (self.length * self.length)
+    }
+
+    /// Create the shape of the snap-fit, in its local coordinate system
+    pub fn shape(&self) -> Shape3d {
+        let [t, e, l, h] = [
+            self.thickness,
+            self.engagement,
+            self.length,
+            self.hook_length(),
+        ];
+
+        // The side profile of the beam and hook. Its x-axis is the local
+        // x-axis, its y-axis is the local z-axis.
+        let profile = Sketch::from_points(vec![
+            [0., 0.],
+            [t, 0.],
+            [t, l],
+            [t + e, l],
+            [t, l + h],
+            [0., l + h],
+        ]);
+
+        sweep_side_profile(profile, self.width).into()
+    }
+
+    /// Create the snap-fit, and move it to the placement
+    pub fn place(&self, placement: &Placement) -> Transform {
+        placement.place(self.shape())
+    }
+
+    /// Create the window that the hook catches behind
+    ///
+    /// The window is in the local x-y plane, as seen when looking down the
+    /// beam. It is big enough for the hook to pass through, plus the
+    /// clearance on all sides.
+    pub fn window(&self) -> Sketch {
+        let c = self.clearance;
+        let [min_x, max_x] = [-c, self.thickness + self.engagement + c];
+        let [min_y, max_y] = [-self.width / 2. - c, self.width / 2. + c];
+
+        Sketch::from_points(vec![
+            [min_x, min_y],
+            [max_x, min_y],
+            [max_x, max_y],
+            [min_x, max_y],
+        ])
+    }
+}
+
+/// An annular snap-fit
+///
+/// A bead that goes all the way around a shaft. In its local coordinate
+/// system, the shaft is the z-axis, and the bead starts at the x-y plane. The
+/// insertion face comes first, so the mating part is pushed on from negative
+/// z.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Annular {
+    /// The radius of the shaft that the bead goes around
+    pub radius: f64,
+
+    /// How far the bead sticks out of the shaft
+    pub engagement: f64,
+
+    /// The angle between the insertion face of the bead and the shaft, in
+    /// radians
+    pub insertion_angle: f64,
+
+    /// The angle between the retention face of the bead and the shaft, in
+    /// radians
+    ///
+    /// At 90°, the default, the joint can't be separated again without
+    /// breaking it. Smaller angles make it possible to pull the parts apart.
+    pub retention_angle: f64,
+
+    /// Put the bead on the inside of a bore, instead of the outside of a shaft
+    pub internal: bool,
+
+    /// The radial gap between the shaft and the mating part
+    pub clearance: f64,
+}
+
+impl Annular {
+    /// Create an annular snap-fit on the outside of a shaft
+    ///
+    /// The insertion angle is initialized to 30°, the retention angle to 90°,
+    /// and the clearance to `0.2`.
+    pub fn new(radius: f64, engagement: f64) -> Self {
+        Self {
+            radius,
+            engagement,
+            insertion_angle: FRAC_PI_6,
+            retention_angle: FRAC_PI_2,
+            internal: false,
+            clearance: 0.2,
+        }
+    }
+
+    /// The length of the bead, along the shaft
+    pub fn bead_length(&self) -> f64 {
+        let [insertion, retention] = self.runs();
+        insertion + retention
+    }
+
+    /// The radius of the mating part, where it touches the shaft
+    ///
+    /// For a bead on a shaft, this is the radius of the bore that the shaft
+    /// goes into. For a bead in a bore, it's the radius of the shaft that goes
+    /// into the bore.
+    pub fn mating_radius(&self) -> f64 {
+        if self.internal {
+            self.radius - self.clearance
+        } else {
+            self.radius + self.clearance
+        }
+    }
+
+    /// How far the parts deform, while the bead passes the mating part
+    pub fn interference(&self) -> f64 {
+        self.engagement - self.clearance
+    }
+
+    /// Create the shape of the bead, in its local coordinate system
+    pub fn shape(&self) -> Shape3d {
+        let [insertion, retention] = self.runs();
+        let e = self.engagement;
+
+        // The cross-section of the bead. Its x-axis is the local z-axis, its
+        // y-axis points away from the shaft.
+        let profile = if self.internal {
+            vec![[0., 0.], [insertion, -e], [insertion + retention, 0.]]
+        } else {
+            vec![[0., 0.], [insertion + retention, 0.], [insertion, e]]
+        };
+
+        // The bead is swept to the length of the circumference, then wrapped
+        // around the shaft. Wrapping expects the circumference along the
+        // x-axis, the axis of the shaft along the y-axis, and the radial
+        // direction along the z-axis, so the sweep is rotated accordingly.
+        let bead = Transform {
+            shape: Sweep {
+                shape: Sketch::from_points(profile).into(),
+                length: TAU * self.radius,
+            }
+            .into(),
+            axis: [1., 1., 1.],
+            angle: TAU / 3.,
+            offset: [0., 0., 0.],
+        };
+
+        Deform {
+            shape: bead.into(),
+            deformation: Deformation::Wrap {
+                radius: self.radius,
+            },
+        }
+        .into()
+    }
+
+    /// Create the snap-fit, and move it to the placement
+    pub fn place(&self, placement: &Placement) -> Transform {
+        placement.place(self.shape())
+    }
+
+    /// The lengths of the insertion and retention faces, along the shaft
+    fn runs(&self) -> [f64; 2] {
+        [self.insertion_angle, self.retention_angle]
+            .map(|angle| self.engagement / angle.tan())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_4;
+
+    use crate::{Shape3d, Transform};
+
+    use super::{Annular, Cantilever};
+
+    #[test]
+    fn cantilever_should_have_hook_at_tip() {
+        let mut cantilever = Cantilever::new(10., 1., 4., 0.5);
+        cantilever.insertion_angle = FRAC_PI_4;
+
+        assert!((cantilever.hook_length() - 0.5).abs() < 1e-12);
+        assert_eq!(cantilever.strain(), 1.5 * 1. * 0.5 / 100.);
+
+        let profile = match cantilever.shape() {
+            Shape3d::Transform(transform) => match transform.shape {
+                Shape3d::Sweep(sweep) => sweep,
+                shape => panic!("Expected sweep, got {:?}", shape),
+            },
+            shape => panic!("Expected transform, got {:?}", shape),
+        };
+        assert_eq!(profile.length, 4.);
+
+        let window = cantilever.window().to_points();
+        assert_eq!(window[0], [-0.2, -2.2]);
+        assert_eq!(window[2], [1.7, 2.2]);
+
+        assert!(crate::Shape::from(cantilever.shape()).check().is_ok());
+    }
+
+    #[test]
+    fn annular_should_wrap_bead_around_shaft() {
+        let annular = Annular::new(5., 0.5);
+
+        // The retention face is perpendicular to the shaft.
+        assert!((annular.bead_length() - 0.5 * 3f64.sqrt()).abs() < 1e-12);
+        assert_eq!(annular.mating_radius(), 5.2);
+        assert!((annular.interference() - 0.3).abs() < 1e-12);
+
+        let internal = Annular {
+            internal: true,
+            ..annular.clone()
+        };
+        assert_eq!(internal.mating_radius(), 4.8);
+
+        for annular in [annular, internal] {
+            let shape = annular.place(&crate::Placement::new(
+                [0., 0., 1.],
+                [1., 0., 0.],
+                [0., 0., 1.],
+            ));
+            let Transform { shape, .. } = shape;
+            assert!(matches!(shape, Shape3d::Deform(_)));
+            assert!(crate::Shape::from(shape).check().is_ok());
+        }
+    }
+}
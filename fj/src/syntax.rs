@@ -1,3 +1,81 @@
+pub trait Anchor {
+    /// Define an anchor
+    ///
+    /// Define an anchor named `name` on `shape`, whose frame has the given
+    /// origin and axes. See [`crate::Anchor`].
+    fn anchor(
+        &self,
+        name: impl Into<String>,
+        origin: [f64; 3],
+        x_axis: [f64; 3],
+        z_axis: [f64; 3],
+    ) -> crate::Anchor;
+}
+
+impl<T> Anchor for T
+where
+    T: Clone + Into<crate::Shape3d>,
+{
+    fn anchor(
+        &self,
+        name: impl Into<String>,
+        origin: [f64; 3],
+        x_axis: [f64; 3],
+        z_axis: [f64; 3],
+    ) -> crate::Anchor {
+        let shape = self.clone().into();
+        crate::Anchor {
+            shape,
+            name: name.into(),
+            origin,
+            x_axis,
+            z_axis,
+        }
+    }
+}
+
+pub trait Bend {
+    /// Create a bend
+    ///
+    /// Create a deformation that bends `shape` around an axis parallel to the
+    /// y-axis. See [`crate::Deformation::Bend`].
+    fn bend(&self, radius: f64) -> crate::Deform;
+}
+
+impl<T> Bend for T
+where
+    T: Clone + Into<crate::Shape3d>,
+{
+    fn bend(&self, radius: f64) -> crate::Deform {
+        let shape = self.clone().into();
+        crate::Deform {
+            shape,
+            deformation: crate::Deformation::Bend { radius },
+        }
+    }
+}
+
+pub trait Material {
+    /// Assign a material
+    ///
+    /// Define that `shape` is made of the material named `name`. See
+    /// [`crate::Material`].
+    fn material(&self, name: impl Into<String>) -> crate::Material;
+}
+
+impl<T> Material for T
+where
+    T: Clone + Into<crate::Shape3d>,
+{
+    fn material(&self, name: impl Into<String>) -> crate::Material {
+        let shape = self.clone().into();
+        crate::Material {
+            shape,
+            name: name.into(),
+        }
+    }
+}
+
 pub trait Rotate {
     /// Create a rotation
     ///
@@ -48,6 +126,63 @@ where
     }
 }
 
+pub trait Texture {
+    /// Apply a texture
+    ///
+    /// Cut a repeating pattern of grooves, `pitch` apart and `depth` deep,
+    /// into the given faces of `shape`. See [`crate::Texture`].
+    fn texture(
+        &self,
+        pattern: crate::TexturePattern,
+        faces: crate::TextureFaces,
+        pitch: f64,
+        depth: f64,
+    ) -> crate::Texture;
+}
+
+impl<T> Texture for T
+where
+    T: Clone + Into<crate::Shape3d>,
+{
+    fn texture(
+        &self,
+        pattern: crate::TexturePattern,
+        faces: crate::TextureFaces,
+        pitch: f64,
+        depth: f64,
+    ) -> crate::Texture {
+        let shape = self.clone().into();
+        crate::Texture {
+            shape,
+            pattern,
+            faces,
+            pitch,
+            depth,
+        }
+    }
+}
+
+pub trait Twist {
+    /// Create a twist
+    ///
+    /// Create a deformation that twists `shape` around the z-axis, by `rate`
+    /// radians per unit of height. See [`crate::Deformation::Twist`].
+    fn twist(&self, rate: f64) -> crate::Deform;
+}
+
+impl<T> Twist for T
+where
+    T: Clone + Into<crate::Shape3d>,
+{
+    fn twist(&self, rate: f64) -> crate::Deform {
+        let shape = self.clone().into();
+        crate::Deform {
+            shape,
+            deformation: crate::Deformation::Twist { rate },
+        }
+    }
+}
+
 pub trait Translate {
     /// Create a translation
     ///
@@ -90,3 +225,24 @@ where
         crate::Union { a, b }
     }
 }
+
+pub trait Wrap {
+    /// Create a wrap
+    ///
+    /// Create a deformation that wraps `shape` around a cylinder with the given
+    /// radius, whose axis is the z-axis. See [`crate::Deformation::Wrap`].
+    fn wrap(&self, radius: f64) -> crate::Deform;
+}
+
+impl<T> Wrap for T
+where
+    T: Clone + Into<crate::Shape3d>,
+{
+    fn wrap(&self, radius: f64) -> crate::Deform {
+        let shape = self.clone().into();
+        crate::Deform {
+            shape,
+            deformation: crate::Deformation::Wrap { radius },
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Metric screw threads
+//!
+//! A [`Thread`] is a threaded rod (an external thread, like that of a screw)
+//! or a threaded sleeve (an internal thread, like that of a nut), with the
+//! basic profile of ISO metric threads. Its dimensions can be derived from one
+//! of the [`ThreadSize`] presets, which use the coarse pitch of each size.
+//!
+//! Printed threads need some clearance to fit, which is why external threads
+//! are made a bit thinner, and internal threads a bit wider, than the basic
+//! profile. How much depends on the printer, so the clearance can be adjusted.
+//!
+//! The helix is created by sweeping the cross-section of the thread, and then
+//! twisting it around the z-axis by one turn per pitch (see
+//! [`Deformation::Twist`]). This results in a right-hand thread. Like the
+//! other features, threads are built in a local coordinate system, standing on
+//! the x-y plane, centered on the z-axis, and placed using a [`Placement`].
+
+use std::f64::consts::TAU;
+
+use crate::{
+    Circle, Deform, Deformation, Difference2d, Placement, Shape2d, Shape3d,
+    Sketch, Sweep, Transform,
+};
+
+/// Common sizes of ISO metric threads
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ThreadSize {
+    /// M2, with a pitch of 0.4
+    M2,
+
+    /// M2.5, with a pitch of 0.45
+    M2_5,
+
+    /// M3, with a pitch of 0.5
+    M3,
+
+    /// M4, with a pitch of 0.7
+    M4,
+
+    /// M5, with a pitch of 0.8
+    M5,
+
+    /// M6, with a pitch of 1
+    M6,
+
+    /// M8, with a pitch of 1.25
+    M8,
+
+    /// M10, with a pitch of 1.5
+    M10,
+
+    /// M12, with a pitch of 1.75
+    M12,
+}
+
+impl ThreadSize {
+    /// The nominal diameter, which is the major diameter of the thread
+    pub fn diameter(&self) -> f64 {
+        match self {
+            Self::M2 => 2.,
+            Self::M2_5 => 2.5,
+            Self::M3 => 3.,
+            Self::M4 => 4.,
+            Self::M5 => 5.,
+            Self::M6 => 6.,
+            Self::M8 => 8.,
+            Self::M10 => 10.,
+            Self::M12 => 12.,
+        }
+    }
+
+    /// The coarse pitch, which is the distance between two turns of the thread
+    pub fn pitch(&self) -> f64 {
+        match self {
+            Self::M2 => 0.4,
+            Self::M2_5 => 0.45,
+            Self::M3 => 0.5,
+            Self::M4 => 0.7,
+            Self::M5 => 0.8,
+            Self::M6 => 1.,
+            Self::M8 => 1.25,
+            Self::M10 => 1.5,
+            Self::M12 => 1.75,
+        }
+    }
+}
+
+/// Whether a thread is on the outside or the inside of a part
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThreadKind {
+    /// A threaded rod, like the shank of a screw
+    External,
+
+    /// A threaded sleeve, like a nut
+    Internal {
+        /// The outer diameter of the sleeve
+        ///
+        /// Must be larger than the major diameter of the thread, plus the
+        /// clearance.
+        outer_diameter: f64,
+    },
+}
+
+/// A metric screw thread
+///
+/// In its local coordinate system, the thread stands on the x-y plane,
+/// centered on the z-axis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Thread {
+    /// The nominal diameter, which is the major diameter of the thread
+    pub diameter: f64,
+
+    /// The distance between two turns of the thread
+    pub pitch: f64,
+
+    /// The length of the thread
+    pub length: f64,
+
+    /// Whether the thread is a rod or a sleeve
+    pub kind: ThreadKind,
+
+    /// How much the radius of the thread deviates from the basic profile
+    ///
+    /// External threads are made thinner by this much, internal threads
+    /// wider, so a screw and a nut with the same clearance have twice that
+    /// much room between their flanks.
+    pub clearance: f64,
+
+    /// The number of points that the cross-section is approximated with
+    ///
+    /// Should be a multiple of 16, so the corners of the profile are
+    /// represented exactly.
+    pub segments: usize,
+}
+
+impl Thread {
+    /// The default clearance, which suits most printers
+    pub const DEFAULT_CLEARANCE: f64 = 0.15;
+
+    /// Create an external thread of the given size and length
+    ///
+    /// The clearance is initialized to [`Thread::DEFAULT_CLEARANCE`].
+    pub fn external(size: ThreadSize, length: f64) -> Self {
+        Self {
+            diameter: size.diameter(),
+            pitch: size.pitch(),
+            length,
+            kind: ThreadKind::External,
+            clearance: Self::DEFAULT_CLEARANCE,
+            segments: 64,
+        }
+    }
+
+    /// Create an internal thread of the given size and length
+    ///
+    /// The sleeve is made twice as wide as the thread, which is a common rule
+    /// of thumb for printed nuts. The clearance is initialized to
+    /// [`Thread::DEFAULT_CLEARANCE`].
+    pub fn internal(size: ThreadSize, length: f64) -> Self {
+        Self {
+            kind: ThreadKind::Internal {
+                outer_diameter: size.diameter() * 2.,
+            },
+            ..Self::external(size, length)
+        }
+    }
+
+    /// Change the clearance of the thread
+    pub fn with_clearance(mut self, clearance: f64) -> Self {
+        self.clearance = clearance;
+        self
+    }
+
+    /// The minor diameter of the basic profile
+    ///
+    /// This is where the roots of an external thread, and the crests of an
+    /// internal one, are.
+    pub fn minor_diameter(&self) -> f64 {
+        self.diameter - 2. * self.depth()
+    }
+
+    /// The pitch diameter of the basic profile
+    ///
+    /// This is where the flanks of the thread are as wide as the gaps between
+    /// them.
+    pub fn pitch_diameter(&self) -> f64 {
+        self.diameter - 0.75 * 3f64.sqrt() / 2. * self.pitch
+    }
+
+    /// The cross-section of the thread at `z = 0`
+    ///
+    /// The cross-section of an internal thread is the hole in the sleeve.
+    pub fn profile(&self) -> Sketch {
+        let offset = match self.kind {
+            ThreadKind::External => -self.clearance,
+            ThreadKind::Internal { .. } => self.clearance,
+        };
+        let major = self.diameter / 2. + offset;
+        let minor = major - self.depth();
+
+        let points = (0..self.segments)
+            .map(|i| {
+                let angle = TAU * i as f64 / self.segments as f64;
+
+                // Rotating the cross-section counter-clockwise moves the
+                // thread up. That means the profile has to run downwards, as
+                // the angle increases.
+                let s = -angle / TAU;
+                let radius = minor + (major - minor) * crest(s);
+
+                [radius * angle.cos(), radius * angle.sin()]
+            })
+            .collect();
+
+        Sketch::from_points(points)
+    }
+
+    /// Create the shape of the thread, in its local coordinate system
+    pub fn shape(&self) -> Shape3d {
+        let section: Shape2d = match self.kind {
+            ThreadKind::External => self.profile().into(),
+            ThreadKind::Internal { outer_diameter } => Difference2d {
+                a: Circle {
+                    center: [0., 0.],
+                    radius: outer_diameter / 2.,
+                }
+                .into(),
+                b: self.profile().into(),
+            }
+            .into(),
+        };
+
+        Deform {
+            shape: Sweep {
+                shape: section,
+                length: self.length,
+            }
+            .into(),
+            deformation: Deformation::Twist {
+                rate: TAU / self.pitch,
+            },
+        }
+        .into()
+    }
+
+    /// Create the thread, and move it to the placement
+    pub fn place(&self, placement: &Placement) -> Transform {
+        placement.place(self.shape())
+    }
+
+    /// The radial depth of the basic profile
+    fn depth(&self) -> f64 {
+        // The height of the fundamental triangle, whose sides are the flanks
+        // of the thread. The basic profile cuts off an eighth of it at the
+        // crest, and a quarter at the root.
+        let height = 3f64.sqrt() / 2. * self.pitch;
+        height * 5. / 8.
+    }
+}
+
+/// The basic profile, as a fraction of its depth, over one pitch
+///
+/// Returns `1` on the crest, which is an eighth of the pitch wide and centered
+/// on whole numbers, `0` on the root, which is a quarter of the pitch wide,
+/// and a straight flank in between.
+fn crest(s: f64) -> f64 {
+    let distance = (s - s.round()).abs();
+
+    let crest = 1. / 16.;
+    let root = 3. / 8.;
+
+    ((root - distance) / (root - crest)).clamp(0., 1.)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Shape, Shape3d};
+
+    use super::{crest, Thread, ThreadKind, ThreadSize};
+
+    #[test]
+    fn crest_should_follow_basic_profile() {
+        assert_eq!(crest(2.), 1.);
+        assert_eq!(crest(2. + 1. / 16.), 1.);
+        assert_eq!(crest(-0.5), 0.);
+        assert_eq!(crest(3. / 8.), 0.);
+        assert!((crest(7. / 32.) - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn thread_should_be_derived_from_size() {
+        let thread = Thread::external(ThreadSize::M3, 10.).with_clearance(0.);
+
+        assert_eq!(thread.diameter, 3.);
+        assert_eq!(thread.pitch, 0.5);
+        assert!((thread.minor_diameter() - 2.4587).abs() < 1e-4);
+        assert!((thread.pitch_diameter() - 2.6752).abs() < 1e-4);
+
+        let radii: Vec<_> = thread
+            .profile()
+            .to_points()
+            .into_iter()
+            .map(|[x, y]| x.hypot(y))
+            .collect();
+        let max = radii.iter().copied().fold(f64::MIN, f64::max);
+        let min = radii.iter().copied().fold(f64::MAX, f64::min);
+        assert!((max - thread.diameter / 2.).abs() < 1e-12);
+        assert!((min - thread.minor_diameter() / 2.).abs() < 1e-12);
+
+        assert!(matches!(thread.shape(), Shape3d::Deform(_)));
+        assert!(Shape::from(thread.shape()).check().is_ok());
+    }
+
+    #[test]
+    fn internal_thread_should_be_wider_by_clearance() {
+        let thread = Thread::internal(ThreadSize::M5, 4.).with_clearance(0.2);
+
+        assert_eq!(
+            thread.kind,
+            ThreadKind::Internal {
+                outer_diameter: 10.
+            }
+        );
+
+        let max = thread
+            .profile()
+            .to_points()
+            .into_iter()
+            .map(|[x, y]| x.hypot(y))
+            .fold(f64::MIN, f64::max);
+        assert!((max - 2.7).abs() < 1e-12);
+
+        assert!(Shape::from(thread.shape()).check().is_ok());
+    }
+}
@@ -1,9 +1,7 @@
-use std::collections::HashMap;
-
 use fj::prelude::*;
 
-#[no_mangle]
-pub extern "C" fn model(_: &HashMap<String, String>) -> fj::Shape {
+#[fj::model]
+pub fn csg_union_disjoint() -> fj::Shape {
     #[rustfmt::skip]
     let vertices = vec![
         [-0.5, -0.5],
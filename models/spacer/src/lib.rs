@@ -1,25 +1,17 @@
-use std::collections::HashMap;
-
-#[no_mangle]
-pub extern "C" fn model(args: &HashMap<String, String>) -> fj::Shape {
-    let outer = args
-        .get("outer")
-        .unwrap_or(&"1.0".to_owned())
-        .parse()
-        .unwrap();
-    let inner = args
-        .get("inner")
-        .unwrap_or(&"0.5".to_owned())
-        .parse()
-        .unwrap();
-    let height = args
-        .get("height")
-        .unwrap_or(&"1.0".to_owned())
-        .parse()
-        .unwrap();
-
-    let outer_edge = fj::Circle { radius: outer };
-    let inner_edge = fj::Circle { radius: inner };
+#[fj::model]
+pub fn spacer(
+    #[param(default = 1.0, min = 0.1, step = 0.1)] outer: f64,
+    #[param(default = 0.5, min = 0.05, step = 0.05)] inner: f64,
+    #[param(default = 1.0, min = 0.1, step = 0.1)] height: f64,
+) -> fj::Shape {
+    let outer_edge = fj::Circle {
+        center: [0., 0.],
+        radius: outer,
+    };
+    let inner_edge = fj::Circle {
+        center: [0., 0.],
+        radius: inner,
+    };
 
     let footprint = fj::Difference2d {
         a: outer_edge.into(),
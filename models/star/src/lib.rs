@@ -1,31 +1,19 @@
-use std::{collections::HashMap, f64::consts::PI};
+use std::f64::consts::PI;
 
-#[no_mangle]
-pub extern "C" fn model(args: &HashMap<String, String>) -> fj::Shape {
+#[fj::model]
+pub fn star(
     // Number of points of the star
     //
     // "Points" in the sense of "pointy ends", not in the sense of geometrical
     // points, or vertices.
-    let num_points: u64 = args
-        .get("num_points")
-        .map(|arg| arg.parse().unwrap())
-        .unwrap_or(5);
-
+    #[param(default = 5, min = 3)] num_points: u64,
     // Radius of the circle that all the vertices between the pointy ends are on
-    let r1: f64 = args
-        .get("r1")
-        .map(|arg| arg.parse().unwrap())
-        .unwrap_or(1.0);
-
+    #[param(default = 1.0)] r1: f64,
     // Radius of the circle that all the pointy ends are on
-    let r2: f64 = args
-        .get("r2")
-        .map(|arg| arg.parse().unwrap())
-        .unwrap_or(2.0);
-
+    #[param(default = 2.0)] r2: f64,
     // The height of the star
-    let h: f64 = args.get("h").map(|arg| arg.parse().unwrap()).unwrap_or(1.0);
-
+    #[param(default = 1.0)] h: f64,
+) -> fj::Shape {
     // We need to figure out where to generate vertices, depending on the number
     // of points the star is supposed to have. Let's generate an iterator that
     // gives us the angle and radius for each vertex.
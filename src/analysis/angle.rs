@@ -0,0 +1,400 @@
+use std::{collections::BTreeMap, ops::Range};
+
+use crate::{
+    math::{Point, Scalar, Triangle, Vector},
+    mesh::FaceGroups,
+};
+
+/// The angle a triangle may deviate from its face, before the face is no longer
+/// considered planar
+///
+/// Planar faces are triangulated exactly, so this only needs to account for
+/// floating point error. This is in radians.
+const MAX_PLANAR_DEVIATION: f64 = 1e-6;
+
+/// The distance a mesh edge may have from a straight edge, relative to the
+/// length of the straight edge, before it is no longer considered part of it
+const MAX_EDGE_DISTANCE: f64 = 1e-9;
+
+/// Measure the dihedral angle along a straight edge of a triangle mesh
+///
+/// The dihedral angle is the angle between the surfaces that meet at the edge,
+/// measured through the inside of the shape. Surfaces that meet at a convex
+/// edge, like the sides of a cube, have an angle of less than PI between them.
+/// Surfaces that meet at a concave edge have an angle of more than PI.
+///
+/// The edge doesn't have to be an edge between faces. The kernel represents
+/// all side walls of a sweep as a single face, for example, and this measures
+/// the angles between them. All edges of the mesh that lie on the given edge
+/// are considered, as long as they are shared by exactly two triangles.
+///
+/// The triangles must face outwards, as the inside of the shape is determined
+/// by them. Use [`super::manifold::orient`], if the orientation of the mesh is
+/// not known.
+///
+/// Returns an error, if no edge of the mesh lies on the given edge.
+pub fn along_edge(
+    triangles: &[Triangle<3>],
+    edge: [Point<3>; 2],
+) -> Result<DihedralAngle, Error> {
+    let mut edges: BTreeMap<[Point<3>; 2], Vec<usize>> = BTreeMap::new();
+    for (i, triangle) in triangles.iter().enumerate() {
+        for edge in directed_edges(triangle) {
+            edges.entry(undirected(edge)).or_default().push(i);
+        }
+    }
+
+    let [start, end] = edge;
+    let max_distance = (end - start).magnitude() * MAX_EDGE_DISTANCE;
+
+    let angles = edges
+        .into_iter()
+        .filter(|(mesh_edge, _)| {
+            mesh_edge
+                .iter()
+                .all(|&point| distance_to_edge(point, edge) <= max_distance)
+        })
+        .filter_map(|(mesh_edge, adjacent)| match adjacent[..] {
+            [i, j] => dihedral_angle(&triangles[i], &triangles[j], mesh_edge),
+            _ => None,
+        });
+
+    DihedralAngle::from_angles(angles).ok_or(Error::NoSuchEdge(start, end))
+}
+
+/// Measure the dihedral angle along the edge that two faces share
+///
+/// See [`along_edge`] for how the angle is defined. `faces` must refer to the
+/// triangles (see [`crate::evaluation::triangulate_by_face`]). The triangles of
+/// face `a` must face outwards, as the inside of the shape is determined by
+/// them. The orientation of face `b` doesn't matter.
+///
+/// The angle is measured between each pair of triangles that share an edge,
+/// one from each face. Returns an error, if either face doesn't exist, or if
+/// the faces don't share an edge.
+pub fn along_shared_edge(
+    triangles: &[Triangle<3>],
+    faces: &FaceGroups,
+    a: usize,
+    b: usize,
+) -> Result<DihedralAngle, Error> {
+    let triangles_a = face_triangles(faces, a)?;
+    let triangles_b = face_triangles(faces, b)?;
+
+    let mut edges_a = BTreeMap::new();
+    for i in triangles_a {
+        for edge in directed_edges(&triangles[i]) {
+            edges_a.insert(undirected(edge), i);
+        }
+    }
+
+    let angles = triangles_b.flat_map(|j| {
+        let edges_a = &edges_a;
+        directed_edges(&triangles[j])
+            .into_iter()
+            .filter_map(move |edge| {
+                let i = *edges_a.get(&undirected(edge))?;
+                dihedral_angle(&triangles[i], &triangles[j], edge)
+            })
+    });
+
+    DihedralAngle::from_angles(angles).ok_or(Error::NoSharedEdge(a, b))
+}
+
+/// Measure the angle between the planes of two planar faces
+///
+/// The faces don't need to share an edge. The angle is between zero and PI / 2,
+/// regardless of the orientation of the faces, which makes it suitable for
+/// checking draft angles. For faces that share an edge, use
+/// [`along_shared_edge`] to distinguish between their angle and its
+/// supplement.
+///
+/// `faces` must refer to the triangles (see
+/// [`crate::evaluation::triangulate_by_face`]). Returns an error, if either
+/// face doesn't exist, or if it is not planar.
+pub fn between_planar_faces(
+    triangles: &[Triangle<3>],
+    faces: &FaceGroups,
+    a: usize,
+    b: usize,
+) -> Result<Scalar, Error> {
+    let normal_a = plane_normal(triangles, faces, a)?;
+    let normal_b = plane_normal(triangles, faces, b)?;
+
+    let angle = normal_a.angle_to(&normal_b);
+    Ok(angle.min(Scalar::PI - angle))
+}
+
+/// The dihedral angle along an edge, as measured by [`along_edge`]
+///
+/// Curved faces are approximated by triangles, so the angle between them can
+/// vary along the edge. For planar faces, `min` and `max` are the same, up to
+/// floating point error.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DihedralAngle {
+    /// The smallest angle along the edge, in radians
+    pub min: Scalar,
+
+    /// The largest angle along the edge, in radians
+    pub max: Scalar,
+}
+
+impl DihedralAngle {
+    fn from_angles(angles: impl IntoIterator<Item = Scalar>) -> Option<Self> {
+        angles.into_iter().fold(None, |range, angle| {
+            let (min, max) = match range {
+                Some(Self { min, max }) => (min.min(angle), max.max(angle)),
+                None => (angle, angle),
+            };
+            Some(Self { min, max })
+        })
+    }
+}
+
+/// An error that can occur while measuring angles between faces
+#[derive(Clone, Copy, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum Error {
+    /// There's no face with this index
+    #[error("Face {0} does not exist")]
+    NoSuchFace(usize),
+
+    /// No edge of the mesh lies on the edge between the two points
+    #[error("Mesh has no edge between {0:?} and {1:?}")]
+    NoSuchEdge(Point<3>, Point<3>),
+
+    /// The faces don't share an edge
+    #[error("Faces {0} and {1} don't share an edge")]
+    NoSharedEdge(usize, usize),
+
+    /// The face is not planar
+    #[error("Face {0} is not planar")]
+    NotPlanar(usize),
+}
+
+fn face_triangles(
+    faces: &FaceGroups,
+    face: usize,
+) -> Result<Range<usize>, Error> {
+    if face >= faces.len() {
+        return Err(Error::NoSuchFace(face));
+    }
+
+    Ok(faces.triangles(face))
+}
+
+fn plane_normal(
+    triangles: &[Triangle<3>],
+    faces: &FaceGroups,
+    face: usize,
+) -> Result<Vector<3>, Error> {
+    let normals: Vec<_> = face_triangles(faces, face)?
+        .filter_map(|i| normal(&triangles[i]))
+        .collect();
+
+    // Can't be empty, unless the face has no area.
+    let first = *normals.first().ok_or(Error::NotPlanar(face))?;
+
+    // Orient all normals like the first one, so the orientation of the face's
+    // triangles doesn't matter.
+    let max_deviation = Scalar::from_f64(MAX_PLANAR_DEVIATION);
+    let mut sum = Vector::from([0., 0., 0.]);
+    for normal in normals {
+        let normal = if normal.dot(&first) < Scalar::ZERO {
+            -normal
+        } else {
+            normal
+        };
+
+        if normal.angle_to(&first) > max_deviation {
+            return Err(Error::NotPlanar(face));
+        }
+
+        sum = sum + normal;
+    }
+
+    Ok(sum.normalize())
+}
+
+/// Compute the dihedral angle between two triangles at their shared edge
+///
+/// The inside of the shape is behind `a`. Returns `None`, if either triangle
+/// has no area.
+fn dihedral_angle(
+    a: &Triangle<3>,
+    b: &Triangle<3>,
+    edge: [Point<3>; 2],
+) -> Option<Scalar> {
+    let normal_a = normal(a)?;
+    let mut normal_b = normal(b)?;
+
+    // Consistently oriented triangles traverse their shared edge in opposite
+    // directions. Don't rely on that, as meshes are not always consistent.
+    if directed_edges(a).contains(&edge) == directed_edges(b).contains(&edge) {
+        normal_b = -normal_b;
+    }
+
+    let bend = normal_a.angle_to(&normal_b);
+
+    // If `b` bends away from the outside of `a`, the edge is convex.
+    let [start, end] = edge;
+    let opposite = b.points().into_iter().find(|&p| p != start && p != end)?;
+    let angle = if (opposite - start).dot(&normal_a) <= Scalar::ZERO {
+        Scalar::PI - bend
+    } else {
+        Scalar::PI + bend
+    };
+
+    Some(angle)
+}
+
+fn distance_to_edge(point: Point<3>, [start, end]: [Point<3>; 2]) -> Scalar {
+    let [point, start, end] = [point, start, end].map(Point::to_na);
+
+    let direction = end - start;
+    let t = (point - start).dot(&direction) / direction.norm_squared();
+    let closest = start + direction * t.clamp(0., 1.);
+
+    Scalar::from_f64((point - closest).norm())
+}
+
+fn normal(triangle: &Triangle<3>) -> Option<Vector<3>> {
+    let [a, b, c] = triangle.points();
+    let normal = (b - a).cross(&(c - a));
+
+    if normal.magnitude() == Scalar::ZERO {
+        return None;
+    }
+
+    Some(normal.normalize())
+}
+
+fn directed_edges(triangle: &Triangle<3>) -> [[Point<3>; 2]; 3] {
+    let [a, b, c] = triangle.points();
+    [[a, b], [b, c], [c, a]]
+}
+
+fn undirected([a, b]: [Point<3>; 2]) -> [Point<3>; 2] {
+    if a < b {
+        [a, b]
+    } else {
+        [b, a]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    use approx::assert_abs_diff_eq;
+
+    use crate::{
+        analysis::manifold,
+        debug::DebugInfo,
+        evaluation::triangulate_by_face,
+        kernel::algorithms::tolerance::Tolerance,
+        math::{Point, Scalar, Triangle},
+        mesh::FaceGroups,
+    };
+
+    use super::{along_edge, along_shared_edge, between_planar_faces, Error};
+
+    #[test]
+    fn along_shared_edge_convex_and_concave() {
+        // Two triangles in the x-y plane, facing up, and one triangle that
+        // bends down from their shared edge along the x-axis.
+        let flat = Triangle::from([
+            Point::from([0., 0., 0.]),
+            Point::from([1., 0., 0.]),
+            Point::from([0., 1., 0.]),
+        ]);
+        let down = Triangle::from([
+            Point::from([1., 0., 0.]),
+            Point::from([0., 0., 0.]),
+            Point::from([0., -1., -1.]),
+        ]);
+        let up = Triangle::from([
+            Point::from([1., 0., 0.]),
+            Point::from([0., 0., 0.]),
+            Point::from([0., -1., 1.]),
+        ]);
+
+        let mut faces = FaceGroups::new();
+        faces.push(1);
+        faces.push(1);
+        faces.push(1);
+        let triangles = [flat, down, up];
+
+        let convex = along_shared_edge(&triangles, &faces, 0, 1).unwrap();
+        assert_abs_diff_eq!(convex.min, Scalar::from_f64(PI - FRAC_PI_4));
+        assert_abs_diff_eq!(convex.max, convex.min);
+
+        let concave = along_shared_edge(&triangles, &faces, 0, 2).unwrap();
+        assert_abs_diff_eq!(concave.min, Scalar::from_f64(PI + FRAC_PI_4));
+
+        assert_eq!(
+            along_shared_edge(&triangles, &faces, 0, 3),
+            Err(Error::NoSuchFace(3)),
+        );
+    }
+
+    #[test]
+    fn angles_of_chamfered_block() {
+        let sketch = fj::Sketch::from_points(vec![
+            [0., 0.],
+            [2., 0.],
+            [2., 1.],
+            [1., 2.],
+            [0., 2.],
+        ]);
+        let block = fj::Sweep {
+            shape: sketch.into(),
+            length: 1.,
+        };
+
+        let mut triangles = Vec::new();
+        let faces = triangulate_by_face(
+            &block.into(),
+            Tolerance::from_scalar(0.01).unwrap(),
+            &mut triangles,
+            &mut DebugInfo::new(),
+        )
+        .unwrap();
+        let triangles = manifold::orient(&triangles).unwrap();
+
+        // Bottom, top, and all side walls as a single face.
+        assert_eq!(faces.len(), 3);
+
+        let bottom_to_sides =
+            along_shared_edge(&triangles, &faces, 0, 2).unwrap();
+        assert_abs_diff_eq!(bottom_to_sides.min, Scalar::from_f64(FRAC_PI_2));
+        assert_abs_diff_eq!(bottom_to_sides.max, Scalar::from_f64(FRAC_PI_2));
+        assert_eq!(
+            along_shared_edge(&triangles, &faces, 0, 1),
+            Err(Error::NoSharedEdge(0, 1)),
+        );
+
+        let caps = between_planar_faces(&triangles, &faces, 0, 1).unwrap();
+        assert_abs_diff_eq!(caps, Scalar::ZERO, epsilon = 1e-9);
+        assert_eq!(
+            between_planar_faces(&triangles, &faces, 0, 2),
+            Err(Error::NotPlanar(2)),
+        );
+
+        let edge = |[x, y]: [f64; 2]| {
+            let edge = [Point::from([x, y, 0.]), Point::from([x, y, 1.])];
+            along_edge(&triangles, edge).unwrap()
+        };
+        let corner = edge([0., 0.]);
+        let chamfer = edge([2., 1.]);
+        assert_abs_diff_eq!(corner.min, Scalar::from_f64(FRAC_PI_2));
+        assert_abs_diff_eq!(chamfer.min, Scalar::from_f64(PI - FRAC_PI_4));
+        assert_abs_diff_eq!(chamfer.max, Scalar::from_f64(PI - FRAC_PI_4));
+
+        // Edges that don't lie on any edge of the mesh can't be measured.
+        let diagonal = [Point::from([0., 0., 0.]), Point::from([1., 1., 1.])];
+        assert!(matches!(
+            along_edge(&triangles, diagonal),
+            Err(Error::NoSuchEdge(..)),
+        ));
+    }
+}
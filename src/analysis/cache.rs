@@ -0,0 +1,146 @@
+use std::{
+    any::Any,
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash as _, Hasher as _},
+};
+
+use crate::math::Triangle;
+
+/// Caches the results of analyses, for as long as the geometry doesn't change
+///
+/// Hosts that let the user toggle analyses, or that re-evaluate a model when
+/// its source changes, can use this to avoid recomputing results. Whenever
+/// the triangles are updated, their [`MeshHash`] is compared to that of the
+/// previous triangles, and the cached results are only discarded, if the
+/// geometry actually changed.
+///
+/// ```
+/// use fj_host::analysis::{cache::AnalysisCache, stats::Stats};
+/// # use fj_host::math::{Point, Triangle};
+/// # let triangles = vec![Triangle::from([
+/// #     Point::from([0., 0., 0.]),
+/// #     Point::from([1., 0., 0.]),
+/// #     Point::from([0., 1., 0.]),
+/// # ])];
+///
+/// let mut cache = AnalysisCache::new();
+/// cache.update(&triangles);
+///
+/// let stats = cache.get_or_compute("stats", || Stats::compute(&triangles));
+/// # assert_eq!(stats.triangles, 1);
+/// ```
+#[derive(Default)]
+pub struct AnalysisCache {
+    mesh: Option<MeshHash>,
+    results: HashMap<&'static str, Box<dyn Any>>,
+}
+
+impl AnalysisCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the triangles that the cached results refer to
+    ///
+    /// Discards all cached results, if the triangles differ from the previous
+    /// ones. Returns whether that was the case.
+    pub fn update(&mut self, triangles: &[Triangle<3>]) -> bool {
+        let mesh = MeshHash::compute(triangles);
+
+        if self.mesh == Some(mesh) {
+            return false;
+        }
+
+        self.mesh = Some(mesh);
+        self.results.clear();
+
+        true
+    }
+
+    /// Access the result of an analysis, computing it, if it isn't cached
+    ///
+    /// `name` identifies the analysis. `compute` must compute the result from
+    /// the triangles that were last passed to [`AnalysisCache::update`].
+    ///
+    /// # Panics
+    ///
+    /// Panics, if a result of a different type was cached under the same
+    /// name.
+    pub fn get_or_compute<T: 'static>(
+        &mut self,
+        name: &'static str,
+        compute: impl FnOnce() -> T,
+    ) -> &T {
+        self.results
+            .entry(name)
+            .or_insert_with(|| Box::new(compute()))
+            .downcast_ref()
+            .unwrap_or_else(|| {
+                panic!("Analysis `{}` was cached with a different type", name)
+            })
+    }
+
+    /// Discard the cached result of an analysis
+    pub fn invalidate(&mut self, name: &'static str) {
+        self.results.remove(name);
+    }
+}
+
+/// A hash of the geometry of a triangle mesh
+///
+/// Meshes with the same triangles, in the same order, have the same hash.
+/// Since triangulation is deterministic, that is the case for shapes that
+/// haven't changed, even if they were evaluated again.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct MeshHash(u64);
+
+impl MeshHash {
+    /// Compute the hash of the given triangles
+    pub fn compute(triangles: &[Triangle<3>]) -> Self {
+        let mut hasher = DefaultHasher::new();
+        triangles.hash(&mut hasher);
+        Self(hasher.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use crate::math::{Point, Triangle};
+
+    use super::AnalysisCache;
+
+    #[test]
+    fn recompute_only_if_geometry_changes() {
+        let triangle = |z: f64| {
+            Triangle::from([
+                Point::from([0., 0., z]),
+                Point::from([1., 0., z]),
+                Point::from([0., 1., z]),
+            ])
+        };
+
+        let computations = Cell::new(0);
+        let analysis = || {
+            computations.set(computations.get() + 1);
+            computations.get()
+        };
+
+        let mut cache = AnalysisCache::new();
+        assert!(cache.update(&[triangle(0.)]));
+        assert_eq!(*cache.get_or_compute("count", analysis), 1);
+        assert_eq!(*cache.get_or_compute("count", analysis), 1);
+
+        // Identical geometry doesn't invalidate the cache.
+        assert!(!cache.update(&[triangle(0.)]));
+        assert_eq!(*cache.get_or_compute("count", analysis), 1);
+
+        assert!(cache.update(&[triangle(1.)]));
+        assert_eq!(*cache.get_or_compute("count", analysis), 2);
+
+        cache.invalidate("count");
+        assert_eq!(*cache.get_or_compute("count", analysis), 3);
+    }
+}
@@ -0,0 +1,195 @@
+use parry3d_f64::{
+    math::Isometry,
+    query::{self, ClosestPoints, PointQuery as _},
+};
+
+use crate::math::{Aabb, AabbTree, Point, Scalar, Triangle, Vector};
+
+/// Compute the minimum clearance between two triangle meshes
+///
+/// This is the smallest distance between any point on the surface of `a` and
+/// any point on the surface of `b`, which makes it possible to check the
+/// clearance between two parts of an assembly. The result is exact for the
+/// meshes, which deviate from the actual geometry of the parts by no more than
+/// the tolerance they were triangulated with.
+///
+/// The triangles of `b` are organized into an [`AabbTree`], and only those
+/// that could be closer than the closest pair found so far are checked, which
+/// keeps this fast for large meshes.
+///
+/// Returns `None`, if either mesh is empty.
+pub fn clearance(a: &[Triangle<3>], b: &[Triangle<3>]) -> Option<Clearance> {
+    // Any pair of vertices is an upper bound for the clearance.
+    let start = a.first()?.points()[0];
+    let end = b.first()?.points()[0];
+    let mut closest = Clearance {
+        distance: (end - start).magnitude(),
+        points: [start, end],
+    };
+
+    let mut tree = AabbTree::new();
+    for (i, triangle) in b.iter().enumerate() {
+        tree.insert(Aabb::<3>::from_points(triangle.points()), i);
+    }
+    let aabb_b = Aabb::<3>::from_points(b.iter().flat_map(|t| t.points()));
+
+    // Check the triangles of `a` that are closest to `b` first. They're the
+    // most likely to contain the closest points, and once those are found, the
+    // remaining triangles can be discarded early.
+    let mut triangles_a: Vec<_> = a
+        .iter()
+        .map(|triangle| {
+            let aabb = Aabb::<3>::from_points(triangle.points());
+            (distance(&aabb, &aabb_b), aabb, triangle)
+        })
+        .collect();
+    triangles_a.sort_by_key(|&(distance, _, _)| distance);
+
+    for (distance_to_b, aabb, triangle_a) in triangles_a {
+        if distance_to_b > closest.distance {
+            break;
+        }
+
+        let margin = Vector::from([closest.distance; 3]);
+        let search = Aabb {
+            min: aabb.min + -margin,
+            max: aabb.max + margin,
+        };
+
+        for &i in tree.query_aabb(&search) {
+            let candidate = between_triangles(triangle_a, &b[i]);
+            if candidate.distance < closest.distance {
+                closest = candidate;
+            }
+
+            if closest.distance == Scalar::ZERO {
+                return Some(closest);
+            }
+        }
+    }
+
+    Some(closest)
+}
+
+/// The minimum clearance between two triangle meshes
+///
+/// See [`clearance`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Clearance {
+    /// The distance between the meshes
+    ///
+    /// This is zero, if the meshes intersect.
+    pub distance: Scalar,
+
+    /// The closest points, on the first and the second mesh
+    ///
+    /// If the meshes intersect, these are points where they overlap.
+    pub points: [Point<3>; 2],
+}
+
+fn between_triangles(a: &Triangle<3>, b: &Triangle<3>) -> Clearance {
+    let identity = Isometry::identity();
+    let [a, b] = [a, b].map(|triangle| triangle.to_parry());
+
+    // Can't fail. Triangles are supported by all queries.
+    let points = query::closest_points(&identity, &a, &identity, &b, f64::MAX)
+        .expect("Triangle queries are supported");
+
+    match points {
+        ClosestPoints::WithinMargin(start, end) => {
+            let points = [start, end].map(Point::from);
+            let [start, end] = points;
+
+            Clearance {
+                distance: (end - start).magnitude(),
+                points,
+            }
+        }
+        ClosestPoints::Intersecting => {
+            let contact = query::contact(&identity, &a, &identity, &b, 0.)
+                .expect("Triangle queries are supported");
+
+            let point = match contact {
+                Some(contact) => contact.point1,
+
+                // Contacts can't be computed between triangles that are
+                // coplanar. Any vertex of `a` that's within `b` is a good
+                // enough substitute.
+                None => a
+                    .vertices()
+                    .iter()
+                    .copied()
+                    .min_by(|x, y| {
+                        b.distance_to_local_point(x, true)
+                            .partial_cmp(&b.distance_to_local_point(y, true))
+                            // Distances can't be NaN, unless the triangles
+                            // are.
+                            .unwrap()
+                    })
+                    // Can't panic. A triangle has 3 vertices.
+                    .unwrap(),
+            };
+
+            Clearance {
+                distance: Scalar::ZERO,
+                points: [Point::from(point); 2],
+            }
+        }
+        ClosestPoints::Disjoint => {
+            // Can't happen, as the maximum distance is unlimited.
+            unreachable!()
+        }
+    }
+}
+
+/// Compute the distance between two AABBs
+///
+/// This is a lower bound for the distance between anything they contain.
+fn distance(a: &Aabb<3>, b: &Aabb<3>) -> Scalar {
+    let [below, above] =
+        [a.min - b.max, b.min - a.max].map(|gap| gap.components());
+    let gap = [0, 1, 2].map(|i| below[i].max(above[i]).max(Scalar::ZERO));
+
+    Vector::from(gap).magnitude()
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::math::{Point, Scalar, Triangle};
+
+    use super::clearance;
+
+    #[test]
+    fn clearance_between_separate_and_intersecting_meshes() {
+        let triangle = |z: f64| {
+            Triangle::from([
+                Point::from([0., 0., z]),
+                Point::from([1., 0., z]),
+                Point::from([0., 1., z]),
+            ])
+        };
+
+        let a = [triangle(0.), triangle(-1.)];
+        let b = [triangle(3.), triangle(2.)];
+
+        let separate = clearance(&a, &b).unwrap();
+        assert_abs_diff_eq!(separate.distance, Scalar::from_f64(2.));
+        let [on_a, on_b] = separate.points;
+        assert_abs_diff_eq!(on_a.z, Scalar::ZERO);
+        assert_abs_diff_eq!(on_b.z, Scalar::from_f64(2.));
+
+        // A triangle that stands upright, piercing the first triangle of `a`.
+        let upright = [Triangle::from([
+            Point::from([0.2, 0.2, -0.5]),
+            Point::from([0.3, 0.2, 0.5]),
+            Point::from([0.2, 0.3, 0.5]),
+        ])];
+
+        let intersecting = clearance(&a, &upright).unwrap();
+        assert_eq!(intersecting.distance, Scalar::ZERO);
+
+        assert!(clearance(&a, &[]).is_none());
+    }
+}
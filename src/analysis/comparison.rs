@@ -0,0 +1,270 @@
+use std::collections::BTreeSet;
+
+use nalgebra::Point3;
+use parry3d_f64::{
+    query::{PointQuery as _, PointQueryWithLocation as _},
+    shape::TriMesh,
+};
+
+use crate::{
+    math::{Point, Scalar, Triangle},
+    mesh::IndexOverflow,
+};
+
+use super::deviation::{self, Deviation};
+
+/// A comparison of two triangle meshes
+///
+/// This can be used to check that two versions of a shape are the same, within
+/// some tolerance. For example, to make sure that a change to the tolerance
+/// value doesn't alter the geometry more than expected.
+pub struct Comparison {
+    /// The deviation of the vertices of the first mesh from the second
+    pub deviation_a: Deviation,
+
+    /// The deviation of the vertices of the second mesh from the first
+    pub deviation_b: Deviation,
+
+    /// The symmetric Hausdorff distance between both meshes
+    ///
+    /// See [`hausdorff_distance`].
+    pub hausdorff_distance: Scalar,
+}
+
+impl Comparison {
+    /// Compare two triangle meshes
+    ///
+    /// Neither `a` nor `b` may be empty. `tolerance` defines the accuracy of
+    /// the Hausdorff distance. Returns an error, if either mesh has too many
+    /// vertices.
+    pub fn compute(
+        a: &[Triangle<3>],
+        b: &[Triangle<3>],
+        tolerance: Scalar,
+    ) -> Result<Self, IndexOverflow> {
+        Ok(Self {
+            deviation_a: Deviation::compute(b, vertices(a))?,
+            deviation_b: Deviation::compute(a, vertices(b))?,
+            hausdorff_distance: hausdorff_distance(a, b, tolerance)?,
+        })
+    }
+
+    /// Indicate whether the meshes are identical, within `tolerance`
+    pub fn is_within(&self, tolerance: Scalar) -> bool {
+        self.hausdorff_distance <= tolerance
+    }
+}
+
+/// Compute the symmetric Hausdorff distance between two triangle meshes
+///
+/// This is the largest distance that any point on the surface of one of the
+/// meshes has from the surface of the other. It's zero, if the meshes cover
+/// the same surface.
+///
+/// The result is accurate to within `tolerance`, and never larger than the
+/// actual Hausdorff distance. Neither `a` nor `b` may be empty. Returns an
+/// error, if either mesh has too many vertices.
+pub fn hausdorff_distance(
+    a: &[Triangle<3>],
+    b: &[Triangle<3>],
+    tolerance: Scalar,
+) -> Result<Scalar, IndexOverflow> {
+    Ok(directed_hausdorff_distance(a, b, tolerance)?
+        .max(directed_hausdorff_distance(b, a, tolerance)?))
+}
+
+/// Compute the directed Hausdorff distance from one triangle mesh to another
+///
+/// This is the largest distance that any point on the surface of `from` has
+/// from the surface of `to`. Unlike the symmetric Hausdorff distance, this is
+/// zero, if `from` covers only part of the surface of `to`.
+///
+/// See [`hausdorff_distance`] for the accuracy of the result, and the errors.
+pub fn directed_hausdorff_distance(
+    from: &[Triangle<3>],
+    to: &[Triangle<3>],
+    tolerance: Scalar,
+) -> Result<Scalar, IndexOverflow> {
+    assert!(!from.is_empty(), "Can't compute distance from empty mesh");
+    assert!(!to.is_empty(), "Can't compute distance to empty mesh");
+    assert!(
+        tolerance > Scalar::ZERO,
+        "Tolerance must be larger than zero"
+    );
+
+    let to = deviation::tri_mesh(to)?;
+    let tolerance = tolerance.into_f64();
+
+    let mut triangles: Vec<_> = from
+        .iter()
+        .map(|triangle| triangle.points().map(Point::to_na))
+        .collect();
+
+    // The distance of the vertices is a lower bound for the result. Computing
+    // it upfront means the search below can discard more triangles early.
+    let mut max_distance = triangles
+        .iter()
+        .flatten()
+        .map(|vertex| closest(&to, vertex).0)
+        .fold(0., f64::max);
+
+    // The distance from a single triangle is a convex function. So if all
+    // vertices of a triangle are close to the same triangle of the other mesh,
+    // then all of its points are. Triangles for which that's not good enough
+    // are subdivided, until they are small enough to not matter.
+    while let Some(triangle) = triangles.pop() {
+        let [a, b, c] = triangle;
+
+        // Vertices are often shared by multiple triangles of the other mesh,
+        // so the triangle closest to the centroid is the best candidate, if
+        // both meshes are similar.
+        let centroid = Point3::from((a.coords + b.coords + c.coords) / 3.);
+        let closest = [a, b, c, centroid].map(|point| closest(&to, &point));
+
+        let lower_bound = closest
+            .iter()
+            .map(|&(distance, _)| distance)
+            .fold(0., f64::max);
+        max_distance = max_distance.max(lower_bound);
+
+        let upper_bound = closest
+            .iter()
+            .map(|&(_, index)| {
+                let candidate = to.triangle(index);
+                triangle
+                    .iter()
+                    .map(|vertex| {
+                        candidate.distance_to_local_point(vertex, false)
+                    })
+                    .fold(0., f64::max)
+            })
+            .fold(f64::INFINITY, f64::min);
+
+        if upper_bound <= max_distance + tolerance {
+            continue;
+        }
+
+        // Every point of a triangle is closer to one of its vertices than the
+        // length of its longest edge.
+        let longest_edge =
+            (b - a).norm().max((c - b).norm()).max((a - c).norm());
+        if longest_edge <= tolerance {
+            continue;
+        }
+
+        let [ab, bc, ca] = [
+            nalgebra::center(&a, &b),
+            nalgebra::center(&b, &c),
+            nalgebra::center(&c, &a),
+        ];
+        triangles.extend([[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]);
+    }
+
+    Ok(Scalar::from_f64(max_distance))
+}
+
+/// Find the triangles of one mesh that are not part of the surface of another
+///
+/// A triangle counts as part of the surface, if its vertices and its centroid
+/// are within `tolerance` of it. This decides for each triangle as a whole, so
+/// a large triangle that's only partially off the surface is returned, and a
+/// triangle whose interior is off the surface might not be.
+///
+/// `to` may not be empty. Returns an error, if it has too many vertices.
+pub fn triangles_off_surface(
+    from: &[Triangle<3>],
+    to: &[Triangle<3>],
+    tolerance: Scalar,
+) -> Result<Vec<Triangle<3>>, IndexOverflow> {
+    assert!(!to.is_empty(), "Can't compare with empty mesh");
+
+    let to = deviation::tri_mesh(to)?;
+    let tolerance = tolerance.into_f64();
+
+    let triangles = from
+        .iter()
+        .filter(|triangle| {
+            let [a, b, c] = triangle.points().map(Point::to_na);
+            let centroid = Point3::from((a.coords + b.coords + c.coords) / 3.);
+
+            [a, b, c, centroid]
+                .iter()
+                .any(|point| closest(&to, point).0 > tolerance)
+        })
+        .copied()
+        .collect();
+
+    Ok(triangles)
+}
+
+/// Find the distance of a point from a mesh, and the closest triangle
+fn closest(mesh: &TriMesh, point: &Point3<f64>) -> (f64, u32) {
+    let (projection, (index, _)) =
+        mesh.project_local_point_and_get_location(point, false);
+    ((projection.point - point).norm(), index)
+}
+
+fn vertices(triangles: &[Triangle<3>]) -> BTreeSet<Point<3>> {
+    triangles
+        .iter()
+        .flat_map(|triangle| triangle.points())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{Point, Scalar, Triangle};
+
+    use super::{directed_hausdorff_distance, hausdorff_distance, Comparison};
+
+    #[test]
+    fn hausdorff_distance_should_consider_points_between_vertices() {
+        // A square, and the same square with a tent-shaped bulge, whose ridge
+        // doesn't touch any of the square's vertices.
+        let [a, b, c, d] = [[0., 0.], [2., 0.], [2., 2.], [0., 2.]]
+            .map(|[x, y]| Point::from([x, y, 0.]));
+        let square = [Triangle::from([a, b, c]), Triangle::from([a, c, d])];
+
+        let [e, f] = [Point::from([1., 0., 1.]), Point::from([1., 2., 1.])];
+        let tent = [
+            Triangle::from([a, e, f]),
+            Triangle::from([a, f, d]),
+            Triangle::from([e, b, c]),
+            Triangle::from([e, c, f]),
+        ];
+
+        let tolerance = Scalar::from_f64(0.01);
+
+        // All vertices of the square are on the tent, but the middle of the
+        // square, right below the ridge, is not.
+        let comparison =
+            Comparison::compute(&square, &tent, tolerance).unwrap();
+        assert_eq!(comparison.deviation_a.max(), Scalar::ZERO);
+
+        let distance =
+            directed_hausdorff_distance(&square, &tent, tolerance).unwrap();
+        let distance = distance.into_f64();
+        assert!((distance - 0.5_f64.sqrt()).abs() <= 0.01);
+
+        // The ridge is the part of the tent that is farthest from the square.
+        let distance = hausdorff_distance(&square, &tent, tolerance).unwrap();
+        let distance = distance.into_f64();
+        assert!((distance - 1.).abs() <= 0.01);
+        assert!(comparison.is_within(Scalar::from_f64(1.01)));
+        assert!(!comparison.is_within(Scalar::from_f64(0.9)));
+    }
+
+    #[test]
+    fn hausdorff_distance_should_be_zero_for_identical_surfaces() {
+        // The same square, triangulated in two different ways.
+        let [a, b, c, d] = [[0., 0.], [2., 0.], [2., 2.], [0., 2.]]
+            .map(|[x, y]| Point::from([x, y, 0.]));
+        let first = [Triangle::from([a, b, c]), Triangle::from([a, c, d])];
+        let second = [Triangle::from([a, b, d]), Triangle::from([b, c, d])];
+
+        let distance =
+            hausdorff_distance(&first, &second, Scalar::from_f64(0.01))
+                .unwrap();
+        assert!(distance.into_f64() <= 1e-12);
+    }
+}
@@ -0,0 +1,196 @@
+use std::io;
+
+use parry3d_f64::{query::PointQuery as _, shape::TriMesh};
+
+use super::field::ScalarField;
+
+use crate::{
+    math::{Point, Scalar, Triangle},
+    mesh::{IndexOverflow, MeshMaker},
+};
+
+/// The deviation of measured points from a nominal shape
+///
+/// This can be used to compare a physical part, for example a 3D scan of a
+/// printed part, against the design it was made from.
+pub struct Deviation {
+    points: Vec<PointDeviation>,
+}
+
+impl Deviation {
+    /// Compute the deviation of `points` from `nominal`
+    ///
+    /// The deviation of a point is its distance from the closest point on the
+    /// surface of the nominal shape. It doesn't matter whether the point is
+    /// inside or outside of the nominal shape.
+    ///
+    /// `nominal` must not be empty. Returns an error, if it has too many
+    /// vertices.
+    pub fn compute(
+        nominal: &[Triangle<3>],
+        points: impl IntoIterator<Item = Point<3>>,
+    ) -> Result<Self, IndexOverflow> {
+        assert!(!nominal.is_empty(), "Nominal shape has no triangles");
+        let nominal = tri_mesh(nominal)?;
+
+        let points = points
+            .into_iter()
+            .map(|point| {
+                let distance =
+                    nominal.distance_to_local_point(&point.to_na(), false);
+
+                PointDeviation {
+                    point,
+                    distance: Scalar::from_f64(distance),
+                }
+            })
+            .collect();
+
+        Ok(Self { points })
+    }
+
+    /// The maximum deviation of any point
+    ///
+    /// Returns zero, if there are no points.
+    pub fn max(&self) -> Scalar {
+        self.points
+            .iter()
+            .map(|point| point.distance)
+            .max()
+            .unwrap_or(Scalar::ZERO)
+    }
+
+    /// The mean deviation of all points
+    ///
+    /// Returns zero, if there are no points.
+    pub fn mean(&self) -> Scalar {
+        if self.points.is_empty() {
+            return Scalar::ZERO;
+        }
+
+        let sum = self
+            .points
+            .iter()
+            .fold(Scalar::ZERO, |sum, point| sum + point.distance);
+
+        sum / self.points.len() as f64
+    }
+
+    /// The percentage of points whose deviation is within `tolerance`
+    ///
+    /// Returns 100%, if there are no points.
+    pub fn percent_within(&self, tolerance: Scalar) -> Scalar {
+        if self.points.is_empty() {
+            return Scalar::from_f64(100.);
+        }
+
+        let within = self
+            .points
+            .iter()
+            .filter(|point| point.distance <= tolerance)
+            .count();
+
+        Scalar::from_u64(within as u64) * 100. / self.points.len() as f64
+    }
+
+    /// Access the measured points and their deviation, as a scalar field
+    ///
+    /// The field has one value per point, which makes it suitable for
+    /// visualizing the deviation as a colored point cloud (see
+    /// [`export::ply::write_field`]).
+    ///
+    /// [`export::ply::write_field`]: crate::export::ply::write_field
+    pub fn field(&self) -> (Vec<Point<3>>, ScalarField) {
+        let points = self.points.iter().map(|point| point.point).collect();
+        let field = ScalarField::per_vertex(
+            self.points.iter().map(|point| point.distance),
+        );
+
+        (points, field)
+    }
+
+    /// Write the deviation of each point as CSV
+    pub fn write_csv(&self, mut writer: impl io::Write) -> io::Result<()> {
+        writeln!(writer, "x,y,z,deviation")?;
+
+        for PointDeviation { point, distance } in &self.points {
+            writeln!(
+                writer,
+                "{},{},{},{}",
+                point.x.into_f64(),
+                point.y.into_f64(),
+                point.z.into_f64(),
+                distance.into_f64(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Convert triangles into a Parry triangle mesh, for distance queries
+///
+/// Returns an error, if there are more vertices than Parry can refer to.
+pub(super) fn tri_mesh(
+    triangles: &[Triangle<3>],
+) -> Result<TriMesh, IndexOverflow> {
+    let mut mesh = MeshMaker::new();
+    for triangle in triangles {
+        for point in triangle.points() {
+            mesh.push(point)?;
+        }
+    }
+
+    let vertices = mesh.vertices().map(|point| point.to_na()).collect();
+    let indices = mesh.indices().collect::<Vec<_>>();
+    let indices = indices
+        .chunks(3)
+        .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+        .collect();
+
+    Ok(TriMesh::new(vertices, indices))
+}
+
+/// The deviation of a single measured point
+pub struct PointDeviation {
+    /// The measured point
+    pub point: Point<3>,
+
+    /// The distance of the point from the nominal shape
+    pub distance: Scalar,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{Point, Scalar, Triangle};
+
+    use super::Deviation;
+
+    #[test]
+    fn compute() {
+        let a = Point::from([0., 0., 0.]);
+        let b = Point::from([1., 0., 0.]);
+        let c = Point::from([1., 1., 0.]);
+        let d = Point::from([0., 1., 0.]);
+
+        let nominal = [Triangle::from([a, b, c]), Triangle::from([a, c, d])];
+
+        let deviation = Deviation::compute(
+            &nominal,
+            [
+                Point::from([0.5, 0.5, 0.5]),
+                Point::from([0.5, 0.5, -1.]),
+                Point::from([0.25, 0.75, 0.]),
+                Point::from([2., 0.5, 0.]),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(deviation.max(), Scalar::from_f64(1.));
+        assert_eq!(deviation.mean(), Scalar::from_f64(0.625));
+        assert_eq!(
+            deviation.percent_within(Scalar::from_f64(0.5)),
+            Scalar::from_f64(50.)
+        );
+    }
+}
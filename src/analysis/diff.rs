@@ -0,0 +1,290 @@
+use crate::{
+    math::{Aabb, Scalar, Triangle},
+    mesh::IndexOverflow,
+};
+
+use super::{
+    comparison::{self, triangles_off_surface},
+    manifold,
+    stats::Stats,
+    voxels::{self, Grid, Voxels},
+};
+
+/// The number of voxels along the longest side, by default
+///
+/// See [`MaterialChange::default_voxel_size`].
+const DEFAULT_VOXELS_PER_SIDE: f64 = 100.;
+
+/// The geometric differences between two versions of a shape
+///
+/// This can be used to review changes to model code, by comparing the shape
+/// before and after the change, or to compare a model with two different sets
+/// of parameters.
+pub struct Diff {
+    /// Statistics about the shape before the change
+    pub before: Stats,
+
+    /// Statistics about the shape after the change
+    pub after: Stats,
+
+    /// The volume of the shape before and after the change
+    ///
+    /// The volume is only known for closed meshes (see [`manifold::orient`]).
+    pub volume: [Option<Scalar>; 2],
+
+    /// The Hausdorff distance between the two shapes
+    ///
+    /// This is the largest distance that a point on one of the shapes has from
+    /// the other shape. It's zero, if the shapes are identical.
+    ///
+    /// See [`comparison::hausdorff_distance`].
+    pub hausdorff_distance: Scalar,
+}
+
+impl Diff {
+    /// Compute the differences between two triangle meshes
+    ///
+    /// Neither `before` nor `after` may be empty. `tolerance` defines the
+    /// accuracy of the Hausdorff distance. Returns an error, if either mesh
+    /// has too many vertices.
+    pub fn compute(
+        before: &[Triangle<3>],
+        after: &[Triangle<3>],
+        tolerance: Scalar,
+    ) -> Result<Self, IndexOverflow> {
+        let (before_stats, before_volume) = stats(before);
+        let (after_stats, after_volume) = stats(after);
+
+        let hausdorff_distance =
+            comparison::hausdorff_distance(before, after, tolerance)?;
+
+        Ok(Self {
+            before: before_stats,
+            after: after_stats,
+            volume: [before_volume, after_volume],
+            hausdorff_distance,
+        })
+    }
+
+    /// The change in volume, if the volume of both shapes is known
+    pub fn volume_delta(&self) -> Option<Scalar> {
+        match self.volume {
+            [Some(before), Some(after)] => Some(after - before),
+            _ => None,
+        }
+    }
+
+    /// Indicate whether the shapes are identical, within `tolerance`
+    pub fn is_within(&self, tolerance: Scalar) -> bool {
+        self.hausdorff_distance <= tolerance
+    }
+}
+
+/// The material that was added and removed between two versions of a shape
+///
+/// This shows what exactly changed, where [`Diff`] only summarizes it. The
+/// material is approximated using voxels (see [`Grid::voxelize`]), so both
+/// versions of the shape must be closed meshes.
+pub struct MaterialChange {
+    /// The material that is only part of the shape after the change
+    pub added: Voxels,
+
+    /// The material that is only part of the shape before the change
+    pub removed: Voxels,
+}
+
+impl MaterialChange {
+    /// Compute the material that changed between two triangle meshes
+    ///
+    /// Neither `before` nor `after` may be empty. The voxels have an edge
+    /// length of `voxel_size`, which is the accuracy of the result. Fails, if
+    /// that doesn't result in a valid grid (see [`Grid::new`]).
+    pub fn compute(
+        before: &[Triangle<3>],
+        after: &[Triangle<3>],
+        voxel_size: Scalar,
+    ) -> Result<Self, voxels::Error> {
+        let grid = Grid::new(&Self::aabb(before, after), voxel_size)?;
+
+        let before = grid.voxelize(before);
+        let after = grid.voxelize(after);
+
+        Ok(Self {
+            added: after.difference(&before),
+            removed: before.difference(&after),
+        })
+    }
+
+    /// Choose a voxel size that results in a reasonable number of voxels
+    ///
+    /// Neither `before` nor `after` may be empty.
+    pub fn default_voxel_size(
+        before: &[Triangle<3>],
+        after: &[Triangle<3>],
+    ) -> Scalar {
+        let size = Self::aabb(before, after).size().components();
+        let longest = size[0].max(size[1]).max(size[2]);
+
+        longest / Scalar::from_f64(DEFAULT_VOXELS_PER_SIDE)
+    }
+
+    fn aabb(before: &[Triangle<3>], after: &[Triangle<3>]) -> Aabb<3> {
+        let [before, after] = [before, after].map(|triangles| {
+            Aabb::<3>::from_points(
+                triangles.iter().flat_map(|triangle| triangle.points()),
+            )
+        });
+        before.merged(&after)
+    }
+}
+
+/// The parts of the surface that changed between two versions of a shape
+///
+/// Unlike [`MaterialChange`], this works with any triangle mesh, and is cheap
+/// enough to compute whenever a model is reloaded. It's only accurate to the
+/// size of the triangles though (see [`triangles_off_surface`]).
+pub struct SurfaceChange {
+    /// The triangles of the shape after the change, that weren't there before
+    pub added: Vec<Triangle<3>>,
+
+    /// The triangles of the shape before the change, that aren't there anymore
+    pub removed: Vec<Triangle<3>>,
+}
+
+impl SurfaceChange {
+    /// Compute the surface that changed between two triangle meshes
+    ///
+    /// Triangles that are within `tolerance` of the other mesh are considered
+    /// unchanged. If the meshes approximate curved surfaces, this needs to be
+    /// at least twice the tolerance that they were triangulated with, or
+    /// differences in the triangulation show up as changes.
+    ///
+    /// Returns an error, if either mesh has too many vertices.
+    pub fn compute(
+        before: &[Triangle<3>],
+        after: &[Triangle<3>],
+        tolerance: Scalar,
+    ) -> Result<Self, IndexOverflow> {
+        // Everything is new, if there was nothing before, and vice versa.
+        if before.is_empty() || after.is_empty() {
+            return Ok(Self {
+                added: after.to_vec(),
+                removed: before.to_vec(),
+            });
+        }
+
+        Ok(Self {
+            added: triangles_off_surface(after, before, tolerance)?,
+            removed: triangles_off_surface(before, after, tolerance)?,
+        })
+    }
+
+    /// Indicate whether the surface is unchanged
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+fn stats(triangles: &[Triangle<3>]) -> (Stats, Option<Scalar>) {
+    let oriented = manifold::orient(triangles);
+    let stats = Stats::compute(oriented.as_deref().unwrap_or(triangles));
+    let volume = oriented.map(|_| stats.volume);
+
+    (stats, volume)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{Point, Scalar, Triangle, Vector};
+
+    use super::{Diff, MaterialChange, SurfaceChange};
+
+    #[test]
+    fn compute() {
+        let small = tetrahedron(1.);
+        let large = tetrahedron(2.);
+        let tolerance = Scalar::from_f64(0.001);
+
+        let diff = Diff::compute(&small, &small, tolerance).unwrap();
+        assert!(diff.is_within(Scalar::from_f64(1e-12)));
+        assert_eq!(diff.volume_delta(), Some(Scalar::ZERO));
+
+        let diff = Diff::compute(&small, &large, tolerance).unwrap();
+        assert_eq!(diff.after.aabb.max, Point::from([2., 2., 2.]));
+        let volume_delta = diff.volume_delta().unwrap().into_f64();
+        assert!((volume_delta - 7. / 6.).abs() < 1e-12);
+
+        // The tip of the large tetrahedron at `[2, 0, 0]` is farthest away
+        // from the small one.
+        let distance = diff.hausdorff_distance.into_f64();
+        assert!((distance - 1.).abs() <= 0.001);
+        assert!(diff.is_within(Scalar::from_f64(1.)));
+        assert!(!diff.is_within(Scalar::from_f64(0.5)));
+    }
+
+    #[test]
+    fn material_change() {
+        let small = tetrahedron(1.);
+        let large = tetrahedron(2.);
+
+        let voxel_size = MaterialChange::default_voxel_size(&small, &large);
+        assert_eq!(voxel_size, Scalar::from_f64(0.02));
+
+        let change =
+            MaterialChange::compute(&small, &large, voxel_size).unwrap();
+        let added = change.added.volume().into_f64();
+        assert!((added - 7. / 6.).abs() < 0.05);
+        assert_eq!(change.removed.count(), 0);
+
+        let change =
+            MaterialChange::compute(&large, &small, voxel_size).unwrap();
+        assert_eq!(change.added.count(), 0);
+        assert_eq!(change.removed.volume().into_f64(), added);
+    }
+
+    #[test]
+    fn surface_change() {
+        let tolerance = Scalar::from_f64(0.001);
+        let unchanged = tetrahedron(1.);
+        let moved = tetrahedron(1.).map(|triangle| {
+            Triangle::from(
+                triangle
+                    .points()
+                    .map(|point| point + Vector::from([2., 0., 0.])),
+            )
+        });
+
+        let change =
+            SurfaceChange::compute(&unchanged, &unchanged, tolerance).unwrap();
+        assert!(change.is_empty());
+
+        let before = [unchanged, tetrahedron(2.)].concat();
+        let after = [unchanged, moved].concat();
+        let change =
+            SurfaceChange::compute(&before, &after, tolerance).unwrap();
+        assert_eq!(change.added, moved);
+
+        // The faces of the large tetrahedron are partially covered by the
+        // small one, but they still changed.
+        assert_eq!(change.removed, tetrahedron(2.));
+
+        let change =
+            SurfaceChange::compute(&[], &unchanged, tolerance).unwrap();
+        assert_eq!(change.added, unchanged);
+        assert!(change.removed.is_empty());
+    }
+
+    fn tetrahedron(size: f64) -> [Triangle<3>; 4] {
+        let a = Point::from([0., 0., 0.]);
+        let b = Point::from([size, 0., 0.]);
+        let c = Point::from([0., size, 0.]);
+        let d = Point::from([0., 0., size]);
+
+        [
+            Triangle::from([a, c, b]),
+            Triangle::from([a, b, d]),
+            Triangle::from([b, c, d]),
+            Triangle::from([c, a, d]),
+        ]
+    }
+}
@@ -0,0 +1,160 @@
+use crate::math::Scalar;
+
+/// A scalar value for each vertex or each triangle of a mesh
+///
+/// Analyses use this to report values that vary over the surface of a shape,
+/// like wall thickness or the deviation from a nominal shape. Together with a
+/// [`Colormap`], any host that can display colored meshes can visualize the
+/// values, without knowing what they mean.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScalarField {
+    /// What the values are attached to
+    pub location: FieldLocation,
+
+    /// The values, one per vertex or triangle, in the order of the mesh
+    pub values: Vec<Scalar>,
+}
+
+impl ScalarField {
+    /// Create a field with one value per vertex
+    pub fn per_vertex(values: impl IntoIterator<Item = Scalar>) -> Self {
+        Self {
+            location: FieldLocation::Vertices,
+            values: values.into_iter().collect(),
+        }
+    }
+
+    /// Create a field with one value per triangle
+    pub fn per_triangle(values: impl IntoIterator<Item = Scalar>) -> Self {
+        Self {
+            location: FieldLocation::Triangles,
+            values: values.into_iter().collect(),
+        }
+    }
+
+    /// Compute the smallest and largest value of the field
+    ///
+    /// Returns `None`, if the field has no values.
+    pub fn range(&self) -> Option<[Scalar; 2]> {
+        let min = self.values.iter().copied().min()?;
+        let max = self.values.iter().copied().max()?;
+
+        Some([min, max])
+    }
+
+    /// Map each value of the field to a color
+    ///
+    /// `range` defines the values that are mapped to the start and the end of
+    /// the colormap. Values outside of it are clamped. Use [`Self::range`] to
+    /// cover all values of the field.
+    pub fn colors(
+        &self,
+        colormap: Colormap,
+        range: [Scalar; 2],
+    ) -> Vec<[u8; 3]> {
+        let [min, max] = range;
+
+        self.values
+            .iter()
+            .map(|&value| {
+                let t = if max > min {
+                    (value - min) / (max - min)
+                } else {
+                    Scalar::ZERO
+                };
+
+                colormap.color(t)
+            })
+            .collect()
+    }
+}
+
+/// What the values of a [`ScalarField`] are attached to
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FieldLocation {
+    /// One value per vertex, interpolated over each triangle
+    Vertices,
+
+    /// One value per triangle, constant over the triangle
+    Triangles,
+}
+
+/// Maps values between `0.` and `1.` to colors
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Colormap {
+    /// Goes from dark purple over teal to yellow
+    ///
+    /// Perceptually uniform, and readable for people with color vision
+    /// deficiencies. A good default for values that start at zero, like
+    /// deviations.
+    Viridis,
+
+    /// Goes from blue over light gray to red
+    ///
+    /// Meant for values that can be positive or negative, with the range
+    /// centered on zero.
+    CoolWarm,
+}
+
+impl Colormap {
+    /// Map a value to a color
+    ///
+    /// Values outside of `0.` to `1.` are clamped.
+    pub fn color(&self, t: Scalar) -> [u8; 3] {
+        let stops: &[[u8; 3]] = match self {
+            Self::Viridis => &[
+                [0x44, 0x01, 0x54],
+                [0x3b, 0x52, 0x8b],
+                [0x21, 0x91, 0x8c],
+                [0x5e, 0xc9, 0x62],
+                [0xfd, 0xe7, 0x25],
+            ],
+            Self::CoolWarm => {
+                &[[0x3b, 0x4c, 0xc0], [0xdd, 0xdd, 0xdd], [0xb4, 0x04, 0x26]]
+            }
+        };
+
+        let t = t.into_f64().clamp(0., 1.) * (stops.len() - 1) as f64;
+        let i = (t.floor() as usize).min(stops.len() - 2);
+        let t = t - i as f64;
+
+        let [a, b] = [stops[i], stops[i + 1]];
+        [0, 1, 2].map(|channel| {
+            let [a, b] = [a[channel], b[channel]].map(f64::from);
+            (a + (b - a) * t).round() as u8
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::Scalar;
+
+    use super::{Colormap, ScalarField};
+
+    #[test]
+    fn colors() {
+        let field =
+            ScalarField::per_vertex([1., 3., 2., 5.].map(Scalar::from_f64));
+
+        let range = field.range().unwrap();
+        assert_eq!(range, [1., 5.].map(Scalar::from_f64));
+
+        let colors = field.colors(Colormap::CoolWarm, range);
+        assert_eq!(
+            colors,
+            [
+                [0x3b, 0x4c, 0xc0],
+                [0xdd, 0xdd, 0xdd],
+                [0x8c, 0x95, 0xcf],
+                [0xb4, 0x04, 0x26],
+            ]
+        );
+
+        // Values outside of the range are clamped.
+        let colors =
+            field.colors(Colormap::Viridis, [0., 2.].map(Scalar::from_f64));
+        assert_eq!(colors[1], colors[3]);
+        assert_eq!(colors[1], [0xfd, 0xe7, 0x25]);
+    }
+}
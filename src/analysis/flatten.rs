@@ -0,0 +1,511 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::f64::consts::TAU;
+
+use nalgebra::{Point2, Vector2};
+
+use crate::math::{Point, Scalar, Triangle};
+
+/// The angle defect a vertex may have, before it is no longer considered flat
+///
+/// Curved meshes are only approximations of developable surfaces, so their
+/// vertices are rarely perfectly flat. This is in radians.
+const MAX_ANGLE_DEFECT: f64 = 1e-3;
+
+/// Flatten a triangle mesh into pieces that can be cut from sheet material
+///
+/// The mesh is split into pieces along its sharp edges, meaning edges where the
+/// normals of the adjacent triangles differ by more than `max_angle` (in
+/// radians), as well as along edges that aren't shared by exactly two
+/// triangles. Each piece is then unrolled into the plane, triangle by triangle.
+/// This doesn't stretch the piece, so all lengths and areas within it are
+/// preserved.
+///
+/// This only works for pieces that are developable surfaces, like planes,
+/// cylinders, or cones, which can be unrolled without distortion. Surfaces that
+/// close around on themselves, like the side of a cylinder, are cut open along
+/// a seam. Returns an error, if a piece is not developable.
+///
+/// Pieces are not checked for overlaps with themselves, which can happen if
+/// they wind around more than once.
+pub fn flatten(
+    triangles: &[Triangle<3>],
+    max_angle: Scalar,
+) -> Result<Vec<Piece>, NotDevelopable> {
+    let mut edges: BTreeMap<[Point<3>; 2], Vec<usize>> = BTreeMap::new();
+    for (i, triangle) in triangles.iter().enumerate() {
+        for edge in directed_edges(triangle) {
+            edges.entry(undirected(edge)).or_default().push(i);
+        }
+    }
+
+    // The neighbors of each triangle within its piece, with the edge they
+    // share.
+    let mut neighbors = vec![Vec::new(); triangles.len()];
+    for (&edge, adjacent) in &edges {
+        if let [i, j] = adjacent[..] {
+            if bend_angle(&triangles[i], &triangles[j], edge) <= max_angle {
+                neighbors[i].push((j, edge));
+                neighbors[j].push((i, edge));
+            }
+        }
+    }
+
+    let longest_edge = edges
+        .keys()
+        .map(|&[a, b]| (b - a).magnitude())
+        .max()
+        .unwrap_or(Scalar::ZERO);
+    let flattener = Flattener {
+        triangles,
+        neighbors,
+        epsilon: longest_edge.into_f64() * 1e-6,
+    };
+
+    let mut pieces = Vec::new();
+    let mut layout = vec![None; triangles.len()];
+
+    for start in 0..triangles.len() {
+        if layout[start].is_none() {
+            pieces.push(flattener.piece(start, &mut layout)?);
+        }
+    }
+
+    Ok(pieces)
+}
+
+/// A piece of a mesh that was flattened by [`flatten`]
+#[derive(Clone, Debug)]
+pub struct Piece {
+    /// The indices of the triangles that make up the piece
+    ///
+    /// The indices refer to the slice of triangles that was passed to
+    /// [`flatten`].
+    pub triangles: Vec<usize>,
+
+    /// The flattened triangles, in the same order as `triangles`
+    pub flattened: Vec<Triangle<2>>,
+
+    /// The outlines of the flattened piece
+    ///
+    /// Outer boundaries run counter-clockwise, holes run clockwise.
+    pub outlines: Vec<Vec<Point<2>>>,
+}
+
+/// Returned by [`flatten`], if a piece of the mesh is not developable
+///
+/// A developable surface is flat everywhere, except for at most one cone point
+/// per piece, like the tip of a cone. Flat vertices are surrounded by angles
+/// that add up to a full turn. The difference from that is the angle defect.
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+#[error(
+    "Surface is not developable (angle defect of {defect:?} at {vertex:?})"
+)]
+pub struct NotDevelopable {
+    /// The vertex where the surface is curved
+    pub vertex: Point<3>,
+
+    /// The angle defect at the vertex, in radians
+    pub defect: Scalar,
+}
+
+struct Flattener<'r> {
+    triangles: &'r [Triangle<3>],
+    neighbors: Vec<Vec<(usize, [Point<3>; 2])>>,
+
+    /// The distance below which two flattened points are considered the same
+    epsilon: f64,
+}
+
+impl Flattener<'_> {
+    fn piece(
+        &self,
+        start: usize,
+        layout: &mut [Option<[Point2<f64>; 3]>],
+    ) -> Result<Piece, NotDevelopable> {
+        layout[start] = Some(place_first(&self.triangles[start]));
+
+        let mut piece = vec![start];
+        let mut queue = VecDeque::from([start]);
+
+        // Going breadth-first keeps the chains of triangles that each
+        // triangle is placed relative to short, which limits the build-up of
+        // rounding errors.
+        while let Some(i) = queue.pop_front() {
+            for &(j, edge) in &self.neighbors[i] {
+                if layout[j].is_some() {
+                    continue;
+                }
+
+                // Can't panic. `i` has been placed before it was queued.
+                let parent = (&self.triangles[i], layout[i].unwrap());
+                layout[j] =
+                    Some(place_adjacent(&self.triangles[j], edge, parent));
+
+                piece.push(j);
+                queue.push_back(j);
+            }
+        }
+
+        self.check_developable(&piece)?;
+
+        let flattened = piece
+            .iter()
+            .map(|&i| {
+                // Can't panic. All triangles of the piece have been placed.
+                let corners = layout[i].unwrap();
+                Triangle::from(corners.map(Point::from_na))
+            })
+            .collect();
+        let outlines = self.outlines(&piece, layout);
+
+        Ok(Piece {
+            triangles: piece,
+            flattened,
+            outlines,
+        })
+    }
+
+    fn check_developable(&self, piece: &[usize]) -> Result<(), NotDevelopable> {
+        // The sum of the angles around each vertex, and whether the vertex is
+        // on the boundary of the piece.
+        let mut vertices: BTreeMap<Point<3>, (f64, bool)> = BTreeMap::new();
+
+        for &i in piece {
+            let points = self.triangles[i].points();
+
+            for k in 0..3 {
+                let [a, b, c] = [0, 1, 2].map(|l| points[(k + l) % 3]);
+                let [ab, ac] = [b - a, c - a].map(|v| v.to_na());
+
+                let (sum, boundary) = vertices.entry(a).or_default();
+                *sum += ab.angle(&ac);
+                *boundary |= [b, c].iter().any(|&other| {
+                    !self.neighbors[i]
+                        .iter()
+                        .any(|(_, edge)| *edge == undirected([a, other]))
+                });
+            }
+        }
+
+        let mut cone_point = None;
+        for (vertex, (sum, boundary)) in vertices {
+            let defect = TAU - sum;
+            if boundary || defect.abs() <= MAX_ANGLE_DEFECT {
+                continue;
+            }
+
+            // A single cone point can be cut open, just like the tip of a
+            // cone. It needs to be convex, or the unrolled piece would overlap
+            // itself.
+            if defect > 0. && cone_point.is_none() {
+                cone_point = Some(vertex);
+                continue;
+            }
+
+            return Err(NotDevelopable {
+                vertex,
+                defect: Scalar::from_f64(defect),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn outlines(
+        &self,
+        piece: &[usize],
+        layout: &[Option<[Point2<f64>; 3]>],
+    ) -> Vec<Vec<Point<2>>> {
+        let position = |i: usize, vertex: Point<3>| {
+            let points = self.triangles[i].points();
+            let k = points.iter().position(|&point| point == vertex)?;
+            Some(layout[i]?[k])
+        };
+
+        // Collect the edges that are not connected to a neighbor in the
+        // flattened piece. These are either edges of the original mesh, or
+        // edges along which the piece was cut open.
+        let mut segments = Vec::new();
+        for &i in piece {
+            // Can't panic. All triangles of the piece have been placed.
+            let corners = layout[i].unwrap();
+            let mut points = self.triangles[i].points();
+            let mut corners = corners;
+
+            // Traverse each flattened triangle counter-clockwise, so the
+            // segments link up into outlines.
+            let [a, b, c] = corners;
+            if (b - a).perp(&(c - a)) < 0. {
+                points.swap(1, 2);
+                corners.swap(1, 2);
+            }
+
+            for k in 0..3 {
+                let l = (k + 1) % 3;
+                let edge = undirected([points[k], points[l]]);
+
+                let connected = self.neighbors[i].iter().any(|&(j, e)| {
+                    e == edge
+                        && [k, l].iter().all(|&m| {
+                            matches!(
+                                position(j, points[m]),
+                                Some(p) if self.same_point(p, corners[m])
+                            )
+                        })
+                });
+
+                if !connected {
+                    segments.push((
+                        (points[k], corners[k]),
+                        (points[l], corners[l]),
+                    ));
+                }
+            }
+        }
+
+        let mut by_start: BTreeMap<Point<3>, Vec<usize>> = BTreeMap::new();
+        for (s, ((start, _), _)) in segments.iter().enumerate() {
+            by_start.entry(*start).or_default().push(s);
+        }
+
+        let mut used = vec![false; segments.len()];
+        let mut outlines = Vec::new();
+
+        for first in 0..segments.len() {
+            if used[first] {
+                continue;
+            }
+
+            let mut outline = Vec::new();
+            let mut current = first;
+
+            loop {
+                used[current] = true;
+
+                let ((_, start), (end, end_position)) = segments[current];
+                outline.push(Point::from_na(start));
+
+                let next = by_start[&end].iter().copied().find(|&s| {
+                    !used[s] && self.same_point(segments[s].0 .1, end_position)
+                });
+                match next {
+                    Some(next) => current = next,
+                    None => break,
+                }
+            }
+
+            outlines.push(outline);
+        }
+
+        outlines
+    }
+
+    fn same_point(&self, a: Point2<f64>, b: Point2<f64>) -> bool {
+        (a - b).norm() <= self.epsilon
+    }
+}
+
+/// The angle between the normals of two triangles that share an edge
+fn bend_angle(a: &Triangle<3>, b: &Triangle<3>, edge: [Point<3>; 2]) -> Scalar {
+    let normal = |triangle: &Triangle<3>| {
+        let [a, b, c] = triangle.points();
+        (b - a).cross(&(c - a)).normalize()
+    };
+
+    // Consistently oriented triangles traverse their shared edge in opposite
+    // directions. Don't rely on that, as meshes are not always consistent.
+    let mut normal_b = normal(b);
+    if directed_edges(a).contains(&edge) == directed_edges(b).contains(&edge) {
+        normal_b = -normal_b;
+    }
+
+    normal(a).angle_to(&normal_b)
+}
+
+/// Place the first triangle of a piece, keeping its orientation
+fn place_first(triangle: &Triangle<3>) -> [Point2<f64>; 3] {
+    let [a, b, c] = triangle.points();
+    let [ab, ac] = [b - a, c - a].map(|v| v.to_na());
+
+    let length = ab.norm();
+    let along = ab.dot(&ac) / length;
+    let height = ab.cross(&ac).norm() / length;
+
+    [
+        Point2::origin(),
+        Point2::new(length, 0.),
+        Point2::new(along, height),
+    ]
+}
+
+/// Place a triangle next to an already placed neighbor
+///
+/// The triangle is unfolded across the edge it shares with the neighbor, so it
+/// ends up on the other side of that edge.
+fn place_adjacent(
+    triangle: &Triangle<3>,
+    edge: [Point<3>; 2],
+    (neighbor, neighbor_corners): (&Triangle<3>, [Point2<f64>; 3]),
+) -> [Point2<f64>; 3] {
+    let [p, q] = edge;
+
+    let neighbor_points = neighbor.points();
+    let corner = |vertex: Point<3>| {
+        // Can't panic. The neighbor shares the edge.
+        let k = neighbor_points.iter().position(|&v| v == vertex).unwrap();
+        neighbor_corners[k]
+    };
+    let opposite = neighbor_points
+        .iter()
+        .position(|&v| v != p && v != q)
+        .map(|k| neighbor_corners[k])
+        .unwrap();
+    let [p_2d, q_2d] = [corner(p), corner(q)];
+
+    let direction = (q_2d - p_2d).normalize();
+    let mut perp = Vector2::new(-direction.y, direction.x);
+    if (opposite - p_2d).dot(&perp) > 0. {
+        perp = -perp;
+    }
+
+    triangle.points().map(|vertex| {
+        if vertex == p {
+            return p_2d;
+        }
+        if vertex == q {
+            return q_2d;
+        }
+
+        let [pq, pr] = [q - p, vertex - p].map(|v| v.to_na());
+        let length = pq.norm();
+        let along = pq.dot(&pr) / length;
+        let height = pq.cross(&pr).norm() / length;
+
+        p_2d + direction * along + perp * height
+    })
+}
+
+fn directed_edges(triangle: &Triangle<3>) -> [[Point<3>; 2]; 3] {
+    let [a, b, c] = triangle.points();
+    [[a, b], [b, c], [c, a]]
+}
+
+fn undirected([a, b]: [Point<3>; 2]) -> [Point<3>; 2] {
+    if a < b {
+        [a, b]
+    } else {
+        [b, a]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{PI, TAU};
+
+    use approx::assert_abs_diff_eq;
+
+    use crate::math::{Point, Scalar, Triangle};
+
+    use super::flatten;
+
+    #[test]
+    fn flatten_should_unroll_cylinder() {
+        let (radius, height, segments) = (1., 2., 32);
+
+        let point = |i: usize, z: f64| {
+            let angle = TAU * (i % segments) as f64 / segments as f64;
+            Point::from([radius * angle.cos(), radius * angle.sin(), z])
+        };
+
+        let mut triangles = Vec::new();
+        for i in 0..segments {
+            let [a, b] = [point(i, 0.), point(i + 1, 0.)];
+            let [c, d] = [point(i + 1, height), point(i, height)];
+
+            triangles.push(Triangle::from([a, b, c]));
+            triangles.push(Triangle::from([a, c, d]));
+        }
+
+        let pieces = flatten(&triangles, Scalar::from_f64(PI / 4.)).unwrap();
+        assert_eq!(pieces.len(), 1);
+
+        // The side of the cylinder is cut open along a seam, resulting in a
+        // single outline, whose area is that of the side.
+        let piece = &pieces[0];
+        assert_eq!(piece.triangles.len(), triangles.len());
+        assert_eq!(piece.outlines.len(), 1);
+
+        let circumference = segments as f64 * 2. * (PI / segments as f64).sin();
+        assert_abs_diff_eq!(
+            area(&piece.outlines[0]),
+            circumference * height,
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    fn flatten_should_split_at_sharp_edges() {
+        let a = Point::from([0., 0., 0.]);
+        let b = Point::from([1., 0., 0.]);
+        let c = Point::from([0., 1., 0.]);
+        let d = Point::from([0., 0., 1.]);
+
+        let tetrahedron = [
+            Triangle::from([a, c, b]),
+            Triangle::from([a, b, d]),
+            Triangle::from([b, c, d]),
+            Triangle::from([c, a, d]),
+        ];
+
+        let pieces = flatten(&tetrahedron, Scalar::from_f64(PI / 4.)).unwrap();
+        assert_eq!(pieces.len(), 4);
+        assert!(pieces.iter().all(|piece| piece.outlines[0].len() == 3));
+
+        // Without any sharp edges, the tetrahedron is not developable.
+        assert!(flatten(&tetrahedron, Scalar::from_f64(PI)).is_err());
+    }
+
+    #[test]
+    fn flatten_should_unroll_cone() {
+        let (radius, height, segments) = (1., 1., 32);
+
+        let apex = Point::from([0., 0., height]);
+        let point = |i: usize| {
+            let angle = TAU * (i % segments) as f64 / segments as f64;
+            Point::from([radius * angle.cos(), radius * angle.sin(), 0.])
+        };
+
+        let triangles: Vec<_> = (0..segments)
+            .map(|i| Triangle::from([point(i), point(i + 1), apex]))
+            .collect();
+
+        let pieces = flatten(&triangles, Scalar::from_f64(PI / 4.)).unwrap();
+        assert_eq!(pieces.len(), 1);
+
+        // All points on the rim end up at the same distance from the apex.
+        let piece = &pieces[0];
+        let apex = piece.flattened[0].points()[2];
+        let slant = 2_f64.sqrt();
+        for triangle in &piece.flattened {
+            let [a, b, _] = triangle.points();
+            for point in [a, b] {
+                assert_abs_diff_eq!(
+                    (point - apex).magnitude().into_f64(),
+                    slant,
+                    epsilon = 1e-9
+                );
+            }
+        }
+    }
+
+    fn area(outline: &[Point<2>]) -> f64 {
+        let mut area = 0.;
+        for (i, a) in outline.iter().enumerate() {
+            let b = outline[(i + 1) % outline.len()];
+            let [ax, ay]: [f64; 2] = (*a).into();
+            let [bx, by]: [f64; 2] = b.into();
+            area += ax * by - bx * ay;
+        }
+        area / 2.
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    math::{Point, Scalar, Triangle},
+    mesh::FaceGroups,
+};
+
+/// Check whether a triangle mesh is a closed, consistently oriented manifold
+///
+/// This is the case, if every edge is shared by exactly two triangles that
+/// traverse it in opposite directions. Meshes that describe a solid must meet
+/// this requirement to be usable for manufacturing, for example for 3D
+/// printing.
+///
+/// Edges are identified by the exact positions of their vertices. Triangles
+/// that share an edge, without also sharing the edge's vertices exactly, are
+/// not considered to be connected.
+///
+/// Returns all problems that were found, ordered by the position of the edge
+/// they relate to. If the mesh is valid, the returned list is empty.
+pub fn check(triangles: &[Triangle<3>]) -> Vec<Problem> {
+    // Maps each edge, identified by its sorted vertices, to the number of
+    // triangles that traverse it in each direction, and the indices of those
+    // triangles.
+    let mut edges: BTreeMap<[Point<3>; 2], ([usize; 2], Vec<usize>)> =
+        BTreeMap::new();
+
+    for (i, triangle) in triangles.iter().enumerate() {
+        for [start, end] in directed_edges(triangle) {
+            let (key, direction) = if start < end {
+                ([start, end], 0)
+            } else {
+                ([end, start], 1)
+            };
+
+            let (directions, triangles) = edges.entry(key).or_default();
+            directions[direction] += 1;
+            triangles.push(i);
+        }
+    }
+
+    edges
+        .into_iter()
+        .filter_map(|(edge, ([forward, backward], triangles))| {
+            let kind = match forward + backward {
+                1 => ProblemKind::OpenEdge,
+                2 if forward == backward => return None,
+                2 => ProblemKind::InconsistentOrientation,
+                _ => ProblemKind::NonManifoldEdge,
+            };
+
+            Some(Problem {
+                kind,
+                edge,
+                triangles,
+            })
+        })
+        .collect()
+}
+
+/// Orient the triangles of a closed mesh consistently
+///
+/// Flips triangles as required, so every edge is traversed in opposite
+/// directions by the two triangles that share it. Each connected part of the
+/// mesh is oriented such that it encloses a positive volume, meaning its
+/// triangles face outwards. Please note that this is wrong for parts that
+/// describe a cavity within another part.
+///
+/// Returns `None`, if the mesh has open or non-manifold edges (see [`check`]),
+/// or if it can't be oriented consistently.
+pub fn orient(triangles: &[Triangle<3>]) -> Option<Vec<Triangle<3>>> {
+    let mut edges: BTreeMap<[Point<3>; 2], Vec<usize>> = BTreeMap::new();
+    for (i, triangle) in triangles.iter().enumerate() {
+        for edge in directed_edges(triangle) {
+            edges.entry(undirected(edge)).or_default().push(i);
+        }
+    }
+
+    if edges.values().any(|triangles| triangles.len() != 2) {
+        return None;
+    }
+
+    let mut oriented = triangles.to_vec();
+    let mut flipped = vec![None; triangles.len()];
+
+    for start in 0..triangles.len() {
+        if flipped[start].is_some() {
+            continue;
+        }
+
+        flipped[start] = Some(false);
+
+        let mut part = vec![start];
+        let mut queue = vec![start];
+
+        while let Some(i) = queue.pop() {
+            for [a, b] in directed_edges(&oriented[i]) {
+                // Can't panic. We made sure above, that every edge is shared
+                // by exactly two triangles.
+                let neighbors = &edges[&undirected([a, b])];
+                let j = if neighbors[0] == i {
+                    neighbors[1]
+                } else {
+                    neighbors[0]
+                };
+
+                // The neighbor must traverse the edge from `b` to `a`.
+                let flip = directed_edges(&triangles[j]).contains(&[a, b]);
+
+                match flipped[j] {
+                    Some(flipped) if flipped != flip => return None,
+                    Some(_) => {}
+                    None => {
+                        flipped[j] = Some(flip);
+                        if flip {
+                            oriented[j] = flipped_triangle(&triangles[j]);
+                        }
+
+                        part.push(j);
+                        queue.push(j);
+                    }
+                }
+            }
+        }
+
+        let volume = part.iter().fold(Scalar::ZERO, |volume, &i| {
+            let [a, b, c] = oriented[i].points();
+            volume + a.coords.dot(&b.coords.cross(&c.coords))
+        });
+
+        if volume < Scalar::ZERO {
+            for i in part {
+                oriented[i] = flipped_triangle(&oriented[i]);
+            }
+        }
+    }
+
+    Some(oriented)
+}
+
+fn directed_edges(triangle: &Triangle<3>) -> [[Point<3>; 2]; 3] {
+    let [a, b, c] = triangle.points();
+    [[a, b], [b, c], [c, a]]
+}
+
+fn undirected([a, b]: [Point<3>; 2]) -> [Point<3>; 2] {
+    if a < b {
+        [a, b]
+    } else {
+        [b, a]
+    }
+}
+
+fn flipped_triangle(triangle: &Triangle<3>) -> Triangle<3> {
+    let [a, b, c] = triangle.points();
+    Triangle::from([a, c, b])
+}
+
+/// A problem found by [`check`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Problem {
+    /// The kind of problem
+    pub kind: ProblemKind,
+
+    /// The vertices of the edge where the problem was found
+    pub edge: [Point<3>; 2],
+
+    /// The indices of the triangles that share the edge
+    ///
+    /// The indices refer to the slice of triangles that was passed to
+    /// [`check`].
+    pub triangles: Vec<usize>,
+}
+
+impl Problem {
+    /// The faces of the shape that the problem affects
+    ///
+    /// Maps the triangles of the problem to the faces they came from, which
+    /// tells the user where to look in the model. `faces` must refer to the
+    /// triangles that were passed to [`check`] (see
+    /// [`crate::evaluation::triangulate_by_face`]).
+    ///
+    /// Returns the face indices in ascending order, without duplicates.
+    pub fn faces(&self, faces: &FaceGroups) -> Vec<usize> {
+        let mut faces: Vec<_> = self
+            .triangles
+            .iter()
+            .filter_map(|&triangle| faces.face_of(triangle))
+            .collect();
+
+        faces.sort_unstable();
+        faces.dedup();
+
+        faces
+    }
+}
+
+/// The kind of a [`Problem`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProblemKind {
+    /// The edge is only part of one triangle, leaving a hole in the mesh
+    OpenEdge,
+
+    /// The edge is shared by more than two triangles
+    NonManifoldEdge,
+
+    /// The two triangles that share the edge have opposite orientations
+    InconsistentOrientation,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{Point, Triangle};
+
+    use super::{check, orient, ProblemKind};
+
+    #[test]
+    fn check_closed() {
+        let triangles = tetrahedron();
+        assert!(check(&triangles).is_empty());
+    }
+
+    #[test]
+    fn check_open() {
+        let triangles = tetrahedron();
+
+        let problems = check(&triangles[..3]);
+        assert_eq!(problems.len(), 3);
+        assert!(problems.iter().all(|problem| problem.triangles.len() == 1));
+        assert!(problems
+            .iter()
+            .all(|problem| problem.kind == ProblemKind::OpenEdge));
+    }
+
+    #[test]
+    fn check_flipped() {
+        let mut triangles = tetrahedron();
+
+        let [a, b, c] = triangles[0].points();
+        triangles[0] = Triangle::from([a, c, b]);
+
+        let problems = check(&triangles);
+        assert_eq!(problems.len(), 3);
+        assert!(problems.iter().all(
+            |problem| problem.kind == ProblemKind::InconsistentOrientation
+        ));
+    }
+
+    #[test]
+    fn orient_flipped() {
+        let mut triangles = tetrahedron();
+
+        // Flip the whole tetrahedron inside out, then flip one triangle back.
+        for triangle in &mut triangles {
+            let [a, b, c] = triangle.points();
+            *triangle = Triangle::from([a, c, b]);
+        }
+        let [a, b, c] = triangles[0].points();
+        triangles[0] = Triangle::from([a, c, b]);
+
+        let oriented = orient(&triangles).unwrap();
+        assert_eq!(oriented, tetrahedron());
+    }
+
+    #[test]
+    fn orient_open() {
+        let triangles = tetrahedron();
+        assert_eq!(orient(&triangles[..3]), None);
+    }
+
+    fn tetrahedron() -> Vec<Triangle<3>> {
+        let a = Point::from([0., 0., 0.]);
+        let b = Point::from([1., 0., 0.]);
+        let c = Point::from([0., 1., 0.]);
+        let d = Point::from([0., 0., 1.]);
+
+        vec![
+            Triangle::from([a, c, b]),
+            Triangle::from([a, b, d]),
+            Triangle::from([b, c, d]),
+            Triangle::from([c, a, d]),
+        ]
+    }
+}
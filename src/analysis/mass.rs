@@ -0,0 +1,219 @@
+use nalgebra::{Matrix3, Point3, Vector3};
+
+use crate::math::{Point, Triangle};
+
+/// The mass, center of mass, and moments of inertia of a solid
+///
+/// Models are assumed to be in millimeters, and densities in grams per cubic
+/// centimeter. Masses are in grams, and moments of inertia in gram square
+/// millimeters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MassProperties {
+    /// The volume of the solid
+    pub volume: f64,
+
+    /// The mass of the solid
+    pub mass: f64,
+
+    /// The center of mass of the solid
+    pub center_of_mass: Point<3>,
+
+    /// The inertia tensor, relative to the center of mass
+    ///
+    /// The axes are the axes of the model's coordinate system. The diagonal
+    /// contains the moments of inertia about those axes, the other entries
+    /// the products of inertia.
+    pub inertia: [[f64; 3]; 3],
+}
+
+impl MassProperties {
+    /// Compute the mass properties of a solid with uniform density
+    ///
+    /// The triangles must form a closed mesh, and be oriented consistently,
+    /// facing outwards (see [`super::manifold::orient`]).
+    pub fn compute(triangles: &[Triangle<3>], density: f64) -> Self {
+        let mut volume = 0.;
+        let mut first = Vector3::zeros();
+        let mut second = Matrix3::zeros();
+
+        // Each triangle forms a tetrahedron with the origin. Integrated over
+        // all of them, the parts outside of the solid cancel each other out,
+        // like for the volume in `Stats::compute`.
+        for triangle in triangles {
+            let [a, b, c] = triangle.points().map(|point| point.coords.to_na());
+            let det = a.dot(&b.cross(&c));
+            let sum = a + b + c;
+
+            volume += det / 6.;
+            first += sum * (det / 24.);
+            second += (sum * sum.transpose()
+                + a * a.transpose()
+                + b * b.transpose()
+                + c * c.transpose())
+                * (det / 120.);
+        }
+
+        // Cubic millimeters to cubic centimeters
+        let mass_per_volume = density / 1000.;
+
+        let center = if volume == 0. {
+            Vector3::zeros()
+        } else {
+            first / volume
+        };
+
+        // Move the second moment to the center of mass, then turn it into the
+        // inertia tensor.
+        let second =
+            (second - center * center.transpose() * volume) * mass_per_volume;
+        let inertia = Matrix3::identity() * second.trace() - second;
+
+        Self {
+            volume,
+            mass: volume * mass_per_volume,
+            center_of_mass: Point::from(Point3::from(center)),
+            inertia: inertia.into(),
+        }
+    }
+
+    /// Combine the mass properties of multiple solids
+    ///
+    /// The solids must not overlap. Returns `None`, if `parts` is empty.
+    pub fn combine(parts: &[Self]) -> Option<Self> {
+        if parts.is_empty() {
+            return None;
+        }
+
+        let volume = parts.iter().map(|part| part.volume).sum();
+        let mass: f64 = parts.iter().map(|part| part.mass).sum();
+
+        let center = if mass == 0. {
+            Vector3::zeros()
+        } else {
+            parts
+                .iter()
+                .map(|part| part.center_of_mass.to_na().coords * part.mass)
+                .sum::<Vector3<f64>>()
+                / mass
+        };
+
+        // Parallel axis theorem
+        let inertia = parts
+            .iter()
+            .map(|part| {
+                let d = part.center_of_mass.to_na().coords - center;
+                Matrix3::from(part.inertia)
+                    + (Matrix3::identity() * d.norm_squared()
+                        - d * d.transpose())
+                        * part.mass
+            })
+            .sum::<Matrix3<f64>>();
+
+        Some(Self {
+            volume,
+            mass,
+            center_of_mass: Point::from(Point3::from(center)),
+            inertia: inertia.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{Point, Triangle};
+
+    use super::MassProperties;
+
+    fn cuboid(min: [f64; 3], max: [f64; 3]) -> Vec<Triangle<3>> {
+        let corner = |i: usize| {
+            Point::from([0, 1, 2].map(|axis| {
+                if i & (1 << axis) == 0 {
+                    min[axis]
+                } else {
+                    max[axis]
+                }
+            }))
+        };
+
+        // The corners of each side, counter-clockwise when seen from outside
+        let sides = [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ];
+
+        sides
+            .iter()
+            .flat_map(|&[a, b, c, d]| {
+                [
+                    Triangle::from([corner(a), corner(b), corner(c)]),
+                    Triangle::from([corner(a), corner(c), corner(d)]),
+                ]
+            })
+            .collect()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn compute_should_match_cuboid_formulas() {
+        // 10 mm x 20 mm x 30 mm, away from the origin
+        let triangles = cuboid([5., 5., 5.], [15., 25., 35.]);
+        let properties = MassProperties::compute(&triangles, 2.);
+
+        let mass = 6000. / 1000. * 2.;
+        assert_close(properties.volume, 6000.);
+        assert_close(properties.mass, mass);
+        let center: [f64; 3] = properties.center_of_mass.into();
+        for (actual, expected) in center.into_iter().zip([10., 15., 20.]) {
+            assert_close(actual, expected);
+        }
+
+        let [x, y, z] = [10., 20., 30.];
+        let expected = [
+            mass / 12. * (y * y + z * z),
+            mass / 12. * (x * x + z * z),
+            mass / 12. * (x * x + y * y),
+        ];
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected = if i == j { expected[i] } else { 0. };
+                assert_close(properties.inertia[i][j], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn combine_should_match_single_solid() {
+        let whole =
+            MassProperties::compute(&cuboid([0., 0., 0.], [4., 1., 1.]), 1.);
+
+        let parts = [
+            MassProperties::compute(&cuboid([0., 0., 0.], [1., 1., 1.]), 1.),
+            MassProperties::compute(&cuboid([1., 0., 0.], [4., 1., 1.]), 1.),
+        ];
+        let combined = MassProperties::combine(&parts).unwrap();
+
+        assert_close(combined.mass, whole.mass);
+        let combined_center: [f64; 3] = combined.center_of_mass.into();
+        let whole_center: [f64; 3] = whole.center_of_mass.into();
+        for i in 0..3 {
+            assert_close(combined_center[i], whole_center[i]);
+            for j in 0..3 {
+                assert_close(combined.inertia[i][j], whole.inertia[i][j]);
+            }
+        }
+
+        assert!(MassProperties::combine(&[]).is_none());
+    }
+}
@@ -0,0 +1,21 @@
+//! Analyses of triangulated shapes
+//!
+//! The code in this module works on the triangle mesh that results from
+//! triangulating a shape, not on the boundary representation itself. This
+//! makes it possible to apply it to any geometry that can be represented as
+//! triangles, including meshes that weren't produced by Fornjot.
+
+pub mod angle;
+pub mod cache;
+pub mod clearance;
+pub mod comparison;
+pub mod deviation;
+pub mod diff;
+pub mod field;
+pub mod flatten;
+pub mod manifold;
+pub mod mass;
+pub mod orientation;
+pub mod stats;
+pub mod symmetry;
+pub mod voxels;
@@ -0,0 +1,396 @@
+use std::cmp::Reverse;
+
+use nalgebra::Vector3;
+
+use crate::math::{Scalar, Triangle, Vector};
+
+use super::mass::MassProperties;
+
+/// The steepest overhang that most printers can print without support
+///
+/// The angle is measured from the vertical, in radians (45 degrees).
+pub const DEFAULT_MAX_ANGLE: f64 = std::f64::consts::FRAC_PI_4;
+
+/// The maximum number of face directions that are considered as candidates
+///
+/// Directions are ranked by the area of the faces that point in them, so this
+/// only drops the small faces, which make for unstable orientations anyway.
+const MAX_FACE_CANDIDATES: usize = 16;
+
+/// Suggest orientations for printing a solid, best first
+///
+/// Candidates are the six directions of the coordinate axes, and the
+/// directions of the largest flat faces, as placing those on the print bed
+/// gives the part a stable footing. Each candidate is evaluated using
+/// [`evaluate`], and they are sorted by the volume of support material they
+/// require. Orientations that require the same volume are sorted by the
+/// height of the center of mass, as lower ones are less likely to topple.
+///
+/// The triangles must form a closed mesh, and be oriented consistently,
+/// facing outwards (see [`super::manifold::orient`]). `triangles` must not be
+/// empty.
+pub fn suggest(
+    triangles: &[Triangle<3>],
+    max_angle: Scalar,
+) -> Vec<Orientation> {
+    assert!(!triangles.is_empty(), "Can't orient empty mesh");
+
+    let mut directions = Vec::new();
+    let mut push = |direction: Vector3<f64>| {
+        let is_new = directions.iter().all(|other: &Vector3<f64>| {
+            other.dot(&direction) < 1. - DIRECTION_EPSILON
+        });
+        if is_new {
+            directions.push(direction);
+        }
+    };
+
+    for axis in 0..3 {
+        let mut direction = Vector3::zeros();
+        direction[axis] = 1.;
+
+        push(-direction);
+        push(direction);
+    }
+
+    let mut faces: Vec<(Vector3<f64>, f64)> = Vec::new();
+    for triangle in triangles {
+        let (normal, area) = match normal_and_area(triangle) {
+            Some(normal_and_area) => normal_and_area,
+            None => continue,
+        };
+
+        match faces
+            .iter_mut()
+            .find(|(other, _)| other.dot(&normal) >= 1. - DIRECTION_EPSILON)
+        {
+            Some((_, total)) => *total += area,
+            None => faces.push((normal, area)),
+        }
+    }
+    faces.sort_by_key(|&(_, area)| Reverse(Scalar::from_f64(area)));
+    for (normal, _) in faces.into_iter().take(MAX_FACE_CANDIDATES) {
+        push(normal);
+    }
+
+    let mass = MassProperties::compute(triangles, 1.);
+
+    let mut orientations: Vec<_> = directions
+        .into_iter()
+        .map(|down| {
+            evaluate_with_mass(triangles, &mass, Vector::from(down), max_angle)
+        })
+        .collect();
+    orientations.sort_by_key(|orientation| {
+        (
+            Scalar::from_f64(orientation.support_volume),
+            Scalar::from_f64(orientation.center_of_mass_height),
+        )
+    });
+
+    orientations
+}
+
+/// Evaluate how well a solid prints, with the given side facing down
+///
+/// `down` is the direction in model space that points towards the print bed.
+/// It doesn't need to have a length of one. Surfaces need support, if they
+/// overhang by more than `max_angle` (in radians), measured from the
+/// vertical, unless they rest on the print bed.
+///
+/// The volume of the support material is approximated, by assuming that
+/// support reaches down from each overhanging surface to the print bed, even
+/// where the solid itself is in the way.
+///
+/// The triangles must form a closed mesh, and be oriented consistently,
+/// facing outwards (see [`super::manifold::orient`]). `triangles` must not be
+/// empty.
+pub fn evaluate(
+    triangles: &[Triangle<3>],
+    down: Vector<3>,
+    max_angle: Scalar,
+) -> Orientation {
+    assert!(!triangles.is_empty(), "Can't orient empty mesh");
+
+    let mass = MassProperties::compute(triangles, 1.);
+    evaluate_with_mass(triangles, &mass, down, max_angle)
+}
+
+/// Find the triangles that need support, with the given side facing down
+///
+/// See [`evaluate`] for the meaning of the arguments.
+pub fn overhangs(
+    triangles: &[Triangle<3>],
+    down: Vector<3>,
+    max_angle: Scalar,
+) -> Vec<Triangle<3>> {
+    if triangles.is_empty() {
+        return Vec::new();
+    }
+
+    let bed = Bed::new(triangles, down, max_angle);
+    triangles
+        .iter()
+        .filter(|triangle| bed.support(triangle).is_some())
+        .copied()
+        .collect()
+}
+
+/// How a solid is oriented for printing, and how well it prints that way
+///
+/// See [`evaluate`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Orientation {
+    /// The direction in model space that points towards the print bed
+    ///
+    /// Has a length of one.
+    pub down: Vector<3>,
+
+    /// The area of the surfaces that need support
+    pub overhang_area: f64,
+
+    /// The approximate volume of the support material
+    pub support_volume: f64,
+
+    /// The height of the solid above the print bed
+    pub height: f64,
+
+    /// The height of the center of mass above the print bed
+    pub center_of_mass_height: f64,
+}
+
+/// The print bed, below a solid that is oriented in a given direction
+struct Bed {
+    down: Vector3<f64>,
+
+    /// The height of the print bed, measured along the up direction
+    level: f64,
+
+    /// Points this close to the print bed are resting on it
+    epsilon: f64,
+
+    /// Surfaces whose normal points down more steeply than this need support
+    min_dot: f64,
+}
+
+impl Bed {
+    fn new(
+        triangles: &[Triangle<3>],
+        down: Vector<3>,
+        max_angle: Scalar,
+    ) -> Self {
+        let down = down.to_na().normalize();
+
+        let heights = triangles
+            .iter()
+            .flat_map(|triangle| triangle.points())
+            .map(|point| -point.coords.to_na().dot(&down));
+        let (min, max) = heights
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), height| {
+                (min.min(height), max.max(height))
+            });
+
+        // A surface that overhangs by `max_angle` from the vertical, has a
+        // normal that is `max_angle` away from the horizontal.
+        let min_dot = max_angle.into_f64().sin();
+
+        Self {
+            down,
+            level: min,
+            epsilon: (max - min) * HEIGHT_EPSILON,
+            min_dot,
+        }
+    }
+
+    /// The height of a point above the print bed
+    fn height(&self, point: &Vector3<f64>) -> f64 {
+        -point.dot(&self.down) - self.level
+    }
+
+    /// Compute the support that a triangle needs
+    ///
+    /// Returns the area of the triangle and the volume of the support below
+    /// it, or `None`, if the triangle doesn't need support.
+    fn support(&self, triangle: &Triangle<3>) -> Option<(f64, f64)> {
+        let (normal, area) = normal_and_area(triangle)?;
+
+        let steepness = normal.dot(&self.down);
+        if steepness <= self.min_dot {
+            return None;
+        }
+
+        let points = triangle.points().map(|point| point.coords.to_na());
+        let heights = points.map(|point| self.height(&point));
+        if heights.iter().all(|&height| height <= self.epsilon) {
+            // The triangle rests on the print bed.
+            return None;
+        }
+
+        // The triangle's projection onto the print bed, times the average
+        // height of its corners.
+        let height = heights.iter().sum::<f64>() / 3.;
+        Some((area, area * steepness * height))
+    }
+}
+
+fn evaluate_with_mass(
+    triangles: &[Triangle<3>],
+    mass: &MassProperties,
+    down: Vector<3>,
+    max_angle: Scalar,
+) -> Orientation {
+    let bed = Bed::new(triangles, down, max_angle);
+
+    let mut overhang_area = 0.;
+    let mut support_volume = 0.;
+    let mut height: f64 = 0.;
+    for triangle in triangles {
+        for point in triangle.points() {
+            height = height.max(bed.height(&point.coords.to_na()));
+        }
+
+        if let Some((area, volume)) = bed.support(triangle) {
+            overhang_area += area;
+            support_volume += volume;
+        }
+    }
+
+    let center_of_mass = mass.center_of_mass.coords.to_na();
+
+    Orientation {
+        down: Vector::from(bed.down),
+        overhang_area,
+        support_volume,
+        height,
+        center_of_mass_height: bed.height(&center_of_mass),
+    }
+}
+
+/// Compute the unit normal and the area of a triangle
+///
+/// Returns `None`, if the triangle is degenerate.
+fn normal_and_area(triangle: &Triangle<3>) -> Option<(Vector3<f64>, f64)> {
+    let [a, b, c] = triangle.points().map(|point| point.coords.to_na());
+    let cross = (b - a).cross(&(c - a));
+
+    let area = cross.norm() / 2.;
+    let normal = cross.try_normalize(0.)?;
+
+    Some((normal, area))
+}
+
+/// Directions that are closer than this are considered the same
+///
+/// This is the difference of the dot product of two unit vectors from one.
+const DIRECTION_EPSILON: f64 = 1e-9;
+
+/// Points closer than this to the print bed are resting on it
+///
+/// This is relative to the height of the solid.
+const HEIGHT_EPSILON: f64 = 1e-9;
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{Point, Scalar, Triangle, Vector};
+
+    use super::{evaluate, overhangs, suggest, DEFAULT_MAX_ANGLE};
+
+    /// An L-shaped solid, lying flat, 10 mm thick
+    ///
+    /// One arm points in +x, the other in +y. Standing on the end of either
+    /// arm, the other one overhangs by 10 x 10 mm, 10 mm above the print bed.
+    fn l_shape() -> Vec<Triangle<3>> {
+        let outline = [
+            [0., 0.],
+            [20., 0.],
+            [20., 10.],
+            [10., 10.],
+            [10., 20.],
+            [0., 20.],
+        ];
+        let depth = 10.;
+
+        let point = |[x, y]: [f64; 2], z: f64| Point::from([x, y, z]);
+
+        let mut triangles = Vec::new();
+
+        // The bottom faces down, the top faces up.
+        for (z, flip) in [(0., true), (depth, false)] {
+            for [a, b, c] in [
+                [outline[0], outline[1], outline[2]],
+                [outline[0], outline[2], outline[3]],
+                [outline[0], outline[3], outline[4]],
+                [outline[0], outline[4], outline[5]],
+            ] {
+                let [a, b, c] = [point(a, z), point(b, z), point(c, z)];
+                triangles.push(if flip {
+                    Triangle::from([a, c, b])
+                } else {
+                    Triangle::from([a, b, c])
+                });
+            }
+        }
+
+        for i in 0..outline.len() {
+            let [a, b] = [outline[i], outline[(i + 1) % outline.len()]];
+            triangles.push(Triangle::from([
+                point(a, 0.),
+                point(b, 0.),
+                point(b, depth),
+            ]));
+            triangles.push(Triangle::from([
+                point(a, 0.),
+                point(b, depth),
+                point(a, depth),
+            ]));
+        }
+
+        triangles
+    }
+
+    fn max_angle() -> Scalar {
+        Scalar::from_f64(DEFAULT_MAX_ANGLE)
+    }
+
+    #[test]
+    fn evaluate_should_find_overhangs() {
+        let triangles = l_shape();
+
+        // Lying flat, nothing overhangs.
+        let flat =
+            evaluate(&triangles, Vector::from([0., 0., -1.]), max_angle());
+        assert_eq!(flat.overhang_area, 0.);
+        assert_eq!(flat.support_volume, 0.);
+        assert!((flat.height - 10.).abs() < 1e-9);
+        assert!((flat.center_of_mass_height - 5.).abs() < 1e-9);
+
+        // Standing on the end of the x arm, the y arm overhangs.
+        let standing =
+            evaluate(&triangles, Vector::from([1., 0., 0.]), max_angle());
+        assert!((standing.overhang_area - 100.).abs() < 1e-9);
+        assert!((standing.support_volume - 1000.).abs() < 1e-9);
+        assert!((standing.height - 20.).abs() < 1e-9);
+
+        let overhangs =
+            overhangs(&triangles, Vector::from([1., 0., 0.]), max_angle());
+        assert_eq!(overhangs.len(), 2);
+    }
+
+    #[test]
+    fn suggest_should_prefer_orientations_without_support() {
+        let triangles = l_shape();
+        let orientations = suggest(&triangles, max_angle());
+
+        let best = &orientations[0];
+        assert_eq!(best.support_volume, 0.);
+
+        // Lying on the side of either arm requires no support either, but
+        // lying flat keeps the center of mass lowest.
+        let [_, _, z]: [f64; 3] = best.down.to_na().into();
+        assert!((z.abs() - 1.).abs() < 1e-9);
+
+        for pair in orientations.windows(2) {
+            assert!(pair[0].support_volume <= pair[1].support_volume);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::BTreeSet;
+
+use crate::math::{Aabb, Scalar, Triangle};
+
+/// Statistics about a triangle mesh
+pub struct Stats {
+    /// The number of triangles
+    pub triangles: usize,
+
+    /// The number of distinct vertices
+    pub vertices: usize,
+
+    /// The axis-aligned bounding box of all vertices
+    pub aabb: Aabb<3>,
+
+    /// The total area of all triangles
+    pub area: Scalar,
+
+    /// The volume enclosed by the mesh
+    ///
+    /// This is only meaningful, if the mesh is closed and consistently
+    /// oriented (see [`super::manifold::orient`]). The volume is negative, if
+    /// the triangles face inwards.
+    pub volume: Scalar,
+}
+
+impl Stats {
+    /// Compute statistics for the given triangles
+    ///
+    /// `triangles` must not be empty.
+    pub fn compute(triangles: &[Triangle<3>]) -> Self {
+        assert!(!triangles.is_empty(), "Can't compute stats of empty mesh");
+
+        let vertices: BTreeSet<_> = triangles
+            .iter()
+            .flat_map(|triangle| triangle.points())
+            .collect();
+
+        let mut area = Scalar::ZERO;
+        let mut volume = Scalar::ZERO;
+
+        for triangle in triangles {
+            let [a, b, c] = triangle.points();
+
+            area += (b - a).cross(&(c - a)).magnitude() / 2.;
+
+            // Signed volume of the tetrahedron between the triangle and the
+            // origin. Summed up over a closed mesh, the parts outside of the
+            // mesh cancel each other out.
+            volume += a.coords.dot(&b.coords.cross(&c.coords)) / 6.;
+        }
+
+        Self {
+            triangles: triangles.len(),
+            vertices: vertices.len(),
+            aabb: Aabb::<3>::from_points(vertices),
+            area,
+            volume,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{Point, Scalar, Triangle};
+
+    use super::Stats;
+
+    #[test]
+    fn compute() {
+        let a = Point::from([0., 0., 0.]);
+        let b = Point::from([1., 0., 0.]);
+        let c = Point::from([0., 1., 0.]);
+        let d = Point::from([0., 0., 1.]);
+
+        let tetrahedron = [
+            Triangle::from([a, c, b]),
+            Triangle::from([a, b, d]),
+            Triangle::from([b, c, d]),
+            Triangle::from([c, a, d]),
+        ];
+
+        let stats = Stats::compute(&tetrahedron);
+
+        assert_eq!(stats.triangles, 4);
+        assert_eq!(stats.vertices, 4);
+        assert_eq!(stats.aabb.min, a);
+        assert_eq!(stats.aabb.max, Point::from([1., 1., 1.]));
+        assert_eq!(stats.volume, Scalar::from_f64(1. / 6.));
+    }
+}
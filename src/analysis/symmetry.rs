@@ -0,0 +1,412 @@
+use std::{collections::BTreeSet, f64::consts::TAU};
+
+use nalgebra::{Matrix3, Point3, Rotation3, Unit, Vector3};
+use parry3d_f64::{query::PointQuery as _, shape::TriMesh};
+
+use crate::{
+    math::{Point, Scalar, Triangle, Vector},
+    mesh::IndexOverflow,
+};
+
+use super::deviation;
+
+/// The highest order of rotational symmetry that is detected
+///
+/// Axes with a higher order are either reported with an order that divides
+/// their actual one, or not at all.
+const MAX_ORDER: u32 = 24;
+
+/// The maximum number of vertices that candidate planes are derived from
+const MAX_CANDIDATE_VERTICES: usize = 64;
+
+/// Detect the planes and axes of symmetry of a solid
+///
+/// A plane of symmetry mirrors the solid onto itself. An axis of symmetry
+/// does the same by rotating it. Both always pass through the centroid of the
+/// solid's surface, which is where the search starts.
+///
+/// Candidates are derived from the principal axes of the surface, and from the
+/// vertices that are farthest from its centroid, as any symmetry must map
+/// those onto each other. A candidate is accepted, if the vertices of the
+/// transformed mesh, the midpoints of its edges, and the centroids of its
+/// triangles are within `tolerance` of the original mesh. The tolerance must be
+/// at least as large as the one the solid was triangulated with, or curved
+/// surfaces won't be recognized as symmetric.
+///
+/// Axes around which the solid can be rotated by any angle, like the axis of a
+/// cylinder, are reported as [`Order::Continuous`]. There's an infinite number
+/// of planes that contain such an axis, so those are not reported.
+///
+/// Works on any triangle mesh, regardless of the orientation of its triangles.
+/// `triangles` must not be empty. Returns an error, if they have too many
+/// vertices.
+pub fn detect(
+    triangles: &[Triangle<3>],
+    tolerance: Scalar,
+) -> Result<Symmetry, IndexOverflow> {
+    assert!(!triangles.is_empty(), "Can't detect symmetry of empty mesh");
+    assert!(
+        tolerance > Scalar::ZERO,
+        "Tolerance must be larger than zero"
+    );
+
+    let (centroid, principal_axes) = match principal_axes(triangles) {
+        Some(principal_axes) => principal_axes,
+        None => return Ok(Symmetry::default()),
+    };
+
+    let checker = Checker::new(triangles, centroid, tolerance)?;
+
+    // Two directions are considered the same, if they can't be told apart
+    // within the tolerance, at the outermost vertex of the mesh.
+    let max_radius = checker
+        .vertices
+        .iter()
+        .map(|vertex| (vertex - centroid).norm())
+        .fold(0., f64::max);
+    let max_angle = tolerance.into_f64() / max_radius;
+
+    let mut normals = Directions::new(max_angle);
+    for axis in principal_axes {
+        normals.push(axis);
+    }
+
+    let farthest: Vec<_> = checker
+        .vertices
+        .iter()
+        .map(|vertex| vertex - centroid)
+        .filter(|vertex| vertex.norm() >= max_radius - tolerance.into_f64())
+        .take(MAX_CANDIDATE_VERTICES)
+        .collect();
+
+    // Any plane of symmetry either mirrors the first of the farthest vertices
+    // onto another one, or contains it.
+    if let Some((first, others)) = farthest.split_first() {
+        for other in others {
+            normals.push(first - other);
+            normals.push(first.cross(other));
+        }
+        for axis in principal_axes {
+            normals.push(axis.cross(first));
+        }
+    }
+
+    let planes: Vec<_> = normals
+        .directions
+        .into_iter()
+        .filter(|normal| checker.is_symmetric(|point| reflect(point, normal)))
+        .collect();
+
+    // Any two planes of symmetry intersect in an axis of symmetry.
+    let mut directions = Directions::new(max_angle);
+    for axis in principal_axes {
+        directions.push(axis);
+    }
+    for (i, a) in planes.iter().enumerate() {
+        for b in &planes[i + 1..] {
+            directions.push(a.cross(b));
+        }
+    }
+
+    let axes: Vec<_> = directions
+        .directions
+        .into_iter()
+        .filter_map(|direction| {
+            let order = checker.order(&direction)?;
+            Some((direction, order))
+        })
+        .collect();
+
+    let planes = planes
+        .into_iter()
+        .filter(|normal| {
+            // Skip the planes that contain an axis of continuous symmetry.
+            !axes.iter().any(|(direction, order)| {
+                *order == Order::Continuous
+                    && normal.dot(direction).abs() <= max_angle.sin()
+            })
+        })
+        .map(|normal| Plane {
+            origin: Point::from(centroid),
+            normal: Vector::from(normal),
+        })
+        .collect();
+    let axes = axes
+        .into_iter()
+        .map(|(direction, order)| Axis {
+            origin: Point::from(centroid),
+            direction: Vector::from(direction),
+            order,
+        })
+        .collect();
+
+    Ok(Symmetry { planes, axes })
+}
+
+/// The symmetry of a solid, as detected by [`detect`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Symmetry {
+    /// The planes of symmetry
+    pub planes: Vec<Plane>,
+
+    /// The axes of symmetry
+    pub axes: Vec<Axis>,
+}
+
+/// A plane of symmetry
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    /// A point on the plane
+    pub origin: Point<3>,
+
+    /// The normal of the plane, with a length of one
+    pub normal: Vector<3>,
+}
+
+/// An axis of symmetry
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Axis {
+    /// A point on the axis
+    pub origin: Point<3>,
+
+    /// The direction of the axis, with a length of one
+    pub direction: Vector<3>,
+
+    /// The order of the rotational symmetry around the axis
+    pub order: Order,
+}
+
+/// The order of an [`Axis`] of symmetry
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Order {
+    /// The solid maps onto itself, when rotated by a full turn, divided by
+    /// this number
+    Finite(u32),
+
+    /// The solid maps onto itself, when rotated by any angle
+    Continuous,
+}
+
+/// Compute the centroid and principal axes of the surface of a mesh
+///
+/// Returns `None`, if the mesh has no area.
+fn principal_axes(
+    triangles: &[Triangle<3>],
+) -> Option<(Point3<f64>, [Vector3<f64>; 3])> {
+    let mut area = 0.;
+    let mut first_moment = Vector3::zeros();
+    let mut second_moment = Matrix3::zeros();
+
+    for triangle in triangles {
+        let [a, b, c] = triangle.points().map(|point| point.to_na().coords);
+        let triangle_area = (b - a).cross(&(c - a)).norm() / 2.;
+        let sum = a + b + c;
+
+        area += triangle_area;
+        first_moment += sum * (triangle_area / 3.);
+        second_moment += (a * a.transpose()
+            + b * b.transpose()
+            + c * c.transpose()
+            + sum * sum.transpose())
+            * (triangle_area / 12.);
+    }
+
+    if area == 0. {
+        return None;
+    }
+
+    let centroid = first_moment / area;
+    let covariance = second_moment / area - centroid * centroid.transpose();
+
+    let eigenvectors = covariance.symmetric_eigen().eigenvectors;
+    let axes = [0, 1, 2].map(|i| eigenvectors.column(i).into_owned());
+
+    Some((Point3::from(centroid), axes))
+}
+
+fn reflect(point: &Point3<f64>, normal: &Vector3<f64>) -> Point3<f64> {
+    point - normal * (2. * point.coords.dot(normal))
+}
+
+/// Checks whether transforms map a mesh onto itself
+struct Checker {
+    centroid: Point3<f64>,
+    tolerance: Scalar,
+
+    mesh: TriMesh,
+    vertices: Vec<Point3<f64>>,
+
+    /// Points on the mesh between the vertices
+    ///
+    /// These are the midpoints of the edges and the centroids of the
+    /// triangles.
+    samples: Vec<Point3<f64>>,
+}
+
+impl Checker {
+    fn new(
+        triangles: &[Triangle<3>],
+        centroid: Point3<f64>,
+        tolerance: Scalar,
+    ) -> Result<Self, IndexOverflow> {
+        let vertices: BTreeSet<_> = triangles
+            .iter()
+            .flat_map(|triangle| triangle.points())
+            .collect();
+
+        let samples = triangles
+            .iter()
+            .flat_map(|triangle| {
+                let [a, b, c] = triangle.points().map(Point::to_na);
+                [
+                    nalgebra::center(&a, &b),
+                    nalgebra::center(&b, &c),
+                    nalgebra::center(&c, &a),
+                    Point3::from((a.coords + b.coords + c.coords) / 3.),
+                ]
+            })
+            .collect();
+
+        Ok(Self {
+            centroid,
+            tolerance,
+
+            mesh: deviation::tri_mesh(triangles)?,
+            vertices: vertices.into_iter().map(Point::to_na).collect(),
+            samples,
+        })
+    }
+
+    /// Determine the order of the rotational symmetry around an axis
+    ///
+    /// Returns `None`, if the axis is not an axis of symmetry.
+    fn order(&self, direction: &Vector3<f64>) -> Option<Order> {
+        let direction = Unit::new_unchecked(*direction);
+        let rotation = |angle| {
+            let rotation = Rotation3::from_axis_angle(&direction, angle);
+            move |point: &Point3<f64>| rotation * point
+        };
+
+        // An angle of one radian is not a fraction of a full turn. If rotating
+        // by that works, rotating by any angle does.
+        if self.is_symmetric(rotation(1.)) {
+            return Some(Order::Continuous);
+        }
+
+        (2..=MAX_ORDER)
+            .rev()
+            .find(|&order| self.is_symmetric(rotation(TAU / order as f64)))
+            .map(Order::Finite)
+    }
+
+    /// Check whether a transform maps the mesh onto itself
+    ///
+    /// The transform is relative to the centroid.
+    fn is_symmetric(
+        &self,
+        transform: impl Fn(&Point3<f64>) -> Point3<f64>,
+    ) -> bool {
+        let transform = |point: &Point3<f64>| {
+            let point = Point3::from(point - self.centroid);
+            transform(&point) + self.centroid.coords
+        };
+
+        // Checking the vertices first is much faster than checking the whole
+        // surface, and rules out most candidates.
+        let tolerance = self.tolerance.into_f64();
+        let vertices_match = self.vertices.iter().all(|vertex| {
+            self.mesh.distance_to_local_point(&transform(vertex), false)
+                <= tolerance
+        });
+        if !vertices_match {
+            return false;
+        }
+
+        self.samples.iter().all(|sample| {
+            self.mesh.distance_to_local_point(&transform(sample), false)
+                <= tolerance
+        })
+    }
+}
+
+/// A set of unit vectors, ignoring their sign
+struct Directions {
+    max_angle: f64,
+    directions: Vec<Vector3<f64>>,
+}
+
+impl Directions {
+    fn new(max_angle: f64) -> Self {
+        Self {
+            max_angle,
+            directions: Vec::new(),
+        }
+    }
+
+    /// Add a direction, unless it has no length, or is already in the set
+    fn push(&mut self, direction: Vector3<f64>) {
+        let direction = match direction.try_normalize(f64::EPSILON) {
+            Some(direction) => direction,
+            None => return,
+        };
+
+        let min_cos = self.max_angle.cos();
+        if self
+            .directions
+            .iter()
+            .any(|existing| existing.dot(&direction).abs() >= min_cos)
+        {
+            return;
+        }
+
+        self.directions.push(direction);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{math::Scalar, testing::Evaluated};
+
+    use super::{detect, Order};
+
+    fn prism(points: Vec<[f64; 2]>, length: f64) -> Evaluated {
+        let sweep = fj::Sweep {
+            shape: fj::Sketch::from_points(points).into(),
+            length,
+        };
+        Evaluated::new(&sweep.into())
+    }
+
+    fn orders(evaluated: &Evaluated) -> (usize, Vec<Order>) {
+        let symmetry =
+            detect(evaluated.triangles(), Scalar::from_f64(1e-6)).unwrap();
+
+        let mut orders: Vec<_> =
+            symmetry.axes.iter().map(|axis| axis.order).collect();
+        orders.sort_by_key(|order| match order {
+            Order::Finite(order) => *order,
+            Order::Continuous => u32::MAX,
+        });
+
+        (symmetry.planes.len(), orders)
+    }
+
+    #[test]
+    fn detect_symmetry_of_prisms() {
+        // A cuboid with three different side lengths has three planes of
+        // symmetry, which intersect in three axes of order 2.
+        let cuboid = prism(vec![[0., 0.], [3., 0.], [3., 2.], [0., 2.]], 1.);
+        assert_eq!(orders(&cuboid), (3, vec![Order::Finite(2); 3]));
+
+        // A square prism adds two diagonal planes, and the axis along its
+        // length has order 4.
+        let square = prism(vec![[0., 0.], [1., 0.], [1., 1.], [0., 1.]], 3.);
+        let mut expected = vec![Order::Finite(2); 4];
+        expected.push(Order::Finite(4));
+        assert_eq!(orders(&square), (5, expected));
+
+        // A right triangle is only symmetric across its middle.
+        let wedge = prism(vec![[0., 0.], [2., 0.], [0., 1.]], 1.);
+        assert_eq!(orders(&wedge), (1, vec![]));
+    }
+}
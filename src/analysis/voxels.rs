@@ -0,0 +1,567 @@
+use thiserror::Error;
+
+use crate::math::{Aabb, Point, Scalar, Triangle, Vector};
+
+/// The maximum number of voxels in a [`Grid`]
+///
+/// Every voxel takes up memory, whether it's occupied or not. This keeps a
+/// voxel size that is small by mistake from exhausting it.
+pub const MAX_VOXELS: usize = 1 << 26;
+
+/// A regular grid of cubic voxels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Grid {
+    /// The corner of the grid with the smallest coordinates
+    pub origin: Point<3>,
+
+    /// The edge length of each voxel
+    pub size: Scalar,
+
+    /// The number of voxels along each axis
+    pub dims: [usize; 3],
+}
+
+impl Grid {
+    /// Create a grid that covers a bounding box
+    ///
+    /// Fails, if `size` isn't larger than zero, or if the grid would have more
+    /// than [`MAX_VOXELS`] voxels.
+    pub fn new(aabb: &Aabb<3>, size: Scalar) -> Result<Self, Error> {
+        if size <= Scalar::ZERO {
+            return Err(Error::InvalidSize(size.into_f64()));
+        }
+
+        let dims = aabb.size().components().map(|extent| {
+            // Always use at least one voxel, even for flat boxes.
+            (extent / size).into_f64().ceil().max(1.)
+        });
+
+        // Checked before converting, as the conversion saturates.
+        let voxels = dims.iter().product::<f64>();
+        if voxels > MAX_VOXELS as f64 {
+            return Err(Error::TooManyVoxels(voxels));
+        }
+
+        Ok(Self {
+            origin: aabb.min,
+            size,
+            dims: dims.map(|dim| dim as usize),
+        })
+    }
+
+    /// Create a grid that covers a bounding box, with `voxels` voxels along
+    /// its longest side
+    ///
+    /// `voxels` is rounded up to `1`. Returns `None`, if the bounding box has
+    /// no extent at all, or if the grid would be too large (see
+    /// [`Grid::new`]).
+    pub fn with_resolution(aabb: &Aabb<3>, voxels: usize) -> Option<Self> {
+        let longest = aabb
+            .size()
+            .components()
+            .into_iter()
+            .fold(Scalar::ZERO, Scalar::max);
+        if longest == Scalar::ZERO {
+            return None;
+        }
+
+        let size = longest / Scalar::from_f64(voxels.max(1) as f64);
+        Self::new(aabb, size).ok()
+    }
+
+    /// Compute the center of a voxel
+    pub fn center(&self, [x, y, z]: [usize; 3]) -> Point<3> {
+        let size = self.size.into_f64();
+        let offset = [x, y, z].map(|i| (i as f64 + 0.5) * size);
+        self.origin + Vector::from(offset)
+    }
+
+    /// Determine which voxels are within a closed triangle mesh
+    ///
+    /// A voxel is within the mesh, if its center is. This is determined by
+    /// casting a ray through each column of voxels along the z axis, and
+    /// counting how often it crosses the mesh. The orientation of the triangles
+    /// doesn't matter, but the mesh must not intersect itself.
+    ///
+    /// Rays that pass exactly through an edge or a vertex are counted as
+    /// crossing only one of the triangles that share it, which is why this
+    /// works for meshes that are aligned with the grid.
+    pub fn voxelize(&self, triangles: &[Triangle<3>]) -> Voxels {
+        let [nx, ny, nz] = self.dims;
+        let size = self.size.into_f64();
+        let origin = self.origin.to_na();
+
+        let mut crossings = vec![Vec::new(); nx * ny];
+
+        for triangle in triangles {
+            let points = triangle.points().map(|point| point.to_na());
+            let [a, mut b, mut c] = points.map(|point| [point.x, point.y]);
+            let [za, mut zb, mut zc] = points.map(|point| point.z);
+
+            let area = orient(&a, &b, &c);
+            if area == 0. {
+                // The triangle is parallel to the rays.
+                continue;
+            }
+            if area < 0. {
+                std::mem::swap(&mut b, &mut c);
+                std::mem::swap(&mut zb, &mut zc);
+            }
+            let area = area.abs();
+
+            // The range of columns that the triangle could cover
+            let range = |axis: usize, n: usize| {
+                let coords = [a[axis], b[axis], c[axis]];
+                let min = coords.iter().copied().fold(f64::INFINITY, f64::min);
+                let max =
+                    coords.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+                let to_index = |coord: f64| (coord - origin[axis]) / size - 0.5;
+                let start = to_index(min).ceil().max(0.) as usize;
+                let end = (to_index(max).floor() + 1.).clamp(0., n as f64);
+
+                start..end as usize
+            };
+
+            for x in range(0, nx) {
+                for y in range(1, ny) {
+                    let p = [
+                        origin.x + (x as f64 + 0.5) * size,
+                        origin.y + (y as f64 + 0.5) * size,
+                    ];
+
+                    let weights =
+                        [edge(&b, &c, &p), edge(&c, &a, &p), edge(&a, &b, &p)];
+                    let edges = [[&b, &c], [&c, &a], [&a, &b]];
+
+                    let covered = weights.iter().zip(edges).all(
+                        |(&weight, [start, end])| {
+                            weight > 0.
+                                || weight == 0. && is_top_left(start, end)
+                        },
+                    );
+                    if !covered {
+                        continue;
+                    }
+
+                    let z =
+                        (weights[0] * za + weights[1] * zb + weights[2] * zc)
+                            / area;
+                    crossings[x * ny + y].push(z);
+                }
+            }
+        }
+
+        let mut occupied = vec![false; nx * ny * nz];
+
+        for (column, crossings) in crossings.iter_mut().enumerate() {
+            crossings.sort_by(|a, b| {
+                // Can't panic, unless the mesh contains NaN.
+                a.partial_cmp(b).unwrap()
+            });
+
+            // If the number of crossings is odd, the mesh isn't closed. The
+            // last crossing doesn't have a partner then, and is ignored.
+            for interval in crossings.chunks_exact(2) {
+                let to_index = |z: f64| (z - origin.z) / size - 0.5;
+                let start = to_index(interval[0]).ceil().max(0.) as usize;
+                let end = to_index(interval[1]).ceil().clamp(0., nz as f64);
+
+                for z in start..end as usize {
+                    occupied[column * nz + z] = true;
+                }
+            }
+        }
+
+        Voxels {
+            grid: *self,
+            occupied,
+        }
+    }
+}
+
+/// Returned by [`Grid::new`]
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Voxel size must be larger than zero, but is {0}")]
+    InvalidSize(f64),
+
+    #[error(
+        "Grid would have {0} voxels, but can have at most {}; increase the \
+        voxel size",
+        MAX_VOXELS
+    )]
+    TooManyVoxels(f64),
+}
+
+/// A set of voxels in a [`Grid`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Voxels {
+    grid: Grid,
+    occupied: Vec<bool>,
+}
+
+impl Voxels {
+    /// Access the grid that the voxels are part of
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Access the occupancy of all voxels in the grid
+    ///
+    /// Contains one entry per voxel, which is `true`, if the voxel is in the
+    /// set. The voxel `[x, y, z]` is at index `(x * ny + y) * nz + z`, where
+    /// `[_, ny, nz]` are the [`Grid::dims`]. This is the layout that
+    /// simulations and other code that processes the grid as a whole need.
+    pub fn occupancy(&self) -> &[bool] {
+        &self.occupied
+    }
+
+    /// Indicate whether the set contains a voxel
+    ///
+    /// Returns `false` for voxels outside of the grid.
+    pub fn contains(&self, voxel: [usize; 3]) -> bool {
+        self.index(voxel)
+            .map(|index| self.occupied[index])
+            .unwrap_or(false)
+    }
+
+    /// Iterate over the voxels in the set
+    pub fn iter(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        let [_, ny, nz] = self.grid.dims;
+
+        self.occupied
+            .iter()
+            .enumerate()
+            .filter(|(_, &occupied)| occupied)
+            .map(move |(i, _)| [i / (ny * nz), i / nz % ny, i % nz])
+    }
+
+    /// The number of voxels in the set
+    pub fn count(&self) -> usize {
+        self.occupied.iter().filter(|&&occupied| occupied).count()
+    }
+
+    /// The total volume of the voxels in the set
+    pub fn volume(&self) -> Scalar {
+        let size = self.grid.size;
+        size * size * size * Scalar::from_f64(self.count() as f64)
+    }
+
+    /// Compute the voxels that are in this set, but not in `other`
+    ///
+    /// Both sets must be part of the same grid.
+    pub fn difference(&self, other: &Self) -> Self {
+        assert_eq!(self.grid, other.grid, "Voxels are in different grids");
+
+        let occupied = self
+            .occupied
+            .iter()
+            .zip(&other.occupied)
+            .map(|(&a, &b)| a && !b)
+            .collect();
+
+        Self {
+            grid: self.grid,
+            occupied,
+        }
+    }
+
+    /// Keep only the voxels for which `f` returns `true`
+    pub fn retain(&mut self, mut f: impl FnMut([usize; 3]) -> bool) {
+        let [_, ny, nz] = self.grid.dims;
+
+        for (i, occupied) in self.occupied.iter_mut().enumerate() {
+            if *occupied {
+                *occupied = f([i / (ny * nz), i / nz % ny, i % nz]);
+            }
+        }
+    }
+
+    /// Remove `layers` layers of voxels from the outside of the set
+    ///
+    /// Each layer consists of the voxels that have a face on the outside, so
+    /// the result only contains voxels that are more than `layers` voxels away
+    /// from the outside, along the axes.
+    pub fn erode(&self, layers: usize) -> Self {
+        let mut eroded = self.clone();
+
+        for _ in 0..layers {
+            let previous = eroded.clone();
+            eroded.retain(|voxel| {
+                (0..3).all(|axis| {
+                    [voxel[axis].wrapping_sub(1), voxel[axis] + 1]
+                        .into_iter()
+                        .all(|neighbor| {
+                            let mut voxel = voxel;
+                            voxel[axis] = neighbor;
+                            previous.contains(voxel)
+                        })
+                })
+            });
+        }
+
+        eroded
+    }
+
+    /// Add voxels, so that no two voxels touch only along an edge
+    ///
+    /// Where two voxels share an edge, but neither of the voxels next to both
+    /// of them is in the set, four faces of the [`boundary`] meet at that edge.
+    /// That makes the boundary non-manifold, which slicers and other tools
+    /// might reject. One of the voxels in between is added, to connect the two.
+    /// This can create new contacts of the same kind, so it's repeated, until
+    /// there are none left.
+    ///
+    /// [`boundary`]: Self::boundary
+    pub fn fill_edge_contacts(&mut self) {
+        loop {
+            let mut added = Vec::new();
+
+            for voxel in self.iter() {
+                for [i, j] in [[0, 1], [1, 2], [2, 0]] {
+                    // Each pair of voxels is found from the one with the
+                    // smaller coordinate along `i`. A neighbor below zero
+                    // wraps around, which puts it outside of the grid.
+                    for j_next in [voxel[j] + 1, voxel[j].wrapping_sub(1)] {
+                        let mut a = voxel;
+                        a[i] += 1;
+                        let mut b = voxel;
+                        b[j] = j_next;
+                        let mut diagonal = a;
+                        diagonal[j] = j_next;
+
+                        if self.contains(diagonal)
+                            && !self.contains(a)
+                            && !self.contains(b)
+                        {
+                            added.push(a);
+                        }
+                    }
+                }
+            }
+
+            if added.is_empty() {
+                break;
+            }
+            for voxel in added {
+                let index = self.index(voxel).expect("Voxel is in grid");
+                self.occupied[index] = true;
+            }
+        }
+    }
+
+    /// Create the triangles that bound the voxels
+    ///
+    /// Faces between two voxels of the set are left out, so the result is a
+    /// closed mesh, whose triangles face outwards. It is only manifold, if no
+    /// two voxels touch only along an edge (see
+    /// [`Voxels::fill_edge_contacts`]).
+    pub fn boundary(&self) -> Vec<Triangle<3>> {
+        let half = self.grid.size.into_f64() / 2.;
+        let mut triangles = Vec::new();
+
+        for voxel in self.iter() {
+            let center = self.grid.center(voxel);
+
+            for axis in 0..3 {
+                for sign in [-1., 1.] {
+                    // A neighbor below zero wraps around, which puts it
+                    // outside of the grid, just like it should be.
+                    let mut neighbor = voxel;
+                    neighbor[axis] = if sign < 0. {
+                        voxel[axis].wrapping_sub(1)
+                    } else {
+                        voxel[axis] + 1
+                    };
+                    if self.contains(neighbor) {
+                        continue;
+                    }
+
+                    let unit = |i: usize, length: f64| {
+                        let mut vector = [0.; 3];
+                        vector[i] = length;
+                        Vector::from(vector)
+                    };
+                    let face = center + unit(axis, sign * half);
+                    let u = unit((axis + 1) % 3, half);
+                    let v = unit((axis + 2) % 3, half);
+
+                    let mut corners = [
+                        face + -u + -v,
+                        face + u + -v,
+                        face + u + v,
+                        face + -u + v,
+                    ];
+                    if sign < 0. {
+                        corners.reverse();
+                    }
+
+                    let [a, b, c, d] = corners;
+                    triangles.push(Triangle::from([a, b, c]));
+                    triangles.push(Triangle::from([a, c, d]));
+                }
+            }
+        }
+
+        triangles
+    }
+
+    fn index(&self, [x, y, z]: [usize; 3]) -> Option<usize> {
+        let [nx, ny, nz] = self.grid.dims;
+        if x >= nx || y >= ny || z >= nz {
+            return None;
+        }
+
+        Some((x * ny + y) * nz + z)
+    }
+}
+
+/// Compute twice the signed area of a triangle in the xy plane
+fn orient(a: &[f64; 2], b: &[f64; 2], c: &[f64; 2]) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+/// Compute on which side of an edge a point is
+///
+/// The result is exactly negated, if the edge is reversed, so triangles that
+/// share the edge agree on it.
+fn edge(start: &[f64; 2], end: &[f64; 2], point: &[f64; 2]) -> f64 {
+    if start < end {
+        orient(start, end, point)
+    } else {
+        -orient(end, start, point)
+    }
+}
+
+/// Decide which of the triangles that share an edge a point on it belongs to
+///
+/// Of two triangles on either side of an edge, only one traverses it in a
+/// direction for which this returns `true`.
+fn is_top_left(start: &[f64; 2], end: &[f64; 2]) -> bool {
+    let [dx, dy] = [end[0] - start[0], end[1] - start[1]];
+    dy < 0. || dy == 0. && dx > 0.
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{
+        analysis::{manifold, stats::Stats},
+        math::{Aabb, Point, Scalar},
+        testing::{cuboid, Evaluated},
+    };
+
+    use super::{Error, Grid, Voxels};
+
+    #[test]
+    fn voxelize_cuboid() {
+        let cuboid = Evaluated::new(&cuboid([3., 2., 1.]).into());
+
+        // The grid is aligned such, that many voxel centers lie exactly on
+        // the edges of the cuboid's triangles. Each of those must be counted
+        // once.
+        let aabb = Aabb {
+            min: Point::from([-1.125, -1.125, -1.125]),
+            max: Point::from([4., 3., 2.]),
+        };
+        let grid = Grid::new(&aabb, Scalar::from_f64(0.25)).unwrap();
+        let voxels = grid.voxelize(cuboid.triangles());
+
+        assert_eq!(voxels.count(), 12 * 8 * 4);
+        assert_eq!(voxels.volume(), Scalar::from_f64(6.));
+        assert!(voxels.contains([4, 4, 4]));
+        assert!(!voxels.contains([3, 4, 4]));
+        assert!(!voxels.contains([16, 4, 4]));
+
+        let boundary = voxels.boundary();
+        let stats = Stats::compute(&boundary);
+        assert_abs_diff_eq!(stats.area, Scalar::from_f64(22.), epsilon = 1e-12);
+        assert_abs_diff_eq!(
+            stats.volume,
+            Scalar::from_f64(6.),
+            epsilon = 1e-12
+        );
+
+        // Only the voxels in the middle of the 3x2x1 cuboid remain.
+        let core = voxels.erode(1);
+        assert_eq!(core.count(), 10 * 6 * 2);
+        assert_eq!(voxels.erode(2).count(), 0);
+
+        let mut odd = voxels.clone();
+        odd.retain(|[x, _, _]| x % 2 == 1);
+        assert_eq!(odd.count(), voxels.count() / 2);
+
+        let empty = voxels.difference(&voxels);
+        assert_eq!(empty.count(), 0);
+        assert!(empty.boundary().is_empty());
+
+        let occupancy = voxels.occupancy();
+        assert_eq!(occupancy.len(), 21 * 17 * 13);
+        assert_eq!(occupancy.iter().filter(|&&occupied| occupied).count(), 384);
+        assert!(occupancy[(4 * 17 + 4) * 13 + 4]);
+    }
+
+    #[test]
+    fn grid_with_resolution() {
+        let aabb = Aabb {
+            min: Point::from([0., 0., 0.]),
+            max: Point::from([8., 2., 0.]),
+        };
+
+        let grid = Grid::with_resolution(&aabb, 16).unwrap();
+        assert_eq!(grid.size, Scalar::from_f64(0.5));
+        assert_eq!(grid.dims, [16, 4, 1]);
+
+        let point = Aabb {
+            min: Point::from([1., 1., 1.]),
+            max: Point::from([1., 1., 1.]),
+        };
+        assert!(Grid::with_resolution(&point, 16).is_none());
+    }
+
+    #[test]
+    fn grid_should_reject_invalid_sizes() {
+        let aabb = Aabb {
+            min: Point::from([0., 0., 0.]),
+            max: Point::from([8., 2., 1.]),
+        };
+
+        for size in [0., -1.] {
+            assert!(matches!(
+                Grid::new(&aabb, Scalar::from_f64(size)),
+                Err(Error::InvalidSize(_))
+            ));
+        }
+        assert!(matches!(
+            Grid::new(&aabb, Scalar::from_f64(1e-6)),
+            Err(Error::TooManyVoxels(_))
+        ));
+    }
+
+    #[test]
+    fn fill_edge_contacts_should_make_boundary_manifold() {
+        let aabb = Aabb {
+            min: Point::origin(),
+            max: Point::from([2., 2., 2.]),
+        };
+        let grid = Grid::new(&aabb, Scalar::ONE).unwrap();
+
+        // Two voxels that only share an edge
+        let mut voxels = Voxels {
+            grid,
+            occupied: vec![false; 8],
+        };
+        for voxel in [[0, 0, 1], [1, 1, 1]] {
+            let index = voxels.index(voxel).unwrap();
+            voxels.occupied[index] = true;
+        }
+        assert!(!manifold::check(&voxels.boundary()).is_empty());
+
+        voxels.fill_edge_contacts();
+        assert_eq!(voxels.count(), 3);
+        assert!(voxels.contains([1, 0, 1]));
+        assert!(manifold::check(&voxels.boundary()).is_empty());
+    }
+}
@@ -0,0 +1,80 @@
+//! Support for animated models
+//!
+//! An animated model is evaluated once per frame, with the normalized time of
+//! that frame passed in the [`fj::TIME`] parameter. This makes it possible to
+//! do motion studies of mechanisms, or to create animated documentation.
+
+use std::path::{Path, PathBuf};
+
+/// The normalized time of each frame of an animation
+///
+/// The first frame is at `0.0`, the last frame is at `1.0`, and the frames in
+/// between are evenly spaced. An animation with a single frame has that frame
+/// at `0.0`.
+pub fn times(frames: usize) -> impl Iterator<Item = f64> {
+    let last = frames.saturating_sub(1).max(1) as f64;
+    (0..frames).map(move |frame| frame as f64 / last)
+}
+
+/// The frame that is visible after `elapsed` seconds of playback
+///
+/// The animation takes `duration` seconds, then starts over.
+pub fn frame_at(elapsed: f64, duration: f64, frames: usize) -> usize {
+    assert!(frames > 0, "Animation has no frames");
+
+    let progress = (elapsed / duration).fract();
+    (progress * frames as f64) as usize % frames
+}
+
+/// Compute the path of the file for a single frame of an animation
+///
+/// The frame number is appended to the file stem, padded with zeros, so the
+/// files sort correctly. `spacer.png` becomes `spacer-07.png` for frame 7 of
+/// an animation with 30 frames, for example.
+pub fn frame_path(path: &Path, frame: usize, frames: usize) -> PathBuf {
+    let digits = frames.to_string().len();
+
+    let mut file_name = path.file_stem().unwrap_or_default().to_owned();
+    file_name.push(format!("-{:0digits$}", frame, digits = digits));
+    if let Some(extension) = path.extension() {
+        file_name.push(".");
+        file_name.push(extension);
+    }
+
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{frame_at, frame_path, times};
+
+    #[test]
+    fn times_should_cover_full_range() {
+        assert_eq!(times(1).collect::<Vec<_>>(), [0.]);
+        assert_eq!(times(3).collect::<Vec<_>>(), [0., 0.5, 1.]);
+    }
+
+    #[test]
+    fn frame_at_should_loop() {
+        assert_eq!(frame_at(0., 2., 4), 0);
+        assert_eq!(frame_at(1., 2., 4), 2);
+        assert_eq!(frame_at(1.99, 2., 4), 3);
+        assert_eq!(frame_at(2.5, 2., 4), 1);
+    }
+
+    #[test]
+    fn frame_path_should_number_frames() {
+        let path = Path::new("output/spacer.png");
+
+        assert_eq!(
+            frame_path(path, 7, 30),
+            PathBuf::from("output/spacer-07.png")
+        );
+        assert_eq!(
+            frame_path(path, 7, 100),
+            PathBuf::from("output/spacer-007.png")
+        );
+    }
+}
@@ -1,19 +1,23 @@
-use std::path::PathBuf;
+use std::{
+    num::{NonZeroU32, NonZeroUsize},
+    path::PathBuf,
+};
+
+use fj_host::{
+    cam::Operation,
+    convention::{Handedness, UpAxis},
+    export::Units,
+    lattice::Pattern,
+    model::Model,
+    solve::Property,
+    stackup::{Distribution, Measurement, Variation},
+};
 
 /// Fornjot - Experimental CAD System - Host Application
 #[derive(clap::Parser)]
 pub struct Args {
-    /// The model to open
-    #[clap(short, long, default_value = "cuboid")]
-    pub model: String,
-
-    /// Export model to this path
-    #[clap(short, long)]
-    pub export: Option<PathBuf>,
-
-    /// Parameters for the model, each in the form `key=value`
-    #[clap(short, long)]
-    pub parameters: Vec<String>,
+    #[clap(subcommand)]
+    pub command: Command,
 }
 
 impl Args {
@@ -25,3 +29,673 @@ impl Args {
         <Self as clap::Parser>::parse()
     }
 }
+
+#[derive(clap::Subcommand)]
+pub enum Command {
+    /// View a model, reloading it whenever its source code changes
+    View {
+        #[clap(flatten)]
+        model: ModelArgs,
+
+        /// Animate the model, by evaluating it for this many frames
+        #[clap(long)]
+        frames: Option<NonZeroUsize>,
+
+        /// The duration of one run of the animation, in seconds
+        #[clap(long, default_value = "2")]
+        duration: f64,
+
+        /// Show the toolpath from this G-code file along with the model
+        ///
+        /// Rapid moves and cutting moves are shown in different colors.
+        #[clap(long)]
+        toolpath: Option<PathBuf>,
+
+        /// Move the toolpath by this offset, to position it in model space
+        #[clap(
+            long,
+            number_of_values = 3,
+            value_names = &["X", "Y", "Z"],
+            allow_hyphen_values = true,
+            requires = "toolpath"
+        )]
+        toolpath_offset: Option<Vec<f64>>,
+
+        /// Derive the tolerance from the model's size on the screen
+        ///
+        /// The model is triangulated again as the camera moves, so that the
+        /// mesh deviates from the actual geometry by at most this many pixels.
+        /// This keeps the viewer responsive, by using coarse meshes for models
+        /// that appear small, while refining the mesh for close-ups.
+        #[clap(long, value_name = "PIXELS")]
+        screen_tolerance: Option<f64>,
+
+        /// The steepest overhang that can be printed without support
+        ///
+        /// The angle is in degrees, measured from the vertical. Surfaces that
+        /// overhang more steeply are highlighted, when pressing `8`. The model
+        /// is assumed to be built along its z-axis.
+        #[clap(long, value_name = "DEGREES", default_value = "45")]
+        overhang_angle: f64,
+
+        /// Load the configuration from this file
+        ///
+        /// Defaults to `fj.toml` in the current directory, if it exists. The
+        /// configuration defines the colors of the viewer, for example.
+        #[clap(long)]
+        config: Option<PathBuf>,
+
+        /// Export the model to this file, whenever it is rebuilt
+        ///
+        /// This keeps the file up to date while editing the model, for a
+        /// slicer that watches it, for example. The format is determined from
+        /// the file extension, and the export uses the default options of the
+        /// `export` command.
+        #[clap(long, value_name = "PATH")]
+        export_on_change: Option<PathBuf>,
+
+        /// Process the model on the server at this address
+        ///
+        /// The model is still built and evaluated locally, but the resulting
+        /// shapes are sent to the server, which does the expensive part of
+        /// triangulating them. Start the server using the `serve` command.
+        #[clap(long, value_name = "ADDRESS")]
+        remote: Option<String>,
+    },
+
+    /// Export a model to a file
+    ///
+    /// The file format is determined from the file extension of the output
+    /// path. Run the `formats` command to list the supported formats.
+    Export {
+        #[clap(flatten)]
+        model: ModelArgs,
+
+        /// The path to export the model to
+        #[clap(short, long)]
+        output: PathBuf,
+
+        /// Export an animation, by evaluating the model for this many frames
+        ///
+        /// Each frame is written to its own file, with the frame number
+        /// appended to the file name.
+        #[clap(long)]
+        frames: Option<NonZeroUsize>,
+
+        /// Write the text-based variant of the format (STL only)
+        #[clap(long)]
+        ascii: bool,
+
+        /// The units to write coordinates in (`mm`, `cm`, `m`, or `in`)
+        #[clap(long, default_value = "mm")]
+        units: Units,
+
+        /// Round coordinates to this many decimal places
+        #[clap(long, value_name = "DIGITS")]
+        precision: Option<u32>,
+
+        /// The axis that points up in the exported file (`y` or `z`)
+        ///
+        /// Slicers and most CAD applications expect `z`, while many game
+        /// engines and 3D viewers expect `y`.
+        #[clap(long, default_value = "z")]
+        up: UpAxis,
+
+        /// The handedness of the exported file's coordinate system
+        ///
+        /// Either `right` or `left`. Some game engines, like Unity, expect
+        /// `left`. The model is mirrored accordingly.
+        #[clap(long, default_value = "right")]
+        handedness: Handedness,
+
+        /// Use 16-bit vertex indices, if the mesh is small enough (PLY only)
+        #[clap(long)]
+        compact_indices: bool,
+
+        /// Make adjacent faces share the vertices along their common edges
+        ///
+        /// Applies to 3MF, PLY, and STL. Without it, the vertices of
+        /// neighboring faces can end up a tiny distance apart.
+        #[clap(long)]
+        weld: bool,
+
+        /// The edge length of the voxels, in millimeters (VTK only)
+        ///
+        /// Defaults to 1/128 of the longest side of the model's bounding box.
+        #[clap(long, value_name = "SIZE")]
+        voxel_size: Option<f64>,
+
+        /// Write the anchors that the model defines to this file, as JSON
+        ///
+        /// The anchors are written in the same units and coordinate system
+        /// as the exported model.
+        #[clap(long, value_name = "PATH")]
+        anchors: Option<PathBuf>,
+
+        /// Record completed frames in this file, to resume after interruption
+        ///
+        /// If the export is interrupted, run it again with the same
+        /// checkpoint. Frames that have been exported before, with the same
+        /// parameters, are skipped, unless their files have been deleted.
+        /// The checkpoint can't be resumed with a different model, output, or
+        /// options.
+        #[clap(long, value_name = "PATH")]
+        checkpoint: Option<PathBuf>,
+
+        /// Load the configuration from this file
+        ///
+        /// Defaults to `fj.toml` in the current directory, if it exists. The
+        /// configuration can define additional export formats.
+        #[clap(long)]
+        config: Option<PathBuf>,
+    },
+
+    /// List the file formats that models can be exported to
+    Formats {
+        /// Load the configuration from this file
+        ///
+        /// Defaults to `fj.toml` in the current directory, if it exists. The
+        /// formats it defines are listed too.
+        #[clap(long)]
+        config: Option<PathBuf>,
+    },
+
+    /// Render an image of a model, using a path tracer
+    ///
+    /// This is much slower than the real-time rendering used when viewing a
+    /// model, but results in higher-quality images, which are written to a
+    /// PNG file.
+    Render {
+        #[clap(flatten)]
+        model: ModelArgs,
+
+        /// The path of the PNG file to write the image to
+        #[clap(short, long)]
+        output: PathBuf,
+
+        /// The width of the image, in pixels
+        #[clap(long, default_value = "1920")]
+        width: NonZeroU32,
+
+        /// The height of the image, in pixels
+        #[clap(long, default_value = "1080")]
+        height: NonZeroU32,
+
+        /// The number of samples per pixel; more samples reduce noise
+        #[clap(long, default_value = "64")]
+        samples: NonZeroU32,
+
+        /// The angle around the model (in degrees) to render it from
+        #[clap(long, default_value = "30")]
+        azimuth: f64,
+
+        /// The angle above the model's x-y plane (in degrees) to render it from
+        #[clap(long, default_value = "30")]
+        elevation: f64,
+
+        /// Render an animation, by evaluating the model for this many frames
+        ///
+        /// Each frame is written to its own file, with the frame number
+        /// appended to the file name.
+        #[clap(long)]
+        frames: Option<NonZeroUsize>,
+
+        /// Also write an image of the model's faces to this PNG file
+        ///
+        /// The color of each pixel is the ID of the face that covers most of
+        /// it, plus one, as a 24-bit number. The image lines up with the
+        /// rendered one, so it can be used to pick faces in it.
+        #[clap(long, value_name = "PATH")]
+        face_ids: Option<PathBuf>,
+    },
+
+    /// Flatten the faces of a model into a template, written to an SVG file
+    ///
+    /// The model is split into pieces along its sharp edges. Each piece is
+    /// unrolled into the plane without stretching it, like a sheet-metal part
+    /// that is unfolded. This only works for pieces that are flat, or curved
+    /// in one direction only, like the side of a cylinder or a cone.
+    Flatten {
+        #[clap(flatten)]
+        model: ModelArgs,
+
+        /// The path of the SVG file to write the template to
+        #[clap(short, long)]
+        output: PathBuf,
+
+        /// The angle (in degrees) above which an edge is considered sharp
+        #[clap(long, default_value = "30")]
+        max_angle: f64,
+
+        /// The distance between the pieces of the template
+        #[clap(long, default_value = "5")]
+        spacing: f64,
+    },
+
+    /// Fill a model with a lattice, and write the result to an STL file
+    ///
+    /// A solid shell is kept around the outside of the model, and the inside
+    /// is replaced by a lattice, to make the part lighter. This is
+    /// experimental. The result is built from voxels, so its surface consists
+    /// of small steps.
+    Lattice {
+        #[clap(flatten)]
+        model: ModelArgs,
+
+        /// The path of the STL file to write the result to
+        #[clap(short, long)]
+        output: PathBuf,
+
+        /// The pattern of the lattice (`gyroid` or `grid`)
+        #[clap(long, default_value = "gyroid")]
+        pattern: Pattern,
+
+        /// The size of one cell of the pattern
+        #[clap(long, default_value = "10")]
+        cell_size: f64,
+
+        /// The thickness of the struts or walls of the pattern
+        #[clap(long, default_value = "1")]
+        thickness: f64,
+
+        /// The thickness of the solid shell around the lattice
+        #[clap(long, default_value = "1.2")]
+        shell: f64,
+
+        /// The size of the voxels that the result is built from
+        ///
+        /// Smaller voxels result in a smoother surface, but take longer to
+        /// compute, and result in bigger files.
+        #[clap(long, default_value = "0.25")]
+        resolution: f64,
+    },
+
+    /// List the parameters of a model, with their types and default values
+    ///
+    /// Only works for models that are defined using `#[fj::model]`.
+    Parameters {
+        /// The model to open, either by name or by the path of its directory
+        model: String,
+    },
+
+    /// Check whether the triangle mesh of a model is valid
+    ///
+    /// Exits with code 1, if the model is invalid, and with code 2, if the
+    /// model couldn't be validated due to another error.
+    Validate {
+        #[clap(flatten)]
+        model: ModelArgs,
+
+        /// Print the result as JSON, instead of as text
+        #[clap(long)]
+        json: bool,
+    },
+
+    /// Print statistics about a model
+    Stats {
+        #[clap(flatten)]
+        model: ModelArgs,
+
+        /// Also print the area and perimeter of each face
+        #[clap(long)]
+        faces: bool,
+
+        /// The material of the parts that the model assigns no material to
+        ///
+        /// Used to compute the mass and moments of inertia of 3-dimensional
+        /// models. Additional materials can be defined in the configuration.
+        #[clap(long)]
+        material: Option<String>,
+
+        /// Load the configuration from this file
+        ///
+        /// Defaults to `fj.toml` in the current directory, if it exists.
+        #[clap(long)]
+        config: Option<PathBuf>,
+
+        /// Print the result as JSON, instead of as text
+        #[clap(long)]
+        json: bool,
+    },
+
+    /// Evaluate a model, and print all results as a single JSON document
+    ///
+    /// The document contains the problems that `validate` finds, the
+    /// statistics that `stats` prints (including the measurements of each
+    /// face), the anchors of the model, and the paths of the files it was
+    /// exported to. This is meant for scripts and other tools, which can
+    /// consume it without parsing text. If the model can't be evaluated, the
+    /// document only contains the error. The exit codes are the same as for
+    /// `validate`.
+    Evaluate {
+        #[clap(flatten)]
+        model: ModelArgs,
+
+        /// Also export the model to this path; can be passed more than once
+        ///
+        /// The format is determined from the file extension, and the export
+        /// uses the default options of the `export` command.
+        #[clap(short, long)]
+        output: Vec<PathBuf>,
+
+        /// The material of the parts that the model assigns no material to
+        #[clap(long)]
+        material: Option<String>,
+
+        /// Load the configuration from this file
+        ///
+        /// Defaults to `fj.toml` in the current directory, if it exists.
+        #[clap(long)]
+        config: Option<PathBuf>,
+    },
+
+    /// Suggest how to orient a model for 3D printing
+    ///
+    /// Evaluates the model lying on each side of its bounding box, and on each
+    /// of its largest flat faces. Lists the orientations that require the
+    /// least support material first. Orientations that require the same
+    /// amount are ranked by the height of the center of mass above the print
+    /// bed, as lower ones are more stable.
+    Orient {
+        #[clap(flatten)]
+        model: ModelArgs,
+
+        /// The steepest overhang that can be printed without support
+        ///
+        /// The angle is in degrees, measured from the vertical.
+        #[clap(long, default_value = "45")]
+        max_angle: f64,
+
+        /// The number of orientations to list
+        #[clap(long, default_value = "3")]
+        count: usize,
+
+        /// Print the result as JSON, instead of as text
+        #[clap(long)]
+        json: bool,
+    },
+
+    /// Generate toolpaths for milling a 2-dimensional model, as G-code
+    ///
+    /// The model is cut out of, or into, stock material whose top is at
+    /// `z = 0`. All distances are in millimeters.
+    Cam {
+        #[clap(flatten)]
+        model: ModelArgs,
+
+        /// The path of the G-code file to write the toolpaths to
+        #[clap(short, long)]
+        output: PathBuf,
+
+        #[clap(flatten)]
+        settings: CamArgs,
+    },
+
+    /// Compare two versions of a model, or a model with two sets of parameters
+    ///
+    /// Reports the changes to the bounding box, area, and volume, as well as
+    /// the Hausdorff distance between both versions, and the volume of the
+    /// material that was added and removed.
+    Diff {
+        #[clap(flatten)]
+        model: ModelArgs,
+
+        /// The other version of the model (defaults to the same model)
+        ///
+        /// This can be the path to a model directory, for example one that an
+        /// older revision of the model was checked out into.
+        #[clap(long)]
+        other: Option<String>,
+
+        /// Parameters for the other version of the model
+        ///
+        /// These override the parameters passed using `--parameters`.
+        #[clap(long)]
+        other_parameters: Vec<String>,
+
+        /// Exit with code 1, if the Hausdorff distance exceeds this value
+        #[clap(long)]
+        max_distance: Option<f64>,
+
+        /// The size of the voxels that approximate the added and removed
+        /// material
+        ///
+        /// Defaults to a hundredth of the longest side of the bounding box.
+        #[clap(long)]
+        voxel_size: Option<f64>,
+
+        /// Write the added and removed material to this PLY file
+        ///
+        /// Added material is colored green, removed material red.
+        #[clap(long)]
+        material_output: Option<PathBuf>,
+
+        /// Print the result as JSON, instead of as text
+        #[clap(long)]
+        json: bool,
+    },
+
+    /// Find the value of a parameter, for which a model has a target property
+    ///
+    /// Evaluates the model repeatedly, adjusting the parameter until the
+    /// property is within the tolerance of the target value. The target value
+    /// must be between the values of the property at both ends of the range.
+    /// All other parameters keep the values that are passed using
+    /// `--parameters`.
+    Solve {
+        #[clap(flatten)]
+        model: ModelArgs,
+
+        /// The parameter to adjust
+        #[clap(long)]
+        parameter: String,
+
+        /// The property to measure
+        ///
+        /// One of `volume`, `area`, `mass` (in grams, requires `--density`),
+        /// `size-x`, `size-y`, or `size-z`.
+        #[clap(long)]
+        property: Property,
+
+        /// The value that the property should have
+        #[clap(long, allow_hyphen_values = true)]
+        target: f64,
+
+        /// The range to search the parameter in
+        ///
+        /// Defaults to the range of the parameter, if the model defines one.
+        #[clap(
+            long,
+            number_of_values = 2,
+            value_names = &["MIN", "MAX"],
+            allow_hyphen_values = true
+        )]
+        range: Option<Vec<f64>>,
+
+        /// The density of the material, in grams per cubic centimeter
+        ///
+        /// Used to compute the mass from the volume, assuming that the model
+        /// is in millimeters.
+        #[clap(long)]
+        density: Option<f64>,
+
+        /// How far the property may be from the target value
+        ///
+        /// Defaults to a thousandth of the target value.
+        #[clap(long)]
+        tolerance: Option<f64>,
+
+        /// The maximum number of times the model is evaluated
+        #[clap(long, default_value = "20")]
+        max_evaluations: usize,
+
+        /// Print the result as JSON, instead of as text
+        #[clap(long)]
+        json: bool,
+    },
+
+    /// Check how a model varies across manufacturing tolerances
+    ///
+    /// Evaluates the model many times, with each toleranced parameter set to
+    /// a random value within its tolerance band, and reports the distribution
+    /// of each measurement. All other parameters keep the values that are
+    /// passed using `--parameters`.
+    StackUp {
+        #[clap(flatten)]
+        model: ModelArgs,
+
+        /// A toleranced parameter, in the form `NAME=NOMINAL+-DEVIATION`
+        #[clap(long = "vary", required = true, allow_hyphen_values = true)]
+        variations: Vec<Variation>,
+
+        /// A measurement to take of each evaluation
+        ///
+        /// Either a property (`volume`, `area`, `mass`, `size-x`, `size-y`,
+        /// or `size-z`), or the width of the gap between two anchors that the
+        /// model defines, in the form `gap:FROM:TO`. The gap is measured along
+        /// the z-axis of the anchor `FROM`.
+        #[clap(long = "measure", required = true)]
+        measurements: Vec<Measurement>,
+
+        /// How parameter values are distributed (`uniform` or `normal`)
+        ///
+        /// For `normal`, the tolerance band covers three standard deviations.
+        #[clap(long, default_value = "normal")]
+        distribution: Distribution,
+
+        /// The number of times the model is evaluated
+        #[clap(long, default_value = "100")]
+        samples: usize,
+
+        /// The seed for the random parameter values
+        #[clap(long, default_value = "0")]
+        seed: u64,
+
+        /// The density of the material, in grams per cubic centimeter
+        ///
+        /// Required to measure the mass.
+        #[clap(long)]
+        density: Option<f64>,
+
+        /// Exit with code 1, if any gap is narrower than this in any sample
+        #[clap(long, allow_hyphen_values = true)]
+        min_gap: Option<f64>,
+
+        /// Print the result as JSON, instead of as text
+        #[clap(long)]
+        json: bool,
+    },
+
+    /// Compare a model against a scanned mesh (STL file)
+    Compare {
+        #[clap(flatten)]
+        model: ModelArgs,
+
+        /// The scanned mesh to compare the model against
+        #[clap(short, long)]
+        scan: PathBuf,
+
+        /// Maximum deviation from the model that a scanned point may have
+        #[clap(short, long, default_value = "0.1")]
+        tolerance: f64,
+
+        /// Write the deviation of each scanned point to this file
+        ///
+        /// Writes a CSV file, unless the path ends in `.ply`. In that case, a
+        /// point cloud is written, with each point colored according to its
+        /// deviation, up to the tolerance.
+        #[clap(long)]
+        deviation_output: Option<PathBuf>,
+
+        /// The axis that points up in the scanned mesh (`y` or `z`)
+        #[clap(long, default_value = "z")]
+        scan_up: UpAxis,
+
+        /// The handedness of the scanned mesh's coordinate system
+        #[clap(long, default_value = "right")]
+        scan_handedness: Handedness,
+    },
+
+    /// Process shapes for other instances of Fornjot, that connect over TCP
+    ///
+    /// Clients either send shapes, or refer to models in the `models/`
+    /// directory, which the server then builds. There is no authentication,
+    /// so only make the server reachable from trusted networks.
+    Serve {
+        /// The address to listen on
+        #[clap(long, default_value = "127.0.0.1:7878")]
+        address: String,
+    },
+}
+
+/// Arguments that configure toolpath generation
+#[derive(clap::Args)]
+pub struct CamArgs {
+    /// The machining operation to generate toolpaths for
+    #[clap(long, arg_enum, default_value = "contour")]
+    pub operation: Operation,
+
+    /// The diameter of the tool
+    #[clap(long)]
+    pub tool_diameter: f64,
+
+    /// The depth to cut to
+    #[clap(long)]
+    pub depth: f64,
+
+    /// The maximum depth to cut in a single pass (defaults to `--depth`)
+    #[clap(long)]
+    pub step_down: Option<f64>,
+
+    /// The distance between pocketing passes, as a fraction of the tool
+    /// diameter
+    #[clap(long, default_value = "0.4")]
+    pub stepover: f64,
+
+    /// The height above the stock, at which the tool can move freely
+    #[clap(long, default_value = "5")]
+    pub safe_height: f64,
+
+    /// The feed rate while cutting, in millimeters per minute
+    #[clap(long, default_value = "300")]
+    pub feed_rate: f64,
+
+    /// The feed rate while plunging, in millimeters per minute
+    #[clap(long, default_value = "100")]
+    pub plunge_rate: f64,
+
+    /// The spindle speed, in revolutions per minute
+    #[clap(long)]
+    pub spindle_speed: Option<f64>,
+}
+
+/// Arguments that select a model, shared by all commands
+#[derive(Clone, clap::Args)]
+pub struct ModelArgs {
+    /// The model to open, either by name or by the path of its directory
+    pub model: String,
+
+    /// Parameters for the model, each in the form `key=value`
+    #[clap(short, long)]
+    pub parameters: Vec<String>,
+
+    /// Simplify the sketches of a shape description, with this tolerance
+    ///
+    /// Removes points that are closer than the tolerance to the simplified
+    /// outline. Useful for descriptions that were converted from other
+    /// applications, whose curves consist of many more points than needed.
+    #[clap(long, value_name = "TOLERANCE")]
+    pub simplify: Option<f64>,
+}
+
+impl ModelArgs {
+    /// Refer to the selected model, as configured by the arguments
+    pub fn model(&self) -> Model {
+        let model = Model::new(self.model.clone());
+
+        match self.simplify {
+            Some(tolerance) => model.with_simplification(tolerance),
+            None => model,
+        }
+    }
+}
@@ -0,0 +1,399 @@
+//! Processing many shapes in parallel
+//!
+//! Exporting or rendering all frames of an animation, or all parts of an
+//! assembly, means processing many shapes that don't depend on each other. A
+//! [`Batch`] spreads that work across all cores. The faces of each shape are
+//! triangulated in parallel too (see [`evaluation`]), and all of it runs on
+//! the same thread pool, where idle threads steal work from busy ones.
+//!
+//! Processed shapes can take up a lot of memory, so a batch doesn't process
+//! all shapes at once. It works through them in windows of limited size, and
+//! hands each result to the caller, before it moves on to the next window.
+//!
+//! Batches can run for hours. A [`Checkpoint`] records which items have been
+//! completed, so a batch that was interrupted can be resumed, without
+//! processing those items again.
+//!
+//! [`evaluation`]: crate::evaluation
+
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{self, Read as _, Write as _},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    evaluation::{ProcessedShape, ShapeProcessor},
+    kernel::{self, algorithms::arena},
+    platform,
+};
+
+/// Processes many independent items in parallel
+#[derive(Clone, Copy, Debug)]
+pub struct Batch {
+    /// The maximum number of items that are processed at the same time
+    ///
+    /// This bounds the number of results that are kept in memory, before they
+    /// are handed to the caller. It is rounded up to `1`.
+    pub max_in_flight: usize,
+}
+
+impl Batch {
+    /// Create a batch that processes two items per thread at a time
+    ///
+    /// Having more items than threads keeps all threads busy, while items
+    /// with less work finish early.
+    pub fn new() -> Self {
+        Self {
+            max_in_flight: platform::num_threads() * 2,
+        }
+    }
+
+    /// Process the items, and pass the results to `done`, in order
+    ///
+    /// `work` is called with the index of each item and the item itself, on
+    /// any thread. `done` is called with the index and result of each item on
+    /// the calling thread, in the order of the items. If `done` returns an
+    /// error, no more items are processed, and the error is returned.
+    pub fn run<T, R, E>(
+        &self,
+        items: impl IntoIterator<Item = T>,
+        work: impl Fn(usize, T) -> R + Send + Sync,
+        mut done: impl FnMut(usize, R) -> Result<(), E>,
+    ) -> Result<(), E>
+    where
+        T: Send,
+        R: Send,
+    {
+        let mut items = items.into_iter().enumerate();
+
+        loop {
+            let window: Vec<_> =
+                items.by_ref().take(self.max_in_flight.max(1)).collect();
+            if window.is_empty() {
+                // Release the memory that was needed while processing, now
+                // that the batch is done.
+                arena::reset();
+                return Ok(());
+            }
+
+            let results = platform::map(window, |(i, item)| (i, work(i, item)));
+            for (i, result) in results {
+                done(i, result)?;
+            }
+        }
+    }
+
+    /// Process shapes into triangle meshes
+    ///
+    /// Works like [`Batch::run`], with each shape processed by `processor`.
+    pub fn process<'r, E>(
+        &self,
+        processor: ShapeProcessor,
+        shapes: impl IntoIterator<Item = &'r fj::Shape>,
+        done: impl FnMut(
+            usize,
+            Result<ProcessedShape, kernel::Error>,
+        ) -> Result<(), E>,
+    ) -> Result<(), E> {
+        self.run(shapes, |_, shape| processor.process(shape), done)
+    }
+}
+
+impl Default for Batch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A record of the completed items of a batch, kept in a file
+///
+/// Each item is identified by a key, like the parameters that a shape was
+/// created with. Once an item is completed, its key is appended to the file,
+/// together with the paths of the files that were written for it (its
+/// artifacts). The file is synced before [`Checkpoint::record`] returns, so
+/// the record survives a crash or power loss.
+///
+/// A checkpoint belongs to one job, which is described in the first line of
+/// the file. Everything about the job that affects the artifacts, like the
+/// export options, must be part of that description, as items are only
+/// compared by their key.
+#[derive(Debug)]
+pub struct Checkpoint {
+    file: File,
+    completed: HashMap<String, Vec<PathBuf>>,
+}
+
+impl Checkpoint {
+    /// Open the checkpoint at `path`, or create it, if it doesn't exist
+    ///
+    /// Returns [`Error::JobMismatch`], if the checkpoint exists, but belongs
+    /// to a different job.
+    pub fn open(path: impl AsRef<Path>, job: &str) -> Result<Self, Error> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+
+        // If there's no complete line, the checkpoint is new, or the process
+        // was interrupted, before the header was written.
+        let is_new = !contents.contains(&b'\n');
+        if is_new {
+            contents.clear();
+            file.set_len(0)?;
+
+            let header = Header {
+                job: job.to_owned(),
+            };
+            let mut line = serde_json::to_vec(&header)?;
+            line.push(b'\n');
+            file.write_all(&line)?;
+            file.sync_data()?;
+        }
+
+        let mut lines = contents.split(|&byte| byte == b'\n');
+
+        if !is_new {
+            // Can't be `None`. There's at least one line.
+            let line = lines.next().unwrap_or_default();
+
+            let header = serde_json::from_slice::<Header>(line)
+                .map_err(|_| Error::JobMismatch(None))?;
+            if header.job != job {
+                return Err(Error::JobMismatch(Some(header.job)));
+            }
+        }
+
+        // A line that can't be parsed was being written, when the process was
+        // interrupted. The item it belongs to is processed again.
+        let completed = lines
+            .filter_map(|line| serde_json::from_slice::<Entry>(line).ok())
+            .map(|entry| (entry.key, entry.artifacts))
+            .collect();
+
+        // The next entry must start on a line of its own.
+        if !contents.is_empty() && !contents.ends_with(b"\n") {
+            file.write_all(b"\n")?;
+        }
+
+        Ok(Self { file, completed })
+    }
+
+    /// Indicate whether the item with this key has been completed
+    ///
+    /// Items whose artifacts have been deleted since are not considered
+    /// completed, so they are processed again.
+    pub fn is_completed(&self, key: &str) -> bool {
+        match self.completed.get(key) {
+            Some(artifacts) => {
+                artifacts.iter().all(|artifact| artifact.exists())
+            }
+            None => false,
+        }
+    }
+
+    /// The number of items that have been completed
+    pub fn len(&self) -> usize {
+        self.completed.len()
+    }
+
+    /// Indicate whether no items have been completed yet
+    pub fn is_empty(&self) -> bool {
+        self.completed.is_empty()
+    }
+
+    /// Record that the item with this key has been completed
+    ///
+    /// Should only be called after its artifacts have been written.
+    pub fn record(
+        &mut self,
+        key: impl Into<String>,
+        artifacts: Vec<PathBuf>,
+    ) -> io::Result<()> {
+        let entry = Entry {
+            key: key.into(),
+            artifacts,
+        };
+
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.sync_data()?;
+
+        self.completed.insert(entry.key, entry.artifacts);
+        Ok(())
+    }
+}
+
+/// The first line in the file of a [`Checkpoint`]
+#[derive(Deserialize, Serialize)]
+struct Header {
+    job: String,
+}
+
+/// A line in the file of a [`Checkpoint`], after the header
+#[derive(Deserialize, Serialize)]
+struct Entry {
+    key: String,
+    artifacts: Vec<PathBuf>,
+}
+
+/// Returned by [`Checkpoint::open`]
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O error accessing checkpoint")]
+    Io(#[from] io::Error),
+
+    #[error("Error writing checkpoint")]
+    Json(#[from] serde_json::Error),
+
+    /// The checkpoint belongs to a different job
+    ///
+    /// Contains the description of that job, if the checkpoint has one.
+    #[error("Checkpoint belongs to a different job{}", match .0 {
+        Some(job) => format!(" ({})", job),
+        None => String::new(),
+    })]
+    JobMismatch(Option<String>),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        env, fs, process,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use crate::{evaluation::ShapeProcessor, testing::cuboid};
+
+    use super::{Batch, Checkpoint, Error};
+
+    #[test]
+    fn run_should_keep_order_and_bound_results_in_memory() {
+        let batch = Batch { max_in_flight: 3 };
+
+        let in_flight = AtomicUsize::new(0);
+        let mut max_in_flight = 0;
+        let mut order = Vec::new();
+
+        batch
+            .run(
+                0..10,
+                |i, item| {
+                    let n = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    assert_eq!(i, item);
+                    (item * 2, n)
+                },
+                |i, (result, n)| {
+                    max_in_flight = max_in_flight.max(n);
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                    order.push((i, result));
+                    Ok::<_, Infallible>(())
+                },
+            )
+            .unwrap();
+
+        assert_eq!(order, (0..10).map(|i| (i, i * 2)).collect::<Vec<_>>());
+        assert!(max_in_flight <= 3);
+
+        // Errors stop the batch.
+        let mut processed = 0;
+        let result = batch.run(
+            0..10,
+            |_, item| item,
+            |_, item| {
+                processed += 1;
+                if item == 4 {
+                    return Err(item);
+                }
+                Ok(())
+            },
+        );
+        assert_eq!(result, Err(4));
+        assert_eq!(processed, 5);
+    }
+
+    #[test]
+    fn process_should_match_sequential_processing() {
+        let shapes: Vec<fj::Shape> = (1..=5)
+            .map(|size| {
+                let size = size as f64;
+                cuboid([size, size, size]).into()
+            })
+            .collect();
+
+        let processor = ShapeProcessor::new();
+        let mut volumes = Vec::new();
+        Batch::new()
+            .process(processor, &shapes, |_, processed| {
+                volumes.push(processed?.volume().unwrap().into_f64());
+                Ok::<_, crate::kernel::Error>(())
+            })
+            .unwrap();
+
+        let expected: Vec<_> =
+            (1..=5).map(|size| f64::from(size).powi(3)).collect();
+        assert_eq!(volumes.len(), expected.len());
+        for (volume, expected) in volumes.iter().zip(expected) {
+            assert!((volume - expected).abs() < 1e-9, "{}", volume);
+        }
+    }
+
+    #[test]
+    fn checkpoint_should_survive_reopening_and_interrupted_writes() {
+        // Test runs can overlap, so each one uses its own files.
+        let name = format!("fj-host-test-checkpoint-{}", process::id());
+        let path = env::temp_dir().join(format!("{}.jsonl", name));
+        let artifact = env::temp_dir().join(format!("{}.stl", name));
+        fs::write(&artifact, b"").unwrap();
+
+        let job = "export cube";
+        let mut checkpoint = Checkpoint::open(&path, job).unwrap();
+        assert!(checkpoint.is_empty());
+        checkpoint.record("size=1", vec![artifact.clone()]).unwrap();
+        checkpoint.record("size=2", vec![]).unwrap();
+        drop(checkpoint);
+
+        // Simulate a crash, while the next entry was being written.
+        let mut contents = fs::read(&path).unwrap();
+        contents.extend_from_slice(br#"{"key":"size=3","artif"#);
+        fs::write(&path, contents).unwrap();
+
+        let mut checkpoint = Checkpoint::open(&path, job).unwrap();
+        assert_eq!(checkpoint.len(), 2);
+        assert!(checkpoint.is_completed("size=1"));
+        assert!(checkpoint.is_completed("size=2"));
+        assert!(!checkpoint.is_completed("size=3"));
+
+        checkpoint.record("size=3", vec![]).unwrap();
+        drop(checkpoint);
+
+        // Items whose artifacts are gone need to be processed again.
+        fs::remove_file(&artifact).unwrap();
+        let checkpoint = Checkpoint::open(&path, job).unwrap();
+
+        assert_eq!(checkpoint.len(), 3);
+        assert!(!checkpoint.is_completed("size=1"));
+        assert!(checkpoint.is_completed("size=3"));
+        drop(checkpoint);
+
+        // A checkpoint can't be resumed by a different job.
+        let result = Checkpoint::open(&path, "export sphere");
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            result,
+            Err(Error::JobMismatch(Some(job))) if job == "export cube"
+        ));
+    }
+}
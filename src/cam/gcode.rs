@@ -0,0 +1,108 @@
+//! G-code output
+//!
+//! The G-code written here is kept to a small subset of commands, that should
+//! be understood by most machine controllers (like GRBL or LinuxCNC). Units
+//! are millimeters.
+
+use std::io::{self, Write};
+
+use super::{Settings, Toolpath};
+
+/// Write a toolpath as G-code
+///
+/// Every loop of the toolpath is cut once per depth pass. The tool retracts to
+/// the safe height between loops.
+pub fn write(
+    mut w: impl Write,
+    toolpath: &Toolpath,
+    settings: &Settings,
+) -> io::Result<()> {
+    writeln!(w, "(Generated by Fornjot)")?;
+    writeln!(w, "G21 (millimeters)")?;
+    writeln!(w, "G90 (absolute coordinates)")?;
+    writeln!(w, "G0 Z{:.4}", settings.safe_height)?;
+
+    if let Some(spindle_speed) = settings.spindle_speed {
+        writeln!(w, "M3 S{:.0}", spindle_speed)?;
+    }
+
+    for depth in settings.depths() {
+        for polygon in &toolpath.loops {
+            let start = match polygon.first() {
+                Some(start) => start,
+                None => continue,
+            };
+
+            writeln!(
+                w,
+                "G0 X{:.4} Y{:.4}",
+                start.u.into_f64(),
+                start.v.into_f64()
+            )?;
+            writeln!(w, "G1 Z{:.4} F{}", depth, settings.plunge_rate)?;
+
+            let mut feed_rate = Some(settings.feed_rate);
+            for point in polygon.iter().skip(1).chain([start]) {
+                write!(
+                    w,
+                    "G1 X{:.4} Y{:.4}",
+                    point.u.into_f64(),
+                    point.v.into_f64()
+                )?;
+                if let Some(feed_rate) = feed_rate.take() {
+                    write!(w, " F{}", feed_rate)?;
+                }
+                writeln!(w)?;
+            }
+
+            writeln!(w, "G0 Z{:.4}", settings.safe_height)?;
+        }
+    }
+
+    if settings.spindle_speed.is_some() {
+        writeln!(w, "M5")?;
+    }
+    writeln!(w, "M2")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cam::{Settings, Toolpath},
+        math::Point,
+    };
+
+    #[test]
+    fn write_should_cut_each_loop_per_pass() {
+        let toolpath = Toolpath {
+            loops: vec![[[0., 0.], [1., 0.], [0., 1.]]
+                .into_iter()
+                .map(Point::from)
+                .collect()],
+        };
+        let settings = Settings {
+            tool_diameter: 1.,
+            stepover: 0.5,
+            depth: 1.5,
+            step_down: 1.,
+            safe_height: 5.,
+            feed_rate: 300.,
+            plunge_rate: 100.,
+            spindle_speed: None,
+        };
+
+        let mut gcode = Vec::new();
+        super::write(&mut gcode, &toolpath, &settings).unwrap();
+        let gcode = String::from_utf8(gcode).unwrap();
+
+        let plunges: Vec<_> = gcode
+            .lines()
+            .filter(|line| line.starts_with("G1 Z"))
+            .collect();
+        assert_eq!(plunges, ["G1 Z-0.7500 F100", "G1 Z-1.5000 F100"]);
+
+        assert_eq!(gcode.matches("G1 X0.0000 Y0.0000").count(), 2);
+    }
+}
@@ -0,0 +1,342 @@
+//! Generation of toolpaths for CNC milling
+//!
+//! This module generates 2.5D toolpaths, which means the tool moves in the x-y
+//! plane at a series of fixed depths. That is enough to cut 2-dimensional
+//! shapes out of, or into, a sheet of stock material.
+//!
+//! The shape is expected to lie in the x-y plane, and the top of the stock is
+//! expected at `z = 0`. Toolpaths are written as G-code, using [`gcode`].
+
+pub mod gcode;
+
+mod offset;
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::{
+    kernel::{
+        algorithms::{approximation::Approximation, tolerance::Tolerance},
+        shape::Shape,
+    },
+    math::{
+        polygon::{ring_contains, signed_area},
+        Point, Scalar,
+    },
+};
+
+pub use self::offset::offset;
+
+/// A machining operation
+#[derive(Clone, Copy, Debug, Eq, PartialEq, clap::ArgEnum)]
+pub enum Operation {
+    /// Cut along the outside of the shape, to cut it out of the stock
+    ///
+    /// The holes in the shape are cut along their inside.
+    Contour,
+
+    /// Remove all material within the shape, leaving a pocket
+    ///
+    /// Holes in the shape are left standing, as islands.
+    Pocket,
+}
+
+/// Settings for generating toolpaths
+#[derive(Clone, Debug)]
+pub struct Settings {
+    /// The diameter of the tool
+    pub tool_diameter: f64,
+
+    /// The distance between two adjacent passes of a pocketing operation
+    ///
+    /// This is a fraction of the tool diameter, so it must be larger than `0`,
+    /// and should be no larger than `1`.
+    pub stepover: f64,
+
+    /// The depth to cut to, below the top of the stock
+    pub depth: f64,
+
+    /// The maximum depth that is cut in a single pass
+    pub step_down: f64,
+
+    /// The height above the stock, at which the tool can move freely
+    pub safe_height: f64,
+
+    /// The speed at which the tool moves while cutting horizontally
+    pub feed_rate: f64,
+
+    /// The speed at which the tool moves while plunging into the stock
+    pub plunge_rate: f64,
+
+    /// The spindle speed in revolutions per minute, if it should be set
+    pub spindle_speed: Option<f64>,
+}
+
+impl Settings {
+    /// The depths of all passes, from the top down
+    pub fn depths(&self) -> impl Iterator<Item = f64> {
+        let passes = (self.depth / self.step_down).ceil().max(1.) as usize;
+        let depth = self.depth;
+
+        (1..=passes).map(move |pass| -depth * pass as f64 / passes as f64)
+    }
+}
+
+/// A toolpath, consisting of closed loops that the tool follows
+///
+/// The loops refer to the center of the tool, meaning the tool radius has
+/// already been compensated for.
+#[derive(Debug)]
+pub struct Toolpath {
+    /// The loops, in the order in which they should be cut
+    pub loops: Vec<Vec<Point<2>>>,
+}
+
+impl Toolpath {
+    /// Generate the toolpath for an operation on a profile
+    ///
+    /// `profile` is the boundary of the region to machine, as returned by
+    /// [`profile`]. `tolerance` defines how far the approximation of rounded
+    /// corners may deviate from an actual arc.
+    ///
+    /// The toolpath is generated for climb milling with a clockwise spindle,
+    /// meaning the material is always to the right of the tool.
+    ///
+    /// Fails, if a pocket is to be cut, but the distance between its passes
+    /// isn't positive, as the pocket would never be finished.
+    pub fn generate(
+        profile: &[Vec<Point<2>>],
+        operation: Operation,
+        settings: &Settings,
+        tolerance: Tolerance,
+    ) -> Result<Self, Error> {
+        let radius = settings.tool_diameter / 2.;
+        let tolerance = tolerance.inner().into_f64();
+
+        let loops = match operation {
+            Operation::Contour => {
+                // The profile is oriented with the region on the left, but the
+                // material is the region here.
+                let mut loops = offset(profile, -radius, tolerance);
+                for polygon in &mut loops {
+                    polygon.reverse();
+                }
+                loops
+            }
+            Operation::Pocket => {
+                let stepover = settings.tool_diameter * settings.stepover;
+                if !(stepover > 0. && stepover.is_finite()) {
+                    return Err(Error::InvalidStepover(stepover));
+                }
+
+                // Each offset is computed from the original profile, so errors
+                // don't accumulate.
+                let mut levels = Vec::new();
+                let mut distance = radius;
+                loop {
+                    let level = offset(profile, distance, tolerance);
+                    if level.is_empty() {
+                        break;
+                    }
+
+                    levels.push(level);
+                    distance += stepover;
+                }
+
+                // Start in the middle of the pocket, so the final pass is the
+                // one along its boundary.
+                levels.into_iter().rev().flatten().collect()
+            }
+        };
+
+        Ok(Self { loops })
+    }
+}
+
+/// Returned by [`Toolpath::generate`]
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The distance between passes, the tool diameter times the stepover
+    #[error("Distance between passes must be positive, but is {0}")]
+    InvalidStepover(f64),
+}
+
+/// Compute the profile of a 2-dimensional shape
+///
+/// Returns the boundary of the shape as closed polygons in the x-y plane,
+/// oriented such that the shape is on their left side.
+pub fn profile(shape: &mut Shape, tolerance: Tolerance) -> Vec<Vec<Point<2>>> {
+    let mut polygons = Vec::new();
+
+    for face in shape.topology().faces() {
+        for cycle in face.get().cycles() {
+            let approx = Approximation::for_cycle(&cycle, tolerance);
+            let mut neighbors: HashMap<_, Vec<_>> = HashMap::new();
+            for segment in approx.segments {
+                let [a, b] = segment.points();
+                neighbors.entry(a).or_default().push(b);
+                neighbors.entry(b).or_default().push(a);
+            }
+
+            let start = match neighbors.keys().next() {
+                Some(&start) => start,
+                None => continue,
+            };
+
+            // Every point of a cycle has exactly two neighbors, so we can just
+            // walk along the cycle, until we're back at the start.
+            let mut polygon = vec![start];
+            let mut previous = start;
+            let mut current = neighbors[&start][0];
+            while current != start {
+                polygon.push(current);
+
+                let next = neighbors[&current]
+                    .iter()
+                    .copied()
+                    .find(|&next| next != previous)
+                    .expect("Cycle is not closed");
+                previous = current;
+                current = next;
+            }
+
+            polygons.push(
+                polygon
+                    .into_iter()
+                    .map(|point| Point::from([point.x, point.y]))
+                    .collect::<Vec<_>>(),
+            );
+        }
+    }
+
+    // Polygons that are inside an odd number of other polygons are holes.
+    // Those need to be clockwise, all others counter-clockwise.
+    let is_hole: Vec<_> = polygons
+        .iter()
+        .enumerate()
+        .map(|(i, polygon)| {
+            let containing = polygons
+                .iter()
+                .enumerate()
+                .filter(|&(j, other)| {
+                    i != j && ring_contains(other, &polygon[0])
+                })
+                .count();
+            containing % 2 == 1
+        })
+        .collect();
+
+    for (polygon, is_hole) in polygons.iter_mut().zip(is_hole) {
+        let is_clockwise = signed_area(polygon) < Scalar::ZERO;
+        if is_clockwise != is_hole {
+            polygon.reverse();
+        }
+    }
+
+    polygons
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        debug::DebugInfo,
+        kernel::{algorithms::tolerance::Tolerance, shapes::ToShape as _},
+        math::{Point, Scalar},
+    };
+
+    use super::{profile, signed_area, Error, Operation, Settings, Toolpath};
+
+    #[test]
+    fn profile_should_orient_holes_clockwise() {
+        let ring = fj::Difference2d {
+            a: fj::Circle {
+                center: [0., 0.],
+                radius: 2.,
+            }
+            .into(),
+            b: fj::Circle {
+                center: [0., 0.],
+                radius: 1.,
+            }
+            .into(),
+        };
+        let tolerance = Tolerance::from_scalar(0.01).unwrap();
+
+        let mut shape =
+            ring.to_shape(tolerance, &mut DebugInfo::new()).unwrap();
+        let mut profile = profile(&mut shape, tolerance);
+        profile.sort_by_key(|polygon| signed_area(polygon));
+
+        assert_eq!(profile.len(), 2);
+        assert!(signed_area(&profile[0]) < Scalar::ZERO);
+        assert!(signed_area(&profile[1]) > Scalar::ZERO);
+    }
+
+    #[test]
+    fn pocket_should_cut_from_the_inside_out() {
+        let square: Vec<_> = [[0., 0.], [10., 0.], [10., 10.], [0., 10.]]
+            .into_iter()
+            .map(Point::from)
+            .collect();
+        let settings = Settings {
+            tool_diameter: 2.,
+            stepover: 0.5,
+            depth: 3.,
+            step_down: 1.,
+            safe_height: 5.,
+            feed_rate: 300.,
+            plunge_rate: 100.,
+            spindle_speed: None,
+        };
+
+        let toolpath = Toolpath::generate(
+            &[square],
+            Operation::Pocket,
+            &settings,
+            Tolerance::from_scalar(0.01).unwrap(),
+        )
+        .unwrap();
+
+        // The tool center stays 1 away from the boundary, so the loops are
+        // offset by 1, 2, 3, and 4.
+        assert_eq!(toolpath.loops.len(), 4);
+        let areas: Vec<_> = toolpath
+            .loops
+            .iter()
+            .map(|polygon| signed_area(polygon).into_f64().round())
+            .collect();
+        assert_eq!(areas, [4., 16., 36., 64.]);
+    }
+
+    #[test]
+    fn pocket_should_fail_without_positive_distance_between_passes() {
+        let square: Vec<_> = [[0., 0.], [10., 0.], [10., 10.], [0., 10.]]
+            .into_iter()
+            .map(Point::from)
+            .collect();
+        let tolerance = Tolerance::from_scalar(0.01).unwrap();
+
+        for (tool_diameter, stepover) in [(2., 0.), (-2., 0.5), (2., f64::NAN)]
+        {
+            let settings = Settings {
+                tool_diameter,
+                stepover,
+                depth: 3.,
+                step_down: 1.,
+                safe_height: 5.,
+                feed_rate: 300.,
+                plunge_rate: 100.,
+                spindle_speed: None,
+            };
+
+            let result = Toolpath::generate(
+                &[square.clone()],
+                Operation::Pocket,
+                &settings,
+                tolerance,
+            );
+            assert!(matches!(result, Err(Error::InvalidStepover(_))));
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+
+use nalgebra::{Point2, Vector2};
+
+use crate::math::Point;
+
+/// Offset the boundary of a region
+///
+/// The region is defined by closed polygons, which must be oriented such that
+/// the region is on their left side. This means outer boundaries are
+/// counter-clockwise, while the boundaries of holes are clockwise.
+///
+/// A positive `distance` shrinks the region, a negative one grows it. The
+/// result is the boundary of the offset region, oriented the same way as the
+/// input. If the region vanishes completely, the result is empty.
+///
+/// Corners that are rounded by the offset are approximated, and `tolerance`
+/// defines how far that approximation may deviate from an actual arc.
+///
+/// # Implementation Note
+///
+/// This first computes a raw offset of each polygon, which has loops wherever
+/// the offset polygon intersects itself, or the offsets of other polygons. All
+/// of those intersections are then split up, and those parts that are too
+/// close to the original boundary are thrown away. What's left is joined
+/// back into polygons.
+///
+/// This approach is simple and robust, but its run time is quadratic in the
+/// number of polygon edges. That should be fine for typical CAM workloads, but
+/// might need to be replaced with a sweep-line algorithm, if it turns out not
+/// to be.
+pub fn offset(
+    region: &[Vec<Point<2>>],
+    distance: f64,
+    tolerance: f64,
+) -> Vec<Vec<Point<2>>> {
+    let region: Vec<Vec<Point2<f64>>> = region
+        .iter()
+        .map(|polygon| polygon.iter().map(|point| point.to_na()).collect())
+        .collect();
+
+    let boundary: Vec<_> = edges(&region).collect();
+
+    let raw: Vec<_> = region
+        .iter()
+        .map(|polygon| raw_offset(polygon, distance, tolerance))
+        .collect();
+    let segments: Vec<_> = edges(&raw).collect();
+
+    // Find all intersections between segments of the raw offset. Each
+    // intersection point is computed only once, and then used for both
+    // segments, which makes sure that the parts of the segments can be joined
+    // back together without any numerical trouble.
+    let mut splits = vec![Vec::new(); segments.len()];
+    for (i, &a) in segments.iter().enumerate() {
+        for (j, &b) in segments.iter().enumerate().skip(i + 1) {
+            if let Some((t, s, point)) = intersect(a, b) {
+                splits[i].push((t, point));
+                splits[j].push((s, point));
+            }
+        }
+    }
+
+    // The arcs at rounded corners are approximated with chords, whose centers
+    // are closer to the boundary than `distance`. `raw_offset` makes sure
+    // they're not too close.
+    let min_distance = distance.abs() - tolerance;
+
+    let mut parts = Vec::new();
+    for ([a, b], mut splits) in segments.into_iter().zip(splits) {
+        splits.sort_by(|(a, _), (b, _)| {
+            a.partial_cmp(b).expect("Intersection parameter is NaN")
+        });
+
+        let mut points = vec![a];
+        points.extend(splits.into_iter().map(|(_, point)| point));
+        points.push(b);
+
+        for part in points.windows(2) {
+            let [a, b] = [part[0], part[1]];
+            if a == b {
+                continue;
+            }
+
+            let center = nalgebra::center(&a, &b);
+            if distance_to_boundary(&boundary, center) >= min_distance {
+                parts.push([a, b]);
+            }
+        }
+    }
+
+    join(&parts)
+        .into_iter()
+        .map(|polygon| polygon.into_iter().map(Point::from_na).collect())
+        .collect()
+}
+
+/// Offset every edge of the polygon, connecting them into a closed polygon
+///
+/// Where the offset edges move apart from each other, they are connected by an
+/// arc. Where they move towards each other, they are connected directly, which
+/// creates a small loop that is removed later.
+fn raw_offset(
+    polygon: &[Point2<f64>],
+    distance: f64,
+    tolerance: f64,
+) -> Vec<Point2<f64>> {
+    let mut raw = Vec::new();
+
+    for i in 0..polygon.len() {
+        let a = polygon[i];
+        let b = polygon[(i + 1) % polygon.len()];
+        let c = polygon[(i + 2) % polygon.len()];
+
+        let ab = left_normal(b - a) * distance;
+        let bc = left_normal(c - b) * distance;
+
+        push(&mut raw, a + ab);
+        push(&mut raw, b + ab);
+
+        // The polygon turns left or right at `b`. If the offset is on the
+        // outside of that turn, there's a gap between the offset edges.
+        let turn = perp(b - a, c - b);
+        if turn * distance < 0. {
+            arc(b, ab, bc, tolerance, &mut raw);
+        }
+    }
+
+    if raw.len() > 1 && raw.first() == raw.last() {
+        raw.pop();
+    }
+
+    raw
+}
+
+/// Approximate an arc around `center`, from `from` to `to`
+///
+/// Only adds the points between the end points of the arc. The arc is
+/// approximated such that its chords deviate from it by at most half of
+/// `tolerance`.
+fn arc(
+    center: Point2<f64>,
+    from: Vector2<f64>,
+    to: Vector2<f64>,
+    tolerance: f64,
+    out: &mut Vec<Point2<f64>>,
+) {
+    let radius = from.magnitude();
+    let angle = perp(from, to).atan2(from.dot(&to));
+
+    let max_step = 2. * (1. - tolerance / 2. / radius).max(-1.).acos();
+    let steps = (angle.abs() / max_step).ceil().max(1.) as usize;
+
+    for step in 1..steps {
+        let rotation =
+            nalgebra::Rotation2::new(angle * step as f64 / steps as f64);
+        push(out, center + rotation * from);
+    }
+}
+
+/// Join directed segments into closed polygons
+///
+/// Segments that can't be joined into a closed polygon are dropped.
+fn join(segments: &[[Point2<f64>; 2]]) -> Vec<Vec<Point2<f64>>> {
+    const EPSILON: f64 = 1e-9;
+
+    let mut by_start: HashMap<_, Vec<_>> = HashMap::new();
+    for (i, [start, _]) in segments.iter().enumerate() {
+        by_start.entry(key(start)).or_default().push(i);
+    }
+
+    let mut used = vec![false; segments.len()];
+    let mut polygons = Vec::new();
+
+    for first in 0..segments.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+
+        let mut polygon = vec![segments[first][0]];
+        let mut current = first;
+
+        loop {
+            let end = segments[current][1];
+            if (end - polygon[0]).magnitude() < EPSILON {
+                if polygon.len() >= 3 {
+                    polygons.push(polygon);
+                }
+                break;
+            }
+
+            // Segments usually connect exactly, but where they intersect at
+            // their end points, the intersection might have been computed
+            // slightly differently for each of them.
+            let next = by_start
+                .get(&key(&end))
+                .and_then(|candidates| {
+                    candidates.iter().copied().find(|&i| !used[i])
+                })
+                .or_else(|| {
+                    (0..segments.len()).find(|&i| {
+                        !used[i] && (segments[i][0] - end).magnitude() < EPSILON
+                    })
+                });
+
+            match next {
+                Some(next) => {
+                    used[next] = true;
+                    polygon.push(end);
+                    current = next;
+                }
+                None => {
+                    // Can't close this polygon. This only happens due to
+                    // numerical inaccuracies, so whatever we found here is
+                    // negligibly small.
+                    break;
+                }
+            }
+        }
+    }
+
+    polygons
+}
+
+/// Compute the intersection of two segments, if they cross each other
+///
+/// Returns the intersection point, and its position on both segments, as a
+/// parameter between `0` and `1`. Segments that only touch at their end points
+/// don't count as intersecting, and neither do parallel ones.
+fn intersect(
+    [a, b]: [Point2<f64>; 2],
+    [c, d]: [Point2<f64>; 2],
+) -> Option<(f64, f64, Point2<f64>)> {
+    const EPSILON: f64 = 1e-9;
+
+    let ab = b - a;
+    let cd = d - c;
+
+    let denominator = perp(ab, cd);
+    if denominator.abs() < f64::EPSILON {
+        return None;
+    }
+
+    let t = perp(c - a, cd) / denominator;
+    let s = perp(c - a, ab) / denominator;
+
+    let range = EPSILON..1. - EPSILON;
+    if range.contains(&t) && range.contains(&s) {
+        return Some((t, s, a + ab * t));
+    }
+
+    None
+}
+
+fn distance_to_boundary(
+    boundary: &[[Point2<f64>; 2]],
+    point: Point2<f64>,
+) -> f64 {
+    boundary
+        .iter()
+        .map(|&[a, b]| {
+            let ab = b - a;
+            let t = ((point - a).dot(&ab) / ab.norm_squared()).clamp(0., 1.);
+            (a + ab * t - point).magnitude()
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Iterate over the edges of all polygons
+fn edges(
+    polygons: &[Vec<Point2<f64>>],
+) -> impl Iterator<Item = [Point2<f64>; 2]> + '_ {
+    polygons.iter().flat_map(|polygon| {
+        (0..polygon.len())
+            .map(move |i| [polygon[i], polygon[(i + 1) % polygon.len()]])
+    })
+}
+
+/// Add a point, unless it's the same as the previous one
+fn push(polygon: &mut Vec<Point2<f64>>, point: Point2<f64>) {
+    if let Some(last) = polygon.last() {
+        if (point - last).magnitude() < 1e-12 {
+            return;
+        }
+    }
+
+    polygon.push(point);
+}
+
+fn left_normal(direction: Vector2<f64>) -> Vector2<f64> {
+    let direction = direction.normalize();
+    Vector2::new(-direction.y, direction.x)
+}
+
+/// The z component of the cross product of two vectors
+fn perp(a: Vector2<f64>, b: Vector2<f64>) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+fn key(point: &Point2<f64>) -> [u64; 2] {
+    [point.x.to_bits(), point.y.to_bits()]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::math::Point;
+
+    use super::offset;
+
+    #[test]
+    fn offset_should_shrink_and_grow_squares() {
+        let square = vec![square(2.)];
+
+        let shrunk = offset(&square, 0.5, 0.01);
+        assert_eq!(shrunk.len(), 1);
+        assert!((area(&shrunk[0]) - 1.).abs() < 1e-9);
+
+        // The corners of the grown square are rounded.
+        let grown = offset(&square, -0.5, 0.01);
+        assert_eq!(grown.len(), 1);
+        let expected = 4. + 4. * 2. * 0.5 + PI * 0.25;
+        assert!((area(&grown[0]) - expected).abs() < 0.01);
+
+        assert!(offset(&square, 1.5, 0.01).is_empty());
+    }
+
+    #[test]
+    fn offset_should_handle_holes() {
+        let mut hole = square(1.);
+        hole.reverse();
+        let region = vec![square(4.), hole];
+
+        let shrunk = offset(&region, 0.25, 0.01);
+        assert_eq!(shrunk.len(), 2);
+
+        let areas: f64 = shrunk.iter().map(|polygon| area(polygon)).sum();
+        let expected = 3.5 * 3.5 - (1. + 4. * 0.25 + PI * 0.0625);
+        assert!((areas - expected).abs() < 0.01);
+    }
+
+    #[test]
+    fn offset_should_split_at_narrow_necks() {
+        // Two 2x2 squares, connected by a 0.5 wide neck.
+        let dumbbell = vec![[
+            [0., 0.],
+            [2., 0.],
+            [2., 0.75],
+            [3., 0.75],
+            [3., 0.],
+            [5., 0.],
+            [5., 2.],
+            [3., 2.],
+            [3., 1.25],
+            [2., 1.25],
+            [2., 2.],
+            [0., 2.],
+        ]
+        .into_iter()
+        .map(Point::from)
+        .collect()];
+
+        assert_eq!(offset(&dumbbell, 0.2, 0.01).len(), 1);
+        assert_eq!(offset(&dumbbell, 0.5, 0.01).len(), 2);
+    }
+
+    fn square(size: f64) -> Vec<Point<2>> {
+        let half = size / 2.;
+        [[-half, -half], [half, -half], [half, half], [-half, half]]
+            .into_iter()
+            .map(Point::from)
+            .collect()
+    }
+
+    fn area(polygon: &[Point<2>]) -> f64 {
+        let mut area = 0.;
+        for (i, a) in polygon.iter().enumerate() {
+            let b = polygon[(i + 1) % polygon.len()];
+            area += (a.u * b.v - b.u * a.v).into_f64() / 2.;
+        }
+        area
+    }
+}
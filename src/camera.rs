@@ -1,11 +1,11 @@
 use std::f64::consts::FRAC_PI_2;
 
-use nalgebra::{Point, TAffine, Transform, Translation, Vector};
+use nalgebra::{TAffine, Transform, Translation};
 use parry3d_f64::query::{Ray, RayCast as _};
 use winit::dpi::PhysicalPosition;
 
 use crate::{
-    math::{Aabb, Scalar, Triangle},
+    math::{Aabb, Point, Projection, Scalar, Triangle, Vector},
     window::Window,
 };
 
@@ -38,53 +38,56 @@ impl Camera {
 
     const INITIAL_FIELD_OF_VIEW_IN_X: f64 = FRAC_PI_2; // 90 degrees
 
-    pub fn new(aabb: &Aabb<3>) -> Self {
-        let initial_distance = {
-            // Let's make sure we choose a distance, so that the model fills
-            // most of the screen.
-            //
-            // To do that, first compute the model's highest point, as well as
-            // the furthest point from the origin, in x and y.
-            let highest_point = aabb.max.z;
-            let furthest_point =
-                [aabb.min.x.abs(), aabb.max.x, aabb.min.y.abs(), aabb.max.y]
-                    .into_iter()
-                    .reduce(Scalar::max)
-                    // `reduce` can only return `None`, if there are no items in
-                    // the iterator. And since we're creating an array full of
-                    // items above, we know this can't panic.
-                    .unwrap();
-
-            // The actual furthest point is not far enough. We don't want the
-            // model to fill the whole screen.
-            let furthest_point = furthest_point * 2.;
-
-            // Having computed those points, figuring out how far the camera
-            // needs to be from the model is just a bit of trigonometry.
-            let distance_from_model =
-                furthest_point / (Self::INITIAL_FIELD_OF_VIEW_IN_X / 2.).atan();
-
-            // An finally, the distance from the origin is trivial now.
-            highest_point + distance_from_model
-        };
+    /// The near plane, relative to the far plane, if the camera is within the
+    /// model
+    const NEAR_PLANE_WITHIN_MODEL: f64 = 0.0001;
 
-        let initial_offset = {
-            let mut offset = aabb.center();
-            offset.z = Scalar::ZERO;
-            -offset
-        };
+    /// The size of the view when fitting a model, relative to the model
+    ///
+    /// We don't want the model to fill the whole screen.
+    const FIT_MARGIN: f64 = 2.;
+
+    /// The minimum navigation distance, relative to the size of the model
+    ///
+    /// See [`Camera::navigation_distance`].
+    const MIN_NAVIGATION_DISTANCE: f64 = 0.1;
 
-        Self {
+    pub fn new(aabb: &Aabb<3>) -> Self {
+        let mut camera = Self {
             near_plane: Self::DEFAULT_NEAR_PLANE,
             far_plane: Self::DEFAULT_FAR_PLANE,
 
             rotation: Transform::identity(),
-            translation: Translation::from([
-                initial_offset.x.into_f64(),
-                initial_offset.y.into_f64(),
-                -initial_distance.into_f64(),
-            ]),
-        }
+            translation: Translation::identity(),
+        };
+
+        camera.fit(aabb);
+        camera.update_planes(aabb);
+
+        camera
+    }
+
+    /// Move the camera, so the whole model is in view
+    ///
+    /// This doesn't change the direction the model is viewed from. It centers
+    /// the model's bounding box in the view, and moves the camera away from it,
+    /// until the box fits, regardless of how small or large it is.
+    pub fn fit(&mut self, aabb: &Aabb<3>) {
+        // The box fits into its bounding sphere, however it is rotated.
+        let radius = aabb.size().magnitude().into_f64() / 2.;
+
+        // An empty model has no size. Any distance works for that, as long as
+        // it isn't zero.
+        let radius = if radius > 0. { radius } else { 1. };
+
+        // Having computed the size of the sphere, figuring out how far the
+        // camera needs to be from it is just a bit of trigonometry.
+        let distance = radius * Self::FIT_MARGIN
+            / (Self::INITIAL_FIELD_OF_VIEW_IN_X / 2.).sin();
+
+        let center = self.rotation.transform_point(&aabb.center().to_na());
+        self.translation =
+            Translation::from([-center.x, -center.y, -center.z - distance]);
     }
 
     pub fn near_plane(&self) -> f64 {
@@ -99,9 +102,33 @@ impl Camera {
         Self::INITIAL_FIELD_OF_VIEW_IN_X
     }
 
-    pub fn position(&self) -> Point<f64, 3> {
+    pub fn position(&self) -> Point<3> {
         self.camera_to_model()
-            .inverse_transform_point(&Point::origin())
+            .inverse()
+            .transform_point(&Point::origin())
+    }
+
+    /// Compute the distance that navigation speed is relative to
+    ///
+    /// This is the distance to the point under the cursor, if there is one.
+    /// Otherwise, it's the distance to the center of the model, or a fraction
+    /// of its size, if the camera is close to the center. Either way, moving
+    /// the camera by a fraction of this distance feels the same, whether the
+    /// model is 2 millimeters or 2 meters large.
+    pub fn navigation_distance(
+        &self,
+        focus_point: &FocusPoint,
+        aabb: &Aabb<3>,
+    ) -> Scalar {
+        if let Some(focus_point) = focus_point.0 {
+            return (focus_point - self.position()).magnitude();
+        }
+
+        let to_center = (aabb.center() - self.position()).magnitude();
+        let min_distance =
+            aabb.size().magnitude() * Self::MIN_NAVIGATION_DISTANCE;
+
+        to_center.max(min_distance)
     }
 
     /// Transform the position of the cursor on the near plane to model space
@@ -109,22 +136,19 @@ impl Camera {
         &self,
         cursor: PhysicalPosition<f64>,
         window: &Window,
-    ) -> Point<f64, 3> {
+    ) -> Point<3> {
         let width = window.width() as f64;
         let height = window.height() as f64;
-        let aspect_ratio = width / height;
 
-        // Cursor position in normalized coordinates (-1 to +1) with
-        // aspect ratio taken into account.
+        // Cursor position in normalized device coordinates (-1 to +1).
         let x = cursor.x / width * 2. - 1.;
-        let y = -(cursor.y / height * 2. - 1.) / aspect_ratio;
-
-        // Cursor position in camera space.
-        let f = (self.field_of_view_in_x() / 2.).tan() * self.near_plane();
-        let cursor =
-            Point::origin() + Vector::from([x * f, y * f, -self.near_plane()]);
+        let y = -(cursor.y / height * 2. - 1.);
 
-        self.camera_to_model().inverse_transform_point(&cursor)
+        let [near, _] = self
+            .projection(width / height)
+            .unproject(&Point::from([x, y]))
+            .points();
+        near
     }
 
     /// Compute the point on the model, that the cursor currently points to
@@ -144,7 +168,10 @@ impl Camera {
         let cursor = self.cursor_to_model_space(cursor, window);
         let dir = (cursor - origin).normalize();
 
-        let ray = Ray { origin, dir };
+        let ray = Ray {
+            origin: origin.to_na(),
+            dir: dir.to_na(),
+        };
 
         let mut min_t = None;
 
@@ -161,19 +188,36 @@ impl Camera {
             }
         }
 
-        FocusPoint(min_t.map(|t| ray.point_at(t)))
+        FocusPoint(min_t.map(|t| Point::from(ray.point_at(t))))
     }
 
     /// Access the transform from camera to model space
-    pub fn camera_to_model(&self) -> Transform<f64, TAffine, 3> {
+    pub fn camera_to_model(&self) -> Projection {
         // Using a mutable variable cleanly takes care of any type inference
         // problems that this operation would otherwise have.
-        let mut transform = Transform::identity();
+        let mut transform = Transform::<f64, TAffine, 3>::identity();
 
         transform *= self.translation;
         transform *= self.rotation;
 
-        transform
+        Projection::from(transform)
+    }
+
+    /// Access the projection from model space into screen space
+    ///
+    /// This is the projection that is used to render the model, so it can be
+    /// used to find out where a point on the model ends up on the screen.
+    pub fn projection(&self, aspect_ratio: f64) -> Projection {
+        let field_of_view_in_y = self.field_of_view_in_x() / aspect_ratio;
+
+        let perspective = Projection::perspective(
+            aspect_ratio,
+            field_of_view_in_y,
+            self.near_plane(),
+            self.far_plane(),
+        );
+
+        perspective * self.camera_to_model()
     }
 
     pub fn update_planes(&mut self, aabb: &Aabb<3>) {
@@ -184,17 +228,17 @@ impl Camera {
         let mut dist_max = f64::NEG_INFINITY;
 
         for vertex in aabb.vertices() {
-            let point = view_transform.transform_point(&vertex.to_na());
+            let point = view_transform.transform_point(&vertex);
 
             // Project `point` onto `view_direction`. See this Wikipedia page:
             // https://en.wikipedia.org/wiki/Vector_projection
             //
-            // Let's rename the variables first, so they fit the names in that
-            // page.
-            let (a, b) = (point.coords, view_direction);
-            let a1 = a.dot(&b) / b.dot(&b) * b;
-
-            let dist = a1.magnitude();
+            // The projection is signed, so points behind the camera don't end
+            // up in front of it.
+            let dist = point
+                .coords
+                .scalar_projection_onto(&view_direction)
+                .into_f64();
 
             if dist < dist_min {
                 dist_min = dist;
@@ -210,6 +254,11 @@ impl Camera {
             // wasn't able to figure out why, and for the time being, this
             // factor seems to work well enough.
             dist_min * 0.5
+        } else if dist_max > 0. {
+            // The camera is within the model. Pick a near plane relative to
+            // its size, so the depth buffer keeps its precision, whether the
+            // model is measured in micrometers or in meters.
+            dist_max * Self::NEAR_PLANE_WITHIN_MODEL
         } else {
             Self::DEFAULT_NEAR_PLANE
         };
@@ -225,7 +274,7 @@ impl Camera {
 ///
 /// Such a point might or might not exist, depending on whether the cursor is
 /// pointing at the model or not.
-pub struct FocusPoint(pub Option<Point<f64, 3>>);
+pub struct FocusPoint(pub Option<Point<3>>);
 
 impl FocusPoint {
     /// Construct the "none" instance of `FocusPoint`
@@ -0,0 +1,224 @@
+//! Configuration of the host application
+//!
+//! The configuration is read from a TOML file. All settings are optional, so
+//! an empty file, or no file at all, results in the default configuration.
+//!
+//! ``` toml
+//! [theme]
+//! # The theme that the viewer starts with, `light` or `dark`
+//! variant = "dark"
+//!
+//! # Override colors of a theme, as linear RGB or RGBA
+//! [theme.dark]
+//! background = [0.0, 0.0, 0.05]
+//! model = [0.1, 0.4, 0.8]
+//!
+//! # Densities of additional materials, in grams per cubic centimeter
+//! [materials]
+//! carbon-fiber = 1.6
+//!
+//! # Additional export formats, keyed by file extension. The model is exported
+//! # to the format given by `via` first, then converted by running `command`.
+//! [exporters.off]
+//! name = "OFF (via MeshLab)"
+//! via = "stl"
+//! command = ["meshlabserver", "-i", "{input}", "-o", "{output}"]
+//! ```
+//!
+//! See [`Colors`](crate::graphics::Colors) for all colors that can be
+//! configured.
+
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::export::{CommandExporter, Exporters};
+use crate::{graphics::ThemeConfig, materials::Materials};
+
+/// The file name that the configuration is loaded from by default
+pub const DEFAULT_PATH: &str = "fj.toml";
+
+/// The configuration of the host application
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The colors of the viewer
+    pub theme: ThemeConfig,
+
+    /// The densities of materials, in addition to the default ones
+    ///
+    /// Densities of materials that are known by default can be overridden.
+    pub materials: BTreeMap<String, f64>,
+
+    /// Export formats, in addition to the built-in ones, keyed by extension
+    ///
+    /// Formats that are built in can be overridden.
+    pub exporters: BTreeMap<String, ExporterConfig>,
+}
+
+impl Config {
+    /// Load the configuration from a file
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let config = fs::read_to_string(path)?;
+        Self::parse(&config)
+    }
+
+    /// Parse the configuration from the contents of a file
+    pub fn parse(config: &str) -> Result<Self, Error> {
+        let config: Self = toml::from_str(config)?;
+
+        for (extension, exporter) in &config.exporters {
+            if exporter.command.is_empty() {
+                return Err(Error::EmptyCommand(extension.clone()));
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Build the registry of materials
+    pub fn materials(&self) -> Materials {
+        let mut materials = Materials::new();
+        for (name, &density) in &self.materials {
+            materials.insert(name.clone(), density);
+        }
+        materials
+    }
+
+    /// Build the registry of exporters
+    ///
+    /// Contains the built-in exporters, and the ones that are configured.
+    /// Configured exporters without a command are skipped.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn exporters(&self) -> Exporters {
+        let mut exporters = Exporters::default();
+        for (extension, exporter) in &self.exporters {
+            let exporter = CommandExporter::new(
+                exporter.name.clone().unwrap_or_else(|| extension.clone()),
+                extension.clone(),
+                exporter.via.clone(),
+                exporter.command.clone(),
+            );
+            if let Some(exporter) = exporter {
+                exporters.register(exporter);
+            }
+        }
+        exporters
+    }
+}
+
+/// An export format that is converted from another one by running a program
+///
+/// See [`CommandExporter`](crate::export::CommandExporter).
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExporterConfig {
+    /// The name of the format, as shown to the user
+    ///
+    /// Defaults to the extension.
+    pub name: Option<String>,
+
+    /// The extension of the built-in format that is converted
+    pub via: String,
+
+    /// The program to run, followed by its arguments
+    ///
+    /// `{input}` and `{output}` are replaced with the paths of the file in the
+    /// intermediate format, and of the output file.
+    pub command: Vec<String>,
+}
+
+/// An error loading the configuration
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Error reading configuration file")]
+    Io(#[from] io::Error),
+
+    #[error("Error parsing configuration file: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("Exporter for `.{0}` has no command")]
+    EmptyCommand(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::{Color, Theme, Variant};
+
+    use super::Config;
+
+    #[test]
+    fn parse_theme_configuration() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config, Config::default());
+        assert_eq!(config.theme.theme(Variant::Light), Theme::light());
+
+        let config = Config::parse(
+            "
+            [theme]
+            variant = \"dark\"
+
+            [theme.dark]
+            background = [0.0, 0.0, 0.5]
+            model = [0.1, 0.2, 0.3, 0.4]
+            ",
+        )
+        .unwrap();
+        assert_eq!(config.theme.variant, Variant::Dark);
+
+        let dark = config.theme.theme(Variant::Dark);
+        assert_eq!(dark.background, Color([0., 0., 0.5, 1.]));
+        assert_eq!(dark.model, Color([0.1, 0.2, 0.3, 0.4]));
+        assert_eq!(dark.text, Theme::dark().text);
+        assert_eq!(config.theme.theme(Variant::Light), Theme::light());
+
+        let invalid_color = |color| {
+            Config::parse(&format!("[theme.light]\ntext = {}", color)).is_err()
+        };
+        assert!(invalid_color("[0.0, 1.0]"));
+        assert!(invalid_color("[0.0, 1.0, 2.0]"));
+        assert!(Config::parse("[theme]\nvariant = \"blue\"").is_err());
+    }
+
+    #[test]
+    fn parse_materials_configuration() {
+        let config = Config::parse(
+            "
+            [materials]
+            carbon-fiber = 1.6
+            steel = 7.9
+            ",
+        )
+        .unwrap();
+
+        let materials = config.materials();
+        assert_eq!(materials.density("carbon-fiber"), Some(1.6));
+        assert_eq!(materials.density("steel"), Some(7.9));
+        assert_eq!(materials.density("aluminum"), Some(2.7));
+        assert_eq!(materials.density("unobtainium"), None);
+
+        assert!(Config::parse("[materials]\nsteel = \"heavy\"").is_err());
+    }
+
+    #[test]
+    fn parse_exporters_configuration() {
+        let config = Config::parse(
+            "
+            [exporters.off]
+            name = \"OFF\"
+            via = \"stl\"
+            command = [\"convert\", \"{input}\", \"{output}\"]
+            ",
+        )
+        .unwrap();
+
+        let exporters = config.exporters();
+        assert_eq!(exporters.find("off").map(|e| e.name()), Some("OFF"));
+        assert_eq!(exporters.find("stl").map(|e| e.name()), Some("STL"));
+
+        let empty = "[exporters.off]\nvia = \"stl\"\ncommand = []";
+        assert!(Config::parse(empty).is_err());
+    }
+}
@@ -0,0 +1,212 @@
+//! Coordinate system conventions of other applications
+//!
+//! Fornjot uses a right-handed coordinate system, in which the z-axis points
+//! up. Other applications don't necessarily agree. A mesh that is exchanged
+//! with them without converting it arrives rotated by 90 degrees, or
+//! mirrored. [`CoordinateSystem`] describes the convention of an application,
+//! and converts geometry from and to it.
+
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::math::Point;
+
+/// The coordinate system that another application expects
+///
+/// Some common conventions:
+///
+/// - Blender, most CAD applications, and slicers use the same coordinate
+///   system as Fornjot, [`CoordinateSystem::FORNJOT`].
+/// - glTF, Maya, and three.js use a right-handed coordinate system, with the
+///   y-axis pointing up.
+/// - Unity uses a left-handed coordinate system, with the y-axis pointing up.
+/// - Unreal Engine uses a left-handed coordinate system, with the z-axis
+///   pointing up.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CoordinateSystem {
+    /// The axis that points up
+    pub up: UpAxis,
+
+    /// Whether the coordinate system is right- or left-handed
+    pub handedness: Handedness,
+}
+
+impl CoordinateSystem {
+    /// The coordinate system that Fornjot uses
+    pub const FORNJOT: Self = Self {
+        up: UpAxis::Z,
+        handedness: Handedness::Right,
+    };
+
+    /// Convert a point from Fornjot's coordinate system into this one
+    ///
+    /// The x-axis is never changed. Converting to a left-handed coordinate
+    /// system mirrors the axis that points forward, away from the viewer.
+    pub fn from_fornjot(&self, point: Point<3>) -> Point<3> {
+        let [x, y, z] = point.coords.components();
+
+        let y = match self.handedness {
+            Handedness::Right => y,
+            Handedness::Left => -y,
+        };
+
+        match self.up {
+            UpAxis::Z => Point::from([x, y, z]),
+            UpAxis::Y => Point::from([x, z, -y]),
+        }
+    }
+
+    /// Convert a point from this coordinate system into Fornjot's
+    ///
+    /// This is the inverse of [`CoordinateSystem::from_fornjot`].
+    pub fn to_fornjot(&self, point: Point<3>) -> Point<3> {
+        let [x, y, z] = point.coords.components();
+
+        let [x, y, z] = match self.up {
+            UpAxis::Z => [x, y, z],
+            UpAxis::Y => [x, -z, y],
+        };
+
+        match self.handedness {
+            Handedness::Right => Point::from([x, y, z]),
+            Handedness::Left => Point::from([x, -y, z]),
+        }
+    }
+
+    /// Convert a triangle from Fornjot's coordinate system into this one
+    ///
+    /// If this coordinate system is left-handed, the winding order of the
+    /// triangle is reversed, so its normal still points out of the shape.
+    pub fn triangle_from_fornjot(
+        &self,
+        triangle: [Point<3>; 3],
+    ) -> [Point<3>; 3] {
+        self.reorder(triangle.map(|point| self.from_fornjot(point)))
+    }
+
+    /// Convert a triangle from this coordinate system into Fornjot's
+    ///
+    /// This is the inverse of [`CoordinateSystem::triangle_from_fornjot`].
+    pub fn triangle_to_fornjot(
+        &self,
+        triangle: [Point<3>; 3],
+    ) -> [Point<3>; 3] {
+        self.reorder(triangle.map(|point| self.to_fornjot(point)))
+    }
+
+    fn reorder(&self, [a, b, c]: [Point<3>; 3]) -> [Point<3>; 3] {
+        match self.handedness {
+            Handedness::Right => [a, b, c],
+            Handedness::Left => [a, c, b],
+        }
+    }
+}
+
+impl Default for CoordinateSystem {
+    fn default() -> Self {
+        Self::FORNJOT
+    }
+}
+
+/// The axis that points up in a coordinate system
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UpAxis {
+    /// The y-axis points up (`y`)
+    Y,
+
+    /// The z-axis points up (`z`)
+    Z,
+}
+
+impl FromStr for UpAxis {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "y" => Ok(Self::Y),
+            "z" => Ok(Self::Z),
+            _ => Err(ParseError("up axis", "y, z")),
+        }
+    }
+}
+
+/// Whether a coordinate system is right- or left-handed
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Handedness {
+    /// A right-handed coordinate system, like Fornjot's (`right`)
+    Right,
+
+    /// A left-handed coordinate system (`left`)
+    Left,
+}
+
+impl FromStr for Handedness {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "right" => Ok(Self::Right),
+            "left" => Ok(Self::Left),
+            _ => Err(ParseError("handedness", "right, left")),
+        }
+    }
+}
+
+/// Error parsing a value from a string
+///
+/// Contains the name of the value, and the valid alternatives.
+#[derive(Debug, Error)]
+#[error("Invalid {0}; expected one of: {1}")]
+pub struct ParseError(pub &'static str, pub &'static str);
+
+#[cfg(test)]
+mod tests {
+    use crate::math::Point;
+
+    use super::{CoordinateSystem, Handedness, UpAxis};
+
+    #[test]
+    fn convert() {
+        let point = Point::from([1., 2., 3.]);
+        let triangle =
+            [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]].map(Point::from);
+
+        let unity = CoordinateSystem {
+            up: UpAxis::Y,
+            handedness: Handedness::Left,
+        };
+        assert_eq!(unity.from_fornjot(point), Point::from([1., 3., 2.]));
+
+        for up in [UpAxis::Y, UpAxis::Z] {
+            for handedness in [Handedness::Right, Handedness::Left] {
+                let system = CoordinateSystem { up, handedness };
+
+                assert_eq!(
+                    system.to_fornjot(system.from_fornjot(point)),
+                    point
+                );
+                assert_eq!(
+                    system.triangle_to_fornjot(
+                        system.triangle_from_fornjot(triangle)
+                    ),
+                    triangle
+                );
+
+                // The up axis ends up pointing up.
+                let up = system.from_fornjot(Point::from([0., 0., 1.]));
+                let expected = match system.up {
+                    UpAxis::Y => [0., 1., 0.],
+                    UpAxis::Z => [0., 0., 1.],
+                };
+                assert_eq!(up, Point::from(expected));
+
+                // The normal, computed in the converted coordinate system, is
+                // the converted normal.
+                let [a, b, c] = system.triangle_from_fornjot(triangle);
+                let normal = (b - a).cross(&(c - a));
+                assert_eq!(Point::origin() + normal, up);
+            }
+        }
+    }
+}
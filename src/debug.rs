@@ -1,4 +1,7 @@
 use parry3d_f64::query::Ray;
+use smallvec::SmallVec;
+
+use crate::kernel::{shape::ValidationError, topology::vertices::Vertex};
 
 /// Debug info from the CAD kernel that can be visualized
 ///
@@ -6,31 +9,56 @@ use parry3d_f64::query::Ray;
 pub struct DebugInfo {
     /// Rays being used during face triangulation
     pub triangle_edge_checks: Vec<TriangleEdgeCheck>,
+
+    /// Validation errors that were found in the shape, but were not fatal
+    pub validation_errors: Vec<ValidationError<Vertex>>,
 }
 
 impl DebugInfo {
     pub fn new() -> Self {
         Self {
             triangle_edge_checks: Vec::new(),
+            validation_errors: Vec::new(),
         }
     }
 
     pub fn clear(&mut self) {
         self.triangle_edge_checks.clear();
+        self.validation_errors.clear();
+    }
+
+    /// Append the debug info that was collected elsewhere
+    ///
+    /// This is used to combine the debug info of work that was done in
+    /// parallel.
+    pub fn append(&mut self, other: Self) {
+        self.triangle_edge_checks.extend(other.triangle_edge_checks);
+        self.validation_errors.extend(other.validation_errors);
+    }
+}
+
+impl Default for DebugInfo {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 /// Record of a check to determine if a triangle edge is within a face
 pub struct TriangleEdgeCheck {
     pub ray: Ray,
-    pub hits: Vec<f64>,
+
+    /// The distances along the ray, at which it hits the edges of the face
+    ///
+    /// A check is recorded for every triangle, and most rays only hit a few
+    /// edges, so these are stored inline, without allocating.
+    pub hits: SmallVec<[f64; 4]>,
 }
 
 impl TriangleEdgeCheck {
     pub fn new(ray: Ray) -> Self {
         Self {
             ray,
-            hits: Vec::new(),
+            hits: SmallVec::new(),
         }
     }
 }
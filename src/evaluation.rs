@@ -0,0 +1,633 @@
+//! Evaluation of shapes
+//!
+//! Evaluating a shape means computing its boundary representation and
+//! triangulating that. Most things that are done with a shape, like displaying,
+//! exporting, or analyzing it, are based on the result.
+
+use std::{ops::Deref, sync::Arc};
+
+use crate::{
+    analysis::{manifold, stats::Stats},
+    debug::DebugInfo,
+    kernel::{
+        self,
+        algorithms::{approximation::Approximation, tolerance::Tolerance},
+        shape::Shape,
+        shapes::ToShape as _,
+    },
+    math::{Aabb, Projection, Scalar, Segment, Triangle, Vector},
+    mesh::FaceGroups,
+    platform,
+};
+
+/// Compute a tolerance value from the size of a shape on the screen
+///
+/// The tolerance is chosen, so the triangulation deviates from the actual
+/// geometry by no more than `pixels` pixels, where the shape's bounding box is
+/// closest to the camera. This way, shapes that appear small get coarse
+/// triangulations, while close-ups are refined.
+///
+/// `projection` transforms from model space into normalized device
+/// coordinates (see [`Projection`]), and `viewport_height` is the height of
+/// the screen in pixels.
+///
+/// To keep the triangulation from getting either degenerate or huge, the
+/// result stays within a factor of 10 below and 100 above the default
+/// tolerance (see [`Tolerance::from_bounding_box`]).
+///
+/// Returns `None`, if no corner of the bounding box is visible.
+pub fn screen_space_tolerance(
+    aabb: &Aabb<3>,
+    projection: &Projection,
+    viewport_height: f64,
+    pixels: f64,
+) -> Option<Tolerance> {
+    let inverse = projection.inverse();
+
+    // Normalized device coordinates go from -1 to 1, across the whole height
+    // of the viewport.
+    let offset = Vector::from([0., 2. / viewport_height * pixels, 0.]);
+
+    let mut tolerance: Option<Scalar> = None;
+    for vertex in aabb.vertices() {
+        let point = projection.transform_point(&vertex);
+
+        // Points outside this range are in front of the near plane, or behind
+        // the far plane. This includes points that are behind the camera.
+        if point.z < -Scalar::ONE || point.z > Scalar::ONE {
+            continue;
+        }
+
+        let distance =
+            (inverse.transform_point(&(point + offset)) - vertex).magnitude();
+        tolerance = Some(match tolerance {
+            Some(tolerance) => tolerance.min(distance),
+            None => distance,
+        });
+    }
+
+    let default = Tolerance::from_bounding_box(aabb).inner();
+    tolerance.map(|tolerance| {
+        let tolerance = tolerance
+            .max(default / Scalar::from_f64(10.))
+            .min(default * Scalar::from_f64(100.));
+
+        // The tolerance is clamped to a multiple of a valid tolerance, so it's
+        // valid too.
+        Tolerance::from_scalar(tolerance).expect("Invalid tolerance")
+    })
+}
+
+/// Triangulate a shape
+///
+/// Appends the triangles to `triangles`. Validation errors that were only
+/// logged as warnings while building the shape are added to `debug_info`.
+///
+/// Returns an error, if the kernel can't build the shape.
+pub fn triangulate(
+    shape: &fj::Shape,
+    tolerance: Tolerance,
+    triangles: &mut Vec<Triangle<3>>,
+    debug_info: &mut DebugInfo,
+) -> Result<(), kernel::Error> {
+    let mut shape = shape.to_shape(tolerance, debug_info)?;
+
+    debug_info
+        .validation_errors
+        .extend(shape.validation_warnings().iter().cloned());
+
+    shape.topology().triangles(tolerance, triangles, debug_info);
+
+    Ok(())
+}
+
+/// Triangulate a shape, recording which face each triangle came from
+///
+/// Like [`triangulate`], but also returns the number of triangles of each face
+/// of the shape, in order. Faces that don't result in any triangles are
+/// included too, so the face indices match the shape's topology. This allows
+/// exporters to group triangles by face, and analyses to map problems they
+/// find in the mesh back to the faces of the model.
+pub fn triangulate_by_face(
+    shape: &fj::Shape,
+    tolerance: Tolerance,
+    triangles: &mut Vec<Triangle<3>>,
+    debug_info: &mut DebugInfo,
+) -> Result<FaceGroups, kernel::Error> {
+    let shape = shape.to_shape(tolerance, debug_info)?;
+
+    debug_info
+        .validation_errors
+        .extend(shape.validation_warnings().iter().cloned());
+
+    Ok(triangulate_each_face(
+        &shape, tolerance, triangles, debug_info,
+    ))
+}
+
+/// Triangulate the faces of a shape in parallel
+///
+/// The faces are independent of each other, so they can be spread across all
+/// cores (see [`platform::map`]). The triangles and debug info are combined in
+/// the order of the faces, so the result is the same as if the faces were
+/// triangulated one after another.
+fn triangulate_each_face(
+    shape: &Shape,
+    tolerance: Tolerance,
+    triangles: &mut Vec<Triangle<3>>,
+    debug_info: &mut DebugInfo,
+) -> FaceGroups {
+    let faces: Vec<_> = shape.faces().collect();
+    let results = platform::map(faces, |face| {
+        let mut triangles = Vec::new();
+        let mut debug_info = DebugInfo::new();
+        face.triangles(tolerance, &mut triangles, &mut debug_info);
+        (triangles, debug_info)
+    });
+
+    let mut faces = FaceGroups::new();
+    for (face_triangles, face_debug_info) in results {
+        faces.push(face_triangles.len());
+        triangles.extend(face_triangles);
+        debug_info.append(face_debug_info);
+    }
+
+    faces
+}
+
+/// Triangulate a shape by face, and approximate its edges
+///
+/// Like [`triangulate_by_face`], but also returns segments that approximate
+/// the edges of the shape's boundary representation. Viewers can draw those
+/// on top of the triangles, to delineate the faces.
+pub fn triangulate_with_edges(
+    shape: &fj::Shape,
+    tolerance: Tolerance,
+    triangles: &mut Vec<Triangle<3>>,
+    debug_info: &mut DebugInfo,
+) -> Result<(FaceGroups, Vec<Segment<3>>), kernel::Error> {
+    let shape = shape.to_shape(tolerance, debug_info)?;
+
+    debug_info
+        .validation_errors
+        .extend(shape.validation_warnings().iter().cloned());
+
+    let faces = triangulate_each_face(&shape, tolerance, triangles, debug_info);
+    let edges = approximate_edges(&shape, tolerance);
+
+    Ok((faces, edges))
+}
+
+/// Approximate the edges of a shape
+fn approximate_edges(shape: &Shape, tolerance: Tolerance) -> Vec<Segment<3>> {
+    shape
+        .edges()
+        .flat_map(|edge| Approximation::for_edge(&edge, tolerance).segments)
+        .collect()
+}
+
+/// The triangles of one face of a shape
+///
+/// See [`triangulate_faces`].
+#[derive(Debug)]
+pub struct FaceTriangles {
+    /// The triangles that approximate the face
+    pub triangles: Vec<Triangle<3>>,
+
+    /// The bounding box of the triangles
+    pub aabb: Aabb<3>,
+}
+
+/// Triangulate a shape, keeping the triangles of each face separate
+///
+/// Like [`triangulate`], but returns the triangles of each face on their own,
+/// together with their bounding box. This allows consumers of the triangles,
+/// like viewers, to skip faces that are not visible (see
+/// [`Projection::can_see`]), or to load the faces of big shapes one by one.
+///
+/// Faces that don't result in any triangles are left out.
+pub fn triangulate_faces(
+    shape: &fj::Shape,
+    tolerance: Tolerance,
+    debug_info: &mut DebugInfo,
+) -> Result<Vec<FaceTriangles>, kernel::Error> {
+    let mut shape = shape.to_shape(tolerance, debug_info)?;
+
+    debug_info
+        .validation_errors
+        .extend(shape.validation_warnings().iter().cloned());
+
+    let faces = shape
+        .topology()
+        .faces()
+        .filter_map(|face| {
+            let mut triangles = Vec::new();
+            face.triangles(tolerance, &mut triangles, debug_info);
+
+            if triangles.is_empty() {
+                return None;
+            }
+
+            let aabb = Aabb::<3>::from_points(
+                triangles.iter().flat_map(|triangle| triangle.points()),
+            );
+            Some(FaceTriangles { triangles, aabb })
+        })
+        .collect();
+
+    Ok(faces)
+}
+
+/// Processes shapes into triangle meshes
+///
+/// Bundles the steps that are needed to get from a shape to a triangle mesh,
+/// so the host application, exporters, tests, and other code that embeds the
+/// host library all process shapes the same way. This is the recommended way
+/// to evaluate a shape, unless one of the functions in this module happens to
+/// fit better.
+///
+/// ``` rust
+/// use fj_host::evaluation::ShapeProcessor;
+///
+/// let square = fj::Sketch::from_points(vec![
+///     [0., 0.], [1., 0.], [1., 1.], [0., 1.],
+/// ]);
+/// let cube: fj::Shape = fj::Sweep { shape: square.into(), length: 1. }.into();
+///
+/// let processed = ShapeProcessor::new().process(&cube).unwrap();
+/// assert_eq!(processed.faces.len(), 3);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ShapeProcessor {
+    /// The tolerance to triangulate shapes with
+    ///
+    /// If this is `None`, the default tolerance is computed for each shape
+    /// (see [`Tolerance::from_bounding_box`]).
+    pub tolerance: Option<Tolerance>,
+
+    /// Whether to approximate the edges of shapes too
+    ///
+    /// See [`ProcessedShape::edges`].
+    pub edges: bool,
+}
+
+impl ShapeProcessor {
+    /// Create a shape processor that uses the default tolerance
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a shape processor that uses the given tolerance
+    pub fn with_tolerance(tolerance: Tolerance) -> Self {
+        Self {
+            tolerance: Some(tolerance),
+            ..Self::default()
+        }
+    }
+
+    /// Build a shape and triangulate it
+    ///
+    /// Returns an error, if the kernel can't build the shape.
+    pub fn process(
+        &self,
+        shape: &fj::Shape,
+    ) -> Result<ProcessedShape, kernel::Error> {
+        let (_, processed) = self.build(shape)?;
+        Ok(processed)
+    }
+
+    /// Build a shape and triangulate it, keeping the boundary representation
+    ///
+    /// Like [`ShapeProcessor::process`], but returns an [`Evaluation`], which
+    /// can be shared by everything that needs the shape.
+    ///
+    /// Returns an error, if the kernel can't build the shape.
+    pub fn evaluate(
+        &self,
+        shape: &fj::Shape,
+    ) -> Result<Evaluation, kernel::Error> {
+        let (brep, processed) = self.build(shape)?;
+        Ok(Evaluation(Arc::new(EvaluationInner { brep, processed })))
+    }
+
+    fn build(
+        &self,
+        shape: &fj::Shape,
+    ) -> Result<(Shape, ProcessedShape), kernel::Error> {
+        let aabb = shape.bounding_volume();
+        let tolerance = self
+            .tolerance
+            .unwrap_or_else(|| Tolerance::from_bounding_box(&aabb));
+
+        let mut debug_info = DebugInfo::new();
+        let brep = shape.to_shape(tolerance, &mut debug_info)?;
+
+        debug_info
+            .validation_errors
+            .extend(brep.validation_warnings().iter().cloned());
+
+        let mut triangles = Vec::new();
+        let faces = triangulate_each_face(
+            &brep,
+            tolerance,
+            &mut triangles,
+            &mut debug_info,
+        );
+        let edges = if self.edges {
+            approximate_edges(&brep, tolerance)
+        } else {
+            Vec::new()
+        };
+
+        let processed = ProcessedShape {
+            tolerance,
+            aabb,
+            triangles,
+            faces,
+            edges,
+            debug_info,
+        };
+
+        Ok((brep, processed))
+    }
+}
+
+/// A shape that has been processed into a triangle mesh
+///
+/// See [`ShapeProcessor`].
+pub struct ProcessedShape {
+    /// The tolerance that the shape was triangulated with
+    pub tolerance: Tolerance,
+
+    /// The bounding box of the shape
+    ///
+    /// This is computed from the shape itself, not from its triangles, so it
+    /// doesn't depend on the tolerance.
+    pub aabb: Aabb<3>,
+
+    /// The triangles that approximate the shape
+    pub triangles: Vec<Triangle<3>>,
+
+    /// The faces of the shape that the triangles came from
+    ///
+    /// See [`triangulate_by_face`].
+    pub faces: FaceGroups,
+
+    /// The approximated edges of the shape
+    ///
+    /// Only filled in, if [`ShapeProcessor::edges`] is set. See
+    /// [`triangulate_with_edges`].
+    pub edges: Vec<Segment<3>>,
+
+    /// Debug info that was collected while processing the shape
+    pub debug_info: DebugInfo,
+}
+
+impl ProcessedShape {
+    /// Compute statistics about the triangle mesh
+    ///
+    /// The triangles are used as they are, so the volume is only meaningful,
+    /// if they are oriented consistently. Use [`ProcessedShape::volume`], if
+    /// that's not known.
+    ///
+    /// Returns `None`, if the shape is empty.
+    pub fn stats(&self) -> Option<Stats> {
+        if self.triangles.is_empty() {
+            return None;
+        }
+
+        Some(Stats::compute(&self.triangles))
+    }
+
+    /// Compute the volume that the triangle mesh encloses
+    ///
+    /// Returns `None`, if the shape is empty, if the mesh is not closed, or if
+    /// its triangles can't be oriented consistently (see
+    /// [`manifold::orient`]).
+    pub fn volume(&self) -> Option<Scalar> {
+        if self.triangles.is_empty() {
+            return None;
+        }
+
+        let oriented = manifold::orient(&self.triangles)?;
+        Some(Stats::compute(&oriented).volume)
+    }
+}
+
+/// An evaluated shape, that can be shared between threads
+///
+/// Contains the boundary representation of the shape, as well as its
+/// [`ProcessedShape`], which it dereferences to. Neither can be modified, and
+/// cloning an `Evaluation` is cheap, as all clones share them. This way, any
+/// number of analyses, and the viewer, can read the same evaluation at the
+/// same time, without any of them having to build the shape again, or to copy
+/// it.
+///
+/// Created by [`ShapeProcessor::evaluate`].
+#[derive(Clone)]
+pub struct Evaluation(Arc<EvaluationInner>);
+
+struct EvaluationInner {
+    brep: Shape,
+    processed: ProcessedShape,
+}
+
+impl Evaluation {
+    /// Access the boundary representation of the shape
+    ///
+    /// Only the read-only methods of [`Shape`] are available, like
+    /// [`Shape::faces`].
+    pub fn brep(&self) -> &Shape {
+        &self.0.brep
+    }
+
+    /// Indicate whether two evaluations share the same data
+    ///
+    /// This is the case, if one is a clone of the other.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for Evaluation {
+    type Target = ProcessedShape;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0.processed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use approx::assert_abs_diff_eq;
+
+    use crate::{
+        debug::DebugInfo,
+        kernel::algorithms::tolerance::Tolerance,
+        math::{Aabb, Point, Projection, Scalar, Transform, Vector},
+        platform,
+        testing::cuboid,
+    };
+
+    use super::{
+        screen_space_tolerance, triangulate_by_face, triangulate_faces,
+        Evaluation, ShapeProcessor,
+    };
+
+    #[test]
+    fn screen_space_tolerance_should_follow_distance() {
+        let aabb = Aabb {
+            min: Point::from([-1., -1., -1.]),
+            max: Point::from([1., 1., 1.]),
+        };
+        let tolerance = |distance: f64| {
+            let camera_to_model: Projection =
+                Transform::translation(Vector::from([0., 0., -distance]))
+                    .into();
+            let projection = Projection::perspective(1., FRAC_PI_2, 0.1, 1000.)
+                * camera_to_model;
+
+            screen_space_tolerance(&aabb, &projection, 1000., 0.5)
+                .map(|tolerance| tolerance.inner())
+        };
+
+        // The closest corners are at a distance of 9. With a field of view of
+        // 90 degrees, the screen is 18 units high there.
+        assert_abs_diff_eq!(
+            tolerance(10.).unwrap(),
+            Scalar::from_f64(18. / 1000. * 0.5),
+            epsilon = 1e-9
+        );
+
+        // Far away, the shape only covers a few pixels.
+        assert_eq!(
+            tolerance(800.).unwrap(),
+            Tolerance::from_bounding_box(&aabb).inner() * 100.
+        );
+
+        // Behind the camera, there's nothing to see.
+        assert_eq!(tolerance(-10.), None);
+    }
+
+    #[test]
+    fn triangulate_faces_should_return_aabb_per_face() {
+        let cube: fj::Shape = cuboid([1., 1., 1.]).into();
+
+        let faces = triangulate_faces(
+            &cube,
+            Tolerance::from_scalar(0.001).unwrap(),
+            &mut DebugInfo::new(),
+        )
+        .unwrap();
+        // The bottom, the top, and the side walls, which are all part of
+        // the same face.
+        assert_eq!(faces.len(), 3);
+
+        let flat_faces = faces
+            .iter()
+            .filter(|face| face.aabb.size().z == Scalar::ZERO)
+            .count();
+        assert_eq!(flat_faces, 2);
+
+        let aabb = faces
+            .iter()
+            .map(|face| face.aabb)
+            .reduce(|a, b| a.merged(&b))
+            .unwrap();
+        assert_eq!(aabb.min, Point::from([0., 0., 0.]));
+        assert_eq!(aabb.max, Point::from([1., 1., 1.]));
+    }
+
+    #[test]
+    fn triangulate_by_face_should_group_triangles() {
+        let cube: fj::Shape = cuboid([1., 1., 1.]).into();
+        let tolerance = Tolerance::from_scalar(0.001).unwrap();
+
+        let mut triangles = Vec::new();
+        let faces = triangulate_by_face(
+            &cube,
+            tolerance,
+            &mut triangles,
+            &mut DebugInfo::new(),
+        )
+        .unwrap();
+
+        // The groups match the faces, in order.
+        let by_face =
+            triangulate_faces(&cube, tolerance, &mut DebugInfo::new()).unwrap();
+        assert_eq!(faces.len(), by_face.len());
+        for (range, face) in faces.iter().zip(&by_face) {
+            let triangles = &triangles[range];
+            assert_eq!(triangles.len(), face.triangles.len());
+
+            let aabb = Aabb::<3>::from_points(
+                triangles.iter().flat_map(|triangle| triangle.points()),
+            );
+            assert_eq!(aabb, face.aabb);
+        }
+    }
+
+    #[test]
+    fn shape_processor_should_triangulate_shape() {
+        let cube: fj::Shape = cuboid([2., 2., 2.]).into();
+
+        let processed = ShapeProcessor::new().process(&cube).unwrap();
+        assert_eq!(
+            processed.tolerance,
+            Tolerance::from_bounding_box(&processed.aabb)
+        );
+        assert_eq!(processed.faces.len(), 3);
+        assert!(processed.edges.is_empty());
+
+        let stats = processed.stats().unwrap();
+        assert_eq!(stats.triangles, processed.triangles.len());
+        assert_eq!(stats.aabb, processed.aabb);
+        assert_abs_diff_eq!(
+            processed.volume().unwrap(),
+            Scalar::from_f64(8.),
+            epsilon = 1e-9
+        );
+
+        let tolerance = Tolerance::from_scalar(0.01).unwrap();
+        let processor = ShapeProcessor {
+            edges: true,
+            ..ShapeProcessor::with_tolerance(tolerance)
+        };
+        let processed = processor.process(&cube).unwrap();
+        assert_eq!(processed.tolerance, tolerance);
+        assert!(!processed.edges.is_empty());
+    }
+
+    #[test]
+    fn evaluation_should_be_readable_from_many_threads() {
+        let cube: fj::Shape = cuboid([2., 2., 2.]).into();
+
+        let evaluation = ShapeProcessor::new().evaluate(&cube).unwrap();
+        let processed = ShapeProcessor::new().process(&cube).unwrap();
+        assert_eq!(evaluation.triangles.len(), processed.triangles.len());
+        assert_eq!(evaluation.brep().faces().count(), processed.faces.len());
+
+        // Every thread gets a clone, and all of them read the same data.
+        let clones = vec![evaluation.clone(); 4];
+        let results = platform::map(clones, |clone: Evaluation| {
+            let area = clone
+                .brep()
+                .faces()
+                .map(|face| face.area(clone.tolerance))
+                .fold(Scalar::ZERO, |sum, area| sum + area);
+            (clone.volume(), area, clone.ptr_eq(&evaluation))
+        });
+
+        for (volume, area, shared) in results {
+            assert_abs_diff_eq!(
+                volume.unwrap(),
+                Scalar::from_f64(8.),
+                epsilon = 1e-9
+            );
+            assert_abs_diff_eq!(area, Scalar::from_f64(24.), epsilon = 1e-9);
+            assert!(shared);
+        }
+    }
+}
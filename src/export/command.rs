@@ -0,0 +1,183 @@
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::Path,
+    process::{self, Stdio},
+};
+
+use super::{Error, Exporter, Options};
+
+/// An exporter that runs an external program to convert another format
+///
+/// This makes it possible to add formats without writing any code, for
+/// example using a converter that comes with another application. The shape is
+/// exported to an intermediate format first, using the built-in exporters,
+/// and written to a temporary file next to the output. Then the program is run
+/// with its arguments, in which `{input}` is replaced with the path of the
+/// temporary file, and `{output}` with the path that is being exported to.
+/// The temporary file is removed afterwards.
+///
+/// The options are passed on to the intermediate format.
+pub struct CommandExporter {
+    name: String,
+    extension: String,
+    via: String,
+    program: String,
+    args: Vec<String>,
+}
+
+impl CommandExporter {
+    /// Create an exporter for files with the given extension
+    ///
+    /// `via` is the extension of the intermediate format. `command` is the
+    /// program, followed by its arguments.
+    ///
+    /// Returns `None`, if `command` is empty.
+    pub fn new(
+        name: String,
+        extension: String,
+        via: String,
+        command: Vec<String>,
+    ) -> Option<Self> {
+        let mut command = command.into_iter();
+        let program = command.next()?;
+
+        Some(Self {
+            name,
+            extension: extension.to_lowercase(),
+            via,
+            program,
+            args: command.collect(),
+        })
+    }
+
+    fn run(&self, input: &Path, output: &Path) -> Result<(), Error> {
+        let args = self.args.iter().map(|arg| {
+            let mut replaced = OsString::new();
+            for (i, part) in arg.split("{input}").enumerate() {
+                if i > 0 {
+                    replaced.push(input);
+                }
+                for (j, part) in part.split("{output}").enumerate() {
+                    if j > 0 {
+                        replaced.push(output);
+                    }
+                    replaced.push(part);
+                }
+            }
+            replaced
+        });
+
+        let status = process::Command::new(&self.program)
+            .args(args)
+            .stdin(Stdio::null())
+            .status()
+            .map_err(|err| self.error(err))?;
+        if !status.success() {
+            let message = format!("`{}` failed ({})", self.program, status);
+            return Err(
+                self.error(io::Error::new(io::ErrorKind::Other, message))
+            );
+        }
+
+        Ok(())
+    }
+
+    fn error(&self, err: io::Error) -> Error {
+        Error::Other {
+            format: self.name.clone(),
+            source: err.into(),
+        }
+    }
+}
+
+impl Exporter for CommandExporter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        vec![self.extension.as_str()]
+    }
+
+    fn export(
+        &self,
+        path: &Path,
+        shape: &fj::Shape,
+        options: &Options,
+    ) -> Result<(), Error> {
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| Error::UnknownFormat(path.to_owned()))?;
+        let mut temporary = OsString::from(".");
+        temporary.push(file_name);
+        temporary.push(".");
+        temporary.push(&self.via);
+        let temporary = path.with_file_name(temporary);
+
+        let result = super::export(&temporary, shape, options)
+            .and_then(|()| self.run(&temporary, path));
+
+        // The temporary file might not exist, if the export failed, which is
+        // fine. The error that matters is the one from the export.
+        let _ = fs::remove_file(&temporary);
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use crate::export::{Exporter as _, Options};
+
+    use super::CommandExporter;
+
+    #[test]
+    #[cfg(unix)]
+    fn export_should_run_command() {
+        let directory = env::temp_dir()
+            .join(format!("fj-command-exporter-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        let exporter = CommandExporter::new(
+            "Copy".into(),
+            "copy".into(),
+            "stl".into(),
+            vec!["cp".into(), "{input}".into(), "{output}".into()],
+        )
+        .unwrap();
+        assert_eq!(exporter.extensions(), ["copy"]);
+
+        let shape = fj::Sweep {
+            shape: fj::Circle {
+                center: [0., 0.],
+                radius: 1.,
+            }
+            .into(),
+            length: 1.,
+        }
+        .into();
+
+        let path = directory.join("shape.copy");
+        exporter.export(&path, &shape, &Options::default()).unwrap();
+
+        // The output is a copy of the STL file, which is removed.
+        assert!(fs::read(&path).unwrap().len() > 84);
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 1);
+
+        let failing = CommandExporter::new(
+            "Fail".into(),
+            "fail".into(),
+            "stl".into(),
+            vec!["false".into()],
+        )
+        .unwrap();
+        let result = failing.export(&path, &shape, &Options::default());
+        assert!(result.is_err());
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 1);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
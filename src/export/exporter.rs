@@ -0,0 +1,149 @@
+use std::path::Path;
+
+use super::{Error, Options};
+
+/// A file format that shapes can be exported to
+///
+/// Implement this trait to add a format, then register the implementation
+/// with [`Exporters`]. Exporters that write meshes can use
+/// [`super::triangles`] and [`super::mesh`] to triangulate the shape.
+pub trait Exporter: Send + Sync {
+    /// The name of the format, as shown to the user
+    fn name(&self) -> &str;
+
+    /// The file extensions of the format
+    ///
+    /// The extensions are lowercase, and don't include the leading dot.
+    fn extensions(&self) -> Vec<&str>;
+
+    /// Export a shape to the file at `path`
+    ///
+    /// Should return [`Error::UnsupportedOption`], if any of the `options`
+    /// can't be honored, instead of silently ignoring it.
+    fn export(
+        &self,
+        path: &Path,
+        shape: &fj::Shape,
+        options: &Options,
+    ) -> Result<(), Error>;
+}
+
+/// A registry of exporters, keyed by file extension
+pub struct Exporters {
+    exporters: Vec<Box<dyn Exporter>>,
+}
+
+impl Exporters {
+    /// Create a registry without any exporters
+    ///
+    /// Use [`Exporters::default`] to get one with the built-in exporters.
+    pub fn empty() -> Self {
+        Self {
+            exporters: Vec::new(),
+        }
+    }
+
+    /// Add an exporter to the registry
+    ///
+    /// If it handles an extension that an already registered exporter handles
+    /// too, the new exporter takes precedence. This makes it possible to
+    /// replace built-in exporters.
+    pub fn register(&mut self, exporter: impl Exporter + 'static) {
+        self.exporters.push(Box::new(exporter));
+    }
+
+    /// Find the exporter for the given file extension
+    ///
+    /// The extension is compared case-insensitively.
+    pub fn find(&self, extension: &str) -> Option<&dyn Exporter> {
+        let extension = extension.to_lowercase();
+
+        self.exporters
+            .iter()
+            .rev()
+            .find(|exporter| exporter.extensions().contains(&&*extension))
+            .map(|exporter| &**exporter)
+    }
+
+    /// Iterate over all registered exporters, in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Exporter> {
+        self.exporters.iter().map(|exporter| &**exporter)
+    }
+
+    /// Export a shape, choosing the format based on the extension of `path`
+    pub fn export(
+        &self,
+        path: &Path,
+        shape: &fj::Shape,
+        options: &Options,
+    ) -> Result<(), Error> {
+        let exporter = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| self.find(extension))
+            .ok_or_else(|| Error::UnknownFormat(path.to_owned()))?;
+
+        exporter.export(path, shape, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, path::Path};
+
+    use crate::export::{Error, Options};
+
+    use super::{Exporter, Exporters};
+
+    struct Custom;
+
+    impl Exporter for Custom {
+        fn name(&self) -> &str {
+            "custom"
+        }
+
+        fn extensions(&self) -> Vec<&str> {
+            vec!["stl", "off"]
+        }
+
+        fn export(
+            &self,
+            _: &Path,
+            _: &fj::Shape,
+            _: &Options,
+        ) -> Result<(), Error> {
+            Err(Error::Other {
+                format: self.name().into(),
+                source: io::Error::from(io::ErrorKind::Other).into(),
+            })
+        }
+    }
+
+    #[test]
+    fn find_and_export() {
+        let mut exporters = Exporters::default();
+        assert_eq!(exporters.find("STL").map(|e| e.name()), Some("STL"));
+        assert!(exporters.find("off").is_none());
+
+        // Later exporters take precedence.
+        exporters.register(Custom);
+        assert_eq!(exporters.find("stl").map(|e| e.name()), Some("custom"));
+        assert_eq!(exporters.find("3mf").map(|e| e.name()), Some("3MF"));
+
+        let shape = fj::Circle {
+            center: [0., 0.],
+            radius: 1.,
+        }
+        .into();
+
+        let options = Options::default();
+
+        match exporters.export(Path::new("shape.OFF"), &shape, &options) {
+            Err(Error::Other { format, .. }) => assert_eq!(format, "custom"),
+            result => panic!("Unexpected result: {:?}", result),
+        }
+
+        let result = exporters.export(Path::new("shape"), &shape, &options);
+        assert!(matches!(result, Err(Error::UnknownFormat(_))));
+    }
+}
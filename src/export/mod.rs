@@ -0,0 +1,451 @@
+//! Export of shapes to files
+//!
+//! The file format is selected based on the extension of the path that is
+//! being exported to. Formats are provided by [`Exporter`]s, which are kept in
+//! a registry ([`Exporters`]). Additional formats can be added by registering
+//! more exporters, including ones that run an external program to convert
+//! another format ([`CommandExporter`]).
+//!
+//! How exactly shapes are written, for example in which units, is controlled
+//! by [`Options`], which are passed to every exporter.
+
+pub mod ply;
+pub mod stl;
+pub mod svg;
+pub mod vtk;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod command;
+mod exporter;
+mod options;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::command::CommandExporter;
+pub use self::{
+    exporter::{Exporter, Exporters},
+    options::{OptionKind, Options, Units},
+};
+
+use std::{
+    fs::File,
+    io::{self, BufWriter},
+    path::Path,
+};
+
+use thiserror::Error;
+
+use crate::{
+    analysis::voxels::{self, Grid, Voxels},
+    evaluation::{ProcessedShape, ShapeProcessor},
+    kernel,
+    math::{Point, Scalar, Triangle},
+    mesh::{EdgeWelder, FaceGroups, Index, IndexOverflow, MeshMaker, Vertex},
+};
+
+/// Export a shape to a file, using the built-in exporters
+///
+/// Supported formats are 3MF (`.3mf`), PLY (`.ply`), and STL (`.stl`), to
+/// which the triangulated shape is exported, SVG (`.svg`), which is only
+/// supported for 2-dimensional shapes (see [`svg::write`]), and VTK (`.vtk`),
+/// to which the voxelized shape is exported (see [`vtk::write`]). 3MF isn't
+/// available on the web (see [`crate::platform`]).
+pub fn export(
+    path: &Path,
+    shape: &fj::Shape,
+    options: &Options,
+) -> Result<(), Error> {
+    Exporters::default().export(path, shape, options)
+}
+
+impl Default for Exporters {
+    fn default() -> Self {
+        let mut exporters = Self::empty();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        exporters.register(ThreeMf);
+        exporters.register(Ply);
+        exporters.register(Stl);
+        exporters.register(Svg);
+        exporters.register(Vtk);
+
+        exporters
+    }
+}
+
+/// Triangulate a shape for export, using the default tolerance
+pub fn triangles(shape: &fj::Shape) -> Result<Vec<Triangle<3>>, Error> {
+    Ok(ShapeProcessor::new().process(shape)?.triangles)
+}
+
+/// Voxelize a shape for export, using the default tolerance
+///
+/// The grid covers the bounding box of the shape. If `size` is `None`, the
+/// longest side of the bounding box is divided into 128 voxels. Returns
+/// [`Error::Not3d`] for shapes that have no volume to voxelize, and
+/// [`Error::Voxels`], if `size` doesn't result in a valid grid.
+pub fn voxels(
+    shape: &fj::Shape,
+    size: Option<Scalar>,
+) -> Result<Voxels, Error> {
+    if let fj::Shape::Shape2d(_) = shape {
+        return Err(Error::Not3d { format: "VTK" });
+    }
+
+    let ProcessedShape {
+        aabb, triangles, ..
+    } = ShapeProcessor::new().process(shape)?;
+
+    let grid = match size {
+        Some(size) => Grid::new(&aabb, size)?,
+        None => Grid::with_resolution(&aabb, 128)
+            .ok_or(Error::Not3d { format: "VTK" })?,
+    };
+
+    Ok(grid.voxelize(&triangles))
+}
+
+/// Triangulate a shape for export, recording which face each triangle came
+/// from
+///
+/// See [`ProcessedShape::faces`]. The `options` are applied to the triangles of
+/// each face (see [`Options::transform_triangles`]), and the face groups are
+/// updated accordingly. If [`Options::weld`] is set, the vertices along the
+/// edges of the shape are welded first (see [`EdgeWelder`]).
+pub fn triangles_by_face(
+    shape: &fj::Shape,
+    options: &Options,
+) -> Result<(Vec<Triangle<3>>, FaceGroups), Error> {
+    let processor = ShapeProcessor {
+        edges: options.weld,
+        ..ShapeProcessor::new()
+    };
+    let ProcessedShape {
+        tolerance,
+        triangles,
+        faces,
+        edges,
+        ..
+    } = processor.process(shape)?;
+
+    // Welding only needs to make up for rounding errors, so the distance can
+    // be much smaller than the tolerance. That way, no detail gets lost.
+    let welder = options
+        .weld
+        .then(|| EdgeWelder::new(&edges, tolerance.inner() / 100.));
+
+    let mut transformed = Vec::with_capacity(triangles.len());
+    let mut transformed_faces = FaceGroups::new();
+    for range in faces.iter() {
+        let face = match &welder {
+            Some(welder) => options
+                .transform_triangles(&welder.weld_triangles(&triangles[range])),
+            None => options.transform_triangles(&triangles[range]),
+        };
+        transformed_faces.push(face.len());
+        transformed.extend(face);
+    }
+
+    Ok((transformed, transformed_faces))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct ThreeMf;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Exporter for ThreeMf {
+    fn name(&self) -> &str {
+        "3MF"
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        vec!["3mf"]
+    }
+
+    fn export(
+        &self,
+        path: &Path,
+        shape: &fj::Shape,
+        options: &Options,
+    ) -> Result<(), Error> {
+        // 3MF files declare their units, which are always millimeters when
+        // written by the `threemf` crate.
+        if options.units != Units::Millimeters {
+            return Err(Error::UnsupportedOption {
+                format: "3MF",
+                option: "units other than millimeters",
+            });
+        }
+
+        options.reject(
+            "3MF",
+            &[
+                OptionKind::Ascii,
+                OptionKind::CompactIndices,
+                OptionKind::VoxelSize,
+            ],
+        )?;
+
+        let (triangles, _) = triangles_by_face(shape, options)?;
+        let (vertices, triangles) = mesh(&triangles)?;
+
+        let mesh = threemf::TriangleMesh {
+            vertices: vertices
+                .into_iter()
+                .map(|vertex| vertex.into())
+                .collect(),
+            triangles: triangles
+                .into_iter()
+                .map(|triangle| triangle.map(|index| index as usize))
+                .collect(),
+        };
+
+        threemf::write(path, &mesh)?;
+
+        Ok(())
+    }
+}
+
+struct Ply;
+
+impl Exporter for Ply {
+    fn name(&self) -> &str {
+        "PLY"
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        vec!["ply"]
+    }
+
+    fn export(
+        &self,
+        path: &Path,
+        shape: &fj::Shape,
+        options: &Options,
+    ) -> Result<(), Error> {
+        options.reject("PLY", &[OptionKind::Ascii, OptionKind::VoxelSize])?;
+
+        let (triangles, faces) = triangles_by_face(shape, options)?;
+        let (vertices, triangles) = mesh(&triangles)?;
+        let index_format = options.index_format(vertices.len())?;
+        let vertices: Vec<_> = vertices.into_iter().map(Vertex::new).collect();
+
+        let writer = BufWriter::new(File::create(path)?);
+        ply::write_with_faces(
+            writer,
+            &vertices,
+            &triangles,
+            index_format,
+            &faces,
+        )?;
+        Ok(())
+    }
+}
+
+struct Stl;
+
+impl Exporter for Stl {
+    fn name(&self) -> &str {
+        "STL"
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        vec!["stl"]
+    }
+
+    fn export(
+        &self,
+        path: &Path,
+        shape: &fj::Shape,
+        options: &Options,
+    ) -> Result<(), Error> {
+        options.reject(
+            "STL",
+            &[OptionKind::CompactIndices, OptionKind::VoxelSize],
+        )?;
+
+        let (triangles, _) = triangles_by_face(shape, options)?;
+        let writer = BufWriter::new(File::create(path)?);
+
+        if options.ascii {
+            stl::write_ascii(writer, &triangles)?;
+        } else {
+            stl::write(writer, &triangles)?;
+        }
+
+        Ok(())
+    }
+}
+
+struct Svg;
+
+impl Exporter for Svg {
+    fn name(&self) -> &str {
+        "SVG (2-dimensional shapes only)"
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        vec!["svg"]
+    }
+
+    fn export(
+        &self,
+        path: &Path,
+        shape: &fj::Shape,
+        options: &Options,
+    ) -> Result<(), Error> {
+        // SVG files are 2-dimensional, so the coordinate system doesn't
+        // apply. They are always written in millimeters, to preserve the size
+        // of the shape for laser cutters.
+        if options.units != Units::Millimeters {
+            return Err(Error::UnsupportedOption {
+                format: "SVG",
+                option: "units other than millimeters",
+            });
+        }
+        options.reject(
+            "SVG",
+            &[
+                OptionKind::Ascii,
+                OptionKind::Precision,
+                OptionKind::Coordinates,
+                OptionKind::CompactIndices,
+                OptionKind::Weld,
+                OptionKind::VoxelSize,
+            ],
+        )?;
+
+        match shape {
+            fj::Shape::Shape2d(shape) => {
+                svg::write(BufWriter::new(File::create(path)?), shape)?;
+                Ok(())
+            }
+            fj::Shape::Shape3d(_) => Err(Error::Not2d),
+        }
+    }
+}
+
+struct Vtk;
+
+impl Exporter for Vtk {
+    fn name(&self) -> &str {
+        "VTK (voxels of 3-dimensional shapes)"
+    }
+
+    fn extensions(&self) -> Vec<&str> {
+        vec!["vtk"]
+    }
+
+    fn export(
+        &self,
+        path: &Path,
+        shape: &fj::Shape,
+        options: &Options,
+    ) -> Result<(), Error> {
+        // The grid is written in model space. Converting it would mean
+        // scaling, swapping, or mirroring its axes, which the readers of
+        // volume data don't expect.
+        if options.units != Units::Millimeters {
+            return Err(Error::UnsupportedOption {
+                format: "VTK",
+                option: "units other than millimeters",
+            });
+        }
+        options.reject(
+            "VTK",
+            &[
+                OptionKind::Precision,
+                OptionKind::Coordinates,
+                OptionKind::CompactIndices,
+                OptionKind::Weld,
+            ],
+        )?;
+
+        let voxels = voxels(shape, options.voxel_size)?;
+        let writer = BufWriter::new(File::create(path)?);
+
+        if options.ascii {
+            vtk::write_ascii(writer, &voxels)?;
+        } else {
+            vtk::write(writer, &voxels)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Convert triangles into an indexed mesh, with shared vertices
+///
+/// Returns an error, if the mesh has more vertices than an [`Index`] can refer
+/// to.
+pub fn mesh(
+    triangles: &[Triangle<3>],
+) -> Result<(Vec<Point<3>>, Vec<[Index; 3]>), IndexOverflow> {
+    let mut mesh_maker = MeshMaker::new();
+
+    for triangle in triangles {
+        for vertex in triangle.points() {
+            mesh_maker.push(vertex)?;
+        }
+    }
+
+    let vertices = mesh_maker.vertices().collect();
+
+    let indices: Vec<_> = mesh_maker.indices().collect();
+    let triangles = indices
+        .chunks(3)
+        .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+        .collect();
+
+    Ok((vertices, triangles))
+}
+
+/// An error that can occur while exporting
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Can't determine export format from path `{0}`")]
+    UnknownFormat(std::path::PathBuf),
+
+    #[error("Only 2-dimensional shapes can be exported to SVG")]
+    Not2d,
+
+    #[error("Only 3-dimensional shapes can be exported to {format}")]
+    Not3d {
+        /// The name of the format
+        format: &'static str,
+    },
+
+    #[error("{format} export doesn't support {option}")]
+    UnsupportedOption {
+        /// The name of the format
+        format: &'static str,
+
+        /// A description of the unsupported option
+        option: &'static str,
+    },
+
+    #[error("I/O error while exporting")]
+    Io(#[from] io::Error),
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("Error exporting to 3MF")]
+    ThreeMf(#[from] threemf::Error),
+
+    #[error("Error building shape")]
+    Kernel(#[from] kernel::Error),
+
+    #[error("Shape is too complex to export")]
+    IndexOverflow(#[from] IndexOverflow),
+
+    #[error("Invalid voxel grid")]
+    Voxels(#[from] voxels::Error),
+
+    /// An error from an exporter that isn't built in
+    #[error("Error exporting to {format}")]
+    Other {
+        /// The name of the format
+        format: String,
+
+        /// The error that the exporter encountered
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
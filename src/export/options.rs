@@ -0,0 +1,342 @@
+use std::str::FromStr;
+
+use tracing::warn;
+
+use crate::{
+    convention::{CoordinateSystem, ParseError},
+    math::{Frame, Point, Scalar, Triangle, Vector},
+    mesh::{IndexFormat, IndexOverflow},
+};
+
+/// Options that control how shapes are exported
+///
+/// Different tools expect different conventions. Slicers usually expect
+/// millimeters and a z-axis that points up, while game engines often expect
+/// meters and a y-axis that points up, sometimes in a left-handed coordinate
+/// system. These options are shared by all exporters, but not every exporter
+/// supports all of them. Exporters return [`super::Error::UnsupportedOption`],
+/// if they can't honor an option (see [`Options::reject`]).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    /// Write the text-based variant of the format, if it has one
+    ///
+    /// This only makes a difference for STL and VTK, which are written in
+    /// their binary variants by default.
+    pub ascii: bool,
+
+    /// The units that coordinates are written in (millimeters, by default)
+    pub units: Units,
+
+    /// Round coordinates to this many decimal places
+    ///
+    /// Triangles that become degenerate because of that are left out.
+    pub precision: Option<u32>,
+
+    /// The coordinate system of the exported file (Fornjot's, by default)
+    pub coordinates: CoordinateSystem,
+
+    /// Use 16-bit vertex indices, if the mesh is small enough
+    ///
+    /// This only makes a difference for PLY, whose indices are declared as
+    /// 32-bit by default. Some readers, like those of older game engines,
+    /// only support 16-bit indices.
+    pub compact_indices: bool,
+
+    /// Make adjacent faces share the vertices along their common edges
+    ///
+    /// The faces of a shape are triangulated separately, which can leave the
+    /// vertices of neighboring faces a tiny distance apart. This snaps them to
+    /// the edges of the shape (see [`crate::mesh::EdgeWelder`]). Which face
+    /// each triangle belongs to is not affected. This only makes a difference
+    /// for 3MF, PLY, and STL.
+    pub weld: bool,
+
+    /// The edge length of voxels, in millimeters, for volume formats
+    ///
+    /// This only makes a difference for VTK. By default, the longest side of
+    /// the shape's bounding box is divided into 128 voxels.
+    pub voxel_size: Option<Scalar>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            ascii: false,
+            units: Units::Millimeters,
+            precision: None,
+            coordinates: CoordinateSystem::FORNJOT,
+            compact_indices: false,
+            weld: false,
+            voxel_size: None,
+        }
+    }
+}
+
+impl Options {
+    /// Return an error, if any of the given options is set
+    ///
+    /// Exporters call this with the options they don't support. Options count
+    /// as set, if they differ from their defaults.
+    pub fn reject(
+        &self,
+        format: &'static str,
+        unsupported: &[OptionKind],
+    ) -> Result<(), super::Error> {
+        let defaults = Self::default();
+
+        for &option in unsupported {
+            let is_set = match option {
+                OptionKind::Ascii => self.ascii != defaults.ascii,
+                OptionKind::Precision => self.precision != defaults.precision,
+                OptionKind::Coordinates => {
+                    self.coordinates != defaults.coordinates
+                }
+                OptionKind::CompactIndices => {
+                    self.compact_indices != defaults.compact_indices
+                }
+                OptionKind::Weld => self.weld != defaults.weld,
+                OptionKind::VoxelSize => self.voxel_size != defaults.voxel_size,
+            };
+
+            if is_set {
+                return Err(super::Error::UnsupportedOption {
+                    format,
+                    option: option.description(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The format of the vertex indices of a mesh with this many vertices
+    ///
+    /// Returns an error, if even 32-bit indices can't refer to all vertices.
+    pub fn index_format(
+        &self,
+        num_vertices: usize,
+    ) -> Result<IndexFormat, IndexOverflow> {
+        if self.compact_indices {
+            IndexFormat::for_vertices(num_vertices)
+        } else {
+            IndexFormat::U32.check(num_vertices)?;
+            Ok(IndexFormat::U32)
+        }
+    }
+
+    /// Transform a point from model space into the exported coordinates
+    pub fn transform_point(&self, point: Point<3>) -> Point<3> {
+        self.scale(self.coordinates.from_fornjot(point))
+    }
+
+    /// Transform a frame from model space into the exported coordinates
+    ///
+    /// The origin is transformed like any other point, while the axes keep a
+    /// length of one. If the coordinate system is left-handed, the frame is
+    /// mirrored along with the geometry, which makes it left-handed too.
+    pub fn transform_frame(&self, frame: &Frame) -> Frame {
+        let axis = |axis: Vector<3>| {
+            self.coordinates.from_fornjot(Point::origin() + axis).coords
+        };
+
+        Frame {
+            origin: self.transform_point(frame.origin),
+            x_axis: axis(frame.x_axis),
+            y_axis: axis(frame.y_axis),
+            z_axis: axis(frame.z_axis),
+        }
+    }
+
+    /// Transform triangles from model space into the exported coordinates
+    ///
+    /// Triangles that become degenerate due to rounding are left out, and a
+    /// warning is logged. If the coordinate system is left-handed, the winding
+    /// order of the triangles is reversed (see
+    /// [`CoordinateSystem::triangle_from_fornjot`]).
+    pub fn transform_triangles(
+        &self,
+        triangles: &[Triangle<3>],
+    ) -> Vec<Triangle<3>> {
+        let transformed: Vec<_> = triangles
+            .iter()
+            .filter_map(|triangle| {
+                let [a, b, c] = self
+                    .coordinates
+                    .triangle_from_fornjot(triangle.points())
+                    .map(|point| self.scale(point));
+
+                let area = (b - a).cross(&(c - a)).magnitude();
+                if area == Scalar::ZERO {
+                    return None;
+                }
+
+                Some(Triangle::from([a, b, c]))
+            })
+            .collect();
+
+        let degenerate = triangles.len() - transformed.len();
+        if degenerate > 0 {
+            warn!(
+                "Left out {} triangles, which became degenerate when rounding \
+                their coordinates",
+                degenerate
+            );
+        }
+
+        transformed
+    }
+
+    /// Convert a point into the configured units, and round it
+    fn scale(&self, point: Point<3>) -> Point<3> {
+        let scale = self.units.per_millimeter();
+
+        Point::from(point.coords.components().map(|coord| {
+            let coord = coord.into_f64() * scale;
+            match self.precision {
+                Some(precision) => {
+                    let factor = 10f64.powi(precision as i32);
+                    (coord * factor).round() / factor
+                }
+                None => coord,
+            }
+        }))
+    }
+}
+
+/// An option that not every exporter supports
+///
+/// See [`Options::reject`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OptionKind {
+    /// [`Options::ascii`]
+    Ascii,
+
+    /// [`Options::precision`]
+    Precision,
+
+    /// [`Options::coordinates`]
+    Coordinates,
+
+    /// [`Options::compact_indices`]
+    CompactIndices,
+
+    /// [`Options::weld`]
+    Weld,
+
+    /// [`Options::voxel_size`]
+    VoxelSize,
+}
+
+impl OptionKind {
+    /// A description of the option, for error messages
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Ascii => "choosing between text and binary variants",
+            Self::Precision => "rounding coordinates",
+            Self::Coordinates => "coordinate systems other than Fornjot's",
+            Self::CompactIndices => "16-bit vertex indices",
+            Self::Weld => "welding vertices",
+            Self::VoxelSize => "voxel sizes",
+        }
+    }
+}
+
+/// The units of exported coordinates
+///
+/// Models are defined in millimeters.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Units {
+    /// Millimeters (`mm`)
+    Millimeters,
+
+    /// Centimeters (`cm`)
+    Centimeters,
+
+    /// Meters (`m`)
+    Meters,
+
+    /// Inches (`in`)
+    Inches,
+}
+
+impl Units {
+    /// The number of these units in one millimeter
+    pub fn per_millimeter(&self) -> f64 {
+        match self {
+            Self::Millimeters => 1.,
+            Self::Centimeters => 0.1,
+            Self::Meters => 0.001,
+            Self::Inches => 1. / 25.4,
+        }
+    }
+}
+
+impl FromStr for Units {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mm" => Ok(Self::Millimeters),
+            "cm" => Ok(Self::Centimeters),
+            "m" => Ok(Self::Meters),
+            "in" => Ok(Self::Inches),
+            _ => Err(ParseError("units", "mm, cm, m, in")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{Frame, Point, Triangle, Vector};
+
+    use crate::convention::{CoordinateSystem, Handedness, UpAxis};
+
+    use super::{OptionKind, Options, Units};
+
+    #[test]
+    fn transform() {
+        let options = Options {
+            units: Units::Centimeters,
+            precision: Some(1),
+            coordinates: CoordinateSystem {
+                up: UpAxis::Y,
+                handedness: Handedness::Right,
+            },
+            ..Options::default()
+        };
+
+        assert_eq!(
+            options.transform_point(Point::from([12., 34., 56.])),
+            Point::from([1.2, 5.6, -3.4])
+        );
+
+        // Triangles that are too small for the precision are left out.
+        let triangle = |size: f64| {
+            Triangle::from(
+                [[0., 0., 0.], [size, 0., 0.], [0., size, 0.]].map(Point::from),
+            )
+        };
+        let [big, small] = [triangle(20.), triangle(0.2)];
+        assert_eq!(options.transform_triangles(&[big, small]).len(), 1);
+
+        // Frames are moved and rotated, but not scaled.
+        let frame = Frame::from_axes(
+            Point::from([12., 34., 56.]),
+            Vector::from([1., 0., 0.]),
+            Vector::from([0., 0., 1.]),
+        )
+        .unwrap();
+        let frame = options.transform_frame(&frame);
+        assert_eq!(frame.origin, Point::from([1.2, 5.6, -3.4]));
+        assert_eq!(frame.y_axis, Vector::from([0., 0., -1.]));
+        assert_eq!(frame.z_axis, Vector::from([0., 1., 0.]));
+
+        // Options that are set can be rejected by exporters that don't
+        // support them.
+        assert!(options.reject("X", &[OptionKind::Ascii]).is_ok());
+        assert!(options.reject("X", &[OptionKind::Precision]).is_err());
+
+        assert_eq!("in".parse::<Units>().unwrap(), Units::Inches);
+        assert!("ft".parse::<Units>().is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use std::io;
+
+use crate::{
+    analysis::field::{Colormap, FieldLocation, ScalarField},
+    math::{Point, Scalar},
+    mesh::{FaceGroups, Index, IndexFormat, Vertex},
+};
+
+/// Write a triangle mesh as an ASCII PLY file
+///
+/// Besides their positions, the attributes of the vertices (normals, texture
+/// coordinates, and colors) are written. Each attribute must be present for
+/// either all vertices or none of them. Unlike STL and 3MF, PLY supports
+/// per-vertex colors, which makes it suitable for visualizing analysis
+/// results, like the deviation from a nominal shape.
+///
+/// The vertex indices of the triangles are declared as `index_format`, which
+/// must be able to refer to all vertices. `triangles` may be empty, in which
+/// case a point cloud is written.
+pub fn write(
+    writer: impl io::Write,
+    vertices: &[Vertex],
+    triangles: &[[Index; 3]],
+    index_format: IndexFormat,
+) -> io::Result<()> {
+    write_mesh(writer, vertices, triangles, index_format, None)
+}
+
+/// Write a triangle mesh, recording which face of the shape each triangle
+/// came from
+///
+/// Like [`write`], but each triangle gets a `face` property, which contains
+/// the index of its face in `faces`. Applications like MeshLab can use that
+/// to select or color the triangles of a face.
+pub fn write_with_faces(
+    writer: impl io::Write,
+    vertices: &[Vertex],
+    triangles: &[[Index; 3]],
+    index_format: IndexFormat,
+    faces: &FaceGroups,
+) -> io::Result<()> {
+    write_mesh(writer, vertices, triangles, index_format, Some(faces))
+}
+
+fn write_mesh(
+    mut writer: impl io::Write,
+    vertices: &[Vertex],
+    triangles: &[[Index; 3]],
+    index_format: IndexFormat,
+    faces: Option<&FaceGroups>,
+) -> io::Result<()> {
+    index_format
+        .check(vertices.len())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+
+    let has_normals = has_attribute(vertices, "normals", |v| v.normal)?;
+    let has_uvs = has_attribute(vertices, "texture coordinates", |v| v.uv)?;
+    let has_colors = has_attribute(vertices, "colors", |v| v.color)?;
+
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format ascii 1.0")?;
+    writeln!(writer, "comment Fornjot")?;
+    writeln!(writer, "element vertex {}", vertices.len())?;
+    for axis in ["x", "y", "z"] {
+        writeln!(writer, "property double {}", axis)?;
+    }
+    if has_normals {
+        for axis in ["nx", "ny", "nz"] {
+            writeln!(writer, "property double {}", axis)?;
+        }
+    }
+    if has_uvs {
+        for axis in ["s", "t"] {
+            writeln!(writer, "property double {}", axis)?;
+        }
+    }
+    if has_colors {
+        for channel in ["red", "green", "blue"] {
+            writeln!(writer, "property uchar {}", channel)?;
+        }
+    }
+    writeln!(writer, "element face {}", triangles.len())?;
+    let index_type = match index_format {
+        IndexFormat::U16 => "ushort",
+        IndexFormat::U32 => "uint",
+    };
+    writeln!(writer, "property list uchar {} vertex_indices", index_type)?;
+    if faces.is_some() {
+        writeln!(writer, "property uint face")?;
+    }
+    writeln!(writer, "end_header")?;
+
+    for vertex in vertices {
+        let [x, y, z] =
+            vertex.position.coords.components().map(|c| c.into_f64());
+        write!(writer, "{} {} {}", x, y, z)?;
+
+        if let Some(normal) = vertex.normal {
+            let [x, y, z] = normal.components().map(|c| c.into_f64());
+            write!(writer, " {} {} {}", x, y, z)?;
+        }
+        if let Some(uv) = vertex.uv {
+            let [u, v] = uv.coords.components().map(|c| c.into_f64());
+            write!(writer, " {} {}", u, v)?;
+        }
+        if let Some([r, g, b]) = vertex.color {
+            write!(writer, " {} {} {}", r, g, b)?;
+        }
+
+        writeln!(writer)?;
+    }
+
+    for (i, [a, b, c]) in triangles.iter().enumerate() {
+        write!(writer, "3 {} {} {}", a, b, c)?;
+
+        if let Some(faces) = faces {
+            let face = faces.face_of(i).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Triangle is not part of any face",
+                )
+            })?;
+            write!(writer, " {}", face)?;
+        }
+
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Check whether all vertices or none of them have an attribute
+///
+/// PLY files define the properties of all vertices in their header, so either
+/// is fine. Anything else is an error.
+fn has_attribute<T>(
+    vertices: &[Vertex],
+    name: &str,
+    attribute: impl Fn(&Vertex) -> Option<T>,
+) -> io::Result<bool> {
+    let count = vertices
+        .iter()
+        .filter(|vertex| attribute(vertex).is_some())
+        .count();
+
+    if count != 0 && count != vertices.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Only some vertices have {}", name),
+        ));
+    }
+
+    Ok(count != 0)
+}
+
+/// Write a triangle mesh, colored according to a scalar field
+///
+/// The values of `field` are mapped to colors using `colormap` (see
+/// [`ScalarField::colors`]). PLY only supports colors per vertex, so if the
+/// field has a value per triangle, each triangle gets its own copy of its
+/// vertices.
+pub fn write_field(
+    writer: impl io::Write,
+    vertices: &[Point<3>],
+    triangles: &[[Index; 3]],
+    field: &ScalarField,
+    colormap: Colormap,
+    range: [Scalar; 2],
+) -> io::Result<()> {
+    let colors = field.colors(colormap, range);
+
+    match field.location {
+        FieldLocation::Vertices => {
+            if colors.len() != vertices.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Number of values doesn't match number of vertices",
+                ));
+            }
+
+            let vertices: Vec<_> = vertices
+                .iter()
+                .zip(colors)
+                .map(|(&point, color)| Vertex::new(point).with_color(color))
+                .collect();
+
+            write(writer, &vertices, triangles, IndexFormat::U32)
+        }
+        FieldLocation::Triangles => {
+            if colors.len() != triangles.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Number of values doesn't match number of triangles",
+                ));
+            }
+
+            let vertices: Vec<_> = triangles
+                .iter()
+                .zip(colors)
+                .flat_map(|(triangle, color)| {
+                    triangle.map(|i| {
+                        Vertex::new(vertices[i as usize]).with_color(color)
+                    })
+                })
+                .collect();
+            IndexFormat::U32.check(vertices.len()).map_err(|err| {
+                io::Error::new(io::ErrorKind::InvalidInput, err)
+            })?;
+            let triangles: Vec<_> = (0..triangles.len() as Index)
+                .map(|i| [i * 3, i * 3 + 1, i * 3 + 2])
+                .collect();
+
+            write(writer, &vertices, &triangles, IndexFormat::U32)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analysis::field::{Colormap, ScalarField},
+        math::{Point, Scalar, Vector},
+        mesh::{FaceGroups, IndexFormat, Vertex},
+    };
+
+    #[test]
+    fn write() {
+        let points = [
+            Point::from([0., 0., 0.]),
+            Point::from([1., 0., 0.]),
+            Point::from([0., 0.5, 0.]),
+        ];
+        let colors = [[255, 0, 0], [0, 255, 0], [0, 0, 255]];
+        let vertices: Vec<_> = points
+            .iter()
+            .zip(colors)
+            .map(|(&point, color)| Vertex::new(point).with_color(color))
+            .collect();
+
+        let mut ply = Vec::new();
+        super::write(&mut ply, &vertices, &[[0, 1, 2]], IndexFormat::U32)
+            .unwrap();
+
+        let ply = String::from_utf8(ply).unwrap();
+        let (header, body) = ply.split_once("end_header\n").unwrap();
+
+        assert!(header.contains("element vertex 3\n"));
+        assert!(header.contains("property uchar red\n"));
+        assert!(header.contains("element face 1\n"));
+        assert!(header.contains("property list uchar uint vertex_indices\n"));
+        assert_eq!(
+            body,
+            "0 0 0 255 0 0\n1 0 0 0 255 0\n0 0.5 0 0 0 255\n3 0 1 2\n"
+        );
+
+        // Normals are written after the position.
+        let normal = Vector::from([0., 0., 1.]);
+        let vertices: Vec<_> = points
+            .iter()
+            .map(|&p| Vertex::new(p).with_normal(normal))
+            .collect();
+
+        let mut ply = Vec::new();
+        super::write(&mut ply, &vertices, &[], IndexFormat::U32).unwrap();
+
+        let ply = String::from_utf8(ply).unwrap();
+        let (header, body) = ply.split_once("end_header\n").unwrap();
+        assert!(header.contains("property double nx\n"));
+        assert!(!header.contains("property uchar red\n"));
+        assert_eq!(body.lines().next(), Some("0 0 0 0 0 1"));
+
+        // Attributes must be present for all vertices, or for none.
+        let mut vertices = vertices;
+        vertices[1].normal = None;
+        let result =
+            super::write(&mut Vec::new(), &vertices, &[], IndexFormat::U32);
+        assert!(result.is_err());
+
+        // The index format must be able to refer to all vertices.
+        let vertices = vec![Vertex::new(Point::origin()); 65537];
+        let result =
+            super::write(&mut Vec::new(), &vertices, &[], IndexFormat::U16);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn write_with_faces() {
+        let vertices = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]]
+            .map(|point| Vertex::new(Point::from(point)));
+        let triangles = [[0, 2, 1], [0, 1, 3], [0, 3, 2]];
+
+        let mut faces = FaceGroups::new();
+        faces.push(1);
+        faces.push(2);
+
+        let mut ply = Vec::new();
+        super::write_with_faces(
+            &mut ply,
+            &vertices,
+            &triangles,
+            IndexFormat::U16,
+            &faces,
+        )
+        .unwrap();
+
+        let ply = String::from_utf8(ply).unwrap();
+        let (header, body) = ply.split_once("end_header\n").unwrap();
+        assert!(header.contains("property list uchar ushort vertex_indices\n"));
+        assert!(header.ends_with("property uint face\n"));
+
+        let lines: Vec<_> = body.lines().skip(vertices.len()).collect();
+        assert_eq!(lines, ["3 0 2 1 0", "3 0 1 3 1", "3 0 3 2 1"]);
+    }
+
+    #[test]
+    fn write_field() {
+        let vertices = [
+            Point::from([0., 0., 0.]),
+            Point::from([1., 0., 0.]),
+            Point::from([0., 1., 0.]),
+            Point::from([1., 1., 0.]),
+        ];
+        let triangles = [[0, 1, 2], [2, 1, 3]];
+        let field = ScalarField::per_triangle([0., 1.].map(Scalar::from_f64));
+
+        let mut ply = Vec::new();
+        super::write_field(
+            &mut ply,
+            &vertices,
+            &triangles,
+            &field,
+            Colormap::CoolWarm,
+            field.range().unwrap(),
+        )
+        .unwrap();
+
+        // Each triangle has its own vertices, so it can have its own color.
+        let ply = String::from_utf8(ply).unwrap();
+        let (header, body) = ply.split_once("end_header\n").unwrap();
+        assert!(header.contains("element vertex 6\n"));
+
+        let lines: Vec<_> = body.lines().collect();
+        assert_eq!(lines[3], "0 1 0 180 4 38");
+        assert_eq!(lines[7], "3 3 4 5");
+    }
+}
@@ -0,0 +1,104 @@
+use std::io;
+
+use crate::math::Triangle;
+
+/// Write triangles as a binary STL file
+///
+/// The normal of each facet is computed from its vertices, which are expected
+/// to be in counter-clockwise order, as seen from the outside of the shape.
+pub fn write(
+    mut writer: impl io::Write,
+    triangles: &[Triangle<3>],
+) -> io::Result<()> {
+    // The header is not used for anything, but it must not start with
+    // "solid", as that would make some readers think this is an ASCII file.
+    let mut header = [0; 80];
+    let text = b"Fornjot";
+    header[..text.len()].copy_from_slice(text);
+    writer.write_all(&header)?;
+
+    let num_facets = u32::try_from(triangles.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Too many triangles for STL file",
+        )
+    })?;
+    writer.write_all(&num_facets.to_le_bytes())?;
+
+    for triangle in triangles {
+        let [a, b, c] = triangle.points();
+
+        // Can't be zero, as `Triangle` doesn't allow degenerate triangles.
+        let normal = (b - a).cross(&(c - a)).normalize();
+
+        for vector in [normal, a.coords, b.coords, c.coords] {
+            for component in vector.components() {
+                writer.write_all(&component.into_f32().to_le_bytes())?;
+            }
+        }
+
+        // attribute byte count
+        writer.write_all(&[0, 0])?;
+    }
+
+    Ok(())
+}
+
+/// Write triangles as an ASCII STL file
+///
+/// Like [`write`], but results in a text file, which is larger, but can be
+/// read by people and by tools that don't support binary STL.
+pub fn write_ascii(
+    mut writer: impl io::Write,
+    triangles: &[Triangle<3>],
+) -> io::Result<()> {
+    writeln!(writer, "solid Fornjot")?;
+
+    for triangle in triangles {
+        let [a, b, c] = triangle.points();
+        let normal = (b - a).cross(&(c - a)).normalize();
+
+        let [x, y, z] = normal.components().map(|c| c.into_f64());
+        writeln!(writer, "  facet normal {} {} {}", x, y, z)?;
+        writeln!(writer, "    outer loop")?;
+        for point in [a, b, c] {
+            let [x, y, z] = point.coords.components().map(|c| c.into_f64());
+            writeln!(writer, "      vertex {} {} {}", x, y, z)?;
+        }
+        writeln!(writer, "    endloop")?;
+        writeln!(writer, "  endfacet")?;
+    }
+
+    writeln!(writer, "endsolid Fornjot")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        import,
+        math::{Point, Triangle},
+    };
+
+    #[test]
+    fn write() {
+        let triangle = Triangle::from([
+            Point::from([0., 0., 0.]),
+            Point::from([1., 0., 0.]),
+            Point::from([0., 1., 0.]),
+        ]);
+
+        let mut stl = Vec::new();
+        super::write(&mut stl, &[triangle]).unwrap();
+
+        let facets = import::stl::parse(&stl).unwrap();
+        assert_eq!(facets, vec![triangle.points()]);
+
+        let mut stl = Vec::new();
+        super::write_ascii(&mut stl, &[triangle]).unwrap();
+
+        let facets = import::stl::parse(&stl).unwrap();
+        assert_eq!(facets, vec![triangle.points()]);
+    }
+}
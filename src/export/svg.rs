@@ -0,0 +1,220 @@
+use std::io;
+
+use fj::{Shape2d, Tag};
+
+use crate::{
+    kernel::shapes::ToShape as _,
+    math::{Aabb, Point, Scalar},
+};
+
+/// Write the outlines of a 2-dimensional shape as SVG, for laser cutting
+///
+/// Laser cutter software usually selects the operation for a line based on its
+/// stroke color. The outlines are sorted into one layer per [`Tag`], each of
+/// which has its own color:
+///
+/// - Scored outlines are blue (`#0000ff`).
+/// - Engraved outlines are black (`#000000`).
+/// - Cut outlines are red (`#ff0000`).
+///
+/// The layers are written in that order, as the part might move, once it has
+/// been cut out. Units are millimeters.
+pub fn write(w: impl io::Write, shape: &Shape2d) -> io::Result<()> {
+    let mut outlines = Vec::new();
+    collect_outlines(shape, &mut outlines);
+
+    let aabb = shape.bounding_volume();
+    let [min, max] = [aabb.min, aabb.max]
+        .map(|point| [point.x, point.y].map(Scalar::into_f64));
+
+    write_outlines(w, [min, max], &outlines)
+}
+
+/// Write polygons as SVG, as outlines that are cut
+///
+/// This is meant for outlines that don't come from a 2-dimensional shape, like
+/// the templates that result from flattening the faces of a 3-dimensional
+/// shape. Otherwise, it works just like [`write`].
+pub fn write_polygons(
+    w: impl io::Write,
+    polygons: &[Vec<Point<2>>],
+) -> io::Result<()> {
+    let outlines: Vec<_> = polygons
+        .iter()
+        .map(|polygon| {
+            let points = polygon.iter().map(|&point| point.into()).collect();
+            (Tag::Cut, Outline::Polygon(points))
+        })
+        .collect();
+
+    let aabb = Aabb::<2>::from_points(polygons.iter().flatten().copied());
+    let [min, max] = [aabb.min, aabb.max].map(Into::into);
+
+    write_outlines(w, [min, max], &outlines)
+}
+
+fn write_outlines(
+    mut w: impl io::Write,
+    [min, max]: [[f64; 2]; 2],
+    outlines: &[(Tag, Outline)],
+) -> io::Result<()> {
+    // The y-axis of SVG points down, so all y coordinates are flipped, to keep
+    // the shape from being mirrored.
+    let [min_x, max_y] = [min[0], max[1]];
+    let [width, height] = [max[0] - min[0], max[1] - min[1]];
+
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        w,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" width="{width:.4}mm" height="{height:.4}mm" viewBox="{:.4} {:.4} {width:.4} {height:.4}">"#,
+        min_x,
+        -max_y,
+        width = width,
+        height = height,
+    )?;
+
+    for layer in [Tag::Score, Tag::Engrave, Tag::Cut] {
+        let (name, color) = match layer {
+            Tag::Cut => ("Cut", "#ff0000"),
+            Tag::Engrave => ("Engrave", "#000000"),
+            Tag::Score => ("Score", "#0000ff"),
+        };
+
+        let mut outlines =
+            outlines.iter().filter(|(tag, _)| *tag == layer).peekable();
+        if outlines.peek().is_none() {
+            continue;
+        }
+
+        // A hairline stroke, as laser cutters expect it for vector operations.
+        writeln!(
+            w,
+            r#"  <g id="{}" inkscape:label="{}" inkscape:groupmode="layer" fill="none" stroke="{}" stroke-width="0.025">"#,
+            name.to_lowercase(),
+            name,
+            color,
+        )?;
+
+        for (_, outline) in outlines {
+            match outline {
+                Outline::Circle {
+                    center: [x, y],
+                    radius,
+                } => {
+                    writeln!(
+                        w,
+                        r#"    <circle cx="{:.4}" cy="{:.4}" r="{:.4}"/>"#,
+                        x,
+                        0. - y,
+                        radius
+                    )?;
+                }
+                Outline::Ellipse {
+                    center: [x, y],
+                    radii: [rx, ry],
+                } => {
+                    writeln!(
+                        w,
+                        r#"    <ellipse cx="{:.4}" cy="{:.4}" rx="{:.4}" ry="{:.4}"/>"#,
+                        x,
+                        0. - y,
+                        rx,
+                        ry
+                    )?;
+                }
+                Outline::Polygon(points) => {
+                    let points: Vec<_> = points
+                        .iter()
+                        .map(|[x, y]| format!("{:.4},{:.4}", x, 0. - y))
+                        .collect();
+                    writeln!(
+                        w,
+                        r#"    <polygon points="{}"/>"#,
+                        points.join(" ")
+                    )?;
+                }
+            }
+        }
+
+        writeln!(w, "  </g>")?;
+    }
+
+    writeln!(w, "</svg>")?;
+
+    Ok(())
+}
+
+enum Outline {
+    Circle { center: [f64; 2], radius: f64 },
+    Ellipse { center: [f64; 2], radii: [f64; 2] },
+    Polygon(Vec<[f64; 2]>),
+}
+
+fn collect_outlines(shape: &Shape2d, outlines: &mut Vec<(Tag, Outline)>) {
+    match shape {
+        Shape2d::Circle(circle) => {
+            // Circles can't be tagged, so they're always cut.
+            outlines.push((
+                Tag::Cut,
+                Outline::Circle {
+                    center: circle.center,
+                    radius: circle.radius,
+                },
+            ));
+        }
+        Shape2d::Ellipse(ellipse) => {
+            // Like circles, ellipses can't be tagged.
+            outlines.push((
+                Tag::Cut,
+                Outline::Ellipse {
+                    center: ellipse.center,
+                    radii: ellipse.radii,
+                },
+            ));
+        }
+        Shape2d::Difference(difference) => {
+            collect_outlines(&difference.a, outlines);
+            collect_outlines(&difference.b, outlines);
+        }
+        Shape2d::Sketch(sketch) => {
+            outlines.push((sketch.tag(), Outline::Polygon(sketch.to_points())));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fj::Tag;
+
+    #[test]
+    fn write_should_sort_outlines_into_layers() {
+        let plate = fj::Difference2d {
+            a: fj::Sketch::from_points(vec![
+                [0., 0.],
+                [4., 0.],
+                [4., 2.],
+                [0., 2.],
+            ])
+            .into(),
+            b: fj::Sketch::from_points(vec![[1., 1.], [2., 1.], [1., 1.5]])
+                .with_tag(Tag::Engrave)
+                .into(),
+        };
+
+        let mut svg = Vec::new();
+        super::write(&mut svg, &plate.into()).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+
+        assert!(svg.contains(r#"viewBox="0.0000 -2.0000 4.0000 2.0000""#));
+        assert!(!svg.contains(r#"id="score""#));
+
+        // Engraving comes first, and the y-axis is flipped.
+        let engrave = svg.find(r#"id="engrave""#).unwrap();
+        let cut = svg.find(r#"id="cut""#).unwrap();
+        assert!(engrave < cut);
+        assert!(svg[engrave..cut].contains(
+            r#"<polygon points="1.0000,-1.0000 2.0000,-1.0000 1.0000,-1.5000"/>"#
+        ));
+        assert!(svg[cut..].contains("4.0000,-2.0000"));
+    }
+}
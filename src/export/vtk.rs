@@ -0,0 +1,151 @@
+//! Export of voxels to VTK files
+//!
+//! Voxels are written in the legacy VTK format, as structured points with one
+//! value per voxel, located at the voxel's center. The value is `1` for
+//! occupied voxels, and `0` for all others. This format is simple, and can be
+//! read by ParaView, by simulation tools, and by most libraries that process
+//! volume data.
+
+use std::io;
+
+use crate::{analysis::voxels::Voxels, math::Scalar};
+
+/// Write voxels as a binary VTK file
+pub fn write(writer: impl io::Write, voxels: &Voxels) -> io::Result<()> {
+    write_inner(writer, voxels, false)
+}
+
+/// Write voxels as an ASCII VTK file
+///
+/// Like [`write`], but results in a text file, which is larger, but can be
+/// read by people.
+pub fn write_ascii(writer: impl io::Write, voxels: &Voxels) -> io::Result<()> {
+    write_inner(writer, voxels, true)
+}
+
+fn write_inner(
+    mut writer: impl io::Write,
+    voxels: &Voxels,
+    ascii: bool,
+) -> io::Result<()> {
+    let grid = voxels.grid();
+    let [nx, ny, nz] = grid.dims;
+    let [x, y, z] = grid
+        .center([0, 0, 0])
+        .coords
+        .components()
+        .map(Scalar::into_f64);
+    let size = grid.size.into_f64();
+
+    writeln!(writer, "# vtk DataFile Version 3.0")?;
+    writeln!(writer, "Fornjot voxels")?;
+    writeln!(writer, "{}", if ascii { "ASCII" } else { "BINARY" })?;
+    writeln!(writer, "DATASET STRUCTURED_POINTS")?;
+    writeln!(writer, "DIMENSIONS {} {} {}", nx, ny, nz)?;
+    writeln!(writer, "ORIGIN {} {} {}", x, y, z)?;
+    writeln!(writer, "SPACING {} {} {}", size, size, size)?;
+    writeln!(writer, "POINT_DATA {}", nx * ny * nz)?;
+    writeln!(writer, "SCALARS occupied unsigned_char 1")?;
+    writeln!(writer, "LOOKUP_TABLE default")?;
+
+    // VTK expects the x index to vary fastest, which is the opposite of how
+    // the occupancy is laid out.
+    let occupancy = voxels.occupancy();
+    let mut row = Vec::with_capacity(nx);
+    for z in 0..nz {
+        for y in 0..ny {
+            row.clear();
+            row.extend((0..nx).map(|x| occupancy[(x * ny + y) * nz + z] as u8));
+
+            if ascii {
+                let values: Vec<_> =
+                    row.iter().map(|value| value.to_string()).collect();
+                writeln!(writer, "{}", values.join(" "))?;
+            } else {
+                writer.write_all(&row)?;
+            }
+        }
+    }
+
+    if !ascii {
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analysis::voxels::{self, Grid},
+        export::{self, Error},
+        math::{Aabb, Point, Scalar, Triangle},
+        testing::cuboid,
+    };
+
+    #[test]
+    fn write() {
+        // A triangulated box, covering 2x1x1 voxels of a 3x1x1 grid.
+        let [a, b, c, d, e, f, g, h] = [
+            [0., 0., 0.],
+            [2., 0., 0.],
+            [2., 1., 0.],
+            [0., 1., 0.],
+            [0., 0., 1.],
+            [2., 0., 1.],
+            [2., 1., 1.],
+            [0., 1., 1.],
+        ]
+        .map(Point::from);
+        let triangles = [
+            [a, c, b],
+            [a, d, c],
+            [e, f, g],
+            [e, g, h],
+            [a, b, f],
+            [a, f, e],
+            [b, c, g],
+            [b, g, f],
+            [c, d, h],
+            [c, h, g],
+            [d, a, e],
+            [d, e, h],
+        ]
+        .map(Triangle::from);
+
+        let aabb = Aabb::<3>::from_points([a, Point::from([3., 1., 1.])]);
+        let voxels =
+            Grid::new(&aabb, Scalar::ONE).unwrap().voxelize(&triangles);
+
+        let mut vtk = Vec::new();
+        super::write_ascii(&mut vtk, &voxels).unwrap();
+        let vtk = String::from_utf8(vtk).unwrap();
+
+        let lines: Vec<_> = vtk.lines().collect();
+        assert_eq!(lines[2], "ASCII");
+        assert_eq!(lines[4], "DIMENSIONS 3 1 1");
+        assert_eq!(lines[5], "ORIGIN 0.5 0.5 0.5");
+        assert_eq!(lines[6], "SPACING 1 1 1");
+        assert_eq!(lines[10..], ["1 1 0"]);
+
+        let mut vtk = Vec::new();
+        super::write(&mut vtk, &voxels).unwrap();
+        assert!(vtk.ends_with(b"LOOKUP_TABLE default\n\x01\x01\x00\n"));
+    }
+
+    #[test]
+    fn voxels_should_reject_invalid_sizes() {
+        let cube: fj::Shape = cuboid([1., 1., 1.]).into();
+        let grid = |size| export::voxels(&cube, Some(Scalar::from_f64(size)));
+
+        assert_eq!(grid(0.5).unwrap().count(), 8);
+        assert!(matches!(
+            grid(0.),
+            Err(Error::Voxels(voxels::Error::InvalidSize(_)))
+        ));
+        assert!(matches!(
+            grid(1e-3),
+            Err(Error::Voxels(voxels::Error::TooManyVoxels(_)))
+        ));
+    }
+}
@@ -8,7 +8,7 @@ use wgpu_glyph::{
 
 use crate::math::Aabb;
 
-use super::draw_config::DrawConfig;
+use super::{draw_config::DrawConfig, theme::Theme};
 
 #[derive(Debug)]
 pub struct ConfigUi {
@@ -44,6 +44,7 @@ impl ConfigUi {
         Ok(Self { glyph_brush, texts })
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn draw(
         &mut self,
         device: &wgpu::Device,
@@ -52,17 +53,20 @@ impl ConfigUi {
         surface_config: &wgpu::SurfaceConfiguration,
         aabb: &Aabb<3>,
         draw_config: &DrawConfig,
+        theme: &Theme,
     ) -> Result<(), String> {
+        let [r, g, b, a] = theme.text.0;
+
         let mut section = Section::new().with_screen_position((50.0, 50.0));
 
         for element in Element::elements() {
             let enabled = element.is_enabled(draw_config);
             let text = &self.texts[&(element, enabled)];
 
-            let alpha = if enabled { 1.0 } else { 0.75 };
+            let alpha = if enabled { a } else { a * 0.75 };
 
             let text = Text::new(text)
-                .with_color([0.0, 0.0, 0.0, alpha])
+                .with_color([r, g, b, alpha])
                 .with_scale(50.0);
 
             section = section.add_text(text);
@@ -76,9 +80,7 @@ impl ConfigUi {
             bbsize[1].into_f32(),
             bbsize[2].into_f32()
         );
-        let text = Text::new(&info)
-            .with_color([0.0, 0.0, 0.0, 1.0])
-            .with_scale(50.0);
+        let text = Text::new(&info).with_color(theme.text.0).with_scale(50.0);
         section = section.add_text(text);
 
         self.glyph_brush.queue(section);
@@ -105,11 +107,25 @@ enum Element {
     Model,
     Mesh,
     Debug,
+    Toolpath,
+    Validation,
+    Edges,
+    XRay,
+    Overhangs,
 }
 
 impl Element {
-    fn elements() -> [Self; 3] {
-        [Self::Model, Self::Mesh, Self::Debug]
+    fn elements() -> [Self; 8] {
+        [
+            Self::Model,
+            Self::Mesh,
+            Self::Debug,
+            Self::Toolpath,
+            Self::Validation,
+            Self::Edges,
+            Self::XRay,
+            Self::Overhangs,
+        ]
     }
 
     fn name_key(&self) -> (&'static str, &'static str) {
@@ -117,6 +133,11 @@ impl Element {
             Self::Model => ("model", "1"),
             Self::Mesh => ("mesh", "2"),
             Self::Debug => ("debug info", "3"),
+            Self::Toolpath => ("toolpath", "4"),
+            Self::Validation => ("validation error", "5"),
+            Self::Edges => ("edge", "6"),
+            Self::XRay => ("x-ray", "7"),
+            Self::Overhangs => ("overhang", "8"),
         }
     }
 
@@ -125,6 +146,11 @@ impl Element {
             Self::Model => config.draw_model,
             Self::Mesh => config.draw_mesh,
             Self::Debug => config.draw_debug,
+            Self::Toolpath => config.draw_toolpath,
+            Self::Validation => config.draw_validation,
+            Self::Edges => config.draw_edges,
+            Self::XRay => config.draw_xray,
+            Self::Overhangs => config.draw_overhangs,
         }
     }
 }
@@ -3,6 +3,11 @@ pub struct DrawConfig {
     pub draw_model: bool,
     pub draw_mesh: bool,
     pub draw_debug: bool,
+    pub draw_toolpath: bool,
+    pub draw_validation: bool,
+    pub draw_edges: bool,
+    pub draw_xray: bool,
+    pub draw_overhangs: bool,
 }
 
 impl Default for DrawConfig {
@@ -11,6 +16,11 @@ impl Default for DrawConfig {
             draw_model: true,
             draw_mesh: false,
             draw_debug: false,
+            draw_toolpath: true,
+            draw_validation: true,
+            draw_edges: true,
+            draw_xray: false,
+            draw_overhangs: false,
         }
     }
 }
@@ -5,17 +5,49 @@ use super::{
 
 pub struct Drawables<'r> {
     pub model: Drawable<'r>,
-    pub mesh: Drawable<'r>,
+    pub mesh: Option<Drawable<'r>>,
+    pub edges: Drawable<'r>,
+    pub silhouette: Drawable<'r>,
+    pub xray: Drawable<'r>,
+    pub overlay: Drawable<'r>,
+    pub overhangs: Drawable<'r>,
     pub lines: Drawable<'r>,
+    pub validation: Drawable<'r>,
+    pub toolpath: Drawable<'r>,
 }
 
 impl<'r> Drawables<'r> {
     pub fn new(geometries: &'r Geometries, pipelines: &'r Pipelines) -> Self {
         let model = Drawable::new(&geometries.mesh, &pipelines.model);
-        let mesh = Drawable::new(&geometries.mesh, &pipelines.mesh);
+        let mesh = pipelines
+            .mesh
+            .as_ref()
+            .map(|pipeline| Drawable::new(&geometries.mesh, pipeline));
+        let edges = Drawable::new(&geometries.edges, &pipelines.lines);
+        let silhouette =
+            Drawable::new(&geometries.silhouette, &pipelines.lines);
+        let xray = Drawable::new(&geometries.xray, &pipelines.translucent);
+        let overlay =
+            Drawable::new(&geometries.overlay, &pipelines.translucent);
+        let overhangs =
+            Drawable::new(&geometries.overhangs, &pipelines.translucent);
         let lines = Drawable::new(&geometries.lines, &pipelines.lines);
+        let validation =
+            Drawable::new(&geometries.validation, &pipelines.lines);
+        let toolpath = Drawable::new(&geometries.toolpath, &pipelines.lines);
 
-        Self { model, mesh, lines }
+        Self {
+            model,
+            mesh,
+            edges,
+            silhouette,
+            xray,
+            overlay,
+            overhangs,
+            lines,
+            validation,
+            toolpath,
+        }
     }
 }
 
@@ -64,7 +96,7 @@ impl<'r> Drawable<'r> {
         render_pass.set_vertex_buffer(0, self.geometry.vertex_buffer.slice(..));
         render_pass.set_index_buffer(
             self.geometry.index_buffer.slice(..),
-            wgpu::IndexFormat::Uint32,
+            self.geometry.index_format,
         );
 
         render_pass.draw_indexed(0..self.geometry.num_indices, 0, 0..1);
@@ -1,29 +1,88 @@
-use crate::math::Aabb;
+use crate::{
+    math::{Aabb, Point},
+    mesh::IndexFormat,
+};
 use std::convert::TryInto;
 
 use wgpu::util::DeviceExt;
 
-use super::vertices::{Vertex, Vertices};
+use super::{theme::Theme, vertices::Vertices};
 
 #[derive(Debug)]
 pub struct Geometries {
     pub mesh: Geometry,
+    pub edges: Geometry,
+
+    /// The silhouette of the mesh, which depends on the camera position
+    ///
+    /// This is empty initially, and is updated by the renderer, when drawing.
+    pub silhouette: Geometry,
+
+    /// The translucent X-ray view of the mesh
+    ///
+    /// Its triangles are sorted by their distance to the camera, so like the
+    /// silhouette, this is empty initially, and updated when drawing.
+    pub xray: Geometry,
+
+    /// Geometry that is drawn on top of the model for a limited time
+    ///
+    /// This is empty initially, and set by the renderer.
+    pub overlay: Geometry,
+
+    /// The surfaces of the model that need support, when printing it
+    ///
+    /// Like the overlay, this is empty initially, and set by the renderer.
+    pub overhangs: Geometry,
+
     pub lines: Geometry,
+    pub validation: Geometry,
+    pub toolpath: Geometry,
     pub aabb: Aabb<3>,
+
+    /// The origin that the vertices are relative to
+    ///
+    /// See [`Vertices::vertices`].
+    pub origin: Point<3>,
 }
 
 impl Geometries {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         device: &wgpu::Device,
         mesh: &Vertices,
+        edges: &Vertices,
         debug_info: &Vertices,
+        validation: &Vertices,
+        toolpath: &Vertices,
         aabb: Aabb<3>,
+        origin: Point<3>,
+        theme: &Theme,
     ) -> Self {
-        let mesh = Geometry::new(device, mesh.vertices(), mesh.indices());
-        let lines =
-            Geometry::new(device, debug_info.vertices(), debug_info.indices());
+        let mesh = Geometry::new(device, mesh, origin, theme);
+        let edges = Geometry::new(device, edges, origin, theme);
+        let silhouette =
+            Geometry::new(device, &Vertices::empty(), origin, theme);
+        let xray = Geometry::new(device, &Vertices::empty(), origin, theme);
+        let overlay = Geometry::new(device, &Vertices::empty(), origin, theme);
+        let overhangs =
+            Geometry::new(device, &Vertices::empty(), origin, theme);
+        let lines = Geometry::new(device, debug_info, origin, theme);
+        let validation = Geometry::new(device, validation, origin, theme);
+        let toolpath = Geometry::new(device, toolpath, origin, theme);
 
-        Self { mesh, lines, aabb }
+        Self {
+            mesh,
+            edges,
+            silhouette,
+            xray,
+            overlay,
+            overhangs,
+            lines,
+            validation,
+            toolpath,
+            aabb,
+            origin,
+        }
     }
 }
 
@@ -31,30 +90,57 @@ impl Geometries {
 pub struct Geometry {
     pub vertex_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
+    pub index_format: wgpu::IndexFormat,
     pub num_indices: u32,
 }
 
 impl Geometry {
-    fn new(
+    pub fn new(
         device: &wgpu::Device,
-        vertices: &[Vertex],
-        indices: &[u32],
+        vertices: &Vertices,
+        origin: Point<3>,
+        theme: &Theme,
     ) -> Self {
+        let indices = vertices.indices();
+        let vertices = vertices.vertices(&origin.to_na(), theme);
+
+        // Use 16-bit indices where possible, to halve the size of the index
+        // buffer.
+        //
+        // Can't panic. `Vertices` checks the number of vertices, when they
+        // are added.
+        let index_format = IndexFormat::for_vertices(vertices.len()).unwrap();
+        let (index_format, index_data) = match index_format {
+            IndexFormat::U16 => {
+                let indices: Vec<_> =
+                    indices.iter().map(|&index| index as u16).collect();
+                (
+                    wgpu::IndexFormat::Uint16,
+                    bytemuck::cast_slice(&indices).to_vec(),
+                )
+            }
+            IndexFormat::U32 => (
+                wgpu::IndexFormat::Uint32,
+                bytemuck::cast_slice(indices).to_vec(),
+            ),
+        };
+
         Self {
             vertex_buffer: device.create_buffer_init(
                 &wgpu::util::BufferInitDescriptor {
                     label: None,
-                    contents: bytemuck::cast_slice(vertices),
+                    contents: bytemuck::cast_slice(&vertices),
                     usage: wgpu::BufferUsages::VERTEX,
                 },
             ),
             index_buffer: device.create_buffer_init(
                 &wgpu::util::BufferInitDescriptor {
                     label: None,
-                    contents: bytemuck::cast_slice(indices),
+                    contents: &index_data,
                     usage: wgpu::BufferUsages::INDEX,
                 },
             ),
+            index_format,
             num_indices: indices
                 .len()
                 .try_into()
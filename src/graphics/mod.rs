@@ -5,6 +5,7 @@ mod geometries;
 mod pipelines;
 mod renderer;
 mod shaders;
+mod theme;
 mod transform;
 mod uniforms;
 mod vertices;
@@ -12,6 +13,8 @@ mod vertices;
 pub use self::{
     draw_config::DrawConfig,
     renderer::{DrawError, Renderer},
+    theme::{Color, ColorError, Colors, Role, Theme, ThemeConfig, Variant},
+    vertices::Vertices,
 };
 
 const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
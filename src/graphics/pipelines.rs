@@ -9,8 +9,14 @@ use super::{
 #[derive(Debug)]
 pub struct Pipelines {
     pub model: Pipeline,
-    pub mesh: Pipeline,
+
+    /// The pipeline that draws the mesh as a wireframe
+    ///
+    /// This is `None`, if the device doesn't support drawing polygons as lines.
+    pub mesh: Option<Pipeline>,
+
     pub lines: Pipeline,
+    pub translucent: Pipeline,
 }
 
 impl Pipelines {
@@ -18,6 +24,7 @@ impl Pipelines {
         device: &wgpu::Device,
         bind_group_layout: &wgpu::BindGroupLayout,
         color_format: wgpu::TextureFormat,
+        features: wgpu::Features,
     ) -> Self {
         let pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -36,22 +43,53 @@ impl Pipelines {
                 wgpu::PrimitiveTopology::TriangleList,
                 wgpu::PolygonMode::Fill,
                 color_format,
+                // Push the model back a bit, so the edges that are drawn on
+                // top of it aren't hidden by it.
+                wgpu::DepthBiasState {
+                    constant: 2,
+                    slope_scale: 1.0,
+                    clamp: 0.0,
+                },
+                true,
             ),
-            mesh: Pipeline::new(
-                device,
-                &pipeline_layout,
-                shaders.mesh(),
-                wgpu::PrimitiveTopology::TriangleList,
-                wgpu::PolygonMode::Line,
-                color_format,
+            mesh: features.contains(wgpu::Features::POLYGON_MODE_LINE).then(
+                || {
+                    Pipeline::new(
+                        device,
+                        &pipeline_layout,
+                        shaders.mesh(),
+                        wgpu::PrimitiveTopology::TriangleList,
+                        wgpu::PolygonMode::Line,
+                        color_format,
+                        wgpu::DepthBiasState::default(),
+                        true,
+                    )
+                },
             ),
+            // The polygon mode only applies to triangles, so this doesn't need
+            // `wgpu::Features::POLYGON_MODE_LINE`.
             lines: Pipeline::new(
                 device,
                 &pipeline_layout,
                 shaders.lines(),
                 wgpu::PrimitiveTopology::LineList,
-                wgpu::PolygonMode::Line,
+                wgpu::PolygonMode::Fill,
+                color_format,
+                wgpu::DepthBiasState::default(),
+                true,
+            ),
+            // Translucent triangles don't hide what's behind them, so they
+            // must not write to the depth buffer. They are drawn from back to
+            // front instead.
+            translucent: Pipeline::new(
+                device,
+                &pipeline_layout,
+                shaders.model(),
+                wgpu::PrimitiveTopology::TriangleList,
+                wgpu::PolygonMode::Fill,
                 color_format,
+                wgpu::DepthBiasState::default(),
+                false,
             ),
         }
     }
@@ -61,6 +99,7 @@ impl Pipelines {
 pub struct Pipeline(pub wgpu::RenderPipeline);
 
 impl Pipeline {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         device: &wgpu::Device,
         pipeline_layout: &wgpu::PipelineLayout,
@@ -68,6 +107,8 @@ impl Pipeline {
         topology: wgpu::PrimitiveTopology,
         polygon_mode: wgpu::PolygonMode,
         color_format: wgpu::TextureFormat,
+        bias: wgpu::DepthBiasState,
+        depth_write_enabled: bool,
     ) -> Self {
         let pipeline =
             device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
@@ -97,7 +138,7 @@ impl Pipeline {
                 },
                 depth_stencil: Some(wgpu::DepthStencilState {
                     format: DEPTH_FORMAT,
-                    depth_write_enabled: true,
+                    depth_write_enabled,
                     depth_compare: wgpu::CompareFunction::LessEqual,
                     stencil: wgpu::StencilState {
                         front: wgpu::StencilFaceState::IGNORE,
@@ -105,7 +146,7 @@ impl Pipeline {
                         read_mask: 0,
                         write_mask: 0,
                     },
-                    bias: wgpu::DepthBiasState::default(),
+                    bias,
                 }),
                 multisample: wgpu::MultisampleState {
                     count: 1,
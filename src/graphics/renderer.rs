@@ -6,12 +6,26 @@ use wgpu::util::DeviceExt as _;
 use wgpu_glyph::ab_glyph::InvalidFont;
 use winit::dpi::PhysicalSize;
 
-use crate::{camera::Camera, math::Aabb, math::Point, window::Window};
+use crate::{
+    camera::Camera,
+    math::Aabb,
+    math::Point,
+    mesh::{IndexOverflow, Silhouette},
+    platform::Instant,
+    window::Window,
+};
 
 use super::{
-    config_ui::ConfigUi, draw_config::DrawConfig, drawables::Drawables,
-    geometries::Geometries, pipelines::Pipelines, transform::Transform,
-    uniforms::Uniforms, vertices::Vertices, DEPTH_FORMAT,
+    config_ui::ConfigUi,
+    draw_config::DrawConfig,
+    drawables::Drawables,
+    geometries::{Geometries, Geometry},
+    pipelines::Pipelines,
+    theme::Theme,
+    transform::Transform,
+    uniforms::Uniforms,
+    vertices::Vertices,
+    DEPTH_FORMAT,
 };
 
 #[derive(Debug)]
@@ -29,11 +43,44 @@ pub struct Renderer {
     geometries: Geometries,
     pipelines: Pipelines,
 
+    /// The vertices, which are kept, to upload them again with a new origin
+    ///
+    /// The toolpath is also kept, when the rest of the geometry is updated.
+    mesh: Vertices,
+    edges: Vertices,
+    lines: Vertices,
+    validation: Vertices,
+    toolpath: Vertices,
+
+    /// The edges of the mesh that can be part of its silhouette
+    silhouette: Silhouette,
+
+    /// The camera position that the silhouette was last uploaded for
+    ///
+    /// This is `None`, if it needs to be uploaded again.
+    silhouette_eye: Option<Point<3>>,
+
+    /// The camera position that the X-ray view was last sorted for
+    ///
+    /// This is `None`, if it needs to be uploaded again.
+    xray_eye: Option<Point<3>>,
+
+    /// Geometry that is drawn on top of the model, and when to stop drawing it
+    ///
+    /// The vertices are kept, like those above, and dropped once the overlay
+    /// has expired.
+    overlay: Vertices,
+    overlay_until: Option<Instant>,
+
+    /// The surfaces of the model that need support, when printing it
+    overhangs: Vertices,
+
+    theme: Theme,
     config_ui: ConfigUi,
 }
 
 impl Renderer {
-    pub async fn new(window: &Window) -> Result<Self, InitError> {
+    pub async fn new(window: &Window, theme: Theme) -> Result<Self, InitError> {
         let instance = wgpu::Instance::new(wgpu::Backends::PRIMARY);
 
         // This is sound, as `window` is an object to create a surface upon.
@@ -48,18 +95,26 @@ impl Renderer {
             .await
             .ok_or(InitError::RequestAdapter)?;
 
+        // Drawing the mesh as a wireframe requires this feature. It isn't
+        // available everywhere (notably not with WebGL), so only request it, if
+        // the adapter supports it.
+        //
+        // See this issue:
+        // https://github.com/hannobraun/fornjot/issues/33
+        let features = adapter.features() & wgpu::Features::POLYGON_MODE_LINE;
+
+        // WebGL can't meet the default limits.
+        #[cfg(not(target_arch = "wasm32"))]
+        let limits = wgpu::Limits::default();
+        #[cfg(target_arch = "wasm32")]
+        let limits = wgpu::Limits::downlevel_webgl2_defaults();
+
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: None,
-                    // Don't just blindly assume that we can request this
-                    // feature. If it isn't available, that might cause a panic,
-                    // or an error to be returned here.
-                    //
-                    // See this issue:
-                    // https://github.com/hannobraun/fornjot/issues/33
-                    features: wgpu::Features::POLYGON_MODE_LINE,
-                    limits: wgpu::Limits::default(),
+                    features,
+                    limits,
                 },
                 None,
             )
@@ -122,13 +177,18 @@ impl Renderer {
             &device,
             &Vertices::empty(),
             &Vertices::empty(),
+            &Vertices::empty(),
+            &Vertices::empty(),
+            &Vertices::empty(),
             Aabb {
                 min: Point::from([0.0, 0.0, 0.0]),
                 max: Point::from([0.0, 0.0, 0.0]),
             },
+            Point::origin(),
+            &theme,
         );
         let pipelines =
-            Pipelines::new(&device, &bind_group_layout, color_format);
+            Pipelines::new(&device, &bind_group_layout, color_format, features);
 
         let config_ui = ConfigUi::new(&device, color_format)?;
 
@@ -146,6 +206,22 @@ impl Renderer {
             geometries,
             pipelines,
 
+            mesh: Vertices::empty(),
+            edges: Vertices::empty(),
+            lines: Vertices::empty(),
+            validation: Vertices::empty(),
+            toolpath: Vertices::empty(),
+
+            silhouette: Silhouette::default(),
+            silhouette_eye: None,
+            xray_eye: None,
+
+            overlay: Vertices::empty(),
+            overlay_until: None,
+
+            overhangs: Vertices::empty(),
+
+            theme,
             config_ui,
         })
     }
@@ -153,10 +229,90 @@ impl Renderer {
     pub fn update_geometry(
         &mut self,
         mesh: Vertices,
+        edges: Vertices,
+        silhouette: Silhouette,
         lines: Vertices,
+        validation: Vertices,
         aabb: Aabb<3>,
     ) {
-        self.geometries = Geometries::new(&self.device, &mesh, &lines, aabb);
+        self.mesh = mesh;
+        self.edges = edges;
+        self.silhouette = silhouette;
+        self.lines = lines;
+        self.validation = validation;
+
+        // The center of the model is a good origin for most views. It's moved
+        // closer to the camera when drawing, if necessary.
+        self.upload_geometry(aabb, aabb.center());
+    }
+
+    pub fn update_toolpath(&mut self, toolpath: Vertices) {
+        self.geometries.toolpath = Geometry::new(
+            &self.device,
+            &toolpath,
+            self.geometries.origin,
+            &self.theme,
+        );
+        self.toolpath = toolpath;
+    }
+
+    /// Draw geometry on top of the model, until `until` has passed
+    ///
+    /// This is used to briefly highlight what changed, when a model is
+    /// reloaded. An overlay replaces the one that's currently shown, if any.
+    pub fn show_overlay(&mut self, overlay: Vertices, until: Instant) {
+        self.geometries.overlay = Geometry::new(
+            &self.device,
+            &overlay,
+            self.geometries.origin,
+            &self.theme,
+        );
+        self.overlay = overlay;
+        self.overlay_until = Some(until);
+    }
+
+    /// Update the surfaces of the model that need support
+    ///
+    /// These are drawn on top of the model, if enabled in the draw
+    /// configuration.
+    pub fn update_overhangs(&mut self, overhangs: Vertices) {
+        self.geometries.overhangs = Geometry::new(
+            &self.device,
+            &overhangs,
+            self.geometries.origin,
+            &self.theme,
+        );
+        self.overhangs = overhangs;
+    }
+
+    /// Switch to another theme
+    ///
+    /// The colors of the vertices are assigned when uploading them, so they
+    /// are uploaded again.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.upload_geometry(self.geometries.aabb, self.geometries.origin);
+    }
+
+    fn upload_geometry(&mut self, aabb: Aabb<3>, origin: Point<3>) {
+        self.geometries = Geometries::new(
+            &self.device,
+            &self.mesh,
+            &self.edges,
+            &self.lines,
+            &self.validation,
+            &self.toolpath,
+            aabb,
+            origin,
+            &self.theme,
+        );
+        self.geometries.overlay =
+            Geometry::new(&self.device, &self.overlay, origin, &self.theme);
+        self.geometries.overhangs =
+            Geometry::new(&self.device, &self.overhangs, origin, &self.theme);
+
+        self.silhouette_eye = None;
+        self.xray_eye = None;
     }
 
     pub fn handle_resize(&mut self, size: PhysicalSize<u32>) {
@@ -175,10 +331,73 @@ impl Renderer {
         camera: &Camera,
         config: &DrawConfig,
     ) -> Result<(), DrawError> {
+        // Vertices are converted to single precision relative to an origin,
+        // which only works well, while the camera is close to it, compared to
+        // the distance of what it looks at. Otherwise, move the origin to the
+        // camera.
+        let camera_position = camera.position();
+        let max_distance = camera.near_plane() * MAX_DISTANCE_TO_ORIGIN;
+        if (camera_position - self.geometries.origin)
+            .magnitude()
+            .into_f64()
+            > max_distance
+        {
+            self.upload_geometry(self.geometries.aabb, camera_position);
+        }
+
+        // Which edges are part of the silhouette depends on where they're
+        // seen from.
+        if config.draw_edges && self.silhouette_eye != Some(camera_position) {
+            // If there are too many edges, the error is only returned once
+            // per camera position, not every frame.
+            self.silhouette_eye = Some(camera_position);
+
+            let silhouette =
+                Vertices::edges(self.silhouette.edges(camera_position))?;
+            self.geometries.silhouette = Geometry::new(
+                &self.device,
+                &silhouette,
+                self.geometries.origin,
+                &self.theme,
+            );
+        }
+
+        // The overlay is only shown for a limited time.
+        if let Some(until) = self.overlay_until {
+            if Instant::now() >= until {
+                self.overlay = Vertices::empty();
+                self.overlay_until = None;
+                self.geometries.overlay = Geometry::new(
+                    &self.device,
+                    &self.overlay,
+                    self.geometries.origin,
+                    &self.theme,
+                );
+            }
+        }
+
+        // Translucent triangles need to be drawn from back to front, which
+        // also depends on where they're seen from.
+        let draw_xray = config.draw_model && config.draw_xray;
+        if draw_xray && self.xray_eye != Some(camera_position) {
+            let xray = self.mesh.xray(camera_position);
+            self.geometries.xray = Geometry::new(
+                &self.device,
+                &xray,
+                self.geometries.origin,
+                &self.theme,
+            );
+            self.xray_eye = Some(camera_position);
+        }
+
         let aspect_ratio = self.surface_config.width as f64
             / self.surface_config.height as f64;
         let uniforms = Uniforms {
-            transform: Transform::for_vertices(camera, aspect_ratio),
+            transform: Transform::for_vertices(
+                camera,
+                aspect_ratio,
+                &self.geometries.origin,
+            ),
             transform_normals: Transform::for_normals(camera),
         };
 
@@ -201,7 +420,7 @@ impl Renderer {
 
         let drawables = Drawables::new(&self.geometries, &self.pipelines);
 
-        if config.draw_model {
+        if config.draw_model && !config.draw_xray {
             drawables.model.draw(
                 &mut encoder,
                 &color_view,
@@ -209,8 +428,22 @@ impl Renderer {
                 &self.bind_group,
             );
         }
-        if config.draw_mesh {
-            drawables.mesh.draw(
+        if config.draw_edges {
+            drawables.edges.draw(
+                &mut encoder,
+                &color_view,
+                &self.depth_view,
+                &self.bind_group,
+            );
+            drawables.silhouette.draw(
+                &mut encoder,
+                &color_view,
+                &self.depth_view,
+                &self.bind_group,
+            );
+        }
+        if let (true, Some(mesh)) = (config.draw_mesh, &drawables.mesh) {
+            mesh.draw(
                 &mut encoder,
                 &color_view,
                 &self.depth_view,
@@ -225,6 +458,49 @@ impl Renderer {
                 &self.bind_group,
             );
         }
+        if config.draw_validation {
+            drawables.validation.draw(
+                &mut encoder,
+                &color_view,
+                &self.depth_view,
+                &self.bind_group,
+            );
+        }
+        if config.draw_toolpath {
+            drawables.toolpath.draw(
+                &mut encoder,
+                &color_view,
+                &self.depth_view,
+                &self.bind_group,
+            );
+        }
+
+        // Translucent geometry comes last, so everything behind it has been
+        // drawn already, when it's blended.
+        if draw_xray {
+            drawables.xray.draw(
+                &mut encoder,
+                &color_view,
+                &self.depth_view,
+                &self.bind_group,
+            );
+        }
+        if config.draw_model && config.draw_overhangs {
+            drawables.overhangs.draw(
+                &mut encoder,
+                &color_view,
+                &self.depth_view,
+                &self.bind_group,
+            );
+        }
+        if self.overlay_until.is_some() {
+            drawables.overlay.draw(
+                &mut encoder,
+                &color_view,
+                &self.depth_view,
+                &self.bind_group,
+            );
+        }
 
         self.config_ui
             .draw(
@@ -234,6 +510,7 @@ impl Renderer {
                 &self.surface_config,
                 &self.geometries.aabb,
                 config,
+                &self.theme,
             )
             .map_err(DrawError::Text)?;
 
@@ -279,7 +556,7 @@ impl Renderer {
                 view,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                    load: wgpu::LoadOp::Clear(self.theme.background.to_wgpu()),
                     store: true,
                 },
             }],
@@ -297,6 +574,19 @@ impl Renderer {
     }
 }
 
+/// The distance the camera may have from the origin of the vertices
+///
+/// This is relative to the distance of the near plane, which approximates the
+/// distance of the closest geometry. Single precision has about 7 significant
+/// digits, so at a factor of 1000, rounding errors still stay well below the
+/// size of a pixel.
+///
+/// Tuning notes:
+/// - If this value is too low, the geometry is uploaded to the GPU again all
+///   the time, while moving the camera.
+/// - If this value is too high, geometry that's far from the origin jitters.
+const MAX_DISTANCE_TO_ORIGIN: f64 = 1000.;
+
 #[derive(Error, Debug)]
 pub enum InitError {
     #[error("I/O error")]
@@ -319,4 +609,7 @@ pub enum DrawError {
 
     #[error("Error drawing text")]
     Text(String),
+
+    #[error("Too many vertices to display")]
+    TooManyVertices(#[from] IndexOverflow),
 }
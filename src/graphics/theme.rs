@@ -0,0 +1,284 @@
+use std::convert::TryFrom;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+/// The colors that the viewer draws with
+///
+/// All colors that are displayed are defined here, so they can be configured
+/// in one place. Which of the colors a vertex is drawn with is decided by its
+/// [`Role`], when it is uploaded to the GPU, so switching between themes
+/// doesn't require the geometry to be created again.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    /// The color of the background
+    pub background: Color,
+
+    /// The color of the text that shows the rendering configuration
+    pub text: Color,
+
+    /// The color of the model
+    pub model: Color,
+
+    /// The color of the edges and the silhouette of the model
+    pub edges: Color,
+
+    /// The color of the model in the X-ray view
+    ///
+    /// This should be translucent, so the inside of the model can be seen.
+    pub xray: Color,
+
+    /// The color of surfaces that were added, when the model was reloaded
+    pub added: Color,
+
+    /// The color of surfaces that were removed, when the model was reloaded
+    pub removed: Color,
+
+    /// The color of surfaces that need support, when printing the model
+    pub overhang: Color,
+
+    /// The color of the markers for validation errors
+    pub validation: Color,
+
+    /// The color of debug rays that start outside of the model
+    pub debug_outside: Color,
+
+    /// The color of debug rays that start inside of the model
+    pub debug_inside: Color,
+
+    /// The color of the markers where debug rays hit the model
+    pub debug_hit: Color,
+
+    /// The color of rapid moves of the toolpath
+    pub toolpath_rapid: Color,
+
+    /// The color of cutting moves of the toolpath
+    pub toolpath_cut: Color,
+}
+
+impl Theme {
+    /// The light theme, which is the default
+    pub fn light() -> Self {
+        Self {
+            background: Color([1., 1., 1., 1.]),
+            text: Color([0., 0., 0., 1.]),
+            model: Color([1., 0., 0., 1.]),
+            edges: Color([0., 0., 0., 1.]),
+            xray: Color([1., 0., 0., 0.25]),
+            added: Color([0., 0.8, 0., 0.5]),
+            removed: Color([0., 0.4, 1., 0.5]),
+            overhang: Color([1., 0., 0., 0.6]),
+            validation: Color([1., 0., 1., 1.]),
+            debug_outside: Color([1., 0., 0., 1.]),
+            debug_inside: Color([0., 1., 0., 1.]),
+            debug_hit: Color([0., 0., 0., 1.]),
+            toolpath_rapid: Color([1., 0.5, 0., 1.]),
+            toolpath_cut: Color([0., 0.3, 1., 1.]),
+        }
+    }
+
+    /// The dark theme
+    pub fn dark() -> Self {
+        Self {
+            background: Color([0.02, 0.02, 0.02, 1.]),
+            text: Color([1., 1., 1., 1.]),
+            model: Color([0.8, 0.05, 0.05, 1.]),
+            edges: Color([0.8, 0.8, 0.8, 1.]),
+            xray: Color([0.8, 0.05, 0.05, 0.3]),
+            added: Color([0.1, 0.9, 0.1, 0.5]),
+            removed: Color([0.1, 0.5, 1., 0.5]),
+            overhang: Color([1., 0.1, 0.1, 0.6]),
+            validation: Color([1., 0., 1., 1.]),
+            debug_outside: Color([1., 0.1, 0.1, 1.]),
+            debug_inside: Color([0.1, 1., 0.1, 1.]),
+            debug_hit: Color([1., 1., 1., 1.]),
+            toolpath_rapid: Color([1., 0.5, 0., 1.]),
+            toolpath_cut: Color([0.1, 0.5, 1., 1.]),
+        }
+    }
+
+    /// Access the color for a role
+    pub fn color(&self, role: Role) -> Color {
+        match role {
+            Role::Model => self.model,
+            Role::Edge => self.edges,
+            Role::XRay => self.xray,
+            Role::Added => self.added,
+            Role::Removed => self.removed,
+            Role::Overhang => self.overhang,
+            Role::Validation => self.validation,
+            Role::DebugOutside => self.debug_outside,
+            Role::DebugInside => self.debug_inside,
+            Role::DebugHit => self.debug_hit,
+            Role::ToolpathRapid => self.toolpath_rapid,
+            Role::ToolpathCut => self.toolpath_cut,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+/// What a vertex represents, which determines its color
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Role {
+    Model,
+    Edge,
+    XRay,
+    Added,
+    Removed,
+    Overhang,
+    Validation,
+    DebugOutside,
+    DebugInside,
+    DebugHit,
+    ToolpathRapid,
+    ToolpathCut,
+}
+
+/// One of the built-in themes
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Variant {
+    Light,
+    Dark,
+}
+
+impl Variant {
+    /// Switch to the other variant
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Light => Self::Dark,
+            Self::Dark => Self::Light,
+        }
+    }
+}
+
+/// The configuration of the themes
+///
+/// Each color of the built-in themes can be overridden.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// The variant that the viewer starts with
+    pub variant: Variant,
+
+    /// Overrides for the light theme
+    pub light: Colors,
+
+    /// Overrides for the dark theme
+    pub dark: Colors,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            variant: Variant::Light,
+            light: Colors::default(),
+            dark: Colors::default(),
+        }
+    }
+}
+
+impl ThemeConfig {
+    /// Create the theme for a variant, with the configured colors applied
+    pub fn theme(&self, variant: Variant) -> Theme {
+        match variant {
+            Variant::Light => self.light.apply(Theme::light()),
+            Variant::Dark => self.dark.apply(Theme::dark()),
+        }
+    }
+}
+
+/// Colors that override those of a [`Theme`]
+///
+/// See [`Theme`] for the meaning of each color.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Colors {
+    pub background: Option<Color>,
+    pub text: Option<Color>,
+    pub model: Option<Color>,
+    pub edges: Option<Color>,
+    pub xray: Option<Color>,
+    pub added: Option<Color>,
+    pub removed: Option<Color>,
+    pub overhang: Option<Color>,
+    pub validation: Option<Color>,
+    pub debug_outside: Option<Color>,
+    pub debug_inside: Option<Color>,
+    pub debug_hit: Option<Color>,
+    pub toolpath_rapid: Option<Color>,
+    pub toolpath_cut: Option<Color>,
+}
+
+impl Colors {
+    fn apply(&self, theme: Theme) -> Theme {
+        Theme {
+            background: self.background.unwrap_or(theme.background),
+            text: self.text.unwrap_or(theme.text),
+            model: self.model.unwrap_or(theme.model),
+            edges: self.edges.unwrap_or(theme.edges),
+            xray: self.xray.unwrap_or(theme.xray),
+            added: self.added.unwrap_or(theme.added),
+            removed: self.removed.unwrap_or(theme.removed),
+            overhang: self.overhang.unwrap_or(theme.overhang),
+            validation: self.validation.unwrap_or(theme.validation),
+            debug_outside: self.debug_outside.unwrap_or(theme.debug_outside),
+            debug_inside: self.debug_inside.unwrap_or(theme.debug_inside),
+            debug_hit: self.debug_hit.unwrap_or(theme.debug_hit),
+            toolpath_rapid: self.toolpath_rapid.unwrap_or(theme.toolpath_rapid),
+            toolpath_cut: self.toolpath_cut.unwrap_or(theme.toolpath_cut),
+        }
+    }
+}
+
+/// A color, as red, green, blue, and alpha components
+///
+/// The components are linear, which is what the GPU expects, and range from
+/// zero to one. In a configuration file, a color is written as an array of
+/// three or four components. If alpha is left out, the color is opaque.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "Vec<f32>")]
+pub struct Color(pub [f32; 4]);
+
+impl Color {
+    pub fn to_wgpu(self) -> wgpu::Color {
+        let [r, g, b, a] = self.0.map(f64::from);
+        wgpu::Color { r, g, b, a }
+    }
+}
+
+impl TryFrom<Vec<f32>> for Color {
+    type Error = ColorError;
+
+    fn try_from(components: Vec<f32>) -> Result<Self, Self::Error> {
+        let color = match components[..] {
+            [r, g, b] => [r, g, b, 1.],
+            [r, g, b, a] => [r, g, b, a],
+            _ => return Err(ColorError::Components(components.len())),
+        };
+
+        if color
+            .iter()
+            .any(|component| !(0. ..=1.).contains(component))
+        {
+            return Err(ColorError::OutOfRange(color));
+        }
+
+        Ok(Self(color))
+    }
+}
+
+/// An invalid color
+#[derive(Debug, Error)]
+pub enum ColorError {
+    #[error("Expected 3 or 4 color components, got {0}")]
+    Components(usize),
+
+    #[error("Color components must be between 0 and 1: {0:?}")]
+    OutOfRange([f32; 4]),
+}
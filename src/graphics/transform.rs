@@ -1,7 +1,7 @@
 use bytemuck::{Pod, Zeroable};
-use nalgebra::{Matrix4, Perspective3};
+use nalgebra::Matrix4;
 
-use crate::camera::Camera;
+use crate::{camera::Camera, math::Point};
 
 #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(transparent)]
@@ -15,19 +15,19 @@ impl Transform {
     /// Compute transform used for vertices
     ///
     /// The returned transform is used for transforming vertices on the GPU.
-    pub fn for_vertices(camera: &Camera, aspect_ratio: f64) -> Self {
-        let field_of_view_in_y = camera.field_of_view_in_x() / aspect_ratio;
-
-        let projection = Perspective3::new(
-            aspect_ratio,
-            field_of_view_in_y,
-            camera.near_plane(),
-            camera.far_plane(),
-        );
-
-        let transform = projection.to_projective() * camera.camera_to_model();
-
-        Self::from(transform.matrix())
+    /// The vertices are relative to `origin` (see [`super::Vertices`]), which
+    /// is added back here, in double precision. The translation that remains
+    /// is the one from the camera to the origin, which is small, if the origin
+    /// is close to the camera.
+    pub fn for_vertices(
+        camera: &Camera,
+        aspect_ratio: f64,
+        origin: &Point<3>,
+    ) -> Self {
+        let matrix = camera.projection(aspect_ratio).to_matrix();
+        let matrix = Matrix4::from_column_slice(&matrix)
+            * Matrix4::new_translation(&origin.coords.to_na());
+        Self::from(&matrix)
     }
 
     /// Compute transform used for normals
@@ -35,11 +35,8 @@ impl Transform {
     /// This method is only relevant for the graphics code. The returned
     /// transform is used for transforming normals on the GPU.
     pub fn for_normals(camera: &Camera) -> Self {
-        let transform = camera
-            .camera_to_model()
-            .inverse()
-            .to_homogeneous()
-            .transpose();
+        let matrix = camera.camera_to_model().inverse().to_matrix();
+        let transform = Matrix4::from_column_slice(&matrix).transpose();
 
         Self::from(&transform)
     }
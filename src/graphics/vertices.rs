@@ -2,14 +2,21 @@ use bytemuck::{Pod, Zeroable};
 use nalgebra::{vector, Point};
 
 use crate::{
+    analysis::diff::SurfaceChange,
     debug::DebugInfo,
-    math::Triangle,
-    mesh::{Index, MeshMaker},
+    import::gcode::Polyline,
+    math::{self, Aabb, Scalar, Triangle},
+    mesh::{
+        smooth_normals_by_face, FaceGroups, Index, IndexFormat, IndexOverflow,
+        MeshMaker,
+    },
 };
 
+use super::theme::{Role, Theme};
+
 #[derive(Debug)]
 pub struct Vertices {
-    vertices: Vec<Vertex>,
+    vertices: Vec<VertexData>,
     indices: Vec<Index>,
 }
 
@@ -21,76 +28,275 @@ impl Vertices {
         }
     }
 
-    pub fn vertices(&self) -> &[Vertex] {
-        self.vertices.as_slice()
+    /// Convert the vertices into the format the GPU expects
+    ///
+    /// The GPU only supports single precision. Converting the positions
+    /// directly would make models that are far from the origin jitter, as
+    /// single precision can't resolve small distances between large
+    /// coordinates. Subtracting an origin close to the camera first, while the
+    /// positions are still in double precision, keeps the coordinates small
+    /// where precision matters.
+    ///
+    /// The color of each vertex is taken from `theme`, according to its role.
+    pub fn vertices(
+        &self,
+        origin: &Point<f64, 3>,
+        theme: &Theme,
+    ) -> Vec<Vertex> {
+        self.vertices
+            .iter()
+            .map(|vertex| {
+                let position = vertex.position - origin;
+                Vertex {
+                    position: position.map(|c| c as f32).into(),
+                    normal: vertex.normal,
+                    color: theme.color(vertex.role).0,
+                }
+            })
+            .collect()
     }
 
     pub fn indices(&self) -> &[Index] {
         self.indices.as_slice()
     }
 
+    /// Add a line
+    ///
+    /// Returns an error, if there are too many vertices for an [`Index`] to
+    /// refer to.
     pub fn push_line(
         &mut self,
         line: [Point<f64, 3>; 2],
         normal: [f32; 3],
-        color: [f32; 4],
-    ) {
-        let line = line.into_iter().map(|point| Vertex {
-            position: [point.x as f32, point.y as f32, point.z as f32],
+        role: Role,
+    ) -> Result<(), IndexOverflow> {
+        IndexFormat::U32.check(self.vertices.len() + 2)?;
+
+        let line = line.into_iter().map(|position| VertexData {
+            position,
             normal,
-            color,
+            role,
         });
 
         self.vertices.extend(line);
 
-        self.indices.push(self.indices.len() as u32);
-        self.indices.push(self.indices.len() as u32);
+        for _ in 0..2 {
+            self.indices.push(self.indices.len() as Index);
+        }
+
+        Ok(())
     }
-}
 
-impl From<&Vec<Triangle<3>>> for Vertices {
-    fn from(triangles: &Vec<Triangle<3>>) -> Self {
-        let mut mesh = MeshMaker::new();
+    /// Add a triangle
+    ///
+    /// Returns an error, if there are too many vertices for an [`Index`] to
+    /// refer to.
+    pub fn push_triangle(
+        &mut self,
+        triangle: &Triangle<3>,
+        role: Role,
+    ) -> Result<(), IndexOverflow> {
+        IndexFormat::U32.check(self.vertices.len() + 3)?;
 
-        for triangle in triangles {
-            let [a, b, c] = triangle.points();
+        let [a, b, c] = triangle.points().map(|point| point.to_na());
+        let normal = (b - a)
+            .cross(&(c - a))
+            .try_normalize(0.)
+            .unwrap_or_else(nalgebra::Vector3::zeros);
+        let normal = normal.map(|c| c as f32).into();
+
+        let triangle = [a, b, c].into_iter().map(|position| VertexData {
+            position,
+            normal,
+            role,
+        });
 
-            let normal = (b - a).cross(&(c - a)).normalize();
+        self.vertices.extend(triangle);
 
-            mesh.push((a, normal));
-            mesh.push((b, normal));
-            mesh.push((c, normal));
+        for _ in 0..3 {
+            self.indices.push(self.indices.len() as Index);
+        }
+
+        Ok(())
+    }
+
+    /// Mark the positions of validation errors
+    ///
+    /// Each position is marked with a cross, whose size is relative to the
+    /// size of the model, so the markers can be found when zoomed out.
+    pub fn validation_errors(
+        debug_info: &DebugInfo,
+        aabb: &Aabb<3>,
+    ) -> Result<Self, IndexOverflow> {
+        let mut self_ = Self::empty();
+
+        let d = aabb.size().magnitude().into_f64() * 0.01;
+        let normal = [0.; 3];
+
+        for error in &debug_info.validation_errors {
+            for point in error.positions() {
+                let point = point.to_na();
+
+                for axis in
+                    [vector![d, 0., 0.], vector![0., d, 0.], vector![0., 0., d]]
+                {
+                    self_.push_line(
+                        [point - axis, point + axis],
+                        normal,
+                        Role::Validation,
+                    )?;
+                }
+            }
+        }
+
+        Ok(self_)
+    }
+}
+
+impl Vertices {
+    /// Create vertices for the triangles of a shape
+    ///
+    /// Curved surfaces are approximated by triangles that meet at shallow
+    /// angles. Smoothing the normals across those makes the surfaces look
+    /// curved, while the edges between the faces of the shape, as recorded in
+    /// `faces`, stay sharp.
+    pub fn mesh(
+        triangles: &[Triangle<3>],
+        faces: &FaceGroups,
+    ) -> Result<Self, IndexOverflow> {
+        let max_angle = Scalar::from_f64(30_f64.to_radians());
+        let vertices = smooth_normals_by_face(triangles, faces, max_angle);
+
+        let mut mesh = MeshMaker::new();
+        for vertex in vertices.into_iter().flatten() {
+            mesh.push(vertex)?;
         }
 
         let vertices = mesh
             .vertices()
-            .map(|(vertex, normal)| Vertex {
-                position: vertex.into(),
-                normal: normal.into(),
-                color: [1.0, 0.0, 0.0, 1.0],
+            .map(|vertex| VertexData {
+                position: vertex.position.to_na(),
+                // Can't panic. `smooth_normals_by_face` sets the normal of
+                // every vertex.
+                normal: vertex.normal.unwrap().into(),
+                role: Role::Model,
             })
             .collect();
 
         let indices = mesh.indices().collect();
 
+        Ok(Self { vertices, indices })
+    }
+
+    /// Create vertices for edges that are drawn on top of the model
+    ///
+    /// This is used for the edges of the shape, as well as its silhouette.
+    pub fn edges(
+        edges: impl IntoIterator<Item = [math::Point<3>; 2]>,
+    ) -> Result<Self, IndexOverflow> {
+        let mut self_ = Self::empty();
+
+        for edge in edges {
+            self_.push_line(
+                edge.map(|point| point.to_na()),
+                [0.; 3],
+                Role::Edge,
+            )?;
+        }
+
+        Ok(self_)
+    }
+
+    /// Create vertices that highlight the surface that changed
+    ///
+    /// Each triangle gets the color of its role, to distinguish added from
+    /// removed surfaces.
+    pub fn surface_change(
+        change: &SurfaceChange,
+    ) -> Result<Self, IndexOverflow> {
+        let mut self_ = Self::empty();
+
+        for (triangles, role) in [
+            (&change.added, Role::Added),
+            (&change.removed, Role::Removed),
+        ] {
+            for triangle in triangles {
+                self_.push_triangle(triangle, role)?;
+            }
+        }
+
+        Ok(self_)
+    }
+
+    /// Create vertices that highlight the surfaces that need support
+    ///
+    /// See [`crate::analysis::orientation::overhangs`].
+    pub fn overhangs(triangles: &[Triangle<3>]) -> Result<Self, IndexOverflow> {
+        let mut self_ = Self::empty();
+
+        for triangle in triangles {
+            self_.push_triangle(triangle, Role::Overhang)?;
+        }
+
+        Ok(self_)
+    }
+
+    /// Create vertices for the translucent X-ray view of a mesh
+    ///
+    /// Translucent triangles have to be drawn from back to front, for their
+    /// colors to blend correctly. This sorts the triangles by the distance of
+    /// their centers from `eye`, which is exact for triangles that don't
+    /// intersect, and close enough otherwise.
+    pub fn xray(&self, eye: math::Point<3>) -> Self {
+        let eye = eye.to_na();
+
+        let mut triangles: Vec<_> = self
+            .indices
+            .chunks_exact(3)
+            .map(|triangle| {
+                let center = triangle
+                    .iter()
+                    .map(|&index| self.vertices[index as usize].position.coords)
+                    .sum::<nalgebra::Vector3<f64>>()
+                    / 3.;
+                ((center - eye.coords).norm_squared(), triangle)
+            })
+            .collect();
+        triangles.sort_by(|(a, _), (b, _)| {
+            // Can't panic, unless the mesh contains NaN.
+            b.partial_cmp(a).unwrap()
+        });
+
+        let vertices = self
+            .vertices
+            .iter()
+            .map(|&vertex| VertexData {
+                role: Role::XRay,
+                ..vertex
+            })
+            .collect();
+        let indices = triangles
+            .into_iter()
+            .flat_map(|(_, triangle)| triangle.iter().copied())
+            .collect();
+
         Self { vertices, indices }
     }
 }
 
-impl From<&DebugInfo> for Vertices {
-    fn from(debug_info: &DebugInfo) -> Self {
+impl TryFrom<&DebugInfo> for Vertices {
+    type Error = IndexOverflow;
+
+    fn try_from(debug_info: &DebugInfo) -> Result<Self, Self::Error> {
         let mut self_ = Self::empty();
 
         for triangle_edge_check in &debug_info.triangle_edge_checks {
             let normal = [0.; 3];
 
-            let red = [1., 0., 0., 1.];
-            let green = [0., 1., 0., 1.];
-
-            let color = if triangle_edge_check.hits.len() % 2 == 0 {
-                red
+            let role = if triangle_edge_check.hits.len() % 2 == 0 {
+                Role::DebugOutside
             } else {
-                green
+                Role::DebugInside
             };
 
             self_.push_line(
@@ -100,32 +306,67 @@ impl From<&DebugInfo> for Vertices {
                         + triangle_edge_check.ray.dir,
                 ],
                 normal,
-                color,
-            );
+                role,
+            )?;
 
             for &hit in &triangle_edge_check.hits {
                 let point = triangle_edge_check.ray.point_at(hit);
 
                 let d = 0.05;
-                let color = [0., 0., 0., 1.];
 
                 self_.push_line(
                     [point - vector![d, 0., 0.], point + vector![d, 0., 0.]],
                     normal,
-                    color,
-                );
+                    Role::DebugHit,
+                )?;
                 self_.push_line(
                     [point - vector![0., d, 0.], point + vector![0., d, 0.]],
                     normal,
-                    color,
-                );
+                    Role::DebugHit,
+                )?;
             }
         }
 
-        self_
+        Ok(self_)
     }
 }
 
+impl TryFrom<&[Polyline]> for Vertices {
+    type Error = IndexOverflow;
+
+    fn try_from(toolpath: &[Polyline]) -> Result<Self, Self::Error> {
+        let mut self_ = Self::empty();
+
+        for polyline in toolpath {
+            let normal = [0.; 3];
+
+            let role = if polyline.rapid {
+                Role::ToolpathRapid
+            } else {
+                Role::ToolpathCut
+            };
+
+            for segment in polyline.points.windows(2) {
+                self_.push_line(
+                    [segment[0].to_na(), segment[1].to_na()],
+                    normal,
+                    role,
+                )?;
+            }
+        }
+
+        Ok(self_)
+    }
+}
+
+/// A vertex, before it is converted for the GPU
+#[derive(Clone, Copy, Debug)]
+struct VertexData {
+    position: Point<f64, 3>,
+    normal: [f32; 3],
+    role: Role,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
 #[repr(C)]
 pub struct Vertex {
@@ -133,3 +374,37 @@ pub struct Vertex {
     pub normal: [f32; 3],
     pub color: [f32; 4],
 }
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::point;
+
+    use crate::{
+        math::{Point, Triangle},
+        mesh::FaceGroups,
+    };
+
+    use super::{Role, Vertices};
+
+    #[test]
+    fn xray_should_sort_triangles_back_to_front() {
+        let triangle = |z: f64| {
+            Triangle::from(
+                [[0., 0., z], [1., 0., z], [0., 1., z]].map(Point::from),
+            )
+        };
+        let triangles = [triangle(1.), triangle(3.), triangle(2.)];
+        let mesh = Vertices::mesh(&triangles, &FaceGroups::default()).unwrap();
+
+        let xray = mesh.xray(Point::from([0., 0., 0.]));
+
+        let depths: Vec<_> = xray
+            .indices
+            .chunks_exact(3)
+            .map(|triangle| xray.vertices[triangle[0] as usize].position.z)
+            .collect();
+        assert_eq!(depths, [3., 2., 1.]);
+        assert!(xray.vertices.iter().all(|vertex| vertex.role == Role::XRay));
+        assert_eq!(xray.vertices[0].position, point![0., 0., 1.]);
+    }
+}
@@ -0,0 +1,393 @@
+use std::{f64::consts::PI, fs, io, path::Path};
+
+use thiserror::Error;
+
+use crate::math::{Arc, Point, Scalar};
+
+/// Read the toolpath from a G-code file
+///
+/// See [`parse`].
+pub fn read(path: impl AsRef<Path>) -> Result<Vec<Polyline>, Error> {
+    let gcode = fs::read_to_string(path)?;
+    parse(&gcode)
+}
+
+/// Parse the toolpath from G-code
+///
+/// Only the subset of G-code that is relevant for previewing a toolpath is
+/// supported: linear moves (`G0`, `G1`), arcs in the x-y plane (`G2`, `G3`),
+/// units (`G20`, `G21`), and absolute or relative coordinates (`G90`, `G91`).
+/// All other commands are ignored.
+///
+/// Coordinates are converted to millimeters. The tool is assumed to start at
+/// the origin.
+pub fn parse(gcode: &str) -> Result<Vec<Polyline>, Error> {
+    let mut state = State {
+        position: [0.; 3],
+        motion: None,
+        scale: 1.,
+        relative: false,
+        plane_is_xy: true,
+    };
+    let mut polylines: Vec<Polyline> = Vec::new();
+
+    for (i, line) in gcode.lines().enumerate() {
+        let line_number = i + 1;
+
+        let words = words(line).map_err(|word| Error::InvalidWord {
+            line: line_number,
+            word,
+        })?;
+
+        let mut target = None;
+        let mut center_offset = None;
+        let mut radius = None;
+
+        for &(letter, value) in &words {
+            match (letter, value) {
+                ('G', code) => state.apply_code(code),
+                ('X' | 'Y' | 'Z', value) => {
+                    let axis = match letter {
+                        'X' => 0,
+                        'Y' => 1,
+                        _ => 2,
+                    };
+
+                    let target = target.get_or_insert(if state.relative {
+                        [0.; 3]
+                    } else {
+                        state.position
+                    });
+                    target[axis] = value * state.scale;
+                }
+                ('I' | 'J', value) => {
+                    let offset = center_offset.get_or_insert([0.; 2]);
+                    offset[if letter == 'I' { 0 } else { 1 }] =
+                        value * state.scale;
+                }
+                ('R', value) => radius = Some(value * state.scale),
+                _ => {
+                    // Everything else (feed rate, spindle speed, tool
+                    // changes, ...) is irrelevant for previewing the toolpath.
+                }
+            }
+        }
+
+        let (motion, mut target) = match (state.motion, target) {
+            (Some(motion), Some(target)) => (motion, target),
+            _ => continue,
+        };
+
+        if state.relative {
+            for (target, position) in target.iter_mut().zip(state.position) {
+                *target += position;
+            }
+        }
+
+        let rapid = motion == Motion::Rapid;
+        let polyline = match polylines.last_mut() {
+            Some(polyline) if polyline.rapid == rapid => polyline,
+            _ => {
+                polylines.push(Polyline {
+                    rapid,
+                    points: vec![Point::from(state.position)],
+                });
+                polylines.last_mut().expect("Just pushed polyline")
+            }
+        };
+
+        match motion {
+            Motion::Rapid | Motion::Linear => {
+                polyline.points.push(Point::from(target));
+            }
+            Motion::Arc { clockwise } => {
+                if !state.plane_is_xy {
+                    return Err(Error::UnsupportedPlane(line_number));
+                }
+
+                let center = match (center_offset, radius) {
+                    (Some([i, j]), _) => {
+                        [state.position[0] + i, state.position[1] + j]
+                    }
+                    (None, Some(radius)) => {
+                        arc_center(state.position, target, radius, clockwise)
+                    }
+                    (None, None) => return Err(Error::InvalidArc(line_number)),
+                };
+
+                arc(state.position, target, center, clockwise, |point| {
+                    polyline.points.push(Point::from(point))
+                });
+            }
+        }
+
+        state.position = target;
+    }
+
+    Ok(polylines)
+}
+
+/// A continuous motion of the tool
+#[derive(Debug, PartialEq)]
+pub struct Polyline {
+    /// Indicates whether this is a rapid move, during which the tool doesn't
+    /// cut
+    pub rapid: bool,
+
+    /// The points that the tool moves through, in millimeters
+    pub points: Vec<Point<3>>,
+}
+
+/// An error that can occur while reading a G-code file
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O error while reading G-code file")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid word `{word}` in line {line}")]
+    InvalidWord { line: usize, word: String },
+
+    #[error("Arc in line {0} has neither center nor radius")]
+    InvalidArc(usize),
+
+    #[error("Arc in line {0} is not in the x-y plane, which is not supported")]
+    UnsupportedPlane(usize),
+}
+
+struct State {
+    position: [f64; 3],
+    motion: Option<Motion>,
+    scale: f64,
+    relative: bool,
+    plane_is_xy: bool,
+}
+
+impl State {
+    fn apply_code(&mut self, code: f64) {
+        // Codes like `G91.1` have a fractional part. None of them are relevant
+        // here, and it's better to ignore them than to mistake them for a
+        // different code.
+        if code.fract() != 0. {
+            return;
+        }
+
+        match code as u32 {
+            0 => self.motion = Some(Motion::Rapid),
+            1 => self.motion = Some(Motion::Linear),
+            2 => self.motion = Some(Motion::Arc { clockwise: true }),
+            3 => self.motion = Some(Motion::Arc { clockwise: false }),
+            17 => self.plane_is_xy = true,
+            18 | 19 => self.plane_is_xy = false,
+            20 => self.scale = 25.4,
+            21 => self.scale = 1.,
+            90 => self.relative = false,
+            91 => self.relative = true,
+            _ => {}
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Motion {
+    Rapid,
+    Linear,
+    Arc { clockwise: bool },
+}
+
+/// Split a line of G-code into words, ignoring comments
+///
+/// Lines that only consist of `%`, which marks the start and end of a program
+/// in many files, don't contain any words. Returns the offending word, if a
+/// word is invalid.
+fn words(line: &str) -> Result<Vec<(char, f64)>, String> {
+    let line = line.split(';').next().unwrap_or_default();
+
+    // Remove comments in parentheses.
+    let mut code = String::new();
+    let mut in_comment = false;
+    for c in line.chars() {
+        match c {
+            '(' => in_comment = true,
+            ')' => in_comment = false,
+            c if !in_comment && !c.is_whitespace() => {
+                code.push(c.to_ascii_uppercase())
+            }
+            _ => {}
+        }
+    }
+
+    if code == "%" {
+        return Ok(Vec::new());
+    }
+
+    let mut words = Vec::new();
+    let mut chars = code.chars().peekable();
+    while let Some(letter) = chars.next() {
+        let mut number = String::new();
+        while let Some(&c) = chars.peek() {
+            if !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+') {
+                break;
+            }
+            number.push(c);
+            chars.next();
+        }
+
+        let word = format!("{}{}", letter, number);
+        if !letter.is_ascii_alphabetic() {
+            return Err(word);
+        }
+
+        let value: f64 = number.parse().map_err(|_| word.clone())?;
+        words.push((letter, value));
+    }
+
+    Ok(words)
+}
+
+/// Compute the center of an arc that is defined by its radius
+///
+/// A positive radius selects the shorter of the two possible arcs, a negative
+/// radius the longer one.
+fn arc_center(
+    start: [f64; 3],
+    end: [f64; 3],
+    radius: f64,
+    clockwise: bool,
+) -> [f64; 2] {
+    let [dx, dy] = [end[0] - start[0], end[1] - start[1]];
+    let chord = (dx * dx + dy * dy).sqrt();
+
+    // If the radius is too small for the end points, the best we can do is
+    // put the center between them.
+    let height = (radius * radius - chord * chord / 4.).max(0.).sqrt();
+
+    let mut side = if clockwise { 1. } else { -1. };
+    if radius < 0. {
+        side = -side;
+    }
+
+    let [mx, my] = [start[0] + dx / 2., start[1] + dy / 2.];
+    if chord == 0. {
+        return [mx, my];
+    }
+    [
+        mx + side * height * dy / chord,
+        my - side * height * dx / chord,
+    ]
+}
+
+/// Approximate an arc, calling `f` with each point after `start`
+fn arc(
+    start: [f64; 3],
+    end: [f64; 3],
+    center: [f64; 2],
+    clockwise: bool,
+    mut f: impl FnMut([f64; 3]),
+) {
+    // That's accurate enough for previewing.
+    const MAX_STEP: f64 = PI / 36.;
+
+    let arc = Arc::from_center_and_points(
+        Point::from(center),
+        Point::from([start[0], start[1]]),
+        Point::from([end[0], end[1]]),
+        clockwise,
+    );
+
+    let steps = (arc.sweep().abs().into_f64() / MAX_STEP).ceil().max(1.);
+    let steps = steps as usize;
+    for step in 1..steps {
+        let t = step as f64 / steps as f64;
+        let [x, y] = arc.point_at(Scalar::from_f64(t)).into();
+
+        f([x, y, start[2] + (end[2] - start[2]) * t]);
+    }
+
+    f(end);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::Point;
+
+    use super::{parse, Polyline};
+
+    #[test]
+    fn parse_linear_moves() {
+        let gcode = "
+            G21 G90 (absolute, in millimeters)
+            G0 Z5
+            G0 X1 Y2
+            G1 Z-1 F100 ; plunge
+            X3
+            G91 Y1
+            G20 G1 X1
+        ";
+
+        let polylines = parse(gcode).unwrap();
+        assert_eq!(
+            polylines,
+            [
+                Polyline {
+                    rapid: true,
+                    points: vec![
+                        Point::from([0., 0., 0.]),
+                        Point::from([0., 0., 5.]),
+                        Point::from([1., 2., 5.]),
+                    ]
+                },
+                Polyline {
+                    rapid: false,
+                    points: vec![
+                        Point::from([1., 2., 5.]),
+                        Point::from([1., 2., -1.]),
+                        Point::from([3., 2., -1.]),
+                        Point::from([3., 3., -1.]),
+                        Point::from([28.4, 3., -1.]),
+                    ]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_arcs() {
+        // Two half circles from `[1, 0]` to `[-1, 0]` around the origin, one
+        // defined by its center, one by its radius.
+        for gcode in ["G0 X1\nG3 X-1 I-1", "G0 X1\nG3 X-1 R1"] {
+            let polylines = parse(gcode).unwrap();
+
+            let arc = &polylines[1].points;
+            assert_eq!(arc.len(), 37);
+            for point in arc {
+                let distance = point.coords.magnitude().into_f64();
+                assert!((distance - 1.).abs() < 1e-9);
+
+                // Counter-clockwise, so the arc goes through positive y.
+                assert!(point.y.into_f64() >= -1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_program_markers() {
+        let gcode = "%\nG1 X1 (start)\n% (end)\n";
+
+        let polylines = parse(gcode).unwrap();
+        assert_eq!(
+            polylines,
+            [Polyline {
+                rapid: false,
+                points: vec![
+                    Point::from([0., 0., 0.]),
+                    Point::from([1., 0., 0.]),
+                ]
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_invalid_word() {
+        assert!(parse("G1 X1\nG1 Xfoo").is_err());
+    }
+}
@@ -0,0 +1,21 @@
+//! Import of external geometry
+//!
+//! This module contains readers for file formats that Fornjot doesn't produce
+//! itself, but needs to be able to consume. An example of this is a mesh that
+//! was captured by a 3D scanner, which can be compared against the nominal
+//! geometry of a model, or a toolpath, which can be checked against the model
+//! it is supposed to machine. Models written for other CAD applications, and 2D
+//! profiles from drawing applications, can be imported too, to make migrating
+//! them to Fornjot easier.
+//!
+//! Imported geometry is often more detailed than necessary. [`simplify`] can
+//! reduce that detail, before the geometry is handed to the kernel.
+
+pub mod gcode;
+pub mod profile;
+pub mod scad;
+pub mod stl;
+
+mod simplify;
+
+pub use self::simplify::simplify;
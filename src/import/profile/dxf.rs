@@ -0,0 +1,625 @@
+//! Reading of outlines from DXF files
+//!
+//! Only the `ENTITIES` section of the file is read, and only the entities that
+//! describe curves in the x-y plane: `LINE`, `ARC`, `CIRCLE`, `ELLIPSE`,
+//! `LWPOLYLINE`, `POLYLINE`, and `SPLINE`. Other entities, like text,
+//! dimensions, or blocks, are ignored with a warning. Drawings often consist of
+//! separate lines and arcs, which are chained into outlines by matching up
+//! their endpoints.
+//!
+//! DXF files don't have to specify a unit, so coordinates are read as
+//! millimeters.
+
+use std::f64::consts::TAU;
+
+use tracing::warn;
+
+use crate::math::{Arc, Point, Scalar};
+
+use super::{ellipse_arc, Error, Outline, TOLERANCE};
+
+/// Parse the closed outlines from a DXF file
+pub fn parse(source: &str) -> Result<Vec<Outline>, Error> {
+    let pairs = pairs(source)?;
+
+    // Find the entities section, which starts with a `SECTION` that is named
+    // `ENTITIES`.
+    let start = pairs
+        .windows(2)
+        .position(|w| {
+            w[0].is(0, "SECTION") && w[1].code == 2 && w[1].value == "ENTITIES"
+        })
+        .map(|i| i + 2)
+        .unwrap_or(pairs.len());
+    let end = pairs[start..]
+        .iter()
+        .position(|pair| pair.is(0, "ENDSEC"))
+        .map(|i| start + i)
+        .unwrap_or(pairs.len());
+
+    let mut outlines = Vec::new();
+    let mut segments = Vec::new();
+
+    let mut entities = Entities {
+        pairs: &pairs[start..end],
+    };
+    while let Some(entity) = entities.next_entity() {
+        let kind = entity.kind();
+        match kind {
+            "LINE" => segments.push(vec![
+                [entity.number(10)?, entity.number(20)?],
+                [entity.number(11)?, entity.number(21)?],
+            ]),
+            "ARC" | "CIRCLE" => {
+                let (start, end) = if kind == "ARC" {
+                    (entity.number(50)?, entity.number(51)?)
+                } else {
+                    (0., 0.)
+                };
+
+                // Arcs go counter-clockwise. Like for `Arc`, an arc that ends
+                // where it starts is a full circle.
+                let arc = Arc {
+                    center: Point::from([
+                        entity.number(10)?,
+                        entity.number(20)?,
+                    ]),
+                    radius: Scalar::from_f64(entity.number(40)?),
+                    start_angle: Scalar::from_f64(start.to_radians()),
+                    end_angle: Scalar::from_f64(end.to_radians()),
+                    clockwise: false,
+                };
+
+                let points = circular_arc(&arc);
+
+                if kind == "CIRCLE" {
+                    outlines.push(points);
+                } else {
+                    segments.push(points);
+                }
+            }
+            "ELLIPSE" => {
+                let center = [entity.number(10)?, entity.number(20)?];
+                let major = [entity.number(11)?, entity.number(21)?];
+                let ratio = entity.number(40)?;
+                let start = entity.number_or(41, 0.)?;
+                let end = entity.number_or(42, TAU)?;
+
+                let rx = major[0].hypot(major[1]);
+                let rotation = major[1].atan2(major[0]);
+                let mut sweep = (end - start).rem_euclid(TAU);
+                if sweep == 0. {
+                    sweep = TAU;
+                }
+
+                let points =
+                    arc(center, [rx, rx * ratio], rotation, start, sweep);
+
+                if sweep == TAU {
+                    outlines.push(points);
+                } else {
+                    segments.push(points);
+                }
+            }
+            "LWPOLYLINE" => {
+                let closed = entity.number_or(70, 0.)? as u32 & 1 == 1;
+
+                // Each vertex starts with its x coordinate, and can have a
+                // bulge, which describes an arc to the next vertex.
+                let mut vertices: Vec<([f64; 2], f64)> = Vec::new();
+                for pair in entity.pairs {
+                    match pair.code {
+                        10 => vertices.push(([pair.number()?, 0.], 0.)),
+                        20 | 42 => {
+                            let (point, bulge) =
+                                vertices.last_mut().ok_or_else(|| {
+                                    pair.error("Value before first vertex")
+                                })?;
+                            if pair.code == 20 {
+                                point[1] = pair.number()?;
+                            } else {
+                                *bulge = pair.number()?;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                let points = polyline(&vertices, closed);
+                if closed {
+                    outlines.push(points);
+                } else {
+                    segments.push(points);
+                }
+            }
+            "POLYLINE" => {
+                let closed = entity.number_or(70, 0.)? as u32 & 1 == 1;
+
+                // The vertices are separate entities that follow, up to the
+                // end of the sequence.
+                let mut vertices = Vec::new();
+                while let Some(vertex) = entities.next_entity() {
+                    match vertex.kind() {
+                        "VERTEX" => vertices.push((
+                            [vertex.number(10)?, vertex.number(20)?],
+                            vertex.number_or(42, 0.)?,
+                        )),
+                        "SEQEND" => break,
+                        kind => {
+                            return Err(vertex.pairs[0].error(&format!(
+                                "Unexpected `{}` in polyline",
+                                kind
+                            )))
+                        }
+                    }
+                }
+
+                let points = polyline(&vertices, closed);
+                if closed {
+                    outlines.push(points);
+                } else {
+                    segments.push(points);
+                }
+            }
+            "SPLINE" => {
+                let (points, closed) = spline(&entity)?;
+                if closed {
+                    outlines.push(points);
+                } else {
+                    segments.push(points);
+                }
+            }
+            kind => {
+                warn!("Ignoring unsupported DXF entity `{}`", kind);
+            }
+        }
+    }
+
+    outlines.extend(chain(segments)?);
+
+    Ok(outlines)
+}
+
+/// A pair of a group code and a value, which DXF files consist of
+#[derive(Debug)]
+struct Pair<'r> {
+    code: u16,
+    value: &'r str,
+
+    /// The line that the value is on, for error messages
+    line: usize,
+}
+
+impl Pair<'_> {
+    fn is(&self, code: u16, value: &str) -> bool {
+        self.code == code && self.value == value
+    }
+
+    fn number(&self) -> Result<f64, Error> {
+        // Rust also parses `inf` and `NaN`, which no valid file contains.
+        self.value
+            .parse()
+            .ok()
+            .filter(|number: &f64| number.is_finite())
+            .ok_or_else(|| {
+                self.error(&format!("Expected number: `{}`", self.value))
+            })
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::Dxf {
+            line: self.line,
+            message: message.to_owned(),
+        }
+    }
+}
+
+/// Read the pairs of group codes and values from a DXF file
+fn pairs(source: &str) -> Result<Vec<Pair<'_>>, Error> {
+    let mut lines = source.lines().map(str::trim).enumerate();
+    let mut pairs = Vec::new();
+
+    while let Some((i, code)) = lines.next() {
+        if code.is_empty() && lines.clone().all(|(_, line)| line.is_empty()) {
+            break;
+        }
+
+        let code = code.parse().map_err(|_| Error::Dxf {
+            line: i + 1,
+            message: format!("Expected group code: `{}`", code),
+        })?;
+        let (i, value) = lines.next().ok_or_else(|| Error::Dxf {
+            line: i + 1,
+            message: "Group code without value".to_owned(),
+        })?;
+
+        if code == 0 && value == "EOF" {
+            break;
+        }
+
+        pairs.push(Pair {
+            code,
+            value,
+            line: i + 1,
+        });
+    }
+
+    Ok(pairs)
+}
+
+/// The entities in the entities section
+struct Entities<'p, 'r> {
+    pairs: &'p [Pair<'r>],
+}
+
+impl<'p, 'r> Entities<'p, 'r> {
+    /// Return the next entity
+    ///
+    /// Each entity starts with a pair with the group code `0`, and goes on up
+    /// to the next one.
+    fn next_entity(&mut self) -> Option<Entity<'p, 'r>> {
+        let start = self.pairs.iter().position(|pair| pair.code == 0)?;
+        let pairs = &self.pairs[start..];
+        let end = pairs[1..]
+            .iter()
+            .position(|pair| pair.code == 0)
+            .map(|i| i + 1)
+            .unwrap_or(pairs.len());
+
+        self.pairs = &pairs[end..];
+        Some(Entity {
+            pairs: &pairs[..end],
+        })
+    }
+}
+
+/// An entity, as the pairs that it consists of
+struct Entity<'p, 'r> {
+    pairs: &'p [Pair<'r>],
+}
+
+impl Entity<'_, '_> {
+    fn kind(&self) -> &str {
+        self.pairs[0].value
+    }
+
+    /// Return the first value with the group code, as a number
+    fn number(&self, code: u16) -> Result<f64, Error> {
+        match self.pairs.iter().find(|pair| pair.code == code) {
+            Some(pair) => pair.number(),
+            None => Err(self.pairs[0].error(&format!(
+                "`{}` without group code {}",
+                self.kind(),
+                code
+            ))),
+        }
+    }
+
+    /// Return the first value with the group code, or a default, if it's
+    /// missing
+    fn number_or(&self, code: u16, default: f64) -> Result<f64, Error> {
+        match self.pairs.iter().find(|pair| pair.code == code) {
+            Some(pair) => pair.number(),
+            None => Ok(default),
+        }
+    }
+
+    /// Return all values with the group code, as numbers
+    fn numbers(&self, code: u16) -> Result<Vec<f64>, Error> {
+        self.pairs
+            .iter()
+            .filter(|pair| pair.code == code)
+            .map(Pair::number)
+            .collect()
+    }
+}
+
+/// Approximate an elliptical arc by points, including its start point
+///
+/// See [`ellipse_arc`] for the meaning of the arguments.
+fn arc(
+    center: [f64; 2],
+    radii: [f64; 2],
+    rotation: f64,
+    start: f64,
+    sweep: f64,
+) -> Outline {
+    // An arc without a sweep consists of just its start point.
+    let mut points = Vec::new();
+    ellipse_arc(center, radii, rotation, start, 0., &mut points);
+    ellipse_arc(center, radii, rotation, start, sweep, &mut points);
+    points
+}
+
+/// Approximate a circular arc by points, including its start point
+fn circular_arc(arc: &Arc) -> Outline {
+    let radius = arc.radius.into_f64();
+    self::arc(
+        arc.center.into(),
+        [radius; 2],
+        0.,
+        arc.start_angle.into_f64(),
+        arc.sweep().into_f64(),
+    )
+}
+
+/// Convert the vertices of a polyline into points
+///
+/// Each vertex has a bulge, which describes the arc from it to the next
+/// vertex. The bulge is the tangent of a quarter of the arc's angle, positive
+/// for arcs that go counter-clockwise. A bulge of `0` is a straight segment.
+fn polyline(vertices: &[([f64; 2], f64)], closed: bool) -> Outline {
+    let mut points = Vec::new();
+
+    for (i, &(from, bulge)) in vertices.iter().enumerate() {
+        points.push(from);
+
+        let to = match vertices.get(i + 1) {
+            Some(&(to, _)) => to,
+            None if closed => vertices[0].0,
+            None => break,
+        };
+        if bulge == 0. || from == to {
+            continue;
+        }
+
+        let angle = 4. * bulge.atan();
+        let dx = to[0] - from[0];
+        let dy = to[1] - from[1];
+        let chord = dx.hypot(dy);
+        let radius = chord / 2. / (angle / 2.).sin();
+
+        // The center is on the perpendicular bisector of the chord, to the
+        // left, for arcs that go counter-clockwise.
+        let offset = chord / 2. / (angle / 2.).tan();
+        let center = [
+            (from[0] + to[0]) / 2. - dy / chord * offset,
+            (from[1] + to[1]) / 2. + dx / chord * offset,
+        ];
+
+        let start = (from[1] - center[1]).atan2(from[0] - center[0]);
+        ellipse_arc(center, [radius.abs(); 2], 0., start, angle, &mut points);
+
+        // The arc ends at the next vertex, which is added by the next
+        // iteration.
+        points.pop();
+    }
+
+    points
+}
+
+/// Approximate a spline by points
+///
+/// Returns the points, and whether the spline is closed.
+fn spline(entity: &Entity) -> Result<(Outline, bool), Error> {
+    let flags = entity.number_or(70, 0.)? as u32;
+    let degree = entity.number(71)? as usize;
+    let knots = entity.numbers(40)?;
+    let xs = entity.numbers(10)?;
+    let ys = entity.numbers(20)?;
+    let mut weights = entity.numbers(41)?;
+
+    let controls: Vec<_> =
+        xs.into_iter().zip(ys).map(|(x, y)| [x, y]).collect();
+    if weights.len() != controls.len() {
+        weights = vec![1.; controls.len()];
+    }
+    if controls.len() <= degree || knots.len() != controls.len() + degree + 1 {
+        return Err(entity.pairs[0].error("Invalid spline"));
+    }
+
+    // The spline is defined between these knots.
+    let start = knots[degree];
+    let end = knots[controls.len()];
+
+    let samples = controls.len() * 16;
+    let points: Vec<_> = (0..=samples)
+        .map(|i| {
+            let t = start + (end - start) * i as f64 / samples as f64;
+            de_boor(t, degree, &knots, &controls, &weights)
+        })
+        .collect();
+
+    let closed = flags & 1 == 1;
+    Ok((points, closed))
+}
+
+/// Evaluate a rational B-spline at `t`, using de Boor's algorithm
+fn de_boor(
+    t: f64,
+    degree: usize,
+    knots: &[f64],
+    controls: &[[f64; 2]],
+    weights: &[f64],
+) -> [f64; 2] {
+    // Find the knot span that contains `t`. The end of the spline belongs to
+    // the last span.
+    let k = (degree..controls.len())
+        .rev()
+        .find(|&k| knots[k] <= t && knots[k] < knots[k + 1])
+        .unwrap_or(degree);
+
+    // The control points that affect the span, in homogeneous coordinates
+    let mut d: Vec<[f64; 3]> = (0..=degree)
+        .map(|j| {
+            let [x, y] = controls[j + k - degree];
+            let w = weights[j + k - degree];
+            [x * w, y * w, w]
+        })
+        .collect();
+
+    for r in 1..=degree {
+        for j in (r..=degree).rev() {
+            let i = j + k - degree;
+            let denominator = knots[i + 1 + degree - r] - knots[i];
+            let alpha = if denominator == 0. {
+                0.
+            } else {
+                (t - knots[i]) / denominator
+            };
+
+            for c in 0..3 {
+                d[j][c] = (1. - alpha) * d[j - 1][c] + alpha * d[j][c];
+            }
+        }
+    }
+
+    let [x, y, w] = d[degree];
+    [x / w, y / w]
+}
+
+/// Chain open segments into closed outlines, by matching up their endpoints
+///
+/// Segments can be reversed, if their direction doesn't match.
+fn chain(mut segments: Vec<Outline>) -> Result<Vec<Outline>, Error> {
+    let close = |[ax, ay]: [f64; 2], [bx, by]: [f64; 2]| {
+        (ax - bx).hypot(ay - by) <= TOLERANCE
+    };
+
+    segments.retain(|segment| !segment.is_empty());
+
+    let mut outlines = Vec::new();
+    while let Some(mut outline) = segments.pop() {
+        loop {
+            // Can't panic. Outlines start out as non-empty segments.
+            let first = outline[0];
+            let last = outline[outline.len() - 1];
+
+            if outline.len() > 2 && close(first, last) {
+                outline.pop();
+                outlines.push(outline);
+                break;
+            }
+
+            let next = segments.iter().position(|segment| {
+                close(segment[0], last)
+                    || close(segment[segment.len() - 1], last)
+            });
+            let mut segment = match next {
+                Some(i) => segments.swap_remove(i),
+                None => return Err(Error::Open(last)),
+            };
+
+            if !close(segment[0], last) {
+                segment.reverse();
+            }
+            outline.extend(segment.into_iter().skip(1));
+        }
+    }
+
+    Ok(outlines)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::{super::Error, parse};
+
+    /// Create a DXF file with the given entities
+    fn dxf(entities: &[&[(u16, &str)]]) -> String {
+        let mut source = String::from("0\nSECTION\n2\nENTITIES\n");
+        for entity in entities {
+            for (code, value) in entity.iter() {
+                source.push_str(&format!("{}\n{}\n", code, value));
+            }
+        }
+        source.push_str("0\nENDSEC\n0\nEOF\n");
+        source
+    }
+
+    fn line(
+        from: [&'static str; 2],
+        to: [&'static str; 2],
+    ) -> Vec<(u16, &'static str)> {
+        vec![
+            (0, "LINE"),
+            (10, from[0]),
+            (20, from[1]),
+            (11, to[0]),
+            (21, to[1]),
+        ]
+    }
+
+    #[test]
+    fn parse_should_chain_lines_into_outline() {
+        // A square, with one line reversed, and one that's slightly off
+        let source = dxf(&[
+            &line(["0", "0"], ["2", "0"]),
+            &line(["0", "2"], ["2", "2"]),
+            &line(["2", "0"], ["2", "2.001"]),
+            &line(["0", "2"], ["0", "0"]),
+            &[(0, "TEXT"), (1, "ignored")],
+        ]);
+
+        let outlines = parse(&source).unwrap();
+        assert_eq!(outlines.len(), 1);
+        assert_eq!(outlines[0].len(), 4);
+
+        // Without the last line, the outline isn't closed.
+        let source = dxf(&[
+            &line(["0", "0"], ["2", "0"]),
+            &line(["2", "0"], ["2", "2"]),
+        ]);
+        assert!(matches!(parse(&source), Err(Error::Open(_))));
+    }
+
+    #[test]
+    fn parse_should_read_arcs_and_bulges() {
+        // A closed polyline with two half circles, which form a circle with a
+        // radius of 1
+        let source = dxf(&[
+            &[
+                (0, "LWPOLYLINE"),
+                (70, "1"),
+                (10, "1"),
+                (20, "0"),
+                (42, "1"),
+                (10, "-1"),
+                (20, "0"),
+                (42, "1"),
+            ],
+            &[(0, "CIRCLE"), (10, "5"), (20, "5"), (40, "2")],
+        ]);
+
+        let outlines = parse(&source).unwrap();
+        assert_eq!(outlines.len(), 2);
+
+        let polyline = &outlines[0];
+        assert!(polyline.len() > 8);
+        for &[x, y] in polyline {
+            assert!((x.hypot(y) - 1.).abs() < 1e-9);
+        }
+
+        // The points go around counter-clockwise.
+        let angles: Vec<_> = polyline
+            .iter()
+            .map(|&[x, y]| y.atan2(x).rem_euclid(2. * PI))
+            .collect();
+        assert!(angles.windows(2).all(|w| w[0] < w[1]));
+
+        for &[x, y] in &outlines[1] {
+            assert!(((x - 5.).hypot(y - 5.) - 2.).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn parse_should_report_line_of_error() {
+        let source = "0\nSECTION\n2\nENTITIES\n0\nLINE\n10\nabc\n";
+
+        match parse(source) {
+            Err(Error::Dxf { line: 8, .. }) => {}
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn parse_should_reject_non_finite_numbers() {
+        for value in ["nan", "inf", "-inf", "1e999"] {
+            let source =
+                dxf(&[&[(0, "CIRCLE"), (10, "0"), (20, "0"), (40, value)]]);
+
+            assert!(matches!(parse(&source), Err(Error::Dxf { .. })));
+        }
+    }
+}
@@ -0,0 +1,370 @@
+//! Import of 2-dimensional profiles from SVG and DXF files
+//!
+//! Logos and existing 2D drawings are often available as SVG or DXF files.
+//! This module reads the closed outlines from such files, approximating any
+//! curves by points, and converts them into sketches (see [`to_shape`]). The
+//! readers for the individual formats are in [`svg`] and [`dxf`].
+
+pub mod dxf;
+pub mod svg;
+
+use std::{ffi::OsStr, fs, io, path::Path};
+
+use thiserror::Error;
+
+/// The maximum distance between curves and the points that approximate them
+///
+/// Endpoints of separate segments that are closer than this are considered to
+/// be the same point.
+pub const TOLERANCE: f64 = 0.01;
+
+/// A closed outline, as a list of points
+///
+/// The last point is connected to the first one.
+pub type Outline = Vec<[f64; 2]>;
+
+/// Read a profile from an SVG or DXF file
+///
+/// The format is determined by the extension. Files with the `.dxf` extension
+/// are read as DXF, anything else as SVG. See [`to_shape`] for the meaning of
+/// `height`.
+pub fn read(
+    path: impl AsRef<Path>,
+    height: Option<f64>,
+) -> Result<fj::Shape, Error> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path)?;
+
+    let is_dxf = path
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(|extension| extension.eq_ignore_ascii_case("dxf"))
+        .unwrap_or(false);
+    let outlines = if is_dxf {
+        dxf::parse(&source)?
+    } else {
+        svg::parse(&source)?
+    };
+
+    to_shape(outlines, height)
+}
+
+/// Convert outlines into a shape
+///
+/// Outlines that are inside of another outline are holes in it, unless they
+/// are inside a hole, in which case they are islands within it, and so on. The
+/// outer outline of each such region, and its hole, become a sketch, or a
+/// difference of two sketches. Only one hole per region is supported.
+///
+/// Without a `height`, the result is a 2-dimensional shape, which requires the
+/// outlines to form a single region. With a `height`, each region is swept by
+/// that height, and the results are combined, which works for any number of
+/// regions.
+///
+/// Outlines with fewer than 3 distinct points don't enclose anything, and are
+/// ignored.
+pub fn to_shape(
+    outlines: Vec<Outline>,
+    height: Option<f64>,
+) -> Result<fj::Shape, Error> {
+    let outlines: Vec<_> = outlines
+        .into_iter()
+        .map(clean)
+        .filter(|outline| outline.len() >= 3)
+        .collect();
+
+    let regions = regions(outlines)?;
+
+    match height {
+        None => {
+            let mut regions = regions.into_iter();
+            match (regions.next(), regions.len()) {
+                (Some(region), 0) => Ok(fj::Shape::Shape2d(region)),
+                (Some(_), others) => Err(Error::MultipleRegions(others + 1)),
+                (None, _) => Err(Error::Empty),
+            }
+        }
+        Some(height) => regions
+            .into_iter()
+            .map(|region| -> fj::Shape3d {
+                fj::Sweep {
+                    shape: region,
+                    length: height,
+                }
+                .into()
+            })
+            .reduce(|a, b| fj::Union { a, b }.into())
+            .map(Into::into)
+            .ok_or(Error::Empty),
+    }
+}
+
+/// An error that can occur while importing a profile
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O error while reading profile")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid SVG: {0}")]
+    Svg(String),
+
+    #[error("Invalid DXF in line {line}: {message}")]
+    Dxf { line: usize, message: String },
+
+    #[error("Outline is not closed; it ends at {0:?}")]
+    Open([f64; 2]),
+
+    #[error("Profile doesn't contain any closed outlines")]
+    Empty,
+
+    #[error(
+        "Profile consists of {0} separate regions, which can only be imported \
+        with a height"
+    )]
+    MultipleRegions(usize),
+
+    #[error("Region has {0} holes, but only one hole per region is supported")]
+    TooManyHoles(usize),
+}
+
+/// Group outlines into regions, and convert each region into a shape
+fn regions(outlines: Vec<Outline>) -> Result<Vec<fj::Shape2d>, Error> {
+    // For each outline, find the outlines that contain it. Outlines don't
+    // intersect each other, so checking one point is enough.
+    let containers: Vec<Vec<usize>> = outlines
+        .iter()
+        .enumerate()
+        .map(|(i, outline)| {
+            (0..outlines.len())
+                .filter(|&j| j != i && contains(&outlines[j], outline[0]))
+                .collect()
+        })
+        .collect();
+
+    let mut holes = vec![Vec::new(); outlines.len()];
+    for (i, containers) in containers.iter().enumerate() {
+        if containers.len() % 2 == 1 {
+            // The hole belongs to the innermost outline that contains it,
+            // which is the smallest one.
+            let parent = containers.iter().copied().min_by(|&a, &b| {
+                area(&outlines[a])
+                    .partial_cmp(&area(&outlines[b]))
+                    .expect("Area of outline is NaN")
+            });
+
+            // Can't panic. The number of containers is odd, so there is one.
+            holes[parent.unwrap()].push(i);
+        }
+    }
+
+    let mut regions = Vec::new();
+    for (i, containers) in containers.iter().enumerate() {
+        if containers.len() % 2 == 1 {
+            continue;
+        }
+
+        let outer = fj::Sketch::from_points(outlines[i].clone());
+        let region = match holes[i].as_slice() {
+            [] => outer.into(),
+            [hole] => fj::Difference2d {
+                a: outer.into(),
+                b: fj::Sketch::from_points(outlines[*hole].clone()).into(),
+            }
+            .into(),
+            holes => return Err(Error::TooManyHoles(holes.len())),
+        };
+
+        regions.push(region);
+    }
+
+    Ok(regions)
+}
+
+/// Remove points that are the same as the previous one
+///
+/// This includes a last point that repeats the first one, as the outline is
+/// closed anyway.
+fn clean(mut outline: Outline) -> Outline {
+    const EPSILON: f64 = 1e-9;
+    let same = |[ax, ay]: [f64; 2], [bx, by]: [f64; 2]| {
+        (ax - bx).abs() <= EPSILON && (ay - by).abs() <= EPSILON
+    };
+
+    outline.dedup_by(|a, b| same(*a, *b));
+    while outline.len() > 1 && same(outline[0], outline[outline.len() - 1]) {
+        outline.pop();
+    }
+
+    outline
+}
+
+/// Indicate whether a point is inside of an outline
+fn contains(outline: &[[f64; 2]], [x, y]: [f64; 2]) -> bool {
+    // Count the edges that a ray towards positive x crosses.
+    let mut inside = false;
+    for (i, &[x0, y0]) in outline.iter().enumerate() {
+        let [x1, y1] = outline[(i + 1) % outline.len()];
+
+        if (y0 > y) != (y1 > y) && x < x0 + (x1 - x0) * (y - y0) / (y1 - y0) {
+            inside = !inside;
+        }
+    }
+
+    inside
+}
+
+/// The area that an outline encloses
+fn area(outline: &[[f64; 2]]) -> f64 {
+    let twice: f64 = outline
+        .iter()
+        .zip(outline.iter().cycle().skip(1))
+        .map(|([x0, y0], [x1, y1])| x0 * y1 - x1 * y0)
+        .sum();
+
+    twice.abs() / 2.
+}
+
+/// Approximate an elliptical arc by points, and add them to an outline
+///
+/// The arc starts at the angle `start` (in radians, relative to the rotated
+/// x-axis of the ellipse), and goes on by `sweep`, which is positive for arcs
+/// that go counter-clockwise. The start point is not added.
+fn ellipse_arc(
+    center: [f64; 2],
+    [rx, ry]: [f64; 2],
+    rotation: f64,
+    start: f64,
+    sweep: f64,
+    outline: &mut Outline,
+) {
+    // The angle that each segment may span, for the curve to deviate from the
+    // segment by no more than the tolerance.
+    let radius = rx.abs().max(ry.abs());
+    let max_angle = if radius > TOLERANCE {
+        2. * (1. - TOLERANCE / radius).acos()
+    } else {
+        sweep.abs()
+    };
+    // Huge radii make `max_angle` zero, so the number of segments needs to be
+    // limited, like for Bézier curves.
+    let segments = (sweep.abs() / max_angle).ceil().clamp(1., 1000.) as usize;
+
+    let [cx, cy] = center;
+    let (sin, cos) = rotation.sin_cos();
+    outline.extend((1..=segments).map(|i| {
+        let angle = start + sweep * i as f64 / segments as f64;
+        let [x, y] = [rx * angle.cos(), ry * angle.sin()];
+        [cx + x * cos - y * sin, cy + x * sin + y * cos]
+    }));
+}
+
+/// Approximate a Bézier curve by points, and add them to an outline
+///
+/// The curve starts at the last point of the outline, which must not be empty,
+/// and has the given control points, the last of which is its end point. Only
+/// quadratic and cubic curves are supported.
+fn bezier(controls: &[[f64; 2]], outline: &mut Outline) {
+    let start = *outline.last().expect("Bézier curve has no start point");
+    let points: Vec<_> = [start]
+        .into_iter()
+        .chain(controls.iter().copied())
+        .collect();
+
+    // The curve deviates from a line segment by at most an eighth of the
+    // maximum of its second derivative, multiplied by the square of the
+    // segment's length in parameter space.
+    let degree = controls.len() as f64;
+    let max_second_difference = points
+        .windows(3)
+        .map(|w| {
+            let dx = w[0][0] - 2. * w[1][0] + w[2][0];
+            let dy = w[0][1] - 2. * w[1][1] + w[2][1];
+            dx.hypot(dy)
+        })
+        .fold(0., f64::max);
+    let max_second_derivative = degree * (degree - 1.) * max_second_difference;
+    let segments = (max_second_derivative / (8. * TOLERANCE))
+        .sqrt()
+        .ceil()
+        .clamp(1., 1000.) as usize;
+
+    outline.extend((1..=segments).map(|i| {
+        // De Casteljau's algorithm
+        let t = i as f64 / segments as f64;
+        let mut points = points.clone();
+        while points.len() > 1 {
+            points = points
+                .windows(2)
+                .map(|w| {
+                    [
+                        w[0][0] + (w[1][0] - w[0][0]) * t,
+                        w[0][1] + (w[1][1] - w[0][1]) * t,
+                    ]
+                })
+                .collect();
+        }
+        points[0]
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::{ellipse_arc, to_shape, Error};
+
+    fn square(min: f64, max: f64) -> Vec<[f64; 2]> {
+        vec![[min, min], [max, min], [max, max], [min, max], [min, min]]
+    }
+
+    #[test]
+    fn to_shape_should_turn_nested_outlines_into_holes() {
+        let shape = to_shape(vec![square(1., 2.), square(0., 3.)], None);
+
+        match shape.unwrap() {
+            fj::Shape::Shape2d(fj::Shape2d::Difference(difference)) => {
+                match (difference.a, difference.b) {
+                    (fj::Shape2d::Sketch(a), fj::Shape2d::Sketch(b)) => {
+                        // The closing point is removed.
+                        assert_eq!(a.to_points(), &square(0., 3.)[..4]);
+                        assert_eq!(b.to_points(), &square(1., 2.)[..4]);
+                    }
+                    shapes => panic!("Unexpected shapes: {:?}", shapes),
+                }
+            }
+            shape => panic!("Unexpected shape: {:?}", shape),
+        }
+    }
+
+    #[test]
+    fn to_shape_should_require_height_for_multiple_regions() {
+        // Two squares, and an island in the hole of the second one
+        let outlines = vec![
+            square(0., 1.),
+            square(2., 8.),
+            square(3., 7.),
+            square(4., 6.),
+        ];
+
+        assert!(matches!(
+            to_shape(outlines.clone(), None),
+            Err(Error::MultipleRegions(3))
+        ));
+
+        match to_shape(outlines, Some(2.)).unwrap() {
+            fj::Shape::Shape3d(fj::Shape3d::Union(_)) => {}
+            shape => panic!("Unexpected shape: {:?}", shape),
+        }
+
+        assert!(matches!(to_shape(Vec::new(), None), Err(Error::Empty)));
+    }
+
+    #[test]
+    fn ellipse_arc_should_limit_number_of_segments() {
+        for radius in [1e20, f64::INFINITY] {
+            let mut outline = Vec::new();
+            ellipse_arc([0., 0.], [radius, radius], 0., 0., TAU, &mut outline);
+            assert_eq!(outline.len(), 1000);
+        }
+    }
+}
@@ -0,0 +1,665 @@
+//! Reading of outlines from SVG files
+//!
+//! Only the geometry of the file is read, not how it is styled. The outlines
+//! of all filled shapes (`path`, `polygon`, `polyline`, `rect`, `circle`, and
+//! `ellipse`) are read, including the `transform` attributes of the shapes and
+//! of the groups that contain them. Anything within `defs` and similar
+//! elements, which isn't drawn by itself, is skipped. Open paths are closed,
+//! like SVG does when filling them. Rounded corners of rectangles are ignored.
+//!
+//! Lengths are read as millimeters, regardless of their unit. The y-axis of SVG
+//! points down, so the outlines are mirrored, for them to appear upright in
+//! the x-y plane.
+
+use std::f64::consts::TAU;
+
+use super::{bezier, ellipse_arc, Error, Outline};
+
+/// Elements whose content isn't drawn by itself
+const HIDDEN: &[&str] = &[
+    "clipPath", "defs", "marker", "mask", "metadata", "pattern", "symbol",
+];
+
+/// Parse the outlines of all shapes from an SVG file
+pub fn parse(source: &str) -> Result<Vec<Outline>, Error> {
+    let mut outlines = Vec::new();
+
+    // The transform of each open element, and whether it is hidden
+    let mut stack: Vec<(Transform, bool)> = Vec::new();
+
+    let mut rest = source;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+
+        // Comments, processing instructions, and declarations don't contain
+        // any geometry.
+        let skipped = [("<!--", "-->"), ("<?", "?>"), ("<!", ">")]
+            .into_iter()
+            .find(|(open, _)| rest.starts_with(open));
+        if let Some((open, close)) = skipped {
+            let end = rest.find(close).ok_or_else(|| {
+                Error::Svg(format!("`{}` is never closed", open))
+            })?;
+            rest = &rest[end + close.len()..];
+            continue;
+        }
+
+        let end = rest
+            .find('>')
+            .ok_or_else(|| Error::Svg("Element is never closed".to_owned()))?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        if tag.starts_with('/') {
+            stack.pop();
+            continue;
+        }
+
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let (name, attributes) = match tag.find(char::is_whitespace) {
+            Some(i) => (&tag[..i], parse_attributes(&tag[i..])?),
+            None => (tag, Vec::new()),
+        };
+        let attribute = |name: &str| {
+            attributes
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, value)| value.as_str())
+        };
+
+        let (parent, hidden) = stack
+            .last()
+            .copied()
+            .unwrap_or((Transform::IDENTITY, false));
+        let transform = match attribute("transform") {
+            Some(transform) => parent.then(&parse_transform(transform)?),
+            None => parent,
+        };
+        let hidden = hidden || HIDDEN.contains(&name);
+
+        if !self_closing {
+            stack.push((transform, hidden));
+        }
+        if hidden {
+            continue;
+        }
+
+        let length = |name| attribute(name).map(parse_length).unwrap_or(Ok(0.));
+        let shape = match name {
+            "path" => parse_path(attribute("d").unwrap_or(""))?,
+            "polygon" | "polyline" => {
+                let numbers = Numbers::new(attribute("points").unwrap_or(""))
+                    .collect::<Result<Vec<_>, _>>()?;
+                vec![numbers.chunks_exact(2).map(|p| [p[0], p[1]]).collect()]
+            }
+            "rect" => {
+                let [x, y] = [length("x")?, length("y")?];
+                let [w, h] = [length("width")?, length("height")?];
+                vec![vec![[x, y], [x + w, y], [x + w, y + h], [x, y + h]]]
+            }
+            "circle" | "ellipse" => {
+                let center = [length("cx")?, length("cy")?];
+                let radii = if name == "circle" {
+                    [length("r")?; 2]
+                } else {
+                    [length("rx")?, length("ry")?]
+                };
+
+                let mut outline = Vec::new();
+                ellipse_arc(center, radii, 0., 0., TAU, &mut outline);
+                vec![outline]
+            }
+            _ => continue,
+        };
+
+        outlines.extend(shape.into_iter().map(|outline| {
+            outline
+                .into_iter()
+                .map(|point| {
+                    let [x, y] = transform.apply(point);
+                    [x, -y]
+                })
+                .collect()
+        }));
+    }
+
+    Ok(outlines)
+}
+
+/// Parse the attributes of an element, as pairs of names and values
+fn parse_attributes(source: &str) -> Result<Vec<(&str, String)>, Error> {
+    let mut attributes = Vec::new();
+
+    let mut rest = source.trim_start();
+    while !rest.is_empty() {
+        let error = || Error::Svg(format!("Invalid attributes: `{}`", source));
+
+        let equals = rest.find('=').ok_or_else(error)?;
+        let name = rest[..equals].trim();
+        rest = rest[equals + 1..].trim_start();
+
+        let quote = rest.chars().next().filter(|&c| c == '"' || c == '\'');
+        let quote = quote.ok_or_else(error)?;
+        let end = rest[1..].find(quote).ok_or_else(error)?;
+        let value = unescape(&rest[1..end + 1]);
+        rest = rest[end + 2..].trim_start();
+
+        attributes.push((name, value));
+    }
+
+    Ok(attributes)
+}
+
+/// Replace the predefined XML entities with the characters they stand for
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Parse a length, ignoring its unit
+fn parse_length(source: &str) -> Result<f64, Error> {
+    Numbers::new(source).number()
+}
+
+/// Parse the path data from the `d` attribute of a path
+///
+/// Returns one outline per sub-path.
+fn parse_path(source: &str) -> Result<Vec<Outline>, Error> {
+    let mut outlines = Vec::new();
+    let mut outline: Outline = Vec::new();
+
+    let mut numbers = Numbers::new(source);
+    let mut current = [0., 0.];
+    let mut start = [0., 0.];
+
+    // The last control point of the previous curve, if it was of the same
+    // kind, which smooth curves reflect
+    let mut last_cubic: Option<[f64; 2]> = None;
+    let mut last_quadratic: Option<[f64; 2]> = None;
+
+    let mut command = None;
+    loop {
+        numbers.skip_separators();
+        let next = match numbers.peek() {
+            Some(c) if c.is_ascii_alphabetic() => {
+                numbers.next_char();
+                c
+            }
+            Some(_) => {
+                // Commands can be repeated implicitly. A move is followed by
+                // implicit lines.
+                match command {
+                    Some('M') => 'L',
+                    Some('m') => 'l',
+                    Some('Z' | 'z') | None => {
+                        return Err(Error::Svg(format!(
+                            "Expected command in path `{}`",
+                            source
+                        )))
+                    }
+                    Some(command) => command,
+                }
+            }
+            None => break,
+        };
+        command = Some(next);
+
+        let relative = next.is_ascii_lowercase();
+        let point = |numbers: &mut Numbers| -> Result<[f64; 2], Error> {
+            let [x, y] = [numbers.number()?, numbers.number()?];
+            Ok(if relative {
+                [current[0] + x, current[1] + y]
+            } else {
+                [x, y]
+            })
+        };
+
+        // A sub-path that was closed continues from its start point.
+        let upper = next.to_ascii_uppercase();
+        if outline.is_empty() && upper != 'M' {
+            outline.push(current);
+        }
+
+        let mut cubic = None;
+        let mut quadratic = None;
+
+        match upper {
+            'M' => {
+                let to = point(&mut numbers)?;
+                outlines.push(std::mem::take(&mut outline));
+                outline.push(to);
+                current = to;
+                start = to;
+            }
+            'L' => {
+                current = point(&mut numbers)?;
+                outline.push(current);
+            }
+            'H' => {
+                let x = numbers.number()?;
+                current[0] = if relative { current[0] + x } else { x };
+                outline.push(current);
+            }
+            'V' => {
+                let y = numbers.number()?;
+                current[1] = if relative { current[1] + y } else { y };
+                outline.push(current);
+            }
+            'C' | 'S' => {
+                let first = if upper == 'C' {
+                    point(&mut numbers)?
+                } else {
+                    reflect(last_cubic, current)
+                };
+                let second = point(&mut numbers)?;
+                let to = point(&mut numbers)?;
+
+                bezier(&[first, second, to], &mut outline);
+                cubic = Some(second);
+                current = to;
+            }
+            'Q' | 'T' => {
+                let control = if upper == 'Q' {
+                    point(&mut numbers)?
+                } else {
+                    reflect(last_quadratic, current)
+                };
+                let to = point(&mut numbers)?;
+
+                bezier(&[control, to], &mut outline);
+                quadratic = Some(control);
+                current = to;
+            }
+            'A' => {
+                let radii = [numbers.number()?.abs(), numbers.number()?.abs()];
+                let rotation = numbers.number()?.to_radians();
+                let large_arc = numbers.flag()?;
+                let sweep = numbers.flag()?;
+                let to = point(&mut numbers)?;
+
+                arc(
+                    current,
+                    radii,
+                    rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                    &mut outline,
+                );
+                current = to;
+            }
+            'Z' => {
+                outlines.push(std::mem::take(&mut outline));
+                current = start;
+            }
+            _ => {
+                return Err(Error::Svg(format!(
+                    "Unknown path command `{}`",
+                    next
+                )))
+            }
+        }
+
+        last_cubic = cubic;
+        last_quadratic = quadratic;
+    }
+
+    outlines.push(outline);
+    outlines.retain(|outline| !outline.is_empty());
+
+    Ok(outlines)
+}
+
+/// Reflect the last control point of the previous curve at the current point
+fn reflect(control: Option<[f64; 2]>, [x, y]: [f64; 2]) -> [f64; 2] {
+    match control {
+        Some([cx, cy]) => [2. * x - cx, 2. * y - cy],
+        None => [x, y],
+    }
+}
+
+/// Approximate an SVG arc by points, and add them to an outline
+///
+/// Converts the arc from the endpoint parameterization that SVG uses to a
+/// center parameterization, as described in the implementation notes of the
+/// SVG specification.
+fn arc(
+    from: [f64; 2],
+    [mut rx, mut ry]: [f64; 2],
+    rotation: f64,
+    large_arc: bool,
+    sweep: bool,
+    to: [f64; 2],
+    outline: &mut Outline,
+) {
+    if from == to {
+        return;
+    }
+    if rx == 0. || ry == 0. {
+        outline.push(to);
+        return;
+    }
+
+    let (sin, cos) = rotation.sin_cos();
+
+    // The midpoint between both endpoints, in the rotated coordinate system
+    // of the ellipse
+    let dx = (from[0] - to[0]) / 2.;
+    let dy = (from[1] - to[1]) / 2.;
+    let x1 = cos * dx + sin * dy;
+    let y1 = -sin * dx + cos * dy;
+
+    // Radii that are too small are scaled up, until the arc fits.
+    let scale = (x1 / rx).powi(2) + (y1 / ry).powi(2);
+    if scale > 1. {
+        rx *= scale.sqrt();
+        ry *= scale.sqrt();
+    }
+
+    let numerator = (rx * ry).powi(2) - (rx * y1).powi(2) - (ry * x1).powi(2);
+    let denominator = (rx * y1).powi(2) + (ry * x1).powi(2);
+    let mut factor = (numerator / denominator).max(0.).sqrt();
+    if large_arc == sweep {
+        factor = -factor;
+    }
+    let cx1 = factor * rx * y1 / ry;
+    let cy1 = -factor * ry * x1 / rx;
+
+    let center = [
+        cos * cx1 - sin * cy1 + (from[0] + to[0]) / 2.,
+        sin * cx1 + cos * cy1 + (from[1] + to[1]) / 2.,
+    ];
+
+    let angle = |x: f64, y: f64| y.atan2(x);
+    let start = angle((x1 - cx1) / rx, (y1 - cy1) / ry);
+    let end = angle((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+
+    let mut delta = (end - start) % TAU;
+    if sweep && delta < 0. {
+        delta += TAU;
+    }
+    if !sweep && delta > 0. {
+        delta -= TAU;
+    }
+
+    ellipse_arc(center, [rx, ry], rotation, start, delta, outline);
+}
+
+/// An affine transform, as the SVG matrix `[a, b, c, d, e, f]`
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Transform([f64; 6]);
+
+impl Transform {
+    const IDENTITY: Self = Self([1., 0., 0., 1., 0., 0.]);
+
+    /// Apply `other` first, then this transform
+    fn then(&self, other: &Self) -> Self {
+        let [a, b, c, d, e, f] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = other.0;
+
+        Self([
+            a * a2 + c * b2,
+            b * a2 + d * b2,
+            a * c2 + c * d2,
+            b * c2 + d * d2,
+            a * e2 + c * f2 + e,
+            b * e2 + d * f2 + f,
+        ])
+    }
+
+    fn apply(&self, [x, y]: [f64; 2]) -> [f64; 2] {
+        let [a, b, c, d, e, f] = self.0;
+        [a * x + c * y + e, b * x + d * y + f]
+    }
+}
+
+/// Parse the value of a `transform` attribute
+fn parse_transform(source: &str) -> Result<Transform, Error> {
+    let mut transform = Transform::IDENTITY;
+
+    let mut rest = source.trim();
+    while !rest.is_empty() {
+        let error = || Error::Svg(format!("Invalid transform: `{}`", source));
+
+        let open = rest.find('(').ok_or_else(error)?;
+        let close = rest.find(')').ok_or_else(error)?;
+        let name = rest[..open].trim();
+        let args = Numbers::new(&rest[open + 1..close])
+            .collect::<Result<Vec<_>, _>>()?;
+        rest = rest[close + 1..]
+            .trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+
+        let next = match (name, args.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => Transform([a, b, c, d, e, f]),
+            ("translate", &[x]) => Transform([1., 0., 0., 1., x, 0.]),
+            ("translate", &[x, y]) => Transform([1., 0., 0., 1., x, y]),
+            ("scale", &[s]) => Transform([s, 0., 0., s, 0., 0.]),
+            ("scale", &[x, y]) => Transform([x, 0., 0., y, 0., 0.]),
+            ("rotate", &[angle]) => rotation(angle),
+            ("rotate", &[angle, x, y]) => Transform([1., 0., 0., 1., x, y])
+                .then(&rotation(angle))
+                .then(&Transform([1., 0., 0., 1., -x, -y])),
+            ("skewX", &[angle]) => {
+                Transform([1., 0., angle.to_radians().tan(), 1., 0., 0.])
+            }
+            ("skewY", &[angle]) => {
+                Transform([1., angle.to_radians().tan(), 0., 1., 0., 0.])
+            }
+            _ => return Err(error()),
+        };
+
+        transform = transform.then(&next);
+    }
+
+    Ok(transform)
+}
+
+fn rotation(degrees: f64) -> Transform {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    Transform([cos, sin, -sin, cos, 0., 0.])
+}
+
+/// The numbers in path data, or another list of numbers
+///
+/// Numbers can be separated by whitespace or commas, but also just by the
+/// sign or decimal point of the next one (as in `1-2.5.5`).
+struct Numbers<'r> {
+    source: &'r str,
+    rest: &'r str,
+}
+
+impl<'r> Numbers<'r> {
+    fn new(source: &'r str) -> Self {
+        Self {
+            source,
+            rest: source,
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        self.rest = self
+            .rest
+            .trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest.chars().next()
+    }
+
+    fn next_char(&mut self) {
+        let mut chars = self.rest.chars();
+        chars.next();
+        self.rest = chars.as_str();
+    }
+
+    fn number(&mut self) -> Result<f64, Error> {
+        self.skip_separators();
+
+        let bytes = self.rest.as_bytes();
+        let mut end = 0;
+        let mut seen_point = false;
+        let mut seen_exponent = false;
+        while end < bytes.len() {
+            match bytes[end] {
+                b'0'..=b'9' => {}
+                b'+' | b'-'
+                    if end == 0 || matches!(bytes[end - 1], b'e' | b'E') => {}
+                b'.' if !seen_point && !seen_exponent => seen_point = true,
+                // An `e` can also be the start of a unit, like `em`.
+                b'e' | b'E'
+                    if end > 0
+                        && !seen_exponent
+                        && matches!(
+                            bytes.get(end + 1),
+                            Some(b'0'..=b'9' | b'+' | b'-')
+                        ) =>
+                {
+                    seen_exponent = true
+                }
+                _ => break,
+            }
+            end += 1;
+        }
+
+        // Numbers with huge exponents parse as infinity.
+        let number = self.rest[..end]
+            .parse()
+            .ok()
+            .filter(|number: &f64| number.is_finite())
+            .ok_or_else(|| {
+                Error::Svg(format!("Expected number in `{}`", self.source))
+            })?;
+        self.rest = &self.rest[end..];
+
+        Ok(number)
+    }
+
+    /// Parse a flag of an arc, which doesn't need to be separated
+    fn flag(&mut self) -> Result<bool, Error> {
+        self.skip_separators();
+
+        let flag = match self.peek() {
+            Some('0') => false,
+            Some('1') => true,
+            _ => {
+                return Err(Error::Svg(format!(
+                    "Expected flag in `{}`",
+                    self.source
+                )))
+            }
+        };
+        self.next_char();
+
+        Ok(flag)
+    }
+}
+
+impl Iterator for Numbers<'_> {
+    type Item = Result<f64, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.skip_separators();
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        Some(self.number())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, parse_length, parse_path};
+
+    fn assert_close(actual: [f64; 2], expected: [f64; 2]) {
+        let [x, y] = actual;
+        let [ex, ey] = expected;
+        assert!(
+            (x - ex).abs() < 1e-9 && (y - ey).abs() < 1e-9,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn parse_path_should_support_all_commands() {
+        let outlines =
+            parse_path("M0,0 h10 v10 H0z m2 2 l1 0 1-.5.5.5").unwrap();
+        assert_eq!(outlines.len(), 2);
+        assert_eq!(
+            outlines[0],
+            vec![[0., 0.], [10., 0.], [10., 10.], [0., 10.]]
+        );
+        assert_eq!(outlines[1], vec![[2., 2.], [3., 2.], [4., 1.5], [4.5, 2.]]);
+
+        // A half circle, as an arc with compact flags
+        let outlines = parse_path("M0 0A5 5 0 01 10 0Z").unwrap();
+        let outline = &outlines[0];
+        assert_close(outline[outline.len() - 1], [10., 0.]);
+        for &[x, y] in outline {
+            assert!(((x - 5.).hypot(y) - 5.).abs() < 1e-9);
+            assert!(y <= 1e-9);
+        }
+
+        // Curves end where they are supposed to.
+        let outlines =
+            parse_path("M0 0C0 5 10 5 10 0S20 -5 20 0Q25 5 30 0T40 0").unwrap();
+        let outline = &outlines[0];
+        assert_close(outline[outline.len() - 1], [40., 0.]);
+        assert!(outline.iter().any(|&[_, y]| y < -3.));
+
+        assert!(parse_path("0 0").is_err());
+        assert!(parse_path("M0 0 L1 1 Z 2 2").is_err());
+        assert!(parse_path("M0 0 X").is_err());
+    }
+
+    #[test]
+    fn parse_should_apply_transforms_and_skip_defs() {
+        let svg = r#"<?xml version="1.0"?>
+            <!-- A comment with <path d="M0 0 L1 1"/> in it -->
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+                <defs><rect width="5" height="5"/></defs>
+                <g transform="translate(10, 20)">
+                    <rect x="1" y="2" width="3mm" height='4' transform="scale(2)"/>
+                </g>
+                <circle cx="50" cy="50" r="10"></circle>
+            </svg>"#;
+
+        let outlines = parse(svg).unwrap();
+        assert_eq!(outlines.len(), 2);
+
+        // Mirrored, because the y-axis of SVG points down
+        assert_eq!(
+            outlines[0],
+            vec![[12., -24.], [18., -24.], [18., -32.], [12., -32.]]
+        );
+        for &[x, y] in &outlines[1] {
+            assert!(((x - 50.).hypot(y + 50.) - 10.).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn parse_length_should_ignore_units() {
+        assert_eq!(parse_length("12.5mm").unwrap(), 12.5);
+        assert_eq!(parse_length(" 3 ").unwrap(), 3.);
+        assert_eq!(parse_length("1e2px").unwrap(), 100.);
+        assert_eq!(parse_length("2em").unwrap(), 2.);
+        assert!(parse_length("mm").is_err());
+        assert!(parse_length("1e999").is_err());
+    }
+
+    #[test]
+    fn parse_should_approximate_huge_circles() {
+        let svg = r#"<svg><circle cx="0" cy="0" r="1e20"/></svg>"#;
+
+        let outlines = parse(svg).unwrap();
+        assert_eq!(outlines.len(), 1);
+        assert_eq!(outlines[0].len(), 1000);
+    }
+}
@@ -0,0 +1,1353 @@
+use std::{collections::HashMap, f64::consts::PI, fmt, fs, io, path::Path};
+
+use thiserror::Error;
+
+/// Read a shape from an OpenSCAD file
+///
+/// See [`parse`].
+pub fn read(path: impl AsRef<Path>) -> Result<fj::Shape, Error> {
+    let source = fs::read_to_string(path)?;
+    parse(&source)
+}
+
+/// Parse a shape from OpenSCAD code
+///
+/// Only a subset of OpenSCAD is supported, which is enough for many simple
+/// models:
+///
+/// - The 2-dimensional primitives `circle`, `square`, and `polygon` (without
+///   `paths`).
+/// - The 3-dimensional primitives `cube` and `cylinder` (without cones).
+/// - The transformations `translate` and `rotate`.
+/// - The operations `union`, `difference`, and `linear_extrude` (with `twist`,
+///   but without `scale`). `color` and `render` are accepted, but don't do
+///   anything.
+/// - Variables and arithmetic, including vectors.
+///
+/// The result is limited by what the fj shape types can represent. Notably,
+/// there is no 3-dimensional difference, so a 3-dimensional `difference` is
+/// only supported, if the shapes that are subtracted are extrusions that go
+/// all the way through the shape they are subtracted from (as is typical for
+/// holes). 2-dimensional shapes can't be combined using `union`, and a union
+/// of 3-dimensional shapes is an aggregation of the original shapes (see
+/// [`fj::Union`]).
+///
+/// Unlike in OpenSCAD, the special variables `$fn`, `$fa`, and `$fs` have no
+/// effect. The resolution of curves is controlled by the tolerance, like for
+/// any other model.
+pub fn parse(source: &str) -> Result<fj::Shape, Error> {
+    let tokens = tokenize(source)?;
+
+    let mut parser = Parser {
+        tokens: &tokens,
+        next: 0,
+    };
+    let statements = parser.statements()?;
+
+    // The statements only stop before the end of the file at a `}` that
+    // doesn't close any block.
+    if !matches!(parser.peek(), Token::End) {
+        return Err(parser.unexpected("statement"));
+    }
+
+    let mut scope = HashMap::new();
+    let nodes = evaluate_block(&statements, &mut scope)?;
+
+    // The top-level shapes are combined implicitly. If that fails, the end of
+    // the file is as good a place to point to as any.
+    let (_, end) = tokens[tokens.len() - 1];
+    let node = union(nodes, end)?.ok_or(Error::Empty)?;
+
+    Ok(node.into_shape())
+}
+
+/// An error that can occur while reading an OpenSCAD file
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O error while reading OpenSCAD file")]
+    Io(#[from] io::Error),
+
+    #[error("Syntax error in line {line}: {message}")]
+    Syntax { line: usize, message: String },
+
+    #[error("Unsupported feature in line {line}: {feature}")]
+    Unsupported { line: usize, feature: String },
+
+    #[error("Invalid argument in line {line}: {message}")]
+    Argument { line: usize, message: String },
+
+    #[error("Undefined variable `{name}` in line {line}")]
+    UndefinedVariable { line: usize, name: String },
+
+    #[error("OpenSCAD file doesn't define any shapes")]
+    Empty,
+}
+
+impl Error {
+    fn syntax(line: usize, message: impl Into<String>) -> Self {
+        Self::Syntax {
+            line,
+            message: message.into(),
+        }
+    }
+
+    fn unsupported(line: usize, feature: impl Into<String>) -> Self {
+        Self::Unsupported {
+            line,
+            feature: feature.into(),
+        }
+    }
+
+    fn argument(line: usize, message: impl Into<String>) -> Self {
+        Self::Argument {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    String(String),
+    Ident(String),
+    Symbol(char),
+    End,
+}
+
+/// Split OpenSCAD code into tokens, each with its line number
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, Error> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => {
+                while matches!(chars.peek(), Some(&c) if c != '\n') {
+                    chars.next();
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                let start = line;
+                chars.next();
+
+                let mut previous = None;
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == Some('*') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            previous = Some(c);
+                        }
+                        None => {
+                            return Err(Error::syntax(
+                                start,
+                                "unterminated comment",
+                            ))
+                        }
+                    }
+                }
+            }
+            '"' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => string.extend(chars.next()),
+                        Some(c) => string.push(c),
+                        None => {
+                            return Err(Error::syntax(
+                                line,
+                                "unterminated string",
+                            ))
+                        }
+                    }
+                }
+                tokens.push((Token::String(string), line));
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut number = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    let exponent_sign =
+                        (c == '-' || c == '+') && number.ends_with(['e', 'E']);
+                    if !(c.is_ascii_digit()
+                        || c == '.'
+                        || c == 'e'
+                        || c == 'E'
+                        || exponent_sign)
+                    {
+                        break;
+                    }
+                    number.push(c);
+                    chars.next();
+                }
+
+                let value = number.parse().map_err(|_| {
+                    Error::syntax(line, format!("invalid number `{}`", number))
+                })?;
+                tokens.push((Token::Number(value), line));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' || c == '$' => {
+                let mut ident = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    ident.push(c);
+                    chars.next();
+                }
+                tokens.push((Token::Ident(ident), line));
+            }
+            c => tokens.push((Token::Symbol(c), line)),
+        }
+    }
+
+    tokens.push((Token::End, line));
+    Ok(tokens)
+}
+
+#[derive(Debug)]
+enum Statement {
+    Assignment {
+        name: String,
+        value: Expr,
+        line: usize,
+    },
+    Instance(Instance),
+}
+
+#[derive(Debug)]
+struct Instance {
+    name: String,
+    arguments: Vec<(Option<String>, Expr)>,
+    children: Vec<Statement>,
+    line: usize,
+}
+
+#[derive(Debug)]
+enum Expr {
+    Number(f64),
+    String(String),
+    Variable(String),
+    Vector(Vec<Expr>),
+    Negate(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+/// Keywords of OpenSCAD language features that are not supported
+const UNSUPPORTED_KEYWORDS: &[&str] = &[
+    "module", "function", "include", "use", "if", "else", "for", "let", "each",
+    "assert", "echo", "children",
+];
+
+struct Parser<'r> {
+    tokens: &'r [(Token, usize)],
+    next: usize,
+}
+
+impl<'r> Parser<'r> {
+    fn peek(&self) -> &'r Token {
+        &self.tokens[self.next].0
+    }
+
+    fn peek_second(&self) -> Option<&'r Token> {
+        self.tokens.get(self.next + 1).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens[self.next].1
+    }
+
+    fn advance(&mut self) -> &'r Token {
+        let token = &self.tokens[self.next].0;
+
+        // The last token is the end marker, which stays where it is.
+        if self.next < self.tokens.len() - 1 {
+            self.next += 1;
+        }
+
+        token
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == &Token::Symbol(symbol) {
+            self.advance();
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), Error> {
+        if !self.eat(symbol) {
+            return Err(self.unexpected(&format!("`{}`", symbol)));
+        }
+        Ok(())
+    }
+
+    fn unexpected(&self, expected: &str) -> Error {
+        unexpected(self.line(), expected, self.peek())
+    }
+
+    /// Parse statements, until the end of the file or the current block
+    fn statements(&mut self) -> Result<Vec<Statement>, Error> {
+        let mut statements = Vec::new();
+
+        while !matches!(self.peek(), Token::End | Token::Symbol('}')) {
+            if let Some(statement) = self.statement()? {
+                statements.push(statement);
+            }
+        }
+
+        Ok(statements)
+    }
+
+    fn statement(&mut self) -> Result<Option<Statement>, Error> {
+        let line = self.line();
+
+        match self.advance() {
+            Token::Symbol(';') => Ok(None),
+            Token::Symbol('{') => {
+                let children = self.statements()?;
+                self.expect('}')?;
+
+                Ok(Some(Statement::Instance(Instance {
+                    name: "union".to_owned(),
+                    arguments: Vec::new(),
+                    children,
+                    line,
+                })))
+            }
+
+            // The debug and root modifiers only affect how OpenSCAD displays
+            // the shape, while the background and disable modifiers remove
+            // their subtree from the shape.
+            Token::Symbol('#' | '!') => self.statement(),
+            Token::Symbol('%' | '*') => {
+                self.statement()?;
+                Ok(None)
+            }
+
+            Token::Ident(name) => {
+                if UNSUPPORTED_KEYWORDS.contains(&name.as_str()) {
+                    return Err(Error::unsupported(
+                        line,
+                        format!("`{}`", name),
+                    ));
+                }
+
+                if self.eat('=') {
+                    let value = self.expr()?;
+                    self.expect(';')?;
+
+                    return Ok(Some(Statement::Assignment {
+                        name: name.clone(),
+                        value,
+                        line,
+                    }));
+                }
+
+                self.expect('(')?;
+                let arguments = self.arguments()?;
+
+                let children = if self.eat(';') {
+                    Vec::new()
+                } else if self.eat('{') {
+                    let children = self.statements()?;
+                    self.expect('}')?;
+                    children
+                } else {
+                    self.statement()?.into_iter().collect()
+                };
+
+                Ok(Some(Statement::Instance(Instance {
+                    name: name.clone(),
+                    arguments,
+                    children,
+                    line,
+                })))
+            }
+            token => Err(unexpected(line, "statement", token)),
+        }
+    }
+
+    /// Parse the arguments of a module instance, after the opening parenthesis
+    fn arguments(&mut self) -> Result<Vec<(Option<String>, Expr)>, Error> {
+        let mut arguments = Vec::new();
+
+        while !self.eat(')') {
+            let name = match (self.peek(), self.peek_second()) {
+                (Token::Ident(name), Some(Token::Symbol('='))) => {
+                    self.advance();
+                    self.advance();
+                    Some(name.clone())
+                }
+                _ => None,
+            };
+            arguments.push((name, self.expr()?));
+
+            if !self.eat(',') {
+                self.expect(')')?;
+                break;
+            }
+        }
+
+        Ok(arguments)
+    }
+
+    fn expr(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.term()?;
+
+        while let Token::Symbol(op @ ('+' | '-')) = *self.peek() {
+            self.advance();
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.term()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.unary()?;
+
+        while let Token::Symbol(op @ ('*' | '/' | '%')) = *self.peek() {
+            self.advance();
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, Error> {
+        if self.eat('-') {
+            return Ok(Expr::Negate(Box::new(self.unary()?)));
+        }
+        if self.eat('+') {
+            return self.unary();
+        }
+
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, Error> {
+        let expr = match self.peek() {
+            Token::Number(number) => Expr::Number(*number),
+            Token::String(string) => Expr::String(string.clone()),
+            Token::Ident(name) => Expr::Variable(name.clone()),
+            Token::Symbol('(') => {
+                self.advance();
+                let expr = self.expr()?;
+                self.expect(')')?;
+                return Ok(expr);
+            }
+            Token::Symbol('[') => {
+                self.advance();
+
+                let mut elements = Vec::new();
+                while !self.eat(']') {
+                    elements.push(self.expr()?);
+
+                    if !self.eat(',') {
+                        self.expect(']')?;
+                        break;
+                    }
+                }
+
+                return Ok(Expr::Vector(elements));
+            }
+            _ => return Err(self.unexpected("expression")),
+        };
+
+        self.advance();
+        Ok(expr)
+    }
+}
+
+fn unexpected(line: usize, expected: &str, found: &Token) -> Error {
+    let found = match found {
+        Token::Number(number) => format!("`{}`", number),
+        Token::String(string) => format!("`\"{}\"`", string),
+        Token::Ident(ident) => format!("`{}`", ident),
+        Token::Symbol(symbol) => format!("`{}`", symbol),
+        Token::End => "end of file".to_owned(),
+    };
+    Error::syntax(line, format!("expected {}, found {}", expected, found))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Number(f64),
+    Bool(bool),
+    String(String),
+    Vector(Vec<Value>),
+    Undef,
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "{}", number),
+            Self::Bool(value) => write!(f, "{}", value),
+            Self::String(string) => write!(f, "\"{}\"", string),
+            Self::Vector(elements) => {
+                write!(f, "[")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
+            Self::Undef => write!(f, "undef"),
+        }
+    }
+}
+
+type Scope = HashMap<String, Value>;
+
+fn evaluate(expr: &Expr, scope: &Scope, line: usize) -> Result<Value, Error> {
+    let value = match expr {
+        Expr::Number(number) => Value::Number(*number),
+        Expr::String(string) => Value::String(string.clone()),
+        Expr::Variable(name) => match (scope.get(name), name.as_str()) {
+            (Some(value), _) => value.clone(),
+            (None, "PI") => Value::Number(PI),
+            (None, "true") => Value::Bool(true),
+            (None, "false") => Value::Bool(false),
+            (None, "undef") => Value::Undef,
+
+            // OpenSCAD sets these, even if the user doesn't.
+            (None, "$fn" | "$fa" | "$fs") => Value::Number(0.),
+
+            (None, _) => {
+                return Err(Error::UndefinedVariable {
+                    line,
+                    name: name.clone(),
+                })
+            }
+        },
+        Expr::Vector(elements) => Value::Vector(
+            elements
+                .iter()
+                .map(|element| evaluate(element, scope, line))
+                .collect::<Result<_, _>>()?,
+        ),
+        Expr::Negate(expr) => {
+            let value = evaluate(expr, scope, line)?;
+            arithmetic('*', Value::Number(-1.), value, line)?
+        }
+        Expr::Binary(op, a, b) => {
+            let a = evaluate(a, scope, line)?;
+            let b = evaluate(b, scope, line)?;
+            arithmetic(*op, a, b, line)?
+        }
+    };
+
+    Ok(value)
+}
+
+fn arithmetic(
+    op: char,
+    a: Value,
+    b: Value,
+    line: usize,
+) -> Result<Value, Error> {
+    let value = match (op, a, b) {
+        (op, Value::Number(a), Value::Number(b)) => Value::Number(match op {
+            '+' => a + b,
+            '-' => a - b,
+            '*' => a * b,
+            '/' => a / b,
+            _ => a % b,
+        }),
+        ('+' | '-', Value::Vector(a), Value::Vector(b))
+            if a.len() == b.len() =>
+        {
+            Value::Vector(
+                a.into_iter()
+                    .zip(b)
+                    .map(|(a, b)| arithmetic(op, a, b, line))
+                    .collect::<Result<_, _>>()?,
+            )
+        }
+        ('*' | '/', Value::Vector(a), b @ Value::Number(_)) => Value::Vector(
+            a.into_iter()
+                .map(|a| arithmetic(op, a, b.clone(), line))
+                .collect::<Result<_, _>>()?,
+        ),
+        ('*', a @ Value::Number(_), Value::Vector(b)) => Value::Vector(
+            b.into_iter()
+                .map(|b| arithmetic(op, a.clone(), b, line))
+                .collect::<Result<_, _>>()?,
+        ),
+        (op, a, b) => {
+            return Err(Error::unsupported(
+                line,
+                format!("`{}` with operands `{}` and `{}`", op, a, b),
+            ))
+        }
+    };
+
+    Ok(value)
+}
+
+/// A shape, while it's being built from OpenSCAD code
+enum Node {
+    /// A 2-dimensional shape
+    Flat(fj::Shape2d),
+
+    /// An extrusion of a 2-dimensional shape along the z-axis
+    ///
+    /// This is kept separate from other 3-dimensional shapes, as it can still
+    /// be rotated around the z-axis, or used to cut holes, by operating on the
+    /// 2-dimensional shape.
+    Prism {
+        shape: fj::Shape2d,
+        bottom: f64,
+        height: f64,
+    },
+
+    /// Any other 3-dimensional shape
+    Solid(fj::Shape3d),
+}
+
+impl Node {
+    fn into_solid(self, line: usize) -> Result<fj::Shape3d, Error> {
+        match self {
+            Self::Flat(_) => Err(Error::unsupported(
+                line,
+                "combining 2-dimensional with 3-dimensional shapes",
+            )),
+            Self::Prism {
+                shape,
+                bottom,
+                height,
+            } => {
+                let sweep = fj::Sweep {
+                    shape,
+                    length: height,
+                };
+                Ok(translate_solid(sweep.into(), [0., 0., bottom]))
+            }
+            Self::Solid(shape) => Ok(shape),
+        }
+    }
+
+    fn into_shape(self) -> fj::Shape {
+        match self {
+            Self::Flat(shape) => fj::Shape::Shape2d(shape),
+            // Can't fail, as only flat nodes can't be converted into solids.
+            node => fj::Shape::Shape3d(node.into_solid(0).unwrap()),
+        }
+    }
+}
+
+/// Evaluate a block of statements, resulting in one node per instance
+///
+/// All assignments in the block are evaluated first, so like in OpenSCAD, a
+/// variable has the same value throughout the block.
+fn evaluate_block(
+    statements: &[Statement],
+    scope: &mut Scope,
+) -> Result<Vec<Node>, Error> {
+    for statement in statements {
+        if let Statement::Assignment { name, value, line } = statement {
+            let value = evaluate(value, scope, *line)?;
+            scope.insert(name.clone(), value);
+        }
+    }
+
+    let mut nodes = Vec::new();
+    for statement in statements {
+        if let Statement::Instance(instance) = statement {
+            nodes.extend(instantiate(instance, scope)?);
+        }
+    }
+
+    Ok(nodes)
+}
+
+fn instantiate(
+    instance: &Instance,
+    scope: &Scope,
+) -> Result<Option<Node>, Error> {
+    let line = instance.line;
+    let args = Arguments::new(instance, scope)?;
+
+    let children = || {
+        let mut scope = scope.clone();
+        evaluate_block(&instance.children, &mut scope)
+    };
+
+    let node = match instance.name.as_str() {
+        "circle" => {
+            let radius = args.radius("r", "d", Some(0))?.unwrap_or(1.);
+            Node::Flat(fj::Shape2d::Circle(fj::Circle {
+                center: [0., 0.],
+                radius,
+            }))
+        }
+        "square" => {
+            let [x, y] = args.size::<2>(0)?;
+            let center = args.boolean("center", Some(1))?;
+            Node::Flat(rectangle([x, y], center))
+        }
+        "polygon" => {
+            if args.get("paths", Some(1)).is_some() {
+                return Err(Error::unsupported(line, "polygon with `paths`"));
+            }
+
+            let points = match args.get("points", Some(0)) {
+                Some(Value::Vector(points)) => points
+                    .iter()
+                    .map(|point| args.coordinates::<2>(point, "points"))
+                    .collect::<Result<_, _>>()?,
+                _ => return Err(args.error("`points` must be a vector")),
+            };
+            Node::Flat(fj::Shape2d::Sketch(fj::Sketch::from_points(points)))
+        }
+        "cube" => {
+            let [x, y, z] = args.size::<3>(0)?;
+            let center = args.boolean("center", Some(1))?;
+
+            Node::Prism {
+                shape: rectangle([x, y], center),
+                bottom: if center { -z / 2. } else { 0. },
+                height: z,
+            }
+        }
+        "cylinder" => {
+            let height = args.number("h", Some(0))?.unwrap_or(1.);
+            let r = args.radius("r", "d", None)?;
+            let r1 = args.radius("r1", "d1", Some(1))?.or(r).unwrap_or(1.);
+            let r2 = args.radius("r2", "d2", Some(2))?.or(r).unwrap_or(1.);
+            let center = args.boolean("center", Some(3))?;
+
+            if r1 != r2 {
+                return Err(Error::unsupported(line, "cones"));
+            }
+
+            Node::Prism {
+                shape: fj::Shape2d::Circle(fj::Circle {
+                    center: [0., 0.],
+                    radius: r1,
+                }),
+                bottom: if center { -height / 2. } else { 0. },
+                height,
+            }
+        }
+        "translate" => {
+            let offset = match args.get("v", Some(0)) {
+                Some(value) => args.coordinates::<3>(value, "v")?,
+                None => [0.; 3],
+            };
+
+            match union(children()?, line)? {
+                Some(node) => translate(node, offset, line)?,
+                None => return Ok(None),
+            }
+        }
+        "rotate" => {
+            let angles = args.get("a", Some(0)).unwrap_or(&Value::Undef);
+            let axis = args.get("v", Some(1));
+
+            let rotations = match (angles, axis) {
+                (Value::Number(angle), Some(axis)) => {
+                    vec![(args.coordinates::<3>(axis, "v")?, *angle)]
+                }
+                (Value::Number(angle), None) => vec![([0., 0., 1.], *angle)],
+                (angles @ Value::Vector(_), _) => {
+                    let [x, y, z] = args.coordinates::<3>(angles, "a")?;
+                    vec![
+                        ([1., 0., 0.], x),
+                        ([0., 1., 0.], y),
+                        ([0., 0., 1.], z),
+                    ]
+                }
+                (Value::Undef, _) => Vec::new(),
+                _ => return Err(args.error("`a` must be a number or a vector")),
+            };
+
+            let mut node = match union(children()?, line)? {
+                Some(node) => node,
+                None => return Ok(None),
+            };
+            for (axis, angle) in rotations {
+                if angle % 360. != 0. {
+                    node = rotate(node, axis, angle, line)?;
+                }
+            }
+            node
+        }
+        "linear_extrude" => {
+            let height = args.number("height", Some(0))?.unwrap_or(100.);
+            let center = args.boolean("center", Some(1))?;
+            let twist = args.number("twist", Some(3))?.unwrap_or(0.);
+
+            let unscaled = match args.get("scale", Some(5)) {
+                Some(Value::Number(scale)) => *scale == 1.,
+                Some(Value::Vector(scale)) => {
+                    scale.iter().all(|scale| *scale == Value::Number(1.))
+                }
+                _ => true,
+            };
+            if !unscaled {
+                return Err(Error::unsupported(
+                    line,
+                    "`linear_extrude` with `scale`",
+                ));
+            }
+
+            let shape = match union(children()?, line)? {
+                Some(Node::Flat(shape)) => shape,
+                Some(_) => {
+                    return Err(args.error("can only extrude 2D shapes"));
+                }
+                None => return Ok(None),
+            };
+            let bottom = if center { -height / 2. } else { 0. };
+
+            if twist == 0. {
+                Node::Prism {
+                    shape,
+                    bottom,
+                    height,
+                }
+            } else {
+                // OpenSCAD twists clockwise, when looking down on the shape.
+                let deform = fj::Deform {
+                    shape: fj::Sweep {
+                        shape,
+                        length: height,
+                    }
+                    .into(),
+                    deformation: fj::Deformation::Twist {
+                        rate: -twist.to_radians() / height,
+                    },
+                };
+                Node::Solid(translate_solid(deform.into(), [0., 0., bottom]))
+            }
+        }
+        "union" | "color" | "render" => match union(children()?, line)? {
+            Some(node) => node,
+            None => return Ok(None),
+        },
+        "difference" => {
+            let mut children = children()?.into_iter();
+            match children.next() {
+                Some(node) => difference(node, children, line)?,
+                None => return Ok(None),
+            }
+        }
+        name => {
+            return Err(Error::unsupported(line, format!("module `{}`", name)))
+        }
+    };
+
+    Ok(Some(node))
+}
+
+/// The arguments of a module instance
+struct Arguments<'r> {
+    instance: &'r Instance,
+    positional: Vec<Value>,
+    named: HashMap<String, Value>,
+}
+
+impl<'r> Arguments<'r> {
+    fn new(instance: &'r Instance, scope: &Scope) -> Result<Self, Error> {
+        let mut positional = Vec::new();
+        let mut named = HashMap::new();
+
+        for (name, expr) in &instance.arguments {
+            let value = evaluate(expr, scope, instance.line)?;
+            match name {
+                Some(name) => {
+                    named.insert(name.clone(), value);
+                }
+                None => positional.push(value),
+            }
+        }
+
+        Ok(Self {
+            instance,
+            positional,
+            named,
+        })
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::argument(
+            self.instance.line,
+            format!("{} (in `{}`)", message, self.instance.name),
+        )
+    }
+
+    /// Access an argument by name, or by its position, if it has one
+    ///
+    /// Arguments that are `undef` are treated as missing.
+    fn get(&self, name: &str, position: Option<usize>) -> Option<&Value> {
+        self.named
+            .get(name)
+            .or_else(|| position.and_then(|i| self.positional.get(i)))
+            .filter(|value| **value != Value::Undef)
+    }
+
+    fn number(
+        &self,
+        name: &str,
+        position: Option<usize>,
+    ) -> Result<Option<f64>, Error> {
+        match self.get(name, position) {
+            Some(Value::Number(number)) => Ok(Some(*number)),
+            Some(_) => Err(self.error(&format!("`{}` must be a number", name))),
+            None => Ok(None),
+        }
+    }
+
+    fn boolean(
+        &self,
+        name: &str,
+        position: Option<usize>,
+    ) -> Result<bool, Error> {
+        match self.get(name, position) {
+            Some(Value::Bool(value)) => Ok(*value),
+            Some(_) => {
+                Err(self.error(&format!("`{}` must be a boolean", name)))
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Access a radius, which can also be defined by a diameter
+    fn radius(
+        &self,
+        radius: &str,
+        diameter: &str,
+        position: Option<usize>,
+    ) -> Result<Option<f64>, Error> {
+        if let Some(diameter) = self.number(diameter, None)? {
+            return Ok(Some(diameter / 2.));
+        }
+        self.number(radius, position)
+    }
+
+    /// Access the `size` argument, which can be a number or a vector
+    fn size<const D: usize>(&self, position: usize) -> Result<[f64; D], Error> {
+        match self.get("size", Some(position)) {
+            Some(Value::Number(size)) => Ok([*size; D]),
+            Some(value) => self.coordinates(value, "size"),
+            None => Ok([1.; D]),
+        }
+    }
+
+    /// Convert a value into coordinates
+    ///
+    /// Like OpenSCAD, this accepts vectors with fewer coordinates than needed
+    /// (filling the missing ones with zero), or more (ignoring the rest).
+    fn coordinates<const D: usize>(
+        &self,
+        value: &Value,
+        name: &str,
+    ) -> Result<[f64; D], Error> {
+        let invalid = || self.error(&format!("`{}` must be a vector", name));
+
+        let elements = match value {
+            Value::Vector(elements) => elements,
+            _ => return Err(invalid()),
+        };
+
+        let mut coordinates = [0.; D];
+        for (coordinate, element) in coordinates.iter_mut().zip(elements) {
+            match element {
+                Value::Number(number) => *coordinate = *number,
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(coordinates)
+    }
+}
+
+fn rectangle([x, y]: [f64; 2], center: bool) -> fj::Shape2d {
+    let [u, v] = if center { [-x / 2., -y / 2.] } else { [0., 0.] };
+    fj::Shape2d::Sketch(fj::Sketch::from_points(vec![
+        [u, v],
+        [u + x, v],
+        [u + x, v + y],
+        [u, v + y],
+    ]))
+}
+
+/// Combine nodes into one
+///
+/// Returns `None`, if there are no nodes.
+fn union(nodes: Vec<Node>, line: usize) -> Result<Option<Node>, Error> {
+    let mut nodes = nodes.into_iter();
+
+    let first = match nodes.next() {
+        Some(node) => node,
+        None => return Ok(None),
+    };
+    let second = match nodes.next() {
+        Some(node) => node,
+        None => return Ok(Some(first)),
+    };
+
+    if matches!(first, Node::Flat(_)) || matches!(second, Node::Flat(_)) {
+        return Err(Error::unsupported(line, "union of 2-dimensional shapes"));
+    }
+
+    let mut union = fj::Union {
+        a: first.into_solid(line)?,
+        b: second.into_solid(line)?,
+    };
+    for node in nodes {
+        union = fj::Union {
+            a: union.into(),
+            b: node.into_solid(line)?,
+        };
+    }
+
+    Ok(Some(Node::Solid(union.into())))
+}
+
+fn difference(
+    node: Node,
+    subtrahends: impl IntoIterator<Item = Node>,
+    line: usize,
+) -> Result<Node, Error> {
+    let (mut shape, extent) = match node {
+        Node::Flat(shape) => (shape, None),
+        Node::Prism {
+            shape,
+            bottom,
+            height,
+        } => (shape, Some((bottom, height))),
+        Node::Solid(_) => {
+            return Err(Error::unsupported(
+                line,
+                "difference of 3-dimensional shapes, other than extrusions",
+            ))
+        }
+    };
+
+    for subtrahend in subtrahends {
+        let b = match (subtrahend, extent) {
+            (Node::Flat(b), None) => b,
+            (
+                Node::Prism {
+                    shape: b,
+                    bottom: b_bottom,
+                    height: b_height,
+                },
+                Some((bottom, height)),
+            ) if b_bottom <= bottom
+                && b_bottom + b_height >= bottom + height =>
+            {
+                b
+            }
+            (_, None) => return Err(Error::unsupported(
+                line,
+                "subtracting a 3-dimensional shape from a 2-dimensional one",
+            )),
+            (_, Some(_)) => {
+                return Err(Error::unsupported(
+                    line,
+                    "difference with a shape that doesn't go all the way \
+                    through",
+                ))
+            }
+        };
+
+        shape =
+            fj::Shape2d::Difference(Box::new(fj::Difference2d { a: shape, b }));
+    }
+
+    Ok(match extent {
+        None => Node::Flat(shape),
+        Some((bottom, height)) => Node::Prism {
+            shape,
+            bottom,
+            height,
+        },
+    })
+}
+
+fn translate(node: Node, offset: [f64; 3], line: usize) -> Result<Node, Error> {
+    let [x, y, z] = offset;
+
+    let node = match node {
+        Node::Flat(shape) => {
+            Node::Flat(transform_flat(shape, 0., [x, y], line)?)
+        }
+        Node::Prism {
+            shape,
+            bottom,
+            height,
+        } => Node::Prism {
+            shape: transform_flat(shape, 0., [x, y], line)?,
+            bottom: bottom + z,
+            height,
+        },
+        Node::Solid(shape) => Node::Solid(translate_solid(shape, offset)),
+    };
+
+    Ok(node)
+}
+
+fn translate_solid(shape: fj::Shape3d, offset: [f64; 3]) -> fj::Shape3d {
+    if offset == [0.; 3] {
+        return shape;
+    }
+
+    // A transform translates after rotating, so the translation can be merged
+    // into an existing one.
+    if let fj::Shape3d::Transform(mut transform) = shape {
+        for (a, b) in transform.offset.iter_mut().zip(offset) {
+            *a += b;
+        }
+        return fj::Shape3d::Transform(transform);
+    }
+
+    fj::Shape3d::Transform(Box::new(fj::Transform {
+        shape,
+        axis: [0., 0., 1.],
+        angle: 0.,
+        offset,
+    }))
+}
+
+/// Rotate a node around an axis through the origin
+///
+/// The angle is in degrees, as in OpenSCAD.
+fn rotate(
+    node: Node,
+    axis: [f64; 3],
+    angle: f64,
+    line: usize,
+) -> Result<Node, Error> {
+    // Rotations around the z-axis can be applied to 2-dimensional shapes.
+    let angle_around_z = match axis {
+        [x, y, z] if x == 0. && y == 0. && z != 0. => Some(angle * z.signum()),
+        _ => None,
+    };
+
+    let node = match (node, angle_around_z) {
+        (Node::Flat(shape), Some(angle)) => {
+            Node::Flat(transform_flat(shape, angle, [0., 0.], line)?)
+        }
+        (
+            Node::Prism {
+                shape,
+                bottom,
+                height,
+            },
+            Some(angle),
+        ) => Node::Prism {
+            shape: transform_flat(shape, angle, [0., 0.], line)?,
+            bottom,
+            height,
+        },
+        (Node::Flat(_), None) => {
+            return Err(Error::unsupported(
+                line,
+                "rotating 2-dimensional shapes out of the x-y plane",
+            ))
+        }
+        (node, _) => {
+            let shape = node.into_solid(line)?;
+
+            // A transform rotates before translating, so it can't be merged
+            // with an existing one.
+            Node::Solid(fj::Shape3d::Transform(Box::new(fj::Transform {
+                shape,
+                axis,
+                angle: angle.to_radians(),
+                offset: [0.; 3],
+            })))
+        }
+    };
+
+    Ok(node)
+}
+
+/// Rotate a 2-dimensional shape around the origin, then translate it
+///
+/// The angle is in degrees, as in OpenSCAD.
+fn transform_flat(
+    shape: fj::Shape2d,
+    angle: f64,
+    offset: [f64; 2],
+    line: usize,
+) -> Result<fj::Shape2d, Error> {
+    // Rotations by multiples of 90 degrees are common, and computing them
+    // using `sin_cos` would leave tiny errors in the coordinates.
+    let quarters = angle / 90.;
+    let (sin, cos) = if quarters == quarters.round() {
+        let sin_cos = [(0., 1.), (1., 0.), (0., -1.), (-1., 0.)];
+        sin_cos[quarters.rem_euclid(4.) as usize]
+    } else {
+        angle.to_radians().sin_cos()
+    };
+
+    let transform_point = |[x, y]: [f64; 2]| {
+        [x * cos - y * sin + offset[0], x * sin + y * cos + offset[1]]
+    };
+
+    let shape = match shape {
+        fj::Shape2d::Circle(circle) => fj::Shape2d::Circle(fj::Circle {
+            center: transform_point(circle.center),
+            ..circle
+        }),
+        fj::Shape2d::Difference(difference) => {
+            let fj::Difference2d { a, b } = *difference;
+            fj::Shape2d::Difference(Box::new(fj::Difference2d {
+                a: transform_flat(a, angle, offset, line)?,
+                b: transform_flat(b, angle, offset, line)?,
+            }))
+        }
+        fj::Shape2d::Ellipse(ellipse) => {
+            // The axes of an ellipse are aligned with the coordinate axes, so
+            // it can only be rotated by multiples of 90 degrees.
+            if quarters != quarters.round() {
+                return Err(Error::unsupported(line, "rotating ellipses"));
+            }
+
+            let [x, y] = ellipse.radii;
+            let radii = if quarters % 2. == 0. { [x, y] } else { [y, x] };
+
+            fj::Shape2d::Ellipse(fj::Ellipse {
+                center: transform_point(ellipse.center),
+                radii,
+            })
+        }
+        fj::Shape2d::Sketch(sketch) => {
+            let points = sketch
+                .to_points()
+                .into_iter()
+                .map(transform_point)
+                .collect();
+            fj::Shape2d::Sketch(
+                fj::Sketch::from_points(points).with_tag(sketch.tag()),
+            )
+        }
+    };
+
+    Ok(shape)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Error};
+
+    #[test]
+    fn parse_spacer() {
+        let shape = parse(
+            "
+            // A spacer, like the one in `models/spacer`.
+            outer = 1.0;
+            inner = outer / 2;
+
+            difference() {
+                cylinder(h = 1, r = outer, $fn = 64);
+                translate([0, 0, -0.1]) cylinder(h = 1.2, d = 2 * inner);
+            }
+            ",
+        )
+        .unwrap();
+
+        let sweep = match shape {
+            fj::Shape::Shape3d(fj::Shape3d::Sweep(sweep)) => sweep,
+            shape => panic!("Unexpected shape: {:?}", shape),
+        };
+        assert_eq!(sweep.length, 1.);
+
+        match sweep.shape {
+            fj::Shape2d::Difference(difference) => {
+                match (difference.a, difference.b) {
+                    (fj::Shape2d::Circle(a), fj::Shape2d::Circle(b)) => {
+                        assert_eq!(a.radius, 1.);
+                        assert_eq!(b.radius, 0.5);
+                    }
+                    shapes => panic!("Unexpected shapes: {:?}", shapes),
+                }
+            }
+            shape => panic!("Unexpected shape: {:?}", shape),
+        }
+    }
+
+    #[test]
+    fn parse_transforms() {
+        let shape = parse(
+            "
+            /* Rotations around the z-axis are applied to the sketch, other
+               transforms result in a transformed shape. */
+            translate([1, 2, 3])
+                rotate([90, 0, 0])
+                    linear_extrude(height = 2, center = true)
+                        rotate(90) square([2, 1]);
+            ",
+        )
+        .unwrap();
+
+        let transform = match shape {
+            fj::Shape::Shape3d(fj::Shape3d::Transform(transform)) => transform,
+            shape => panic!("Unexpected shape: {:?}", shape),
+        };
+        assert_eq!(transform.axis, [1., 0., 0.]);
+        assert_eq!(transform.angle, 90f64.to_radians());
+        assert_eq!(transform.offset, [1., 2., 3.]);
+
+        let sketch = match transform.shape {
+            fj::Shape3d::Transform(transform) => {
+                assert_eq!(transform.offset, [0., 0., -1.]);
+
+                match transform.shape {
+                    fj::Shape3d::Sweep(fj::Sweep {
+                        shape: fj::Shape2d::Sketch(sketch),
+                        length,
+                    }) => {
+                        assert_eq!(length, 2.);
+                        sketch
+                    }
+                    shape => panic!("Unexpected shape: {:?}", shape),
+                }
+            }
+            shape => panic!("Unexpected shape: {:?}", shape),
+        };
+        assert_eq!(
+            sketch.to_points(),
+            [[0., 0.], [0., 2.], [-1., 2.], [-1., 0.]]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(
+            parse("cube(1);\ncube(2 3);"),
+            Err(Error::Syntax { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse("cube(1);\nsphere(2);"),
+            Err(Error::Unsupported { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse("module foo() {}"),
+            Err(Error::Unsupported { line: 1, .. })
+        ));
+        assert!(matches!(
+            parse("cube(size);"),
+            Err(Error::UndefinedVariable { line: 1, .. })
+        ));
+        assert!(matches!(parse("// nothing"), Err(Error::Empty)));
+        assert!(matches!(
+            parse("cube(1);\n}\ncube(2);"),
+            Err(Error::Syntax { line: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,158 @@
+use crate::math::{fit, Point, Scalar};
+
+/// Simplify all sketches of an imported shape
+///
+/// Files that were exported from other applications often describe curves as
+/// polylines with far more points than needed. Each of those points becomes
+/// a vertex and an edge in the kernel, which makes the shape slow to process.
+/// This removes points that are within `tolerance` of the simplified outline
+/// (see [`fit::simplify_closed`]). Open outlines are simplified as polylines
+/// instead (see [`fit::simplify`]), and stay open.
+///
+/// Returns the number of points that were removed.
+pub fn simplify(shape: &mut fj::Shape, tolerance: f64) -> usize {
+    let tolerance = Scalar::from_f64(tolerance);
+
+    match shape {
+        fj::Shape::Shape2d(shape) => simplify_2d(shape, tolerance),
+        fj::Shape::Shape3d(shape) => simplify_3d(shape, tolerance),
+    }
+}
+
+fn simplify_2d(shape: &mut fj::Shape2d, tolerance: Scalar) -> usize {
+    match shape {
+        fj::Shape2d::Circle(_) | fj::Shape2d::Ellipse(_) => 0,
+        fj::Shape2d::Difference(difference) => {
+            simplify_2d(&mut difference.a, tolerance)
+                + simplify_2d(&mut difference.b, tolerance)
+        }
+        fj::Shape2d::Sketch(sketch) => {
+            let points: Vec<_> =
+                sketch.to_points().into_iter().map(Point::from).collect();
+            let simplified = if sketch.is_open() {
+                fit::simplify(&points, tolerance)
+            } else {
+                fit::simplify_closed(&points, tolerance)
+            };
+
+            let removed = points.len() - simplified.len();
+            if removed > 0 {
+                let points = simplified
+                    .into_iter()
+                    .map(|point| {
+                        point.coords.components().map(Scalar::into_f64)
+                    })
+                    .collect();
+
+                // The end points of an open outline are kept, and they were
+                // never identical. That means it's still open after this.
+                let simplified = if sketch.is_open() {
+                    fj::Sketch::from_path(points, 0.)
+                } else {
+                    fj::Sketch::from_points(points)
+                };
+                *sketch = simplified.with_tag(sketch.tag());
+            }
+
+            removed
+        }
+    }
+}
+
+fn simplify_3d(shape: &mut fj::Shape3d, tolerance: Scalar) -> usize {
+    match shape {
+        fj::Shape3d::Sweep(sweep) => simplify_2d(&mut sweep.shape, tolerance),
+        fj::Shape3d::Transform(transform) => {
+            simplify_3d(&mut transform.shape, tolerance)
+        }
+        fj::Shape3d::Union(union) => {
+            simplify_3d(&mut union.a, tolerance)
+                + simplify_3d(&mut union.b, tolerance)
+        }
+        fj::Shape3d::Deform(deform) => {
+            simplify_3d(&mut deform.shape, tolerance)
+        }
+        fj::Shape3d::Anchor(anchor) => {
+            simplify_3d(&mut anchor.shape, tolerance)
+        }
+        fj::Shape3d::Material(material) => {
+            simplify_3d(&mut material.shape, tolerance)
+        }
+        fj::Shape3d::Texture(texture) => {
+            simplify_3d(&mut texture.shape, tolerance)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::simplify;
+
+    #[test]
+    fn simplify_sweep() {
+        // A circle with many points, as exported by some applications, and a
+        // square with redundant points.
+        let circle = (0..360)
+            .map(|i| {
+                let angle = (i as f64).to_radians();
+                [angle.cos() * 10., angle.sin() * 10.]
+            })
+            .collect();
+        let square =
+            vec![[0., 0.], [1., 0.], [2., 0.], [2., 2.], [1., 2.], [0., 2.]];
+
+        let mut shape: fj::Shape = fj::Sweep {
+            shape: fj::Difference2d {
+                a: fj::Sketch::from_points(circle).into(),
+                b: fj::Sketch::from_points(square)
+                    .with_tag(fj::Tag::Engrave)
+                    .into(),
+            }
+            .into(),
+            length: 1.,
+        }
+        .into();
+
+        assert!(simplify(&mut shape, 0.1) > 300);
+
+        let difference = match shape {
+            fj::Shape::Shape3d(fj::Shape3d::Sweep(fj::Sweep {
+                shape: fj::Shape2d::Difference(difference),
+                ..
+            })) => difference,
+            shape => panic!("Unexpected shape: {:?}", shape),
+        };
+        match difference.b {
+            fj::Shape2d::Sketch(sketch) => {
+                assert_eq!(
+                    sketch.to_points(),
+                    [[0., 0.], [2., 0.], [2., 2.], [0., 2.]]
+                );
+                assert_eq!(sketch.tag(), fj::Tag::Engrave);
+            }
+            shape => panic!("Unexpected shape: {:?}", shape),
+        }
+    }
+
+    #[test]
+    fn simplify_open_path() {
+        let path = vec![[0., 0.], [1., 0.], [2., 0.], [2., 2.], [2.1, 2.]];
+        let mut shape: fj::Shape = fj::Sketch::from_path(path, 1e-6)
+            .with_tag(fj::Tag::Engrave)
+            .into();
+
+        assert_eq!(simplify(&mut shape, 0.01), 1);
+
+        match shape {
+            fj::Shape::Shape2d(fj::Shape2d::Sketch(sketch)) => {
+                assert_eq!(
+                    sketch.to_points(),
+                    [[0., 0.], [2., 0.], [2., 2.], [2.1, 2.]]
+                );
+                assert!(sketch.is_open());
+                assert_eq!(sketch.tag(), fj::Tag::Engrave);
+            }
+            shape => panic!("Unexpected shape: {:?}", shape),
+        }
+    }
+}
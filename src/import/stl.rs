@@ -0,0 +1,195 @@
+use std::{fs, io, path::Path, str::FromStr};
+
+use thiserror::Error;
+
+use crate::math::Point;
+
+/// Read the facets of an STL file
+///
+/// Both the binary and the ASCII variant of the format are supported. Which of
+/// the two is present, is determined from the file contents.
+///
+/// Facets are returned as they are found in the file. They are not checked for
+/// validity, meaning degenerate facets (which are quite common in scan data)
+/// are returned too.
+pub fn read(path: impl AsRef<Path>) -> Result<Vec<[Point<3>; 3]>, Error> {
+    let data = fs::read(path)?;
+    parse(&data)
+}
+
+/// Parse the facets from the contents of an STL file
+pub fn parse(data: &[u8]) -> Result<Vec<[Point<3>; 3]>, Error> {
+    if is_binary(data) {
+        parse_binary(data)
+    } else {
+        parse_ascii(data)
+    }
+}
+
+fn is_binary(data: &[u8]) -> bool {
+    // Binary STL files are allowed to start with "solid", just like ASCII STL
+    // files are required to. The only reliable way to distinguish them, is to
+    // check whether the size of the file matches the facet count in the
+    // binary header.
+    if data.len() < BINARY_HEADER_SIZE {
+        return false;
+    }
+
+    let num_facets = read_u32(&data[80..84]) as usize;
+    data.len() == BINARY_HEADER_SIZE + num_facets * BINARY_FACET_SIZE
+}
+
+fn parse_binary(data: &[u8]) -> Result<Vec<[Point<3>; 3]>, Error> {
+    let mut facets = Vec::new();
+
+    for facet in data[BINARY_HEADER_SIZE..].chunks(BINARY_FACET_SIZE) {
+        if facet.len() != BINARY_FACET_SIZE {
+            return Err(Error::UnexpectedEnd);
+        }
+
+        // The first 12 bytes are the facet normal, which we ignore. It can be
+        // recomputed from the vertices, if required.
+        let vertices = &facet[12..48];
+
+        let mut points = [Point::origin(); 3];
+        for (point, vertex) in points.iter_mut().zip(vertices.chunks(12)) {
+            let [x, y, z] = [0, 4, 8].map(|offset| {
+                f32::from_bits(read_u32(&vertex[offset..offset + 4])) as f64
+            });
+
+            if !(x.is_finite() && y.is_finite() && z.is_finite()) {
+                return Err(Error::InvalidNumber);
+            }
+
+            *point = Point::from([x, y, z]);
+        }
+
+        facets.push(points);
+    }
+
+    Ok(facets)
+}
+
+fn parse_ascii(data: &[u8]) -> Result<Vec<[Point<3>; 3]>, Error> {
+    let data = std::str::from_utf8(data).map_err(|_| Error::InvalidAscii)?;
+
+    let mut facets = Vec::new();
+    let mut vertices = Vec::new();
+
+    let mut tokens = data.split_whitespace();
+    while let Some(token) = tokens.next() {
+        match token {
+            "vertex" => {
+                let mut coords = [0.; 3];
+                for coord in &mut coords {
+                    let token = tokens.next().ok_or(Error::UnexpectedEnd)?;
+                    *coord = f64::from_str(token)
+                        .ok()
+                        .filter(|coord| coord.is_finite())
+                        .ok_or(Error::InvalidNumber)?;
+                }
+
+                vertices.push(Point::from(coords));
+            }
+            "endfacet" => {
+                if vertices.len() != 3 {
+                    return Err(Error::InvalidFacet(vertices.len()));
+                }
+
+                facets.push([vertices[0], vertices[1], vertices[2]]);
+                vertices.clear();
+            }
+            _ => {
+                // Everything else is either structure we don't need to track
+                // (`solid`, `outer loop`, ...) or data we ignore (normals).
+            }
+        }
+    }
+
+    Ok(facets)
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    let mut buf = [0; 4];
+    buf.copy_from_slice(bytes);
+    u32::from_le_bytes(buf)
+}
+
+const BINARY_HEADER_SIZE: usize = 84;
+const BINARY_FACET_SIZE: usize = 50;
+
+/// An error that can occur while reading an STL file
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O error while reading STL file")]
+    Io(#[from] io::Error),
+
+    #[error("ASCII STL file is not valid UTF-8")]
+    InvalidAscii,
+
+    #[error("STL file ended unexpectedly")]
+    UnexpectedEnd,
+
+    #[error("STL file contains an invalid number")]
+    InvalidNumber,
+
+    #[error("STL facet has {0} vertices; expected 3")]
+    InvalidFacet(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::Point;
+
+    use super::parse;
+
+    #[test]
+    fn parse_ascii() {
+        let stl = "
+            solid test
+              facet normal 0 0 1
+                outer loop
+                  vertex 0 0 0
+                  vertex 1 0 0
+                  vertex 0 1 0
+                endloop
+              endfacet
+            endsolid test
+        ";
+
+        let facets = parse(stl.as_bytes()).unwrap();
+        assert_eq!(
+            facets,
+            vec![[
+                Point::from([0., 0., 0.]),
+                Point::from([1., 0., 0.]),
+                Point::from([0., 1., 0.]),
+            ]]
+        );
+    }
+
+    #[test]
+    fn parse_binary() {
+        let mut stl = vec![0; 80];
+        stl.extend(1u32.to_le_bytes());
+
+        // normal
+        stl.extend([0f32; 3].iter().flat_map(|v| v.to_le_bytes()));
+        // vertices
+        for vertex in [[0f32, 0., 0.], [1., 0., 0.], [0., 1., 0.]] {
+            stl.extend(vertex.iter().flat_map(|v| v.to_le_bytes()));
+        }
+        // attribute byte count
+        stl.extend([0, 0]);
+
+        let facets = parse(&stl).unwrap();
+        assert_eq!(
+            facets,
+            vec![[
+                Point::from([0., 0., 0.]),
+                Point::from([1., 0., 0.]),
+                Point::from([0., 1., 0.]),
+            ]]
+        );
+    }
+}
@@ -1,5 +1,3 @@
-use std::time::Instant;
-
 use winit::{
     dpi::PhysicalPosition,
     event::{
@@ -10,7 +8,8 @@ use winit::{
 
 use crate::{
     camera::{Camera, FocusPoint},
-    math::Triangle,
+    math::{Aabb, Triangle},
+    platform::Instant,
     window::Window,
 };
 
@@ -56,6 +55,14 @@ impl Handler {
                 VirtualKeyCode::Key1 => actions.toggle_model = true,
                 VirtualKeyCode::Key2 => actions.toggle_mesh = true,
                 VirtualKeyCode::Key3 => actions.toggle_debug = true,
+                VirtualKeyCode::Key4 => actions.toggle_toolpath = true,
+                VirtualKeyCode::Key5 => actions.toggle_validation = true,
+                VirtualKeyCode::Key6 => actions.toggle_edges = true,
+                VirtualKeyCode::Key7 => actions.toggle_xray = true,
+                VirtualKeyCode::Key8 => actions.toggle_overhangs = true,
+
+                VirtualKeyCode::F => actions.zoom_to_fit = true,
+                VirtualKeyCode::T => actions.toggle_theme = true,
 
                 _ => (),
             }
@@ -67,12 +74,13 @@ impl Handler {
         cursor: PhysicalPosition<f64>,
         camera: &mut Camera,
         window: &Window,
+        aabb: &Aabb<3>,
     ) {
         if let Some(previous) = self.cursor {
             let diff_x = cursor.x - previous.x;
             let diff_y = cursor.y - previous.y;
 
-            self.movement.apply(self.cursor, camera, window);
+            self.movement.apply(self.cursor, camera, window, aabb);
             self.rotation.apply(diff_x, diff_y, camera);
         }
 
@@ -122,11 +130,13 @@ impl Handler {
         camera: &mut Camera,
         window: &Window,
         triangles: &[Triangle<3>],
+        aabb: &Aabb<3>,
     ) {
         let focus_point = camera.focus_point(window, self.cursor, triangles);
 
         self.zoom.discard_old_events(now);
-        self.zoom.update_speed(now, delta_t, focus_point, camera);
+        self.zoom
+            .update_speed(now, delta_t, focus_point, camera, aabb);
 
         camera.translation.z -= self.zoom.speed();
     }
@@ -138,6 +148,14 @@ pub struct Actions {
     pub toggle_model: bool,
     pub toggle_mesh: bool,
     pub toggle_debug: bool,
+    pub toggle_toolpath: bool,
+    pub toggle_validation: bool,
+    pub toggle_edges: bool,
+    pub toggle_xray: bool,
+    pub toggle_overhangs: bool,
+
+    pub zoom_to_fit: bool,
+    pub toggle_theme: bool,
 }
 
 impl Actions {
@@ -148,6 +166,20 @@ impl Actions {
             toggle_model: false,
             toggle_mesh: false,
             toggle_debug: false,
+            toggle_toolpath: false,
+            toggle_validation: false,
+            toggle_edges: false,
+            toggle_xray: false,
+            toggle_overhangs: false,
+
+            zoom_to_fit: false,
+            toggle_theme: false,
         }
     }
 }
+
+impl Default for Actions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,8 +1,8 @@
-use nalgebra::distance;
 use winit::dpi::PhysicalPosition;
 
 use crate::{
     camera::{Camera, FocusPoint},
+    math::Aabb,
     window::Window,
 };
 
@@ -37,21 +37,22 @@ impl Movement {
         cursor: Option<PhysicalPosition<f64>>,
         camera: &mut Camera,
         window: &Window,
+        aabb: &Aabb<3>,
     ) {
         if let (Some(previous), Some(cursor)) = (self.cursor, cursor) {
             let previous = camera.cursor_to_model_space(previous, window);
             let cursor = camera.cursor_to_model_space(cursor, window);
 
-            if let Some(focus_point) = self.focus_point.0 {
-                let d1 = distance(&camera.position(), &cursor);
-                let d2 = distance(&camera.position(), &focus_point);
+            // Move the point under the cursor along with it. If there is no
+            // such point, move the model as if there was one at its center.
+            let d1 = (cursor - camera.position()).magnitude();
+            let d2 = camera.navigation_distance(&self.focus_point, aabb);
 
-                let diff = (cursor - previous) * d2 / d1;
-                let offset = camera.camera_to_model().transform_vector(&diff);
+            let diff = (cursor - previous) * d2 / d1;
+            let offset = camera.camera_to_model().transform_vector(&diff);
 
-                camera.translation.x += offset.x;
-                camera.translation.y += offset.y;
-            }
+            camera.translation.x += offset.x.into_f64();
+            camera.translation.y += offset.y.into_f64();
         }
 
         self.cursor = cursor;
@@ -28,7 +28,7 @@ impl Rotation {
             let angle_x = diff_y * f;
             let angle_y = diff_x * f;
 
-            let trans = Translation::from(focus_point.coords);
+            let trans = Translation::from(focus_point.coords.to_na());
 
             let rot_x = Rotation3::from_axis_angle(&Vector::x_axis(), angle_x);
             let rot_y = Rotation3::from_axis_angle(&Vector::y_axis(), angle_y);
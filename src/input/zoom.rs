@@ -1,11 +1,10 @@
-use std::{
-    collections::VecDeque,
-    time::{Duration, Instant},
-};
-
-use nalgebra::distance;
+use std::{collections::VecDeque, time::Duration};
 
-use crate::camera::{Camera, FocusPoint};
+use crate::{
+    camera::{Camera, FocusPoint},
+    math::Aabb,
+    platform::Instant,
+};
 
 pub struct Zoom {
     events: VecDeque<(Instant, f64)>,
@@ -13,6 +12,11 @@ pub struct Zoom {
     target_speed: f64,
     current_speed: f64,
 
+    /// The distance that the zoom speed is relative to
+    ///
+    /// See [`Camera::navigation_distance`].
+    distance: f64,
+
     last_direction: Direction,
     idle_since: Option<Instant>,
 }
@@ -25,6 +29,8 @@ impl Zoom {
             target_speed: 0.0,
             current_speed: 0.0,
 
+            distance: 0.0,
+
             last_direction: Direction::None,
             idle_since: Some(now),
         }
@@ -78,7 +84,11 @@ impl Zoom {
         delta_t: f64,
         focus_point: FocusPoint,
         camera: &Camera,
+        aabb: &Aabb<3>,
     ) {
+        self.distance =
+            camera.navigation_distance(&focus_point, aabb).into_f64();
+
         self.target_speed = self.events.iter().map(|(_, event)| event).sum();
 
         // Compute current speed from target speed. Gradually converge towards
@@ -111,8 +121,11 @@ impl Zoom {
         // Limit current speed, if close to focus point and zooming in.
         if let Some(focus_point) = focus_point.0 {
             if self.last_direction == Direction::In {
-                let d = distance(&focus_point, &camera.position());
-                self.current_speed = -f64::min(-self.current_speed, d / 8.);
+                let d = (focus_point - camera.position()).magnitude();
+                self.current_speed = -f64::min(
+                    -self.current_speed,
+                    d.into_f64() / 8. / self.scale(),
+                );
             }
         }
 
@@ -126,9 +139,14 @@ impl Zoom {
         }
     }
 
-    /// Access the current zoom speed
+    /// Access the current zoom speed, in model units
     pub fn speed(&self) -> f64 {
-        self.current_speed
+        self.current_speed * self.scale()
+    }
+
+    /// The factor that converts zoom speed into model units
+    fn scale(&self) -> f64 {
+        self.distance * SPEED_PER_DISTANCE
     }
 }
 
@@ -221,3 +239,15 @@ const MIN_SPEED_DELTA: f64 = 0.01;
 /// This value should be as high as possible, while not causing jarring
 /// accelerations.
 const ACCELERATION: f64 = 0.5;
+
+/// The zoom speed, relative to the distance of the model
+///
+/// All speeds and accelerations above are multiplied with this and the distance
+/// of the model from the camera, to convert them into model units. This keeps
+/// zooming usable for models of any size, and slows it down close to the
+/// model, where precise control is needed.
+///
+/// Tuning notes:
+/// - If this value is too low, zooming across larger distances takes too long.
+/// - If this value is too high, zooming overshoots easily.
+const SPEED_PER_DISTANCE: f64 = 0.05;
@@ -1,14 +1,19 @@
 use std::collections::HashSet;
 
 use crate::{
-    kernel::topology::{
-        edges::{Cycle, Edge},
-        faces::Face,
-        vertices::Vertex,
+    kernel::{
+        geometry::Curve,
+        topology::{
+            edges::{Cycle, Edge},
+            faces::Face,
+            vertices::Vertex,
+        },
     },
-    math::{Point, Scalar, Segment},
+    math::{Point, Segment},
 };
 
+use super::{arena, tolerance::Tolerance};
+
 /// An approximation of an edge, multiple edges, or a face
 #[derive(Debug, PartialEq)]
 pub struct Approximation {
@@ -33,18 +38,30 @@ impl Approximation {
     ///
     /// `tolerance` defines how far the approximation is allowed to deviate from
     /// the actual edge.
-    pub fn for_edge(edge: &Edge, tolerance: Scalar) -> Self {
-        let mut points = Vec::new();
+    pub fn for_edge(edge: &Edge, tolerance: Tolerance) -> Self {
+        // Most edges of typical models are straight. Those are approximated by
+        // their vertices alone, so there's no need to go through the curve.
+        if let (Curve::Line(_), Some([a, b])) = (edge.curve(), edge.vertices())
+        {
+            let [a, b] = [a, b].map(|vertex| vertex.point());
+
+            return Self {
+                points: [a, b].into_iter().collect(),
+                segments: [Segment::from([a, b])].into_iter().collect(),
+            };
+        }
+
+        let mut points = arena::take();
         edge.curve().approx(tolerance, &mut points);
 
-        approximate_edge(points, edge.vertices())
+        approximate_edge(&mut points, edge.vertices())
     }
 
     /// Compute an approximation for a cycle
     ///
     /// `tolerance` defines how far the approximation is allowed to deviate from
     /// the actual cycle.
-    pub fn for_cycle(cycle: &Cycle, tolerance: Scalar) -> Self {
+    pub fn for_cycle(cycle: &Cycle, tolerance: Tolerance) -> Self {
         let mut points = HashSet::new();
         let mut segments = HashSet::new();
 
@@ -61,20 +78,17 @@ impl Approximation {
     /// Compute an approximation for a face
     ///
     /// `tolerance` defines how far the approximation is allowed to deviate from
-    /// the actual edges.
-    pub fn for_face(face: &Face, tolerance: Scalar) -> Self {
-        // Curved faces whose curvature is not fully defined by their edges
-        // are not supported yet. For that reason, we can fully ignore `face`'s
-        // `surface` field and just pass the edges to `Self::for_edges`.
-        //
-        // An example of a curved face that is supported, is the cylinder. Its
-        // curvature is fully defined be the edges (circles) that border it. The
-        // circle approximations are sufficient to triangulate the surface.
+    /// the actual face.
+    pub fn for_face(face: &Face, tolerance: Tolerance) -> Self {
+        // The curvature of most faces is fully defined by their edges. An
+        // example of that is the cylinder, whose curvature is fully defined by
+        // the edges (circles) that border it. The circle approximations are
+        // sufficient to triangulate the surface.
         //
-        // An example of a curved face that is currently not supported, and thus
-        // doesn't need to be handled here, is a sphere. A spherical face would
-        // would need to provide its own approximation, as the edges that bound
-        // it have nothing to do with its curvature.
+        // An example of a face that is not covered by that, is a sphere. A
+        // spherical face needs to provide its own approximation, as the edges
+        // that bound it have nothing to do with its curvature. Only external
+        // surfaces do that, currently.
         let mut points = HashSet::new();
         let mut segments = HashSet::new();
 
@@ -85,12 +99,16 @@ impl Approximation {
             segments.extend(approx.segments);
         }
 
+        let mut surface_points = arena::take();
+        face.surface().approx(tolerance, &mut surface_points);
+        points.extend(surface_points.iter().copied());
+
         Self { points, segments }
     }
 }
 
 fn approximate_edge(
-    mut points: Vec<Point<3>>,
+    points: &mut Vec<Point<3>>,
     vertices: Option<[Vertex; 2]>,
 ) -> Approximation {
     // Insert the exact vertices of this edge into the approximation. This means
@@ -106,26 +124,25 @@ fn approximate_edge(
         points.push(b.point());
     }
 
-    let mut segment_points = points.clone();
-    if vertices.is_none() {
-        // The edge has no vertices, which means it connects to itself. We need
-        // to reflect that in the approximation.
-
-        if let Some(&point) = points.first() {
-            segment_points.push(point);
-        }
-    }
-
     let mut segments = HashSet::new();
-    for segment in segment_points.windows(2) {
+    for segment in points.windows(2) {
         let p0 = segment[0];
         let p1 = segment[1];
 
         segments.insert(Segment::from([p0, p1]));
     }
 
+    if vertices.is_none() {
+        // The edge has no vertices, which means it connects to itself. We need
+        // to reflect that in the approximation.
+
+        if let (Some(&first), Some(&last)) = (points.first(), points.last()) {
+            segments.insert(Segment::from([last, first]));
+        }
+    }
+
     Approximation {
-        points: points.into_iter().collect(),
+        points: points.iter().copied().collect(),
         segments,
     }
 }
@@ -133,6 +150,7 @@ fn approximate_edge(
 #[cfg(test)]
 mod tests {
     use map_macro::set;
+    use smallvec::smallvec;
 
     use crate::{
         kernel::{
@@ -143,7 +161,7 @@ mod tests {
         math::{Point, Scalar, Segment},
     };
 
-    use super::{approximate_edge, Approximation};
+    use super::{approximate_edge, Approximation, Tolerance};
 
     #[test]
     fn for_edge() {
@@ -168,7 +186,7 @@ mod tests {
         // Regular edge
         assert_eq!(
             approximate_edge(
-                points.clone(),
+                &mut points.clone(),
                 Some([v1.get().clone(), v2.get().clone()])
             ),
             Approximation {
@@ -183,7 +201,7 @@ mod tests {
 
         // Continuous edge
         assert_eq!(
-            approximate_edge(points, None),
+            approximate_edge(&mut points.clone(), None),
             Approximation {
                 points: set![b, c],
                 segments: set![Segment::from([b, c]), Segment::from([c, b])],
@@ -193,7 +211,7 @@ mod tests {
 
     #[test]
     fn for_cycle() {
-        let tolerance = Scalar::ONE;
+        let tolerance = Tolerance::from_scalar(Scalar::ONE).unwrap();
 
         let mut shape = Shape::new();
 
@@ -217,7 +235,7 @@ mod tests {
         let ca = shape.topology().add_line_segment([v3, v1]).unwrap();
 
         let cycle = Cycle {
-            edges: vec![ab, bc, ca],
+            edges: smallvec![ab, bc, ca],
         };
 
         assert_eq!(
@@ -237,7 +255,7 @@ mod tests {
     fn for_face_closed() {
         // Test a closed face, i.e. one that is completely encircled by edges.
 
-        let tolerance = Scalar::ONE;
+        let tolerance = Tolerance::from_scalar(Scalar::ONE).unwrap();
 
         let mut shape = Shape::new();
 
@@ -267,14 +285,14 @@ mod tests {
         let abcd = shape
             .topology()
             .add_cycle(Cycle {
-                edges: vec![ab, bc, cd, da],
+                edges: smallvec![ab, bc, cd, da],
             })
             .unwrap();
 
         let surface = shape.geometry().add_surface(Surface::x_y_plane());
         let face = Face::Face {
             surface,
-            cycles: vec![abcd],
+            cycles: smallvec![abcd],
         };
 
         assert_eq!(
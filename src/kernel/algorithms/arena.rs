@@ -0,0 +1,181 @@
+//! Reusable memory for transient data
+//!
+//! Approximating and triangulating a shape needs many small, short-lived
+//! buffers: the points of each edge, the surface coordinates of each face, and
+//! so on. Allocating and freeing them one by one adds up, for large models.
+//! Instead, such buffers are taken from an arena, using [`take`]. When a
+//! [`Buffer`] is dropped, its memory goes back to the arena, and is handed out
+//! again by the next call to [`take`].
+//!
+//! Each thread has its own arena, so taking buffers doesn't need any
+//! synchronization. The memory is kept until [`reset`] is called. Code that
+//! runs evaluations, like the viewer or the remote server, calls it between
+//! runs, so the memory that one large model needed isn't kept around forever.
+
+use std::{
+    cell::RefCell,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{kernel::geometry, math::Point};
+
+/// The maximum number of unused buffers that are kept per type and thread
+///
+/// Buffers are usually taken and dropped in quick succession, so few of them
+/// are unused at any time. This only keeps the arena from growing without
+/// bounds, if that's not the case.
+const MAX_UNUSED: usize = 32;
+
+/// Incremented by [`reset`]
+static EPOCH: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static ARENA: RefCell<Arena> = RefCell::new(Arena::default());
+}
+
+/// Take a buffer from the arena of the current thread
+///
+/// The buffer is empty, but might have capacity left over from earlier use.
+pub fn take<T: Allocate>() -> Buffer<T> {
+    let vec = ARENA
+        .with(|arena| T::pool(arena.borrow_mut().current()).pop())
+        .unwrap_or_default();
+
+    Buffer { vec }
+}
+
+/// Release the memory of all arenas
+///
+/// Buffers that are in use at that time stay valid, but their memory is freed
+/// when they are dropped, instead of going back to the arena. Arenas of other
+/// threads are reset the next time they are used.
+pub fn reset() {
+    EPOCH.fetch_add(1, Ordering::Relaxed);
+}
+
+/// A buffer that was taken from an arena
+///
+/// Dereferences to a [`Vec`]. Returns its memory to the arena of the thread it
+/// is dropped on.
+#[derive(Debug)]
+pub struct Buffer<T: Allocate> {
+    vec: Vec<T>,
+}
+
+impl<T: Allocate> Deref for Buffer<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.vec
+    }
+}
+
+impl<T: Allocate> DerefMut for Buffer<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.vec
+    }
+}
+
+impl<T: Allocate> Drop for Buffer<T> {
+    fn drop(&mut self) {
+        let mut vec = std::mem::take(&mut self.vec);
+        if vec.capacity() == 0 {
+            return;
+        }
+        vec.clear();
+
+        // The arena might already be gone, if the buffer is dropped while its
+        // thread shuts down. The memory is just freed then.
+        let _ = ARENA.try_with(|arena| {
+            let mut arena = arena.borrow_mut();
+            let pool = T::pool(arena.current());
+            if pool.len() < MAX_UNUSED {
+                pool.push(vec);
+            }
+        });
+    }
+}
+
+/// Types that buffers can be taken for
+///
+/// The arena keeps a separate pool of buffers for each type.
+pub trait Allocate: Sized {
+    #[doc(hidden)]
+    fn pool(arena: &mut Arena) -> &mut Vec<Vec<Self>>;
+}
+
+#[doc(hidden)]
+#[derive(Default)]
+pub struct Arena {
+    epoch: usize,
+    points: Vec<Vec<Point<3>>>,
+    surface_points: Vec<Vec<geometry::Point<2>>>,
+    surface_segments: Vec<Vec<[geometry::Point<2>; 2]>>,
+}
+
+impl Arena {
+    /// Access the arena, after releasing its memory, if it was reset
+    fn current(&mut self) -> &mut Self {
+        let epoch = EPOCH.load(Ordering::Relaxed);
+        if self.epoch != epoch {
+            *self = Self {
+                epoch,
+                ..Self::default()
+            };
+        }
+
+        self
+    }
+}
+
+impl Allocate for Point<3> {
+    fn pool(arena: &mut Arena) -> &mut Vec<Vec<Self>> {
+        &mut arena.points
+    }
+}
+
+impl Allocate for geometry::Point<2> {
+    fn pool(arena: &mut Arena) -> &mut Vec<Vec<Self>> {
+        &mut arena.surface_points
+    }
+}
+
+impl Allocate for [geometry::Point<2>; 2] {
+    fn pool(arena: &mut Arena) -> &mut Vec<Vec<Self>> {
+        &mut arena.surface_segments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use crate::math::Point;
+
+    use super::{reset, take, EPOCH};
+
+    #[test]
+    fn take_should_reuse_memory_until_reset() {
+        let epoch = EPOCH.load(Ordering::Relaxed);
+
+        let mut buffer = take::<Point<3>>();
+        buffer.extend((0..100).map(|i| Point::from([i as f64, 0., 0.])));
+        let ptr = buffer.as_ptr();
+        drop(buffer);
+
+        let buffer = take::<Point<3>>();
+        assert!(buffer.is_empty());
+
+        // Other tests might reset the arenas at any time.
+        if EPOCH.load(Ordering::Relaxed) == epoch {
+            assert!(buffer.capacity() >= 100);
+            assert_eq!(buffer.as_ptr(), ptr);
+        }
+        drop(buffer);
+
+        reset();
+        let buffer = take::<Point<3>>();
+        assert_eq!(buffer.capacity(), 0);
+    }
+}
@@ -0,0 +1,621 @@
+use std::f64::consts::FRAC_PI_2;
+
+use nalgebra::Point3;
+use smallvec::{smallvec, SmallVec};
+
+use crate::{
+    debug::DebugInfo,
+    kernel::{shape::Shape, topology::faces::Face, Error},
+    math::{Aabb, Frame, Point, Scalar, Triangle, Vector},
+};
+
+use super::tolerance::Tolerance;
+
+/// A non-rigid deformation
+///
+/// See [`fj::Deformation`] for the definition of each deformation.
+#[derive(Clone, Copy, Debug)]
+pub enum Deformation {
+    Bend { radius: f64 },
+    Twist { rate: f64 },
+    Wrap { radius: f64 },
+}
+
+impl Deformation {
+    /// Deform a point
+    pub fn deform_point(&self, point: Point<3>) -> Point<3> {
+        let [x, y, z]: [f64; 3] = point.into();
+
+        match *self {
+            Self::Bend { radius } => {
+                let angle = z / radius;
+                let distance = radius - x;
+
+                Point::from([
+                    radius - distance * angle.cos(),
+                    y,
+                    distance * angle.sin(),
+                ])
+            }
+            Self::Twist { rate } => {
+                let (sin, cos) = (rate * z).sin_cos();
+                Point::from([x * cos - y * sin, x * sin + y * cos, z])
+            }
+            Self::Wrap { radius } => {
+                let (sin, cos) = (x / radius).sin_cos();
+                let distance = radius + z;
+
+                Point::from([distance * cos, distance * sin, y])
+            }
+        }
+    }
+
+    /// Deform a frame
+    ///
+    /// The origin of the frame is deformed like any other point. Its axes
+    /// follow the deformation in the immediate vicinity of the origin, which
+    /// is approximated by deforming points that are a small step away from it.
+    pub fn deform_frame(&self, frame: &Frame) -> Frame {
+        let origin = self.deform_point(frame.origin);
+
+        let step = frame.origin.coords.magnitude().max(Scalar::ONE)
+            * Scalar::from_f64(1e-6);
+        let deform_axis = |axis: Vector<3>| {
+            self.deform_point(frame.origin + axis * step) - origin
+        };
+
+        // Can only fail, if the deformation collapses the neighborhood of the
+        // origin, as bending at the center of the circle does. Leave the axes
+        // alone then.
+        Frame::from_axes(
+            origin,
+            deform_axis(frame.x_axis),
+            deform_axis(frame.z_axis),
+        )
+        .unwrap_or(Frame { origin, ..*frame })
+    }
+
+    /// Deform an axis-aligned bounding box
+    ///
+    /// The result contains all points of the deformed box.
+    pub fn deform_aabb(&self, aabb: &Aabb<3>) -> Aabb<3> {
+        let [min_x, min_y, min_z]: [f64; 3] = aabb.min.into();
+        let [max_x, max_y, max_z]: [f64; 3] = aabb.max.into();
+
+        match *self {
+            Self::Bend { radius } => {
+                let mut points = Vec::new();
+                for x in [min_x, max_x] {
+                    for y in [min_y, max_y] {
+                        for z in arc_samples(min_z, max_z, radius) {
+                            points.push(self.deform_point([x, y, z].into()));
+                        }
+                    }
+                }
+
+                Aabb::<3>::from_points(points)
+            }
+            Self::Twist { .. } => {
+                // Each cross-section can be rotated by any angle, so only the
+                // distance from the z-axis is known.
+                let distance = [min_x, max_x]
+                    .iter()
+                    .flat_map(|x| [min_y, max_y].map(|y| x.hypot(y)))
+                    .fold(0., f64::max);
+
+                Aabb {
+                    min: [-distance, -distance, min_z].into(),
+                    max: [distance, distance, max_z].into(),
+                }
+            }
+            Self::Wrap { radius } => {
+                let mut points = Vec::new();
+                for x in arc_samples(min_x, max_x, radius) {
+                    for y in [min_y, max_y] {
+                        for z in [min_z, max_z] {
+                            points.push(self.deform_point([x, y, z].into()));
+                        }
+                    }
+                }
+
+                Aabb::<3>::from_points(points)
+            }
+        }
+    }
+
+    /// Check whether a shape within `aabb` can be deformed
+    ///
+    /// Bending and wrapping collapse everything at the axis of their circle
+    /// into a line, and turn everything beyond it inside out. Shapes that
+    /// reach that axis can't be deformed without creating degenerate
+    /// triangles.
+    fn check_aabb(&self, aabb: &Aabb<3>) -> Result<(), Error> {
+        let (axis, min, max, coordinate) = match *self {
+            Self::Bend { radius } => {
+                (radius, aabb.min.x.into_f64(), aabb.max.x.into_f64(), "x")
+            }
+            Self::Twist { .. } => return Ok(()),
+            Self::Wrap { radius } => {
+                (-radius, aabb.min.z.into_f64(), aabb.max.z.into_f64(), "z")
+            }
+        };
+
+        if min <= axis && axis <= max {
+            return Err(Error::Deformation(format!(
+                "Shape reaches the axis of the deformation at {} = {}",
+                coordinate, axis
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Compute the maximum length of an edge that can be deformed
+    ///
+    /// The deformation turns straight edges into curves. If an edge within
+    /// `aabb` is not longer than the returned length, its deformed version
+    /// doesn't deviate from a straight line by more than `tolerance`.
+    fn max_edge_length(&self, aabb: &Aabb<3>, tolerance: f64) -> f64 {
+        let [min_x, min_y, min_z]: [f64; 3] = aabb.min.into();
+        let [max_x, max_y, max_z]: [f64; 3] = aabb.max.into();
+
+        // An edge that is turned into an arc with radius `r` and angle `a`
+        // deviates from the arc's chord by `r * (1 - cos(a / 2))`, which is
+        // approximately `r * a^2 / 8`.
+        let (distance, angle_per_length) = match *self {
+            Self::Bend { radius } => {
+                let distance =
+                    (radius - min_x).abs().max((radius - max_x).abs());
+                (distance, 1. / radius.abs())
+            }
+            Self::Twist { rate } => {
+                let distance = [min_x, max_x]
+                    .iter()
+                    .flat_map(|x| [min_y, max_y].map(|y| x.hypot(y)))
+                    .fold(0., f64::max);
+                (distance, rate.abs())
+            }
+            Self::Wrap { radius } => {
+                let distance =
+                    (radius + min_z).abs().max((radius + max_z).abs());
+                (distance, 1. / radius)
+            }
+        };
+
+        (8. * tolerance / distance).sqrt() / angle_per_length
+    }
+}
+
+impl From<fj::Deformation> for Deformation {
+    fn from(deformation: fj::Deformation) -> Self {
+        match deformation {
+            fj::Deformation::Bend { radius } => Self::Bend { radius },
+            fj::Deformation::Twist { rate } => Self::Twist { rate },
+            fj::Deformation::Wrap { radius } => Self::Wrap { radius },
+        }
+    }
+}
+
+/// Compute the coordinates at which a straight line must be sampled, to find
+/// the extreme points of the arc it is bent into
+///
+/// The line goes from `min` to `max` and is bent into an arc with the given
+/// radius, starting at the angle zero. The deformed arc is bounded by its
+/// end points, and by the points where it crosses the axes of the circle.
+fn arc_samples(min: f64, max: f64, radius: f64) -> Vec<f64> {
+    let mut samples = vec![min, max];
+
+    let [start, end] = [min / radius, max / radius];
+    let mut quarter = (start.min(end) / FRAC_PI_2).ceil();
+    while quarter * FRAC_PI_2 < start.max(end) {
+        samples.push(quarter * FRAC_PI_2 * radius);
+        quarter += 1.;
+    }
+
+    samples
+}
+
+/// Create a new shape that is a deformed version of an existing one
+///
+/// The faces of the original shape are approximated by triangles, which are
+/// subdivided until the deformed triangles are within `tolerance` of the
+/// deformed shape. Each face of the original shape becomes a face of the
+/// resulting shape, made up of its deformed triangles.
+///
+/// The surfaces and edges of the original faces are lost, as the deformed
+/// surfaces can't be represented exactly. The resulting shape can still be
+/// displayed and exported, but operations that need the exact geometry, like
+/// measuring the curvature of a face, only see its triangles.
+///
+/// Fails, if the shape reaches the axis of a bend or wrap, as it would be
+/// collapsed there.
+pub fn deform_shape(
+    mut original: Shape,
+    deformation: &Deformation,
+    tolerance: Tolerance,
+    debug_info: &mut DebugInfo,
+) -> Result<Shape, Error> {
+    // The faces are triangulated one by one, so they can be kept apart.
+    let mut triangles = Vec::new();
+    let mut faces = Vec::new();
+    for face in original.topology().faces() {
+        let start = triangles.len();
+        face.triangles(tolerance, &mut triangles, debug_info);
+        faces.push(start..triangles.len());
+    }
+
+    let mut deformed = Shape::new();
+    if triangles.is_empty() {
+        return Ok(deformed);
+    }
+
+    let aabb = Aabb::<3>::from_points(
+        triangles.iter().flat_map(|triangle| triangle.points()),
+    );
+    deformation.check_aabb(&aabb)?;
+
+    let max_length =
+        deformation.max_edge_length(&aabb, tolerance.inner().into_f64());
+
+    for face in faces {
+        let mut subdivided = Vec::new();
+        for triangle in &triangles[face] {
+            subdivide(
+                triangle.points().map(Point::to_na),
+                max_length,
+                &mut subdivided,
+            );
+        }
+
+        // Faces that are too small to have triangles are skipped.
+        if subdivided.is_empty() {
+            continue;
+        }
+
+        let triangles = subdivided
+            .into_iter()
+            .map(|points| {
+                Triangle::from(points.map(|point| {
+                    deformation.deform_point(Point::from_na(point))
+                }))
+            })
+            .collect();
+
+        deformed.topology().add_face(Face::Triangles(triangles))?;
+    }
+
+    Ok(deformed)
+}
+
+/// Subdivide a triangle, until none of its edges is longer than `max_length`
+///
+/// Whether an edge is split depends only on the edge itself, and it's always
+/// split at the same point. This means that triangles that share an edge are
+/// subdivided in a compatible way, and the resulting mesh has no cracks.
+pub(super) fn subdivide(
+    triangle: [Point3<f64>; 3],
+    max_length: f64,
+    out: &mut Vec<[Point3<f64>; 3]>,
+) {
+    subdivide_where(triangle, [true; 3], true, max_length, out);
+}
+
+/// Subdivide a triangle, but only split the given edges
+///
+/// `edges` contains one flag for each edge, starting with the one from the
+/// first to the second point. The flagged edges are split in the same way as
+/// by [`subdivide`], so the triangle matches up with a neighbor that is
+/// subdivided completely. The edges that this creates inside of the triangle
+/// are never split.
+pub(super) fn subdivide_edges(
+    triangle: [Point3<f64>; 3],
+    edges: [bool; 3],
+    max_length: f64,
+    out: &mut Vec<[Point3<f64>; 3]>,
+) {
+    subdivide_where(triangle, edges, false, max_length, out);
+}
+
+/// Subdivide a triangle, splitting the edges that are flagged
+///
+/// The parts of a split edge keep its flag. Edges that are created inside of
+/// the triangle are flagged with `inner`.
+fn subdivide_where(
+    triangle: [Point3<f64>; 3],
+    edges: [bool; 3],
+    inner: bool,
+    max_length: f64,
+    out: &mut Vec<[Point3<f64>; 3]>,
+) {
+    let split = [0, 1, 2].map(|i| {
+        let [a, b] = [triangle[i], triangle[(i + 1) % 3]];
+        (edges[i] && (b - a).norm() > max_length).then(|| {
+            // Computed in a way that doesn't depend on the order of `a` and
+            // `b`, so neighboring triangles end up with the same point.
+            Point3::from((a.coords + b.coords) / 2.)
+        })
+    });
+
+    // Rotate the triangle, so the edges that are split come first. This keeps
+    // the number of cases below manageable.
+    let offset = match split {
+        [None, None, None] => {
+            out.push(triangle);
+            return;
+        }
+        [Some(_), None, None] | [Some(_), Some(_), _] => 0,
+        [None, Some(_), None] | [None, Some(_), Some(_)] => 1,
+        [None, None, Some(_)] | [Some(_), None, Some(_)] => 2,
+    };
+    let [a, b, c] = [0, 1, 2].map(|i| triangle[(i + offset) % 3]);
+    let [ab, bc, ca] = [0, 1, 2].map(|i| split[(i + offset) % 3]);
+    let [e_ab, e_bc, e_ca] = [0, 1, 2].map(|i| edges[(i + offset) % 3]);
+    let e_in = inner;
+
+    let children: SmallVec<[_; 4]> = match (ab, bc, ca) {
+        (Some(ab), None, None) => smallvec![
+            ([a, ab, c], [e_ab, e_in, e_ca]),
+            ([ab, b, c], [e_ab, e_bc, e_in]),
+        ],
+        (Some(ab), Some(bc), None) => smallvec![
+            ([a, ab, bc], [e_ab, e_in, e_in]),
+            ([ab, b, bc], [e_ab, e_bc, e_in]),
+            ([a, bc, c], [e_in, e_bc, e_ca]),
+        ],
+        (Some(ab), Some(bc), Some(ca)) => smallvec![
+            ([a, ab, ca], [e_ab, e_in, e_ca]),
+            ([ab, b, bc], [e_ab, e_bc, e_in]),
+            ([ca, bc, c], [e_in, e_bc, e_ca]),
+            ([ab, bc, ca], [e_in, e_in, e_in]),
+        ],
+        _ => unreachable!("Triangle was rotated incorrectly"),
+    };
+
+    for (child, edges) in children {
+        subdivide_where(child, edges, inner, max_length, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, f64::consts::PI};
+
+    use approx::assert_abs_diff_eq;
+    use nalgebra::Point3;
+
+    use crate::{
+        debug::DebugInfo,
+        kernel::{
+            algorithms::tolerance::Tolerance, shapes::ToShape as _, Error,
+        },
+        math::{Aabb, Frame, Point, Vector},
+        testing::cuboid,
+    };
+
+    use super::{deform_shape, Deformation};
+
+    #[test]
+    fn bend_should_map_z_axis_to_circle() {
+        let bend = Deformation::Bend { radius: 2. };
+
+        let quarter = bend.deform_point(Point::from([0., 1., PI]));
+        assert_abs_diff_eq!(
+            quarter,
+            Point::from([2., 1., 2.]),
+            epsilon = 1e-12
+        );
+
+        // Points farther from the center are stretched.
+        let outside = bend.deform_point(Point::from([-1., 0., PI]));
+        assert_abs_diff_eq!(
+            outside,
+            Point::from([2., 0., 3.]),
+            epsilon = 1e-12
+        );
+
+        let aabb = bend.deform_aabb(&Aabb {
+            min: Point::from([-1., 0., 0.]),
+            max: Point::from([0., 1., PI]),
+        });
+        assert_abs_diff_eq!(aabb.min, Point::from([-1., 0., 0.]));
+        assert_abs_diff_eq!(
+            aabb.max,
+            Point::from([2., 1., 3.]),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn bend_should_fail_for_shapes_that_reach_the_axis() {
+        let tolerance = Tolerance::from_scalar(0.01).unwrap();
+        let shape = cuboid([1., 1., 1.]);
+
+        let deform = |radius| {
+            let shape =
+                shape.to_shape(tolerance, &mut DebugInfo::new()).unwrap();
+            deform_shape(
+                shape,
+                &Deformation::Bend { radius },
+                tolerance,
+                &mut DebugInfo::new(),
+            )
+        };
+
+        assert!(deform(2.).is_ok());
+        assert!(matches!(deform(1.), Err(Error::Deformation(_))));
+        assert!(matches!(deform(0.5), Err(Error::Deformation(_))));
+    }
+
+    #[test]
+    fn deform_shape_should_keep_faces() {
+        let tolerance = Tolerance::from_scalar(0.01).unwrap();
+        let shape = cuboid([1., 1., 1.]);
+        let mut shape =
+            shape.to_shape(tolerance, &mut DebugInfo::new()).unwrap();
+        let num_faces = shape.topology().faces().count();
+        assert!(num_faces > 1);
+
+        let mut deformed = deform_shape(
+            shape,
+            &Deformation::Wrap { radius: 2. },
+            tolerance,
+            &mut DebugInfo::new(),
+        )
+        .unwrap();
+
+        assert_eq!(deformed.topology().faces().count(), num_faces);
+    }
+
+    #[test]
+    fn twist_should_rotate_cross_sections() {
+        let twist = Deformation::Twist { rate: PI / 2. };
+
+        let bottom = twist.deform_point(Point::from([1., 0., 0.]));
+        assert_eq!(bottom, Point::from([1., 0., 0.]));
+
+        let top = twist.deform_point(Point::from([1., 0., 1.]));
+        assert_abs_diff_eq!(top, Point::from([0., 1., 1.]), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn wrap_should_preserve_arc_length() {
+        let wrap = Deformation::Wrap { radius: 2. };
+
+        // A quarter of the circumference along the x-axis is a quarter turn
+        // around the cylinder.
+        let quarter = wrap.deform_point(Point::from([PI, 1., 0.]));
+        assert_abs_diff_eq!(
+            quarter,
+            Point::from([0., 2., 1.]),
+            epsilon = 1e-12
+        );
+
+        // The z-axis points away from the cylinder.
+        let outside = wrap.deform_point(Point::from([PI, 0., 1.]));
+        assert_abs_diff_eq!(
+            outside,
+            Point::from([0., 3., 0.]),
+            epsilon = 1e-12
+        );
+
+        let aabb = wrap.deform_aabb(&Aabb {
+            min: Point::from([0., 0., 0.]),
+            max: Point::from([PI, 1., 1.]),
+        });
+        assert_abs_diff_eq!(
+            aabb.min,
+            Point::from([0., 0., 0.]),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            aabb.max,
+            Point::from([3., 3., 1.]),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn wrap_should_move_frames_onto_cylinder() {
+        let wrap = Deformation::Wrap { radius: 1. };
+
+        // A frame at the origin, pointing up, ends up on the surface of the
+        // cylinder, pointing away from its axis.
+        let frame = Frame::from_axes(
+            Point::from([0., 0., 0.]),
+            Vector::from([1., 0., 0.]),
+            Vector::from([0., 0., 1.]),
+        )
+        .unwrap();
+        let frame = wrap.deform_frame(&frame);
+
+        assert_abs_diff_eq!(frame.origin, Point::from([1., 0., 0.]));
+        assert_abs_diff_eq!(
+            frame.x_axis,
+            Vector::from([0., 1., 0.]),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(
+            frame.y_axis,
+            Vector::from([0., 0., 1.]),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(
+            frame.z_axis,
+            Vector::from([1., 0., 0.]),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn subdivide_should_not_create_cracks() {
+        // Two triangles sharing the long edge from `b` to `c`, at `x = 0`. Only
+        // one of them has other edges that need to be split.
+        let [a, b, c, d] =
+            [[-4., 0., 0.], [0., -1., 0.], [0., 1., 0.], [0.5, 0., 0.]]
+                .map(Point3::from);
+
+        let mut triangles = Vec::new();
+        super::subdivide([a, b, c], 1., &mut triangles);
+        super::subdivide([b, d, c], 1., &mut triangles);
+
+        let mut edges = HashMap::new();
+        for triangle in &triangles {
+            for i in 0..3 {
+                let [p, q] = [triangle[i], triangle[(i + 1) % 3]];
+                assert!((q - p).norm() <= 1.);
+
+                let [p, q] = [p, q].map(|point| {
+                    [point.x, point.y, point.z].map(|c| (c * 1024.) as i64)
+                });
+                *edges.entry([p.min(q), p.max(q)]).or_insert(0) += 1;
+            }
+        }
+
+        // If the shared edge had been split differently on both sides, some
+        // parts of it would only belong to one triangle.
+        for ([p, q], count) in edges {
+            if p[0] == 0 && q[0] == 0 {
+                assert_eq!(count, 2);
+            }
+        }
+    }
+
+    #[test]
+    fn subdivide_edges_should_only_split_flagged_edges() {
+        // Same as above, but the second triangle only has its edge from `c` to
+        // `b` split.
+        let [a, b, c, d] =
+            [[-4., 0., 0.], [0., -1., 0.], [0., 1., 0.], [0.5, 0., 0.]]
+                .map(Point3::from);
+
+        let mut triangles = Vec::new();
+        super::subdivide([a, b, c], 1., &mut triangles);
+        let full = triangles.len();
+        super::subdivide_edges(
+            [b, d, c],
+            [false, false, true],
+            1.,
+            &mut triangles,
+        );
+
+        // The shared edge is split into two parts, which only takes two
+        // triangles on the side of `d`.
+        assert_eq!(triangles.len() - full, 2);
+
+        let mut edges = HashMap::new();
+        for triangle in &triangles {
+            for i in 0..3 {
+                let [p, q] =
+                    [triangle[i], triangle[(i + 1) % 3]].map(|point| {
+                        [point.x, point.y, point.z].map(|c| (c * 1024.) as i64)
+                    });
+                *edges.entry([p.min(q), p.max(q)]).or_insert(0) += 1;
+            }
+        }
+        for ([p, q], count) in edges {
+            if p[0] == 0 && q[0] == 0 {
+                assert_eq!(count, 2);
+            }
+        }
+    }
+}
@@ -0,0 +1,587 @@
+use nalgebra::Vector3;
+use parry3d_f64::query::{Ray as Ray3, RayCast as _};
+use smallvec::{smallvec, SmallVec};
+
+use crate::{
+    debug::DebugInfo,
+    kernel::{
+        geometry::{Curve, Ellipse, Surface},
+        shape::handle::Handle,
+        topology::faces::Face,
+    },
+    math::{Point, Scalar, Triangle, Vector},
+};
+
+use super::{approximation::Approximation, tolerance::Tolerance};
+
+/// A ray, that starts at `origin` and extends infinitely in the direction of
+/// `dir`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    /// The point where the ray starts
+    pub origin: Point<3>,
+
+    /// The direction of the ray
+    ///
+    /// The length of this vector defines the unit of the ray parameter.
+    pub dir: Vector<3>,
+}
+
+impl Ray {
+    /// Compute the point at the given ray parameter
+    pub fn point_at(&self, t: Scalar) -> Point<3> {
+        self.origin + self.dir * t
+    }
+
+    fn to_parry(self) -> Ray3 {
+        Ray3 {
+            origin: self.origin.to_na(),
+            dir: self.dir.to_na(),
+        }
+    }
+}
+
+/// An intersection of a ray with a surface or face
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    /// The ray parameter of the intersection, in multiples of [`Ray::dir`]
+    pub t: Scalar,
+
+    /// The point of the intersection, in model coordinates
+    pub point: Point<3>,
+}
+
+/// Compute the exact intersections of a ray with a surface
+///
+/// Returns the intersections in front of the ray's origin, ordered by their
+/// distance from it. Intersections are computed exactly for planes and
+/// cylinders. More generally, for all surfaces that are swept from a line,
+/// circle, or ellipse. The surfaces are treated as unbounded.
+///
+/// A ray that lies within a surface, like a ray within a plane, touches it
+/// everywhere. No intersections are returned for it.
+///
+/// Returns `None` for all other surfaces, which don't have an exact routine.
+/// [`ray_face`] falls back to the triangulation of the face for those.
+pub fn ray_surface(ray: &Ray, surface: &Surface) -> Option<Vec<Hit>> {
+    let origin = ray.origin.to_na().coords;
+    let dir = ray.dir.to_na();
+
+    // All supported surfaces are quadrics. Each is described by a function,
+    // that maps a point relative to some center to coordinates, in which the
+    // surface has a simple equation. The function is linear, so it can be
+    // applied to the origin and direction of the ray separately.
+    let roots = match surface {
+        Surface::Swept(swept) => {
+            let path = swept.path.to_na();
+
+            match &swept.curve {
+                Curve::Line(line) => {
+                    let normal = line.direction.to_na().cross(&path);
+                    let [d0, d1] = [origin - line.origin.to_na().coords, dir]
+                        .map(|v| v.dot(&normal));
+
+                    solve_quadratic(0., d1, d0)
+                }
+                Curve::Circle(circle) => {
+                    swept_ellipse(&Ellipse::from(*circle), &path, origin, dir)?
+                }
+                Curve::Ellipse(ellipse) => {
+                    swept_ellipse(ellipse, &path, origin, dir)?
+                }
+                Curve::External(_) => return None,
+            }
+        }
+        Surface::External(_) => return None,
+    };
+
+    let mut hits: Vec<_> = roots
+        .into_iter()
+        .filter(|&t| t >= 0.)
+        .map(|t| {
+            let t = Scalar::from_f64(t);
+            Hit {
+                t,
+                point: ray.point_at(t),
+            }
+        })
+        .collect();
+    hits.sort_by_key(|hit| hit.t);
+
+    Some(hits)
+}
+
+/// Intersect a ray with the cylinder that is swept from an ellipse
+fn swept_ellipse(
+    ellipse: &Ellipse,
+    path: &Vector3<f64>,
+    origin: Vector3<f64>,
+    dir: Vector3<f64>,
+) -> Option<Roots> {
+    let [a, b] = [ellipse.a, ellipse.b].map(Vector::to_na);
+    let normal = a.cross(&b);
+
+    // A path within the plane of the ellipse sweeps it into a flat region,
+    // not a cylinder.
+    let path_along_normal = path.dot(&normal);
+    if path_along_normal == 0. {
+        return None;
+    }
+
+    // Project along the path into the plane of the ellipse, and compute the
+    // coordinates in units of its semi-axes there.
+    let coords = |v: Vector3<f64>| {
+        let v = v - path * (v.dot(&normal) / path_along_normal);
+        [v.dot(&a) / a.norm_squared(), v.dot(&b) / b.norm_squared()]
+    };
+    let [x0, y0] = coords(origin - ellipse.center.to_na().coords);
+    let [x1, y1] = coords(dir);
+
+    // x² + y² = 1
+    Some(solve_quadratic(
+        x1 * x1 + y1 * y1,
+        2. * (x0 * x1 + y0 * y1),
+        x0 * x0 + y0 * y0 - 1.,
+    ))
+}
+
+/// Find the real roots of `a * t² + b * t + c`
+///
+/// Also handles the linear case, `a == 0`. Returns no roots, if the equation
+/// is satisfied for all, or for no values of `t`.
+fn solve_quadratic(a: f64, b: f64, c: f64) -> Roots {
+    if a == 0. {
+        if b == 0. {
+            return SmallVec::new();
+        }
+        return smallvec![-c / b];
+    }
+
+    let discriminant = b * b - 4. * a * c;
+    if discriminant < 0. {
+        return SmallVec::new();
+    }
+
+    // Avoid the cancellation in `-b + sqrt(discriminant)`, by computing the
+    // root with the larger magnitude first.
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if q == 0. {
+        return smallvec![0.];
+    }
+    if discriminant == 0. {
+        return smallvec![q / a];
+    }
+
+    smallvec![q / a, c / q]
+}
+
+/// The roots of a quadratic equation, of which there are at most two
+type Roots = SmallVec<[f64; 2]>;
+
+/// Compute the intersections of a ray with a face
+///
+/// Returns the intersections in front of the ray's origin, ordered by their
+/// distance from it.
+///
+/// If [`ray_surface`] supports the surface of the face, the intersections
+/// with the surface are computed exactly, and those outside of the face's
+/// boundary are discarded. To decide that, the boundary is approximated
+/// within `tolerance`. Otherwise, the ray is intersected with the
+/// triangulation of the face, with the same tolerance.
+pub fn ray_face(ray: &Ray, face: &Face, tolerance: Tolerance) -> Vec<Hit> {
+    let triangles = match face {
+        Face::Face { surface, .. } => {
+            let surface = surface.get();
+
+            if let Some(hits) = ray_surface(ray, surface) {
+                let boundary = boundary(face, surface, tolerance);
+
+                return hits
+                    .into_iter()
+                    .filter(|hit| {
+                        let point = surface.point_model_to_surface(hit.point);
+                        contains(&boundary, point.native())
+                    })
+                    .collect();
+            }
+
+            let mut triangles = Vec::new();
+            face.triangles(tolerance, &mut triangles, &mut DebugInfo::new());
+            triangles
+        }
+        Face::Triangles(triangles) => triangles.clone(),
+    };
+
+    ray_triangles(ray, &triangles)
+}
+
+/// Find the face that a ray hits first
+///
+/// This is the B-rep equivalent of picking a triangle of the display mesh.
+/// Returns the face, and the intersection of the ray with it.
+pub fn ray_faces(
+    ray: &Ray,
+    faces: impl IntoIterator<Item = Handle<Face>>,
+    tolerance: Tolerance,
+) -> Option<(Handle<Face>, Hit)> {
+    faces
+        .into_iter()
+        .filter_map(|face| {
+            let hit =
+                ray_face(ray, face.get(), tolerance).into_iter().next()?;
+            Some((face, hit))
+        })
+        .min_by_key(|(_, hit)| hit.t)
+}
+
+/// Check whether a point is within the volume that the faces enclose
+///
+/// Casts a ray from the point, and counts how often it crosses the faces. If
+/// the faces form a closed shell, the point is inside, if that number is odd.
+/// Points on the faces can be classified either way.
+pub fn contains_point(
+    faces: impl IntoIterator<Item = Handle<Face>>,
+    point: Point<3>,
+    tolerance: Tolerance,
+) -> bool {
+    // A ray along an axis would often go exactly through the edges between
+    // faces, or the edges of their triangles. Hits there would be counted
+    // twice. A direction that isn't aligned with anything, makes that
+    // unlikely.
+    let ray = Ray {
+        origin: point,
+        dir: Vector::from([0.5773, 0.6271, 0.5229]),
+    };
+
+    let crossings: usize = faces
+        .into_iter()
+        .map(|face| ray_face(&ray, face.get(), tolerance).len())
+        .sum();
+
+    crossings % 2 == 1
+}
+
+fn ray_triangles(ray: &Ray, triangles: &[Triangle<3>]) -> Vec<Hit> {
+    let parry_ray = ray.to_parry();
+
+    let mut hits: Vec<_> = triangles
+        .iter()
+        .filter_map(|triangle| {
+            let t = triangle.to_parry().cast_local_ray(
+                &parry_ray,
+                f64::INFINITY,
+                true,
+            )?;
+            let t = Scalar::from_f64(t);
+
+            Some(Hit {
+                t,
+                point: ray.point_at(t),
+            })
+        })
+        .collect();
+    hits.sort_by_key(|hit| hit.t);
+
+    hits
+}
+
+/// Approximate the boundary of a face, in surface coordinates
+///
+/// On surfaces that go around periodically (see [`u_period`]), the
+/// u-coordinate jumps back to zero at the seam. Segments that cross the seam
+/// are unwrapped, and the whole boundary is repeated one period before and
+/// after itself, so every point in the surface's range of u-coordinates
+/// finds the parts of the boundary around it.
+fn boundary(
+    face: &Face,
+    surface: &Surface,
+    tolerance: Tolerance,
+) -> Vec<[Point<2>; 2]> {
+    let period = u_period(surface);
+    let mut boundary = Vec::new();
+
+    for cycle in face.cycles() {
+        let approx = Approximation::for_cycle(&cycle, tolerance);
+
+        boundary.extend(approx.segments.into_iter().map(|segment| {
+            let [a, mut b] = segment
+                .points()
+                .map(|point| surface.point_model_to_surface(point).native());
+
+            if let Some(period) = period {
+                if b.u - a.u > period / 2. {
+                    b.u -= period;
+                } else if a.u - b.u > period / 2. {
+                    b.u += period;
+                }
+            }
+
+            [a, b]
+        }));
+    }
+
+    if let Some(period) = period {
+        let shifted = |offset: Scalar| {
+            let offset = Vector::from([offset, Scalar::ZERO]);
+            boundary
+                .iter()
+                .map(move |&[a, b]| [a + offset, b + offset])
+                .collect::<Vec<_>>()
+        };
+
+        let before = shifted(-period);
+        let after = shifted(period);
+        boundary.extend(before);
+        boundary.extend(after);
+    }
+
+    boundary
+}
+
+/// The period of a surface's u-coordinate, if the surface goes around
+///
+/// Surfaces that are swept from a circle or ellipse go around their axis once
+/// for every full turn of u.
+fn u_period(surface: &Surface) -> Option<Scalar> {
+    match surface {
+        Surface::Swept(swept) => match swept.curve {
+            Curve::Circle(_) | Curve::Ellipse(_) => Some(Scalar::PI * 2.),
+            Curve::Line(_) | Curve::External(_) => None,
+        },
+        Surface::External(_) => None,
+    }
+}
+
+/// Check whether a point is within a boundary, in surface coordinates
+///
+/// Casts a ray in the direction of the v-axis. Unlike the u-axis, that
+/// direction doesn't go around on any of the surfaces that are supported
+/// here, so a ray along it crosses faces that go all the way around, like the
+/// side of a cylinder, where their boundary is.
+fn contains(boundary: &[[Point<2>; 2]], point: Point<2>) -> bool {
+    let mut inside = false;
+
+    for [a, b] in boundary {
+        // Each edge includes its lower end point, but not its upper one.
+        if (a.u > point.u) == (b.u > point.u) {
+            continue;
+        }
+
+        let t = a.v + (point.u - a.u) * (b.v - a.v) / (b.u - a.u) - point.v;
+        if t > Scalar::ZERO {
+            inside = !inside;
+        }
+    }
+
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::smallvec;
+
+    use crate::{
+        debug::DebugInfo,
+        kernel::{
+            algorithms::tolerance::Tolerance,
+            geometry::{surfaces::Swept, Circle, Curve, Line, Surface},
+            shape::Shape,
+            shapes::ToShape as _,
+            topology::{edges::Cycle, faces::Face, vertices::Vertex},
+        },
+        math::{Point, Scalar, Vector},
+    };
+
+    use super::{contains_point, ray_face, ray_faces, ray_surface, Ray};
+
+    fn ray(origin: [f64; 3], dir: [f64; 3]) -> Ray {
+        Ray {
+            origin: Point::from(origin),
+            dir: Vector::from(dir),
+        }
+    }
+
+    fn hits(ray: &Ray, surface: &Surface) -> Vec<f64> {
+        ray_surface(ray, surface)
+            .unwrap()
+            .into_iter()
+            .map(|hit| hit.t.into_f64())
+            .collect()
+    }
+
+    fn assert_hits(actual: Vec<f64>, expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-12, "{:?}", actual);
+        }
+    }
+
+    #[test]
+    fn ray_surface_should_intersect_quadrics() {
+        let plane = Surface::x_y_plane();
+        assert_hits(hits(&ray([1., 2., 3.], [0., 0., -2.]), &plane), &[1.5]);
+        assert_hits(hits(&ray([1., 2., 3.], [0., 0., 1.]), &plane), &[]);
+        assert_hits(hits(&ray([1., 2., 0.], [1., 0., 0.]), &plane), &[]);
+
+        let cylinder = Surface::Swept(Swept {
+            curve: Curve::Circle(Circle {
+                center: Point::from([0., 0., 0.]),
+                radius: Vector::from([2., 0.]),
+            }),
+            path: Vector::from([0., 0., 1.]),
+        });
+        assert_hits(
+            hits(&ray([-5., 0., 3.], [1., 0., 0.]), &cylinder),
+            &[3., 7.],
+        );
+        assert_hits(hits(&ray([0., 0., 3.], [1., 0., 0.]), &cylinder), &[2.]);
+        assert_hits(hits(&ray([-5., 3., 3.], [1., 0., 0.]), &cylinder), &[]);
+    }
+
+    #[test]
+    fn ray_face_should_respect_boundary() {
+        let tolerance = Tolerance::from_scalar(0.001).unwrap();
+        let mut shape = Shape::new();
+
+        let mut vertices = Vec::new();
+        for [x, y] in [[0., 0.], [2., 0.], [2., 1.], [0., 1.]] {
+            let point = shape.geometry().add_point(Point::from([x, y, 0.]));
+            vertices
+                .push(shape.topology().add_vertex(Vertex { point }).unwrap());
+        }
+        let edges = (0..vertices.len())
+            .map(|i| {
+                let b = vertices[(i + 1) % vertices.len()].clone();
+                shape
+                    .topology()
+                    .add_line_segment([vertices[i].clone(), b])
+                    .unwrap()
+            })
+            .collect();
+        let cycle = shape.topology().add_cycle(Cycle { edges }).unwrap();
+
+        let surface = shape.geometry().add_surface(Surface::Swept(Swept {
+            curve: Curve::Line(Line {
+                origin: Point::origin(),
+                direction: Vector::from([1., 0., 0.]),
+            }),
+            path: Vector::from([0., 1., 0.]),
+        }));
+        let face = Face::Face {
+            surface,
+            cycles: smallvec![cycle],
+        };
+
+        let inside =
+            ray_face(&ray([1.5, 0.5, 1.], [0., 0., -1.]), &face, tolerance);
+        assert_eq!(inside.len(), 1);
+        assert_eq!(inside[0].point, Point::from([1.5, 0.5, 0.]));
+
+        let outside =
+            ray_face(&ray([2.5, 0.5, 1.], [0., 0., -1.]), &face, tolerance);
+        assert!(outside.is_empty());
+    }
+
+    #[test]
+    fn ray_face_should_respect_boundary_across_seam() {
+        let tolerance = Tolerance::from_scalar(0.001).unwrap();
+        let mut shape = Shape::new();
+
+        // The side of a cylinder, as an exact face, bounded by its top and
+        // bottom circles.
+        let cycles = [0., 2.]
+            .map(|z| {
+                let circle = shape
+                    .topology()
+                    .add_circle(Point::from([0., 0., z]), Scalar::ONE)
+                    .unwrap();
+                shape
+                    .topology()
+                    .add_cycle(Cycle {
+                        edges: smallvec![circle],
+                    })
+                    .unwrap()
+            })
+            .into_iter()
+            .collect();
+        let surface = shape.geometry().add_surface(Surface::Swept(Swept {
+            curve: Curve::Circle(Circle {
+                center: Point::origin(),
+                radius: Vector::from([1., 0.]),
+            }),
+            path: Vector::from([0., 0., 2.]),
+        }));
+        let face = Face::Face { surface, cycles };
+
+        // Rays that hit the side on both sides of the seam, and right on it
+        for (origin, dir) in [
+            ([-5., 0.1, 1.], [1., 0., 0.]),
+            ([5., -0.1, 1.], [-1., 0., 0.]),
+            ([-5., 0., 1.], [1., 0., 0.]),
+        ] {
+            let hits = ray_face(&ray(origin, dir), &face, tolerance);
+            assert_eq!(hits.len(), 2, "{:?}", origin);
+        }
+
+        let above =
+            ray_face(&ray([-5., 0.1, 3.], [1., 0., 0.]), &face, tolerance);
+        assert!(above.is_empty());
+    }
+
+    #[test]
+    fn ray_faces_should_pick_and_classify_on_brep() {
+        let tolerance = Tolerance::from_scalar(0.001).unwrap();
+
+        // A cylinder, whose side is represented by triangles, and whose top
+        // and bottom are exact faces.
+        let cylinder: fj::Shape3d = fj::Sweep {
+            shape: fj::Circle {
+                center: [0., 0.],
+                radius: 1.,
+            }
+            .into(),
+            length: 2.,
+        }
+        .into();
+        let mut shape =
+            cylinder.to_shape(tolerance, &mut DebugInfo::new()).unwrap();
+        let faces: Vec<_> = shape.topology().faces().collect();
+
+        let (_, hit) = ray_faces(
+            &ray([0.2, 0.1, 5.], [0., 0., -1.]),
+            faces.clone(),
+            tolerance,
+        )
+        .unwrap();
+        assert_eq!(hit.point, Point::from([0.2, 0.1, 2.]));
+
+        let (_, hit) = ray_faces(
+            &ray([-5., 0., 1.], [1., 0., 0.]),
+            faces.clone(),
+            tolerance,
+        )
+        .unwrap();
+        assert!((hit.point.x + Scalar::ONE).abs() < 0.01.into());
+
+        assert!(ray_faces(
+            &ray([0., 0., 3.], [1., 0., 0.]),
+            faces.clone(),
+            tolerance
+        )
+        .is_none());
+
+        for (point, expected) in [
+            ([0., 0., 1.], true),
+            ([0.9, 0., 1.9], true),
+            ([1.1, 0., 1.], false),
+            ([0., 0., 2.1], false),
+        ] {
+            assert_eq!(
+                contains_point(faces.clone(), Point::from(point), tolerance),
+                expected,
+                "{:?}",
+                point
+            );
+        }
+    }
+}
@@ -1,4 +1,9 @@
 pub mod approximation;
+pub mod arena;
+pub mod deform;
+pub mod intersection;
 pub mod sweep;
+pub mod texture;
+pub mod tolerance;
 pub mod transform;
 pub mod triangulation;
@@ -8,18 +8,19 @@ use crate::{
             faces::Face,
             vertices::Vertex,
         },
+        Error,
     },
-    math::{Scalar, Transform, Vector},
+    math::{Transform, Vector},
 };
 
-use super::approximation::Approximation;
+use super::{approximation::Approximation, tolerance::Tolerance};
 
 /// Create a new shape by sweeping an existing one
 pub fn sweep_shape(
     mut shape_orig: Shape,
     path: Vector<3>,
-    tolerance: Scalar,
-) -> Shape {
+    tolerance: Tolerance,
+) -> Result<Shape, Error> {
     let mut shape = shape_orig.clone();
 
     let translation = Transform::translation(path);
@@ -30,10 +31,19 @@ pub fn sweep_shape(
     let mut vertices = HashMap::new();
     for vertex_orig in shape_orig.topology().vertices() {
         let point = shape.geometry().add_point(vertex_orig.point() + path);
-        let vertex = shape.topology().add_vertex(Vertex { point }).unwrap();
+        let vertex = shape.topology().add_vertex(Vertex { point })?;
         vertices.insert(vertex_orig, vertex);
     }
 
+    // Create the side edges, which connect the original vertices to the new
+    // ones. The side faces that they bound are not made up of edges yet (see
+    // below), but the side edges are still needed to display the shape.
+    for vertex_orig in shape_orig.topology().vertices() {
+        // Can't panic. We've added all vertices to `vertices` above.
+        let vertex = vertices.get(&vertex_orig).unwrap().clone();
+        shape.topology().add_line_segment([vertex_orig, vertex])?;
+    }
+
     // Create the new edges.
     let mut edges = HashMap::new();
     for edge_orig in shape_orig.topology().edges() {
@@ -49,7 +59,7 @@ pub fn sweep_shape(
             })
         });
 
-        let edge = shape.topology().add_edge(Edge { curve, vertices }).unwrap();
+        let edge = shape.topology().add_edge(Edge { curve, vertices })?;
         edges.insert(edge_orig, edge);
     }
 
@@ -66,7 +76,7 @@ pub fn sweep_shape(
             })
             .collect();
 
-        let cycle = shape.topology().add_cycle(Cycle { edges }).unwrap();
+        let cycle = shape.topology().add_cycle(Cycle { edges })?;
         cycles.insert(cycle_orig, cycle);
     }
 
@@ -94,14 +104,11 @@ pub fn sweep_shape(
             })
             .collect();
 
-        shape
-            .topology()
-            .add_face(Face::Face { surface, cycles })
-            .unwrap();
+        shape.topology().add_face(Face::Face { surface, cycles })?;
     }
 
-    // We could use `vertices` to create the side edges and faces here, but the
-    // side walls are created below, in triangle representation.
+    // We could use the side edges to create the side faces here, but the side
+    // walls are created below, in triangle representation.
 
     for cycle in shape_orig.topology().cycles() {
         let approx = Approximation::for_cycle(&cycle, tolerance);
@@ -132,16 +139,19 @@ pub fn sweep_shape(
     }
 
     for face in side_faces {
-        shape.topology().add_face(face).unwrap();
+        shape.topology().add_face(face)?;
     }
 
-    shape
+    Ok(shape)
 }
 
 #[cfg(test)]
 mod tests {
+    use smallvec::smallvec;
+
     use crate::{
         kernel::{
+            algorithms::tolerance::Tolerance,
             geometry::{surfaces::Swept, Surface},
             shape::{handle::Handle, Shape},
             topology::{edges::Cycle, faces::Face, vertices::Vertex},
@@ -158,8 +168,9 @@ mod tests {
         let mut swept = sweep_shape(
             sketch.shape,
             Vector::from([0., 0., 1.]),
-            Scalar::from_f64(0.),
-        );
+            Tolerance::from_scalar(Scalar::ONE).unwrap(),
+        )
+        .unwrap();
 
         let bottom_face = sketch.face.get().clone();
         let top_face =
@@ -185,6 +196,10 @@ mod tests {
         assert!(contains_bottom_face);
         assert!(contains_top_face);
 
+        // The edges of the bottom and top faces, and one side edge per vertex
+        // of the triangle.
+        assert_eq!(swept.topology().edges().count(), 9);
+
         // Side faces are not tested, as those use triangle representation. The
         // plan is to start testing them, as they are transitioned to b-rep.
     }
@@ -222,7 +237,7 @@ mod tests {
             let cycles = shape
                 .topology()
                 .add_cycle(Cycle {
-                    edges: vec![ab, bc, ca],
+                    edges: smallvec![ab, bc, ca],
                 })
                 .unwrap();
 
@@ -233,7 +248,7 @@ mod tests {
             ));
             let abc = Face::Face {
                 surface,
-                cycles: vec![cycles],
+                cycles: smallvec![cycles],
             };
 
             let face = shape.topology().add_face(abc).unwrap();
@@ -0,0 +1,370 @@
+use std::{
+    collections::{HashMap, HashSet},
+    f64::consts::TAU,
+};
+
+use nalgebra::{Point3, Vector3};
+
+use crate::{
+    analysis::manifold,
+    debug::DebugInfo,
+    kernel::{shape::Shape, topology::faces::Face, Error},
+    math::{Point, Triangle},
+};
+
+use super::{
+    deform::{subdivide, subdivide_edges},
+    tolerance::Tolerance,
+};
+
+/// The maximum angle between the normal of a triangle and the direction that a
+/// textured face must face, in degrees
+const MAX_ANGLE: f64 = 1.;
+
+/// Create a new shape, with a texture cut into some of the faces of an
+/// existing one
+///
+/// The faces of the original shape are approximated by triangles. Those of the
+/// textured faces are subdivided, until they are small enough to follow the
+/// pattern. Then the vertices of the textured faces are moved along their
+/// normal. Vertices that are shared with faces that aren't textured stay where
+/// they are, so the texture fades out towards the edges of the textured faces.
+///
+/// The resulting shape consists of a single face, made up of the triangles.
+pub fn texture_shape(
+    mut original: Shape,
+    texture: &fj::Texture,
+    tolerance: Tolerance,
+    debug_info: &mut DebugInfo,
+) -> Result<Shape, Error> {
+    // The faces are triangulated one by one, to know which triangles belong to
+    // the faces that are textured.
+    let mut triangles = Vec::new();
+    let mut faces = Vec::new();
+    for face in original.topology().faces() {
+        let start = triangles.len();
+        face.triangles(tolerance, &mut triangles, debug_info);
+        faces.push(start..triangles.len());
+    }
+
+    let mut textured = Shape::new();
+    if triangles.is_empty() {
+        return Ok(textured);
+    }
+
+    // Which way the triangles face depends on how the faces were
+    // triangulated. The pattern must be cut inwards, so orient them
+    // consistently, if possible.
+    if let Some(oriented) = manifold::orient(&triangles) {
+        triangles = oriented;
+    }
+
+    let triangles: Vec<_> = triangles
+        .into_iter()
+        .map(|triangle| triangle.points().map(Point::to_na))
+        .collect();
+
+    let mut selected = vec![false; triangles.len()];
+    for face in faces {
+        let is_textured = triangles[face.clone()]
+            .iter()
+            .all(|triangle| faces_towards(triangle, texture.faces));
+        if is_textured {
+            selected[face].fill(true);
+        }
+    }
+
+    let pattern = Pattern::new(texture, &triangles, &selected);
+
+    // Subdividing places points in a deterministic way, so the same point is
+    // bit-for-bit identical in all triangles that share it.
+    let key =
+        |point: &Point3<f64>| [point.x, point.y, point.z].map(f64::to_bits);
+    let edge = |triangle: &[Point3<f64>; 3], i: usize| {
+        let [a, b] = [triangle[i], triangle[(i + 1) % 3]].map(|p| key(&p));
+        [a.min(b), a.max(b)]
+    };
+
+    let textured_edges: HashSet<_> = triangles
+        .iter()
+        .zip(&selected)
+        .filter(|(_, &selected)| selected)
+        .flat_map(|(triangle, _)| (0..3).map(move |i| edge(triangle, i)))
+        .collect();
+
+    // Only the textured triangles are subdivided completely. Of the others,
+    // only the edges they share with textured triangles are split, so the
+    // edges between textured faces and others still match up.
+    let max_length = texture.pitch / 8.;
+    let mut subdivided = Vec::new();
+    let mut subdivided_selected = Vec::new();
+    for (triangle, selected) in triangles.into_iter().zip(selected) {
+        if selected {
+            subdivide(triangle, max_length, &mut subdivided);
+        } else {
+            let edges =
+                [0, 1, 2].map(|i| textured_edges.contains(&edge(&triangle, i)));
+            subdivide_edges(triangle, edges, max_length, &mut subdivided);
+        }
+        subdivided_selected.resize(subdivided.len(), selected);
+    }
+
+    let mut fixed = HashSet::new();
+    let mut normals: HashMap<_, Vector3<f64>> = HashMap::new();
+    for (triangle, &selected) in subdivided.iter().zip(&subdivided_selected) {
+        if selected {
+            let [a, b, c] = triangle;
+            let normal = (b - a).cross(&(c - a));
+            for point in triangle {
+                *normals.entry(key(point)).or_insert_with(Vector3::zeros) +=
+                    normal;
+            }
+        } else {
+            fixed.extend(triangle.iter().map(key));
+        }
+    }
+
+    let displaced = subdivided
+        .into_iter()
+        .zip(subdivided_selected)
+        .map(|(triangle, selected)| {
+            Triangle::from(triangle.map(|point| {
+                let key = key(&point);
+                if !selected || fixed.contains(&key) {
+                    return Point::from_na(point);
+                }
+
+                // Can't panic. All points of selected triangles have a normal.
+                let normal = normals[&key].normalize();
+                let depth = texture.depth * pattern.groove(&point);
+                Point::from_na(point - normal * depth)
+            }))
+        })
+        .collect();
+
+    textured.topology().add_face(Face::Triangles(displaced))?;
+
+    Ok(textured)
+}
+
+/// Indicate whether a triangle faces the way that textured faces must face
+fn faces_towards(triangle: &[Point3<f64>; 3], faces: fj::TextureFaces) -> bool {
+    let [a, b, c] = triangle;
+    let normal = (b - a).cross(&(c - a));
+    let norm = normal.norm();
+    if norm == 0. {
+        // Degenerate triangles don't face anywhere, so they don't prevent a
+        // face from being textured.
+        return true;
+    }
+
+    let normal = normal / norm;
+    match faces {
+        fj::TextureFaces::Sides => {
+            normal.z.abs() <= MAX_ANGLE.to_radians().sin()
+        }
+        fj::TextureFaces::Top | fj::TextureFaces::Facing(_) => {
+            normal.dot(&direction(faces)) >= MAX_ANGLE.to_radians().cos()
+        }
+    }
+}
+
+/// The direction that the textured faces face, if they are planar
+///
+/// # Panics
+///
+/// Panics, if `faces` is [`fj::TextureFaces::Sides`].
+fn direction(faces: fj::TextureFaces) -> Vector3<f64> {
+    match faces {
+        fj::TextureFaces::Sides => {
+            panic!("Sides don't face in a single direction")
+        }
+        fj::TextureFaces::Top => Vector3::z(),
+        fj::TextureFaces::Facing(direction) => {
+            Vector3::from(direction).normalize()
+        }
+    }
+}
+
+/// The pattern of a texture, mapped onto the textured faces
+struct Pattern {
+    pattern: fj::TexturePattern,
+    faces: fj::TextureFaces,
+    pitch: f64,
+
+    /// The number of grooves around the z-axis, for textures on the sides
+    count: f64,
+
+    /// The axes of the pattern, for textures on planar faces
+    ///
+    /// The grooves of ribs run along the second axis.
+    axes: [Vector3<f64>; 2],
+}
+
+impl Pattern {
+    fn new(
+        texture: &fj::Texture,
+        triangles: &[[Point3<f64>; 3]],
+        selected: &[bool],
+    ) -> Self {
+        let mut pitch = texture.pitch;
+        let mut count = 0.;
+        let mut axes = [Vector3::x(), Vector3::y()];
+
+        if let fj::TextureFaces::Sides = texture.faces {
+            // The pattern must repeat a whole number of times around the
+            // z-axis, or there's a seam where it comes back around.
+            let distances: Vec<_> = triangles
+                .iter()
+                .zip(selected)
+                .filter(|(_, &selected)| selected)
+                .flat_map(|(triangle, _)| triangle.iter())
+                .map(|point| point.x.hypot(point.y))
+                .collect();
+            let distance =
+                distances.iter().sum::<f64>() / distances.len().max(1) as f64;
+
+            count = (TAU * distance / pitch).round().max(1.);
+            pitch = TAU * distance / count;
+        } else {
+            // Project the y-axis into the plane of the faces, to get the
+            // direction of the ribs. Fall back to the z-axis, if the faces face
+            // along the y-axis.
+            let normal = direction(texture.faces);
+            let mut v = Vector3::y() - normal * normal.y;
+            if v.norm() < 1e-6 {
+                v = Vector3::z() - normal * normal.z;
+            }
+            let v = v.normalize();
+            axes = [v.cross(&normal), v];
+        }
+
+        Self {
+            pattern: texture.pattern,
+            faces: texture.faces,
+            pitch,
+            count,
+            axes,
+        }
+    }
+
+    /// Compute how deep the groove is at a point, as a fraction of its depth
+    fn groove(&self, point: &Point3<f64>) -> f64 {
+        // The coordinates of the point in the pattern, in multiples of the
+        // pitch
+        let [u, v] = match self.faces {
+            fj::TextureFaces::Sides => {
+                let angle = point.y.atan2(point.x);
+                [angle / TAU * self.count, point.z / self.pitch]
+            }
+            fj::TextureFaces::Top | fj::TextureFaces::Facing(_) => {
+                self.axes.map(|axis| point.coords.dot(&axis) / self.pitch)
+            }
+        };
+
+        match self.pattern {
+            fj::TexturePattern::Ribs => v_groove(u),
+            fj::TexturePattern::Knurl => v_groove(u + v).max(v_groove(u - v)),
+        }
+    }
+}
+
+/// A V-shaped groove, that is deepest at whole numbers, and flat in between
+///
+/// Returns `1` at the bottom of the groove, and `0` on the ridge between
+/// grooves.
+fn v_groove(t: f64) -> f64 {
+    1. - 2. * (t - t.round()).abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use approx::assert_abs_diff_eq;
+
+    use crate::testing::{cuboid, Evaluated};
+
+    use super::v_groove;
+
+    #[test]
+    fn v_groove_should_go_from_bottom_to_ridge() {
+        assert_eq!(v_groove(3.), 1.);
+        assert_eq!(v_groove(3.25), 0.5);
+        assert_eq!(v_groove(-3.5), 0.);
+    }
+
+    #[test]
+    fn ribs_should_remove_half_the_groove_volume() {
+        let cylinder: fj::Shape3d = fj::Sweep {
+            shape: fj::Circle {
+                center: [0., 0.],
+                radius: 10.,
+            }
+            .into(),
+            length: 10.,
+        }
+        .into();
+        let plain = Evaluated::new(&cylinder.clone().into());
+
+        let textured = Evaluated::new(
+            &fj::Texture {
+                shape: cylinder,
+                pattern: fj::TexturePattern::Ribs,
+                faces: fj::TextureFaces::Sides,
+                pitch: 2.,
+                depth: 0.4,
+            }
+            .into(),
+        );
+
+        textured.assert_watertight();
+
+        // On average, the V-shaped grooves are half as deep as their depth.
+        // They fade out towards the top and bottom, so a bit less is removed.
+        let removed = plain.volume().unwrap() - textured.volume().unwrap();
+        let expected = 2. * PI * 10. * 10. * 0.4 / 2.;
+        assert_abs_diff_eq!(removed, expected, epsilon = expected * 0.1);
+
+        // The grooves are cut into the sides, so the top and bottom stay
+        // where they are.
+        textured.assert_aabb([-10., -10., 0.], [10., 10., 10.]);
+
+        // Only the edges that the top shares with the sides are subdivided, so
+        // its inside stays coarse.
+        let top_edges = textured
+            .triangles()
+            .iter()
+            .map(|triangle| triangle.points().map(|point| point.to_na()))
+            .filter(|points| points.iter().all(|point| point.z == 10.))
+            .flat_map(|[a, b, c]| [b - a, c - b, a - c])
+            .map(|edge| edge.norm());
+        assert!(top_edges.fold(0., f64::max) > 2.);
+    }
+
+    #[test]
+    fn facing_should_texture_the_faces_facing_the_direction() {
+        let cube: fj::Shape3d = cuboid([10., 10., 10.]).into();
+        let plain = Evaluated::new(&cube.clone().into());
+
+        let textured = Evaluated::new(
+            &fj::Texture {
+                shape: cube,
+                pattern: fj::TexturePattern::Ribs,
+                faces: fj::TextureFaces::Facing([0., 0., -1.]),
+                pitch: 1.,
+                depth: 0.2,
+            }
+            .into(),
+        );
+
+        textured.assert_watertight();
+
+        // Only the bottom is textured, so the volume that's removed is that of
+        // the grooves in it.
+        let removed = plain.volume().unwrap() - textured.volume().unwrap();
+        let expected = 10. * 10. * 0.2 / 2.;
+        assert_abs_diff_eq!(removed, expected, epsilon = expected * 0.1);
+        textured.assert_aabb([0., 0., 0.], [10., 10., 10.]);
+    }
+}
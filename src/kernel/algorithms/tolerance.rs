@@ -0,0 +1,95 @@
+use crate::math::{Aabb, Scalar};
+
+/// A tolerance value
+///
+/// A tolerance value defines how far an approximation of a curve, or the
+/// triangulation of a shape, may deviate from the actual geometry. It must be
+/// larger than zero, as approximating anything exactly would take an infinite
+/// number of points. This is checked when a `Tolerance` is constructed, so
+/// code that uses one doesn't need to check it again.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Tolerance(Scalar);
+
+impl Tolerance {
+    /// Construct a tolerance from a scalar
+    ///
+    /// Returns an error, if the scalar is not larger than zero, or if it's
+    /// infinite.
+    pub fn from_scalar(
+        scalar: impl Into<Scalar>,
+    ) -> Result<Self, InvalidTolerance> {
+        let scalar = scalar.into();
+
+        if scalar <= Scalar::ZERO || !scalar.into_f64().is_finite() {
+            return Err(InvalidTolerance(scalar));
+        }
+
+        Ok(Self(scalar))
+    }
+
+    /// Compute a reasonable default tolerance for a shape with the given
+    /// bounding box
+    ///
+    /// The tolerance is derived from the smallest non-zero extent of the
+    /// bounding box, so it is small in relation to the shape's features, but
+    /// doesn't result in huge triangulations either.
+    pub fn from_bounding_box(aabb: &Aabb<3>) -> Self {
+        let mut min_extent = Scalar::MAX;
+        for extent in aabb.size().components() {
+            if extent > Scalar::ZERO && extent < min_extent {
+                min_extent = extent;
+            }
+        }
+
+        // An empty bounding box leaves `min_extent` at the maximum value,
+        // which still results in a valid tolerance.
+        Self(min_extent / Scalar::from_f64(1000.))
+    }
+
+    /// Access the scalar value of the tolerance
+    pub fn inner(&self) -> Scalar {
+        self.0
+    }
+}
+
+/// Returned by [`Tolerance::from_scalar`], if the value is invalid
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid tolerance ({0:?}); must be larger than zero and finite")]
+pub struct InvalidTolerance(pub Scalar);
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{Aabb, Point, Scalar};
+
+    use super::Tolerance;
+
+    #[test]
+    fn from_scalar_should_reject_invalid_values() {
+        assert_eq!(
+            Tolerance::from_scalar(0.1).unwrap().inner(),
+            Scalar::from_f64(0.1)
+        );
+
+        assert!(Tolerance::from_scalar(0.).is_err());
+        assert!(Tolerance::from_scalar(-0.1).is_err());
+        assert!(Tolerance::from_scalar(f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn from_bounding_box_should_use_smallest_extent() {
+        let aabb = Aabb {
+            min: Point::from([0., 0., 0.]),
+            max: Point::from([4., 2., 0.]),
+        };
+        assert_eq!(
+            Tolerance::from_bounding_box(&aabb).inner(),
+            Scalar::from_f64(0.002)
+        );
+
+        let empty = Aabb {
+            min: Point::from([1., 1., 1.]),
+            max: Point::from([1., 1., 1.]),
+        };
+        assert!(Tolerance::from_bounding_box(&empty).inner() > Scalar::ZERO);
+    }
+}
@@ -1,3 +1,7 @@
+use std::collections::HashMap;
+
+use smallvec::SmallVec;
+
 use crate::{
     kernel::{
         shape::Shape,
@@ -6,10 +10,13 @@ use crate::{
             faces::Face,
             vertices::Vertex,
         },
+        Error,
     },
-    math::Transform,
+    math::{Point, Transform},
 };
 
+use super::tolerance::Tolerance;
+
 /// Create a new shape that is a transformed version of an existing one
 ///
 /// # Implementation note
@@ -20,57 +27,61 @@ use crate::{
 ///
 /// Addressing the shortcomings in this method probably doesn't make sense,
 /// except as a side effect of addressing the shortcomings of `Shape`.
-pub fn transform_shape(mut original: Shape, transform: &Transform) -> Shape {
+///
+/// Faces in triangle representation, like the side walls of sweeps, were
+/// built from the approximations of edges, using `tolerance`. Their vertices
+/// are moved to the approximations of the transformed edges, so they stay
+/// connected to the faces those edges bound.
+pub fn transform_shape(
+    mut original: Shape,
+    transform: &Transform,
+    tolerance: Tolerance,
+) -> Result<Shape, Error> {
+    let approximations =
+        transformed_approximations(&mut original, transform, tolerance);
+
     let mut transformed = Shape::new();
 
     for face in original.topology().faces() {
         let face = match face.get().clone() {
             Face::Face { cycles, surface } => {
-                let mut cycles_trans = Vec::new();
+                let mut cycles_trans = SmallVec::new();
 
                 for cycle in cycles {
-                    let mut edges = Vec::new();
+                    let mut edges = SmallVec::new();
 
                     for edge in &cycle.edges {
                         let curve = transformed
                             .geometry()
                             .add_curve(edge.curve().transform(transform));
 
-                        let vertices =
-                            edge.vertices().clone().map(|vertices| {
-                                vertices.map(|vertex| {
-                                    let point =
-                                        transformed.geometry().add_point(
-                                            transform.transform_point(
-                                                &vertex.point(),
-                                            ),
-                                        );
-
-                                    transformed
-                                        .topology()
-                                        .add_vertex(Vertex { point })
-                                        .unwrap()
-                                })
-                            });
+                        let mut add_vertex = |vertex: Vertex| {
+                            let point = transformed.geometry().add_point(
+                                transform.transform_point(&vertex.point()),
+                            );
+                            transformed.topology().add_vertex(Vertex { point })
+                        };
+                        let vertices = match edge.vertices() {
+                            Some([a, b]) => {
+                                Some([add_vertex(a)?, add_vertex(b)?])
+                            }
+                            None => None,
+                        };
 
                         let edge = Edge { curve, vertices };
-                        let edge =
-                            transformed.topology().add_edge(edge).unwrap();
+                        let edge = transformed.topology().add_edge(edge)?;
 
                         edges.push(edge);
                     }
 
                     cycles_trans.push(
-                        transformed
-                            .topology()
-                            .add_cycle(Cycle { edges })
-                            .unwrap(),
+                        transformed.topology().add_cycle(Cycle { edges })?,
                     );
                 }
 
                 let surface = transformed
                     .geometry()
-                    .add_surface(surface.transform(transform));
+                    .add_surface(surface.get().clone().transform(transform));
 
                 Face::Face {
                     cycles: cycles_trans,
@@ -79,15 +90,57 @@ pub fn transform_shape(mut original: Shape, transform: &Transform) -> Shape {
             }
             Face::Triangles(mut triangles) => {
                 for triangle in &mut triangles {
-                    *triangle = transform.transform_triangle(triangle);
+                    *triangle = triangle
+                        .points()
+                        .map(|point| match approximations.get(&point) {
+                            Some(point) => *point,
+                            None => transform.transform_point(&point),
+                        })
+                        .into();
                 }
 
                 Face::Triangles(triangles)
             }
         };
 
-        transformed.topology().add_face(face).unwrap();
+        transformed.topology().add_face(face)?;
+    }
+
+    Ok(transformed)
+}
+
+/// Map the points of all edge approximations to the transformed ones
+///
+/// Transforming a curve can change how it is approximated. A circle that is
+/// rotated out of the xy-plane becomes an ellipse, for example, whose points
+/// differ from the transformed points of the circle in the last bits. Faces
+/// that were built from the approximation of the original curve use the
+/// points of the transformed curve's approximation instead, so they still
+/// share their vertices with the faces that the edges bound.
+///
+/// Both approximations have the same number of points, unless rounding
+/// changes the size of the curve enough to require another one. The points of
+/// those curves are transformed like all others.
+fn transformed_approximations(
+    original: &mut Shape,
+    transform: &Transform,
+    tolerance: Tolerance,
+) -> HashMap<Point<3>, Point<3>> {
+    let mut approximations = HashMap::new();
+
+    for edge in original.topology().edges() {
+        let mut points = Vec::new();
+        edge.curve().approx(tolerance, &mut points);
+
+        let mut transformed = Vec::new();
+        edge.curve()
+            .transform(transform)
+            .approx(tolerance, &mut transformed);
+
+        if points.len() == transformed.len() {
+            approximations.extend(points.into_iter().zip(transformed));
+        }
     }
 
-    transformed
+    approximations
 }
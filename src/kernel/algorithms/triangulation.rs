@@ -3,14 +3,39 @@ use spade::HasPosition;
 
 use crate::{kernel::geometry, math::Scalar};
 
-/// Create a Delaunay triangulation of all points
+/// Create a constrained Delaunay triangulation of all points
+///
+/// Each segment in `constraints` becomes an edge of the triangulation, unless
+/// it crosses a segment that was added before it. This makes sure that no
+/// triangle crosses the boundary of a face, if the segments of that boundary
+/// are passed as constraints, which is required for concave faces.
+///
+/// The whole convex hull of the points is triangulated. It's up to the caller
+/// to remove the triangles that are outside of the face.
 pub fn triangulate(
-    points: Vec<geometry::Point<2>>,
+    points: &[geometry::Point<2>],
+    constraints: &[[geometry::Point<2>; 2]],
 ) -> Vec<[geometry::Point<2>; 3]> {
     use spade::Triangulation as _;
 
-    let triangulation = spade::DelaunayTriangulation::<_>::bulk_load(points)
-        .expect("Inserted invalid values into triangulation");
+    let mut triangulation = spade::ConstrainedDelaunayTriangulation::<_>::new();
+    for &point in points {
+        triangulation
+            .insert(point)
+            .expect("Inserted invalid values into triangulation");
+    }
+    for &[a, b] in constraints {
+        // Inserting a point that's already there returns the existing vertex.
+        let [a, b] = [a, b].map(|point| {
+            triangulation
+                .insert(point)
+                .expect("Inserted invalid values into triangulation")
+        });
+
+        if a != b && triangulation.can_add_constraint(a, b) {
+            triangulation.add_constraint(a, b);
+        }
+    }
 
     let mut triangles = Vec::new();
     for triangle in triangulation.inner_faces() {
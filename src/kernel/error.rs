@@ -0,0 +1,45 @@
+use crate::math::Point;
+
+use super::shape::{validate::Validatable, ValidationError};
+
+/// An error that can occur in the CAD kernel
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// An object failed validation, when it was added to a shape
+    ///
+    /// Contains the positions of the objects that caused the error, as far as
+    /// they are known. See [`ValidationError::positions`].
+    #[error("Invalid {object}: {message}")]
+    Validation {
+        /// The kind of object that failed validation
+        object: &'static str,
+
+        /// The message of the validation error
+        message: String,
+
+        /// The positions of the objects that caused the error
+        positions: Vec<Point<3>>,
+    },
+
+    /// The shape uses a feature that is not supported yet
+    ///
+    /// Contains a description of the feature.
+    #[error("Not supported yet: {0}")]
+    Unsupported(&'static str),
+
+    /// A deformation can't be applied to the shape
+    ///
+    /// Contains a description of the problem.
+    #[error("Invalid deformation: {0}")]
+    Deformation(String),
+}
+
+impl<T: Validatable> From<ValidationError<T>> for Error {
+    fn from(err: ValidationError<T>) -> Self {
+        Self::Validation {
+            object: T::NAME,
+            message: err.to_string(),
+            positions: err.positions(),
+        }
+    }
+}
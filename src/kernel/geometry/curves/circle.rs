@@ -1,6 +1,9 @@
 use std::f64::consts::PI;
 
-use crate::math::{Point, Scalar, Transform, Vector};
+use crate::{
+    kernel::algorithms::tolerance::Tolerance,
+    math::{Point, Scalar, Transform, Vector},
+};
 
 /// A circle
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
@@ -75,7 +78,7 @@ impl Circle {
         Vector::from([x, y, Scalar::ZERO])
     }
 
-    pub fn approx(&self, tolerance: Scalar, out: &mut Vec<Point<3>>) {
+    pub fn approx(&self, tolerance: Tolerance, out: &mut Vec<Point<3>>) {
         let radius = self.radius.magnitude();
 
         // To approximate the circle, we use a regular polygon for which
@@ -93,8 +96,11 @@ impl Circle {
         }
     }
 
-    fn number_of_vertices(tolerance: Scalar, radius: Scalar) -> u64 {
-        assert!(tolerance > Scalar::ZERO);
+    pub(super) fn number_of_vertices(
+        tolerance: Tolerance,
+        radius: Scalar,
+    ) -> u64 {
+        let tolerance = tolerance.inner();
         if tolerance > radius / Scalar::TWO {
             3
         } else {
@@ -109,7 +115,10 @@ impl Circle {
 mod tests {
     use std::f64::consts::{FRAC_PI_2, PI};
 
-    use crate::math::{Point, Scalar, Vector};
+    use crate::{
+        kernel::algorithms::tolerance::Tolerance,
+        math::{Point, Scalar, Vector},
+    };
 
     use super::Circle;
 
@@ -152,7 +161,13 @@ mod tests {
             let tolerance = tolerance.into();
             let radius = radius.into();
 
-            assert_eq!(n, Circle::number_of_vertices(tolerance, radius));
+            assert_eq!(
+                n,
+                Circle::number_of_vertices(
+                    Tolerance::from_scalar(tolerance).unwrap(),
+                    radius
+                )
+            );
 
             assert!(calculate_error(radius, n) <= tolerance);
             if n > 3 {
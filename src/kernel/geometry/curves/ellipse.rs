@@ -0,0 +1,191 @@
+use std::f64::consts::PI;
+
+use crate::{
+    kernel::algorithms::tolerance::Tolerance,
+    math::{Aabb, Point, Scalar, Transform, Vector},
+};
+
+use super::Circle;
+
+/// An ellipse
+///
+/// The ellipse is defined by its center and two semi-axes, which must be
+/// perpendicular to each other. A point on the ellipse, at the curve coordinate
+/// `t`, is `center + a * cos(t) + b * sin(t)`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Ellipse {
+    /// The center point of the ellipse
+    pub center: Point<3>,
+
+    /// The first semi-axis of the ellipse
+    ///
+    /// Points from the center to the point on the circumference that defines
+    /// the origin of the ellipse's 1-dimensional curve coordinate system.
+    pub a: Vector<3>,
+
+    /// The second semi-axis of the ellipse
+    ///
+    /// Points from the center to the point on the circumference at the curve
+    /// coordinate `PI / 2.`.
+    pub b: Vector<3>,
+}
+
+impl Ellipse {
+    /// Access the origin of the curve's coordinate system
+    pub fn origin(&self) -> Point<3> {
+        self.center
+    }
+
+    #[must_use]
+    pub fn transform(self, transform: &Transform) -> Self {
+        Self {
+            center: transform.transform_point(&self.center),
+            a: transform.transform_vector(&self.a),
+            b: transform.transform_vector(&self.b),
+        }
+    }
+
+    /// Compute the axis-aligned bounding box of the ellipse
+    ///
+    /// Along each axis, `a * cos(t) + b * sin(t)` ranges from `-sqrt(a^2 +
+    /// b^2)` to `sqrt(a^2 + b^2)`, so the box fits the ellipse exactly.
+    pub fn aabb(&self) -> Aabb<3> {
+        let [a, b] = [self.a, self.b].map(|axis| axis.components());
+        let extent = Vector::from(
+            [0, 1, 2].map(|i| (a[i] * a[i] + b[i] * b[i]).into_f64().sqrt()),
+        );
+
+        Aabb {
+            min: self.center + -extent,
+            max: self.center + extent,
+        }
+    }
+
+    /// Convert a point in model coordinates to curve coordinates
+    ///
+    /// Converts the provided point into curve coordinates between `0.`
+    /// (inclusive) and `PI * 2.` (exclusive).
+    ///
+    /// Like [`Circle::point_model_to_curve`], this projects the point onto
+    /// the ellipse, and the same caveats apply.
+    pub fn point_model_to_curve(&self, point: &Point<3>) -> Point<1> {
+        let v = point - self.center;
+
+        let x = v.scalar_projection_onto(&self.a) / self.a.magnitude();
+        let y = v.scalar_projection_onto(&self.b) / self.b.magnitude();
+
+        let atan = Scalar::atan2(y, x);
+        let coord = if atan >= Scalar::ZERO {
+            atan
+        } else {
+            atan + Scalar::PI * 2.
+        };
+        Point::from([coord])
+    }
+
+    /// Convert a point on the curve into model coordinates
+    pub fn point_curve_to_model(&self, point: &Point<1>) -> Point<3> {
+        self.center + self.vector_curve_to_model(&point.coords)
+    }
+
+    /// Convert a vector on the curve into model coordinates
+    pub fn vector_curve_to_model(&self, vector: &Vector<1>) -> Vector<3> {
+        let (sin, cos) = vector.t.sin_cos();
+        self.a * cos + self.b * sin
+    }
+
+    pub fn approx(&self, tolerance: Tolerance, out: &mut Vec<Point<3>>) {
+        // Where the ellipse is curved the most, it is curved less than a
+        // circle with the radius of its longer semi-axis. Approximating it
+        // with as many vertices as that circle keeps it within the tolerance.
+        let radius = self.a.magnitude().max(self.b.magnitude());
+        let n = Circle::number_of_vertices(tolerance, radius);
+
+        for i in 0..n {
+            let angle = 2. * PI / n as f64 * i as f64;
+            let point = self.point_curve_to_model(&Point::from([angle]));
+            out.push(point);
+        }
+    }
+}
+
+impl From<Circle> for Ellipse {
+    fn from(circle: Circle) -> Self {
+        // The curve coordinates of a circle start at the positive x-axis,
+        // regardless of the direction of its radius vector.
+        let radius = circle.radius.magnitude();
+
+        Self {
+            center: circle.center,
+            a: Vector::from([radius, Scalar::ZERO, Scalar::ZERO]),
+            b: Vector::from([Scalar::ZERO, radius, Scalar::ZERO]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    use approx::assert_abs_diff_eq;
+
+    use crate::{
+        kernel::algorithms::tolerance::Tolerance,
+        math::{Point, Scalar, Vector},
+    };
+
+    use super::Ellipse;
+
+    #[test]
+    fn point_model_to_curve() {
+        let ellipse = Ellipse {
+            center: Point::from([1., 2., 3.]),
+            a: Vector::from([2., 0., 0.]),
+            b: Vector::from([0., 1., 0.]),
+        };
+
+        for t in [0., FRAC_PI_2, PI, 4.] {
+            let point = ellipse.point_curve_to_model(&Point::from([t]));
+            assert_abs_diff_eq!(
+                ellipse.point_model_to_curve(&point),
+                Point::from([t]),
+                epsilon = 1e-12
+            );
+        }
+
+        assert_abs_diff_eq!(
+            ellipse.point_curve_to_model(&Point::from([FRAC_PI_2])),
+            Point::from([1., 3., 3.]),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn approx() {
+        let ellipse = Ellipse {
+            center: Point::origin(),
+            a: Vector::from([4., 0., 0.]),
+            b: Vector::from([0., 1., 0.]),
+        };
+        let tolerance = Tolerance::from_scalar(0.01).unwrap();
+
+        let mut points = Vec::new();
+        ellipse.approx(tolerance, &mut points);
+
+        // The midpoints between neighboring vertices must be within the
+        // tolerance of the ellipse.
+        for (i, a) in points.iter().enumerate() {
+            let b = points[(i + 1) % points.len()];
+            let [x, y, _]: [f64; 3] =
+                (*a + (b - *a) * Scalar::from_f64(0.5)).coords.into();
+
+            let distance = (0..10_000)
+                .map(|j| {
+                    let t = 2. * PI * j as f64 / 10_000.;
+                    f64::hypot(4. * t.cos() - x, t.sin() - y)
+                })
+                .fold(f64::INFINITY, f64::min);
+            assert!(distance <= tolerance.inner().into_f64());
+        }
+    }
+}
@@ -1,9 +1,16 @@
 mod circle;
+mod ellipse;
 mod line;
 
-use crate::math::{Point, Scalar, Transform, Vector};
+use crate::{
+    kernel::{
+        algorithms::tolerance::Tolerance,
+        geometry::{External, ExternalCurve},
+    },
+    math::{Point, Scalar, Transform, Vector},
+};
 
-pub use self::{circle::Circle, line::Line};
+pub use self::{circle::Circle, ellipse::Ellipse, line::Line};
 
 /// A one-dimensional shape
 ///
@@ -14,13 +21,22 @@ pub use self::{circle::Circle, line::Line};
 /// The nomenclature is inspired by Boundary Representation Modelling Techniques
 /// by Ian Stroud. "Curve" refers to unbounded one-dimensional geometry, while
 /// while edges are bounded portions of curves.
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+///
+/// Curve types that the kernel doesn't know about can be added as
+/// [`Curve::External`].
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub enum Curve {
     /// A circle
     Circle(Circle),
 
+    /// An ellipse
+    Ellipse(Ellipse),
+
     /// A line
     Line(Line),
+
+    /// A curve that is defined outside of the kernel
+    External(External<dyn ExternalCurve>),
 }
 
 impl Curve {
@@ -28,15 +44,29 @@ impl Curve {
     pub fn origin(&self) -> Point<3> {
         match self {
             Self::Circle(curve) => curve.origin(),
+            Self::Ellipse(curve) => curve.origin(),
             Self::Line(curve) => curve.origin(),
+            Self::External(curve) => curve.origin(),
         }
     }
 
     #[must_use]
     pub fn transform(self, transform: &Transform) -> Self {
         match self {
-            Self::Circle(curve) => Self::Circle(curve.transform(transform)),
+            Self::Circle(curve) => {
+                // `Circle` can only represent circles that are parallel to the
+                // xy-plane. Once a circle is rotated out of that, it needs to
+                // become an ellipse, to be represented exactly.
+                let ellipse = Ellipse::from(curve).transform(transform);
+                if ellipse.a.z == Scalar::ZERO && ellipse.b.z == Scalar::ZERO {
+                    Self::Circle(curve.transform(transform))
+                } else {
+                    Self::Ellipse(ellipse)
+                }
+            }
+            Self::Ellipse(curve) => Self::Ellipse(curve.transform(transform)),
             Self::Line(curve) => Self::Line(curve.transform(transform)),
+            Self::External(curve) => curve.transform(transform),
         }
     }
 
@@ -52,7 +82,9 @@ impl Curve {
     pub fn point_model_to_curve(&self, point: &Point<3>) -> Point<1> {
         match self {
             Self::Circle(curve) => curve.point_model_to_curve(point),
+            Self::Ellipse(curve) => curve.point_model_to_curve(point),
             Self::Line(curve) => curve.point_model_to_curve(point),
+            Self::External(curve) => curve.point_model_to_curve(point),
         }
     }
 
@@ -60,7 +92,9 @@ impl Curve {
     pub fn point_curve_to_model(&self, point: &Point<1>) -> Point<3> {
         match self {
             Self::Circle(curve) => curve.point_curve_to_model(point),
+            Self::Ellipse(curve) => curve.point_curve_to_model(point),
             Self::Line(curve) => curve.point_curve_to_model(point),
+            Self::External(curve) => curve.point_curve_to_model(point),
         }
     }
 
@@ -68,7 +102,9 @@ impl Curve {
     pub fn vector_curve_to_model(&self, point: &Vector<1>) -> Vector<3> {
         match self {
             Self::Circle(curve) => curve.vector_curve_to_model(point),
+            Self::Ellipse(curve) => curve.vector_curve_to_model(point),
             Self::Line(curve) => curve.vector_curve_to_model(point),
+            Self::External(curve) => curve.vector_curve_to_model(point),
         }
     }
 
@@ -87,10 +123,52 @@ impl Curve {
     /// The `approximate_between` methods of the curves then need to make sure
     /// to only return points in between those vertices, not the vertices
     /// themselves.
-    pub fn approx(&self, tolerance: Scalar, out: &mut Vec<Point<3>>) {
+    pub fn approx(&self, tolerance: Tolerance, out: &mut Vec<Point<3>>) {
         match self {
             Self::Circle(circle) => circle.approx(tolerance, out),
+            Self::Ellipse(ellipse) => ellipse.approx(tolerance, out),
             Self::Line(_) => {}
+            Self::External(curve) => curve.approx(tolerance, out),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use approx::assert_abs_diff_eq;
+    use parry3d_f64::math::Isometry;
+
+    use crate::math::{Point, Transform, Vector};
+
+    use super::{Circle, Curve};
+
+    #[test]
+    fn transform_should_keep_circles_exact() {
+        let circle = Curve::Circle(Circle {
+            center: Point::origin(),
+            radius: Vector::from([2., 0.]),
+        });
+
+        let translation = Transform::translation(Vector::from([1., 2., 3.]));
+        assert!(matches!(
+            circle.clone().transform(&translation),
+            Curve::Circle(_)
+        ));
+
+        let rotation: Transform =
+            Isometry::rotation(nalgebra::Vector3::x() * FRAC_PI_2).into();
+        let rotated = circle.clone().transform(&rotation);
+        assert!(matches!(rotated, Curve::Ellipse(_)));
+
+        for t in [0., FRAC_PI_2, 2.] {
+            let point = Point::from([t]);
+            assert_abs_diff_eq!(
+                rotated.point_curve_to_model(&point),
+                rotation.transform_point(&circle.point_curve_to_model(&point)),
+                epsilon = 1e-12
+            );
         }
     }
 }
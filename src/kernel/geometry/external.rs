@@ -0,0 +1,402 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    sync::{
+        atomic::{AtomicU64, Ordering as AtomicOrdering},
+        Arc,
+    },
+};
+
+use crate::{
+    kernel::algorithms::tolerance::Tolerance,
+    math::{Point, Transform, Vector},
+};
+
+use super::{Curve, Surface};
+
+/// A curve type that is defined outside of the kernel
+///
+/// Implement this trait to plug a curve type into the kernel, that the kernel
+/// doesn't know about, for example a NURBS curve from another library. Wrap it
+/// in [`External`] and use it as [`Curve::External`], and it is approximated
+/// and triangulated like any of the built-in curves.
+///
+/// The methods have the same meaning as the methods of [`Curve`] with the same
+/// name.
+pub trait ExternalCurve: fmt::Debug + Send + Sync {
+    /// Access the origin of the curve's coordinate system
+    fn origin(&self) -> Point<3>;
+
+    /// Transform the curve
+    ///
+    /// Returns a [`Curve`], so the transformed curve doesn't need to be of the
+    /// same type.
+    fn transform(&self, transform: &Transform) -> Curve;
+
+    /// Convert a point in model coordinates to curve coordinates
+    fn point_model_to_curve(&self, point: &Point<3>) -> Point<1>;
+
+    /// Convert a point on the curve into model coordinates
+    fn point_curve_to_model(&self, point: &Point<1>) -> Point<3>;
+
+    /// Convert a vector on the curve into model coordinates
+    fn vector_curve_to_model(&self, vector: &Vector<1>) -> Vector<3>;
+
+    /// Compute an approximation of the curve
+    ///
+    /// Must not deviate from the curve by more than `tolerance`. Straight
+    /// curves don't need to push any points.
+    fn approx(&self, tolerance: Tolerance, out: &mut Vec<Point<3>>);
+}
+
+/// A surface type that is defined outside of the kernel
+///
+/// The surface equivalent of [`ExternalCurve`]. Use it as
+/// [`Surface::External`].
+pub trait ExternalSurface: fmt::Debug + Send + Sync {
+    /// Transform the surface
+    fn transform(&self, transform: &Transform) -> Surface;
+
+    /// Convert a point in model coordinates to surface coordinates
+    fn point_model_to_surface(&self, point: &Point<3>) -> Point<2>;
+
+    /// Convert a point in surface coordinates to model coordinates
+    fn point_surface_to_model(&self, point: &Point<2>) -> Point<3>;
+
+    /// Convert a vector in surface coordinates to model coordinates
+    fn vector_surface_to_model(&self, vector: &Vector<2>) -> Vector<3>;
+
+    /// Compute an approximation of the surface
+    ///
+    /// The edges that bound a face only define its curvature, if the surface
+    /// is curved in one direction only, like a cylinder. Any other surface
+    /// needs to push points that approximate it within `tolerance`, which are
+    /// triangulated together with the edges of the face. Points outside of the
+    /// face are fine, as the triangles they end up in are thrown away.
+    fn approx(&self, tolerance: Tolerance, out: &mut Vec<Point<3>>);
+}
+
+/// A geometry object that is defined outside of the kernel
+///
+/// Wraps an [`ExternalCurve`] or [`ExternalSurface`], so it can be stored
+/// alongside the built-in geometry. Cloning an `External` is cheap, as the
+/// wrapped object is shared.
+///
+/// # Equality
+///
+/// The kernel can't compare external objects by value, so two `External`s are
+/// equal, if they were created by the same call to [`External::curve`] or
+/// [`External::surface`]. Clones of an `External` are equal to it, but two
+/// objects that describe the same geometry are not.
+///
+/// `External`s are ordered by when they were created. Unlike their addresses
+/// in memory, that order is the same every time a model is built, so the
+/// geometry that contains them is processed in the same order too.
+pub struct External<T: ?Sized> {
+    object: Arc<T>,
+    id: u64,
+}
+
+impl<T: ?Sized> External<T> {
+    fn new(object: Arc<T>) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+        Self {
+            object,
+            id: NEXT_ID.fetch_add(1, AtomicOrdering::Relaxed),
+        }
+    }
+}
+
+impl External<dyn ExternalCurve> {
+    /// Wrap an external curve
+    pub fn curve(curve: impl ExternalCurve + 'static) -> Self {
+        Self::new(Arc::new(curve))
+    }
+}
+
+impl External<dyn ExternalSurface> {
+    /// Wrap an external surface
+    pub fn surface(surface: impl ExternalSurface + 'static) -> Self {
+        Self::new(Arc::new(surface))
+    }
+}
+
+impl<T: ?Sized> Deref for External<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.object
+    }
+}
+
+impl<T: ?Sized> Clone for External<T> {
+    fn clone(&self) -> Self {
+        Self {
+            object: self.object.clone(),
+            id: self.id,
+        }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for External<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.object.fmt(f)
+    }
+}
+
+impl<T: ?Sized> PartialEq for External<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T: ?Sized> Eq for External<T> {}
+
+impl<T: ?Sized> Hash for External<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T: ?Sized> PartialOrd for External<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: ?Sized> Ord for External<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::smallvec;
+
+    use crate::{
+        debug::DebugInfo,
+        kernel::{
+            algorithms::{approximation::Approximation, tolerance::Tolerance},
+            geometry::{Curve, Ellipse, Surface},
+            shape::Shape,
+            topology::{
+                edges::{Cycle, Edge},
+                faces::Face,
+                vertices::Vertex,
+            },
+        },
+        math::{Frame, Point, Scalar, Transform, Vector},
+    };
+
+    use super::{External, ExternalCurve, ExternalSurface};
+
+    /// An external curve that just forwards to a built-in one
+    #[derive(Debug)]
+    struct Wrapped(Ellipse);
+
+    impl ExternalCurve for Wrapped {
+        fn origin(&self) -> Point<3> {
+            self.0.origin()
+        }
+
+        fn transform(&self, transform: &Transform) -> Curve {
+            Curve::Ellipse(self.0.transform(transform))
+        }
+
+        fn point_model_to_curve(&self, point: &Point<3>) -> Point<1> {
+            self.0.point_model_to_curve(point)
+        }
+
+        fn point_curve_to_model(&self, point: &Point<1>) -> Point<3> {
+            self.0.point_curve_to_model(point)
+        }
+
+        fn vector_curve_to_model(&self, vector: &Vector<1>) -> Vector<3> {
+            self.0.vector_curve_to_model(vector)
+        }
+
+        fn approx(&self, tolerance: Tolerance, out: &mut Vec<Point<3>>) {
+            self.0.approx(tolerance, out)
+        }
+    }
+
+    /// The surface `z = x² + y²`, whose curvature isn't defined by its edges
+    ///
+    /// The coordinates are relative to the frame, which is moved, when the
+    /// surface is transformed.
+    #[derive(Debug)]
+    struct Paraboloid(Frame);
+
+    impl Paraboloid {
+        fn new() -> Self {
+            Self(Frame {
+                origin: Point::origin(),
+                x_axis: Vector::from([1., 0., 0.]),
+                y_axis: Vector::from([0., 1., 0.]),
+                z_axis: Vector::from([0., 0., 1.]),
+            })
+        }
+    }
+
+    impl ExternalSurface for Paraboloid {
+        fn transform(&self, transform: &Transform) -> Surface {
+            let frame = transform.transform_frame(&self.0);
+            Surface::External(External::surface(Self(frame)))
+        }
+
+        fn point_model_to_surface(&self, point: &Point<3>) -> Point<2> {
+            let offset = point - self.0.origin;
+            Point::from([
+                offset.dot(&self.0.x_axis),
+                offset.dot(&self.0.y_axis),
+            ])
+        }
+
+        fn point_surface_to_model(&self, point: &Point<2>) -> Point<3> {
+            let Frame {
+                origin,
+                x_axis,
+                y_axis,
+                z_axis,
+            } = self.0;
+            origin
+                + x_axis * point.u
+                + y_axis * point.v
+                + z_axis * (point.u * point.u + point.v * point.v)
+        }
+
+        fn vector_surface_to_model(&self, vector: &Vector<2>) -> Vector<3> {
+            self.0.x_axis * vector.u + self.0.y_axis * vector.v
+        }
+
+        fn approx(&self, tolerance: Tolerance, out: &mut Vec<Point<3>>) {
+            // The cell centers of a grid over -1 <= u, v <= 1, which is fine
+            // enough, that the sag between grid points is within tolerance.
+            let step = tolerance.inner().into_f64().sqrt() * 2.;
+            let n = (2. / step).ceil() as usize;
+
+            for i in 0..n {
+                for j in 0..n {
+                    let [u, v] = [i, j].map(|k| {
+                        Scalar::from_f64((k as f64 + 0.5) * 2. / n as f64 - 1.)
+                    });
+                    out.push(self.point_surface_to_model(&Point::from([u, v])));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn external_curve_should_be_approximated() {
+        let tolerance = Tolerance::from_scalar(0.01).unwrap();
+        let ellipse = Ellipse {
+            center: Point::from([1., 2., 3.]),
+            a: Vector::from([2., 0., 0.]),
+            b: Vector::from([0., 0., 1.]),
+        };
+
+        let mut shape = Shape::new();
+        let mut approx = |curve| {
+            let curve = shape.geometry().add_curve(curve);
+            let edge = shape
+                .topology()
+                .add_edge(Edge {
+                    curve,
+                    vertices: None,
+                })
+                .unwrap();
+            Approximation::for_edge(edge.get(), tolerance)
+        };
+
+        let external = Curve::External(External::curve(Wrapped(ellipse)));
+        assert_eq!(approx(external.clone()), approx(Curve::Ellipse(ellipse)));
+
+        let translation = Transform::translation(Vector::from([1., 0., 0.]));
+        assert_eq!(
+            external.transform(&translation),
+            Curve::Ellipse(ellipse.transform(&translation))
+        );
+    }
+
+    #[test]
+    fn external_surface_should_be_triangulated() {
+        let tolerance = Tolerance::from_scalar(0.01).unwrap();
+
+        let mut shape = Shape::new();
+
+        let mut vertices = Vec::new();
+        for [x, y] in [[-1., -1.], [1., -1.], [1., 1.], [-1., 1.]] {
+            let point = shape.geometry().add_point(Point::from([x, y, 2.]));
+            vertices
+                .push(shape.topology().add_vertex(Vertex { point }).unwrap());
+        }
+        let edges = (0..vertices.len())
+            .map(|i| {
+                let b = vertices[(i + 1) % vertices.len()].clone();
+                shape
+                    .topology()
+                    .add_line_segment([vertices[i].clone(), b])
+                    .unwrap()
+            })
+            .collect();
+        let cycle = shape.topology().add_cycle(Cycle { edges }).unwrap();
+
+        let surface =
+            shape
+                .geometry()
+                .add_surface(Surface::External(External::surface(
+                    Paraboloid::new(),
+                )));
+        let face = Face::Face {
+            surface,
+            cycles: smallvec![cycle],
+        };
+
+        let mut triangles = Vec::new();
+        face.triangles(tolerance, &mut triangles, &mut DebugInfo::new());
+
+        // The grid has 10 x 10 cells, and every one of them is covered.
+        assert!(triangles.len() >= 100);
+        for point in triangles.iter().flat_map(|triangle| triangle.points()) {
+            assert!(point.x.abs() <= Scalar::ONE);
+            assert!(point.y.abs() <= Scalar::ONE);
+            assert_eq!(point.z, point.x * point.x + point.y * point.y);
+        }
+    }
+
+    #[test]
+    fn external_objects_should_be_equal_by_identity() {
+        let a = External::surface(Paraboloid::new());
+        let b = External::surface(Paraboloid::new());
+
+        assert_eq!(a, a.clone());
+        assert_ne!(a, b);
+
+        // Objects are ordered by when they were created, not by where they
+        // ended up in memory.
+        assert!(a < b);
+        assert!(a.clone() < b);
+    }
+
+    #[test]
+    fn external_surface_should_be_transformed() {
+        let surface = Surface::External(External::surface(Paraboloid::new()))
+            .transform(&Transform::translation(Vector::from([1., 2., 3.])));
+
+        let point = Point::from([1., 2., 3.]);
+        assert_eq!(
+            surface.point_surface_to_model(&Point::from([1., 1.])),
+            Point::from([2., 3., 5.])
+        );
+        assert_eq!(
+            surface
+                .point_model_to_surface(point + Vector::from([1., 1., 2.]))
+                .native(),
+            Point::from([1., 1.])
+        );
+    }
+}
@@ -1,9 +1,11 @@
 pub mod curves;
+pub mod external;
 pub mod points;
 pub mod surfaces;
 
 pub use self::{
-    curves::{Circle, Curve, Line},
+    curves::{Circle, Curve, Ellipse, Line},
+    external::{External, ExternalCurve, ExternalSurface},
     points::Point,
     surfaces::Surface,
 };
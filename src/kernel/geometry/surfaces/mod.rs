@@ -1,21 +1,27 @@
 pub mod swept;
 
-pub use self::swept::Swept;
+pub use self::swept::{AxisPlane, Swept};
 
 use nalgebra::vector;
 
 use crate::{
-    kernel::geometry,
+    kernel::{algorithms::tolerance::Tolerance, geometry},
     math::{Point, Transform, Vector},
 };
 
-use super::{Curve, Line};
+use super::{Curve, External, ExternalSurface, Line};
 
 /// A two-dimensional shape
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+///
+/// Surface types that the kernel doesn't know about can be added as
+/// [`Surface::External`].
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub enum Surface {
     /// A swept curve
     Swept(Swept),
+
+    /// A surface that is defined outside of the kernel
+    External(External<dyn ExternalSurface>),
 }
 
 impl Surface {
@@ -35,6 +41,17 @@ impl Surface {
     pub fn transform(self, transform: &Transform) -> Self {
         match self {
             Self::Swept(surface) => Self::Swept(surface.transform(transform)),
+            Self::External(surface) => surface.transform(transform),
+        }
+    }
+
+    /// Access the surface as an axis-aligned plane, if it is one
+    ///
+    /// See [`Swept::axis_plane`].
+    pub fn axis_plane(&self) -> Option<AxisPlane> {
+        match self {
+            Self::Swept(surface) => surface.axis_plane(),
+            _ => None,
         }
     }
 
@@ -45,6 +62,9 @@ impl Surface {
     ) -> geometry::Point<2> {
         let point_2d = match self {
             Self::Swept(surface) => surface.point_model_to_surface(&point_3d),
+            Self::External(surface) => {
+                surface.point_model_to_surface(&point_3d)
+            }
         };
 
         geometry::Point::new(point_2d, point_3d)
@@ -54,6 +74,7 @@ impl Surface {
     pub fn point_surface_to_model(&self, point: &Point<2>) -> Point<3> {
         match self {
             Self::Swept(surface) => surface.point_surface_to_model(point),
+            Self::External(surface) => surface.point_surface_to_model(point),
         }
     }
 
@@ -61,6 +82,20 @@ impl Surface {
     pub fn vector_surface_to_model(&self, vector: &Vector<2>) -> Vector<3> {
         match self {
             Self::Swept(surface) => surface.vector_surface_to_model(vector),
+            Self::External(surface) => surface.vector_surface_to_model(vector),
+        }
+    }
+
+    /// Compute an approximation of the surface
+    ///
+    /// Pushes the points that are needed to approximate the surface within
+    /// `tolerance`, in addition to the approximations of the edges that bound
+    /// a face. None are needed for swept surfaces, as their curvature is fully
+    /// defined by those edges.
+    pub fn approx(&self, tolerance: Tolerance, out: &mut Vec<Point<3>>) {
+        match self {
+            Self::Swept(_) => {}
+            Self::External(surface) => surface.approx(tolerance, out),
         }
     }
 }
@@ -1,10 +1,10 @@
 use crate::{
     kernel::geometry::Curve,
-    math::{Point, Transform, Vector},
+    math::{Point, Scalar, Transform, Vector},
 };
 
 /// A surface that was swept from a curve
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub struct Swept {
     /// The curve that this surface was swept from
     pub curve: Curve,
@@ -36,7 +36,8 @@ impl Swept {
     /// Convert a point in model coordinates to surface coordinates
     pub fn point_model_to_surface(&self, point: &Point<3>) -> Point<2> {
         let u = self.curve.point_model_to_curve(point).t;
-        let v = (point - self.curve.origin()).dot(&self.path.normalize())
+        let v = (point - self.curve.origin())
+            .scalar_projection_onto(&self.path)
             / self.path.magnitude();
 
         Point::from([u, v])
@@ -51,6 +52,93 @@ impl Swept {
     pub fn vector_surface_to_model(&self, vector: &Vector<2>) -> Vector<3> {
         self.curve.vector_curve_to_model(&vector.to_t()) + self.path * vector.v
     }
+
+    /// Access the surface as an axis-aligned plane, if it is one
+    ///
+    /// Returns `None`, unless the surface is a line that was swept along a
+    /// coordinate axis, and that line points along another one.
+    pub fn axis_plane(&self) -> Option<AxisPlane> {
+        let line = match &self.curve {
+            Curve::Line(line) => line,
+            _ => return None,
+        };
+
+        let u = axis(&line.direction)?;
+        let v = axis(&self.path)?;
+        if u.0 == v.0 {
+            return None;
+        }
+
+        Some(AxisPlane {
+            origin: line.origin,
+            u,
+            v,
+        })
+    }
+}
+
+/// A plane whose surface coordinate axes are coordinate axes of the model
+///
+/// Most faces of typical models lie in such planes. Converting between model
+/// and surface coordinates boils down to picking and scaling components, which
+/// is much cheaper than the projections that [`Swept`] needs in general. It
+/// results in the same coordinates, up to floating point rounding.
+///
+/// Created by [`Swept::axis_plane`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AxisPlane {
+    origin: Point<3>,
+
+    /// The index of the model axis of each surface axis, and its scale
+    u: (usize, Scalar),
+    v: (usize, Scalar),
+}
+
+impl AxisPlane {
+    /// Convert a point in model coordinates to surface coordinates
+    pub fn point_model_to_surface(&self, point: &Point<3>) -> Point<2> {
+        let point = point.coords.components();
+        let origin = self.origin.coords.components();
+
+        let [u, v] =
+            [self.u, self.v].map(|(i, scale)| (point[i] - origin[i]) / scale);
+
+        Point::from([u, v])
+    }
+
+    /// Convert a point in surface coordinates to model coordinates
+    pub fn point_surface_to_model(&self, point: &Point<2>) -> Point<3> {
+        self.origin + self.vector_surface_to_model(&point.coords)
+    }
+
+    /// Convert a vector in surface coordinates to model coordinates
+    pub fn vector_surface_to_model(&self, vector: &Vector<2>) -> Vector<3> {
+        let mut components = [Scalar::ZERO; 3];
+        components[self.u.0] = vector.u * self.u.1;
+        components[self.v.0] = vector.v * self.v.1;
+
+        Vector::from(components)
+    }
+}
+
+/// Find the coordinate axis that a vector points along, and its length
+///
+/// Returns `None`, if the vector doesn't point along a coordinate axis.
+fn axis(vector: &Vector<3>) -> Option<(usize, Scalar)> {
+    let mut axis = None;
+
+    for (i, component) in vector.components().into_iter().enumerate() {
+        if component == Scalar::ZERO {
+            continue;
+        }
+        if axis.is_some() {
+            return None;
+        }
+
+        axis = Some((i, component));
+    }
+
+    axis
 }
 
 #[cfg(test)]
@@ -102,6 +190,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn axis_plane_should_convert_like_swept_surface() {
+        let swept = Swept {
+            curve: Curve::Line(Line {
+                origin: Point::from([1., 2., 3.]),
+                direction: Vector::from([0., 0., -2.]),
+            }),
+            path: Vector::from([4., 0., 0.]),
+        };
+        let plane = swept.axis_plane().unwrap();
+
+        let point = Point::from([5., 2., -1.]);
+        assert_eq!(
+            plane.point_model_to_surface(&point),
+            swept.point_model_to_surface(&point),
+        );
+
+        let point = Point::from([2., -3.]);
+        assert_eq!(
+            plane.point_surface_to_model(&point),
+            swept.point_surface_to_model(&point),
+        );
+    }
+
+    #[test]
+    fn axis_plane_should_only_exist_for_axis_aligned_planes() {
+        let swept = |direction: [f64; 3], path: [f64; 3]| Swept {
+            curve: Curve::Line(Line {
+                origin: Point::origin(),
+                direction: Vector::from(direction),
+            }),
+            path: Vector::from(path),
+        };
+
+        assert!(swept([1., 0., 0.], [0., 1., 0.]).axis_plane().is_some());
+        assert!(swept([1., 1., 0.], [0., 0., 1.]).axis_plane().is_none());
+        assert!(swept([0., 1., 0.], [0., 2., 0.]).axis_plane().is_none());
+    }
+
     #[test]
     fn vector_surface_to_model() {
         let swept = Swept {
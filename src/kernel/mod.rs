@@ -77,7 +77,10 @@
 //! should lead to less work overall.
 
 pub mod algorithms;
+pub mod error;
 pub mod geometry;
 pub mod shape;
 pub mod shapes;
 pub mod topology;
+
+pub use self::error::Error;
@@ -5,7 +5,7 @@ pub mod validate;
 
 pub use self::validate::{ValidationError, ValidationResult};
 
-use crate::math::{Point, Scalar};
+use crate::math::{AabbTree, Point, Scalar};
 
 use super::{
     geometry::{Curve, Surface},
@@ -16,7 +16,11 @@ use super::{
     },
 };
 
-use self::{geometry::Geometry, handle::Storage, topology::Topology};
+use self::{
+    geometry::Geometry,
+    handle::{Handle, Storage},
+    topology::Topology,
+};
 
 /// The boundary representation of a shape
 #[derive(Clone, Debug)]
@@ -34,6 +38,14 @@ pub struct Shape {
     edges: Edges,
     cycles: Cycles,
     faces: Faces,
+
+    /// The vertices, organized by position, for fast uniqueness checks
+    vertex_tree: AabbTree<3, Handle<Vertex>>,
+
+    /// Validation errors that were only logged as warnings
+    ///
+    /// See [`Topology::add_vertex`].
+    validation_warnings: Vec<ValidationError<Vertex>>,
 }
 
 impl Shape {
@@ -53,6 +65,10 @@ impl Shape {
             edges: Edges::new(),
             cycles: Cycles::new(),
             faces: Faces::new(),
+
+            vertex_tree: AabbTree::new(),
+
+            validation_warnings: Vec::new(),
         }
     }
 
@@ -71,6 +87,13 @@ impl Shape {
         self
     }
 
+    /// Access the minimum distance between distinct vertices
+    ///
+    /// See [`Topology::add_vertex`].
+    pub fn min_distance(&self) -> Scalar {
+        self.min_distance
+    }
+
     /// Access the shape's geometry
     pub fn geometry(&mut self) -> Geometry {
         Geometry {
@@ -95,8 +118,55 @@ impl Shape {
             edges: &mut self.edges,
             cycles: &mut self.cycles,
             faces: &mut self.faces,
+
+            vertex_tree: &mut self.vertex_tree,
+
+            validation_warnings: &mut self.validation_warnings,
         }
     }
+
+    /// Access an iterator over all vertices
+    ///
+    /// Unlike the methods of [`Topology`], the read-only methods of `Shape`
+    /// only need shared access. A finished shape can be read from many
+    /// threads at once, without any of them having to copy it.
+    ///
+    /// The caller must not make any assumptions about the order of vertices.
+    pub fn vertices(&self) -> impl Iterator<Item = Handle<Vertex>> + '_ {
+        self.vertices.iter().map(|storage| storage.handle())
+    }
+
+    /// Access an iterator over all edges
+    ///
+    /// The caller must not make any assumptions about the order of edges.
+    pub fn edges(&self) -> impl Iterator<Item = Handle<Edge>> + '_ {
+        self.edges.iter().map(|storage| storage.handle())
+    }
+
+    /// Access an iterator over all cycles
+    pub fn cycles(&self) -> impl Iterator<Item = Handle<Cycle>> + '_ {
+        self.cycles.iter().map(|storage| storage.handle())
+    }
+
+    /// Access an iterator over all faces
+    pub fn faces(&self) -> impl Iterator<Item = Handle<Face>> + '_ {
+        self.faces.iter().map(|storage| storage.handle())
+    }
+
+    /// Access the validation errors that were only logged as warnings
+    ///
+    /// Some validation checks are not strict yet, as that would break existing
+    /// models. They still log a warning, and the errors they find are stored
+    /// here, so they can be shown to the user.
+    pub fn validation_warnings(&self) -> &[ValidationError<Vertex>] {
+        &self.validation_warnings
+    }
+}
+
+impl Default for Shape {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 type Points = Vec<Storage<Point<3>>>;
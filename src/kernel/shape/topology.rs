@@ -5,14 +5,15 @@ use tracing::warn;
 use crate::{
     debug::DebugInfo,
     kernel::{
-        geometry::{Circle, Curve, Line},
+        algorithms::tolerance::Tolerance,
+        geometry::{Circle, Curve, Ellipse, Line},
         topology::{
             edges::{Cycle, Edge},
             faces::Face,
             vertices::Vertex,
         },
     },
-    math::{Point, Scalar, Triangle, Vector},
+    math::{Aabb, AabbTree, Point, Scalar, Triangle, Vector},
 };
 
 use super::{
@@ -31,6 +32,10 @@ pub struct Topology<'r> {
     pub(super) edges: &'r mut Edges,
     pub(super) cycles: &'r mut Cycles,
     pub(super) faces: &'r mut Faces,
+
+    pub(super) vertex_tree: &'r mut AabbTree<3, Handle<Vertex>>,
+
+    pub(super) validation_warnings: &'r mut Vec<ValidationError<Vertex>>,
 }
 
 impl Topology<'_> {
@@ -41,7 +46,8 @@ impl Topology<'_> {
     /// case.
     ///
     /// Logs a warning, if the vertex is not unique, meaning if another vertex
-    /// defined by the same point already exists.
+    /// defined by the same point already exists. The error is also recorded,
+    /// and can be accessed via [`Shape::validation_warnings`].
     ///
     /// In the context of of vertex uniqueness, points that are close to each
     /// other are considered identical. The minimum distance between distinct
@@ -60,20 +66,38 @@ impl Topology<'_> {
         if !self.geometry.points.contains(vertex.point.storage()) {
             return Err(ValidationError::Structural(()));
         }
-        for existing in &*self.vertices {
-            let distance = (existing.point() - vertex.point()).magnitude();
+
+        // Only vertices within the minimum distance in every dimension can be
+        // too close.
+        let point = vertex.point();
+        let [min, max] =
+            [-self.min_distance, self.min_distance].map(|offset| {
+                Point::from(point.coords.components().map(|c| c + offset))
+            });
+
+        for existing in self.vertex_tree.query_aabb(&Aabb { min, max }) {
+            let distance = (existing.point() - point).magnitude();
 
             if distance < self.min_distance {
                 warn!(
                     "Invalid vertex: {vertex:?}; \
                     identical vertex at {existing:?}",
                 );
+                self.validation_warnings
+                    .push(ValidationError::Uniqueness(existing.clone()));
             }
         }
 
         let storage = Storage::new(vertex);
         let handle = storage.handle();
         self.vertices.push(storage);
+        self.vertex_tree.insert(
+            Aabb {
+                min: point,
+                max: point,
+            },
+            handle.clone(),
+        );
 
         Ok(handle)
     }
@@ -126,9 +150,13 @@ impl Topology<'_> {
     ///
     /// Calls [`Edges::add`] internally, and is subject to the same
     /// restrictions.
-    pub fn add_circle(&mut self, radius: Scalar) -> ValidationResult<Edge> {
+    pub fn add_circle(
+        &mut self,
+        center: Point<3>,
+        radius: Scalar,
+    ) -> ValidationResult<Edge> {
         let curve = self.geometry.add_curve(Curve::Circle(Circle {
-            center: Point::origin(),
+            center,
             radius: Vector::from([radius, Scalar::ZERO]),
         }));
         self.add_edge(Edge {
@@ -137,6 +165,26 @@ impl Topology<'_> {
         })
     }
 
+    /// Add an ellipse to the shape
+    ///
+    /// The axes of the ellipse are aligned with the x- and y-axes. Calls
+    /// [`Edges::add`] internally, and is subject to the same restrictions.
+    pub fn add_ellipse(
+        &mut self,
+        center: Point<3>,
+        [x, y]: [Scalar; 2],
+    ) -> ValidationResult<Edge> {
+        let curve = self.geometry.add_curve(Curve::Ellipse(Ellipse {
+            center,
+            a: Vector::from([x, Scalar::ZERO, Scalar::ZERO]),
+            b: Vector::from([Scalar::ZERO, y, Scalar::ZERO]),
+        }));
+        self.add_edge(Edge {
+            curve,
+            vertices: None,
+        })
+    }
+
     /// Add a line segment to the shape
     ///
     /// Calls [`Edges::add`] internally, and is subject to the same
@@ -157,12 +205,19 @@ impl Topology<'_> {
     /// Add a cycle to the shape
     ///
     /// Validates that the cycle is structurally sound (i.e. the edges it refers
-    /// to are part of the shape). Returns an error, if that is not the case.
+    /// to are part of the shape), and that its edges form a closed loop.
+    /// Returns an error, if that is not the case.
+    ///
+    /// The edges form a closed loop, if the end of each edge connects to the
+    /// start of the next one, and the end of the last edge connects to the
+    /// start of the first. Vertices that are closer than the minimum distance
+    /// (see [`Topology::add_vertex`]) are considered to be connected. An edge
+    /// without vertices is closed by itself, and must be the only edge of its
+    /// cycle.
     ///
     /// # Implementation note
     ///
     /// The validation of the cycle should be extended to cover more cases:
-    /// - That those edges form a cycle.
     /// - That the cycle is not self-overlapping.
     /// - That there exists no duplicate cycle, with the same edges.
     pub fn add_cycle(&mut self, cycle: Cycle) -> ValidationResult<Cycle> {
@@ -177,6 +232,26 @@ impl Topology<'_> {
             return Err(ValidationError::Structural(missing_edges));
         }
 
+        let mut gaps = Vec::new();
+        for (i, edge) in cycle.edges.iter().enumerate() {
+            let next = &cycle.edges[(i + 1) % cycle.edges.len()];
+
+            match (edge.vertices(), next.vertices()) {
+                (Some([_, end]), Some([start, _])) => {
+                    let distance = (start.point() - end.point()).magnitude();
+                    if distance >= self.min_distance {
+                        gaps.push(end.point());
+                    }
+                }
+                (None, None) if cycle.edges.len() == 1 => {}
+                _ => gaps.push(edge.curve().origin()),
+            }
+        }
+
+        if !gaps.is_empty() {
+            return Err(ValidationError::NotClosed(gaps));
+        }
+
         let storage = Storage::new(cycle);
         let handle = storage.handle();
         self.cycles.push(storage);
@@ -245,7 +320,7 @@ impl Topology<'_> {
 
     pub fn triangles(
         &self,
-        tolerance: Scalar,
+        tolerance: Tolerance,
         out: &mut Vec<Triangle<3>>,
         debug_info: &mut DebugInfo,
     ) {
@@ -259,6 +334,8 @@ impl Topology<'_> {
 mod tests {
     use std::ops::{Deref, DerefMut};
 
+    use smallvec::{smallvec, SmallVec};
+
     use crate::{
         kernel::{
             geometry::{Curve, Line, Surface},
@@ -289,9 +366,14 @@ mod tests {
 
         // `point` is too close to the original point. `assert!` is commented,
         // because that only causes a warning to be logged right now.
-        let point = shape.geometry().add_point(Point::from([5e-6, 0., 0.]));
+        let point = shape.geometry().add_point(Point::from([5e-8, 0., 0.]));
         let _result = shape.topology().add_vertex(Vertex { point });
-        // assert!(matches!(result, Err(ValidationError::Uniqueness)));
+        // assert!(matches!(result, Err(ValidationError::Uniqueness(_))));
+
+        // The error is recorded though, together with its position.
+        let warnings = shape.validation_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].positions(), [Point::from([0., 0., 0.])]);
 
         // `point` is farther than `MIN_DISTANCE` away from original point.
         // Should work.
@@ -321,6 +403,7 @@ mod tests {
         assert!(err.missing_curve(&curve));
         assert!(err.missing_vertex(&a));
         assert!(err.missing_vertex(&b));
+        assert_eq!(err.positions().len(), 3);
 
         let curve = shape.add_curve();
         let a = shape.add_vertex()?;
@@ -345,14 +428,25 @@ mod tests {
         let err = shape
             .topology()
             .add_cycle(Cycle {
-                edges: vec![edge.clone()],
+                edges: smallvec![edge.clone()],
             })
             .unwrap_err();
         assert!(err.missing_edge(&edge));
 
-        // Referring to edge that *is* from the same shape. Should work.
+        // Referring to edge that *is* from the same shape. Should fail, as the
+        // edge doesn't form a closed loop by itself.
         let edge = shape.add_edge()?;
-        shape.topology().add_cycle(Cycle { edges: vec![edge] })?;
+        let err = shape
+            .topology()
+            .add_cycle(Cycle {
+                edges: smallvec![edge],
+            })
+            .unwrap_err();
+        assert!(matches!(err, ValidationError::NotClosed(_)));
+        assert_eq!(err.positions(), [Point::from([1., 0., 0.])]);
+
+        // Edges that form a closed loop. Should work.
+        shape.add_cycle()?;
 
         Ok(())
     }
@@ -370,7 +464,7 @@ mod tests {
             .topology()
             .add_face(Face::Face {
                 surface: surface.clone(),
-                cycles: vec![cycle.clone()],
+                cycles: smallvec![cycle.clone()],
             })
             .unwrap_err();
         assert!(err.missing_surface(&surface));
@@ -382,7 +476,7 @@ mod tests {
         // Everything has been added to `shape` now. Should work!
         shape.topology().add_face(Face::Face {
             surface,
-            cycles: vec![cycle],
+            cycles: smallvec![cycle],
         })?;
 
         Ok(())
@@ -429,9 +523,17 @@ mod tests {
         }
 
         fn add_cycle(&mut self) -> anyhow::Result<Handle<Cycle>> {
-            let edge = self.add_edge()?;
-            let cycle =
-                self.topology().add_cycle(Cycle { edges: vec![edge] })?;
+            let [a, b, c] = [(); 3].map(|()| self.add_vertex().unwrap());
+
+            let mut edges = SmallVec::new();
+            for vertices in [[&a, &b], [&b, &c], [&c, &a]] {
+                let edge = self
+                    .topology()
+                    .add_line_segment(vertices.map(Clone::clone))?;
+                edges.push(edge);
+            }
+
+            let cycle = self.topology().add_cycle(Cycle { edges })?;
             Ok(cycle)
         }
     }
@@ -1,12 +1,15 @@
 use std::collections::HashSet;
 
-use crate::kernel::{
-    geometry::{Curve, Surface},
-    topology::{
-        edges::{Cycle, Edge},
-        faces::Face,
-        vertices::Vertex,
+use crate::{
+    kernel::{
+        geometry::{Curve, Surface},
+        topology::{
+            edges::{Cycle, Edge},
+            faces::Face,
+            vertices::Vertex,
+        },
     },
+    math::Point,
 };
 
 use super::handle::Handle;
@@ -15,7 +18,7 @@ use super::handle::Handle;
 pub type ValidationResult<T> = Result<Handle<T>, ValidationError<T>>;
 
 /// An error that can occur during a validation
-#[derive(Debug, thiserror::Error)]
+#[derive(Clone, Debug, thiserror::Error)]
 pub enum ValidationError<T: Validatable> {
     /// Structural validation failed
     ///
@@ -29,9 +32,10 @@ pub enum ValidationError<T: Validatable> {
     /// Uniqueness validation checks, that an object is unique. Uniqueness is
     /// only required for topological objects, as there's no harm in geometric
     /// objects being duplicated.
+    ///
+    /// Contains the existing object, that the validated object duplicates.
     #[error("Uniqueness validation failed")]
-    #[allow(unused)]
-    Uniqueness,
+    Uniqueness(Handle<T>),
 
     /// Geometric validation failed
     ///
@@ -41,6 +45,29 @@ pub enum ValidationError<T: Validatable> {
     #[error("Geometric validation failed")]
     #[allow(unused)]
     Geometric,
+
+    /// The edges of a cycle don't form a closed loop
+    ///
+    /// Only cycles are validated for this. Contains the positions where an
+    /// edge doesn't connect to the next one.
+    #[error("Edges don't form a closed loop")]
+    NotClosed(Vec<Point<3>>),
+}
+
+impl<T: Validatable> ValidationError<T> {
+    /// Access the positions of the objects that caused the error
+    ///
+    /// This can be used to show the user where in the model an error is
+    /// located. Curves and surfaces don't have a single position, so they are
+    /// not represented here.
+    pub fn positions(&self) -> Vec<Point<3>> {
+        match self {
+            Self::Structural(missing) => T::missing_positions(missing),
+            Self::Uniqueness(existing) => existing.positions(),
+            Self::Geometric => Vec::new(),
+            Self::NotClosed(positions) => positions.clone(),
+        }
+    }
 }
 
 impl ValidationError<Edge> {
@@ -104,20 +131,87 @@ impl ValidationError<Face> {
 /// Used by [`ValidationError`] to provide context on how validation failed.
 pub trait Validatable {
     type Structural;
+
+    /// The name of the object, as used in error messages
+    const NAME: &'static str;
+
+    /// Access the positions of the object
+    fn positions(&self) -> Vec<Point<3>>;
+
+    /// Access the positions of the objects that structural validation found
+    /// missing
+    fn missing_positions(missing: &Self::Structural) -> Vec<Point<3>>;
 }
 
 impl Validatable for Vertex {
     type Structural = ();
+    const NAME: &'static str = "vertex";
+
+    fn positions(&self) -> Vec<Point<3>> {
+        vec![self.point()]
+    }
+
+    fn missing_positions((): &Self::Structural) -> Vec<Point<3>> {
+        Vec::new()
+    }
 }
 
 impl Validatable for Edge {
     type Structural = (Option<Handle<Curve>>, HashSet<Handle<Vertex>>);
+    const NAME: &'static str = "edge";
+
+    fn positions(&self) -> Vec<Point<3>> {
+        match &self.vertices {
+            Some(vertices) => vertices.iter().map(|v| v.point()).collect(),
+            // A continuous edge has no vertices, so fall back to its curve.
+            None => vec![self.curve().origin()],
+        }
+    }
+
+    fn missing_positions(
+        (curve, vertices): &Self::Structural,
+    ) -> Vec<Point<3>> {
+        curve
+            .iter()
+            .map(|curve| curve.origin())
+            .chain(vertices.iter().map(|vertex| vertex.point()))
+            .collect()
+    }
 }
 
 impl Validatable for Cycle {
     type Structural = HashSet<Handle<Edge>>;
+    const NAME: &'static str = "cycle";
+
+    fn positions(&self) -> Vec<Point<3>> {
+        self.edges
+            .iter()
+            .flat_map(|edge| edge.positions())
+            .collect()
+    }
+
+    fn missing_positions(edges: &Self::Structural) -> Vec<Point<3>> {
+        edges.iter().flat_map(|edge| edge.positions()).collect()
+    }
 }
 
 impl Validatable for Face {
     type Structural = (Option<Handle<Surface>>, HashSet<Handle<Cycle>>);
+    const NAME: &'static str = "face";
+
+    fn positions(&self) -> Vec<Point<3>> {
+        match self {
+            Self::Face { cycles, .. } => {
+                cycles.iter().flat_map(|cycle| cycle.positions()).collect()
+            }
+            Self::Triangles(triangles) => triangles
+                .iter()
+                .flat_map(|triangle| triangle.points())
+                .collect(),
+        }
+    }
+
+    fn missing_positions((_, cycles): &Self::Structural) -> Vec<Point<3>> {
+        cycles.iter().flat_map(|cycle| cycle.positions()).collect()
+    }
 }
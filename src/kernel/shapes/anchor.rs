@@ -0,0 +1,104 @@
+use crate::{
+    debug::DebugInfo,
+    kernel::{algorithms::tolerance::Tolerance, shape::Shape, Error},
+    math::{Aabb, Frame, Point, Transform, Vector},
+};
+
+use super::ToShape;
+
+/// A named frame on a shape, as defined by [`fj::Anchor`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Anchor {
+    /// The name of the anchor
+    pub name: String,
+
+    /// The frame of the anchor
+    pub frame: Frame,
+}
+
+impl ToShape for fj::Anchor {
+    fn to_shape(
+        &self,
+        tolerance: Tolerance,
+        debug_info: &mut DebugInfo,
+    ) -> Result<Shape, Error> {
+        self.shape.to_shape(tolerance, debug_info)
+    }
+
+    fn transformed_bounding_volume(&self, transform: &Transform) -> Aabb<3> {
+        self.shape.transformed_bounding_volume(transform)
+    }
+
+    fn anchors(&self) -> Vec<Anchor> {
+        let mut anchors = self.shape.anchors();
+
+        // The host application checks every shape it receives, which rules
+        // out degenerate axes.
+        let frame = Frame::from_axes(
+            Point::from(self.origin),
+            Vector::from(self.x_axis),
+            Vector::from(self.z_axis),
+        )
+        .expect("Anchor axes are degenerate");
+
+        anchors.push(Anchor {
+            name: self.name.clone(),
+            frame,
+        });
+
+        anchors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use approx::assert_abs_diff_eq;
+
+    use crate::{
+        kernel::shapes::ToShape as _,
+        math::{Point, Vector},
+        testing::cuboid,
+    };
+
+    #[test]
+    fn anchors_should_follow_transforms() {
+        let cube: fj::Shape3d = cuboid([1., 1., 1.]).into();
+        let anchor = |shape, name: &str| -> fj::Shape3d {
+            fj::Anchor {
+                shape,
+                name: name.to_owned(),
+                origin: [1., 0., 1.],
+                x_axis: [1., 0., 0.],
+                z_axis: [0., 0., 1.],
+            }
+            .into()
+        };
+
+        let rotated = fj::Transform {
+            shape: anchor(cube.clone(), "rotated"),
+            axis: [0., 0., 1.],
+            angle: FRAC_PI_2,
+            offset: [0., 0., 2.],
+        };
+        let shape = fj::Union {
+            a: anchor(cube, "fixed"),
+            b: rotated.into(),
+        };
+
+        let anchors = shape.anchors();
+        assert_eq!(anchors.len(), 2);
+
+        let fixed = &anchors[0];
+        assert_eq!(fixed.name, "fixed");
+        assert_eq!(fixed.frame.origin, Point::from([1., 0., 1.]));
+
+        let rotated = &anchors[1].frame;
+        assert_eq!(anchors[1].name, "rotated");
+        assert_abs_diff_eq!(rotated.origin, Point::from([0., 1., 3.]));
+        assert_abs_diff_eq!(rotated.x_axis, Vector::from([0., 1., 0.]));
+        assert_abs_diff_eq!(rotated.y_axis, Vector::from([-1., 0., 0.]));
+        assert_abs_diff_eq!(rotated.z_axis, Vector::from([0., 0., 1.]));
+    }
+}
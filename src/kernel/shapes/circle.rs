@@ -1,45 +1,54 @@
+use smallvec::smallvec;
+
 use crate::{
     debug::DebugInfo,
     kernel::{
-        geometry::Surface,
+        algorithms::tolerance::Tolerance,
+        geometry::{Ellipse, Surface},
         shape::Shape,
         topology::{edges::Cycle, faces::Face},
+        Error,
     },
-    math::{Aabb, Point, Scalar},
+    math::{Aabb, Point, Scalar, Transform, Vector},
 };
 
 use super::ToShape;
 
 impl ToShape for fj::Circle {
-    fn to_shape(&self, _: Scalar, _: &mut DebugInfo) -> Shape {
+    fn to_shape(
+        &self,
+        _: Tolerance,
+        _: &mut DebugInfo,
+    ) -> Result<Shape, Error> {
         let mut shape = Shape::new();
 
         // Circles have just a single round edge with no vertices. So none need
         // to be added here.
 
-        let edge = shape
-            .topology()
-            .add_circle(Scalar::from_f64(self.radius))
-            .unwrap();
-        shape
-            .topology()
-            .add_cycle(Cycle { edges: vec![edge] })
-            .unwrap();
+        let [x, y] = self.center;
+        let edge = shape.topology().add_circle(
+            Point::from([x, y, 0.]),
+            Scalar::from_f64(self.radius),
+        )?;
+        shape.topology().add_cycle(Cycle {
+            edges: smallvec![edge],
+        })?;
 
         let cycles = shape.topology().cycles().collect();
         let surface = shape.geometry().add_surface(Surface::x_y_plane());
-        shape
-            .topology()
-            .add_face(Face::Face { cycles, surface })
-            .unwrap();
+        shape.topology().add_face(Face::Face { cycles, surface })?;
 
-        shape
+        Ok(shape)
     }
 
-    fn bounding_volume(&self) -> Aabb<3> {
-        Aabb {
-            min: Point::from([-self.radius, -self.radius, 0.0]),
-            max: Point::from([self.radius, self.radius, 0.0]),
+    fn transformed_bounding_volume(&self, transform: &Transform) -> Aabb<3> {
+        let [x, y] = self.center;
+        Ellipse {
+            center: Point::from([x, y, 0.]),
+            a: Vector::from([self.radius, 0., 0.]),
+            b: Vector::from([0., self.radius, 0.]),
         }
+        .transform(transform)
+        .aabb()
     }
 }
@@ -0,0 +1,47 @@
+use crate::{
+    debug::DebugInfo,
+    kernel::{
+        algorithms::{
+            deform::{deform_shape, Deformation},
+            tolerance::Tolerance,
+        },
+        shape::Shape,
+        Error,
+    },
+    math::{Aabb, Transform},
+};
+
+use super::{anchor::Anchor, ToShape};
+
+impl ToShape for fj::Deform {
+    fn to_shape(
+        &self,
+        tolerance: Tolerance,
+        debug_info: &mut DebugInfo,
+    ) -> Result<Shape, Error> {
+        let shape = self.shape.to_shape(tolerance, debug_info)?;
+        deform_shape(shape, &self.deformation.into(), tolerance, debug_info)
+    }
+
+    fn transformed_bounding_volume(&self, transform: &Transform) -> Aabb<3> {
+        // The deformation happens before the transform, so the box can only be
+        // transformed after deforming it. This is conservative, if the
+        // transform includes a rotation.
+        let aabb = Deformation::from(self.deformation)
+            .deform_aabb(&self.shape.bounding_volume());
+        transform.transform_aabb(&aabb)
+    }
+
+    fn anchors(&self) -> Vec<Anchor> {
+        let deformation = Deformation::from(self.deformation);
+
+        self.shape
+            .anchors()
+            .into_iter()
+            .map(|anchor| Anchor {
+                frame: deformation.deform_frame(&anchor.frame),
+                ..anchor
+            })
+            .collect()
+    }
+}
@@ -1,46 +1,54 @@
 use std::collections::HashMap;
 
+use smallvec::SmallVec;
+
 use crate::{
     debug::DebugInfo,
     kernel::{
-        shape::Shape,
+        algorithms::tolerance::Tolerance,
+        shape::{handle::Handle, Shape},
         topology::{
             edges::{Cycle, Edge},
             faces::Face,
             vertices::Vertex,
         },
+        Error,
     },
-    math::{Aabb, Scalar},
+    math::{Aabb, Transform},
 };
 
 use super::ToShape;
 
 impl ToShape for fj::Difference2d {
-    fn to_shape(&self, tolerance: Scalar, debug_info: &mut DebugInfo) -> Shape {
+    fn to_shape(
+        &self,
+        tolerance: Tolerance,
+        debug_info: &mut DebugInfo,
+    ) -> Result<Shape, Error> {
         // This method assumes that `b` is fully contained within `a`:
         // https://github.com/hannobraun/Fornjot/issues/92
 
         let mut shape = Shape::new();
 
-        let [mut a, mut b] = [&self.a, &self.b]
-            .map(|shape| shape.to_shape(tolerance, debug_info));
+        let mut a = self.a.to_shape(tolerance, debug_info)?;
+        let mut b = self.b.to_shape(tolerance, debug_info)?;
 
         for shape in [&mut a, &mut b] {
             if shape.topology().cycles().count() != 1 {
                 // See issue:
                 // https://github.com/hannobraun/Fornjot/issues/95
-                todo!(
+                return Err(Error::Unsupported(
                     "The 2-dimensional difference operation only supports one \
-                    cycle in each operand."
-                );
+                    cycle in each operand.",
+                ));
             }
             if shape.topology().faces().count() != 1 {
                 // See issue:
                 // https://github.com/hannobraun/Fornjot/issues/95
-                todo!(
+                return Err(Error::Unsupported(
                     "The 2-dimensional difference operation only supports one \
-                    face in each operand."
-                );
+                    face in each operand.",
+                ));
             }
         }
 
@@ -49,37 +57,36 @@ impl ToShape for fj::Difference2d {
             .map(|shape| shape.topology().cycles().next().unwrap());
 
         let mut vertices = HashMap::new();
-        let mut cycles = Vec::new();
+        let mut cycles = SmallVec::new();
 
         for cycle in cycles_orig {
-            let mut edges = Vec::new();
+            let mut edges = SmallVec::new();
             for edge in &cycle.edges {
                 let curve = shape.geometry().add_curve(edge.curve());
 
-                let vertices = edge.vertices().clone().map(|vs| {
-                    vs.map(|vertex| {
-                        vertices
-                            .entry(vertex.clone())
-                            .or_insert_with(|| {
-                                let point =
-                                    shape.geometry().add_point(vertex.point());
-                                shape
-                                    .topology()
-                                    .add_vertex(Vertex { point })
-                                    .unwrap()
-                            })
-                            .clone()
-                    })
-                });
-
-                let edge = shape
-                    .topology()
-                    .add_edge(Edge { curve, vertices })
-                    .unwrap();
+                let mut add_vertex = |vertex: Vertex| -> Result<_, Error> {
+                    if let Some(handle) = vertices.get(&vertex) {
+                        return Ok(Handle::clone(handle));
+                    }
+
+                    let point = shape.geometry().add_point(vertex.point());
+                    let handle =
+                        shape.topology().add_vertex(Vertex { point })?;
+                    vertices.insert(vertex, handle.clone());
+
+                    Ok(handle)
+                };
+                let vertices = match edge.vertices() {
+                    Some([a, b]) => Some([add_vertex(a)?, add_vertex(b)?]),
+                    None => None,
+                };
+
+                let edge =
+                    shape.topology().add_edge(Edge { curve, vertices })?;
                 edges.push(edge);
             }
 
-            let cycle = shape.topology().add_cycle(Cycle { edges }).unwrap();
+            let cycle = shape.topology().add_cycle(Cycle { edges })?;
             cycles.push(cycle);
         }
 
@@ -87,24 +94,22 @@ impl ToShape for fj::Difference2d {
         let [face_a, face_b] = [&mut a, &mut b]
             .map(|shape| shape.topology().faces().next().unwrap());
 
-        assert!(
-            face_a.surface() == face_b.surface(),
-            "Trying to subtract sketches with different surfaces."
-        );
+        if face_a.surface() != face_b.surface() {
+            return Err(Error::Unsupported(
+                "Subtracting sketches with different surfaces",
+            ));
+        }
         let surface = shape.geometry().add_surface(face_a.surface());
 
-        shape
-            .topology()
-            .add_face(Face::Face { cycles, surface })
-            .unwrap();
+        shape.topology().add_face(Face::Face { cycles, surface })?;
 
-        shape
+        Ok(shape)
     }
 
-    fn bounding_volume(&self) -> Aabb<3> {
+    fn transformed_bounding_volume(&self, transform: &Transform) -> Aabb<3> {
         // This is a conservative estimate of the bounding box: It's never going
         // to be bigger than the bounding box of the original shape that another
         // is being subtracted from.
-        self.a.bounding_volume()
+        self.a.transformed_bounding_volume(transform)
     }
 }
@@ -0,0 +1,56 @@
+use smallvec::smallvec;
+
+use crate::{
+    debug::DebugInfo,
+    kernel::{
+        algorithms::tolerance::Tolerance,
+        geometry::{self, Surface},
+        shape::Shape,
+        topology::{edges::Cycle, faces::Face},
+        Error,
+    },
+    math::{Aabb, Point, Scalar, Transform, Vector},
+};
+
+use super::ToShape;
+
+impl ToShape for fj::Ellipse {
+    fn to_shape(
+        &self,
+        _: Tolerance,
+        _: &mut DebugInfo,
+    ) -> Result<Shape, Error> {
+        let mut shape = Shape::new();
+
+        // Ellipses have just a single round edge with no vertices. So none need
+        // to be added here.
+
+        let [x, y] = self.center;
+        let [rx, ry] = self.radii;
+        let edge = shape.topology().add_ellipse(
+            Point::from([x, y, 0.]),
+            [Scalar::from_f64(rx), Scalar::from_f64(ry)],
+        )?;
+        shape.topology().add_cycle(Cycle {
+            edges: smallvec![edge],
+        })?;
+
+        let cycles = shape.topology().cycles().collect();
+        let surface = shape.geometry().add_surface(Surface::x_y_plane());
+        shape.topology().add_face(Face::Face { cycles, surface })?;
+
+        Ok(shape)
+    }
+
+    fn transformed_bounding_volume(&self, transform: &Transform) -> Aabb<3> {
+        let [x, y] = self.center;
+        let [rx, ry] = self.radii;
+        geometry::Ellipse {
+            center: Point::from([x, y, 0.]),
+            a: Vector::from([rx, 0., 0.]),
+            b: Vector::from([0., ry, 0.]),
+        }
+        .transform(transform)
+        .aabb()
+    }
+}
@@ -0,0 +1,27 @@
+use crate::{
+    debug::DebugInfo,
+    kernel::{algorithms::tolerance::Tolerance, shape::Shape, Error},
+    math::{Aabb, Transform},
+};
+
+use super::{anchor::Anchor, ToShape};
+
+impl ToShape for fj::Material {
+    fn to_shape(
+        &self,
+        tolerance: Tolerance,
+        debug_info: &mut DebugInfo,
+    ) -> Result<Shape, Error> {
+        // The material doesn't affect the geometry. It is only used to compute
+        // mass properties (see `crate::materials`).
+        self.shape.to_shape(tolerance, debug_info)
+    }
+
+    fn transformed_bounding_volume(&self, transform: &Transform) -> Aabb<3> {
+        self.shape.transformed_bounding_volume(transform)
+    }
+
+    fn anchors(&self) -> Vec<Anchor> {
+        self.shape.anchors()
+    }
+}
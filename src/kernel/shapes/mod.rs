@@ -1,27 +1,62 @@
+pub mod anchor;
 pub mod circle;
+pub mod deform;
 pub mod difference_2d;
+pub mod ellipse;
+pub mod material;
 pub mod sketch;
 pub mod sweep;
+pub mod texture;
 pub mod transform;
 pub mod union;
 
 use crate::{
     debug::DebugInfo,
-    math::{Aabb, Scalar},
+    math::{Aabb, Transform},
 };
 
-use super::shape::Shape;
+use super::{algorithms::tolerance::Tolerance, shape::Shape, Error};
+
+use self::anchor::Anchor;
 
 /// Implemented by all shapes
 pub trait ToShape {
     /// Compute the boundary representation of the shape
-    fn to_shape(&self, tolerance: Scalar, debug: &mut DebugInfo) -> Shape;
+    ///
+    /// Returns an error, if the shape can't be represented by the kernel.
+    fn to_shape(
+        &self,
+        tolerance: Tolerance,
+        debug: &mut DebugInfo,
+    ) -> Result<Shape, Error>;
 
     /// Access the axis-aligned bounding box of a shape
     ///
     /// If a shape is empty, its [`Aabb`]'s `min` and `max` points must be equal
     /// (but are otherwise not specified).
-    fn bounding_volume(&self) -> Aabb<3>;
+    fn bounding_volume(&self) -> Aabb<3> {
+        self.transformed_bounding_volume(&Transform::identity())
+    }
+
+    /// Compute the axis-aligned bounding box of the shape, once transformed
+    ///
+    /// Transforming the bounding box of a shape results in a box that is too
+    /// big, if the transform includes a rotation. Instead, this computes the
+    /// box from the extrema of the transformed curves, so it fits the shape
+    /// exactly, regardless of any tolerance.
+    ///
+    /// Only differences and deformations fall back to boxes that can be
+    /// bigger than the shape. Computing their exact box would require
+    /// evaluating them.
+    fn transformed_bounding_volume(&self, transform: &Transform) -> Aabb<3>;
+
+    /// Collect the anchors of the shape
+    ///
+    /// The frames of the anchors are transformed and deformed along with the
+    /// shape, so they are in the shape's coordinate system.
+    fn anchors(&self) -> Vec<Anchor> {
+        Vec::new()
+    }
 }
 
 macro_rules! dispatch {
@@ -43,6 +78,7 @@ macro_rules! dispatch {
                     match self {
                         Self::Circle(shape) => shape.$method($($arg_name,)*),
                         Self::Difference(shape) => shape.$method($($arg_name,)*),
+                        Self::Ellipse(shape) => shape.$method($($arg_name,)*),
                         Self::Sketch(shape) => shape.$method($($arg_name,)*),
                     }
                 }
@@ -56,6 +92,10 @@ macro_rules! dispatch {
                         Self::Sweep(shape) => shape.$method($($arg_name,)*),
                         Self::Transform(shape) => shape.$method($($arg_name,)*),
                         Self::Union(shape) => shape.$method($($arg_name,)*),
+                        Self::Deform(shape) => shape.$method($($arg_name,)*),
+                        Self::Anchor(shape) => shape.$method($($arg_name,)*),
+                        Self::Material(shape) => shape.$method($($arg_name,)*),
+                        Self::Texture(shape) => shape.$method($($arg_name,)*),
                     }
                 }
             )*
@@ -65,8 +105,9 @@ macro_rules! dispatch {
 
 dispatch! {
     to_shape(
-        tolerance: Scalar,
+        tolerance: Tolerance,
         debug: &mut DebugInfo,
-    ) -> Shape;
-    bounding_volume() -> Aabb<3>;
+    ) -> Result<Shape, Error>;
+    transformed_bounding_volume(transform: &Transform,) -> Aabb<3>;
+    anchors() -> Vec<Anchor>;
 }
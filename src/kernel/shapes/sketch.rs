@@ -1,28 +1,44 @@
+use smallvec::SmallVec;
+use tracing::warn;
+
 use crate::{
     debug::DebugInfo,
     kernel::{
+        algorithms::tolerance::Tolerance,
         geometry::Surface,
         shape::Shape,
         topology::{edges::Cycle, faces::Face, vertices::Vertex},
+        Error,
     },
-    math::{Aabb, Point, Scalar},
+    math::{Aabb, Point, Scalar, Transform},
 };
 
 use super::ToShape;
 
 impl ToShape for fj::Sketch {
-    fn to_shape(&self, _: Scalar, _: &mut DebugInfo) -> Shape {
+    fn to_shape(
+        &self,
+        _: Tolerance,
+        _: &mut DebugInfo,
+    ) -> Result<Shape, Error> {
         let mut shape = Shape::new();
         let mut vertices = Vec::new();
 
-        for [x, y] in self.to_points() {
-            let point = shape.geometry().add_point(Point::from([x, y, 0.]));
-            let vertex = shape.topology().add_vertex(Vertex { point }).unwrap();
+        let points = merge_close_points(
+            self.to_points().into_iter().map(Point::from).collect(),
+            shape.min_distance(),
+        );
+
+        for point in points {
+            let point = shape.geometry().add_point(point.to_xyz());
+            let vertex = shape.topology().add_vertex(Vertex { point })?;
             vertices.push(vertex);
         }
 
         {
-            if !vertices.is_empty() {
+            // Open sketches are left open, so validating the cycle rejects
+            // them, instead of closing them without the user noticing.
+            if !vertices.is_empty() && !self.is_open() {
                 // Add the first vertex at the end again, to close the loop.
                 //
                 // This can't panic. We just checked that `vertices` is not
@@ -30,7 +46,7 @@ impl ToShape for fj::Sketch {
                 vertices.push(vertices[0].clone());
             }
 
-            let mut edges = Vec::new();
+            let mut edges = SmallVec::new();
             for window in vertices.windows(2) {
                 // Can't panic, we passed `2` to `windows`.
                 //
@@ -38,11 +54,11 @@ impl ToShape for fj::Sketch {
                 let a = window[0].clone();
                 let b = window[1].clone();
 
-                let edge = shape.topology().add_line_segment([a, b]).unwrap();
+                let edge = shape.topology().add_line_segment([a, b])?;
                 edges.push(edge);
             }
 
-            shape.topology().add_cycle(Cycle { edges }).unwrap();
+            shape.topology().add_cycle(Cycle { edges })?;
         };
 
         let surface = shape.geometry().add_surface(Surface::x_y_plane());
@@ -50,17 +66,126 @@ impl ToShape for fj::Sketch {
             cycles: shape.topology().cycles().collect(),
             surface,
         };
-        shape.topology().add_face(face).unwrap();
+        shape.topology().add_face(face)?;
 
-        shape
+        Ok(shape)
     }
 
-    fn bounding_volume(&self) -> Aabb<3> {
+    fn transformed_bounding_volume(&self, transform: &Transform) -> Aabb<3> {
         Aabb::<3>::from_points(
             self.to_points()
                 .into_iter()
                 .map(Point::from)
-                .map(Point::to_xyz),
+                .map(|point| transform.transform_point(&point.to_xyz())),
         )
     }
 }
+
+/// Merge consecutive points that are closer than `min_distance`
+///
+/// Such points would result in edges that are too short to be distinguished
+/// from a single vertex, which breaks the kernel in ways that are hard to
+/// diagnose. They are common in imported sketches, so instead of rejecting
+/// them, they are merged into the first of the points, and a warning is
+/// logged. This includes the last and the first point, which are connected
+/// too.
+fn merge_close_points(
+    points: Vec<Point<2>>,
+    min_distance: Scalar,
+) -> Vec<Point<2>> {
+    let mut merged: Vec<Point<2>> = Vec::with_capacity(points.len());
+
+    for point in points {
+        if let Some(&previous) = merged.last() {
+            if (point - previous).magnitude() < min_distance {
+                warn!(
+                    "Merging sketch point {:?} into nearly identical point \
+                    {:?}",
+                    point, previous
+                );
+                continue;
+            }
+        }
+
+        merged.push(point);
+    }
+
+    while merged.len() > 1 {
+        let (first, last) = (merged[0], merged[merged.len() - 1]);
+        if (last - first).magnitude() >= min_distance {
+            break;
+        }
+
+        warn!(
+            "Merging sketch point {:?} into nearly identical point {:?}",
+            last, first
+        );
+        merged.pop();
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        debug::DebugInfo,
+        kernel::{algorithms::tolerance::Tolerance, shapes::ToShape as _},
+    };
+
+    #[test]
+    fn to_shape_should_merge_nearly_identical_points() {
+        let sketch = fj::Sketch::from_points(vec![
+            [0., 0.],
+            [1., 0.],
+            [1. + 1e-8, 0.],
+            [1., 1.],
+            [0., 1.],
+            [0., 1e-8],
+        ]);
+
+        let mut shape =
+            sketch.to_shape(tolerance(), &mut DebugInfo::new()).unwrap();
+
+        assert!(shape.validation_warnings().is_empty());
+        assert_eq!(shape.topology().vertices().count(), 4);
+        assert_eq!(shape.topology().edges().count(), 4);
+    }
+
+    #[test]
+    fn to_shape_should_merge_identical_points() {
+        let sketch = fj::Sketch::from_points(vec![
+            [0., 0.],
+            [1., 0.],
+            [1., 0.],
+            [1., 1.],
+            [0., 1.],
+            [0., 0.],
+        ]);
+        assert_eq!(fj::Shape::from(sketch.clone()).check(), Ok(()));
+
+        let mut shape =
+            sketch.to_shape(tolerance(), &mut DebugInfo::new()).unwrap();
+
+        assert!(shape.validation_warnings().is_empty());
+        assert_eq!(shape.topology().vertices().count(), 4);
+        assert_eq!(shape.topology().edges().count(), 4);
+    }
+
+    #[test]
+    fn to_shape_should_reject_open_sketch() {
+        let path = vec![[0., 0.], [1., 0.], [1., 1.], [0., 1.]];
+
+        let sketch = fj::Sketch::from_path(path, 1e-6);
+        let result = sketch.to_shape(tolerance(), &mut DebugInfo::new());
+        assert!(result.is_err());
+
+        let sketch = sketch.with_auto_close();
+        let result = sketch.to_shape(tolerance(), &mut DebugInfo::new());
+        assert!(result.is_ok());
+    }
+
+    fn tolerance() -> Tolerance {
+        Tolerance::from_scalar(1.).unwrap()
+    }
+}
@@ -1,23 +1,160 @@
 use crate::{
     debug::DebugInfo,
-    kernel::{algorithms::sweep::sweep_shape, shape::Shape},
-    math::{Aabb, Scalar, Vector},
+    kernel::{
+        algorithms::{sweep::sweep_shape, tolerance::Tolerance},
+        shape::Shape,
+        Error,
+    },
+    math::{Aabb, Transform, Vector},
 };
 
 use super::ToShape;
 
 impl ToShape for fj::Sweep {
-    fn to_shape(&self, tolerance: Scalar, debug_info: &mut DebugInfo) -> Shape {
+    fn to_shape(
+        &self,
+        tolerance: Tolerance,
+        debug_info: &mut DebugInfo,
+    ) -> Result<Shape, Error> {
         sweep_shape(
-            self.shape.to_shape(tolerance, debug_info),
+            self.shape.to_shape(tolerance, debug_info)?,
             Vector::from([0., 0., self.length]),
             tolerance,
         )
     }
 
-    fn bounding_volume(&self) -> Aabb<3> {
-        let mut aabb = self.shape.bounding_volume();
-        aabb.max.z = self.length.into();
-        aabb
+    fn transformed_bounding_volume(&self, transform: &Transform) -> Aabb<3> {
+        // The swept shape is the convex hull of its bottom and top, as far as
+        // each axis is concerned. So the box of both is the box of the sweep.
+        let top = transform.compose(&Transform::translation(Vector::from([
+            0.,
+            0.,
+            self.length,
+        ])));
+
+        let bottom = self.shape.transformed_bounding_volume(transform);
+        let top = self.shape.transformed_bounding_volume(&top);
+
+        bottom.merged(&top)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        kernel::shapes::ToShape as _,
+        math::{Point, Scalar},
+        testing::Evaluated,
+    };
+
+    fn sweep(points: Vec<[f64; 2]>) -> Evaluated {
+        let sketch = fj::Sketch::from_points(points);
+        let sweep = fj::Sweep {
+            shape: sketch.into(),
+            length: 1.,
+        };
+
+        Evaluated::new(&sweep.into())
+    }
+
+    #[test]
+    fn sweep_should_handle_concave_sketches() {
+        // The triangulation of the caps must not cover the gaps between the
+        // teeth, or leave out any of the teeth.
+        #[rustfmt::skip]
+        let comb = sweep(vec![
+            [0., 0.], [5., 0.], [5., 3.], [4., 3.], [4., 0.5], [3., 0.5],
+            [3., 3.], [2., 3.], [2., 0.5], [1., 0.5], [1., 3.], [0., 3.],
+        ]);
+        comb.assert_aabb([0., 0., 0.], [5., 3., 1.]);
+        comb.assert_watertight();
+        comb.assert_volume(10., 1e-9);
+
+        // A narrow notch, whose tip is close to a long edge.
+        let notched = sweep(vec![
+            [0., 0.],
+            [4., 0.],
+            [4., 4.],
+            [2.1, 4.],
+            [2., 0.2],
+            [1.9, 4.],
+            [0., 4.],
+        ]);
+        notched.assert_watertight();
+        notched.assert_volume(16. - 0.38, 1e-9);
+    }
+
+    #[test]
+    fn sweep_should_handle_star_with_hole() {
+        let star = |radius: f64| {
+            (0..10).map(move |i| {
+                let angle = std::f64::consts::PI / 5. * i as f64;
+                let radius = if i % 2 == 0 { radius } else { radius * 2. };
+                let (sin, cos) = angle.sin_cos();
+                [cos * radius, sin * radius]
+            })
+        };
+
+        let footprint = fj::Difference2d {
+            a: fj::Sketch::from_points(star(1.).collect()).into(),
+            b: fj::Sketch::from_points(star(0.5).collect()).into(),
+        };
+        let sweep = fj::Sweep {
+            shape: footprint.into(),
+            length: 1.,
+        };
+        let evaluated = Evaluated::new(&sweep.into());
+
+        // A star with 5 points consists of 10 triangles, each with the area
+        // `r1 * r2 * sin(PI / 5) / 2`. The hole is half the size.
+        let area = 10. * (std::f64::consts::PI / 5.).sin();
+        evaluated.assert_watertight();
+        evaluated.assert_volume(area * 0.75, 1e-9);
+    }
+
+    #[test]
+    fn bounding_volume_should_fit_exact_geometry() {
+        let assert_aabb = |shape: fj::Shape, min: [f64; 3], max: [f64; 3]| {
+            let aabb = shape.bounding_volume();
+            for (actual, expected) in [(aabb.min, min), (aabb.max, max)] {
+                assert!(
+                    (actual - Point::from(expected)).magnitude()
+                        < Scalar::from_f64(1e-9),
+                    "Unexpected bounding box: {:?}",
+                    aabb,
+                );
+            }
+        };
+
+        let circle = || fj::Circle {
+            center: [0., 0.],
+            radius: 1.,
+        };
+
+        let down = fj::Sweep {
+            shape: circle().into(),
+            length: -1.,
+        };
+        assert_aabb(down.into(), [-1., -1., -1.], [1., 1., 0.]);
+
+        // Rotating the box of the sweep would make it bigger than the sweep.
+        let rotated = fj::Transform {
+            shape: fj::Sweep {
+                shape: circle().into(),
+                length: 2.,
+            }
+            .into(),
+            axis: [1., 0., 0.],
+            angle: PI / 4.,
+            offset: [0., 0., 0.],
+        };
+        let half = 0.5_f64.sqrt();
+        assert_aabb(
+            rotated.into(),
+            [-1., -2. * half - half, -half],
+            [1., half, 2. * half + half],
+        );
     }
 }
@@ -0,0 +1,38 @@
+use crate::{
+    debug::DebugInfo,
+    kernel::{
+        algorithms::{texture::texture_shape, tolerance::Tolerance},
+        shape::Shape,
+        Error,
+    },
+    math::{Aabb, Transform, Vector},
+};
+
+use super::{anchor::Anchor, ToShape};
+
+impl ToShape for fj::Texture {
+    fn to_shape(
+        &self,
+        tolerance: Tolerance,
+        debug_info: &mut DebugInfo,
+    ) -> Result<Shape, Error> {
+        let shape = self.shape.to_shape(tolerance, debug_info)?;
+        texture_shape(shape, self, tolerance, debug_info)
+    }
+
+    fn transformed_bounding_volume(&self, transform: &Transform) -> Aabb<3> {
+        // The texture is cut into the shape, unless its depth is negative, in
+        // which case it stands out from it.
+        let margin = self.depth.min(0.).abs();
+        let aabb = self.shape.bounding_volume();
+        let aabb = Aabb {
+            min: aabb.min + Vector::from([-margin; 3]),
+            max: aabb.max + Vector::from([margin; 3]),
+        };
+        transform.transform_aabb(&aabb)
+    }
+
+    fn anchors(&self) -> Vec<Anchor> {
+        self.shape.anchors()
+    }
+}
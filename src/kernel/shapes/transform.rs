@@ -2,21 +2,43 @@ use parry3d_f64::math::Isometry;
 
 use crate::{
     debug::DebugInfo,
-    kernel::{algorithms::transform::transform_shape, shape::Shape},
-    math::{Aabb, Scalar, Transform},
+    kernel::{
+        algorithms::{tolerance::Tolerance, transform::transform_shape},
+        shape::Shape,
+        Error,
+    },
+    math::{Aabb, Transform},
 };
 
-use super::ToShape;
+use super::{anchor::Anchor, ToShape};
 
 impl ToShape for fj::Transform {
-    fn to_shape(&self, tolerance: Scalar, debug_info: &mut DebugInfo) -> Shape {
-        let shape = self.shape.to_shape(tolerance, debug_info);
+    fn to_shape(
+        &self,
+        tolerance: Tolerance,
+        debug_info: &mut DebugInfo,
+    ) -> Result<Shape, Error> {
+        let shape = self.shape.to_shape(tolerance, debug_info)?;
         let transform = transform(self);
-        transform_shape(shape, &transform)
+        transform_shape(shape, &transform, tolerance)
     }
 
-    fn bounding_volume(&self) -> Aabb<3> {
-        transform(self).transform_aabb(&self.shape.bounding_volume())
+    fn transformed_bounding_volume(&self, outer: &Transform) -> Aabb<3> {
+        self.shape
+            .transformed_bounding_volume(&outer.compose(&transform(self)))
+    }
+
+    fn anchors(&self) -> Vec<Anchor> {
+        let transform = transform(self);
+
+        self.shape
+            .anchors()
+            .into_iter()
+            .map(|anchor| Anchor {
+                frame: transform.transform_frame(&anchor.frame),
+                ..anchor
+            })
+            .collect()
     }
 }
 
@@ -28,3 +50,34 @@ fn transform(transform: &fj::Transform) -> Transform {
     )
     .into()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use crate::testing::Evaluated;
+
+    #[test]
+    fn rotated_cylinders_should_be_watertight() {
+        let cylinder = fj::Sweep {
+            shape: fj::Circle {
+                center: [0., 0.],
+                radius: 1.,
+            }
+            .into(),
+            length: 2.,
+        };
+
+        // Rotating the cylinder turns its circular edges into ellipses.
+        for axis in [[1., 0., 0.], [1., 1., 0.]] {
+            let rotated = fj::Transform {
+                shape: cylinder.clone().into(),
+                axis,
+                angle: FRAC_PI_2,
+                offset: [0., 0., 0.],
+            };
+
+            Evaluated::new(&rotated.into()).assert_watertight();
+        }
+    }
+}
@@ -3,45 +3,57 @@ use std::collections::HashMap;
 use crate::{
     debug::DebugInfo,
     kernel::{
+        algorithms::tolerance::Tolerance,
         shape::Shape,
         topology::{
             edges::{Cycle, Edge},
             faces::Face,
             vertices::Vertex,
         },
+        Error,
     },
-    math::{Aabb, Scalar},
+    math::{Aabb, Transform},
 };
 
-use super::ToShape;
+use super::{anchor::Anchor, ToShape};
 
 impl ToShape for fj::Union {
-    fn to_shape(&self, tolerance: Scalar, debug_info: &mut DebugInfo) -> Shape {
+    fn to_shape(
+        &self,
+        tolerance: Tolerance,
+        debug_info: &mut DebugInfo,
+    ) -> Result<Shape, Error> {
         let mut shape = Shape::new();
 
-        let a = self.a.to_shape(tolerance, debug_info);
-        let b = self.b.to_shape(tolerance, debug_info);
+        let a = self.a.to_shape(tolerance, debug_info)?;
+        let b = self.b.to_shape(tolerance, debug_info)?;
 
         // This doesn't create a true union, as it doesn't eliminate, merge, or
         // split faces.
         //
         // See issue:
         // https://github.com/hannobraun/Fornjot/issues/42
-        copy_shape(a, &mut shape);
-        copy_shape(b, &mut shape);
+        copy_shape(a, &mut shape)?;
+        copy_shape(b, &mut shape)?;
 
-        shape
+        Ok(shape)
     }
 
-    fn bounding_volume(&self) -> Aabb<3> {
-        let a = self.a.bounding_volume();
-        let b = self.b.bounding_volume();
+    fn transformed_bounding_volume(&self, transform: &Transform) -> Aabb<3> {
+        let a = self.a.transformed_bounding_volume(transform);
+        let b = self.b.transformed_bounding_volume(transform);
 
         a.merged(&b)
     }
+
+    fn anchors(&self) -> Vec<Anchor> {
+        let mut anchors = self.a.anchors();
+        anchors.extend(self.b.anchors());
+        anchors
+    }
 }
 
-fn copy_shape(mut orig: Shape, target: &mut Shape) {
+fn copy_shape(mut orig: Shape, target: &mut Shape) -> Result<(), Error> {
     let mut points = HashMap::new();
     let mut curves = HashMap::new();
     let mut surfaces = HashMap::new();
@@ -55,46 +67,38 @@ fn copy_shape(mut orig: Shape, target: &mut Shape) {
         points.insert(point_orig, point);
     }
     for curve_orig in orig.geometry().curves() {
-        let curve = target.geometry().add_curve(*curve_orig.get());
+        let curve = target.geometry().add_curve(curve_orig.get().clone());
         curves.insert(curve_orig, curve);
     }
     for surface_orig in orig.geometry().surfaces() {
-        let surface = target.geometry().add_surface(*surface_orig.get());
+        let surface = target.geometry().add_surface(surface_orig.get().clone());
         surfaces.insert(surface_orig, surface);
     }
 
     for vertex_orig in orig.topology().vertices() {
-        let vertex = target
-            .topology()
-            .add_vertex(Vertex {
-                point: points[&vertex_orig.point].clone(),
-            })
-            .unwrap();
+        let vertex = target.topology().add_vertex(Vertex {
+            point: points[&vertex_orig.point].clone(),
+        })?;
         vertices.insert(vertex_orig, vertex);
     }
     for edge_orig in orig.topology().edges() {
-        let edge = target
-            .topology()
-            .add_edge(Edge {
-                curve: curves[&edge_orig.curve].clone(),
-                vertices: edge_orig.vertices.as_ref().map(|vs| {
-                    vs.clone().map(|vertex| vertices[&vertex].clone())
-                }),
-            })
-            .unwrap();
+        let edge = target.topology().add_edge(Edge {
+            curve: curves[&edge_orig.curve].clone(),
+            vertices: edge_orig
+                .vertices
+                .as_ref()
+                .map(|vs| vs.clone().map(|vertex| vertices[&vertex].clone())),
+        })?;
         edges.insert(edge_orig, edge);
     }
     for cycle_orig in orig.topology().cycles() {
-        let cycle = target
-            .topology()
-            .add_cycle(Cycle {
-                edges: cycle_orig
-                    .edges
-                    .iter()
-                    .map(|edge| edges[edge].clone())
-                    .collect(),
-            })
-            .unwrap();
+        let cycle = target.topology().add_cycle(Cycle {
+            edges: cycle_orig
+                .edges
+                .iter()
+                .map(|edge| edges[edge].clone())
+                .collect(),
+        })?;
         cycles.insert(cycle_orig, cycle);
     }
 
@@ -104,20 +108,89 @@ fn copy_shape(mut orig: Shape, target: &mut Shape) {
                 surface,
                 cycles: cs,
             } => {
-                target
-                    .topology()
-                    .add_face(Face::Face {
-                        surface: surfaces[surface].clone(),
-                        cycles: cs
-                            .iter()
-                            .map(|cycle| cycles[cycle].clone())
-                            .collect(),
-                    })
-                    .unwrap();
+                target.topology().add_face(Face::Face {
+                    surface: surfaces[surface].clone(),
+                    cycles: cs
+                        .iter()
+                        .map(|cycle| cycles[cycle].clone())
+                        .collect(),
+                })?;
             }
             face @ Face::Triangles(_) => {
-                target.topology().add_face(face.clone()).unwrap();
+                target.topology().add_face(face.clone())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use fj::enclosure::{Enclosure, Lid, Standoff};
+
+    use crate::testing::Evaluated;
+
+    #[test]
+    fn union_should_combine_parts_of_enclosure() {
+        // The enclosure combines sweeps of sketches, differences, and circles,
+        // some of them transformed, into one shape.
+        let mut enclosure = Enclosure::new([60., 40., 25.], 2.)
+            .with_standoff(Standoff::new([10., 10.]))
+            .with_standoff(Standoff::new([50., 30.]));
+        enclosure.corner_radius = 0.;
+
+        let base = enclosure.base();
+        let tolerance = 0.01;
+        let evaluated =
+            Evaluated::with_tolerance(&base.clone().into(), tolerance);
+        evaluated.assert_aabb([0., 0., 0.], [60., 40., 23.]);
+
+        // The union doesn't merge the parts where they touch, so the result
+        // isn't watertight. Each part is, though, and the union contains all
+        // of their triangles.
+        let mut parts = Vec::new();
+        collect_parts(base, &mut parts);
+        assert_eq!(parts.len(), 4);
+
+        let parts: Vec<_> = parts
+            .into_iter()
+            .map(|part| Evaluated::with_tolerance(&part.into(), tolerance))
+            .collect();
+        let num_triangles = parts
+            .iter()
+            .map(|part| part.triangles().len())
+            .sum::<usize>();
+        assert_eq!(evaluated.triangles().len(), num_triangles);
+
+        let [floor, walls, standoff_a, standoff_b] = [0, 1, 2, 3];
+        parts[floor].assert_volume(60. * 40. * 2., 1e-9);
+        parts[walls].assert_volume((60. * 40. - 56. * 36.) * 21., 1e-9);
+
+        let standoff = PI * (3_f64.powi(2) - 1.1_f64.powi(2)) * 5.;
+        parts[standoff_a].assert_volume(standoff, 0.01);
+        parts[standoff_b].assert_volume(standoff, 0.01);
+
+        enclosure.corner_radius = 4.;
+        enclosure.lid = Lid::Inset {
+            depth: 3.,
+            clearance: 0.2,
+        };
+
+        let lid = Evaluated::new(&enclosure.lid().into());
+        lid.assert_aabb([0., 0., 20.], [60., 40., 25.]);
+    }
+
+    fn collect_parts(shape: fj::Shape3d, parts: &mut Vec<fj::Shape3d>) {
+        match shape {
+            fj::Shape3d::Union(union) => {
+                let fj::Union { a, b } = *union;
+                collect_parts(a, parts);
+                collect_parts(b, parts);
             }
+            shape => parts.push(shape),
         }
     }
 }
@@ -1,5 +1,7 @@
 use std::hash::{Hash, Hasher};
 
+use smallvec::SmallVec;
+
 use crate::kernel::{geometry::Curve, shape::handle::Handle};
 
 use super::vertices::Vertex;
@@ -16,7 +18,11 @@ use super::vertices::Vertex;
 /// equality of topological objects.
 #[derive(Clone, Debug, Eq, Ord, PartialOrd)]
 pub struct Cycle {
-    pub edges: Vec<Handle<Edge>>,
+    /// The edges that make up the cycle
+    ///
+    /// Most cycles consist of only a few edges, so up to four of them are
+    /// stored inline, without allocating.
+    pub edges: SmallVec<[Handle<Edge>; 4]>,
 }
 
 impl Cycle {
@@ -81,7 +87,7 @@ impl Edge {
     /// This is a convenience method that saves the caller from dealing with the
     /// [`Handle`].
     pub fn curve(&self) -> Curve {
-        *self.curve.get()
+        self.curve.get().clone()
     }
 
     /// Access the vertices that the edge refers to
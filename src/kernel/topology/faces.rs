@@ -1,21 +1,24 @@
 use std::{
-    collections::BTreeSet,
+    collections::BTreeMap,
+    f64::consts::PI,
     hash::{Hash, Hasher},
 };
 
-use parry2d_f64::query::{Ray as Ray2, RayCast as _};
+use nalgebra::Vector3;
 use parry3d_f64::query::Ray as Ray3;
+use smallvec::SmallVec;
 
 use crate::{
     debug::{DebugInfo, TriangleEdgeCheck},
     kernel::{
         algorithms::{
-            approximation::Approximation, triangulation::triangulate,
+            approximation::Approximation, arena, tolerance::Tolerance,
+            triangulation::triangulate,
         },
-        geometry::Surface,
+        geometry::{self, Curve, Ellipse, Surface},
         shape::handle::Handle,
     },
-    math::{Aabb, Scalar, Segment, Triangle},
+    math::{Aabb, AabbTree, Point, Scalar, Segment, Triangle, Vector},
 };
 
 use super::edges::Cycle;
@@ -46,7 +49,10 @@ pub enum Face {
         ///
         /// It might be less error-prone to specify the edges in surface
         /// coordinates.
-        cycles: Vec<Handle<Cycle>>,
+        ///
+        /// Most faces have no more than one hole, so up to two cycles are
+        /// stored inline, without allocating.
+        cycles: SmallVec<[Handle<Cycle>; 2]>,
     },
 
     /// The triangles of the face
@@ -65,7 +71,7 @@ impl Face {
     /// [`Handle`].
     pub fn surface(&self) -> Surface {
         match self {
-            Self::Face { surface, .. } => *surface.get(),
+            Self::Face { surface, .. } => surface.get().clone(),
             _ => {
                 // No code that still uses triangle representation is calling
                 // this method.
@@ -93,7 +99,7 @@ impl Face {
 
     pub fn triangles(
         &self,
-        tolerance: Scalar,
+        tolerance: Tolerance,
         out: &mut Vec<Triangle<3>>,
         debug_info: &mut DebugInfo,
     ) {
@@ -101,124 +107,108 @@ impl Face {
             Self::Face { surface, .. } => {
                 let approx = Approximation::for_face(self, tolerance);
 
-                let points: Vec<_> = approx
-                    .points
-                    .into_iter()
-                    .map(|vertex| {
-                        // Can't panic, unless the approximation wrongfully
-                        // generates points that are not in the surface.
-                        surface.point_model_to_surface(vertex)
-                    })
-                    .collect();
+                // Most faces of typical models lie in axis-aligned planes, for
+                // which converting between model and surface coordinates is
+                // much cheaper than in general.
+                let plane = surface.axis_plane();
+                let point_model_to_surface = |point: Point<3>| match &plane {
+                    Some(plane) => geometry::Point::new(
+                        plane.point_model_to_surface(&point),
+                        point,
+                    ),
+                    None => surface.point_model_to_surface(point),
+                };
+
+                let mut points = arena::take();
+                points.extend(approx.points.into_iter().map(|vertex| {
+                    // Can't panic, unless the approximation wrongfully
+                    // generates points that are not in the surface.
+                    point_model_to_surface(vertex)
+                }));
 
-                let segments: Vec<_> = approx
-                    .segments
-                    .into_iter()
-                    .map(|segment| {
-                        let [a, b] = segment.points();
+                let mut segments = arena::take();
+                segments.extend(approx.segments.into_iter().map(|segment| {
+                    let [a, b] = segment.points();
 
-                        // Can't panic, unless the approximation wrongfully
-                        // generates points that are not in the surface.
-                        let a = surface.point_model_to_surface(a);
-                        let b = surface.point_model_to_surface(b);
+                    // Can't panic, unless the approximation wrongfully
+                    // generates points that are not in the surface.
+                    let a = point_model_to_surface(a);
+                    let b = point_model_to_surface(b);
 
-                        [a, b]
-                    })
-                    .collect();
+                    [a, b]
+                }));
 
-                // We're also going to need a point outside of the polygon, for
-                // the point-in-polygon tests.
-                let aabb = Aabb::<2>::from_points(
-                    points.iter().map(|vertex| vertex.native()),
-                );
-                let outside = aabb.max * 2.;
+                let mut triangles = triangulate(&points, &segments);
 
-                let mut triangles = triangulate(points);
-                let face_as_polygon = segments;
+                // Organize the edges of the face by position, so the
+                // ray-casting below only needs to look at the edges that the
+                // ray can actually hit.
+                let mut edges = AabbTree::new();
+                for edge in segments.iter() {
+                    let edge = edge.map(|point| point.native());
+                    edges.insert(
+                        Aabb::<2>::from_points(edge),
+                        Segment::from(edge),
+                    );
+                }
 
                 triangles.retain(|t| {
-                    for segment in [t[0], t[1], t[2], t[0]].windows(2) {
-                        // This can't panic, as we passed `2` to `windows`. It
-                        // can be cleaned up a bit, once `array_windows` is
-                        // stable.
-                        let segment = [segment[0], segment[1]];
-                        let inverted_segment = [segment[1], segment[0]];
-
-                        // If the segment is an edge of the face, we don't need
-                        // to take a closer look.
-                        if face_as_polygon.contains(&segment) {
-                            continue;
-                        }
-                        if face_as_polygon.contains(&inverted_segment) {
-                            continue;
-                        }
-
-                        // To determine if the edge is within the polygon, we
-                        // determine if its center point is in the polygon.
-                        let center = segment[0]
-                            + (segment[1] - segment[0]) / Scalar::TWO;
-
-                        let origin = center;
-                        let dir = outside - center;
-                        let ray = Ray2 {
-                            origin: origin.to_na(),
-                            dir: dir.to_na(),
-                        };
-
-                        let mut check = TriangleEdgeCheck::new(Ray3 {
+                    // The edges of the face are part of the triangulation, so
+                    // each triangle is either completely within the face, or
+                    // completely outside of it. To find out which, we check
+                    // whether its center point is in the face-polygon. Unlike
+                    // points on its edges, the center of a triangle can't lie
+                    // on an edge of the face.
+                    let center = t[0]
+                        + ((t[1] - t[0]) + (t[2] - t[0]))
+                            / Scalar::from_f64(3.);
+
+                    // Use ray-casting to determine if `center` is within the
+                    // face-polygon. The ray goes into the direction of the
+                    // u-axis, which keeps the intersection tests simple.
+                    let dir = Vector::from([1., 0.]);
+
+                    let ray = match &plane {
+                        Some(plane) => Ray3 {
+                            origin: plane
+                                .point_surface_to_model(&center)
+                                .to_na(),
+                            dir: plane.vector_surface_to_model(&dir).to_na(),
+                        },
+                        None => Ray3 {
                             origin: surface
-                                .point_surface_to_model(&origin)
+                                .point_surface_to_model(&center)
                                 .to_na(),
                             dir: surface.vector_surface_to_model(&dir).to_na(),
-                        });
-
-                        // We need to keep track of where our ray hits the
-                        // edges. Otherwise, if the ray hits a vertex, we might
-                        // count that hit twice, as every vertex is attached to
-                        // two edges.
-                        let mut hits = BTreeSet::new();
-
-                        // Use ray-casting to determine if `center` is within
-                        // the face-polygon.
-                        for edge in &face_as_polygon {
-                            // Please note that we if we get to this point, then
-                            // the point is not on a polygon edge, due to the
-                            // check above. We don't need to handle any edge
-                            // cases that would arise from that case.
-
-                            let edge =
-                                Segment::from(edge.map(|point| point.native()));
-
-                            let intersection = edge
-                                .to_parry()
-                                .cast_local_ray(&ray, f64::INFINITY, true)
-                                .map(Scalar::from_f64);
-
-                            if let Some(t) = intersection {
-                                // Due to slight inaccuracies, we might get
-                                // different values for the same intersections.
-                                // Let's round `t` before using it.
-                                let eps = 1_000_000.0;
-                                let t = (t * eps).round() / eps;
-
-                                if hits.insert(t) {
-                                    check.hits.push(t.into_f64());
-                                }
-                            }
+                        },
+                    };
+                    let mut check = TriangleEdgeCheck::new(ray);
+
+                    let mut inside = false;
+                    for edge in edges.query_ray(&center, &dir) {
+                        let [a, b] = edge.points();
+
+                        // Each edge includes its lower end point, but not its
+                        // upper one. If the ray goes through a vertex, it is
+                        // counted once, if the face boundary crosses the ray
+                        // there, and not at all or twice otherwise.
+                        if (a.v > center.v) == (b.v > center.v) {
+                            continue;
                         }
 
-                        debug_info.triangle_edge_checks.push(check);
-
-                        if hits.len() % 2 == 0 {
-                            // The segment is outside of the face. This means we
-                            // can throw away the whole triangle.
-                            return false;
+                        let t = a.u
+                            + (center.v - a.v) * (b.u - a.u) / (b.v - a.v)
+                            - center.u;
+                        if t > Scalar::ZERO {
+                            inside = !inside;
+                            check.hits.push(t.into_f64());
                         }
                     }
 
-                    // If we didn't throw away the triangle up till now, this
-                    // means all its edges are within the face.
-                    true
+                    debug_info.triangle_edge_checks.push(check);
+
+                    // If the triangle is outside of the face, we throw it away.
+                    inside
                 });
 
                 out.extend(triangles.into_iter().map(|triangle| {
@@ -231,6 +221,201 @@ impl Face {
     }
 }
 
+impl Face {
+    /// Compute the area of the face
+    ///
+    /// The area of a plane face, that is bounded by lines, circles, and
+    /// ellipses, is computed exactly, using Green's theorem. The largest cycle
+    /// of such a face is its outer boundary, and all other cycles are holes in
+    /// it. The area of any other face is approximated by its triangles, using
+    /// `tolerance`.
+    pub fn area(&self, tolerance: Tolerance) -> Scalar {
+        if let Some(cycles) = self.exact_cycles() {
+            // The sign of an area only tells the orientation of its cycle,
+            // which differs between faces.
+            let areas = cycles.iter().map(|&(area, _)| area.abs());
+            let outer = areas.clone().fold(0., f64::max);
+            let holes = areas.sum::<f64>() - outer;
+
+            return Scalar::from_f64(outer - holes);
+        }
+
+        let mut triangles = Vec::new();
+        self.triangles(tolerance, &mut triangles, &mut DebugInfo::new());
+
+        triangles
+            .iter()
+            .map(|triangle| {
+                let [a, b, c] = triangle.points();
+                (b - a).cross(&(c - a)).magnitude() / 2.
+            })
+            .fold(Scalar::ZERO, |sum, area| sum + area)
+    }
+
+    /// Compute the perimeter of the face
+    ///
+    /// This is the total length of all cycles of the face, including those
+    /// that bound its holes. Like [`Face::area`], it is computed exactly for
+    /// plane faces that are bounded by lines, circles, and ellipses. The
+    /// perimeter of any other face is approximated by the approximation of its
+    /// cycles, using `tolerance`.
+    ///
+    /// For a face that is represented by triangles, the perimeter is the
+    /// length of those triangle edges, that belong to only one triangle.
+    pub fn perimeter(&self, tolerance: Tolerance) -> Scalar {
+        if let Some(cycles) = self.exact_cycles() {
+            let length: f64 = cycles.iter().map(|&(_, length)| length).sum();
+            return Scalar::from_f64(length);
+        }
+
+        match self {
+            Self::Face { .. } => self
+                .cycles()
+                .flat_map(|cycle| {
+                    Approximation::for_cycle(&cycle, tolerance).segments
+                })
+                .map(|segment| {
+                    let [a, b] = segment.points();
+                    (b - a).magnitude()
+                })
+                .fold(Scalar::ZERO, |sum, length| sum + length),
+            Self::Triangles(triangles) => {
+                let mut edges = BTreeMap::new();
+                for triangle in triangles {
+                    let [a, b, c] = triangle.points();
+                    for [a, b] in [[a, b], [b, c], [c, a]] {
+                        let edge = if a < b { [a, b] } else { [b, a] };
+                        *edges.entry(edge).or_insert(0) += 1;
+                    }
+                }
+
+                edges
+                    .into_iter()
+                    .filter(|&(_, count)| count == 1)
+                    .map(|([a, b], _)| (b - a).magnitude())
+                    .fold(Scalar::ZERO, |sum, length| sum + length)
+            }
+        }
+    }
+
+    /// Compute the signed area and length of each cycle exactly
+    ///
+    /// Returns `None`, if the face is not a plane face, that is bounded by
+    /// lines, circles, and ellipses.
+    fn exact_cycles(&self) -> Option<SmallVec<[(f64, f64); 2]>> {
+        let surface = match self {
+            Self::Face { surface, .. } => surface.get(),
+            Self::Triangles(_) => return None,
+        };
+        let (origin, u, v) = match surface {
+            Surface::Swept(swept) => match &swept.curve {
+                Curve::Line(line) => {
+                    let u = line.direction.to_na().normalize();
+                    let normal = u.cross(&swept.path.to_na());
+                    if normal.norm() == 0. {
+                        return None;
+                    }
+
+                    (line.origin, u, normal.normalize().cross(&u))
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        // Coordinates in an orthonormal frame of the plane. Surface
+        // coordinates can't be used, as they might be skewed or scaled.
+        let to_plane = |point: Point<3>| {
+            let point = (point - origin).to_na();
+            [point.dot(&u), point.dot(&v)]
+        };
+
+        let mut cycles = SmallVec::new();
+        for cycle in self.cycles() {
+            let mut area = 0.;
+            let mut length = 0.;
+
+            for edge in cycle.edges() {
+                let vertices = edge.vertices().map(|vertices| {
+                    vertices.map(|vertex| {
+                        (vertex.point(), to_plane(vertex.point()))
+                    })
+                });
+
+                // The area between the chord of the edge and the origin of the
+                // plane. For edges without vertices, there is no chord.
+                if let Some([(_, [x0, y0]), (_, [x1, y1])]) = vertices {
+                    area += (x0 * y1 - x1 * y0) / 2.;
+                }
+
+                let ellipse = match edge.curve() {
+                    Curve::Line(_) => {
+                        let [(a, _), (b, _)] = vertices?;
+                        length += (b - a).magnitude().into_f64();
+                        continue;
+                    }
+                    Curve::Circle(circle) => Ellipse::from(circle),
+                    Curve::Ellipse(ellipse) => ellipse,
+                    Curve::External(_) => return None,
+                };
+
+                // The ellipse has the same orientation as the cycle, if it
+                // runs counter-clockwise around the plane's normal.
+                let [a, b] = [ellipse.a, ellipse.b].map(Vector::to_na);
+                let orientation = a.cross(&b).dot(&u.cross(&v));
+                if orientation == 0. {
+                    return None;
+                }
+
+                // Arcs run from their first vertex to their second, in the
+                // direction of increasing curve coordinates.
+                let (start, angle) = match vertices {
+                    Some([(start, _), (end, _)]) => {
+                        let [start, end] = [start, end].map(|point| {
+                            ellipse.point_model_to_curve(&point).t.into_f64()
+                        });
+                        (start, (end - start).rem_euclid(2. * PI))
+                    }
+                    None => (0., 2. * PI),
+                };
+
+                // The area between the arc and its chord. That is the area of
+                // a circular segment, scaled to the ellipse.
+                let segment = a.cross(&b).norm() / 2. * (angle - angle.sin());
+                area += segment * orientation.signum();
+
+                length += arc_length(a, b, start, angle);
+            }
+
+            cycles.push((area, length));
+        }
+
+        Some(cycles)
+    }
+}
+
+/// Compute the length of an elliptic arc
+///
+/// The ellipse is defined by its radius vectors, and the arc starts at the
+/// curve coordinate `start`, covering `angle`. There is no closed form for the
+/// length of an ellipse, so it is integrated numerically. The integrand is
+/// smooth and periodic, for which the composite Simpson's rule converges to
+/// the accuracy of floating point numbers quickly. For circles, it is exact.
+fn arc_length(a: Vector3<f64>, b: Vector3<f64>, start: f64, angle: f64) -> f64 {
+    const INTERVALS: usize = 1024;
+
+    let h = angle / INTERVALS as f64;
+    let speed = |t: f64| (b * t.cos() - a * t.sin()).norm();
+
+    let mut sum = speed(start) + speed(start + angle);
+    for i in 1..INTERVALS {
+        let weight = if i % 2 == 1 { 4. } else { 2. };
+        sum += weight * speed(start + h * i as f64);
+    }
+
+    sum * h / 3.
+}
+
 impl PartialEq for Face {
     fn eq(&self, other: &Self) -> bool {
         self.surface() == other.surface() && self.cycles().eq(other.cycles())
@@ -245,3 +430,118 @@ impl Hash for Face {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use smallvec::smallvec;
+
+    use crate::{
+        debug::DebugInfo,
+        kernel::{
+            algorithms::tolerance::Tolerance,
+            geometry::{Circle, Curve, Surface},
+            shape::Shape,
+            shapes::ToShape as _,
+            topology::{
+                edges::{Cycle, Edge},
+                vertices::Vertex,
+            },
+        },
+        math::{Point, Scalar, Triangle, Vector},
+    };
+
+    use super::Face;
+
+    fn assert_close(actual: Scalar, expected: f64) {
+        let actual = actual.into_f64();
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn area_and_perimeter_should_be_exact_for_plane_faces() {
+        let tolerance = Tolerance::from_scalar(0.1).unwrap();
+
+        // A rectangle with a circular hole
+        let sketch: fj::Shape2d = fj::Difference2d {
+            a: fj::Sketch::from_points(vec![
+                [0., 0.],
+                [4., 0.],
+                [4., 2.],
+                [0., 2.],
+            ])
+            .into(),
+            b: fj::Circle {
+                center: [2., 1.],
+                radius: 0.5,
+            }
+            .into(),
+        }
+        .into();
+        let mut shape =
+            sketch.to_shape(tolerance, &mut DebugInfo::new()).unwrap();
+        let face = shape.topology().faces().next().unwrap();
+
+        assert_close(face.get().area(tolerance), 8. - PI / 4.);
+        assert_close(face.get().perimeter(tolerance), 12. + PI);
+
+        // A half disk, bounded by an arc and a line segment
+        let mut shape = Shape::new();
+        let mut vertices = Vec::new();
+        for x in [1., -1.] {
+            let point = shape.geometry().add_point(Point::from([x, 0., 0.]));
+            vertices
+                .push(shape.topology().add_vertex(Vertex { point }).unwrap());
+        }
+        let curve = shape.geometry().add_curve(Curve::Circle(Circle {
+            center: Point::origin(),
+            radius: Vector::from([1., 0.]),
+        }));
+        let arc = shape
+            .topology()
+            .add_edge(Edge {
+                curve,
+                vertices: Some([vertices[0].clone(), vertices[1].clone()]),
+            })
+            .unwrap();
+        let line = shape
+            .topology()
+            .add_line_segment([vertices[1].clone(), vertices[0].clone()])
+            .unwrap();
+        let cycle = shape
+            .topology()
+            .add_cycle(Cycle {
+                edges: smallvec![arc, line],
+            })
+            .unwrap();
+        let face = Face::Face {
+            surface: shape.geometry().add_surface(Surface::x_y_plane()),
+            cycles: smallvec![cycle],
+        };
+
+        assert_close(face.area(tolerance), PI / 2.);
+        assert_close(face.perimeter(tolerance), PI + 2.);
+    }
+
+    #[test]
+    fn area_and_perimeter_should_fall_back_to_triangles() {
+        let tolerance = Tolerance::from_scalar(0.1).unwrap();
+
+        let [a, b, c, d] =
+            [[0., 0., 0.], [2., 0., 0.], [2., 1., 0.], [0., 1., 0.]]
+                .map(Point::from);
+        let face = Face::Triangles(vec![
+            Triangle::from([a, b, c]),
+            Triangle::from([a, c, d]),
+        ]);
+
+        assert_close(face.area(tolerance), 2.);
+        assert_close(face.perimeter(tolerance), 6.);
+    }
+}
@@ -0,0 +1,260 @@
+//! Lattice infill for closed shapes
+//!
+//! **This is experimental.** Parts that don't need to be solid can be made
+//! lighter, and faster to print, by replacing their inside with a lattice. A
+//! [`Lattice`] keeps a solid shell around the outside of a shape, and fills
+//! the rest with a regular [`Pattern`].
+//!
+//! The result is built from the voxels of the shape (see [`voxels`]), which
+//! makes it robust, but means that its surface consists of steps the size of
+//! a voxel. The boundary of the voxels is a closed, manifold mesh, that can be
+//! exported and printed like any other.
+//!
+//! [`voxels`]: crate::analysis::voxels
+
+use std::{f64::consts::PI, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{
+    analysis::voxels::{self, Grid, Voxels},
+    convention::ParseError,
+    math::{Aabb, Point, Scalar, Triangle},
+};
+
+/// Fills closed shapes with a lattice
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lattice {
+    /// The pattern that the lattice follows
+    pub pattern: Pattern,
+
+    /// The size of one cell of the pattern
+    pub cell_size: Scalar,
+
+    /// The thickness of the struts or walls of the pattern
+    pub thickness: Scalar,
+
+    /// The thickness of the solid shell around the lattice
+    ///
+    /// The shell is rounded up to a whole number of voxels.
+    pub shell: Scalar,
+
+    /// The edge length of the voxels that the result is built from
+    ///
+    /// Must be larger than zero, and should be well below `thickness`, or the
+    /// struts or walls of the pattern come out ragged, or not at all.
+    pub resolution: Scalar,
+}
+
+impl Lattice {
+    /// Fill the shape that a closed triangle mesh bounds
+    ///
+    /// The pattern is aligned with the origin of the model, not with the
+    /// shape, so shapes that are modeled next to each other get matching
+    /// lattices. Use [`Voxels::boundary`] to get a mesh of the result. Voxels
+    /// of the pattern that only touch along an edge are connected (see
+    /// [`Voxels::fill_edge_contacts`]), so that mesh is manifold.
+    ///
+    /// Returns an error, if there are no triangles, or if the resolution
+    /// doesn't result in a valid grid.
+    pub fn fill(&self, triangles: &[Triangle<3>]) -> Result<Voxels, Error> {
+        if triangles.is_empty() {
+            return Err(Error::Empty);
+        }
+
+        let aabb = Aabb::<3>::from_points(
+            triangles.iter().flat_map(|triangle| triangle.points()),
+        );
+        let grid = Grid::new(&aabb, self.resolution)?;
+        let mut voxels = grid.voxelize(triangles);
+
+        let layers = (self.shell / self.resolution).into_f64().ceil() as usize;
+        let core = voxels.erode(layers);
+
+        voxels.retain(|voxel| {
+            !core.contains(voxel)
+                || self.pattern.contains(
+                    grid.center(voxel),
+                    self.cell_size,
+                    self.thickness,
+                )
+        });
+        voxels.fill_edge_contacts();
+
+        Ok(voxels)
+    }
+}
+
+/// Returned by [`Lattice::fill`]
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Shape is empty")]
+    Empty,
+
+    #[error("Invalid lattice resolution")]
+    Grid(#[from] voxels::Error),
+}
+
+/// A periodic pattern that a [`Lattice`] follows
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Pattern {
+    /// Straight struts along the x, y, and z axes (`grid`)
+    ///
+    /// The struts meet at the corners of each cell. This is easy to inspect,
+    /// but the struts along the x and y axes need support while printing.
+    Grid,
+
+    /// A gyroid, a smooth surface that divides space into two halves
+    /// (`gyroid`)
+    ///
+    /// The gyroid is about equally strong in all directions, and it doesn't
+    /// overhang steeply anywhere, so it can be printed without support.
+    Gyroid,
+}
+
+impl Pattern {
+    /// Indicate whether a point is within the material of the pattern
+    pub fn contains(
+        &self,
+        point: Point<3>,
+        cell_size: Scalar,
+        thickness: Scalar,
+    ) -> bool {
+        let cell_size = cell_size.into_f64();
+        let half_thickness = thickness.into_f64() / 2.;
+        let point = point.coords.components().map(Scalar::into_f64);
+
+        match self {
+            Self::Grid => {
+                // A point is within a strut, if it's close to the planes
+                // between the cells along two of the axes. That places it
+                // near the line where those planes meet.
+                let close = point
+                    .into_iter()
+                    .filter(|&coord| {
+                        let cells = coord / cell_size;
+                        (cells - cells.round()).abs() * cell_size
+                            <= half_thickness
+                    })
+                    .count();
+
+                close >= 2
+            }
+            Self::Gyroid => {
+                let k = 2. * PI / cell_size;
+                let [(sx, cx), (sy, cy), (sz, cz)] =
+                    point.map(|coord| ((k * coord).sin(), (k * coord).cos()));
+
+                let value = sx * cy + sy * cz + sz * cx;
+                let gradient =
+                    [cx * cy - sz * sx, cy * cz - sx * sy, cz * cx - sy * sz]
+                        .map(|component| component * k);
+                let gradient = gradient
+                    .iter()
+                    .map(|component| component * component)
+                    .sum::<f64>()
+                    .sqrt();
+
+                // The value divided by its gradient approximates the distance
+                // from the surface.
+                value.abs() <= half_thickness * gradient
+            }
+        }
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "grid" => Ok(Self::Grid),
+            "gyroid" => Ok(Self::Gyroid),
+            _ => Err(ParseError("lattice pattern", "grid, gyroid")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{
+        analysis::{manifold, stats::Stats},
+        math::{Point, Scalar},
+        testing::{cuboid, Evaluated},
+    };
+
+    use super::{Error, Lattice, Pattern};
+
+    #[test]
+    fn pattern_should_contain_points_near_struts_and_walls() {
+        let cell_size = Scalar::from_f64(10.);
+        let thickness = Scalar::from_f64(1.);
+        let contains = |pattern: Pattern, point: [f64; 3]| {
+            pattern.contains(Point::from(point), cell_size, thickness)
+        };
+
+        assert!(contains(Pattern::Grid, [3., 10.2, 19.9]));
+        assert!(!contains(Pattern::Grid, [3., 10.2, 15.]));
+        assert!(!contains(Pattern::Grid, [3., 5., 15.]));
+
+        // The gyroid passes through the origin. At a quarter cell along the
+        // x-axis, it is about 1.6 away.
+        assert!(contains(Pattern::Gyroid, [0., 0., 0.]));
+        assert!(contains(Pattern::Gyroid, [0.4, 0., 0.]));
+        assert!(!contains(Pattern::Gyroid, [2.5, 0., 0.]));
+    }
+
+    #[test]
+    fn lattice_should_keep_shell_and_thin_out_inside() {
+        let cube = Evaluated::new(&cuboid([20., 20., 20.]).into());
+
+        for pattern in [Pattern::Grid, Pattern::Gyroid] {
+            let lattice = Lattice {
+                pattern,
+                cell_size: Scalar::from_f64(10.),
+                thickness: Scalar::from_f64(1.),
+                shell: Scalar::from_f64(1.),
+                resolution: Scalar::from_f64(0.5),
+            };
+            let voxels = lattice.fill(cube.triangles()).unwrap();
+
+            // The shell is two voxels thick. Voxels on the outside of the
+            // cube must all be there.
+            assert!(voxels.contains([0, 20, 20]));
+            assert!(voxels.contains([1, 20, 20]));
+
+            let volume = voxels.volume().into_f64();
+            let shell = 20_f64.powi(3) - 18_f64.powi(3);
+            assert!(volume > shell, "{:?}: {}", pattern, volume);
+            assert!(volume < 20_f64.powi(3) * 0.6, "{:?}: {}", pattern, volume);
+
+            // The boundary faces outwards, so it encloses the voxels.
+            let boundary = voxels.boundary();
+            assert!(manifold::check(&boundary).is_empty(), "{:?}", pattern);
+            assert_abs_diff_eq!(
+                Stats::compute(&boundary).volume.into_f64(),
+                volume,
+                epsilon = 1e-6
+            );
+        }
+
+        let lattice = |resolution| Lattice {
+            pattern: Pattern::Grid,
+            cell_size: Scalar::ONE,
+            thickness: Scalar::ONE,
+            shell: Scalar::ONE,
+            resolution: Scalar::from_f64(resolution),
+        };
+        assert!(matches!(lattice(1.).fill(&[]), Err(Error::Empty)));
+        assert!(matches!(
+            lattice(0.).fill(cube.triangles()),
+            Err(Error::Grid(_))
+        ));
+        assert!(matches!(
+            lattice(-1.).fill(cube.triangles()),
+            Err(Error::Grid(_))
+        ));
+    }
+}
@@ -0,0 +1,39 @@
+//! Fornjot host application
+//!
+//! The host application builds and loads models, and displays or exports the
+//! shapes they define. Most of it is made available as a library, so it can
+//! be used from other code too. Most notably, this allows models to test the
+//! shapes they define, using [`testing`].
+
+pub mod analysis;
+pub mod animation;
+pub mod batch;
+pub mod cam;
+pub mod camera;
+pub mod config;
+pub mod convention;
+pub mod debug;
+pub mod evaluation;
+pub mod export;
+pub mod graphics;
+pub mod import;
+pub mod input;
+pub mod kernel;
+pub mod lattice;
+pub mod materials;
+pub mod math;
+pub mod mesh;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod model;
+pub mod platform;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod queue;
+pub mod random;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod remote;
+pub mod render;
+pub mod report;
+pub mod solve;
+pub mod stackup;
+pub mod testing;
+pub mod window;
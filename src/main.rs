@@ -1,18 +1,18 @@
 mod args;
-mod camera;
-mod debug;
-mod graphics;
-mod input;
-mod kernel;
-mod math;
-mod mesh;
-mod model;
-mod window;
-
-use std::collections::HashSet;
-use std::ffi::OsStr;
-use std::{collections::HashMap, sync::mpsc, time::Instant};
 
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use std::{collections::HashMap, sync::mpsc};
+
+use anyhow::Context as _;
 use futures::executor::block_on;
 use notify::Watcher as _;
 use tracing::trace;
@@ -23,25 +23,63 @@ use winit::{
     event_loop::{ControlFlow, EventLoop},
 };
 
-use crate::math::Scalar;
-use crate::{
-    args::Args,
+use fj_host::{
+    analysis::{
+        self,
+        deviation::Deviation,
+        diff::{Diff, MaterialChange, SurfaceChange},
+        field::Colormap,
+        manifold::{Problem, ProblemKind},
+        stats::Stats,
+    },
+    animation,
+    batch::{Batch, Checkpoint},
+    cam,
     camera::Camera,
+    config::{self, Config},
+    convention::CoordinateSystem,
     debug::DebugInfo,
-    graphics::{DrawConfig, Renderer},
-    kernel::shapes::ToShape as _,
-    mesh::MeshMaker,
-    model::Model,
-    window::Window,
+    evaluation::{
+        screen_space_tolerance, Evaluation, ProcessedShape, ShapeProcessor,
+    },
+    export,
+    graphics::{DrawConfig, DrawError, Renderer, Vertices},
+    import, input,
+    kernel::{
+        algorithms::{arena, tolerance::Tolerance},
+        shapes::ToShape as _,
+    },
+    lattice::Lattice,
+    materials::{self, MassError, Materials},
+    math::{Aabb, Scalar, Triangle, Vector},
+    mesh::{self, IndexFormat, IndexOverflow, Silhouette},
+    model::{self, Model},
+    platform::{self, Instant},
+    queue::{Priority, Queue},
+    remote, render,
+    report::{
+        AnchorsReport, DiagnosticReport, DiffReport, ErrorReport,
+        EvaluateReport, FaceReport, MassReport, MeshStatsReport, OrientReport,
+        SolveReport, StackUpReport, StatsReport, ValidationReport,
+    },
+    solve::{Property, Solver},
+    stackup::{Measurement, StackUp, Summary},
+    window::{ModelState, Window},
 };
 
-fn main() -> anyhow::Result<()> {
+use crate::args::{Args, CamArgs, Command, ModelArgs};
+
+fn main() {
     // Respect `RUST_LOG`. If that's not defined or erroneous, log warnings and
     // above.
     //
     // It would be better to fail, if `RUST_LOG` is erroneous, but I don't know
     // how to distinguish between that and the "not defined" case.
+    //
+    // Log to stderr, so commands that print JSON to stdout don't end up with
+    // log messages in their output.
     tracing_subscriber::fmt()
+        .with_writer(io::stderr)
         .with_env_filter(
             EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| EnvFilter::new("WARN")),
@@ -50,23 +88,284 @@ fn main() -> anyhow::Result<()> {
         .init();
 
     let args = Args::parse();
-    let model = Model::new(args.model);
 
-    let mut parameters = HashMap::new();
-    for parameter in args.parameters {
-        let mut parameter = parameter.splitn(2, '=');
+    let result = match args.command {
+        Command::View {
+            model,
+            frames,
+            duration,
+            toolpath,
+            toolpath_offset,
+            screen_tolerance,
+            overhang_angle,
+            config,
+            export_on_change,
+            remote,
+        } => view(
+            model,
+            frames,
+            duration,
+            toolpath,
+            toolpath_offset,
+            screen_tolerance,
+            overhang_angle,
+            config,
+            export_on_change,
+            remote,
+        ),
+        Command::Export {
+            model,
+            output,
+            frames,
+            ascii,
+            units,
+            precision,
+            up,
+            handedness,
+            compact_indices,
+            weld,
+            voxel_size,
+            anchors,
+            checkpoint,
+            config,
+        } => {
+            let options = export::Options {
+                ascii,
+                units,
+                precision,
+                coordinates: CoordinateSystem { up, handedness },
+                compact_indices,
+                weld,
+                voxel_size: voxel_size.map(Scalar::from_f64),
+            };
+            export(
+                model,
+                &output,
+                frames,
+                &options,
+                anchors.as_deref(),
+                checkpoint.as_deref(),
+                config,
+            )
+        }
+        Command::Formats { config } => formats(config),
+        Command::Render {
+            model,
+            output,
+            width,
+            height,
+            samples,
+            azimuth,
+            elevation,
+            frames,
+            face_ids,
+        } => {
+            let settings = render::Settings {
+                width,
+                height,
+                samples,
+            };
+            render(
+                model,
+                &output,
+                face_ids.as_deref(),
+                &settings,
+                azimuth,
+                elevation,
+                frames,
+            )
+        }
+        Command::Flatten {
+            model,
+            output,
+            max_angle,
+            spacing,
+        } => flatten(model, &output, max_angle, spacing),
+        Command::Lattice {
+            model,
+            output,
+            pattern,
+            cell_size,
+            thickness,
+            shell,
+            resolution,
+        } => {
+            let lattice = Lattice {
+                pattern,
+                cell_size: Scalar::from_f64(cell_size),
+                thickness: Scalar::from_f64(thickness),
+                shell: Scalar::from_f64(shell),
+                resolution: Scalar::from_f64(resolution),
+            };
+            lattice_fill(model, &output, &lattice)
+        }
+        Command::Parameters { model } => parameters(model),
+        Command::Validate { model, json } => validate(model, json),
+        Command::Stats {
+            model,
+            faces,
+            material,
+            config,
+            json,
+        } => stats(model, faces, material.as_deref(), config, json),
+        Command::Evaluate {
+            model,
+            output,
+            material,
+            config,
+        } => evaluate(model, &output, material.as_deref(), config),
+        Command::Orient {
+            model,
+            max_angle,
+            count,
+            json,
+        } => orient(model, max_angle, count, json),
+        Command::Cam {
+            model,
+            output,
+            settings,
+        } => cam(model, &output, settings),
+        Command::Diff {
+            model,
+            other,
+            other_parameters,
+            max_distance,
+            voxel_size,
+            material_output,
+            json,
+        } => diff(
+            model,
+            other,
+            other_parameters,
+            max_distance,
+            voxel_size,
+            material_output.as_deref(),
+            json,
+        ),
+        Command::Solve {
+            model,
+            parameter,
+            property,
+            target,
+            range,
+            density,
+            tolerance,
+            max_evaluations,
+            json,
+        } => solve(
+            model,
+            parameter,
+            property,
+            target,
+            range,
+            density,
+            tolerance,
+            max_evaluations,
+            json,
+        ),
+        Command::StackUp {
+            model,
+            variations,
+            measurements,
+            distribution,
+            samples,
+            seed,
+            density,
+            min_gap,
+            json,
+        } => stack_up(
+            model,
+            StackUp {
+                distribution,
+                samples,
+                seed,
+                ..StackUp::new(variations)
+            },
+            measurements,
+            density,
+            min_gap,
+            json,
+        ),
+        Command::Compare {
+            model,
+            scan,
+            tolerance,
+            deviation_output,
+            scan_up,
+            scan_handedness,
+        } => compare(
+            model,
+            &scan,
+            CoordinateSystem {
+                up: scan_up,
+                handedness: scan_handedness,
+            },
+            tolerance,
+            deviation_output.as_deref(),
+        ),
+        Command::Serve { address } => serve(&address),
+    };
 
-        let key = parameter
-            .next()
-            .expect("model parameter: key not found")
-            .to_owned();
-        let value = parameter
-            .next()
-            .expect("model parameter: value not found")
-            .to_owned();
+    if let Err(err) = result {
+        eprintln!("Error: {:?}", err);
 
-        parameters.insert(key, value);
+        // Distinguish between a model that failed a check, and errors that
+        // prevented us from doing our job, so scripts can tell the
+        // difference.
+        let check_failed = err.is::<InvalidModel>()
+            || err.is::<DifferenceTooLarge>()
+            || err.is::<GapTooNarrow>();
+        let exit_code = if check_failed {
+            EXIT_CHECK_FAILED
+        } else {
+            EXIT_ERROR
+        };
+        process::exit(exit_code);
     }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn view(
+    args: ModelArgs,
+    frames: Option<NonZeroUsize>,
+    duration: f64,
+    toolpath: Option<PathBuf>,
+    toolpath_offset: Option<Vec<f64>>,
+    screen_tolerance: Option<f64>,
+    overhang_angle: f64,
+    config: Option<PathBuf>,
+    export_on_change: Option<PathBuf>,
+    remote: Option<String>,
+) -> anyhow::Result<()> {
+    let model = args.model();
+    let parameters = parse_parameters(args.parameters);
+    let config = load_config(config)?;
+    let exporters = config.exporters();
+    let overhang_angle = Scalar::from_f64(overhang_angle.to_radians());
+
+    // Unlike the model, the toolpath isn't reloaded on changes. It's only there
+    // to check whether it fits the model.
+    let toolpath = toolpath
+        .map(|path| load_toolpath(&path, toolpath_offset))
+        .transpose()?
+        .unwrap_or_default();
+
+    // The camera needs to take the toolpath into account, or it might be cut
+    // off by the far plane.
+    let toolpath_aabb = toolpath
+        .iter()
+        .any(|polyline| !polyline.points.is_empty())
+        .then(|| {
+            Aabb::<3>::from_points(
+                toolpath
+                    .iter()
+                    .flat_map(|polyline| polyline.points.iter().copied()),
+            )
+        });
+    let scene_aabb = move |aabb: Aabb<3>| match toolpath_aabb {
+        Some(toolpath_aabb) => aabb.merged(&toolpath_aabb),
+        None => aabb,
+    };
 
     // Since we're loading the model before setting up the watcher below,
     // there's a race condition, and a modification could be missed between
@@ -77,72 +376,47 @@ fn main() -> anyhow::Result<()> {
     //
     // This is being tracked in the following issue:
     // https://github.com/hannobraun/fornjot/issues/32
-    let shape = model.load(&parameters)?;
-
-    let mut aabb = shape.bounding_volume();
-
-    // Compute a reasonable default for the tolerance value. To do this, we just
-    // look at the smallest non-zero extent of the bounding box and divide that
-    // by some value.
-    let tolerance = {
-        let mut min_extent = Scalar::MAX;
-        for extent in aabb.size().components() {
-            if extent > Scalar::ZERO && extent < min_extent {
-                min_extent = extent;
-            }
-        }
-
-        // `tolerance` must not be zero, or we'll run into trouble.
-        let tolerance = min_extent / Scalar::from_f64(1000.);
-        assert!(tolerance > Scalar::ZERO);
-
-        tolerance
-    };
-
-    let mut debug_info = DebugInfo::new();
-    let mut triangles = Vec::new();
-    shape
-        .to_shape(tolerance, &mut debug_info)
-        .topology()
-        .triangles(tolerance, &mut triangles, &mut debug_info);
-
-    if let Some(path) = args.export {
-        let mut mesh_maker = MeshMaker::new();
-
-        for triangle in triangles {
-            for vertex in triangle.points() {
-                mesh_maker.push(vertex);
-            }
-        }
-
-        let vertices =
-            mesh_maker.vertices().map(|vertex| vertex.into()).collect();
+    let shapes = load_frames(&model, &parameters, frames)?;
+    if let Some(output) = &export_on_change {
+        export_rebuilt(&exporters, output, &shapes, frames)?;
+    }
 
-        let indices: Vec<_> = mesh_maker.indices().collect();
-        let triangles = indices
-            .chunks(3)
-            .map(|triangle| {
-                [
-                    triangle[0] as usize,
-                    triangle[1] as usize,
-                    triangle[2] as usize,
-                ]
+    let remote = remote
+        .map(|address| {
+            remote::Client::connect(&address).with_context(|| {
+                format!("Failed to connect to server at `{}`", address)
             })
-            .collect();
-
-        let mesh = threemf::TriangleMesh {
-            vertices,
-            triangles,
-        };
+        })
+        .transpose()?;
 
-        threemf::write(path, &mesh)?;
+    let mut tolerance =
+        Tolerance::from_bounding_box(&shapes[0].bounding_volume());
+    let mut evaluator = Evaluator {
+        shapes,
+        tolerance,
+        remote,
+    };
+    let mut animation = evaluator.process()?;
 
-        return Ok(());
-    }
+    // From here on, the model is processed on its own thread, so the window
+    // stays responsive while that takes a while.
+    let updates = Arc::new(Queue::new());
+    let (processed_tx, processed_rx) = mpsc::channel();
+    thread::spawn({
+        let updates = updates.clone();
+        move || evaluator.run(&updates, processed_tx)
+    });
 
     let (watcher_tx, watcher_rx) = mpsc::sync_channel(0);
+    let reloads = updates.clone();
 
+    let model_name = model.name().to_owned();
     let watch_path = model.src_path();
+    let watch_mode = if model.is_description() {
+        notify::RecursiveMode::NonRecursive
+    } else {
+        notify::RecursiveMode::Recursive
+    };
     let mut watcher = notify::recommended_watcher(
         move |event: notify::Result<notify::Event>| {
             // Unfortunately the `notify` documentation doesn't say when this
@@ -158,11 +432,15 @@ fn main() -> anyhow::Result<()> {
                 notify::event::DataChange::Content,
             )) = event.kind
             {
-                let file_ext = event
+                let path = event
                     .paths
                     .get(0)
-                    .expect("File path missing in watch event")
-                    .extension();
+                    .expect("File path missing in watch event");
+                if !model.is_source(path) {
+                    return;
+                }
+
+                let file_ext = path.extension();
 
                 let black_list = HashSet::from([
                     OsStr::new("swp"),
@@ -176,13 +454,52 @@ fn main() -> anyhow::Result<()> {
                     }
                 }
 
-                let shape = match model.load(&parameters) {
-                    Ok(shape) => shape,
+                // The sends below will panic, if the other end is
+                // disconnected, which is probably the result of a panic on that
+                // thread, or the application is being shut down.
+                //
+                // Either way, not much we can do about it here, except maybe to
+                // provide a better error message in the future.
+                watcher_tx.send(WatcherEvent::Rebuilding).unwrap();
+
+                let shapes = match load_frames(&model, &parameters, frames) {
+                    Ok(shapes) => shapes,
                     Err(model::Error::Compile) => {
-                        // It would be better to display an error in the UI,
+                        // It would be better to display the error in the UI,
                         // where the user can actually see it. Issue:
                         // https://github.com/hannobraun/fornjot/issues/30
                         println!("Error compiling model");
+                        watcher_tx.send(WatcherEvent::Failed).unwrap();
+                        return;
+                    }
+                    Err(model::Error::InvalidShape(err)) => {
+                        println!("Model returned invalid shape: {}", err);
+                        watcher_tx.send(WatcherEvent::Failed).unwrap();
+                        return;
+                    }
+                    Err(model::Error::Description(err)) => {
+                        println!("Error parsing shape description: {}", err);
+                        watcher_tx.send(WatcherEvent::Failed).unwrap();
+                        return;
+                    }
+                    Err(model::Error::Scad(err)) => {
+                        println!("Error importing OpenSCAD file: {}", err);
+                        watcher_tx.send(WatcherEvent::Failed).unwrap();
+                        return;
+                    }
+                    Err(model::Error::Profile(err)) => {
+                        println!("Error importing 2D profile: {}", err);
+                        watcher_tx.send(WatcherEvent::Failed).unwrap();
+                        return;
+                    }
+                    Err(
+                        err @ (model::Error::AbiMismatch { .. }
+                        | model::Error::MissingParameter(_)
+                        | model::Error::InvalidParameter { .. }
+                        | model::Error::ParameterOutOfRange { .. }),
+                    ) => {
+                        println!("Error loading model: {}", err);
+                        watcher_tx.send(WatcherEvent::Failed).unwrap();
                         return;
                     }
                     Err(err) => {
@@ -190,30 +507,37 @@ fn main() -> anyhow::Result<()> {
                     }
                 };
 
-                // This will panic, if the other end is disconnected, which is
-                // probably the result of a panic on that thread, or the
-                // application is being shut down.
-                //
-                // Either way, not much we can do about it here, except maybe to
-                // provide a better error message in the future.
-                watcher_tx.send(shape).unwrap();
+                if let Some(output) = &export_on_change {
+                    let result =
+                        export_rebuilt(&exporters, output, &shapes, frames);
+                    if let Err(err) = result {
+                        println!("Error exporting model: {:#}", err);
+                    }
+                }
+
+                Update::Shapes(shapes).push_to(&reloads);
             }
         },
     )?;
-    watcher.watch(&watch_path, notify::RecursiveMode::Recursive)?;
+    watcher.watch(&watch_path, watch_mode)?;
 
     let event_loop = EventLoop::new();
-    let window = Window::new(&event_loop);
+    let window = Window::new(&event_loop, &model_name);
 
     let mut previous_time = Instant::now();
 
     let mut input_handler = input::Handler::new(previous_time);
-    let mut renderer = block_on(Renderer::new(&window))?;
+    let mut theme = config.theme.variant;
+    let mut renderer =
+        block_on(Renderer::new(&window, config.theme.theme(theme)))?;
 
-    renderer.update_geometry((&triangles).into(), (&debug_info).into(), aabb);
+    animation.update_geometry(&mut renderer, overhang_angle)?;
+    renderer.update_toolpath(toolpath.as_slice().try_into()?);
 
     let mut draw_config = DrawConfig::default();
-    let mut camera = Camera::new(&aabb);
+    let mut camera = Camera::new(&scene_aabb(animation.aabb));
+
+    let mut animation_start = previous_time;
 
     event_loop.run(move |event, _, control_flow| {
         trace!("Handling event: {:?}", event);
@@ -223,21 +547,83 @@ fn main() -> anyhow::Result<()> {
         let now = Instant::now();
 
         match watcher_rx.try_recv() {
-            Ok(shape) => {
-                debug_info.clear();
-                triangles.clear();
-
-                aabb = shape.bounding_volume();
-                shape
-                    .to_shape(tolerance, &mut debug_info)
-                    .topology()
-                    .triangles(tolerance, &mut triangles, &mut debug_info);
-
-                renderer.update_geometry(
-                    (&triangles).into(),
-                    (&debug_info).into(),
-                    aabb,
+            Ok(WatcherEvent::Rebuilding) => {
+                window.set_model_state(ModelState::Rebuilding);
+            }
+            Ok(WatcherEvent::Failed) => {
+                window.set_model_state(ModelState::Failed);
+            }
+            Err(mpsc::TryRecvError::Empty) => {
+                // Nothing to receive from the channel. We don't care.
+            }
+            Err(mpsc::TryRecvError::Disconnected) => {
+                // The other end has disconnected. This is probably the result
+                // of a panic on the other thread, or a program shutdown in
+                // progress. In any case, not much we can do here.
+                panic!();
+            }
+        }
+
+        match processed_rx.try_recv() {
+            Ok(Processed {
+                animation: Ok(new_animation),
+                reloaded: true,
+            }) => {
+                // Keep the user's view, unless the model changed size so
+                // much, that it would be hard to find.
+                if size_changed(&animation.aabb, &new_animation.aabb) {
+                    camera.fit(&scene_aabb(new_animation.aabb));
+                }
+
+                // Highlight what changed, so it's easy to see whether an edit
+                // did what it was supposed to.
+                let change = SurfaceChange::compute(
+                    &animation.frames[0].triangles,
+                    &new_animation.frames[0].triangles,
+                    tolerance.inner() * 2.,
                 );
+                let overlay = change.and_then(|change| {
+                    if change.is_empty() {
+                        return Ok(None);
+                    }
+                    Vertices::surface_change(&change).map(Some)
+                });
+                match overlay {
+                    Ok(Some(overlay)) => {
+                        renderer.show_overlay(
+                            overlay,
+                            now + CHANGE_HIGHLIGHT_DURATION,
+                        );
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        println!("Error highlighting changes: {}", err);
+                    }
+                }
+
+                animation = new_animation;
+                animation.show(&mut renderer, overhang_angle);
+                animation_start = now;
+
+                window.set_model_state(ModelState::Current);
+            }
+            Ok(Processed {
+                animation: Ok(new_animation),
+                reloaded: false,
+            }) => {
+                let current = animation.current;
+                animation = new_animation;
+                animation.current = current;
+                animation.show(&mut renderer, overhang_angle);
+            }
+            Ok(Processed {
+                animation: Err(err),
+                reloaded,
+            }) => {
+                println!("Error building model: {:#}", err);
+                if reloaded {
+                    window.set_model_state(ModelState::Failed);
+                }
             }
             Err(mpsc::TryRecvError::Empty) => {
                 // Nothing to receive from the channel. We don't care.
@@ -277,6 +663,7 @@ fn main() -> anyhow::Result<()> {
                     position,
                     &mut camera,
                     &window,
+                    &scene_aabb(animation.aabb),
                 );
             }
             Event::WindowEvent {
@@ -286,7 +673,7 @@ fn main() -> anyhow::Result<()> {
                 let focus_point = camera.focus_point(
                     &window,
                     input_handler.cursor(),
-                    &triangles,
+                    animation.triangles(),
                 );
 
                 input_handler.handle_mouse_input(button, state, focus_point);
@@ -306,16 +693,40 @@ fn main() -> anyhow::Result<()> {
                     now,
                     &mut camera,
                     &window,
-                    &triangles,
+                    animation.triangles(),
+                    &scene_aabb(animation.aabb),
                 );
 
+                if let Some(pixels) = screen_tolerance {
+                    let new_tolerance =
+                        camera_tolerance(&camera, &window, &animation, pixels);
+
+                    // Triangulating the model is expensive, so don't do it
+                    // unless the tolerance has changed significantly.
+                    let current = tolerance.inner();
+                    if let Some(new_tolerance) = new_tolerance.filter(|t| {
+                        t.inner() < current / 2. || t.inner() > current * 2.
+                    }) {
+                        tolerance = new_tolerance;
+                        Update::Tolerance(tolerance).push_to(&updates);
+                    }
+                }
+
+                let elapsed = now.duration_since(animation_start);
+                if animation.advance(elapsed.as_secs_f64(), duration) {
+                    animation.show(&mut renderer, overhang_angle);
+                }
+
                 window.inner().request_redraw();
             }
             Event::RedrawRequested(_) => {
-                camera.update_planes(&aabb);
+                camera.update_planes(&scene_aabb(animation.aabb));
 
                 match renderer.draw(&camera, &draw_config) {
                     Ok(()) => {}
+                    Err(err @ DrawError::TooManyVertices(_)) => {
+                        println!("Error drawing model: {:#}", err);
+                    }
                     Err(err) => {
                         panic!("Draw error: {}", err);
                     }
@@ -336,5 +747,1594 @@ fn main() -> anyhow::Result<()> {
         if actions.toggle_debug {
             draw_config.draw_debug = !draw_config.draw_debug;
         }
+        if actions.toggle_toolpath {
+            draw_config.draw_toolpath = !draw_config.draw_toolpath;
+        }
+        if actions.toggle_validation {
+            draw_config.draw_validation = !draw_config.draw_validation;
+        }
+        if actions.toggle_edges {
+            draw_config.draw_edges = !draw_config.draw_edges;
+        }
+        if actions.toggle_xray {
+            draw_config.draw_xray = !draw_config.draw_xray;
+        }
+        if actions.toggle_overhangs {
+            draw_config.draw_overhangs = !draw_config.draw_overhangs;
+        }
+        if actions.zoom_to_fit {
+            camera.fit(&scene_aabb(animation.aabb));
+        }
+        if actions.toggle_theme {
+            theme = theme.toggled();
+            renderer.set_theme(config.theme.theme(theme));
+        }
+    });
+}
+
+/// Indicate whether a model changed size significantly after reloading it
+///
+/// This is the case, if it became more than twice as big or small, for example
+/// because it's now modeled in different units.
+fn size_changed(previous: &Aabb<3>, current: &Aabb<3>) -> bool {
+    let previous = previous.size().magnitude();
+    let current = current.size().magnitude();
+
+    current > previous * 2. || previous > current * 2.
+}
+
+/// Compute the tolerance for the model's current size on the screen
+///
+/// Returns `None`, if the model isn't visible.
+fn camera_tolerance(
+    camera: &Camera,
+    window: &Window,
+    animation: &Animation,
+    pixels: f64,
+) -> Option<Tolerance> {
+    let [width, height] = [window.width(), window.height()].map(f64::from);
+    if width == 0. || height == 0. {
+        // The window is minimized.
+        return None;
+    }
+
+    let projection = camera.projection(width / height);
+    screen_space_tolerance(&animation.aabb, &projection, height, pixels)
+}
+
+fn export(
+    args: ModelArgs,
+    output: &Path,
+    frames: Option<NonZeroUsize>,
+    options: &export::Options,
+    anchors: Option<&Path>,
+    checkpoint: Option<&Path>,
+    config: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let exporters = load_config(config)?.exporters();
+
+    if let Some(voxel_size) = options.voxel_size {
+        if voxel_size <= Scalar::ZERO {
+            anyhow::bail!("Voxel size must be larger than zero");
+        }
+    }
+
+    let shapes = load_shapes(&args, frames)?;
+
+    // Frames are identified by the parameters they were created with, so a
+    // checkpoint can't be resumed with different ones by accident.
+    let mut parameters = args.parameters.clone();
+    parameters.sort();
+    let key = |i: usize| match frames {
+        Some(frames) => {
+            format!("{} frame={}/{}", parameters.join(" "), i, frames)
+        }
+        None => parameters.join(" "),
+    };
+
+    // Everything else that affects the exported files describes the job that
+    // the checkpoint belongs to. Resuming it with anything different is
+    // refused, as frames would be skipped that don't match.
+    let job = format!(
+        "export {} (simplify: {:?}) to {} (anchors: {:?}) with {:?}",
+        args.model().path(),
+        args.simplify,
+        output.display(),
+        anchors,
+        options,
+    );
+
+    let mut checkpoint = checkpoint
+        .map(|path| {
+            Checkpoint::open(path, &job).with_context(|| {
+                format!("Failed to open checkpoint `{}`", path.display())
+            })
+        })
+        .transpose()?;
+    let remaining: Vec<_> = shapes
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| match &checkpoint {
+            Some(checkpoint) => !checkpoint.is_completed(&key(*i)),
+            None => true,
+        })
+        .collect();
+    if remaining.len() < shapes.len() {
+        println!(
+            "Resuming; skipping {} of {} frames that were already exported",
+            shapes.len() - remaining.len(),
+            shapes.len()
+        );
+    }
+
+    // The frames are independent of each other, so they are exported in
+    // parallel.
+    Batch::new().run(
+        remaining,
+        |_, (i, shape)| -> anyhow::Result<_> {
+            let mut artifacts = vec![frame_output(output, i, frames)];
+            exporters.export(&artifacts[0], shape, options)?;
+
+            if let Some(anchors) = anchors {
+                let path = frame_output(anchors, i, frames);
+                let report = AnchorsReport::new(&shape.anchors(), options);
+                let file = File::create(&path)?;
+                serde_json::to_writer_pretty(BufWriter::new(file), &report)?;
+                artifacts.push(path);
+            }
+
+            Ok((i, artifacts))
+        },
+        |_, result| -> anyhow::Result<()> {
+            let (i, artifacts) = result?;
+            if let Some(checkpoint) = &mut checkpoint {
+                checkpoint.record(key(i), artifacts)?;
+            }
+            Ok(())
+        },
+    )
+}
+
+/// Export a model that was rebuilt, for `view --export-on-change`
+///
+/// Each file is written under a temporary name first, then moved into place,
+/// so programs that watch it never read a partially written file.
+fn export_rebuilt(
+    exporters: &export::Exporters,
+    output: &Path,
+    shapes: &[fj::Shape],
+    frames: Option<NonZeroUsize>,
+) -> anyhow::Result<()> {
+    let options = export::Options::default();
+
+    for (i, shape) in shapes.iter().enumerate() {
+        let output = frame_output(output, i, frames);
+
+        // The exporter is selected by the extension, so it has to stay.
+        let file_name = output.file_name().ok_or_else(|| {
+            anyhow::anyhow!("Invalid export path: {}", output.display())
+        })?;
+        let mut temporary = OsString::from(".");
+        temporary.push(file_name);
+        let temporary = output.with_file_name(temporary);
+
+        exporters.export(&temporary, shape, &options)?;
+        fs::rename(&temporary, &output)?;
+    }
+
+    Ok(())
+}
+
+fn formats(config: Option<PathBuf>) -> anyhow::Result<()> {
+    for exporter in load_config(config)?.exporters().iter() {
+        let extensions: Vec<_> = exporter
+            .extensions()
+            .iter()
+            .map(|extension| format!(".{}", extension))
+            .collect();
+
+        println!("{}: {}", exporter.name(), extensions.join(", "));
+    }
+
+    Ok(())
+}
+
+fn render(
+    args: ModelArgs,
+    output: &Path,
+    face_ids: Option<&Path>,
+    settings: &render::Settings,
+    azimuth: f64,
+    elevation: f64,
+    frames: Option<NonZeroUsize>,
+) -> anyhow::Result<()> {
+    let shapes = load_shapes(&args, frames)?;
+
+    // All frames of an animation are rendered from the same camera, so the
+    // motion of the model is visible.
+    let aabb = shapes
+        .iter()
+        .map(|shape| shape.bounding_volume())
+        .reduce(|a, b| a.merged(&b))
+        .expect("Model has no frames");
+    let camera = render::Camera::orbit(
+        &aabb,
+        Scalar::from_f64(azimuth.to_radians()),
+        Scalar::from_f64(elevation.to_radians()),
+        f64::from(settings.width.get()) / f64::from(settings.height.get()),
+    );
+
+    Batch::new().process(ShapeProcessor::new(), &shapes, |i, processed| {
+        let ProcessedShape {
+            triangles, faces, ..
+        } = processed?;
+
+        if triangles.is_empty() {
+            anyhow::bail!("Model is empty; nothing to render");
+        }
+
+        let image = render::render(&triangles, &camera, settings)?;
+        let output = File::create(frame_output(output, i, frames))?;
+        render::png::write(BufWriter::new(output), &image)?;
+
+        if let Some(face_ids) = face_ids {
+            let ids =
+                render::render_face_ids(&triangles, &faces, &camera, settings)?;
+            let output = File::create(frame_output(face_ids, i, frames))?;
+            render::png::write_face_ids(BufWriter::new(output), &ids)?;
+        }
+
+        Ok(())
+    })
+}
+
+fn flatten(
+    args: ModelArgs,
+    output: &Path,
+    max_angle: f64,
+    spacing: f64,
+) -> anyhow::Result<()> {
+    let (_, triangles) = load(args)?;
+
+    let max_angle = Scalar::from_f64(max_angle.to_radians());
+    let pieces = analysis::flatten::flatten(&triangles, max_angle)?;
+
+    // Lay out the pieces next to each other, from left to right, so they don't
+    // overlap.
+    let mut polygons = Vec::new();
+    let mut x = Scalar::ZERO;
+    for piece in &pieces {
+        let points = piece.outlines.iter().flatten().copied();
+        let aabb = Aabb::<2>::from_points(points);
+        let [min_x, min_y]: [Scalar; 2] = aabb.min.into();
+        let [max_x, _]: [Scalar; 2] = aabb.max.into();
+        let offset = Vector::from([x - min_x, -min_y]);
+
+        for outline in &piece.outlines {
+            polygons
+                .push(outline.iter().map(|&point| point + offset).collect());
+        }
+
+        x = x + max_x - min_x + Scalar::from_f64(spacing);
+    }
+
+    let file = BufWriter::new(File::create(output)?);
+    export::svg::write_polygons(file, &polygons)?;
+
+    println!("Flattened model into {} pieces", pieces.len());
+
+    Ok(())
+}
+
+fn lattice_fill(
+    args: ModelArgs,
+    output: &Path,
+    lattice: &Lattice,
+) -> anyhow::Result<()> {
+    let (shape, triangles) = load(args)?;
+
+    if let fj::Shape::Shape2d(_) = shape {
+        anyhow::bail!("Only 3-dimensional models can be filled with a lattice");
+    }
+    let voxels = lattice.fill(&triangles)?;
+
+    let file = BufWriter::new(File::create(output)?);
+    export::stl::write(file, &voxels.boundary())?;
+
+    let solid = voxels.grid().voxelize(&triangles).volume();
+    println!(
+        "Filled model with lattice; volume {:.4} ({:.1}% of solid model)",
+        voxels.volume().into_f64(),
+        (voxels.volume() / solid).into_f64() * 100.
+    );
+
+    Ok(())
+}
+
+fn parameters(model: String) -> anyhow::Result<()> {
+    let model = Model::new(model);
+
+    let metadata = match model.metadata()? {
+        Some(metadata) => metadata,
+        None => {
+            anyhow::bail!(
+                "Model doesn't provide metadata; define it using `#[fj::model]`"
+            );
+        }
+    };
+
+    for parameter in &metadata.parameters {
+        let mut details = vec![match &parameter.default {
+            Some(default) => format!("default: {}", default),
+            None => "required".to_owned(),
+        }];
+        if let Some(range) = parameter.range() {
+            details.push(format!("range: {}", range));
+        }
+        if let Some(step) = parameter.step {
+            details.push(format!("step: {}", step));
+        }
+
+        println!(
+            "{}: {} ({})",
+            parameter.name,
+            parameter.ty,
+            details.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+fn validate(args: ModelArgs, json: bool) -> anyhow::Result<()> {
+    let shape = load_shape(&args)?;
+
+    let ProcessedShape {
+        triangles, faces, ..
+    } = ShapeProcessor::new().process(&shape)?;
+
+    let problems = check_mesh(&shape, &triangles);
+
+    if json {
+        let report = ValidationReport::new(&problems, &faces);
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if problems.is_empty() {
+        println!("Model is valid");
+    } else {
+        for problem in &problems {
+            let [a, b] = problem.edge;
+            println!(
+                "{:?}: {:?} - {:?} (triangles {:?}, faces {:?})",
+                problem.kind,
+                a,
+                b,
+                problem.triangles,
+                problem.faces(&faces)
+            );
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(InvalidModel(problems.len()).into());
+    }
+
+    Ok(())
+}
+
+/// Check the triangle mesh of a model, as `validate` and `evaluate` do
+fn check_mesh(shape: &fj::Shape, triangles: &[Triangle<3>]) -> Vec<Problem> {
+    let mut problems = analysis::manifold::check(triangles);
+
+    // The triangles of a 2-dimensional shape don't enclose a volume, so the
+    // boundary of the shape is expected to consist of open edges.
+    if let fj::Shape::Shape2d(_) = shape {
+        problems.retain(|problem| problem.kind != ProblemKind::OpenEdge);
+    }
+
+    problems
+}
+
+fn stats(
+    args: ModelArgs,
+    faces: bool,
+    material: Option<&str>,
+    config: Option<PathBuf>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let shape = load_shape(&args)?;
+    let materials = load_config(config)?.materials();
+
+    let evaluation = ShapeProcessor::new().evaluate(&shape)?;
+    let report =
+        stats_report(&shape, &evaluation, faces, &materials, material)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!("Faces:     {}", report.faces);
+    println!("Cycles:    {}", report.cycles);
+    println!("Edges:     {}", report.edges);
+    println!("Vertices:  {}", report.vertices);
+
+    if let Some(face_measurements) = &report.face_measurements {
+        for (i, face) in face_measurements.iter().enumerate() {
+            println!(
+                "Face {}: area {:.4}, perimeter {:.4}",
+                i, face.area, face.perimeter
+            );
+        }
+    }
+
+    let mesh = match report.mesh {
+        Some(mesh) => mesh,
+        None => {
+            println!("Model is empty");
+            return Ok(());
+        }
+    };
+
+    println!("Triangles: {}", mesh.triangles);
+    println!("Mesh vertices: {}", mesh.vertices);
+    println!("Bounding box: {:?} - {:?}", mesh.aabb.min, mesh.aabb.max);
+    println!("Area: {:.4}", mesh.area);
+
+    if let fj::Shape::Shape3d(_) = shape {
+        match mesh.volume {
+            Some(volume) => println!("Volume: {:.4}", volume),
+            None => {
+                println!("Volume: unknown (mesh is not closed; see `validate`)")
+            }
+        }
+
+        match &report.mass {
+            Some(mass) => {
+                println!("Mass: {:.4} g", mass.mass);
+                println!("Center of mass: {:?}", mass.center_of_mass);
+                println!("Inertia tensor (g*mm^2):");
+                for row in &mass.inertia {
+                    println!(
+                        "    [{:.4}, {:.4}, {:.4}]",
+                        row[0], row[1], row[2]
+                    );
+                }
+            }
+            None => println!(
+                "Mass: unknown (no material assigned; see `--material`)"
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// Compute the statistics of a model, as `stats` and `evaluate` report them
+fn stats_report(
+    shape: &fj::Shape,
+    evaluation: &Evaluation,
+    faces: bool,
+    materials: &Materials,
+    material: Option<&str>,
+) -> anyhow::Result<StatsReport> {
+    let tolerance = evaluation.tolerance;
+    let triangles = &evaluation.triangles;
+
+    // Both analyses only read the evaluation, so they can run at the same
+    // time.
+    let (mesh, face_measurements) = platform::join(
+        || {
+            if triangles.is_empty() {
+                return None;
+            }
+
+            // The volume can only be computed for closed meshes, and only if
+            // they are consistently oriented.
+            let oriented = match shape {
+                fj::Shape::Shape3d(_) => analysis::manifold::orient(triangles),
+                fj::Shape::Shape2d(_) => None,
+            };
+
+            let stats =
+                Stats::compute(oriented.as_deref().unwrap_or(triangles));
+            Some(MeshStatsReport::new(&stats, oriented.is_some()))
+        },
+        || {
+            faces.then(|| {
+                evaluation
+                    .brep()
+                    .faces()
+                    .map(|face| FaceReport {
+                        area: face.get().area(tolerance).into_f64(),
+                        perimeter: face.get().perimeter(tolerance).into_f64(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+        },
+    );
+
+    // A missing material means the mass is unknown, which isn't an error.
+    let mass = match shape {
+        fj::Shape::Shape3d(shape) if mesh.is_some() => {
+            match materials::mass_properties(shape, materials, material) {
+                Ok(properties) => Some(MassReport::new(&properties)),
+                Err(MassError::MissingMaterial) => None,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        _ => None,
+    };
+
+    let brep = evaluation.brep();
+    Ok(StatsReport {
+        faces: brep.faces().count(),
+        cycles: brep.cycles().count(),
+        edges: brep.edges().count(),
+        vertices: brep.vertices().count(),
+        face_measurements,
+        mesh,
+        mass,
+    })
+}
+
+fn evaluate(
+    args: ModelArgs,
+    outputs: &[PathBuf],
+    material: Option<&str>,
+    config: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    // Errors end up in the document too, so scripts don't have to look for
+    // them anywhere else.
+    let report = match evaluate_report(args, outputs, material, config) {
+        Ok(report) => report,
+        Err(err) => {
+            let report = ErrorReport {
+                error: format!("{:#}", err),
+            };
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Err(err);
+        }
+    };
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+
+    if !report.validation.valid {
+        return Err(InvalidModel(report.validation.problems.len()).into());
+    }
+
+    Ok(())
+}
+
+fn evaluate_report(
+    args: ModelArgs,
+    outputs: &[PathBuf],
+    material: Option<&str>,
+    config: Option<PathBuf>,
+) -> anyhow::Result<EvaluateReport> {
+    let shape = load_shape(&args)?;
+    let config = load_config(config)?;
+    let materials = config.materials();
+
+    let evaluation = ShapeProcessor::new().evaluate(&shape)?;
+    let problems = check_mesh(&shape, &evaluation.triangles);
+    let stats = stats_report(&shape, &evaluation, true, &materials, material)?;
+
+    let exporters = config.exporters();
+    let options = export::Options::default();
+    for output in outputs {
+        exporters
+            .export(output, &shape, &options)
+            .with_context(|| {
+                format!("Failed to export to `{}`", output.display())
+            })?;
+    }
+
+    Ok(EvaluateReport {
+        tolerance: evaluation.tolerance.inner().into_f64(),
+        validation: ValidationReport::new(&problems, &evaluation.faces),
+        stats,
+        anchors: AnchorsReport::new(&shape.anchors(), &options).anchors,
+        diagnostics: evaluation
+            .debug_info
+            .validation_errors
+            .iter()
+            .map(DiagnosticReport::new)
+            .collect(),
+        exports: outputs.to_vec(),
+    })
+}
+
+fn orient(
+    args: ModelArgs,
+    max_angle: f64,
+    count: usize,
+    json: bool,
+) -> anyhow::Result<()> {
+    let (shape, triangles) = load(args)?;
+
+    if let fj::Shape::Shape2d(_) = shape {
+        anyhow::bail!("Only 3-dimensional models can be oriented for printing");
+    }
+    if triangles.is_empty() {
+        anyhow::bail!("Model is empty");
+    }
+
+    // Which surfaces overhang depends on which way they face.
+    let triangles = analysis::manifold::orient(&triangles)
+        .ok_or_else(|| anyhow::anyhow!("Mesh is not closed; see `validate`"))?;
+
+    let mut orientations = analysis::orientation::suggest(
+        &triangles,
+        Scalar::from_f64(max_angle.to_radians()),
+    );
+    orientations.truncate(count);
+
+    if json {
+        let report = OrientReport::new(max_angle, &orientations);
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    for (i, orientation) in orientations.iter().enumerate() {
+        println!("{}. Down: {:?}", i + 1, orientation.down);
+        println!("   Support volume: {:.4}", orientation.support_volume);
+        println!("   Overhang area:  {:.4}", orientation.overhang_area);
+        println!("   Height:         {:.4}", orientation.height);
+        println!(
+            "   Center of mass: {:.4} above the print bed",
+            orientation.center_of_mass_height
+        );
+    }
+
+    Ok(())
+}
+
+fn cam(
+    args: ModelArgs,
+    output: &Path,
+    settings: CamArgs,
+) -> anyhow::Result<()> {
+    let shape = load_shape(&args)?;
+
+    if let fj::Shape::Shape3d(_) = shape {
+        anyhow::bail!(
+            "Toolpaths can only be generated for 2-dimensional models"
+        );
+    }
+    let operation = settings.operation;
+    let settings = cam::Settings {
+        tool_diameter: settings.tool_diameter,
+        stepover: settings.stepover,
+        depth: settings.depth,
+        step_down: settings.step_down.unwrap_or(settings.depth),
+        safe_height: settings.safe_height,
+        feed_rate: settings.feed_rate,
+        plunge_rate: settings.plunge_rate,
+        spindle_speed: settings.spindle_speed,
+    };
+
+    let distances = [
+        settings.tool_diameter,
+        settings.stepover,
+        settings.depth,
+        settings.step_down,
+    ];
+    if distances.into_iter().any(|distance| distance <= 0.) {
+        anyhow::bail!(
+            "Tool diameter, stepover, depth, and step-down must be positive"
+        );
+    }
+
+    let tolerance = Tolerance::from_bounding_box(&shape.bounding_volume());
+    let mut brep = shape.to_shape(tolerance, &mut DebugInfo::new())?;
+
+    let profile = cam::profile(&mut brep, tolerance);
+    let toolpath =
+        cam::Toolpath::generate(&profile, operation, &settings, tolerance)?;
+
+    if toolpath.loops.is_empty() {
+        anyhow::bail!(
+            "Toolpath is empty; is the tool too large for the model?"
+        );
+    }
+
+    cam::gcode::write(
+        BufWriter::new(File::create(output)?),
+        &toolpath,
+        &settings,
+    )?;
+
+    Ok(())
+}
+
+fn diff(
+    args: ModelArgs,
+    other: Option<String>,
+    other_parameters: Vec<String>,
+    max_distance: Option<f64>,
+    voxel_size: Option<f64>,
+    material_output: Option<&Path>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let mut other_args = args.clone();
+    if let Some(other) = other {
+        other_args.model = other;
+    }
+    other_args.parameters.extend(other_parameters);
+
+    let (shape, before) = load(args)?;
+    let (_, after) = load(other_args)?;
+
+    if before.is_empty() || after.is_empty() {
+        anyhow::bail!("Model is empty; can't compare it");
+    }
+
+    let tolerance = Tolerance::from_bounding_box(&shape.bounding_volume());
+    let diff = Diff::compute(&before, &after, tolerance.inner())?;
+
+    let voxel_size = match voxel_size {
+        Some(size) if !(size > 0. && size.is_finite()) => {
+            anyhow::bail!("Voxel size must be a positive number")
+        }
+        Some(size) => Scalar::from_f64(size),
+        None => MaterialChange::default_voxel_size(&before, &after),
+    };
+    let change = MaterialChange::compute(&before, &after, voxel_size)?;
+
+    let report = DiffReport::new(&diff, &change);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        let [before, after] = [&report.before, &report.after];
+
+        println!(
+            "Bounding box: {:?} - {:?} -> {:?} - {:?}",
+            before.aabb.min, before.aabb.max, after.aabb.min, after.aabb.max
+        );
+        println!(
+            "Area: {:.4} -> {:.4} ({:+.4})",
+            before.area,
+            after.area,
+            after.area - before.area
+        );
+        match (before.volume, after.volume, report.volume_delta) {
+            (Some(before), Some(after), Some(delta)) => {
+                println!(
+                    "Volume: {:.4} -> {:.4} ({:+.4})",
+                    before, after, delta
+                )
+            }
+            _ => {
+                println!("Volume: unknown (mesh is not closed; see `validate`)")
+            }
+        }
+        println!("Hausdorff distance: {:.4}", report.hausdorff_distance);
+        println!(
+            "Material added: {:.4}, removed: {:.4} (voxel size {})",
+            report.added_volume, report.removed_volume, report.voxel_size
+        );
+    }
+
+    if let Some(path) = material_output {
+        write_material_change(path, &change)?;
+    }
+
+    if let Some(max_distance) = max_distance {
+        if report.hausdorff_distance > max_distance {
+            return Err(DifferenceTooLarge(report.hausdorff_distance).into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the added and removed material as a colored PLY file
+#[allow(clippy::too_many_arguments)]
+fn solve(
+    args: ModelArgs,
+    parameter: String,
+    property: Property,
+    target: f64,
+    range: Option<Vec<f64>>,
+    density: Option<f64>,
+    tolerance: Option<f64>,
+    max_evaluations: usize,
+    json: bool,
+) -> anyhow::Result<()> {
+    if property == Property::Mass && density.is_none() {
+        anyhow::bail!("Solving for the mass requires `--density`");
+    }
+
+    let model = args.model();
+    let mut arguments = parse_parameters(args.parameters.clone());
+
+    // The model's metadata is used to check the parameter, if it provides
+    // any.
+    let definition = model
+        .metadata()?
+        .map(|metadata| {
+            metadata
+                .parameters
+                .into_iter()
+                .find(|definition| definition.name == parameter)
+                .with_context(|| {
+                    format!("Model has no parameter `{}`", parameter)
+                })
+        })
+        .transpose()?;
+
+    if let Some(definition) = &definition {
+        // The solver needs the property to change continuously with the
+        // parameter.
+        if definition.is_integer() {
+            anyhow::bail!(
+                "Parameter `{}` is an integer (`{}`); only floating-point \
+                parameters can be solved for",
+                parameter,
+                definition.ty
+            );
+        }
+    }
+
+    let range = match (range, &definition) {
+        (Some(range), definition) => {
+            if let Some(definition) = definition {
+                for &value in &range {
+                    definition.check_number(value, value.to_string())?;
+                }
+            }
+
+            [range[0], range[1]]
+        }
+        (None, Some(definition)) => match (definition.min, definition.max) {
+            (Some(min), Some(max)) => [min, max],
+            _ => anyhow::bail!(
+                "Parameter `{}` has no range; pass it using `--range`",
+                parameter
+            ),
+        },
+        (None, None) => anyhow::bail!(
+            "Model doesn't provide metadata; pass the range using `--range`"
+        ),
+    };
+
+    let solver = Solver {
+        tolerance: tolerance.unwrap_or(target.abs() / 1000.),
+        max_evaluations,
+        ..Solver::new(range)
+    };
+
+    let solution = solver.solve(target, |value| -> anyhow::Result<f64> {
+        arguments.insert(parameter.clone(), value.to_string());
+
+        let shape = model.load(&arguments)?;
+        let processed = ShapeProcessor::new().process(&shape)?;
+
+        Ok(property.measure(&processed, density)?)
+    })?;
+
+    if json {
+        let report = SolveReport::new(parameter, &solution);
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    for evaluation in &solution.evaluations {
+        println!(
+            "{} = {}: {:.4}",
+            parameter, evaluation.parameter, evaluation.value
+        );
+    }
+    println!();
+    println!(
+        "Found {} = {} ({:?}: {:.4}, after {} evaluations)",
+        parameter,
+        solution.parameter,
+        property,
+        solution.value,
+        solution.evaluations.len()
+    );
+
+    Ok(())
+}
+
+fn write_material_change(
+    path: &Path,
+    change: &MaterialChange,
+) -> anyhow::Result<()> {
+    let added = [0, 200, 0];
+    let removed = [220, 0, 0];
+
+    let vertices: Vec<_> = [(&change.added, added), (&change.removed, removed)]
+        .into_iter()
+        .flat_map(|(voxels, color)| {
+            voxels.boundary().into_iter().flat_map(move |triangle| {
+                triangle
+                    .points()
+                    .map(|point| mesh::Vertex::new(point).with_color(color))
+            })
+        })
+        .collect();
+
+    // Each triangle has its own vertices, so they can be indexed in order.
+    IndexFormat::U32.check(vertices.len())?;
+    let triangles: Vec<_> = (0..vertices.len() as mesh::Index / 3)
+        .map(|i| [i * 3, i * 3 + 1, i * 3 + 2])
+        .collect();
+
+    let file = BufWriter::new(File::create(path)?);
+    export::ply::write(file, &vertices, &triangles, IndexFormat::U32)?;
+
+    Ok(())
+}
+
+fn stack_up(
+    args: ModelArgs,
+    stack_up: StackUp,
+    measurements: Vec<Measurement>,
+    density: Option<f64>,
+    min_gap: Option<f64>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let measures_mass = measurements.iter().any(|measurement| {
+        *measurement == Measurement::Property(Property::Mass)
     });
+    if measures_mass && density.is_none() {
+        anyhow::bail!("Measuring the mass requires `--density`");
+    }
+
+    let model = args.model();
+    let mut arguments = parse_parameters(args.parameters.clone());
+
+    let parameters = stack_up.parameters();
+    let mut values = vec![Vec::new(); measurements.len()];
+
+    for (i, sample) in parameters.iter().enumerate() {
+        for (variation, value) in stack_up.variations.iter().zip(sample) {
+            arguments.insert(variation.parameter.clone(), value.to_string());
+        }
+
+        let shape = model.load(&arguments)?;
+        let processed = ShapeProcessor::new().process(&shape)?;
+        let anchors = shape.anchors();
+
+        for (measurement, values) in measurements.iter().zip(&mut values) {
+            let value = measurement
+                .measure(&processed, &anchors, density)
+                .with_context(|| format!("Failed to measure sample {}", i))?;
+            values.push(value);
+        }
+    }
+
+    let summaries: Vec<_> = values
+        .iter()
+        .map(|values| {
+            Summary::new(values).context("Stack-up analysis needs samples")
+        })
+        .collect::<Result<_, _>>()?;
+
+    if json {
+        let report = StackUpReport::new(
+            &stack_up,
+            &parameters,
+            &measurements,
+            &summaries,
+            &values,
+        );
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{} samples", stack_up.samples);
+        for (measurement, summary) in measurements.iter().zip(&summaries) {
+            println!();
+            println!("{}", measurement);
+            println!("  Min:  {:.4}", summary.min);
+            println!("  Max:  {:.4}", summary.max);
+            println!("  Mean: {:.4}", summary.mean);
+            println!("  Standard deviation: {:.4}", summary.std_dev);
+        }
+    }
+
+    if let Some(min_gap) = min_gap {
+        let gaps =
+            measurements
+                .iter()
+                .zip(&summaries)
+                .filter(|(measurement, _)| {
+                    matches!(measurement, Measurement::Gap { .. })
+                });
+        for (measurement, summary) in gaps {
+            if summary.min < min_gap {
+                return Err(
+                    GapTooNarrow(measurement.clone(), summary.min).into()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn compare(
+    args: ModelArgs,
+    scan: &Path,
+    scan_coordinates: CoordinateSystem,
+    tolerance: f64,
+    deviation_output: Option<&Path>,
+) -> anyhow::Result<()> {
+    let (_, triangles) = load(args)?;
+
+    if triangles.is_empty() {
+        anyhow::bail!("Model is empty; can't compare it against scan");
+    }
+
+    // Only the vertices of the scan are relevant for the comparison. Many of
+    // them are shared between facets, so let's deduplicate them first.
+    let points: BTreeSet<_> = import::stl::read(scan)?
+        .into_iter()
+        .flatten()
+        .map(|point| scan_coordinates.to_fornjot(point))
+        .collect();
+
+    let deviation = Deviation::compute(&triangles, points)?;
+
+    println!("Max. deviation: {:.4}", deviation.max().into_f64());
+    println!("Mean deviation: {:.4}", deviation.mean().into_f64());
+    println!(
+        "Within tolerance ({}): {:.1}%",
+        tolerance,
+        deviation
+            .percent_within(Scalar::from_f64(tolerance))
+            .into_f64()
+    );
+
+    if let Some(path) = deviation_output {
+        let file = BufWriter::new(File::create(path)?);
+
+        if path.extension() == Some(OsStr::new("ply")) {
+            // Color the points from zero deviation up to the tolerance, so
+            // any points that are out of tolerance stand out.
+            let (points, field) = deviation.field();
+            export::ply::write_field(
+                file,
+                &points,
+                &[],
+                &field,
+                Colormap::Viridis,
+                [Scalar::ZERO, Scalar::from_f64(tolerance)],
+            )?;
+        } else {
+            deviation.write_csv(file)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Build and load a model
+fn load_shape(args: &ModelArgs) -> anyhow::Result<fj::Shape> {
+    let model = args.model();
+    let parameters = parse_parameters(args.parameters.clone());
+
+    Ok(model.load(&parameters)?)
+}
+
+/// Build and load a model, then triangulate it with the default tolerance
+fn load(args: ModelArgs) -> anyhow::Result<(fj::Shape, Vec<Triangle<3>>)> {
+    let shape = load_shape(&args)?;
+    let triangles = ShapeProcessor::new().process(&shape)?.triangles;
+
+    Ok((shape, triangles))
+}
+
+/// Build and load a model, evaluating it once per frame, if it is animated
+fn load_shapes(
+    args: &ModelArgs,
+    frames: Option<NonZeroUsize>,
+) -> anyhow::Result<Vec<fj::Shape>> {
+    let model = args.model();
+    let parameters = parse_parameters(args.parameters.clone());
+
+    Ok(load_frames(&model, &parameters, frames)?)
+}
+
+fn load_frames(
+    model: &Model,
+    parameters: &HashMap<String, String>,
+    frames: Option<NonZeroUsize>,
+) -> Result<Vec<fj::Shape>, model::Error> {
+    match frames {
+        Some(frames) => model.load_animation(parameters, frames.get()),
+        None => Ok(vec![model.load(parameters)?]),
+    }
+}
+
+/// Load the configuration
+///
+/// Falls back to the default path, if no path is given, and to the default
+/// configuration, if there's no file there.
+fn load_config(path: Option<PathBuf>) -> anyhow::Result<Config> {
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let path = PathBuf::from(config::DEFAULT_PATH);
+            if !path.exists() {
+                return Ok(Config::default());
+            }
+            path
+        }
+    };
+
+    let config = Config::load(&path)
+        .with_context(|| format!("Failed to load `{}`", path.display()))?;
+    Ok(config)
+}
+
+/// Read a toolpath from a G-code file, moving it by `offset`
+fn load_toolpath(
+    path: &Path,
+    offset: Option<Vec<f64>>,
+) -> anyhow::Result<Vec<import::gcode::Polyline>> {
+    let mut toolpath = import::gcode::read(path)?;
+
+    if let Some(offset) = offset {
+        let offset = Vector::from([offset[0], offset[1], offset[2]]);
+
+        for point in toolpath
+            .iter_mut()
+            .flat_map(|polyline| polyline.points.iter_mut())
+        {
+            *point = *point + offset;
+        }
+    }
+
+    Ok(toolpath)
+}
+
+/// The path to write a frame to, when writing the output of a command
+///
+/// Frames of an animation each get their own numbered file.
+fn frame_output(
+    output: &Path,
+    frame: usize,
+    frames: Option<NonZeroUsize>,
+) -> PathBuf {
+    match frames {
+        Some(frames) => animation::frame_path(output, frame, frames.get()),
+        None => output.to_owned(),
+    }
+}
+
+fn serve(address: &str) -> anyhow::Result<()> {
+    let server = remote::Server::bind(address)
+        .with_context(|| format!("Failed to listen on `{}`", address))?;
+    println!("Listening on {}", server.local_addr()?);
+
+    server.run()?;
+
+    Ok(())
+}
+
+fn parse_parameters(parameters: Vec<String>) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+
+    for parameter in parameters {
+        let mut parameter = parameter.splitn(2, '=');
+
+        let key = parameter
+            .next()
+            .expect("model parameter: key not found")
+            .to_owned();
+        let value = parameter
+            .next()
+            .expect("model parameter: value not found")
+            .to_owned();
+
+        map.insert(key, value);
+    }
+
+    map
+}
+
+/// How long the surface that changed is highlighted, after reloading a model
+const CHANGE_HIGHLIGHT_DURATION: Duration = Duration::from_secs(2);
+
+/// The exit code for a model that failed a check, like validation
+const EXIT_CHECK_FAILED: i32 = 1;
+
+/// The exit code for any other error
+const EXIT_ERROR: i32 = 2;
+
+/// Returned by commands that find a model to be invalid
+#[derive(Debug, thiserror::Error)]
+#[error("Model is invalid ({0} problems found)")]
+struct InvalidModel(usize);
+
+/// Returned by `diff`, if two versions of a model differ too much
+#[derive(Debug, thiserror::Error)]
+#[error("Versions of model differ by {0}, more than allowed")]
+struct DifferenceTooLarge(f64);
+
+/// Returned by `stack-up`, if a gap is too narrow in any sample
+#[derive(Debug, thiserror::Error)]
+#[error("Gap `{0}` is {1:.4} wide in at least one sample, less than allowed")]
+struct GapTooNarrow(Measurement, f64);
+
+/// A message from the model watcher to the event loop
+///
+/// A model that has been rebuilt successfully is sent to the [`Evaluator`]
+/// instead, as an [`Update`].
+enum WatcherEvent {
+    /// The model has changed and is being rebuilt
+    Rebuilding,
+
+    /// Rebuilding the model failed
+    Failed,
+}
+
+/// A change to the model, that the [`Evaluator`] needs to process
+enum Update {
+    /// The model has been rebuilt
+    ///
+    /// Contains one shape per frame, if the model is animated.
+    Shapes(Vec<fj::Shape>),
+
+    /// The model needs to be triangulated with a different tolerance
+    Tolerance(Tolerance),
+}
+
+impl Update {
+    /// Add the update to the queue of an [`Evaluator`]
+    ///
+    /// A rebuilt model is what the user waits for, so it's processed first.
+    /// Each kind of update has its own key, so a pending update is superseded
+    /// by a newer one of the same kind.
+    fn push_to(self, queue: &Queue<Self>) {
+        let key = match self {
+            Self::Shapes(_) => "shapes",
+            Self::Tolerance(_) => "tolerance",
+        };
+        queue.push(self.priority(), Some(key.to_owned()), self);
+    }
+
+    /// The priority to process the update with
+    fn priority(&self) -> Priority {
+        match self {
+            Self::Shapes(_) => Priority::Visible,
+            Self::Tolerance(_) => Priority::Background,
+        }
+    }
+}
+
+/// The result of processing an [`Update`]
+struct Processed {
+    animation: anyhow::Result<Animation>,
+
+    /// Whether the update was a rebuilt model, or just a new tolerance
+    reloaded: bool,
+}
+
+/// Processes the model of the viewer, locally or on a server
+///
+/// Remembers the latest shapes and tolerance, so that either can be updated
+/// on its own.
+struct Evaluator {
+    shapes: Vec<fj::Shape>,
+    tolerance: Tolerance,
+    remote: Option<remote::Client>,
+}
+
+impl Evaluator {
+    /// Process the frames of the model, and wait for the result
+    fn process(&mut self) -> anyhow::Result<Animation> {
+        match &mut self.remote {
+            Some(client) => {
+                send_frames(
+                    client,
+                    &self.shapes,
+                    self.tolerance,
+                    Priority::Visible,
+                )?;
+                let frames = (0..self.shapes.len())
+                    .map(|_| client.receive())
+                    .collect::<Result<_, _>>()?;
+                Ok(Animation::new(frames))
+            }
+            None => {
+                // The edges are drawn on top of the triangles.
+                let processor = ShapeProcessor {
+                    edges: true,
+                    ..ShapeProcessor::with_tolerance(self.tolerance)
+                };
+                let frames = self
+                    .shapes
+                    .iter()
+                    .map(|shape| processor.process(shape))
+                    .collect::<Result<_, _>>()?;
+
+                // The viewer keeps running, so the memory that was needed
+                // while triangulating is released, instead of waiting for the
+                // next time.
+                arena::reset();
+
+                Ok(Animation::new(frames))
+            }
+        }
+    }
+
+    /// Apply an update
+    ///
+    /// Returns whether the model was rebuilt, as opposed to only needing a
+    /// different tolerance.
+    fn apply(&mut self, update: Update) -> bool {
+        match update {
+            Update::Shapes(shapes) => {
+                self.shapes = shapes;
+                true
+            }
+            Update::Tolerance(tolerance) => {
+                self.tolerance = tolerance;
+                false
+            }
+        }
+    }
+
+    /// Process updates from the queue, and send the results to `processed`
+    ///
+    /// Returns, once the queue is closed, or nobody receives the results
+    /// anymore.
+    fn run(
+        mut self,
+        updates: &Queue<Update>,
+        processed: mpsc::Sender<Processed>,
+    ) {
+        match self.remote.take() {
+            Some(client) => self.run_remote(client, updates, processed),
+            None => {
+                while let Some(update) = updates.pop(Priority::Background) {
+                    let reloaded = self.apply(update);
+                    let animation = self.process();
+
+                    if processed
+                        .send(Processed {
+                            animation,
+                            reloaded,
+                        })
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Process updates on the server
+    ///
+    /// The requests for an update are sent right away, even if the server is
+    /// still working on earlier ones. The requests for the frames of a model
+    /// have the same keys every time, so the server drops pending frames that
+    /// are already outdated. Only the results for the latest update are sent
+    /// on.
+    fn run_remote(
+        mut self,
+        mut client: remote::Client,
+        updates: &Queue<Update>,
+        processed: mpsc::Sender<Processed>,
+    ) {
+        // The updates that have been sent to the server, oldest first
+        let mut pending = VecDeque::new();
+
+        loop {
+            // Only wait for an update, if there are no responses to wait for.
+            let update = if pending.is_empty() {
+                match updates.pop(Priority::Background) {
+                    Some(update) => Some(update),
+                    None => break,
+                }
+            } else {
+                updates.try_pop(Priority::Background)
+            };
+
+            if let Some(update) = update {
+                let priority = update.priority();
+                let reloaded = self.apply(update);
+
+                match send_frames(
+                    &mut client,
+                    &self.shapes,
+                    self.tolerance,
+                    priority,
+                ) {
+                    Ok(()) => pending.push_back(Pending {
+                        reloaded,
+                        num_frames: self.shapes.len(),
+                        frames: Vec::new(),
+                    }),
+                    Err(err) => {
+                        let animation = Err(err.into());
+                        if processed
+                            .send(Processed {
+                                animation,
+                                reloaded,
+                            })
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                }
+
+                continue;
+            }
+
+            let oldest = pending.front_mut().expect("Nothing to wait for");
+            oldest.frames.push(client.receive());
+            if oldest.frames.len() < oldest.num_frames {
+                continue;
+            }
+
+            let Pending {
+                reloaded, frames, ..
+            } = pending.pop_front().expect("Checked above");
+            if !pending.is_empty() {
+                // A newer update is on its way, which makes this one
+                // outdated.
+                continue;
+            }
+
+            let animation = match frames.into_iter().collect::<Result<_, _>>() {
+                Ok(frames) => Ok(Animation::new(frames)),
+                Err(remote::Error::Superseded) => {
+                    // Only requests for outdated updates are superseded, and
+                    // those are skipped above. Should this ever happen for
+                    // the latest one anyway, there's nothing to show for it.
+                    continue;
+                }
+                Err(err) => Err(err.into()),
+            };
+            if processed
+                .send(Processed {
+                    animation,
+                    reloaded,
+                })
+                .is_err()
+            {
+                break;
+            }
+        }
+    }
+}
+
+/// An update, for which requests have been sent to the server
+struct Pending {
+    reloaded: bool,
+    num_frames: usize,
+
+    /// The responses that have been received so far, one per frame
+    frames: Vec<Result<ProcessedShape, remote::Error>>,
+}
+
+/// Send requests for all frames of a model to a server
+///
+/// Each frame has its own key, so a request for a frame supersedes the
+/// pending request for the same frame of an earlier version of the model.
+fn send_frames(
+    client: &mut remote::Client,
+    shapes: &[fj::Shape],
+    tolerance: Tolerance,
+    priority: Priority,
+) -> Result<(), remote::Error> {
+    for (i, shape) in shapes.iter().enumerate() {
+        client.send(&remote::Request {
+            source: remote::Source::Shape(shape.clone()),
+            tolerance: Some(tolerance.inner().into_f64()),
+            // The edges are drawn on top of the triangles.
+            edges: true,
+            priority,
+            key: Some(format!("frame {}", i)),
+        })?;
+    }
+
+    Ok(())
+}
+
+/// The triangulated frames of the model being viewed
+///
+/// A model that isn't animated has a single frame.
+struct Animation {
+    frames: Vec<ProcessedShape>,
+    current: usize,
+
+    /// The bounding box of all frames
+    ///
+    /// Keeping the bounding box stable means the camera doesn't jump around
+    /// while the animation is playing.
+    aabb: Aabb<3>,
+}
+
+impl Animation {
+    /// Show the processed frames of the model
+    ///
+    /// # Panics
+    ///
+    /// Panics, if there are no frames.
+    fn new(frames: Vec<ProcessedShape>) -> Self {
+        let aabb = frames
+            .iter()
+            .map(|frame| frame.aabb)
+            .reduce(|a, b| a.merged(&b))
+            .expect("Model has no frames");
+
+        Self {
+            frames,
+            current: 0,
+            aabb,
+        }
+    }
+
+    /// The triangles of the current frame
+    fn triangles(&self) -> &[Triangle<3>] {
+        &self.frames[self.current].triangles
+    }
+
+    /// Advance to the frame that is visible after `elapsed` seconds
+    ///
+    /// Returns `true`, if the current frame has changed.
+    fn advance(&mut self, elapsed: f64, duration: f64) -> bool {
+        let frame = animation::frame_at(elapsed, duration, self.frames.len());
+
+        let changed = frame != self.current;
+        self.current = frame;
+
+        changed
+    }
+
+    /// Upload the current frame to the renderer, or print why it can't be
+    ///
+    /// See [`Animation::update_geometry`].
+    fn show(&self, renderer: &mut Renderer, overhang_angle: Scalar) {
+        if let Err(err) = self.update_geometry(renderer, overhang_angle) {
+            println!("Error displaying model: {}", err);
+        }
+    }
+
+    /// Upload the current frame to the renderer
+    ///
+    /// Surfaces that overhang by more than `overhang_angle` from the vertical
+    /// are highlighted as needing support.
+    ///
+    /// Returns an error, if the frame has too many vertices to display. The
+    /// renderer keeps the previous frame, in that case.
+    fn update_geometry(
+        &self,
+        renderer: &mut Renderer,
+        overhang_angle: Scalar,
+    ) -> Result<(), IndexOverflow> {
+        let frame = &self.frames[self.current];
+        let debug_info = &frame.debug_info;
+
+        // The model is shown as it would be printed, with -z facing down.
+        // Which surfaces overhang depends on which way they face, so the mesh
+        // is oriented first, if it is closed.
+        let oriented = analysis::manifold::orient(&frame.triangles);
+        let overhangs = analysis::orientation::overhangs(
+            oriented.as_deref().unwrap_or(&frame.triangles),
+            Vector::from([0., 0., -1.]),
+            overhang_angle,
+        );
+        let overhangs = Vertices::overhangs(&overhangs)?;
+
+        renderer.update_geometry(
+            Vertices::mesh(&frame.triangles, &frame.faces)?,
+            Vertices::edges(frame.edges.iter().map(|edge| edge.points()))?,
+            Silhouette::new(&frame.triangles),
+            debug_info.try_into()?,
+            Vertices::validation_errors(debug_info, &self.aabb)?,
+            self.aabb,
+        );
+        renderer.update_overhangs(overhangs);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,281 @@
+//! Materials, and the mass properties of shapes made of them
+//!
+//! Models assign materials to shapes by name, using [`fj::Material`]. A
+//! [`Materials`] registry maps those names to densities. It knows the
+//! materials in [`fj::material::DENSITIES`] by default, and more can be added
+//! in the configuration file (see [`crate::config`]).
+//!
+//! [`mass_properties`] splits a shape into the parts that are made of a single
+//! material, triangulates each of them, and combines their mass properties.
+
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+
+use crate::{
+    analysis::{manifold, mass::MassProperties},
+    evaluation::ShapeProcessor,
+    kernel,
+};
+
+/// A registry of materials and their densities
+///
+/// Densities are in grams per cubic centimeter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Materials {
+    densities: BTreeMap<String, f64>,
+}
+
+impl Materials {
+    /// Create a registry that knows the materials in
+    /// [`fj::material::DENSITIES`]
+    pub fn new() -> Self {
+        let densities = fj::material::DENSITIES
+            .iter()
+            .map(|&(name, density)| (name.to_owned(), density))
+            .collect();
+
+        Self { densities }
+    }
+
+    /// Add a material, or override the density of a known one
+    pub fn insert(&mut self, name: impl Into<String>, density: f64) {
+        self.densities.insert(name.into(), density);
+    }
+
+    /// Look up the density of a material
+    pub fn density(&self, name: &str) -> Option<f64> {
+        self.densities.get(name).copied()
+    }
+}
+
+impl Default for Materials {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A part of a shape that is made of a single material
+#[derive(Clone, Debug)]
+pub struct Part {
+    /// The material of the part, if one was assigned
+    pub material: Option<String>,
+
+    /// The shape of the part
+    ///
+    /// Contains the transformations and deformations that were applied to the
+    /// part, so it is in the same place as in the original shape.
+    pub shape: fj::Shape3d,
+}
+
+/// Split a shape into the parts that are made of a single material
+///
+/// Unions are split into their operands. If a material is assigned within
+/// another material, the inner one applies.
+pub fn parts(shape: &fj::Shape3d) -> Vec<Part> {
+    let mut parts = Vec::new();
+    collect_parts(shape, None, &|shape| shape, &mut parts);
+    parts
+}
+
+/// Collect the parts of `shape`, wrapping each of them using `wrap`
+///
+/// `wrap` re-applies the transformations and deformations that enclose
+/// `shape` in the original shape.
+fn collect_parts(
+    shape: &fj::Shape3d,
+    material: Option<&str>,
+    wrap: &dyn Fn(fj::Shape3d) -> fj::Shape3d,
+    parts: &mut Vec<Part>,
+) {
+    match shape {
+        fj::Shape3d::Union(union) => {
+            collect_parts(&union.a, material, wrap, parts);
+            collect_parts(&union.b, material, wrap, parts);
+        }
+        fj::Shape3d::Material(inner) => {
+            collect_parts(&inner.shape, Some(&inner.name), wrap, parts);
+        }
+        fj::Shape3d::Anchor(anchor) => {
+            collect_parts(&anchor.shape, material, wrap, parts);
+        }
+        fj::Shape3d::Transform(transform) => {
+            let wrap = |shape| {
+                wrap(
+                    fj::Transform {
+                        shape,
+                        axis: transform.axis,
+                        angle: transform.angle,
+                        offset: transform.offset,
+                    }
+                    .into(),
+                )
+            };
+            collect_parts(&transform.shape, material, &wrap, parts);
+        }
+        fj::Shape3d::Deform(deform) => {
+            let wrap = |shape| {
+                wrap(
+                    fj::Deform {
+                        shape,
+                        deformation: deform.deformation.clone(),
+                    }
+                    .into(),
+                )
+            };
+            collect_parts(&deform.shape, material, &wrap, parts);
+        }
+        fj::Shape3d::Texture(texture) => {
+            let wrap = |shape| {
+                wrap(
+                    fj::Texture {
+                        shape,
+                        pattern: texture.pattern,
+                        faces: texture.faces,
+                        pitch: texture.pitch,
+                        depth: texture.depth,
+                    }
+                    .into(),
+                )
+            };
+            collect_parts(&texture.shape, material, &wrap, parts);
+        }
+        fj::Shape3d::Sweep(_) => parts.push(Part {
+            material: material.map(ToOwned::to_owned),
+            shape: wrap(shape.clone()),
+        }),
+    }
+}
+
+/// Compute the mass properties of a shape
+///
+/// Parts of the shape that have no material assigned are made of
+/// `default_material`. The parts must not overlap. Where they do, their mass
+/// is counted more than once.
+pub fn mass_properties(
+    shape: &fj::Shape3d,
+    materials: &Materials,
+    default_material: Option<&str>,
+) -> Result<MassProperties, MassError> {
+    let processor = ShapeProcessor::new();
+
+    let mut properties = Vec::new();
+    for part in parts(shape) {
+        let name = part
+            .material
+            .as_deref()
+            .or(default_material)
+            .ok_or(MassError::MissingMaterial)?;
+        let density = materials
+            .density(name)
+            .ok_or_else(|| MassError::UnknownMaterial(name.to_owned()))?;
+
+        let processed = processor.process(&part.shape.into())?;
+        let triangles = manifold::orient(&processed.triangles)
+            .ok_or(MassError::NotClosed)?;
+
+        properties.push(MassProperties::compute(&triangles, density));
+    }
+
+    MassProperties::combine(&properties).ok_or(MassError::NotClosed)
+}
+
+/// An error computing the mass properties of a shape
+#[derive(Debug, Error)]
+pub enum MassError {
+    #[error("No material assigned to part of the shape")]
+    MissingMaterial,
+
+    #[error("Unknown material `{0}`")]
+    UnknownMaterial(String),
+
+    #[error("Error processing shape")]
+    Kernel(#[from] kernel::Error),
+
+    #[error("Shape is not a closed solid")]
+    NotClosed,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::cuboid;
+
+    use super::{mass_properties, parts, MassError, Materials};
+
+    fn material(shape: impl Into<fj::Shape3d>, name: &str) -> fj::Shape3d {
+        fj::Material {
+            shape: shape.into(),
+            name: name.to_owned(),
+        }
+        .into()
+    }
+
+    fn translate(
+        shape: impl Into<fj::Shape3d>,
+        offset: [f64; 3],
+    ) -> fj::Shape3d {
+        fj::Transform {
+            shape: shape.into(),
+            axis: [1., 0., 0.],
+            angle: 0.,
+            offset,
+        }
+        .into()
+    }
+
+    #[test]
+    fn parts_should_keep_innermost_material_and_transforms() {
+        let shape = material(
+            fj::Union {
+                a: material(cuboid([1., 1., 1.]), "steel"),
+                b: translate(cuboid([1., 1., 1.]), [2., 0., 0.]),
+            },
+            "wood",
+        );
+
+        let parts = parts(&shape);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].material.as_deref(), Some("steel"));
+        assert_eq!(parts[1].material.as_deref(), Some("wood"));
+        assert!(matches!(parts[1].shape, fj::Shape3d::Transform(_)));
+    }
+
+    #[test]
+    fn mass_properties_should_combine_parts_of_different_materials() {
+        let mut materials = Materials::new();
+        materials.insert("heavy", 3.);
+
+        // A 10 mm cube of density 1, and one of density 3 next to it
+        let shape: fj::Shape3d = fj::Union {
+            a: cuboid([10., 10., 10.]).into(),
+            b: material(
+                translate(cuboid([10., 10., 10.]), [10., 0., 0.]),
+                "heavy",
+            ),
+        }
+        .into();
+
+        assert!(matches!(
+            mass_properties(&shape, &materials, Some("light")),
+            Err(MassError::UnknownMaterial(name)) if name == "light"
+        ));
+        assert!(matches!(
+            mass_properties(&shape, &materials, None),
+            Err(MassError::MissingMaterial)
+        ));
+
+        materials.insert("light", 1.);
+        let properties =
+            mass_properties(&shape, &materials, Some("light")).unwrap();
+
+        assert!((properties.volume - 2000.).abs() < 1e-6);
+        assert!((properties.mass - 4.).abs() < 1e-9);
+
+        // The heavy cube pulls the center of mass towards it.
+        let center: [f64; 3] = properties.center_of_mass.into();
+        let expected = [(5. * 1. + 15. * 3.) / 4., 5., 5.];
+        for (actual, expected) in center.iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-9, "{:?}", center);
+        }
+    }
+}
@@ -1,6 +1,4 @@
-use parry3d_f64::bounding_volume::BoundingVolume as _;
-
-use super::{Point, Vector};
+use super::{Point, Scalar, Vector};
 
 /// An axis-aligned bounding box (AABB)
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
@@ -12,6 +10,78 @@ pub struct Aabb<const D: usize> {
     pub max: Point<D>,
 }
 
+impl<const D: usize> Aabb<D> {
+    /// Merge this AABB with another
+    pub fn merged(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.component_min(&other.min),
+            max: self.max.component_max(&other.max),
+        }
+    }
+
+    /// Check whether the AABB contains a point
+    ///
+    /// Points on the boundary of the AABB are considered to be contained.
+    pub fn contains(&self, point: &Point<D>) -> bool {
+        let [min, max] = self.components();
+        let point = point.coords.components();
+
+        (0..D).all(|i| min[i] <= point[i] && point[i] <= max[i])
+    }
+
+    /// Check whether the AABB intersects another
+    ///
+    /// AABBs that only touch are considered to intersect.
+    pub fn intersects(&self, other: &Self) -> bool {
+        let [a, b] = [self, other].map(|aabb| aabb.components());
+        (0..D).all(|i| a[0][i] <= b[1][i] && b[0][i] <= a[1][i])
+    }
+
+    /// Cast a ray against the AABB
+    ///
+    /// Returns the ray parameter of the point where the ray enters the AABB,
+    /// in multiples of `dir`. Returns zero, if `origin` is within the AABB, and
+    /// `None`, if the ray misses it.
+    pub fn cast_ray(
+        &self,
+        origin: &Point<D>,
+        dir: &Vector<D>,
+    ) -> Option<Scalar> {
+        let [min, max] = self.components();
+        let origin = origin.coords.components();
+        let dir = dir.components();
+
+        let mut enter = 0.;
+        let mut exit = f64::INFINITY;
+
+        for i in 0..D {
+            let [min, max, origin, dir] =
+                [min[i], max[i], origin[i], dir[i]].map(Scalar::into_f64);
+
+            if dir == 0. {
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+
+            let [a, b] = [(min - origin) / dir, (max - origin) / dir];
+            enter = f64::max(enter, a.min(b));
+            exit = f64::min(exit, a.max(b));
+        }
+
+        if enter > exit {
+            return None;
+        }
+
+        Some(Scalar::from_f64(enter))
+    }
+
+    fn components(&self) -> [[Scalar; D]; 2] {
+        [self.min, self.max].map(|point| point.coords.components())
+    }
+}
+
 impl Aabb<2> {
     /// Construct a 2-dimensional AABB from a list of points
     ///
@@ -71,11 +141,6 @@ impl Aabb<3> {
     pub fn size(&self) -> Vector<3> {
         self.to_parry().extents().into()
     }
-
-    /// Merge this AABB with another
-    pub fn merged(&self, other: &Self) -> Self {
-        self.to_parry().merged(&other.to_parry()).into()
-    }
 }
 
 impl From<parry2d_f64::bounding_volume::AABB> for Aabb<2> {
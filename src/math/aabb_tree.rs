@@ -0,0 +1,411 @@
+use smallvec::SmallVec;
+
+use super::{Aabb, Point, Scalar, Vector};
+
+/// A tree of axis-aligned bounding boxes, for fast spatial queries
+///
+/// Stores values of type `T`, each with an [`Aabb`]. The AABBs are organized
+/// into a hierarchy, in which the AABB of each node contains the AABBs of its
+/// children. Queries only descend into nodes whose AABB matches, which makes
+/// them much faster than checking every value, once there are more than a
+/// handful of values.
+///
+/// The tree is built incrementally, by inserting values one by one, and kept
+/// balanced as that happens. If the AABBs of values change, they can be updated
+/// using [`AabbTree::set_aabb`], followed by a call to [`AabbTree::refit`].
+#[derive(Clone, Debug)]
+pub struct AabbTree<const D: usize, T> {
+    nodes: Vec<Node<D>>,
+    root: Option<usize>,
+
+    /// The values in the tree, each with the index of its leaf node
+    values: Vec<(T, usize)>,
+}
+
+impl<const D: usize, T> AabbTree<D, T> {
+    /// Construct an empty tree
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            root: None,
+            values: Vec::new(),
+        }
+    }
+
+    /// The number of values in the tree
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Indicate whether the tree is empty
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Insert a value into the tree
+    ///
+    /// Returns an id that refers to the value. Ids are assigned in order of
+    /// insertion, starting at zero.
+    pub fn insert(&mut self, aabb: Aabb<D>, value: T) -> usize {
+        let id = self.values.len();
+        let leaf = self.push_node(aabb, NodeKind::Leaf(id));
+        self.values.push((value, leaf));
+
+        let mut sibling = match self.root {
+            Some(root) => root,
+            None => {
+                self.root = Some(leaf);
+                return id;
+            }
+        };
+
+        // Find the node that the new leaf is cheapest to pair up with. The
+        // cost of a node is measured by the size of its AABB, as bigger AABBs
+        // are more likely to match queries.
+        while let NodeKind::Branch(children) = self.nodes[sibling].kind {
+            let node = &self.nodes[sibling];
+            let merged = size(&node.aabb.merged(&aabb));
+
+            // The cost of pairing up with this node, and the cost that every
+            // level below it needs to pay, due to this node getting bigger.
+            let pair_cost = merged * 2.;
+            let inherited_cost = (merged - size(&node.aabb)) * 2.;
+
+            let [a, b] = children.map(|child| {
+                let child = &self.nodes[child];
+                let merged = size(&child.aabb.merged(&aabb));
+
+                match child.kind {
+                    NodeKind::Leaf(_) => merged + inherited_cost,
+                    NodeKind::Branch(_) => {
+                        merged - size(&child.aabb) + inherited_cost
+                    }
+                }
+            });
+
+            if pair_cost < a && pair_cost < b {
+                break;
+            }
+
+            sibling = if a <= b { children[0] } else { children[1] };
+        }
+
+        let parent = self.nodes[sibling].parent;
+        let branch = self.push_node(
+            self.nodes[sibling].aabb.merged(&aabb),
+            NodeKind::Branch([sibling, leaf]),
+        );
+        self.nodes[branch].parent = parent;
+        self.nodes[sibling].parent = Some(branch);
+        self.nodes[leaf].parent = Some(branch);
+        self.replace_child(parent, sibling, branch);
+
+        let mut next = Some(branch);
+        while let Some(index) = next {
+            let index = self.balance(index);
+            self.update(index);
+            next = self.nodes[index].parent;
+        }
+
+        id
+    }
+
+    /// Access the value with the given id
+    pub fn get(&self, id: usize) -> Option<&T> {
+        self.values.get(id).map(|(value, _)| value)
+    }
+
+    /// Access the AABB of the value with the given id
+    pub fn aabb(&self, id: usize) -> Option<Aabb<D>> {
+        self.values.get(id).map(|&(_, leaf)| self.nodes[leaf].aabb)
+    }
+
+    /// Change the AABB of the value with the given id
+    ///
+    /// The tree is not updated right away, to make it cheaper to change many
+    /// AABBs at once. Call [`AabbTree::refit`] afterwards, before querying the
+    /// tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if there is no value with the given id.
+    pub fn set_aabb(&mut self, id: usize, aabb: Aabb<D>) {
+        let (_, leaf) = self.values[id];
+        self.nodes[leaf].aabb = aabb;
+    }
+
+    /// Update the tree, after the AABBs of values have changed
+    ///
+    /// This doesn't change the structure of the tree, so queries can become
+    /// slower, if the values move around a lot.
+    pub fn refit(&mut self) {
+        if let Some(root) = self.root {
+            self.refit_node(root);
+        }
+    }
+
+    /// Find all values whose AABB intersects the given one
+    pub fn query_aabb(&self, aabb: &Aabb<D>) -> Found<'_, T> {
+        self.query(|node| node.intersects(aabb))
+    }
+
+    /// Find all values whose AABB contains the given point
+    pub fn query_point(&self, point: &Point<D>) -> Found<'_, T> {
+        self.query(|node| node.contains(point))
+    }
+
+    /// Find all values whose AABB is hit by the given ray
+    ///
+    /// The ray starts at `origin` and extends infinitely in the direction of
+    /// `dir`.
+    pub fn query_ray(
+        &self,
+        origin: &Point<D>,
+        dir: &Vector<D>,
+    ) -> Found<'_, T> {
+        self.query(|node| node.cast_ray(origin, dir).is_some())
+    }
+
+    fn query(&self, matches: impl Fn(&Aabb<D>) -> bool) -> Found<'_, T> {
+        // The tree is kept balanced, so the stack stays shallow.
+        let mut found = SmallVec::new();
+        let mut stack: SmallVec<[usize; 32]> = self.root.into_iter().collect();
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !matches(&node.aabb) {
+                continue;
+            }
+
+            match node.kind {
+                NodeKind::Leaf(id) => found.push(&self.values[id].0),
+                NodeKind::Branch(children) => stack.extend(children),
+            }
+        }
+
+        found
+    }
+
+    fn push_node(&mut self, aabb: Aabb<D>, kind: NodeKind) -> usize {
+        self.nodes.push(Node {
+            aabb,
+            parent: None,
+            height: 0,
+            kind,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn replace_child(&mut self, parent: Option<usize>, old: usize, new: usize) {
+        let parent = match parent {
+            Some(parent) => parent,
+            None => {
+                self.root = Some(new);
+                return;
+            }
+        };
+
+        if let NodeKind::Branch(children) = &mut self.nodes[parent].kind {
+            for child in children {
+                if *child == old {
+                    *child = new;
+                }
+            }
+        }
+    }
+
+    /// Rotate the subtree at the given node, if it is unbalanced
+    ///
+    /// Returns the index of the node that is now the root of the subtree.
+    fn balance(&mut self, index: usize) -> usize {
+        let [a, b] = match self.nodes[index].kind {
+            NodeKind::Branch(children) => children,
+            NodeKind::Leaf(_) => return index,
+        };
+        let [height_a, height_b] = [a, b].map(|child| self.nodes[child].height);
+
+        let (heavy, side) = if height_a > height_b + 1 {
+            (a, 0)
+        } else if height_b > height_a + 1 {
+            (b, 1)
+        } else {
+            return index;
+        };
+
+        // Can't panic. The heavy child has a height of at least 2, so it is a
+        // branch.
+        let [c, d] = match self.nodes[heavy].kind {
+            NodeKind::Branch(children) => children,
+            NodeKind::Leaf(_) => unreachable!(),
+        };
+        let (keep, give) = if self.nodes[c].height > self.nodes[d].height {
+            (c, d)
+        } else {
+            (d, c)
+        };
+
+        // The heavy child takes the place of this node, which becomes one of
+        // its children, and takes over its lower child in exchange.
+        let parent = self.nodes[index].parent;
+        self.replace_child(parent, index, heavy);
+        self.nodes[heavy].parent = parent;
+        self.nodes[heavy].kind = NodeKind::Branch([index, keep]);
+
+        self.nodes[index].parent = Some(heavy);
+        if let NodeKind::Branch(children) = &mut self.nodes[index].kind {
+            children[side] = give;
+        }
+        self.nodes[give].parent = Some(index);
+
+        self.update(index);
+        self.update(heavy);
+
+        heavy
+    }
+
+    /// Update a branch's AABB and height from its children
+    fn update(&mut self, index: usize) {
+        if let NodeKind::Branch([a, b]) = self.nodes[index].kind {
+            let [a, b] = [a, b].map(|child| &self.nodes[child]);
+
+            let aabb = a.aabb.merged(&b.aabb);
+            let height = a.height.max(b.height) + 1;
+
+            let node = &mut self.nodes[index];
+            node.aabb = aabb;
+            node.height = height;
+        }
+    }
+
+    fn refit_node(&mut self, index: usize) {
+        if let NodeKind::Branch(children) = self.nodes[index].kind {
+            for child in children {
+                self.refit_node(child);
+            }
+            self.update(index);
+        }
+    }
+}
+
+impl<const D: usize, T> Default for AabbTree<D, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The values found by a query of an [`AabbTree`]
+///
+/// Queries usually only find a few values, so up to eight of them are stored
+/// inline, without allocating.
+pub type Found<'r, T> = SmallVec<[&'r T; 8]>;
+
+#[derive(Clone, Debug)]
+struct Node<const D: usize> {
+    aabb: Aabb<D>,
+    parent: Option<usize>,
+
+    /// The length of the longest path from this node to a leaf
+    height: usize,
+
+    kind: NodeKind,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum NodeKind {
+    /// A leaf, with the id of its value
+    Leaf(usize),
+
+    /// A branch, with the indices of its child nodes
+    Branch([usize; 2]),
+}
+
+/// A measure of the size of an AABB, for deciding where to insert a node
+///
+/// This is the sum of the AABB's extents. Unlike its volume, this is not zero
+/// for AABBs that are flat in one dimension.
+fn size<const D: usize>(aabb: &Aabb<D>) -> Scalar {
+    let extents = aabb.max - aabb.min;
+    extents
+        .components()
+        .into_iter()
+        .fold(Scalar::ZERO, |sum, extent| sum + extent)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{Aabb, Point, Vector};
+
+    use super::{AabbTree, NodeKind};
+
+    #[test]
+    fn queries_should_find_matching_values() {
+        let mut tree = AabbTree::new();
+        for i in 0..100 {
+            let point = Point::from([i as f64, 0.]);
+            tree.insert(
+                Aabb {
+                    min: point,
+                    max: point,
+                },
+                i,
+            );
+        }
+
+        let mut found = tree.query_aabb(&Aabb {
+            min: Point::from([9.5, -1.]),
+            max: Point::from([12., 1.]),
+        });
+        found.sort();
+        assert_eq!(found.as_slice(), [&10, &11, &12]);
+
+        assert_eq!(tree.query_point(&Point::from([42., 0.])).as_slice(), [&42]);
+        assert!(tree.query_point(&Point::from([42.5, 0.])).is_empty());
+
+        let mut found =
+            tree.query_ray(&Point::from([96., 1.]), &Vector::from([1., -1.]));
+        found.sort();
+        assert_eq!(found.as_slice(), [&97]);
+
+        // Inserting values in order must not result in a degenerate tree.
+        let root = tree.root.unwrap();
+        assert!(tree.nodes[root].height <= 10);
+    }
+
+    #[test]
+    fn refit_should_update_tree() {
+        let mut tree = AabbTree::new();
+        for i in 0..10 {
+            let point = Point::from([i as f64, 0., 0.]);
+            tree.insert(
+                Aabb {
+                    min: point,
+                    max: point,
+                },
+                i,
+            );
+        }
+
+        let point = Point::from([0., 5., 0.]);
+        tree.set_aabb(
+            3,
+            Aabb {
+                min: point,
+                max: point,
+            },
+        );
+        tree.refit();
+
+        assert_eq!(tree.query_point(&point).as_slice(), [&3]);
+        assert!(tree.query_point(&Point::from([3., 0., 0.])).is_empty());
+
+        // Every branch contains the AABBs of its children.
+        for node in &tree.nodes {
+            if let NodeKind::Branch(children) = node.kind {
+                for child in children {
+                    let child = tree.nodes[child].aabb;
+                    assert_eq!(node.aabb.merged(&child), node.aabb);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::f64::consts::FRAC_PI_2;
+
+use super::{Aabb, Point, Scalar, Vector};
+
+/// A circular arc in 2-dimensional space
+///
+/// The arc goes from `start_angle` to `end_angle`, around `center`, in the
+/// direction defined by `clockwise`. Angles are in radians, measured
+/// counter-clockwise from the positive u-axis.
+///
+/// If the start and end angles are the same (or differ by a multiple of a full
+/// turn), the arc is a full circle.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Arc {
+    /// The center of the circle that the arc is a part of
+    pub center: Point<2>,
+
+    /// The radius of the circle that the arc is a part of
+    pub radius: Scalar,
+
+    /// The angle at which the arc starts
+    pub start_angle: Scalar,
+
+    /// The angle at which the arc ends
+    pub end_angle: Scalar,
+
+    /// Whether the arc goes clockwise from its start to its end
+    pub clockwise: bool,
+}
+
+impl Arc {
+    /// Construct an arc from its center and its end points
+    ///
+    /// The radius is taken from the distance between `center` and `start`.
+    /// `end` is only used to determine the end angle, so it doesn't need to
+    /// be at the exact same distance from the center.
+    pub fn from_center_and_points(
+        center: Point<2>,
+        start: Point<2>,
+        end: Point<2>,
+        clockwise: bool,
+    ) -> Self {
+        let angle = |point: Point<2>| {
+            let v = point - center;
+            Scalar::atan2(v.v, v.u)
+        };
+
+        Self {
+            center,
+            radius: (start - center).magnitude(),
+            start_angle: angle(start),
+            end_angle: angle(end),
+            clockwise,
+        }
+    }
+
+    /// Compute the angle that the arc sweeps over
+    ///
+    /// The angle is negative for clockwise arcs, and positive otherwise. Its
+    /// magnitude is larger than zero and no larger than a full turn.
+    pub fn sweep(&self) -> Scalar {
+        let full_turn = Scalar::PI * 2.;
+
+        let mut sweep = self.end_angle - self.start_angle;
+        if self.clockwise {
+            while sweep >= Scalar::ZERO {
+                sweep -= full_turn;
+            }
+            while sweep < -full_turn {
+                sweep += full_turn;
+            }
+        } else {
+            while sweep <= Scalar::ZERO {
+                sweep += full_turn;
+            }
+            while sweep > full_turn {
+                sweep -= full_turn;
+            }
+        }
+
+        sweep
+    }
+
+    /// Compute the length of the arc
+    pub fn length(&self) -> Scalar {
+        self.radius * self.sweep().abs()
+    }
+
+    /// Compute the point at the given parameter
+    ///
+    /// The parameter is `0.` at the start of the arc and `1.` at its end, and
+    /// proportional to the distance along the arc in between.
+    pub fn point_at(&self, t: Scalar) -> Point<2> {
+        self.point_at_angle(self.start_angle + self.sweep() * t)
+    }
+
+    /// Access the point at the start of the arc
+    pub fn start(&self) -> Point<2> {
+        self.point_at_angle(self.start_angle)
+    }
+
+    /// Access the point at the end of the arc
+    pub fn end(&self) -> Point<2> {
+        self.point_at_angle(self.end_angle)
+    }
+
+    /// Compute the axis-aligned bounding box of the arc
+    ///
+    /// The arc is bounded by its end points, and by the points where it
+    /// crosses the axes of its circle.
+    pub fn aabb(&self) -> Aabb<2> {
+        let start = self.start_angle.into_f64();
+        let end = start + self.sweep().into_f64();
+
+        let mut points = vec![self.start(), self.end()];
+
+        let mut quarter = (start.min(end) / FRAC_PI_2).ceil();
+        while quarter * FRAC_PI_2 < start.max(end) {
+            let angle = quarter * FRAC_PI_2;
+            points.push(self.point_at_angle(Scalar::from_f64(angle)));
+            quarter += 1.;
+        }
+
+        Aabb::<2>::from_points(points)
+    }
+
+    fn point_at_angle(&self, angle: Scalar) -> Point<2> {
+        // Computing the axis points from the angle directly would leave tiny
+        // errors in the coordinates that should be zero.
+        let angle = angle.into_f64();
+        let quarters = angle / FRAC_PI_2;
+        let (sin, cos) = if quarters == quarters.round() {
+            let sin_cos = [(0., 1.), (1., 0.), (0., -1.), (-1., 0.)];
+            sin_cos[quarters.rem_euclid(4.) as usize]
+        } else {
+            angle.sin_cos()
+        };
+
+        self.center + Vector::from([cos, sin]) * self.radius
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    use approx::assert_abs_diff_eq;
+
+    use crate::math::{Aabb, Point, Scalar};
+
+    use super::Arc;
+
+    #[test]
+    fn sweep_and_length() {
+        let arc = Arc::from_center_and_points(
+            Point::from([1., 1.]),
+            Point::from([2., 1.]),
+            Point::from([1., 2.]),
+            false,
+        );
+        assert_eq!(arc.radius, Scalar::ONE);
+        assert_eq!(arc.sweep(), Scalar::from_f64(FRAC_PI_2));
+        assert_eq!(arc.length(), Scalar::from_f64(FRAC_PI_2));
+
+        let arc = Arc {
+            clockwise: true,
+            ..arc
+        };
+        assert_abs_diff_eq!(
+            arc.sweep(),
+            Scalar::from_f64(-3. * FRAC_PI_2),
+            epsilon = 1e-12
+        );
+
+        // Arcs with the same start and end angle are full circles.
+        let arc = Arc {
+            end_angle: arc.start_angle,
+            ..arc
+        };
+        assert_eq!(arc.sweep(), Scalar::from_f64(-2. * PI));
+    }
+
+    #[test]
+    fn point_at() {
+        let arc = Arc {
+            center: Point::from([1., 1.]),
+            radius: Scalar::TWO,
+            start_angle: Scalar::ZERO,
+            end_angle: Scalar::PI,
+            clockwise: true,
+        };
+
+        assert_eq!(arc.point_at(Scalar::ZERO), Point::from([3., 1.]));
+        assert_eq!(arc.point_at(Scalar::from_f64(0.5)), Point::from([1., -1.]));
+        assert_eq!(arc.point_at(Scalar::ONE), Point::from([-1., 1.]));
+        assert_abs_diff_eq!(
+            arc.point_at(Scalar::from_f64(0.25)),
+            Point::from([1. + 2f64.sqrt(), 1. - 2f64.sqrt()]),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn aabb() {
+        let arc = Arc {
+            center: Point::origin(),
+            radius: Scalar::ONE,
+            start_angle: Scalar::from_f64(-FRAC_PI_2 / 2.),
+            end_angle: Scalar::from_f64(PI),
+            clockwise: false,
+        };
+
+        let half = 0.5f64.sqrt();
+        assert_abs_diff_eq!(
+            arc.aabb().min,
+            Point::from([-1., -half]),
+            epsilon = 1e-12
+        );
+        assert_eq!(arc.aabb().max, Point::from([1., 1.]));
+
+        let arc = Arc {
+            end_angle: arc.start_angle,
+            ..arc
+        };
+        assert_eq!(
+            arc.aabb(),
+            Aabb {
+                min: Point::from([-1., -1.]),
+                max: Point::from([1., 1.]),
+            }
+        );
+    }
+}
@@ -0,0 +1,372 @@
+use std::f64::consts::PI;
+
+use super::{Arc, Point, Scalar, Segment};
+
+/// A piece of a curve that was fitted to a polyline
+///
+/// See [`fit`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CurveSegment {
+    /// A straight line
+    Line(Segment<2>),
+
+    /// A circular arc
+    Arc(Arc),
+}
+
+impl CurveSegment {
+    /// Access the point where the segment starts
+    pub fn start(&self) -> Point<2> {
+        match self {
+            Self::Line(segment) => segment.points()[0],
+            Self::Arc(arc) => arc.start(),
+        }
+    }
+
+    /// Access the point where the segment ends
+    pub fn end(&self) -> Point<2> {
+        match self {
+            Self::Line(segment) => segment.points()[1],
+            Self::Arc(arc) => arc.end(),
+        }
+    }
+}
+
+/// Approximate a polyline with a sequence of lines and arcs
+///
+/// Dense polylines, like flattened Bézier curves or scanned outlines, have far
+/// more points than needed to describe their shape. This replaces runs of
+/// points with lines and arcs, such that every point of the polyline is within
+/// `tolerance` of the segment that replaces it. The segments start and end at
+/// points of the polyline (up to rounding errors, in the case of arcs), so its
+/// end points are preserved.
+///
+/// Each segment is extended as far as possible, preferring arcs over lines
+/// only if they cover more points. This doesn't always result in the smallest
+/// possible number of segments, but comes close for typical input.
+///
+/// Repeated points are ignored. Returns an empty `Vec`, if there are fewer
+/// than 2 distinct points.
+pub fn fit(points: &[Point<2>], tolerance: Scalar) -> Vec<CurveSegment> {
+    let mut points = points.to_vec();
+    points.dedup();
+
+    let mut segments = Vec::new();
+    let mut i = 0;
+
+    while i + 1 < points.len() {
+        let mut line_end = i + 1;
+        while line_end + 1 < points.len()
+            && fits_line(&points[i..=line_end + 1], tolerance)
+        {
+            line_end += 1;
+        }
+
+        let mut arc = None;
+        for end in i + 2..points.len() {
+            match fit_arc(&points[i..=end], tolerance) {
+                Some(fitted) => arc = Some((end, fitted)),
+                None => break,
+            }
+        }
+
+        match arc {
+            Some((end, arc)) if end > line_end => {
+                segments.push(CurveSegment::Arc(arc));
+                i = end;
+            }
+            _ => {
+                let line = Segment::from([points[i], points[line_end]]);
+                segments.push(CurveSegment::Line(line));
+                i = line_end;
+            }
+        }
+    }
+
+    segments
+}
+
+/// Remove points from a polyline, that barely contribute to its shape
+///
+/// Uses the Douglas-Peucker algorithm: The first and last point are kept, as
+/// is the point that is farthest from the line between them, if it is
+/// farther than `tolerance`. Then the same is done recursively for the two
+/// halves. As a result, every removed point is within `tolerance` of the
+/// simplified polyline.
+///
+/// Unlike [`fit`], this doesn't introduce any new points, so the result is
+/// still a polyline, with a subset of the original points.
+pub fn simplify(points: &[Point<2>], tolerance: Scalar) -> Vec<Point<2>> {
+    let mut points = points.to_vec();
+    points.dedup();
+
+    if points.len() < 3 {
+        return points;
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = ranges.pop() {
+        let farthest = (start + 1..end)
+            .map(|i| {
+                let distance =
+                    distance_to_line(points[i], points[start], points[end]);
+                (i, distance)
+            })
+            .max_by_key(|&(_, distance)| distance);
+
+        if let Some((i, distance)) = farthest {
+            if distance > tolerance {
+                keep[i] = true;
+                ranges.push((start, i));
+                ranges.push((i, end));
+            }
+        }
+    }
+
+    points
+        .into_iter()
+        .zip(keep)
+        .filter(|&(_, keep)| keep)
+        .map(|(point, _)| point)
+        .collect()
+}
+
+/// Remove points from a closed polygon, that barely contribute to its shape
+///
+/// Like [`simplify`], but for polygons, whose last point connects back to the
+/// first. The polygon is split into two polylines at the point that is
+/// farthest from the first, which are then simplified separately.
+///
+/// Returns the polygon unchanged, if simplifying it would leave fewer than 3
+/// points.
+pub fn simplify_closed(
+    points: &[Point<2>],
+    tolerance: Scalar,
+) -> Vec<Point<2>> {
+    let mut points = points.to_vec();
+    points.dedup();
+    while points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+
+    let farthest =
+        (1..points.len()).max_by_key(|&i| (points[i] - points[0]).magnitude());
+    let farthest = match farthest {
+        Some(farthest) => farthest,
+        None => return points,
+    };
+
+    let mut second_half = points[farthest..].to_vec();
+    second_half.push(points[0]);
+
+    let mut simplified = simplify(&points[..=farthest], tolerance);
+    simplified.pop();
+    simplified.extend(simplify(&second_half, tolerance));
+    simplified.pop();
+
+    if simplified.len() < 3 {
+        return points;
+    }
+
+    simplified
+}
+
+/// Check whether all points are close to the line from the first to the last
+fn fits_line(points: &[Point<2>], tolerance: Scalar) -> bool {
+    let (a, b) = (points[0], points[points.len() - 1]);
+
+    points
+        .iter()
+        .all(|&point| distance_to_line(point, a, b) <= tolerance)
+}
+
+/// Compute the distance of a point from the line segment from `a` to `b`
+///
+/// Points beyond the ends of the segment are measured against the ends.
+fn distance_to_line(point: Point<2>, a: Point<2>, b: Point<2>) -> Scalar {
+    let ab = b - a;
+    let ap = point - a;
+    let length = ab.magnitude();
+
+    if length == Scalar::ZERO {
+        return ap.magnitude();
+    }
+
+    let t = ap.dot(&ab) / (length * length);
+    if t < Scalar::ZERO {
+        ap.magnitude()
+    } else if t > Scalar::ONE {
+        (point - b).magnitude()
+    } else {
+        (ap.u * ab.v - ap.v * ab.u).abs() / length
+    }
+}
+
+/// Fit an arc through the first, the middle, and the last point
+///
+/// Returns `None`, if the points don't lie on such an arc within tolerance, or
+/// if they don't progress along it in order.
+fn fit_arc(points: &[Point<2>], tolerance: Scalar) -> Option<Arc> {
+    let a = points[0];
+    let m = points[points.len() / 2];
+    let b = points[points.len() - 1];
+
+    if a == b {
+        return None;
+    }
+
+    let [ax, ay, mx, my, bx, by] =
+        [a.u, a.v, m.u, m.v, b.u, b.v].map(Scalar::into_f64);
+
+    let d = 2. * (ax * (my - by) + mx * (by - ay) + bx * (ay - my));
+    if d == 0. {
+        // The points are collinear.
+        return None;
+    }
+
+    let [a2, m2, b2] =
+        [ax * ax + ay * ay, mx * mx + my * my, bx * bx + by * by];
+    let center = Point::from([
+        (a2 * (my - by) + m2 * (by - ay) + b2 * (ay - my)) / d,
+        (a2 * (bx - mx) + m2 * (ax - bx) + b2 * (mx - ax)) / d,
+    ]);
+
+    let clockwise = d < 0.;
+    let arc = Arc::from_center_and_points(center, a, b, clockwise);
+
+    let mut previous = 0.;
+    for &point in points {
+        let deviation = ((point - center).magnitude() - arc.radius).abs();
+        if deviation > tolerance {
+            return None;
+        }
+
+        // The angle along the arc, from its start, must not decrease.
+        let angle = (point - center).v.atan2((point - center).u).into_f64();
+        let mut along = angle - arc.start_angle.into_f64();
+        if clockwise {
+            along = -along;
+        }
+        let along = along.rem_euclid(2. * PI);
+
+        if along < previous {
+            return None;
+        }
+        previous = along;
+    }
+
+    Some(arc)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use approx::assert_abs_diff_eq;
+
+    use crate::math::{Point, Scalar, Segment};
+
+    use super::{fit, simplify, simplify_closed, CurveSegment};
+
+    #[test]
+    fn lines_and_arcs() {
+        let mut points = Vec::new();
+
+        // A line, followed by a quarter circle around (10, 5), followed by
+        // another line.
+        points.extend((0..=10).map(|i| Point::from([i as f64, 0.])));
+        points.extend((1..=20).map(|i| {
+            let angle = FRAC_PI_2 * i as f64 / 20.;
+            Point::from([10. + 5. * angle.sin(), 5. - 5. * angle.cos()])
+        }));
+        points.extend((6..=15).map(|i| Point::from([15., i as f64])));
+
+        let segments = fit(&points, Scalar::from_f64(0.01));
+        assert_eq!(segments.len(), 3);
+
+        assert_eq!(segments[0].start(), Point::from([0., 0.]));
+        assert_eq!(segments[0].end(), Point::from([10., 0.]));
+        assert_eq!(segments[2].end(), Point::from([15., 15.]));
+
+        match segments[1] {
+            CurveSegment::Arc(arc) => {
+                assert_abs_diff_eq!(
+                    arc.center,
+                    Point::from([10., 5.]),
+                    epsilon = 1e-9
+                );
+                assert!(!arc.clockwise);
+                assert_abs_diff_eq!(
+                    arc.end(),
+                    Point::from([15., 5.]),
+                    epsilon = 1e-9
+                );
+            }
+            segment => panic!("Expected arc, got {:?}", segment),
+        }
+        assert!(matches!(segments[2], CurveSegment::Line(_)));
+    }
+
+    #[test]
+    fn noisy_line() {
+        let points = [[0., 0.], [1., 0.05], [2., -0.05], [3., 0.], [3., 0.]]
+            .map(Point::from);
+
+        let segments = fit(&points, Scalar::from_f64(0.1));
+        assert_eq!(
+            segments,
+            [CurveSegment::Line(Segment::from([points[0], points[3]]))]
+        );
+
+        // With a smaller tolerance, only the first 3 points can be combined,
+        // as they lie on a circle.
+        let segments = fit(&points, Scalar::from_f64(0.01));
+        assert_eq!(segments.len(), 2);
+        assert!(matches!(segments[0], CurveSegment::Arc(_)));
+        assert_abs_diff_eq!(segments[0].end(), points[2], epsilon = 1e-12);
+    }
+
+    #[test]
+    fn simplify_polyline() {
+        let points = [[0., 0.], [1., 0.05], [2., -0.05], [3., 0.], [3., 2.]]
+            .map(Point::from);
+
+        let simplified = simplify(&points, Scalar::from_f64(0.1));
+        assert_eq!(simplified, [points[0], points[3], points[4]]);
+
+        let simplified = simplify(&points, Scalar::from_f64(0.01));
+        assert_eq!(simplified, points);
+    }
+
+    #[test]
+    fn simplify_polygon() {
+        // A square with extra points along its edges.
+        let points = [
+            [0., 0.],
+            [1., 0.],
+            [2., 0.01],
+            [2., 1.],
+            [2., 2.],
+            [1., 2.],
+            [0., 2.],
+            [0., 1.],
+            [0., 0.],
+        ]
+        .map(Point::from);
+
+        let simplified = simplify_closed(&points, Scalar::from_f64(0.1));
+        assert_eq!(
+            simplified,
+            [[0., 0.], [2., 0.01], [2., 2.], [0., 2.]].map(Point::from)
+        );
+
+        // Polygons aren't simplified below 3 points.
+        let sliver = [[0., 0.], [1., 0.01], [2., 0.]].map(Point::from);
+        let simplified = simplify_closed(&sliver, Scalar::from_f64(0.1));
+        assert_eq!(simplified, sliver);
+    }
+}
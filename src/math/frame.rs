@@ -0,0 +1,84 @@
+use super::{Point, Scalar, Vector};
+
+/// A right-handed coordinate frame, defined by an origin and three axes
+///
+/// The axes are perpendicular to each other, and have a length of one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    /// The origin of the frame
+    pub origin: Point<3>,
+
+    /// The x-axis of the frame
+    pub x_axis: Vector<3>,
+
+    /// The y-axis of the frame
+    pub y_axis: Vector<3>,
+
+    /// The z-axis of the frame
+    pub z_axis: Vector<3>,
+}
+
+impl Frame {
+    /// Construct a frame from its origin, x-axis, and z-axis
+    ///
+    /// The axes don't need to be perpendicular or have a length of one. The
+    /// z-axis keeps its direction, while the x-axis is made perpendicular to
+    /// it. The y-axis is computed from both.
+    ///
+    /// Returns `None`, if either axis has a length of zero, or if the axes are
+    /// parallel.
+    pub fn from_axes(
+        origin: Point<3>,
+        x_axis: Vector<3>,
+        z_axis: Vector<3>,
+    ) -> Option<Self> {
+        if z_axis.magnitude() == Scalar::ZERO {
+            return None;
+        }
+        let z_axis = z_axis.normalize();
+
+        let x_axis = x_axis - z_axis * x_axis.dot(&z_axis);
+        if x_axis.magnitude() == Scalar::ZERO {
+            return None;
+        }
+        let x_axis = x_axis.normalize();
+
+        Some(Self {
+            origin,
+            x_axis,
+            y_axis: z_axis.cross(&x_axis),
+            z_axis,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::math::{Point, Vector};
+
+    use super::Frame;
+
+    #[test]
+    fn from_axes_should_make_axes_orthonormal() {
+        let frame = Frame::from_axes(
+            Point::from([1., 2., 3.]),
+            Vector::from([2., 0., 1.]),
+            Vector::from([0., 0., 3.]),
+        )
+        .unwrap();
+
+        assert_eq!(frame.origin, Point::from([1., 2., 3.]));
+        assert_abs_diff_eq!(frame.x_axis, Vector::from([1., 0., 0.]));
+        assert_abs_diff_eq!(frame.y_axis, Vector::from([0., 1., 0.]));
+        assert_abs_diff_eq!(frame.z_axis, Vector::from([0., 0., 1.]));
+
+        let parallel = Frame::from_axes(
+            Point::from([0., 0., 0.]),
+            Vector::from([0., 0., -1.]),
+            Vector::from([0., 0., 1.]),
+        );
+        assert_eq!(parallel, None);
+    }
+}
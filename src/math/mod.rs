@@ -1,6 +1,12 @@
 pub mod aabb;
+pub mod aabb_tree;
+pub mod arc;
 pub mod coordinates;
+pub mod fit;
+pub mod frame;
 pub mod point;
+pub mod polygon;
+pub mod projection;
 pub mod scalar;
 pub mod segment;
 pub mod transform;
@@ -8,6 +14,7 @@ pub mod triangle;
 pub mod vector;
 
 pub use self::{
-    aabb::Aabb, point::Point, scalar::Scalar, segment::Segment,
+    aabb::Aabb, aabb_tree::AabbTree, arc::Arc, frame::Frame, point::Point,
+    projection::Projection, scalar::Scalar, segment::Segment,
     transform::Transform, triangle::Triangle, vector::Vector,
 };
@@ -60,6 +60,24 @@ impl<const D: usize> Point<D> {
             coords: self.coords.to_xyz(),
         }
     }
+
+    /// Compute the component-wise minimum of this point and another
+    ///
+    /// See [`Vector::component_min`].
+    pub fn component_min(&self, other: &Self) -> Self {
+        Self {
+            coords: self.coords.component_min(&other.coords),
+        }
+    }
+
+    /// Compute the component-wise maximum of this point and another
+    ///
+    /// See [`Vector::component_max`].
+    pub fn component_max(&self, other: &Self) -> Self {
+        Self {
+            coords: self.coords.component_max(&other.coords),
+        }
+    }
 }
 
 impl ops::Deref for Point<1> {
@@ -0,0 +1,65 @@
+use super::{Point, Scalar};
+
+/// Compute the signed area of a ring
+///
+/// The area is positive, if the ring is counter-clockwise, negative, if it is
+/// clockwise.
+pub fn signed_area(ring: &[Point<2>]) -> Scalar {
+    let mut area = Scalar::ZERO;
+    for (i, a) in ring.iter().enumerate() {
+        let b = ring[(i + 1) % ring.len()];
+        area += (a.u * b.v - b.u * a.v) / 2.;
+    }
+    area
+}
+
+/// Even-odd point-in-polygon test for a single ring
+pub fn ring_contains(ring: &[Point<2>], point: &Point<2>) -> bool {
+    let mut inside = false;
+
+    for (i, a) in ring.iter().enumerate() {
+        let b = ring[(i + 1) % ring.len()];
+
+        if (a.v > point.v) != (b.v > point.v) {
+            let u = a.u + (point.v - a.v) * (b.u - a.u) / (b.v - a.v);
+            if point.u < u {
+                inside = !inside;
+            }
+        }
+    }
+
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{Point, Scalar};
+
+    use super::{ring_contains, signed_area};
+
+    fn square(min: f64, max: f64) -> Vec<Point<2>> {
+        vec![
+            Point::from([min, min]),
+            Point::from([max, min]),
+            Point::from([max, max]),
+            Point::from([min, max]),
+        ]
+    }
+
+    #[test]
+    fn signed_area_depends_on_orientation() {
+        let mut ring = square(0., 4.);
+        assert_eq!(signed_area(&ring), Scalar::from_f64(16.));
+
+        ring.reverse();
+        assert_eq!(signed_area(&ring), Scalar::from_f64(-16.));
+    }
+
+    #[test]
+    fn contains() {
+        let ring = square(0., 4.);
+
+        assert!(ring_contains(&ring, &Point::from([3., 3.])));
+        assert!(!ring_contains(&ring, &Point::from([5., 3.])));
+    }
+}
@@ -0,0 +1,236 @@
+use std::ops;
+
+use super::{Aabb, Point, Segment, Transform, Vector};
+
+/// A projective transform
+///
+/// Unlike [`Transform`], which is limited to rotations and translations, this
+/// can represent anything that a 4x4 matrix in homogeneous coordinates can,
+/// including the perspective projection that the viewer uses. That makes it
+/// suitable for going back and forth between model space and screen space.
+///
+/// Screen space is represented by normalized device coordinates, which go from
+/// `-1.` to `1.` along each axis. Along the z-axis, `-1.` is the near plane,
+/// and `1.` is the far plane.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Projection(nalgebra::Projective3<f64>);
+
+impl Projection {
+    /// Construct the identity projection
+    pub fn identity() -> Self {
+        Self(nalgebra::Projective3::identity())
+    }
+
+    /// Construct a perspective projection
+    ///
+    /// The camera is located at the origin, looking along the negative z-axis.
+    /// Points between the near and far planes, within the field of view, are
+    /// projected into normalized device coordinates.
+    pub fn perspective(
+        aspect_ratio: f64,
+        field_of_view_in_y: f64,
+        near_plane: f64,
+        far_plane: f64,
+    ) -> Self {
+        let perspective = nalgebra::Perspective3::new(
+            aspect_ratio,
+            field_of_view_in_y,
+            near_plane,
+            far_plane,
+        );
+        Self(perspective.to_projective())
+    }
+
+    /// Construct a projection from a matrix
+    ///
+    /// The matrix is in homogeneous coordinates, in column-major order. It
+    /// must be invertible.
+    pub fn from_matrix(matrix: [f64; 16]) -> Self {
+        let matrix = nalgebra::Matrix4::from_column_slice(&matrix);
+        Self(nalgebra::Projective3::from_matrix_unchecked(matrix))
+    }
+
+    /// Access the matrix of the projection
+    ///
+    /// The matrix is in homogeneous coordinates, in column-major order, which
+    /// is the format that graphics APIs expect.
+    pub fn to_matrix(&self) -> [f64; 16] {
+        let mut matrix = [0.; 16];
+        matrix.copy_from_slice(self.0.matrix().as_slice());
+        matrix
+    }
+
+    /// Compute the inverse of the projection
+    #[must_use]
+    pub fn inverse(&self) -> Self {
+        Self(self.0.inverse())
+    }
+
+    /// Transform the given point
+    pub fn transform_point(&self, point: &Point<3>) -> Point<3> {
+        Point::from(self.0.transform_point(&point.to_na()))
+    }
+
+    /// Transform the given vector
+    ///
+    /// Vectors are not affected by the perspective division, so this is only
+    /// meaningful for affine transforms.
+    pub fn transform_vector(&self, vector: &Vector<3>) -> Vector<3> {
+        Vector::from(self.0.transform_vector(&vector.to_na()))
+    }
+
+    /// Check whether any part of an axis-aligned bounding box could be visible
+    ///
+    /// The box is considered invisible, if it is completely outside of the
+    /// view frustum (the space that is projected into normalized device
+    /// coordinates). The check is conservative: Boxes that are close to a
+    /// corner of the frustum might be reported as visible, even if they
+    /// aren't.
+    pub fn can_see(&self, aabb: &Aabb<3>) -> bool {
+        // Transform the corners into homogeneous coordinates, without the
+        // perspective division, so points behind the camera are handled
+        // correctly.
+        let corners = aabb
+            .vertices()
+            .map(|vertex| self.0.matrix() * vertex.to_na().to_homogeneous());
+
+        // The box is invisible, if all corners are on the outside of one of
+        // the planes that bound the frustum.
+        for axis in 0..3 {
+            for sign in [-1., 1.] {
+                let outside = corners
+                    .iter()
+                    .all(|corner| corner[axis] * sign > corner[3]);
+                if outside {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Compute the segment that the given point in screen space covers
+    ///
+    /// Transforms the point at the given x and y coordinates on the near
+    /// plane, and the point behind it on the far plane, using the inverse of
+    /// this projection. For a projection into screen space, this results in
+    /// the segment of the picking ray, that is within the view frustum.
+    pub fn unproject(&self, point: &Point<2>) -> Segment<3> {
+        let inverse = self.inverse();
+        let [near, far] = [-1., 1.].map(|z| {
+            let point =
+                Point::from([point.u.into_f64(), point.v.into_f64(), z]);
+            inverse.transform_point(&point)
+        });
+
+        Segment::from([near, far])
+    }
+}
+
+impl ops::Mul<Projection> for Projection {
+    type Output = Self;
+
+    /// Compose two projections
+    ///
+    /// The resulting projection applies `rhs` first, then `self`.
+    fn mul(self, rhs: Projection) -> Self::Output {
+        Self(self.0 * rhs.0)
+    }
+}
+
+impl From<Transform> for Projection {
+    fn from(transform: Transform) -> Self {
+        Self::from(&transform)
+    }
+}
+
+impl From<&Transform> for Projection {
+    fn from(transform: &Transform) -> Self {
+        let isometry: parry3d_f64::math::Isometry<f64> = transform.into();
+        Self(nalgebra::Projective3::from_matrix_unchecked(
+            isometry.to_homogeneous(),
+        ))
+    }
+}
+
+impl From<nalgebra::Affine3<f64>> for Projection {
+    fn from(affine: nalgebra::Affine3<f64>) -> Self {
+        Self(nalgebra::Projective3::from_matrix_unchecked(
+            affine.to_homogeneous(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use approx::assert_abs_diff_eq;
+
+    use crate::math::{Aabb, Point, Transform, Vector};
+
+    use super::Projection;
+
+    #[test]
+    fn perspective() {
+        let projection = Projection::perspective(2., FRAC_PI_2, 1., 10.);
+
+        // The corners of the field of view on the near plane.
+        assert_abs_diff_eq!(
+            projection.transform_point(&Point::from([2., 1., -1.])),
+            Point::from([1., 1., -1.]),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            projection.transform_point(&Point::from([-20., -10., -10.])),
+            Point::from([-1., -1., 1.]),
+            epsilon = 1e-12
+        );
+
+        let point = Point::from([0.5, -0.25, -4.]);
+        assert_abs_diff_eq!(
+            projection
+                .inverse()
+                .transform_point(&projection.transform_point(&point)),
+            point,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn can_see() {
+        let projection = Projection::perspective(1., FRAC_PI_2, 1., 10.);
+        let aabb = |min: [f64; 3], max: [f64; 3]| Aabb {
+            min: Point::from(min),
+            max: Point::from(max),
+        };
+
+        assert!(projection.can_see(&aabb([-1., -1., -5.], [1., 1., -4.])));
+
+        // Partially visible boxes are visible.
+        assert!(projection.can_see(&aabb([2., -1., -3.], [4., 1., -2.])));
+
+        // Behind the camera, beside the frustum, and beyond the far plane.
+        assert!(!projection.can_see(&aabb([-1., -1., 1.], [1., 1., 2.])));
+        assert!(!projection.can_see(&aabb([4., -1., -3.], [5., 1., -2.])));
+        assert!(!projection.can_see(&aabb([-1., -1., -12.], [1., 1., -11.])));
+    }
+
+    #[test]
+    fn unproject() {
+        let camera_to_model: Projection =
+            Transform::translation(Vector::from([1., 2., -3.])).into();
+        let projection =
+            Projection::perspective(1., FRAC_PI_2, 1., 10.) * camera_to_model;
+
+        let segment = projection.unproject(&Point::from([1., 0.]));
+        let [near, far] = segment.points();
+
+        assert_abs_diff_eq!(near, Point::from([0., -2., 2.]), epsilon = 1e-12);
+        assert_abs_diff_eq!(far, Point::from([9., -2., -7.]), epsilon = 1e-12);
+
+        // The matrix round-trips.
+        assert_eq!(Projection::from_matrix(projection.to_matrix()), projection);
+    }
+}
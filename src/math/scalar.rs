@@ -80,6 +80,11 @@ impl Scalar {
         self.0.abs().into()
     }
 
+    /// Compute the minimum of this and another scalar
+    pub fn min(self, other: Self) -> Self {
+        self.0.min(other.0).into()
+    }
+
     /// Compute the maximum of this and another scalar
     pub fn max(self, other: Self) -> Self {
         self.0.max(other.0).into()
@@ -196,6 +201,13 @@ impl ops::Sub<Self> for Scalar {
     }
 }
 
+impl ops::SubAssign<Self> for Scalar {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0.sub_assign(rhs.0);
+        *self = Self::from_f64(self.0);
+    }
+}
+
 impl ops::Mul<Self> for Scalar {
     type Output = Self;
 
@@ -1,9 +1,14 @@
-use super::{Aabb, Point, Segment, Triangle, Vector};
+use super::{Aabb, Frame, Point, Segment, Triangle, Vector};
 
 /// A transform
 pub struct Transform(parry3d_f64::math::Isometry<f64>);
 
 impl Transform {
+    /// Construct a transform that doesn't change anything
+    pub fn identity() -> Self {
+        Self(parry3d_f64::math::Isometry::identity())
+    }
+
     /// Construct a translation
     pub fn translation(vector: Vector<3>) -> Self {
         Self(parry3d_f64::math::Isometry::translation(
@@ -13,6 +18,13 @@ impl Transform {
         ))
     }
 
+    /// Combine this transform with another one
+    ///
+    /// The returned transform applies `inner` first, then this one.
+    pub fn compose(&self, inner: &Self) -> Self {
+        Self(self.0 * inner.0)
+    }
+
     /// Transform the given point
     pub fn transform_point(&self, point: &Point<3>) -> Point<3> {
         Point::from(self.0.transform_point(&point.to_na()))
@@ -39,12 +51,26 @@ impl Transform {
         ])
     }
 
+    /// Transform the given frame
+    pub fn transform_frame(&self, frame: &Frame) -> Frame {
+        Frame {
+            origin: self.transform_point(&frame.origin),
+            x_axis: self.transform_vector(&frame.x_axis),
+            y_axis: self.transform_vector(&frame.y_axis),
+            z_axis: self.transform_vector(&frame.z_axis),
+        }
+    }
+
     /// Transform the given axis-aligned bounding box
+    ///
+    /// The result contains all points of the transformed box. If the transform
+    /// includes a rotation, it is bigger than the box.
     pub fn transform_aabb(&self, aabb: &Aabb<3>) -> Aabb<3> {
-        Aabb {
-            min: self.transform_point(&aabb.min),
-            max: self.transform_point(&aabb.max),
-        }
+        Aabb::<3>::from_points(
+            aabb.vertices()
+                .iter()
+                .map(|vertex| self.transform_point(vertex)),
+        )
     }
 }
 
@@ -72,12 +72,55 @@ impl<const D: usize> Vector<D> {
         self.to_na().dot(&other.to_na()).into()
     }
 
+    /// Compute the angle between this vector and another
+    ///
+    /// The angle is in radians, between `0.` and `PI`.
+    pub fn angle_to(&self, other: &Self) -> Scalar {
+        self.to_na().angle(&other.to_na()).into()
+    }
+
+    /// Compute the scalar projection of this vector onto another
+    ///
+    /// This is the length of the component of this vector that is parallel to
+    /// the other vector. It is negative, if the vectors point in opposite
+    /// directions.
+    pub fn scalar_projection_onto(&self, other: &Self) -> Scalar {
+        self.dot(&other.normalize())
+    }
+
+    /// Compute the vector projection of this vector onto another
+    ///
+    /// This is the component of this vector that is parallel to the other
+    /// vector.
+    pub fn vector_projection_onto(&self, other: &Self) -> Self {
+        *other * (self.dot(other) / other.dot(other))
+    }
+
+    /// Compute the component-wise minimum of this vector and another
+    pub fn component_min(&self, other: &Self) -> Self {
+        self.to_na().inf(&other.to_na()).into()
+    }
+
+    /// Compute the component-wise maximum of this vector and another
+    pub fn component_max(&self, other: &Self) -> Self {
+        self.to_na().sup(&other.to_na()).into()
+    }
+
     /// Access an iterator over the vector's components
     pub fn components(&self) -> [Scalar; D] {
         self.0
     }
 }
 
+impl Vector<2> {
+    /// Compute a vector that is perpendicular to this one
+    ///
+    /// The result is this vector, rotated counter-clockwise by 90 degrees.
+    pub fn perp(&self) -> Self {
+        Vector::from([-self.v, self.u])
+    }
+}
+
 impl Vector<3> {
     /// Compute the cross product with another vector
     pub fn cross(&self, other: &Self) -> Self {
@@ -206,6 +249,22 @@ impl<const D: usize> ops::Add<Self> for Vector<D> {
     }
 }
 
+impl<const D: usize> ops::Sub<Self> for Vector<D> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.to_na().sub(rhs.to_na()).into()
+    }
+}
+
+impl<const D: usize> ops::Neg for Vector<D> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self.to_na().neg().into()
+    }
+}
+
 impl<const D: usize> ops::Mul<Scalar> for Vector<D> {
     type Output = Self;
 
@@ -242,7 +301,11 @@ impl<const D: usize> approx::AbsDiffEq for Vector<D> {
 
 #[cfg(test)]
 mod tests {
-    use crate::math::Vector;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    use approx::assert_abs_diff_eq;
+
+    use crate::math::{Scalar, Vector};
 
     #[test]
     fn to_xyz() {
@@ -255,4 +318,50 @@ mod tests {
             Vector::from([1., 2., 3.]),
         );
     }
+
+    #[test]
+    fn angle_to() {
+        let a = Vector::from([1., 0., 0.]);
+
+        assert_eq!(a.angle_to(&a), Scalar::ZERO);
+        assert_abs_diff_eq!(
+            a.angle_to(&Vector::from([0., 2., 0.])),
+            Scalar::from_f64(FRAC_PI_2),
+        );
+        assert_abs_diff_eq!(
+            a.angle_to(&Vector::from([1., 1., 0.])),
+            Scalar::from_f64(FRAC_PI_4),
+        );
+        assert_abs_diff_eq!(
+            a.angle_to(&Vector::from([-3., 0., 0.])),
+            Scalar::from_f64(PI),
+        );
+    }
+
+    #[test]
+    fn projections() {
+        let v = Vector::from([3., 4.]);
+        let axis = Vector::from([2., 0.]);
+
+        assert_eq!(v.scalar_projection_onto(&axis), Scalar::from_f64(3.));
+        assert_eq!(v.vector_projection_onto(&axis), Vector::from([3., 0.]));
+        assert_eq!(v.scalar_projection_onto(&-axis), Scalar::from_f64(-3.),);
+    }
+
+    #[test]
+    fn component_min_max() {
+        let a = Vector::from([1., 5., -2.]);
+        let b = Vector::from([3., 4., -1.]);
+
+        assert_eq!(a.component_min(&b), Vector::from([1., 4., -2.]));
+        assert_eq!(a.component_max(&b), Vector::from([3., 5., -1.]));
+    }
+
+    #[test]
+    fn perp() {
+        let v = Vector::from([2., 1.]);
+
+        assert_eq!(v.perp(), Vector::from([-1., 2.]));
+        assert_eq!(v.dot(&v.perp()), Scalar::ZERO);
+    }
 }
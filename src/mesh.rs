@@ -1,4 +1,9 @@
-use std::{collections::HashMap, hash::Hash};
+use std::{collections::HashMap, hash::Hash, ops::Range};
+
+use thiserror::Error;
+use tracing::warn;
+
+use crate::math::{Point, Scalar, Segment, Triangle, Vector};
 
 /// API for creating a mesh
 pub struct MeshMaker<V> {
@@ -22,15 +27,27 @@ where
     }
 
     /// Add a vertex to the mesh
-    pub fn push(&mut self, vertex: V) {
-        let index =
-            *self.indices_by_vertex.entry(vertex).or_insert_with(|| {
-                let index = self.vertices.len();
+    ///
+    /// Returns an error, if the vertex is new, and the mesh already has as
+    /// many vertices as an [`Index`] can refer to.
+    pub fn push(&mut self, vertex: V) -> Result<(), IndexOverflow> {
+        let index = match self.indices_by_vertex.get(&vertex) {
+            Some(&index) => index,
+            None => {
+                let num_vertices = self.vertices.len() + 1;
+                IndexFormat::U32.check(num_vertices)?;
+
+                let index = self.vertices.len() as Index;
                 self.vertices.push(vertex);
-                index as u32
-            });
+                self.indices_by_vertex.insert(vertex, index);
+
+                index
+            }
+        };
 
         self.indices.push(index);
+
+        Ok(())
     }
 
     /// Access the vertices of the mesh
@@ -44,5 +61,668 @@ where
     }
 }
 
+impl<V> Default for MeshMaker<V>
+where
+    V: Copy + Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// An index that refers to a vertex in a mesh
 pub type Index = u32;
+
+/// The width of the indices of a mesh
+///
+/// Meshes are built with 32-bit indices ([`Index`]), but GPUs and some file
+/// formats also support 16-bit indices, which take up half the space. Those
+/// can be used for meshes with up to 65536 vertices.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IndexFormat {
+    /// 16-bit indices
+    U16,
+
+    /// 32-bit indices
+    U32,
+}
+
+impl IndexFormat {
+    /// The smallest format whose indices can refer to all vertices of a mesh
+    pub fn for_vertices(num_vertices: usize) -> Result<Self, IndexOverflow> {
+        if Self::U16.check(num_vertices).is_ok() {
+            return Ok(Self::U16);
+        }
+
+        Self::U32.check(num_vertices)?;
+        Ok(Self::U32)
+    }
+
+    /// Check whether indices of this format can refer to all vertices
+    pub fn check(&self, num_vertices: usize) -> Result<(), IndexOverflow> {
+        // The highest index is one less than the number of vertices.
+        let max_index = match num_vertices.checked_sub(1) {
+            Some(max_index) => max_index,
+            None => return Ok(()),
+        };
+
+        let fits = match self {
+            Self::U16 => u16::try_from(max_index).is_ok(),
+            Self::U32 => u32::try_from(max_index).is_ok(),
+        };
+
+        if !fits {
+            return Err(IndexOverflow {
+                num_vertices,
+                format: *self,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// The number of bits per index
+    pub fn bits(&self) -> u32 {
+        match self {
+            Self::U16 => 16,
+            Self::U32 => 32,
+        }
+    }
+}
+
+/// A mesh has more vertices than its indices can refer to
+#[derive(Debug, Error)]
+#[error(
+    "Mesh has {num_vertices} vertices, too many for {}-bit indices",
+    format.bits()
+)]
+pub struct IndexOverflow {
+    /// The number of vertices of the mesh
+    pub num_vertices: usize,
+
+    /// The format of the indices
+    pub format: IndexFormat,
+}
+
+/// A vertex of a mesh, with optional attributes besides its position
+///
+/// Use this as the vertex type of [`MeshMaker`], to keep the attributes of
+/// each vertex together with its position. Vertices that only differ in their
+/// attributes, like the corners of a cube, which have a different normal for
+/// each side, are kept separate.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Vertex {
+    /// The position of the vertex
+    pub position: Point<3>,
+
+    /// The normal of the surface at the vertex, used for shading
+    pub normal: Option<Vector<3>>,
+
+    /// Texture coordinates
+    pub uv: Option<Point<2>>,
+
+    /// An RGB color
+    pub color: Option<[u8; 3]>,
+}
+
+impl Vertex {
+    /// Create a vertex without any attributes
+    pub fn new(position: Point<3>) -> Self {
+        Self {
+            position,
+            normal: None,
+            uv: None,
+            color: None,
+        }
+    }
+
+    /// Set the normal of the vertex
+    pub fn with_normal(mut self, normal: Vector<3>) -> Self {
+        self.normal = Some(normal);
+        self
+    }
+
+    /// Set the texture coordinates of the vertex
+    pub fn with_uv(mut self, uv: Point<2>) -> Self {
+        self.uv = Some(uv);
+        self
+    }
+
+    /// Set the color of the vertex
+    pub fn with_color(mut self, color: [u8; 3]) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+impl From<Point<3>> for Vertex {
+    fn from(position: Point<3>) -> Self {
+        Self::new(position)
+    }
+}
+
+/// Compute vertices with normals, for smooth shading of curved surfaces
+///
+/// The normal of each corner of a triangle is the average of the normals of
+/// all triangles that share the vertex, weighted by their area. Triangles
+/// whose normals differ from the triangle's own by more than `max_angle` (in
+/// radians) are left out, so sharp edges stay sharp.
+///
+/// Corners that end up with the same normal result in identical vertices,
+/// which [`MeshMaker`] merges.
+///
+/// Without knowing the faces of the shape, edges that meet at a shallow angle
+/// are smoothed too. Use [`smooth_normals_by_face`], if the faces are known.
+pub fn smooth_normals(
+    triangles: &[Triangle<3>],
+    max_angle: Scalar,
+) -> Vec<[Vertex; 3]> {
+    smooth(triangles, |_| None, max_angle)
+}
+
+/// Compute vertices with normals, keeping the edges between faces sharp
+///
+/// Like [`smooth_normals`], but normals are only averaged between triangles
+/// of the same face. The edges between faces of the shape stay sharp, no
+/// matter the angle at which the faces meet, while the boundaries between the
+/// triangles that approximate a curved face are smoothed.
+///
+/// `max_angle` still applies within faces. Some faces, like the side walls of
+/// a sweep, are represented by triangles that include edges of the shape, so
+/// this keeps those sharp too.
+pub fn smooth_normals_by_face(
+    triangles: &[Triangle<3>],
+    faces: &FaceGroups,
+    max_angle: Scalar,
+) -> Vec<[Vertex; 3]> {
+    smooth(triangles, |i| faces.face_of(i), max_angle)
+}
+
+fn smooth(
+    triangles: &[Triangle<3>],
+    face_of: impl Fn(usize) -> Option<usize>,
+    max_angle: Scalar,
+) -> Vec<[Vertex; 3]> {
+    // The magnitude of the cross product is proportional to the area of the
+    // triangle, which weights the normals accordingly.
+    let normals: Vec<_> = triangles
+        .iter()
+        .map(|triangle| {
+            let [a, b, c] = triangle.points();
+            (b - a).cross(&(c - a))
+        })
+        .collect();
+
+    let mut triangles_by_vertex: HashMap<_, Vec<usize>> = HashMap::new();
+    for (i, triangle) in triangles.iter().enumerate() {
+        for point in triangle.points() {
+            triangles_by_vertex.entry(point).or_default().push(i);
+        }
+    }
+
+    triangles
+        .iter()
+        .zip(&normals)
+        .enumerate()
+        .map(|(j, (triangle, own))| {
+            let face = face_of(j);
+
+            triangle.points().map(|point| {
+                let normal = triangles_by_vertex[&point]
+                    .iter()
+                    .filter(|&&i| face_of(i) == face)
+                    .map(|&i| normals[i])
+                    .filter(|normal| normal.angle_to(own) <= max_angle)
+                    .fold(Vector::from([0., 0., 0.]), |sum, normal| {
+                        sum + normal
+                    });
+
+                Vertex::new(point).with_normal(normal.normalize())
+            })
+        })
+        .collect()
+}
+
+/// Snaps the vertices of a mesh to the edges of a shape
+///
+/// The faces of a shape are triangulated one by one. Where two faces meet,
+/// both approximate the same edge of the boundary representation, but rounding
+/// errors can leave their vertices a tiny distance apart. [`MeshMaker`] then
+/// keeps them as separate vertices, which makes the mesh bigger than it needs
+/// to be, and breaks the smoothing of normals in other applications. Snapping
+/// the vertices of all faces to the same points makes the faces share them.
+///
+/// Only vertices that are within a given distance of a vertex of an edge are
+/// moved, so the vertices inside of faces stay where they are.
+#[derive(Clone, Debug)]
+pub struct EdgeWelder {
+    /// The points on the edges, sorted into cubic cells of size `distance`
+    cells: HashMap<[i64; 3], Vec<Point<3>>>,
+    distance: Scalar,
+}
+
+impl EdgeWelder {
+    /// Collect the points of the approximated edges of a shape
+    ///
+    /// Edges that are shared by two faces are usually approximated once for
+    /// each of them, so points that are within `distance` of each other are
+    /// merged into one.
+    pub fn new(edges: &[Segment<3>], distance: Scalar) -> Self {
+        let mut points: Vec<_> =
+            edges.iter().flat_map(|edge| edge.points()).collect();
+
+        // The edges come in no particular order. Sorting their points makes
+        // the choice of the merged points, and therefore the result,
+        // deterministic.
+        points.sort();
+        points.dedup();
+
+        let mut welder = Self {
+            cells: HashMap::new(),
+            distance,
+        };
+        for point in points {
+            if welder.find(point).is_none() {
+                let cell = welder.cell(point);
+                welder.cells.entry(cell).or_default().push(point);
+            }
+        }
+
+        welder
+    }
+
+    /// Move a point to the nearest vertex of the approximated edges
+    ///
+    /// The point stays where it is, if no such vertex is within the distance.
+    /// Points between the vertices of an edge aren't considered.
+    pub fn weld(&self, point: Point<3>) -> Point<3> {
+        self.find(point).unwrap_or(point)
+    }
+
+    /// Weld the vertices of triangles
+    ///
+    /// Triangles that become degenerate, because two of their vertices are
+    /// moved to the same point, are left out. A warning is logged, if that
+    /// happens.
+    pub fn weld_triangles(
+        &self,
+        triangles: &[Triangle<3>],
+    ) -> Vec<Triangle<3>> {
+        let welded: Vec<_> = triangles
+            .iter()
+            .filter_map(|triangle| {
+                let [a, b, c] = triangle.points().map(|point| self.weld(point));
+
+                let area = (b - a).cross(&(c - a)).magnitude();
+                if area == Scalar::ZERO {
+                    return None;
+                }
+
+                Some(Triangle::from([a, b, c]))
+            })
+            .collect();
+
+        let degenerate = triangles.len() - welded.len();
+        if degenerate > 0 {
+            warn!(
+                "Left out {} triangles, which became degenerate when welding \
+                their vertices to the edges",
+                degenerate
+            );
+        }
+
+        welded
+    }
+
+    fn find(&self, point: Point<3>) -> Option<Point<3>> {
+        let [x, y, z] = self.cell(point);
+
+        // Points within the distance can only be in the same cell, or in
+        // one of its neighbors.
+        let mut closest: Option<(Scalar, Point<3>)> = None;
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let cell = match self.cells.get(&[x + dx, y + dy, z + dz]) {
+                        Some(cell) => cell,
+                        None => continue,
+                    };
+
+                    for &candidate in cell {
+                        let distance = (candidate - point).magnitude();
+                        let is_closer = match closest {
+                            Some((min, _)) => distance < min,
+                            None => distance <= self.distance,
+                        };
+
+                        if is_closer {
+                            closest = Some((distance, candidate));
+                        }
+                    }
+                }
+            }
+        }
+
+        closest.map(|(_, point)| point)
+    }
+
+    fn cell(&self, point: Point<3>) -> [i64; 3] {
+        point
+            .coords
+            .components()
+            .map(|coord| (coord / self.distance).into_f64().floor() as i64)
+    }
+}
+
+/// The faces of a shape that the triangles of a mesh came from
+///
+/// The triangles of each face are stored consecutively, in the order of the
+/// faces in the shape's topology. This only records how many triangles each
+/// face has, which is enough to map any triangle back to its face, and vice
+/// versa. Faces are identified by their index.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FaceGroups {
+    /// For each face, the index one past its last triangle
+    ends: Vec<usize>,
+}
+
+impl FaceGroups {
+    /// Create an instance without any faces
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a face, whose triangles follow those of the previous face
+    pub fn push(&mut self, num_triangles: usize) {
+        let start = self.ends.last().copied().unwrap_or(0);
+        self.ends.push(start + num_triangles);
+    }
+
+    /// The number of faces
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Indicate whether there are no faces
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// The range of triangle indices that belong to the given face
+    ///
+    /// # Panics
+    ///
+    /// Panics, if there is no face with that index.
+    pub fn triangles(&self, face: usize) -> Range<usize> {
+        let start = if face == 0 { 0 } else { self.ends[face - 1] };
+        start..self.ends[face]
+    }
+
+    /// The index of the face that the given triangle belongs to
+    ///
+    /// Returns `None`, if the triangle is not part of any face.
+    pub fn face_of(&self, triangle: usize) -> Option<usize> {
+        let face = self.ends.partition_point(|&end| end <= triangle);
+        if face < self.ends.len() {
+            Some(face)
+        } else {
+            None
+        }
+    }
+
+    /// Iterate over the ranges of triangle indices of all faces
+    pub fn iter(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        (0..self.len()).map(|face| self.triangles(face))
+    }
+}
+
+/// The edges of a mesh that can be part of its silhouette
+///
+/// An edge is part of the silhouette, if one of the two triangles that share
+/// it faces the viewer, while the other faces away. Whether that's the case
+/// depends on where the viewer is, so the triangles next to each edge are
+/// recorded once, and the silhouette is then computed for each point of view.
+///
+/// Edges with only one adjacent triangle, which closed meshes don't have, are
+/// ignored.
+#[derive(Clone, Debug, Default)]
+pub struct Silhouette {
+    edges: Vec<SilhouetteEdge>,
+}
+
+impl Silhouette {
+    /// Find the edges that triangles share in a mesh
+    ///
+    /// The orientation of the triangles doesn't matter.
+    pub fn new(triangles: &[Triangle<3>]) -> Self {
+        let mut apexes_by_edge: HashMap<_, Vec<Point<3>>> = HashMap::new();
+
+        for triangle in triangles {
+            let [a, b, c] = triangle.points();
+
+            for [start, end, apex] in [[a, b, c], [b, c, a], [c, a, b]] {
+                let edge = if start < end {
+                    [start, end]
+                } else {
+                    [end, start]
+                };
+                apexes_by_edge.entry(edge).or_default().push(apex);
+            }
+        }
+
+        let edges = apexes_by_edge
+            .into_iter()
+            .filter_map(|(points, apexes)| match apexes[..] {
+                [a, b] => Some(SilhouetteEdge {
+                    points,
+                    apexes: [a, b],
+                }),
+                _ => None,
+            })
+            .collect();
+
+        Self { edges }
+    }
+
+    /// Iterate over the edges of the silhouette, as seen from `eye`
+    pub fn edges(
+        &self,
+        eye: Point<3>,
+    ) -> impl Iterator<Item = [Point<3>; 2]> + '_ {
+        self.edges
+            .iter()
+            .filter(move |edge| edge.is_silhouette(eye))
+            .map(|edge| edge.points)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct SilhouetteEdge {
+    points: [Point<3>; 2],
+
+    /// The vertices of the two adjacent triangles that are not on the edge
+    apexes: [Point<3>; 2],
+}
+
+impl SilhouetteEdge {
+    fn is_silhouette(&self, eye: Point<3>) -> bool {
+        // One of the triangles faces the eye and the other faces away, if
+        // both are on the same side of the plane through the edge and the
+        // eye. If the triangles are coplanar, they're always on different
+        // sides.
+        let [start, end] = self.points;
+        let normal = (end - start).cross(&(eye - start));
+
+        let [a, b] = self.apexes.map(|apex| normal.dot(&(apex - start)));
+        a * b > Scalar::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_4;
+
+    use crate::{
+        math::{Point, Scalar, Segment, Triangle, Vector},
+        testing::{cuboid, Evaluated},
+    };
+
+    use super::{
+        smooth_normals, smooth_normals_by_face, EdgeWelder, FaceGroups,
+        IndexFormat, MeshMaker, Silhouette,
+    };
+
+    #[test]
+    fn face_groups() {
+        let mut faces = FaceGroups::new();
+        faces.push(2);
+        faces.push(0);
+        faces.push(3);
+
+        assert_eq!(faces.len(), 3);
+        assert_eq!(faces.iter().collect::<Vec<_>>(), [0..2, 2..2, 2..5]);
+
+        assert_eq!(faces.face_of(1), Some(0));
+        assert_eq!(faces.face_of(2), Some(2));
+        assert_eq!(faces.face_of(5), None);
+    }
+
+    #[test]
+    fn index_format() {
+        assert_eq!(IndexFormat::for_vertices(0).unwrap(), IndexFormat::U16);
+        assert_eq!(IndexFormat::for_vertices(65536).unwrap(), IndexFormat::U16);
+        assert_eq!(IndexFormat::for_vertices(65537).unwrap(), IndexFormat::U32);
+
+        let error = IndexFormat::U16.check(65537).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Mesh has 65537 vertices, too many for 16-bit indices"
+        );
+    }
+
+    #[test]
+    fn smooth_normals_should_keep_sharp_edges() {
+        // Two pairs of triangles. Within each pair, the triangles meet at a
+        // shallow angle, while the pairs meet at a right angle.
+        let triangle =
+            |points: [[f64; 3]; 3]| Triangle::from(points.map(Point::from));
+        let triangles = [
+            triangle([[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]),
+            triangle([[0., 0., 0.], [0., 1., 0.], [-1., 0., 0.1]]),
+            triangle([[1., 0., 0.], [0., 0., 0.], [0., 0., -1.]]),
+            triangle([[1., 0., 0.], [0., 0., -1.], [1., 0.1, -1.]]),
+        ];
+
+        let vertices = smooth_normals(&triangles, Scalar::from_f64(FRAC_PI_4));
+
+        let normal = |triangle: usize, corner: usize| {
+            vertices[triangle][corner].normal.unwrap()
+        };
+
+        // The shared vertices within each pair have the same normal.
+        assert_eq!(normal(0, 0), normal(1, 0));
+        assert_eq!(normal(0, 2), normal(1, 1));
+        assert_eq!(normal(2, 0), normal(3, 0));
+
+        // Where the pairs meet, the normals stay separate.
+        assert_eq!(normal(0, 1), Vector::from([0., 0., 1.]));
+        assert!(
+            normal(2, 0).angle_to(&normal(0, 1)) > Scalar::from_f64(FRAC_PI_4)
+        );
+
+        // Identical vertices are merged.
+        let mut mesh = MeshMaker::new();
+        for vertex in vertices.iter().flatten() {
+            mesh.push(*vertex).unwrap();
+        }
+        assert_eq!(mesh.vertices().count(), 8);
+    }
+
+    #[test]
+    fn smooth_normals_by_face_should_keep_edges_between_faces_sharp() {
+        // Two triangles that meet at a shallow angle, but belong to different
+        // faces.
+        let triangle =
+            |points: [[f64; 3]; 3]| Triangle::from(points.map(Point::from));
+        let triangles = [
+            triangle([[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]),
+            triangle([[0., 0., 0.], [0., 1., 0.], [-1., 0., 0.1]]),
+        ];
+        let max_angle = Scalar::from_f64(FRAC_PI_4);
+
+        let mut faces = FaceGroups::new();
+        faces.push(1);
+        faces.push(1);
+
+        let vertices = smooth_normals_by_face(&triangles, &faces, max_angle);
+        assert_eq!(vertices[0][0].normal, Some(Vector::from([0., 0., 1.])));
+        assert_ne!(vertices[0][0].normal, vertices[1][0].normal);
+
+        // Within a face, they are smoothed.
+        let mut faces = FaceGroups::new();
+        faces.push(2);
+
+        let vertices = smooth_normals_by_face(&triangles, &faces, max_angle);
+        assert_eq!(vertices[0][0].normal, vertices[1][0].normal);
+    }
+
+    #[test]
+    fn edge_welder_should_merge_vertices_of_adjacent_faces() {
+        // Two faces that share an edge, whose approximations differ slightly
+        let point = |point: [f64; 3]| Point::from(point);
+        let nudged = 1. + 1e-12;
+        let edges = [
+            Segment::from([point([0., 0., 0.]), point([1., 0., 0.])]),
+            Segment::from([point([0., 0., 0.]), point([nudged, 0., 0.])]),
+        ];
+        let triangles = [
+            Triangle::from(
+                [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]].map(point),
+            ),
+            Triangle::from(
+                [[nudged, 0., 0.], [0., 0., 0.], [0., 0., 1.]].map(point),
+            ),
+        ];
+
+        let count_vertices = |triangles: &[Triangle<3>]| {
+            let mut mesh = MeshMaker::new();
+            for triangle in triangles {
+                for point in triangle.points() {
+                    mesh.push(point).unwrap();
+                }
+            }
+            mesh.vertices().count()
+        };
+        assert_eq!(count_vertices(&triangles), 5);
+
+        let welder = EdgeWelder::new(&edges, Scalar::from_f64(1e-6));
+        let welded = welder.weld_triangles(&triangles);
+        assert_eq!(count_vertices(&welded), 4);
+
+        // Vertices that aren't on an edge stay where they are.
+        assert_eq!(welded[1].points()[2], point([0., 0., 1.]));
+        assert_eq!(welder.weld(point([0.5, 0., 0.])), point([0.5, 0., 0.]));
+    }
+
+    #[test]
+    fn silhouette_of_cube() {
+        let cube = Evaluated::new(&cuboid([1., 1., 1.]).into());
+        let silhouette = Silhouette::new(cube.triangles());
+
+        // Seen from a corner, three faces are visible, and the silhouette is
+        // a hexagon. The diagonals within the faces are never part of it.
+        let mut edges: Vec<_> =
+            silhouette.edges(Point::from([5., 6., 7.])).collect();
+        assert_eq!(edges.len(), 6);
+
+        // The edges that meet at the corner that's closest to the eye are not
+        // part of it, nor are those at the opposite corner.
+        edges.retain(|edge| {
+            edge.iter().any(|point| {
+                *point == Point::from([1., 1., 1.])
+                    || *point == Point::from([0., 0., 0.])
+            })
+        });
+        assert!(edges.is_empty());
+    }
+}
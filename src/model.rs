@@ -1,14 +1,86 @@
-use std::{collections::HashMap, io, path::PathBuf, process::Command};
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf, MAIN_SEPARATOR},
+    process::Command,
+};
 
+use libloading::{Library, Symbol};
 use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::{animation, import};
 
 pub struct Model {
     name: String,
+    path: String,
+    simplify: Option<f64>,
 }
 
 impl Model {
-    pub fn new(name: String) -> Self {
-        Self { name }
+    /// Refer to a model by name, or by the path of its directory
+    ///
+    /// A plain name refers to a model in the `models/` directory. Anything
+    /// that contains a path separator is interpreted as the path to the
+    /// model's directory. This makes it possible to work with models that
+    /// live elsewhere, like an older version of a model that was checked out
+    /// into another directory.
+    ///
+    /// Either way, the name of the model's package must match the name of
+    /// its directory.
+    ///
+    /// A path to a file with the `.json`, `.scad`, `.svg`, or `.dxf` extension
+    /// refers to a shape description instead (see [`Model::is_description`]).
+    /// Its name is the name of the file, without the extension.
+    pub fn new(model: String) -> Self {
+        let is_path = model.contains('/') || model.contains(MAIN_SEPARATOR);
+        if !is_path {
+            return Self {
+                path: format!("models/{}", model),
+                name: model,
+                simplify: None,
+            };
+        }
+
+        let path = model.trim_end_matches(&['/', MAIN_SEPARATOR][..]);
+        let name = if is_description(path) {
+            Path::new(path).file_stem()
+        } else {
+            Path::new(path).file_name()
+        };
+        let name = name
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_owned());
+
+        Self {
+            name,
+            path: path.to_owned(),
+            simplify: None,
+        }
+    }
+
+    /// Simplify the sketches of shape descriptions when loading them
+    ///
+    /// Points that are within `tolerance` of the simplified outlines are
+    /// removed (see [`import::simplify`]). This is meant for descriptions that
+    /// were generated from files of other applications, which tend to contain
+    /// many more points than needed. It has no effect on compiled models.
+    pub fn with_simplification(mut self, tolerance: f64) -> Self {
+        self.simplify = Some(tolerance);
+        self
+    }
+
+    /// Indicate whether the model is a shape description
+    ///
+    /// Shape descriptions are JSON files that contain the serialized form of an
+    /// [`fj::Shape`], OpenSCAD files (see [`import::scad`]), or 2D profiles
+    /// from SVG and DXF files (see [`import::profile`]). They don't need to be
+    /// compiled, which makes them handy for quick one-off parts, or for
+    /// generating shapes from other languages. They don't have any parameters,
+    /// except for profiles, which can be extruded by passing a `height`.
+    pub fn is_description(&self) -> bool {
+        is_description(&self.path)
     }
 
     pub fn name(&self) -> &str {
@@ -16,13 +88,34 @@ impl Model {
     }
 
     pub fn path(&self) -> String {
-        format!("models/{}", self.name)
+        self.path.clone()
     }
 
+    /// The path that needs to be watched for changes to the model
+    ///
+    /// For shape descriptions, this is the directory that contains the file.
+    /// Only changes to the file itself are relevant, which can be checked
+    /// using [`Model::is_source`].
     pub fn src_path(&self) -> PathBuf {
+        if self.is_description() {
+            return match Path::new(&self.path).parent() {
+                Some(parent) if parent != Path::new("") => parent.to_owned(),
+                _ => PathBuf::from("."),
+            };
+        }
+
         format!("{}/src", self.path()).into()
     }
 
+    /// Indicate whether a file within [`Model::src_path`] is part of the model
+    pub fn is_source(&self, path: &Path) -> bool {
+        if self.is_description() {
+            return path.file_name() == Path::new(&self.path).file_name();
+        }
+
+        true
+    }
+
     pub fn lib_path(&self) -> String {
         let name = self.name().replace('-', "_");
 
@@ -38,10 +131,67 @@ impl Model {
         format!("{}/target/debug/{}", self.path(), file)
     }
 
+    /// Build the model and read its metadata
+    ///
+    /// Returns `None`, if the model doesn't provide metadata, because it
+    /// wasn't defined using [`fj::model`].
+    pub fn metadata(&self) -> Result<Option<Metadata>, Error> {
+        if self.is_description() {
+            return Ok(None);
+        }
+
+        self.build()?;
+
+        // This is unsound, for the same reasons that are explained in
+        // `evaluate`.
+        unsafe {
+            let lib = Library::new(self.lib_path())?;
+            read_metadata(&lib)
+        }
+    }
+
+    /// Build the model and evaluate it with the given arguments
+    ///
+    /// Shape descriptions are read instead, ignoring the arguments, except for
+    /// the `height` of profiles.
     pub fn load(
         &self,
         arguments: &HashMap<String, String>,
     ) -> Result<fj::Shape, Error> {
+        if self.is_description() {
+            return self.read_description(arguments);
+        }
+
+        self.build()?;
+        self.evaluate(arguments)
+    }
+
+    /// Build the model and evaluate it once per animation frame
+    ///
+    /// The normalized time of each frame (see [`animation::times`]) is passed
+    /// to the model in the [`fj::TIME`] parameter, in addition to `arguments`.
+    pub fn load_animation(
+        &self,
+        arguments: &HashMap<String, String>,
+        frames: usize,
+    ) -> Result<Vec<fj::Shape>, Error> {
+        // Shape descriptions have no parameters, so they can't be animated.
+        if self.is_description() {
+            return Ok(vec![self.read_description(arguments)?; frames]);
+        }
+
+        self.build()?;
+
+        let mut arguments = arguments.clone();
+        animation::times(frames)
+            .map(|time| {
+                arguments.insert(fj::TIME.to_owned(), time.to_string());
+                self.evaluate(&arguments)
+            })
+            .collect()
+    }
+
+    fn build(&self) -> Result<(), Error> {
         let status = Command::new("cargo")
             .arg("build")
             .args(["--manifest-path", &format!("{}/Cargo.toml", self.path())])
@@ -51,6 +201,13 @@ impl Model {
             return Err(Error::Compile);
         }
 
+        Ok(())
+    }
+
+    fn evaluate(
+        &self,
+        arguments: &HashMap<String, String>,
+    ) -> Result<fj::Shape, Error> {
         // So, strictly speaking this is all unsound:
         // - `Library::new` requires us to abide by the arbitrary requirements
         //   of any library initialization or termination routines.
@@ -68,15 +225,83 @@ impl Model {
         // to switch to a better technique:
         // https://github.com/hannobraun/Fornjot/issues/71
         let shape = unsafe {
-            let lib = libloading::Library::new(self.lib_path())?;
-            let model: libloading::Symbol<ModelFn> = lib.get(b"model")?;
-            model(arguments)
+            let lib = Library::new(self.lib_path())?;
+
+            match read_metadata(&lib)? {
+                Some(metadata) => {
+                    // A model with missing arguments would panic across the
+                    // FFI boundary, so make sure it doesn't get evaluated.
+                    metadata.check_arguments(arguments)?;
+
+                    // The model checks its shape itself (see
+                    // `fj::Shape::checked`).
+                    let model: Symbol<CheckedModelFn> = lib.get(b"model")?;
+                    model(arguments)?
+                }
+                None => {
+                    // A hand-written model function returns its shape
+                    // unchecked. Degenerate input would lead to invalid
+                    // geometry, or panics deep in the kernel. Better to catch
+                    // it here, where we can still report it properly.
+                    let model: Symbol<ModelFn> = lib.get(b"model")?;
+                    let shape = model(arguments);
+                    shape.check()?;
+                    shape
+                }
+            }
+        };
+
+        Ok(shape)
+    }
+
+    fn read_description(
+        &self,
+        arguments: &HashMap<String, String>,
+    ) -> Result<fj::Shape, Error> {
+        let mut shape = if has_extension(&self.path, "scad") {
+            import::scad::read(&self.path)?
+        } else if is_profile(&self.path) {
+            let height = match arguments.get("height") {
+                Some(value) => Some(value.parse().map_err(|_| {
+                    Error::InvalidParameter {
+                        name: "height".to_owned(),
+                        value: value.clone(),
+                    }
+                })?),
+                None => None,
+            };
+
+            import::profile::read(&self.path, height)?
+        } else {
+            let description = fs::read_to_string(&self.path)?;
+            serde_json::from_str(&description)?
         };
 
+        if let Some(tolerance) = self.simplify {
+            let removed = import::simplify(&mut shape, tolerance);
+            info!("Simplification removed {} points", removed);
+        }
+
+        shape.check()?;
+
         Ok(shape)
     }
 }
 
+fn is_description(path: &str) -> bool {
+    has_extension(path, "json")
+        || has_extension(path, "scad")
+        || is_profile(path)
+}
+
+fn is_profile(path: &str) -> bool {
+    has_extension(path, "svg") || has_extension(path, "dxf")
+}
+
+fn has_extension(path: &str, extension: &str) -> bool {
+    Path::new(path).extension() == Some(OsStr::new(extension))
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Error compiling model")]
@@ -87,7 +312,483 @@ pub enum Error {
 
     #[error("Error loading model from dynamic library")]
     LibLoading(#[from] libloading::Error),
+
+    #[error("Model returned invalid shape")]
+    InvalidShape(#[from] fj::ShapeError),
+
+    #[error("Error parsing shape description")]
+    Description(#[from] serde_json::Error),
+
+    #[error("Error importing OpenSCAD file")]
+    Scad(#[from] import::scad::Error),
+
+    #[error("Error importing 2D profile")]
+    Profile(#[from] import::profile::Error),
+
+    #[error(
+        "Model was built for version {model} of the model interface, but the \
+        host application supports version {host}"
+    )]
+    AbiMismatch { model: u32, host: u32 },
+
+    #[error("Missing value for model parameter `{0}`")]
+    MissingParameter(String),
+
+    #[error("Invalid value `{value}` for model parameter `{name}`")]
+    InvalidParameter { name: String, value: String },
+
+    #[error(
+        "Value `{value}` for model parameter `{name}` is out of range \
+        `{range}`"
+    )]
+    ParameterOutOfRange {
+        name: String,
+        value: String,
+        range: String,
+    },
+}
+
+/// Metadata about a model
+///
+/// See [`fj::abi::Metadata`].
+#[derive(Clone, Debug)]
+pub struct Metadata {
+    /// The name of the model function
+    pub name: String,
+
+    /// The version of the `fj` crate that the model was built against
+    pub fj_version: String,
+
+    /// The parameters of the model
+    pub parameters: Vec<Parameter>,
+}
+
+impl Metadata {
+    /// Check the arguments against the model's parameters
+    ///
+    /// Makes sure that all required parameters have a value, and that the
+    /// values of parameters with a limited range are within that range. If no
+    /// value is passed for a parameter, its default value is checked instead.
+    pub fn check_arguments(
+        &self,
+        arguments: &HashMap<String, String>,
+    ) -> Result<(), Error> {
+        for parameter in &self.parameters {
+            let argument = arguments.get(&parameter.name);
+
+            // The evaluated default is used, if the model provides it, as the
+            // default as written in the source code might not parse.
+            if let (None, Some(default)) = (argument, parameter.default_value) {
+                parameter.check_number(default, default.to_string())?;
+                continue;
+            }
+
+            let value = match (argument, &parameter.default) {
+                (Some(value), _) | (None, Some(value)) => value,
+                (None, None) => {
+                    return Err(Error::MissingParameter(
+                        parameter.name.clone(),
+                    ));
+                }
+            };
+
+            parameter.check_range(value)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl From<&fj::abi::Metadata> for Metadata {
+    fn from(metadata: &fj::abi::Metadata) -> Self {
+        Self {
+            name: metadata.name.to_owned(),
+            fj_version: metadata.fj_version.to_owned(),
+            parameters: metadata.parameters.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// A parameter of a model
+///
+/// See [`fj::abi::Parameter`].
+#[derive(Clone, Debug)]
+pub struct Parameter {
+    /// The name of the parameter
+    pub name: String,
+
+    /// The type of the parameter, as written in the model's source code
+    pub ty: String,
+
+    /// The default value of the parameter, if it has one
+    pub default: Option<String>,
+
+    /// The default value of the parameter, evaluated as a number
+    ///
+    /// See [`fj::abi::Parameter::default_value`].
+    pub default_value: Option<f64>,
+
+    /// The minimum value of the parameter, if it is limited
+    pub min: Option<f64>,
+
+    /// The maximum value of the parameter, if it is limited
+    pub max: Option<f64>,
+
+    /// The increment by which the parameter is meant to be changed
+    pub step: Option<f64>,
+}
+
+impl Parameter {
+    /// The range of valid values, in Rust syntax
+    ///
+    /// Returns `None`, if the parameter isn't limited.
+    pub fn range(&self) -> Option<String> {
+        match (self.min, self.max) {
+            (None, None) => None,
+            (min, max) => Some(format!(
+                "{}..{}",
+                min.map(|min| min.to_string()).unwrap_or_default(),
+                max.map(|max| format!("={}", max)).unwrap_or_default(),
+            )),
+        }
+    }
+
+    /// Indicate whether the parameter has an integer type
+    pub fn is_integer(&self) -> bool {
+        const INTEGERS: &[&str] = &[
+            "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32",
+            "u64", "u128", "usize",
+        ];
+
+        INTEGERS.contains(&self.ty.as_str())
+    }
+
+    fn check_range(&self, value: &str) -> Result<(), Error> {
+        // Parameters without a range might not even be numeric.
+        if self.range().is_none() {
+            return Ok(());
+        }
+
+        let invalid = || Error::InvalidParameter {
+            name: self.name.clone(),
+            value: value.to_owned(),
+        };
+
+        // Integers are compared as integers, as large ones can't be
+        // represented exactly as `f64`.
+        if self.is_integer() {
+            let integer = value
+                .parse::<i128>()
+                .or_else(|_| value.parse::<u128>().map(|_| i128::MAX))
+                .map_err(|_| invalid())?;
+
+            return self.check_integer(integer, value.to_owned());
+        }
+
+        let number: f64 = value.parse().map_err(|_| invalid())?;
+
+        self.check_number(number, value.to_owned())
+    }
+
+    fn check_integer(&self, integer: i128, value: String) -> Result<(), Error> {
+        let range = match self.range() {
+            Some(range) => range,
+            None => return Ok(()),
+        };
+
+        // Rounding the limits towards the range makes the comparison exact,
+        // as there are no integers in between. Converting saturates.
+        let too_small =
+            matches!(self.min, Some(min) if integer < min.ceil() as i128);
+        let too_large =
+            matches!(self.max, Some(max) if integer > max.floor() as i128);
+        if too_small || too_large {
+            return Err(Error::ParameterOutOfRange {
+                name: self.name.clone(),
+                value,
+                range,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Check whether a number is within the range of the parameter
+    ///
+    /// `value` is the number as the user wrote it, which is used in the error.
+    pub fn check_number(
+        &self,
+        number: f64,
+        value: String,
+    ) -> Result<(), Error> {
+        let range = match self.range() {
+            Some(range) => range,
+            None => return Ok(()),
+        };
+
+        let too_small = matches!(self.min, Some(min) if number < min);
+        let too_large = matches!(self.max, Some(max) if number > max);
+        if too_small || too_large {
+            return Err(Error::ParameterOutOfRange {
+                name: self.name.clone(),
+                value,
+                range,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl From<&fj::abi::Parameter> for Parameter {
+    fn from(parameter: &fj::abi::Parameter) -> Self {
+        Self {
+            name: parameter.name.to_owned(),
+            ty: parameter.ty.to_owned(),
+            default: parameter.default.map(ToOwned::to_owned),
+            default_value: parameter.default_value,
+            min: parameter.min,
+            max: parameter.max,
+            step: parameter.step,
+        }
+    }
+}
+
+/// Read the metadata of a model from its library
+///
+/// The metadata is copied, so it stays valid after the library is unloaded.
+/// Returns `None`, if the model doesn't provide metadata.
+///
+/// # Safety
+///
+/// `lib` must be a model library. See [`Model::evaluate`].
+unsafe fn read_metadata(lib: &Library) -> Result<Option<Metadata>, Error> {
+    // Models with a hand-written model function don't provide any metadata.
+    let abi_version: Symbol<AbiVersionFn> = match lib.get(b"fj_abi_version") {
+        Ok(abi_version) => abi_version,
+        Err(_) => return Ok(None),
+    };
+
+    let version = abi_version();
+    if version != fj::abi::VERSION {
+        return Err(Error::AbiMismatch {
+            model: version,
+            host: fj::abi::VERSION,
+        });
+    }
+
+    let metadata: Symbol<MetadataFn> = lib.get(b"fj_metadata")?;
+    let metadata = Metadata::from(metadata());
+
+    if metadata.fj_version != fj::abi::FJ_VERSION {
+        warn!(
+            "Model was built against fj {}, host application uses fj {}",
+            metadata.fj_version,
+            fj::abi::FJ_VERSION,
+        );
+    }
+
+    Ok(Some(metadata))
 }
 
 type ModelFn =
     unsafe extern "C" fn(args: &HashMap<String, String>) -> fj::Shape;
+
+#[allow(improper_ctypes_definitions)]
+type CheckedModelFn = unsafe extern "C" fn(
+    args: &HashMap<String, String>,
+)
+    -> Result<fj::Shape, fj::ShapeError>;
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+
+#[allow(improper_ctypes_definitions)]
+type MetadataFn = unsafe extern "C" fn() -> &'static fj::abi::Metadata;
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, env, fs, path::Path};
+
+    use super::{Error, Metadata, Model, Parameter};
+
+    #[test]
+    fn new_should_accept_names_and_paths() {
+        let model = Model::new("spacer".to_owned());
+        assert_eq!(model.name(), "spacer");
+        assert_eq!(model.path(), "models/spacer");
+
+        let model = Model::new("../old/models/spacer/".to_owned());
+        assert_eq!(model.name(), "spacer");
+        assert_eq!(model.path(), "../old/models/spacer");
+        assert!(!model.is_description());
+
+        let model = Model::new("parts/plate.json".to_owned());
+        assert_eq!(model.name(), "plate");
+        assert!(model.is_description());
+        assert_eq!(model.src_path(), Path::new("parts"));
+        assert!(model.is_source(Path::new("/home/user/parts/plate.json")));
+        assert!(!model.is_source(Path::new("/home/user/parts/other.json")));
+
+        let model = Model::new("../bracket.scad".to_owned());
+        assert_eq!(model.name(), "bracket");
+        assert!(model.is_description());
+
+        let model = Model::new("./logo.svg".to_owned());
+        assert_eq!(model.name(), "logo");
+        assert!(model.is_description());
+    }
+
+    #[test]
+    fn descriptions_should_be_loaded() {
+        let path = env::temp_dir().join("fj-host-test-description.json");
+        fs::write(
+            &path,
+            r#"{
+                "Shape3d": {
+                    "Sweep": {
+                        "shape": {
+                            "Circle": { "center": [1.0, 0.0], "radius": 2.0 }
+                        },
+                        "length": 3.0
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let model = Model::new(path.to_string_lossy().into_owned());
+        let shape = model.load(&HashMap::new());
+        fs::remove_file(&path).unwrap();
+
+        match shape.unwrap() {
+            fj::Shape::Shape3d(fj::Shape3d::Sweep(sweep)) => {
+                assert_eq!(sweep.length, 3.);
+            }
+            shape => panic!("Unexpected shape: {:?}", shape),
+        }
+    }
+
+    #[test]
+    fn check_arguments_should_require_parameters_without_default() {
+        let parameter = |name: &str, default: Option<&str>| Parameter {
+            name: name.to_owned(),
+            ty: "f64".to_owned(),
+            default: default.map(ToOwned::to_owned),
+            default_value: None,
+            min: None,
+            max: None,
+            step: None,
+        };
+        let metadata = Metadata {
+            name: "model".to_owned(),
+            fj_version: fj::abi::FJ_VERSION.to_owned(),
+            parameters: vec![parameter("x", Some("1.0")), parameter("y", None)],
+        };
+
+        let mut arguments = HashMap::new();
+        assert!(matches!(
+            metadata.check_arguments(&arguments),
+            Err(Error::MissingParameter(name)) if name == "y"
+        ));
+
+        arguments.insert("y".to_owned(), "2.0".to_owned());
+        assert!(metadata.check_arguments(&arguments).is_ok());
+    }
+
+    #[test]
+    fn check_arguments_should_check_ranges() {
+        let metadata = Metadata {
+            name: "model".to_owned(),
+            fj_version: fj::abi::FJ_VERSION.to_owned(),
+            parameters: vec![Parameter {
+                name: "x".to_owned(),
+                ty: "f64".to_owned(),
+                default: Some("20.0".to_owned()),
+                default_value: Some(20.),
+                min: Some(1.),
+                max: Some(10.),
+                step: None,
+            }],
+        };
+        assert_eq!(metadata.parameters[0].range().unwrap(), "1..=10");
+
+        let check = |value: Option<&str>| {
+            let mut arguments = HashMap::new();
+            if let Some(value) = value {
+                arguments.insert("x".to_owned(), value.to_owned());
+            }
+            metadata.check_arguments(&arguments)
+        };
+
+        assert!(check(Some("5")).is_ok());
+        assert!(check(Some("10")).is_ok());
+        assert!(matches!(
+            check(Some("0.5")),
+            Err(Error::ParameterOutOfRange { .. })
+        ));
+        assert!(matches!(
+            check(Some("five")),
+            Err(Error::InvalidParameter { .. })
+        ));
+
+        // The default value is out of range too.
+        assert!(matches!(
+            check(None),
+            Err(Error::ParameterOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn check_arguments_should_check_integer_ranges_exactly() {
+        let metadata = Metadata {
+            name: "model".to_owned(),
+            fj_version: fj::abi::FJ_VERSION.to_owned(),
+            parameters: vec![Parameter {
+                name: "n".to_owned(),
+                ty: "u64".to_owned(),
+                default: None,
+                default_value: None,
+                min: None,
+                max: Some(9007199254740992.),
+                step: None,
+            }],
+        };
+
+        let check = |value: &str| {
+            let arguments = HashMap::from([("n".to_owned(), value.to_owned())]);
+            metadata.check_arguments(&arguments)
+        };
+
+        // Both numbers are the same, if converted to `f64`.
+        assert!(check("9007199254740992").is_ok());
+        assert!(matches!(
+            check("9007199254740993"),
+            Err(Error::ParameterOutOfRange { .. })
+        ));
+        assert!(matches!(
+            check("340282366920938463463374607431768211455"),
+            Err(Error::ParameterOutOfRange { .. })
+        ));
+        assert!(matches!(check("1.5"), Err(Error::InvalidParameter { .. })));
+    }
+
+    #[test]
+    fn check_arguments_should_check_evaluated_default() {
+        // This is how the `model` macro writes a default of `-1.0`.
+        let metadata = Metadata {
+            name: "model".to_owned(),
+            fj_version: fj::abi::FJ_VERSION.to_owned(),
+            parameters: vec![Parameter {
+                name: "x".to_owned(),
+                ty: "f64".to_owned(),
+                default: Some("- 1.0".to_owned()),
+                default_value: Some(-1.),
+                min: Some(-2.),
+                max: Some(0.),
+                step: None,
+            }],
+        };
+
+        assert!(metadata.check_arguments(&HashMap::new()).is_ok());
+    }
+}
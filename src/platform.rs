@@ -0,0 +1,107 @@
+//! Shims for functionality that differs between platforms
+//!
+//! Fornjot mainly targets desktop platforms, but most of the library can also
+//! be built for the web (`wasm32`). There, threads aren't available, and
+//! `std::time::Instant` panics. Code that relies on either goes through this
+//! module. On the web, work that would be spread across threads is done one
+//! item after another instead.
+//!
+//! Loading models from dynamic libraries (see `model`) is only available on
+//! native platforms. The same goes for watching model files for changes,
+//! which the host application does, for evaluating shapes on a server (see
+//! `remote`), and for exporting to 3MF.
+
+pub use instant::Instant;
+
+/// Call `f` for each row of an image
+///
+/// `f` receives the index of the row and its pixels. On native platforms, the
+/// rows are processed in parallel, so the order in which `f` is called for
+/// them is not specified.
+pub fn for_each_row<T>(
+    pixels: &mut [T],
+    width: usize,
+    f: impl Fn(usize, &mut [T]) + Send + Sync,
+) where
+    T: Send,
+{
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use rayon::prelude::*;
+
+        pixels
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| f(y, row));
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        pixels
+            .chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| f(y, row));
+    }
+}
+
+/// Call `f` for each item, and return the results in order
+///
+/// On native platforms, the items are processed in parallel, on Rayon's
+/// thread pool. Threads that run out of work steal it from the others, so a
+/// few expensive items don't leave the remaining threads idle.
+pub fn map<T, R>(items: Vec<T>, f: impl Fn(T) -> R + Send + Sync) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+{
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use rayon::prelude::*;
+
+        items.into_par_iter().map(f).collect()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        items.into_iter().map(f).collect()
+    }
+}
+
+/// Call `a` and `b`, and return both results
+///
+/// On native platforms, `a` and `b` run in parallel, on Rayon's thread pool.
+/// This allows running different analyses of the same shape at the same time
+/// (see [`Evaluation`]).
+///
+/// [`Evaluation`]: crate::evaluation::Evaluation
+pub fn join<A, B>(
+    a: impl FnOnce() -> A + Send,
+    b: impl FnOnce() -> B + Send,
+) -> (A, B)
+where
+    A: Send,
+    B: Send,
+{
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        rayon::join(a, b)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        (a(), b())
+    }
+}
+
+/// The number of threads that [`map`] distributes items across
+pub fn num_threads() -> usize {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        rayon::current_num_threads()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        1
+    }
+}
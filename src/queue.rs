@@ -0,0 +1,278 @@
+//! A queue of evaluation jobs, ordered by priority
+//!
+//! Evaluating a shape can take a while, so requests pile up, when a model
+//! changes quickly, for example because a parameter is tweaked, or a file is
+//! saved repeatedly. Most of those requests are outdated before they are
+//! processed. A [`Queue`] drops them: each job can have a key, and a newer
+//! job with the same key supersedes a pending one.
+//!
+//! Jobs are taken from the queue by [`Priority`], and in the order in which
+//! they arrived within each priority, so the model that the user is looking at
+//! doesn't wait for background work.
+
+use std::{
+    cmp::Reverse,
+    mem,
+    sync::{Condvar, Mutex, MutexGuard},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The priority of a job in a [`Queue`]
+#[derive(
+    Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize,
+)]
+pub enum Priority {
+    /// Work that nobody is waiting for, like exporting or pre-computing
+    Background,
+
+    /// Work that results in the model that is currently visible
+    Visible,
+}
+
+impl Default for Priority {
+    fn default() -> Self {
+        Self::Visible
+    }
+}
+
+/// A queue of jobs, that can be shared between threads
+///
+/// Producers add jobs using [`Queue::push`], while workers take them using
+/// [`Queue::pop`]. Once the queue is closed, workers get the remaining jobs,
+/// then `None`.
+#[derive(Debug)]
+pub struct Queue<T> {
+    state: Mutex<State<T>>,
+    pushed: Condvar,
+}
+
+impl<T> Queue<T> {
+    /// Create an empty queue
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(State {
+                jobs: Vec::new(),
+                next_sequence: 0,
+                closed: false,
+            }),
+            pushed: Condvar::new(),
+        }
+    }
+
+    /// Add a job to the queue
+    ///
+    /// If a pending job has the same key, it is removed from the queue, and
+    /// returned, so whoever waits for its result can be told that it was
+    /// superseded. Jobs without a key never supersede each other.
+    pub fn push(
+        &self,
+        priority: Priority,
+        key: Option<String>,
+        item: T,
+    ) -> Option<T> {
+        let mut state = self.lock();
+
+        let superseded = key
+            .as_ref()
+            .and_then(|key| {
+                state
+                    .jobs
+                    .iter()
+                    .position(|job| job.key.as_ref() == Some(key))
+            })
+            .map(|i| state.jobs.remove(i).item);
+
+        let sequence = state.next_sequence;
+        state.next_sequence += 1;
+        state.jobs.push(Job {
+            priority,
+            key,
+            sequence,
+            item,
+        });
+
+        drop(state);
+        self.pushed.notify_all();
+
+        superseded
+    }
+
+    /// Take the next job with at least the given priority, if there is one
+    pub fn try_pop(&self, min_priority: Priority) -> Option<T> {
+        self.lock().pop(min_priority)
+    }
+
+    /// Wait for the next job with at least the given priority, and take it
+    ///
+    /// Returns `None`, once the queue is closed and has no such jobs left.
+    pub fn pop(&self, min_priority: Priority) -> Option<T> {
+        let mut state = self.lock();
+
+        loop {
+            if let Some(item) = state.pop(min_priority) {
+                return Some(item);
+            }
+            if state.closed {
+                return None;
+            }
+
+            state = self
+                .pushed
+                .wait(state)
+                .expect("Thread panicked while holding lock");
+        }
+    }
+
+    /// Remove all pending jobs for which `f` returns `true`, and return them
+    ///
+    /// This is used to drop the jobs of a client that went away, as nobody is
+    /// waiting for their results anymore.
+    pub fn remove(&self, mut f: impl FnMut(&T) -> bool) -> Vec<T> {
+        let mut state = self.lock();
+
+        let (removed, kept): (Vec<_>, Vec<_>) = mem::take(&mut state.jobs)
+            .into_iter()
+            .partition(|job| f(&job.item));
+        state.jobs = kept;
+
+        removed.into_iter().map(|job| job.item).collect()
+    }
+
+    /// Close the queue, which makes [`Queue::pop`] return once it's empty
+    ///
+    /// Jobs can still be added after that, but workers might not be there to
+    /// take them anymore.
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.pushed.notify_all();
+    }
+
+    /// The number of pending jobs
+    pub fn len(&self) -> usize {
+        self.lock().jobs.len()
+    }
+
+    /// Indicate whether there are no pending jobs
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> MutexGuard<State<T>> {
+        self.state
+            .lock()
+            .expect("Thread panicked while holding lock")
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug)]
+struct State<T> {
+    jobs: Vec<Job<T>>,
+    next_sequence: u64,
+    closed: bool,
+}
+
+impl<T> State<T> {
+    fn pop(&mut self, min_priority: Priority) -> Option<T> {
+        // There are rarely more than a handful of jobs, so searching them is
+        // cheaper than keeping them sorted.
+        let (i, _) = self
+            .jobs
+            .iter()
+            .enumerate()
+            .filter(|(_, job)| job.priority >= min_priority)
+            .min_by_key(|(_, job)| (Reverse(job.priority), job.sequence))?;
+
+        Some(self.jobs.remove(i).item)
+    }
+}
+
+#[derive(Debug)]
+struct Job<T> {
+    priority: Priority,
+    key: Option<String>,
+    sequence: u64,
+    item: T,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread};
+
+    use super::{Priority, Queue};
+
+    #[test]
+    fn queue_should_prioritize_and_drop_superseded_jobs() {
+        let queue = Queue::new();
+
+        assert_eq!(queue.push(Priority::Background, None, "export"), None);
+        assert_eq!(
+            queue.push(Priority::Background, Some("b".into()), "b1"),
+            None
+        );
+        assert_eq!(queue.push(Priority::Visible, Some("v".into()), "v1"), None);
+        assert_eq!(
+            queue.push(Priority::Visible, Some("v".into()), "v2"),
+            Some("v1")
+        );
+        assert_eq!(queue.push(Priority::Visible, None, "other"), None);
+        assert_eq!(
+            queue.push(Priority::Background, Some("b".into()), "b2"),
+            Some("b1")
+        );
+        assert_eq!(queue.len(), 4);
+
+        // Workers that only take visible work leave the rest alone.
+        assert_eq!(queue.try_pop(Priority::Visible), Some("v2"));
+        assert_eq!(queue.try_pop(Priority::Visible), Some("other"));
+        assert_eq!(queue.try_pop(Priority::Visible), None);
+
+        assert_eq!(queue.try_pop(Priority::Background), Some("export"));
+        assert_eq!(queue.try_pop(Priority::Background), Some("b2"));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn remove_should_only_remove_matching_jobs() {
+        let queue = Queue::new();
+
+        queue.push(Priority::Visible, None, ("a", 1));
+        queue.push(Priority::Background, None, ("b", 2));
+        queue.push(Priority::Background, None, ("a", 3));
+
+        assert_eq!(
+            queue.remove(|&(client, _)| client == "a"),
+            [("a", 1), ("a", 3)]
+        );
+        assert_eq!(queue.try_pop(Priority::Background), Some(("b", 2)));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn pop_should_wait_until_pushed_or_closed() {
+        let queue = Arc::new(Queue::new());
+
+        let worker = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                let mut items = Vec::new();
+                while let Some(item) = queue.pop(Priority::Background) {
+                    items.push(item);
+                }
+                items
+            })
+        };
+
+        queue.push(Priority::Visible, None, 1);
+        queue.push(Priority::Visible, None, 2);
+        queue.close();
+
+        assert_eq!(worker.join().unwrap(), [1, 2]);
+    }
+}
@@ -0,0 +1,30 @@
+//! Generation of random numbers
+
+/// A small, fast, non-cryptographic random number generator
+///
+/// Implements xorshift64*. Rendering and stack-up analysis need a lot of random
+/// numbers, but their quality is not very important.
+pub struct Rng(u64);
+
+impl Rng {
+    /// Create a random number generator from a seed
+    ///
+    /// The same seed always results in the same sequence of numbers.
+    pub fn new(seed: u64) -> Self {
+        // The state must never be zero. Scrambling the seed also makes sure
+        // that similar seeds result in different sequences.
+        Self(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    /// Return a random number in the range `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+
+        let bits = self.0.wrapping_mul(0x2545_f491_4f6c_dd1d);
+
+        // Use the upper 53 bits, which is what fits into the mantissa.
+        (bits >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
@@ -0,0 +1,789 @@
+//! Evaluating shapes on a remote server
+//!
+//! Building and triangulating a complex shape can take a while. A [`Server`]
+//! does that work for other instances of the host application, which connect
+//! to it using a [`Client`]. This way, a heavy model can be computed on a
+//! workstation, while it is previewed on a laptop.
+//!
+//! Client and server exchange JSON messages over TCP, one message per line.
+//! For each [`Request`] the client sends, the server answers with a
+//! [`Response`]. A request either contains the shape itself, or refers to a
+//! model on the server, which the server then builds and evaluates.
+//!
+//! Requests are processed in the order of their [`Priority`] (see [`queue`]).
+//! Clients can send further requests, before the responses to earlier ones
+//! have arrived. The responses still arrive in the order of the requests. If a
+//! request has the same key as an earlier one that is still waiting to be
+//! processed, the earlier one is dropped, and answered with
+//! [`Response::Superseded`].
+//!
+//! There is no authentication, and the server builds any model it is asked to.
+//! Only make it reachable from trusted networks.
+//!
+//! [`queue`]: crate::queue
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead as _, BufReader, Write as _},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc},
+    thread,
+    time::Instant,
+};
+
+use parry3d_f64::query::Ray;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::{
+    debug::{DebugInfo, TriangleEdgeCheck},
+    evaluation::{ProcessedShape, ShapeProcessor},
+    kernel::{
+        algorithms::{
+            arena,
+            tolerance::{InvalidTolerance, Tolerance},
+        },
+        shape::{Shape, ValidationError},
+        topology::vertices::Vertex,
+    },
+    math::{Aabb, Point, Scalar, Segment, Triangle},
+    mesh::FaceGroups,
+    model::Model,
+    platform,
+    queue::{Priority, Queue},
+};
+
+/// A request to process a shape, sent from the client to the server
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Request {
+    /// The shape to process
+    pub source: Source,
+
+    /// The tolerance to triangulate the shape with
+    ///
+    /// If this is `None`, the default tolerance for the shape is used (see
+    /// [`Tolerance::from_bounding_box`]).
+    pub tolerance: Option<f64>,
+
+    /// Whether to approximate the edges of the shape too
+    ///
+    /// See [`ShapeProcessor::edges`].
+    pub edges: bool,
+
+    /// The priority of the request
+    ///
+    /// Defaults to [`Priority::Visible`], if the request doesn't specify it.
+    #[serde(default)]
+    pub priority: Priority,
+
+    /// Identifies what the request is for, like a frame of a model
+    ///
+    /// A request that is waiting to be processed is superseded by a newer one
+    /// from the same client, that has the same key. Requests without a key
+    /// are never superseded.
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+/// The shape that a [`Request`] refers to
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Source {
+    /// A shape that the client has already evaluated
+    Shape(fj::Shape),
+
+    /// A model in the `models/` directory of the server
+    ///
+    /// Only plain model names are accepted, not paths, so clients can't make
+    /// the server build code from anywhere else.
+    Model {
+        name: String,
+        parameters: HashMap<String, String>,
+    },
+}
+
+/// The answer to a [`Request`], sent from the server to the client
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Response {
+    /// The shape was processed successfully
+    Mesh(Mesh),
+
+    /// The shape couldn't be processed
+    ///
+    /// Contains the error, followed by the errors that caused it.
+    Error(Vec<String>),
+
+    /// The request was superseded by a newer one, before it was processed
+    Superseded,
+}
+
+/// A processed shape, in a form that can be sent over the network
+///
+/// Contains everything from [`ProcessedShape`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Mesh {
+    pub tolerance: f64,
+    pub aabb: [[f64; 3]; 2],
+    pub triangles: Vec<[[f64; 3]; 3]>,
+
+    /// The number of triangles of each face
+    pub faces: Vec<usize>,
+
+    pub edges: Vec<[[f64; 3]; 2]>,
+
+    /// How long the server took to process the shape, in seconds
+    pub duration: f64,
+
+    /// The checks that were made while triangulating the shape
+    ///
+    /// See [`DebugInfo::triangle_edge_checks`].
+    #[serde(default)]
+    pub triangle_edge_checks: Vec<EdgeCheck>,
+
+    /// The problems that were found in the shape, but were not fatal
+    ///
+    /// See [`DebugInfo::validation_errors`].
+    #[serde(default)]
+    pub validation_warnings: Vec<Warning>,
+}
+
+/// A [`TriangleEdgeCheck`], in a form that can be sent over the network
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EdgeCheck {
+    pub origin: [f64; 3],
+    pub dir: [f64; 3],
+    pub hits: Vec<f64>,
+}
+
+/// A validation warning, in a form that can be sent over the network
+///
+/// Mirrors [`ValidationError`], with positions in place of the objects.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Warning {
+    Structural,
+
+    /// Contains the position of the existing vertex
+    Uniqueness([f64; 3]),
+
+    Geometric,
+    NotClosed(Vec<[f64; 3]>),
+}
+
+impl Warning {
+    fn new(error: &ValidationError<Vertex>) -> Self {
+        match error {
+            ValidationError::Structural(()) => Self::Structural,
+            ValidationError::Uniqueness(existing) => {
+                Self::Uniqueness(existing.point().into())
+            }
+            ValidationError::Geometric => Self::Geometric,
+            ValidationError::NotClosed(positions) => Self::NotClosed(
+                positions.iter().map(|&point| point.into()).collect(),
+            ),
+        }
+    }
+
+    /// Convert the warning back into a validation error
+    ///
+    /// The vertices that the error refers to are added to `shape`.
+    fn into_error(self, shape: &mut Shape) -> ValidationError<Vertex> {
+        match self {
+            Self::Structural => ValidationError::Structural(()),
+            Self::Uniqueness(position) => {
+                let point = shape.geometry().add_point(Point::from(position));
+
+                // The vertex is a duplicate, which is only a warning, so
+                // adding it can't fail.
+                let vertex = shape
+                    .topology()
+                    .add_vertex(Vertex { point })
+                    .expect("Adding vertex with existing point failed");
+
+                ValidationError::Uniqueness(vertex)
+            }
+            Self::Geometric => ValidationError::Geometric,
+            Self::NotClosed(positions) => ValidationError::NotClosed(
+                positions.into_iter().map(Point::from).collect(),
+            ),
+        }
+    }
+}
+
+impl Mesh {
+    /// Convert a processed shape, that took `duration` seconds to process
+    pub fn new(shape: &ProcessedShape, duration: f64) -> Self {
+        Self {
+            tolerance: shape.tolerance.inner().into_f64(),
+            aabb: [shape.aabb.min.into(), shape.aabb.max.into()],
+            triangles: shape
+                .triangles
+                .iter()
+                .map(|triangle| triangle.points().map(Into::into))
+                .collect(),
+            faces: shape.faces.iter().map(|range| range.len()).collect(),
+            edges: shape
+                .edges
+                .iter()
+                .map(|edge| edge.points().map(Into::into))
+                .collect(),
+            duration,
+            triangle_edge_checks: shape
+                .debug_info
+                .triangle_edge_checks
+                .iter()
+                .map(|check| EdgeCheck {
+                    origin: check.ray.origin.into(),
+                    dir: check.ray.dir.into(),
+                    hits: check.hits.to_vec(),
+                })
+                .collect(),
+            validation_warnings: shape
+                .debug_info
+                .validation_errors
+                .iter()
+                .map(Warning::new)
+                .collect(),
+        }
+    }
+
+    /// Convert the mesh back into a processed shape
+    ///
+    /// The mesh comes from the network, so it is checked first. Returns an
+    /// error, if it has an invalid tolerance, degenerate triangles or edges,
+    /// or faces that don't add up to its triangles.
+    pub fn into_processed(self) -> Result<ProcessedShape, InvalidMesh> {
+        let num_triangles: usize = self.faces.iter().sum();
+        if num_triangles != self.triangles.len() {
+            return Err(InvalidMesh::Faces {
+                faces: num_triangles,
+                triangles: self.triangles.len(),
+            });
+        }
+
+        let mut faces = FaceGroups::new();
+        for num_triangles in self.faces {
+            faces.push(num_triangles);
+        }
+
+        let [min, max] = self.aabb.map(Point::from);
+
+        let triangles = self
+            .triangles
+            .into_iter()
+            .enumerate()
+            .map(|(i, triangle)| {
+                let [a, b, c] = triangle.map(Point::from);
+                let area = (b - a).cross(&(c - a)).magnitude();
+                if area == Scalar::ZERO {
+                    return Err(InvalidMesh::DegenerateTriangle(i));
+                }
+
+                Ok(Triangle::from([a, b, c]))
+            })
+            .collect::<Result<_, _>>()?;
+        let edges = self
+            .edges
+            .into_iter()
+            .enumerate()
+            .map(|(i, [a, b])| {
+                if a == b {
+                    return Err(InvalidMesh::DegenerateEdge(i));
+                }
+
+                Ok(Segment::from([a, b].map(Point::from)))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut debug_info = DebugInfo::new();
+        debug_info.triangle_edge_checks.extend(
+            self.triangle_edge_checks.into_iter().map(|check| {
+                let ray = Ray::new(check.origin.into(), check.dir.into());
+                TriangleEdgeCheck {
+                    ray,
+                    hits: check.hits.into(),
+                }
+            }),
+        );
+
+        // The errors refer to vertices, which need a shape to live in.
+        let mut shape = Shape::new();
+        debug_info.validation_errors.extend(
+            self.validation_warnings
+                .into_iter()
+                .map(|warning| warning.into_error(&mut shape)),
+        );
+
+        Ok(ProcessedShape {
+            tolerance: Tolerance::from_scalar(self.tolerance)?,
+            aabb: Aabb { min, max },
+            triangles,
+            faces,
+            edges,
+            debug_info,
+        })
+    }
+}
+
+/// Returned by [`Mesh::into_processed`], if the mesh is invalid
+#[derive(Debug, thiserror::Error)]
+pub enum InvalidMesh {
+    #[error(transparent)]
+    Tolerance(#[from] InvalidTolerance),
+
+    #[error("Triangle {0} is degenerate")]
+    DegenerateTriangle(usize),
+
+    #[error("Edge {0} is degenerate")]
+    DegenerateEdge(usize),
+
+    #[error("Faces have {faces} triangles, but mesh has {triangles}")]
+    Faces { faces: usize, triangles: usize },
+}
+
+/// Process a request, as the server does
+///
+/// A panic while processing the shape is caught, and answered with
+/// [`Response::Error`], so it can't take down the server.
+pub fn process(request: &Request) -> Response {
+    let start = Instant::now();
+
+    let result = || -> anyhow::Result<ProcessedShape> {
+        let shape = match &request.source {
+            Source::Shape(shape) => {
+                // The server can't trust its clients to check their shapes.
+                shape.check()?;
+                shape.clone()
+            }
+            Source::Model { name, parameters } => {
+                if name.contains(&['/', '\\'][..]) || name.starts_with('.') {
+                    anyhow::bail!("Invalid model name `{}`", name);
+                }
+                Model::new(name.clone()).load(parameters)?
+            }
+        };
+
+        let processor = ShapeProcessor {
+            tolerance: request
+                .tolerance
+                .map(Tolerance::from_scalar)
+                .transpose()?,
+            edges: request.edges,
+        };
+
+        Ok(processor.process(&shape)?)
+    };
+
+    let result = panic::catch_unwind(AssertUnwindSafe(result)).unwrap_or_else(
+        |payload| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|&message| message.to_owned())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown cause".to_owned());
+
+            Err(anyhow::anyhow!("Panic while processing shape: {}", message))
+        },
+    );
+
+    // The server keeps running, so the memory that was needed while
+    // processing the shape is released.
+    arena::reset();
+
+    match result {
+        Ok(shape) => {
+            Response::Mesh(Mesh::new(&shape, start.elapsed().as_secs_f64()))
+        }
+        Err(err) => {
+            Response::Error(err.chain().map(ToString::to_string).collect())
+        }
+    }
+}
+
+/// A server that processes shapes for clients
+///
+/// Requests from all clients are put into one [`Queue`], from which a pool of
+/// workers takes them. There is one worker per thread that
+/// [`platform::map`] uses, which only processes requests with
+/// [`Priority::Visible`], plus one worker that also processes those with
+/// [`Priority::Background`]. This way, a request for a visible model never
+/// has to wait for background work to finish.
+pub struct Server {
+    listener: TcpListener,
+}
+
+/// A request that is waiting to be processed by a [`Server`]
+struct Job {
+    /// The client that sent the request
+    client: SocketAddr,
+
+    request: Request,
+    response: mpsc::Sender<Response>,
+}
+
+impl Server {
+    /// Create a server that listens on the given address
+    pub fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        Ok(Self { listener })
+    }
+
+    /// Access the address that the server listens on
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serve clients, until accepting a connection fails
+    ///
+    /// Each client is served on its own thread, so multiple clients can be
+    /// connected at the same time.
+    pub fn run(self) -> io::Result<()> {
+        let queue = Arc::new(Queue::new());
+
+        let visible = (0..platform::num_threads()).map(|_| Priority::Visible);
+        for min_priority in visible.chain([Priority::Background]) {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                while let Some(job) = queue.pop(min_priority) {
+                    let Job {
+                        request, response, ..
+                    } = job;
+
+                    // The client might have disconnected in the meantime.
+                    let _ = response.send(process(&request));
+                }
+            });
+        }
+
+        let result = self.accept(&queue);
+
+        // Let the workers finish the requests they have, then exit.
+        queue.close();
+        result
+    }
+
+    fn accept(&self, queue: &Arc<Queue<Job>>) -> io::Result<()> {
+        loop {
+            let (stream, address) = self.listener.accept()?;
+            info!("Client connected: {}", address);
+
+            let queue = queue.clone();
+            thread::spawn(move || match serve_client(stream, &queue) {
+                Ok(()) => info!("Client disconnected: {}", address),
+                Err(err) => warn!("Error serving client {}: {}", address, err),
+            });
+        }
+    }
+}
+
+fn serve_client(stream: TcpStream, queue: &Queue<Job>) -> io::Result<()> {
+    let address = stream.peer_addr()?;
+    let mut writer = stream.try_clone()?;
+
+    // Responses are written on their own thread, in the order of the
+    // requests. This way, the next request can be read and queued, while the
+    // previous one is still being processed, and supersede it.
+    let (pending_tx, pending_rx) = mpsc::channel::<mpsc::Receiver<_>>();
+    let responses = thread::spawn(move || -> io::Result<()> {
+        for pending in pending_rx {
+            let response = pending.recv().unwrap_or_else(|_| {
+                // The request was dropped without an answer. This only
+                // happens, if the client disconnected, in which case writing
+                // the response is going to fail anyway.
+                Response::Error(vec!["Failed to process request".to_owned()])
+            });
+
+            serde_json::to_writer(&mut writer, &response)?;
+            writer.write_all(b"\n")?;
+            writer.flush()?;
+        }
+
+        Ok(())
+    });
+
+    let read = read_requests(stream, address, queue, pending_tx);
+
+    // The client is gone, or can't receive responses anymore. Its requests
+    // don't need to be processed.
+    let dropped = queue.remove(|job| job.client == address);
+    if !dropped.is_empty() {
+        info!(
+            "Dropped {} pending requests from {}",
+            dropped.len(),
+            address
+        );
+    }
+
+    let written = responses.join().expect("Thread writing responses panicked");
+    read.and(written)
+}
+
+/// Read requests from a client, and queue them, until it disconnects
+///
+/// For each request, the receiver for its response is sent to `pending_tx`.
+fn read_requests(
+    stream: TcpStream,
+    address: SocketAddr,
+    queue: &Queue<Job>,
+    pending_tx: mpsc::Sender<mpsc::Receiver<Response>>,
+) -> io::Result<()> {
+    for line in BufReader::new(stream).lines() {
+        let (response, pending) = mpsc::channel();
+
+        match serde_json::from_str::<Request>(&line?) {
+            Ok(request) => {
+                // Keys only apply to requests from the same client.
+                let priority = request.priority;
+                let key = request
+                    .key
+                    .as_ref()
+                    .map(|key| format!("{} {}", address, key));
+
+                let job = Job {
+                    client: address,
+                    request,
+                    response,
+                };
+
+                let superseded = queue.push(priority, key, job);
+                if let Some(superseded) = superseded {
+                    let _ = superseded.response.send(Response::Superseded);
+                }
+            }
+            Err(err) => {
+                let _ = response.send(Response::Error(vec![format!(
+                    "Invalid request: {}",
+                    err
+                )]));
+            }
+        }
+
+        if pending_tx.send(pending).is_err() {
+            // Writing a response failed. The error is returned by the thread
+            // that writes the responses.
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// A connection to a [`Server`]
+pub struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Client {
+    /// Connect to the server at the given address
+    pub fn connect(address: impl ToSocketAddrs) -> Result<Self, Error> {
+        let writer = TcpStream::connect(address)?;
+        let reader = BufReader::new(writer.try_clone()?);
+
+        Ok(Self { reader, writer })
+    }
+
+    /// Send a request to the server, and wait for the processed shape
+    pub fn process(
+        &mut self,
+        request: &Request,
+    ) -> Result<ProcessedShape, Error> {
+        self.send(request)?;
+        self.receive()
+    }
+
+    /// Send a request to the server, without waiting for the response
+    ///
+    /// Use [`Client::receive`] to receive the responses, which arrive in the
+    /// order of the requests.
+    pub fn send(&mut self, request: &Request) -> Result<(), Error> {
+        serde_json::to_writer(&mut self.writer, request)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Wait for the response to the oldest request that hasn't been answered
+    pub fn receive(&mut self) -> Result<ProcessedShape, Error> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(Error::Disconnected);
+        }
+
+        match serde_json::from_str(&line)? {
+            Response::Mesh(mesh) => {
+                info!("Shape processed by server in {:.2}s", mesh.duration);
+                Ok(mesh.into_processed()?)
+            }
+            Response::Error(errors) => Err(Error::Remote(errors.join(": "))),
+            Response::Superseded => Err(Error::Superseded),
+        }
+    }
+}
+
+/// Returned by [`Client`], if processing a shape on the server failed
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("I/O error communicating with server")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid message from server")]
+    Protocol(#[from] serde_json::Error),
+
+    #[error("Server returned invalid mesh")]
+    InvalidMesh(#[from] InvalidMesh),
+
+    #[error("Server closed the connection")]
+    Disconnected,
+
+    #[error("Server failed to process shape: {0}")]
+    Remote(String),
+
+    #[error("Request was superseded by a newer one")]
+    Superseded,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, thread};
+
+    use crate::{
+        evaluation::ShapeProcessor, math::Point, queue::Priority,
+        testing::cuboid,
+    };
+
+    use super::{
+        Client, EdgeCheck, Error, InvalidMesh, Mesh, Request, Server, Source,
+        Warning,
+    };
+
+    #[test]
+    fn client_should_receive_processed_shape() {
+        let server = Server::bind("127.0.0.1:0").unwrap();
+        let address = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+
+        let cube: fj::Shape = cuboid([1., 1., 1.]).into();
+
+        let mut client = Client::connect(address).unwrap();
+        let remote = client
+            .process(&Request {
+                source: Source::Shape(cube.clone()),
+                tolerance: None,
+                edges: true,
+                priority: Priority::Visible,
+                key: None,
+            })
+            .unwrap();
+
+        let local = ShapeProcessor {
+            edges: true,
+            ..ShapeProcessor::new()
+        }
+        .process(&cube)
+        .unwrap();
+
+        // The triangulation isn't deterministic, so the triangles themselves
+        // can differ between both.
+        assert_eq!(remote.tolerance, local.tolerance);
+        assert_eq!(remote.aabb, local.aabb);
+        assert_eq!(remote.triangles.len(), local.triangles.len());
+        assert_eq!(remote.faces.len(), local.faces.len());
+        assert_eq!(remote.edges.len(), local.edges.len());
+        assert_eq!(remote.volume(), local.volume());
+
+        // Errors are reported, and the connection stays usable.
+        let result = client.process(&Request {
+            source: Source::Model {
+                name: "../secret".to_owned(),
+                parameters: HashMap::new(),
+            },
+            tolerance: None,
+            edges: false,
+            priority: Priority::Visible,
+            key: None,
+        });
+        assert!(matches!(result, Err(Error::Remote(_))));
+
+        let result = client.process(&Request {
+            source: Source::Shape(cube.clone()),
+            tolerance: Some(0.),
+            edges: false,
+            priority: Priority::Visible,
+            key: None,
+        });
+        assert!(matches!(result, Err(Error::Remote(_))));
+
+        // Requests can be sent before earlier ones are answered. Whether the
+        // first one is superseded depends on whether a worker took it already,
+        // but the last one is always processed.
+        let request = |tolerance| Request {
+            source: Source::Shape(cube.clone()),
+            tolerance: Some(tolerance),
+            edges: false,
+            priority: Priority::Background,
+            key: Some("cube".to_owned()),
+        };
+        client.send(&request(0.1)).unwrap();
+        client.send(&request(0.2)).unwrap();
+
+        match client.receive() {
+            Ok(first) => assert_eq!(first.tolerance.inner().into_f64(), 0.1),
+            Err(err) => assert!(matches!(err, Error::Superseded)),
+        }
+        let second = client.receive().unwrap();
+        assert_eq!(second.tolerance.inner().into_f64(), 0.2);
+    }
+
+    #[test]
+    fn into_processed_should_check_mesh_and_keep_debug_info() {
+        let mesh = || Mesh {
+            tolerance: 0.1,
+            aabb: [[0., 0., 0.], [1., 1., 0.]],
+            triangles: vec![[[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]],
+            faces: vec![1],
+            edges: vec![[[0., 0., 0.], [1., 0., 0.]]],
+            duration: 0.,
+            triangle_edge_checks: vec![EdgeCheck {
+                origin: [0., 0., 0.],
+                dir: [1., 0., 0.],
+                hits: vec![0.5],
+            }],
+            validation_warnings: vec![Warning::Uniqueness([1., 0., 0.])],
+        };
+
+        let processed = mesh().into_processed().unwrap();
+        let debug_info = &processed.debug_info;
+        assert_eq!(debug_info.triangle_edge_checks[0].hits.as_slice(), [0.5]);
+        let positions: Vec<_> = debug_info
+            .validation_errors
+            .iter()
+            .flat_map(|error| error.positions())
+            .collect();
+        assert_eq!(positions, [Point::from([1., 0., 0.])]);
+
+        let mut degenerate = mesh();
+        degenerate.triangles[0][2] = [2., 0., 0.];
+        assert!(matches!(
+            degenerate.into_processed(),
+            Err(InvalidMesh::DegenerateTriangle(0))
+        ));
+
+        let mut degenerate = mesh();
+        degenerate.edges[0][1] = [0., 0., 0.];
+        assert!(matches!(
+            degenerate.into_processed(),
+            Err(InvalidMesh::DegenerateEdge(0))
+        ));
+
+        let mut faces = mesh();
+        faces.faces = vec![2];
+        assert!(matches!(
+            faces.into_processed(),
+            Err(InvalidMesh::Faces {
+                faces: 2,
+                triangles: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,517 @@
+//! Offline rendering of models
+//!
+//! In contrast to [`crate::graphics`], which renders models in real time on
+//! the GPU, this module renders still images on the CPU, using a simple path
+//! tracer. This is much slower, but results in soft shadows and indirect
+//! lighting, which makes the images suitable for documentation and
+//! publications.
+
+pub mod png;
+
+mod sampling;
+
+use std::{cmp::Reverse, f64::consts::PI, num::NonZeroU32};
+
+use nalgebra::{vector, Point3, Vector3};
+use parry3d_f64::{
+    query::{Ray, RayCast as _},
+    shape::{FeatureId, TriMesh},
+};
+
+use crate::{
+    math::{Aabb, Scalar, Triangle},
+    mesh::{FaceGroups, IndexOverflow, MeshMaker},
+    platform,
+    random::Rng,
+};
+
+/// Render the given triangles into an image
+///
+/// The triangles are placed on an infinite ground plane, which touches the
+/// bottom of their bounding box, and are lit by a sky and a sun.
+///
+/// `triangles` must not be empty. Returns an error, if there are too many
+/// vertices to render.
+pub fn render(
+    triangles: &[Triangle<3>],
+    camera: &Camera,
+    settings: &Settings,
+) -> Result<Image, IndexOverflow> {
+    let scene = Scene::new(triangles, camera)?;
+
+    let width = settings.width.get() as usize;
+    let height = settings.height.get() as usize;
+
+    let mut pixels = vec![[0; 3]; width * height];
+    platform::for_each_row(&mut pixels, width, |y, row| {
+        // Seed per row, so the result doesn't depend on how rows are
+        // distributed over threads.
+        let mut rng = Rng::new(y as u64);
+
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let mut radiance = Vector3::zeros();
+
+            for _ in 0..settings.samples.get() {
+                let u = (x as f64 + rng.next_f64()) / width as f64;
+                let v = (y as f64 + rng.next_f64()) / height as f64;
+
+                let ray = camera.ray(u, v, width as f64 / height as f64);
+                radiance += scene.trace(ray, &mut rng);
+            }
+
+            radiance /= settings.samples.get() as f64;
+            *pixel = tone_map(radiance);
+        }
+    });
+
+    Ok(Image {
+        width: settings.width.get(),
+        height: settings.height.get(),
+        pixels,
+    })
+}
+
+/// Render an image of which face of the model is visible at each pixel
+///
+/// Uses the same camera and settings as [`render`], so the result lines up
+/// with the rendered image. External tools can use it to find the face under
+/// the mouse cursor, for example, or to highlight a face in the image. Each
+/// pixel is sampled `settings.samples` times, like in [`render`], which
+/// results in smooth edges: the pixel stores the face that covers most of it,
+/// and how much of the pixel that face covers.
+///
+/// `faces` assigns the triangles to faces. `triangles` must not be empty.
+/// Returns an error, if there are too many vertices to render.
+pub fn render_face_ids(
+    triangles: &[Triangle<3>],
+    faces: &FaceGroups,
+    camera: &Camera,
+    settings: &Settings,
+) -> Result<FaceIds, IndexOverflow> {
+    let scene = Scene::new(triangles, camera)?;
+
+    let width = settings.width.get() as usize;
+    let height = settings.height.get() as usize;
+
+    let mut pixels = vec![FacePixel::default(); width * height];
+    platform::for_each_row(&mut pixels, width, |y, row| {
+        let mut rng = Rng::new(y as u64);
+        let mut counts: Vec<(usize, u32)> = Vec::new();
+
+        for (x, pixel) in row.iter_mut().enumerate() {
+            counts.clear();
+
+            for _ in 0..settings.samples.get() {
+                let u = (x as f64 + rng.next_f64()) / width as f64;
+                let v = (y as f64 + rng.next_f64()) / height as f64;
+
+                let ray = camera.ray(u, v, width as f64 / height as f64);
+                let face = scene
+                    .triangle(&ray)
+                    .and_then(|triangle| faces.face_of(triangle));
+
+                if let Some(face) = face {
+                    match counts.iter_mut().find(|(f, _)| *f == face) {
+                        Some((_, count)) => *count += 1,
+                        None => counts.push((face, 1)),
+                    }
+                }
+            }
+
+            // On ties, prefer the face with the lower index, so the result
+            // doesn't depend on the order of the samples.
+            let dominant = counts
+                .iter()
+                .max_by_key(|&&(face, count)| (count, Reverse(face)));
+            if let Some(&(face, count)) = dominant {
+                // A pixel that a face covers at all, must not end up with a
+                // coverage of zero, or it would look like it was empty.
+                let coverage = count as f64 / settings.samples.get() as f64;
+                *pixel = FacePixel {
+                    face: Some(face),
+                    coverage: (coverage * 255.).round().max(1.) as u8,
+                };
+            }
+        }
+    });
+
+    Ok(FaceIds {
+        width: settings.width.get(),
+        height: settings.height.get(),
+        pixels,
+    })
+}
+
+/// Settings for [`render`]
+///
+/// None of these can be zero, as there would be no pixels to render, or no
+/// samples to average.
+pub struct Settings {
+    /// The width of the image, in pixels
+    pub width: NonZeroU32,
+
+    /// The height of the image, in pixels
+    pub height: NonZeroU32,
+
+    /// The number of samples per pixel
+    ///
+    /// More samples result in less noise, but take longer to render.
+    pub samples: NonZeroU32,
+}
+
+/// A rendered image
+pub struct Image {
+    /// The width of the image, in pixels
+    pub width: u32,
+
+    /// The height of the image, in pixels
+    pub height: u32,
+
+    /// The pixels of the image, row by row, as 8-bit sRGB
+    pub pixels: Vec<[u8; 3]>,
+}
+
+/// An image of the faces of a model, as rendered by [`render_face_ids`]
+pub struct FaceIds {
+    /// The width of the image, in pixels
+    pub width: u32,
+
+    /// The height of the image, in pixels
+    pub height: u32,
+
+    /// The pixels of the image, row by row
+    pub pixels: Vec<FacePixel>,
+}
+
+/// A pixel of [`FaceIds`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FacePixel {
+    /// The index of the face that covers most of the pixel
+    ///
+    /// This is the face's index in the model's boundary representation, the
+    /// same ID that the `validate` command reports. `None`, if no face is
+    /// visible at the pixel.
+    pub face: Option<usize>,
+
+    /// How much of the pixel the face covers, from 0 (nothing) to 255 (all)
+    ///
+    /// This is only 0, if `face` is `None`.
+    pub coverage: u8,
+}
+
+/// The camera that a scene is rendered from
+pub struct Camera {
+    position: Point3<f64>,
+    forward: Vector3<f64>,
+    right: Vector3<f64>,
+    up: Vector3<f64>,
+}
+
+impl Camera {
+    /// The horizontal field of view
+    ///
+    /// Narrower than the one of the interactive camera, as a wide field of
+    /// view results in distortions that are more noticeable in still images.
+    const FIELD_OF_VIEW_IN_X: f64 = PI / 4.; // 45 degrees
+
+    /// Construct a camera that looks at the center of an [`Aabb`]
+    ///
+    /// The camera is placed on a sphere around the center of the bounding box,
+    /// such that the bounding box fits into the image. `azimuth` is the angle
+    /// around the z axis, measured from the negative y axis. `elevation` is
+    /// the angle above the x-y plane. Both are in radians, and `elevation` is
+    /// clamped to the range between -89 and 89 degrees.
+    pub fn orbit(
+        aabb: &Aabb<3>,
+        azimuth: Scalar,
+        elevation: Scalar,
+        aspect_ratio: f64,
+    ) -> Self {
+        let max_elevation = 89_f64.to_radians();
+        let azimuth = azimuth.into_f64();
+        let elevation =
+            elevation.into_f64().clamp(-max_elevation, max_elevation);
+
+        let center = aabb.center().to_na();
+        let radius = (aabb.size().magnitude() / 2.).into_f64();
+
+        let half_fov_x = Self::FIELD_OF_VIEW_IN_X / 2.;
+        let half_fov_y = (half_fov_x.tan() / aspect_ratio).atan();
+        let distance = radius / half_fov_x.min(half_fov_y).sin();
+
+        let direction = vector![
+            elevation.cos() * azimuth.sin(),
+            -elevation.cos() * azimuth.cos(),
+            elevation.sin()
+        ];
+
+        let forward = -direction;
+        let right = forward.cross(&Vector3::z()).normalize();
+        let up = right.cross(&forward);
+
+        Self {
+            position: center + direction * distance,
+            forward,
+            right,
+            up,
+        }
+    }
+
+    /// Compute the ray through a point on the image
+    ///
+    /// `u` and `v` are the coordinates of the point, from 0 to 1, with the
+    /// origin in the top-left corner.
+    fn ray(&self, u: f64, v: f64, aspect_ratio: f64) -> Ray {
+        let f = (Self::FIELD_OF_VIEW_IN_X / 2.).tan();
+
+        let x = (u * 2. - 1.) * f;
+        let y = (1. - v * 2.) * f / aspect_ratio;
+
+        let dir = (self.forward + self.right * x + self.up * y).normalize();
+
+        Ray {
+            origin: self.position,
+            dir,
+        }
+    }
+}
+
+struct Scene {
+    mesh: TriMesh,
+    ground: f64,
+    sun: Vector3<f64>,
+
+    /// Distance by which secondary rays are offset from the surface
+    ///
+    /// Prevents them from hitting the surface they originate from, due to
+    /// floating-point inaccuracies.
+    epsilon: f64,
+}
+
+impl Scene {
+    const MAX_BOUNCES: usize = 4;
+
+    const MODEL_ALBEDO: [f64; 3] = [0.7, 0.05, 0.05];
+    const GROUND_ALBEDO: [f64; 3] = [0.8, 0.8, 0.8];
+
+    const SUN_IRRADIANCE: f64 = 2.5;
+
+    fn new(
+        triangles: &[Triangle<3>],
+        camera: &Camera,
+    ) -> Result<Self, IndexOverflow> {
+        assert!(!triangles.is_empty(), "Can't render empty mesh");
+
+        let mut mesh = MeshMaker::new();
+        for triangle in triangles {
+            for point in triangle.points() {
+                mesh.push(point)?;
+            }
+        }
+
+        let vertices: Vec<_> =
+            mesh.vertices().map(|point| point.to_na()).collect();
+        let indices = mesh.indices().collect::<Vec<_>>();
+        let indices = indices
+            .chunks(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+            .collect();
+
+        let aabb = Aabb::<3>::from_points(
+            triangles.iter().flat_map(|triangle| triangle.points()),
+        );
+
+        // Light the scene from above, behind and to the left of the camera,
+        // so the sides of the model that face the camera are lit.
+        let back = vector![-camera.forward.x, -camera.forward.y, 0.];
+        let back = back.try_normalize(0.).unwrap_or_else(|| -Vector3::y());
+        let sun = (back - camera.right * 0.7 + Vector3::z() * 1.5).normalize();
+
+        Ok(Self {
+            mesh: TriMesh::new(vertices, indices),
+            ground: aabb.min.z.into_f64(),
+            sun,
+            epsilon: aabb.size().magnitude().into_f64() * 1e-6,
+        })
+    }
+
+    /// Compute the radiance arriving along the ray
+    fn trace(&self, mut ray: Ray, rng: &mut Rng) -> Vector3<f64> {
+        let mut radiance = Vector3::zeros();
+        let mut throughput = Vector3::repeat(1.);
+
+        for _ in 0..Self::MAX_BOUNCES {
+            let hit = match self.intersect(&ray) {
+                Some(hit) => hit,
+                None => {
+                    radiance += throughput.component_mul(&sky(&ray.dir));
+                    break;
+                }
+            };
+
+            let origin = hit.point + hit.normal * self.epsilon;
+
+            // Lambertian surfaces reflect incoming light evenly into all
+            // directions, hence the division by pi.
+            let cos_sun = hit.normal.dot(&self.sun);
+            if cos_sun > 0. {
+                let shadow_ray = Ray {
+                    origin,
+                    dir: self.sun,
+                };
+                if !self.mesh.intersects_local_ray(&shadow_ray, f64::MAX) {
+                    radiance += throughput.component_mul(&hit.albedo)
+                        * (Self::SUN_IRRADIANCE * cos_sun / PI);
+                }
+            }
+
+            // Sampling proportional to the cosine term cancels out both it
+            // and the division by pi, leaving just the albedo.
+            throughput = throughput.component_mul(&hit.albedo);
+            ray = Ray {
+                origin,
+                dir: sampling::cosine_hemisphere(&hit.normal, rng),
+            };
+        }
+
+        radiance
+    }
+
+    /// The index of the model's triangle that the ray hits first, if any
+    fn triangle(&self, ray: &Ray) -> Option<usize> {
+        let hit =
+            self.mesh
+                .cast_local_ray_and_get_normal(ray, f64::MAX, false)?;
+
+        match hit.feature {
+            // Hits on the back of a triangle are numbered after the front, so
+            // wrap them around.
+            FeatureId::Face(i) => Some(i as usize % self.mesh.indices().len()),
+            _ => None,
+        }
+    }
+
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let model =
+            self.mesh
+                .cast_local_ray_and_get_normal(ray, f64::MAX, false);
+
+        let ground = if ray.dir.z < 0. {
+            Some((self.ground - ray.origin.z) / ray.dir.z)
+                .filter(|&toi| toi > 0.)
+        } else {
+            None
+        };
+
+        // Only keep the closer of the two hits.
+        let model = model.filter(|model| match ground {
+            Some(ground) => model.toi <= ground,
+            None => true,
+        });
+
+        let (toi, normal, albedo) = match (model, ground) {
+            (Some(model), _) => (model.toi, model.normal, Self::MODEL_ALBEDO),
+            (None, Some(ground)) => (ground, Vector3::z(), Self::GROUND_ALBEDO),
+            (None, None) => return None,
+        };
+
+        // The orientation of the model's triangles is not reliable, so make
+        // sure the normal always faces the incoming ray.
+        let normal = if normal.dot(&ray.dir) > 0. {
+            -normal
+        } else {
+            normal
+        };
+
+        Some(Hit {
+            point: ray.point_at(toi),
+            normal,
+            albedo: albedo.into(),
+        })
+    }
+}
+
+struct Hit {
+    point: Point3<f64>,
+    normal: Vector3<f64>,
+    albedo: Vector3<f64>,
+}
+
+/// The radiance of the sky in the given direction
+fn sky(dir: &Vector3<f64>) -> Vector3<f64> {
+    let horizon = vector![1.0, 1.0, 1.0];
+    let zenith = vector![0.55, 0.7, 0.9];
+
+    let t = dir.z.max(0.);
+    horizon.lerp(&zenith, t)
+}
+
+/// Convert radiance to an 8-bit sRGB color
+fn tone_map(radiance: Vector3<f64>) -> [u8; 3] {
+    [radiance.x, radiance.y, radiance.z].map(|channel| {
+        // Reinhard tone mapping, followed by an approximation of the sRGB
+        // transfer function.
+        let channel = channel / (1. + channel);
+        let channel = channel.powf(1. / 2.2);
+
+        (channel * 255.).round().clamp(0., 255.) as u8
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use crate::{
+        math::{Aabb, Point, Scalar, Triangle},
+        mesh::FaceGroups,
+    };
+
+    use super::{render_face_ids, Camera, Settings};
+
+    #[test]
+    fn render_face_ids_should_find_visible_faces() {
+        // Two faces next to each other, in the x-z plane, facing the camera.
+        // Each consists of two triangles.
+        let quad = |x: f64| {
+            let [a, b, c, d] =
+                [[x, 0., 0.], [x + 1., 0., 0.], [x + 1., 0., 1.], [x, 0., 1.]]
+                    .map(Point::from);
+            [Triangle::from([a, b, c]), Triangle::from([a, c, d])]
+        };
+        let triangles: Vec<_> = quad(0.).into_iter().chain(quad(1.)).collect();
+
+        let mut faces = FaceGroups::new();
+        faces.push(2);
+        faces.push(2);
+
+        let aabb = Aabb::<3>::from_points(
+            triangles.iter().flat_map(|triangle| triangle.points()),
+        );
+        let camera = Camera::orbit(&aabb, Scalar::ZERO, Scalar::ZERO, 1.);
+
+        // With this many samples, a face that's hit by a single one covers
+        // less than half of a step of the 8-bit coverage.
+        let settings = Settings {
+            width: NonZeroU32::new(16).unwrap(),
+            height: NonZeroU32::new(16).unwrap(),
+            samples: NonZeroU32::new(600).unwrap(),
+        };
+        let ids =
+            render_face_ids(&triangles, &faces, &camera, &settings).unwrap();
+
+        assert_eq!(ids.pixels.len(), 16 * 16);
+        for pixel in &ids.pixels {
+            assert_eq!(pixel.face.is_some(), pixel.coverage > 0);
+        }
+
+        // The faces fill the middle of the image, but not its corners.
+        let pixel = |x: usize, y: usize| ids.pixels[y * 16 + x];
+        let [left, right] = [pixel(4, 8), pixel(11, 8)];
+        assert_eq!(left.coverage, 255);
+        assert_eq!(right.coverage, 255);
+        assert!(left.face.is_some() && right.face.is_some());
+        assert_ne!(left.face, right.face);
+        assert_eq!(pixel(0, 0).face, None);
+    }
+}
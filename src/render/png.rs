@@ -0,0 +1,164 @@
+use std::io::{self, Write as _};
+
+use flate2::{write::ZlibEncoder, Compression, Crc};
+
+use super::{FaceIds, Image};
+
+/// The PNG color type of RGB images
+const RGB: u8 = 2;
+
+/// Write an image as a PNG file
+///
+/// Writes an 8-bit RGB image without any optional chunks, which is all we
+/// need, and saves us from depending on a full image library.
+pub fn write(writer: impl io::Write, image: &Image) -> io::Result<()> {
+    write_inner(writer, image.width, image.height, RGB, &image.pixels)
+}
+
+/// Write the faces of a model as a PNG file
+///
+/// Writes an 8-bit RGB image. The color of each pixel is the index of its face
+/// plus one, as a big-endian 24-bit number (`0` where no face is visible).
+///
+/// The coverage of the pixels isn't written. It can't go into an alpha
+/// channel, as image tools commonly premultiply the color with it, which would
+/// change the IDs.
+///
+/// Returns an error, if a face index doesn't fit into 24 bits.
+pub fn write_face_ids(writer: impl io::Write, ids: &FaceIds) -> io::Result<()> {
+    let pixels = ids
+        .pixels
+        .iter()
+        .map(|pixel| {
+            let id = match pixel.face {
+                Some(face) => u32::try_from(face + 1)
+                    .ok()
+                    .filter(|&id| id < 1 << 24)
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            "Too many faces to write as PNG",
+                        )
+                    })?,
+                None => 0,
+            };
+
+            let [_, r, g, b] = id.to_be_bytes();
+            Ok([r, g, b])
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    write_inner(writer, ids.width, ids.height, RGB, &pixels)
+}
+
+fn write_inner<const N: usize>(
+    mut writer: impl io::Write,
+    width: u32,
+    height: u32,
+    color_type: u8,
+    pixels: &[[u8; N]],
+) -> io::Result<()> {
+    writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+    let mut header = Vec::new();
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    header.extend([
+        8,          // bit depth
+        color_type, // color type
+        0,          // compression method: deflate
+        0,          // filter method: adaptive
+        0,          // interlace method: none
+    ]);
+    write_chunk(&mut writer, b"IHDR", &header)?;
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    for row in pixels.chunks(width as usize) {
+        // Filter type "none". Since rendered images tend to be noisy, other
+        // filters wouldn't gain us much.
+        encoder.write_all(&[0])?;
+
+        for pixel in row {
+            encoder.write_all(pixel)?;
+        }
+    }
+    write_chunk(&mut writer, b"IDAT", &encoder.finish()?)?;
+
+    write_chunk(&mut writer, b"IEND", &[])?;
+
+    Ok(())
+}
+
+fn write_chunk(
+    writer: &mut impl io::Write,
+    kind: &[u8; 4],
+    data: &[u8],
+) -> io::Result<()> {
+    let length = u32::try_from(data.len()).map_err(|_| {
+        io::Error::new(io::ErrorKind::InvalidInput, "PNG chunk too large")
+    })?;
+
+    let mut crc = Crc::new();
+    crc.update(kind);
+    crc.update(data);
+
+    writer.write_all(&length.to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    writer.write_all(&crc.sum().to_be_bytes())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::{FaceIds, FacePixel, Image};
+
+    #[test]
+    fn write() {
+        let image = Image {
+            width: 2,
+            height: 1,
+            pixels: vec![[255, 0, 0], [0, 0, 255]],
+        };
+
+        let mut png = Vec::new();
+        super::write(&mut png, &image).unwrap();
+
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        // The `IEND` chunk is always the same, including its checksum.
+        assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+    }
+
+    #[test]
+    fn write_face_ids() {
+        let ids = FaceIds {
+            width: 2,
+            height: 1,
+            pixels: vec![
+                FacePixel {
+                    face: Some(0x0102),
+                    coverage: 128,
+                },
+                FacePixel::default(),
+            ],
+        };
+
+        let mut png = Vec::new();
+        super::write_face_ids(&mut png, &ids).unwrap();
+
+        // The header: 2x1 pixels, 8-bit RGB
+        assert_eq!(&png[16..26], b"\0\0\0\x02\0\0\0\x01\x08\x02");
+
+        let too_many = FaceIds {
+            width: 1,
+            height: 1,
+            pixels: vec![FacePixel {
+                face: Some(1 << 24),
+                coverage: 255,
+            }],
+        };
+        assert!(super::write_face_ids(&mut Vec::new(), &too_many).is_err());
+    }
+}
@@ -0,0 +1,64 @@
+use std::f64::consts::PI;
+
+use nalgebra::{vector, Vector3};
+
+use crate::random::Rng;
+
+/// Sample a direction from the hemisphere around `normal`
+///
+/// The probability of each direction is proportional to the cosine of its
+/// angle to `normal`.
+pub fn cosine_hemisphere(normal: &Vector3<f64>, rng: &mut Rng) -> Vector3<f64> {
+    let r = rng.next_f64().sqrt();
+    let phi = 2. * PI * rng.next_f64();
+
+    let x = r * phi.cos();
+    let y = r * phi.sin();
+    let z = (1. - r * r).max(0.).sqrt();
+
+    let [tangent, bitangent] = orthonormal_basis(normal);
+    tangent * x + bitangent * y + normal * z
+}
+
+/// Compute two vectors that form an orthonormal basis with `normal`
+///
+/// Uses the method from "Building an Orthonormal Basis, Revisited" by Duff et
+/// al., which doesn't require any branches to avoid degenerate cases.
+fn orthonormal_basis(n: &Vector3<f64>) -> [Vector3<f64>; 2] {
+    let sign = 1_f64.copysign(n.z);
+    let a = -1. / (sign + n.z);
+    let b = n.x * n.y * a;
+
+    [
+        vector![1. + sign * n.x * n.x * a, sign * b, -sign * n.x],
+        vector![b, sign + n.y * n.y * a, -n.y],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+    use nalgebra::vector;
+
+    use crate::random::Rng;
+
+    use super::cosine_hemisphere;
+
+    #[test]
+    fn cosine_hemisphere_stays_in_hemisphere() {
+        let mut rng = Rng::new(0);
+
+        for normal in [
+            vector![0., 0., 1.],
+            vector![0., 0., -1.],
+            vector![1., 2., 3.].normalize(),
+        ] {
+            for _ in 0..100 {
+                let dir = cosine_hemisphere(&normal, &mut rng);
+
+                assert_abs_diff_eq!(dir.magnitude(), 1., epsilon = 1e-9);
+                assert!(dir.dot(&normal) >= 0.);
+            }
+        }
+    }
+}
@@ -0,0 +1,476 @@
+//! Machine-readable reports
+//!
+//! Commands that analyze a model can write their results as JSON, so other
+//! tools can consume them. This is useful in build pipelines, for example to
+//! gate a CI job on the validity of a model.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::{
+    analysis::{
+        diff::{Diff, MaterialChange},
+        manifold::{Problem, ProblemKind},
+        mass::MassProperties,
+        orientation::Orientation,
+        stats::Stats,
+    },
+    export::Options,
+    kernel::{
+        shape::ValidationError, shapes::anchor::Anchor,
+        topology::vertices::Vertex,
+    },
+    math::{Aabb, Point, Scalar},
+    mesh::FaceGroups,
+    solve::{Evaluation, Solution},
+    stackup::{Measurement, StackUp, Summary},
+};
+
+/// The result of the `validate` command
+#[derive(Serialize)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub problems: Vec<ProblemReport>,
+}
+
+impl ValidationReport {
+    pub fn new(problems: &[Problem], faces: &FaceGroups) -> Self {
+        Self {
+            valid: problems.is_empty(),
+            problems: problems
+                .iter()
+                .map(|problem| ProblemReport::new(problem, faces))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ProblemReport {
+    /// The kind of problem, as a `snake_case` identifier
+    pub kind: &'static str,
+
+    /// The positions of the vertices of the edge with the problem
+    pub edge: [[f64; 3]; 2],
+
+    /// The IDs of the triangles that share the edge
+    ///
+    /// A triangle's ID is its index in the model's triangle mesh. That means
+    /// IDs are stable, as long as the model and the tolerance value used for
+    /// triangulating it don't change.
+    pub triangles: Vec<usize>,
+
+    /// The IDs of the model's faces that the triangles belong to
+    ///
+    /// A face's ID is its index in the model's boundary representation. Like
+    /// triangle IDs, they are stable, as long as the model doesn't change.
+    pub faces: Vec<usize>,
+}
+
+impl ProblemReport {
+    pub fn new(problem: &Problem, faces: &FaceGroups) -> Self {
+        let kind = match problem.kind {
+            ProblemKind::OpenEdge => "open_edge",
+            ProblemKind::NonManifoldEdge => "non_manifold_edge",
+            ProblemKind::InconsistentOrientation => "inconsistent_orientation",
+        };
+
+        Self {
+            kind,
+            edge: problem.edge.map(Point::into),
+            triangles: problem.triangles.clone(),
+            faces: problem.faces(faces),
+        }
+    }
+}
+
+/// The result of the `stats` command
+#[derive(Serialize)]
+pub struct StatsReport {
+    pub faces: usize,
+    pub cycles: usize,
+    pub edges: usize,
+    pub vertices: usize,
+
+    /// The area and perimeter of each face, if they were requested
+    pub face_measurements: Option<Vec<FaceReport>>,
+
+    /// Statistics about the triangle mesh, unless the model is empty
+    pub mesh: Option<MeshStatsReport>,
+
+    /// The mass properties, if the model is a 3-dimensional shape, and all of
+    /// its parts have a material
+    pub mass: Option<MassReport>,
+}
+
+/// The mass properties of a model
+///
+/// See [`MassProperties`] for the units.
+#[derive(Serialize)]
+pub struct MassReport {
+    pub volume: f64,
+    pub mass: f64,
+    pub center_of_mass: [f64; 3],
+
+    /// The inertia tensor, relative to the center of mass
+    pub inertia: [[f64; 3]; 3],
+}
+
+impl MassReport {
+    pub fn new(properties: &MassProperties) -> Self {
+        Self {
+            volume: properties.volume,
+            mass: properties.mass,
+            center_of_mass: properties.center_of_mass.into(),
+            inertia: properties.inertia,
+        }
+    }
+}
+
+/// The result of the `evaluate` command
+///
+/// Combines the results of `validate` and `stats`, so that tools which
+/// automate Fornjot get everything they need from a single document.
+#[derive(Serialize)]
+pub struct EvaluateReport {
+    /// The tolerance that the model was triangulated with
+    pub tolerance: f64,
+
+    pub validation: ValidationReport,
+    pub stats: StatsReport,
+
+    /// The anchors of the model, in model coordinates
+    pub anchors: Vec<AnchorReport>,
+
+    /// Problems that the kernel found while evaluating the model
+    ///
+    /// Unlike the problems in `validation`, these were found in the model's
+    /// boundary representation, not in its triangle mesh. They weren't fatal,
+    /// but can explain problems with the mesh.
+    pub diagnostics: Vec<DiagnosticReport>,
+
+    /// The paths of the files that the model was exported to
+    pub exports: Vec<PathBuf>,
+}
+
+/// A problem that the kernel found while evaluating a model
+#[derive(Serialize)]
+pub struct DiagnosticReport {
+    pub message: String,
+
+    /// The positions of the objects that caused the problem, if known
+    pub positions: Vec<[f64; 3]>,
+}
+
+impl DiagnosticReport {
+    pub fn new(error: &ValidationError<Vertex>) -> Self {
+        Self {
+            message: error.to_string(),
+            positions: error.positions().into_iter().map(Point::into).collect(),
+        }
+    }
+}
+
+/// Written instead of a report, if a command failed
+#[derive(Serialize)]
+pub struct ErrorReport {
+    /// The error message, including its causes
+    pub error: String,
+}
+
+/// The area and perimeter of a face
+#[derive(Serialize)]
+pub struct FaceReport {
+    pub area: f64,
+    pub perimeter: f64,
+}
+
+#[derive(Serialize)]
+pub struct MeshStatsReport {
+    pub triangles: usize,
+    pub vertices: usize,
+    pub aabb: AabbReport,
+    pub area: f64,
+
+    /// The volume of the model, if it is a closed 3-dimensional shape
+    pub volume: Option<f64>,
+}
+
+impl MeshStatsReport {
+    pub fn new(stats: &Stats, closed: bool) -> Self {
+        Self {
+            triangles: stats.triangles,
+            vertices: stats.vertices,
+            aabb: AabbReport::new(&stats.aabb),
+            area: stats.area.into_f64(),
+            volume: closed.then(|| stats.volume.into_f64()),
+        }
+    }
+}
+
+/// The result of the `diff` command
+#[derive(Serialize)]
+pub struct DiffReport {
+    pub before: MeshStatsReport,
+    pub after: MeshStatsReport,
+
+    /// The change in volume, if the volume of both versions is known
+    pub volume_delta: Option<f64>,
+
+    pub hausdorff_distance: f64,
+
+    /// The volume of the material that was added, approximated by voxels
+    pub added_volume: f64,
+
+    /// The volume of the material that was removed, approximated by voxels
+    pub removed_volume: f64,
+
+    /// The size of the voxels that approximate the material
+    pub voxel_size: f64,
+}
+
+impl DiffReport {
+    pub fn new(diff: &Diff, change: &MaterialChange) -> Self {
+        let [before_volume, after_volume] = diff.volume;
+
+        Self {
+            before: MeshStatsReport::new(&diff.before, before_volume.is_some()),
+            after: MeshStatsReport::new(&diff.after, after_volume.is_some()),
+            volume_delta: diff.volume_delta().map(Scalar::into_f64),
+            hausdorff_distance: diff.hausdorff_distance.into_f64(),
+            added_volume: change.added.volume().into_f64(),
+            removed_volume: change.removed.volume().into_f64(),
+            voxel_size: change.added.grid().size.into_f64(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct AabbReport {
+    pub min: [f64; 3],
+    pub max: [f64; 3],
+}
+
+impl AabbReport {
+    pub fn new(aabb: &Aabb<3>) -> Self {
+        Self {
+            min: aabb.min.into(),
+            max: aabb.max.into(),
+        }
+    }
+}
+
+/// The anchors of a model, as written by the `export` command
+#[derive(Serialize)]
+pub struct AnchorsReport {
+    pub anchors: Vec<AnchorReport>,
+}
+
+impl AnchorsReport {
+    /// Create the report, in the coordinates of the exported file
+    pub fn new(anchors: &[Anchor], options: &Options) -> Self {
+        Self {
+            anchors: anchors
+                .iter()
+                .map(|anchor| AnchorReport::new(anchor, options))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct AnchorReport {
+    pub name: String,
+    pub origin: [f64; 3],
+
+    /// The axes of the anchor's frame, which have a length of one
+    pub x_axis: [f64; 3],
+    pub y_axis: [f64; 3],
+    pub z_axis: [f64; 3],
+}
+
+impl AnchorReport {
+    pub fn new(anchor: &Anchor, options: &Options) -> Self {
+        let frame = options.transform_frame(&anchor.frame);
+
+        Self {
+            name: anchor.name.clone(),
+            origin: frame.origin.into(),
+            x_axis: frame.x_axis.into(),
+            y_axis: frame.y_axis.into(),
+            z_axis: frame.z_axis.into(),
+        }
+    }
+}
+
+/// The result of the `solve` command
+#[derive(Serialize)]
+pub struct SolveReport {
+    pub parameter: String,
+    pub value: f64,
+
+    /// The value of the property, for the parameter value that was found
+    pub property: f64,
+
+    /// The evaluations of the model, in order
+    pub evaluations: Vec<EvaluationReport>,
+}
+
+impl SolveReport {
+    pub fn new(parameter: String, solution: &Solution) -> Self {
+        Self {
+            parameter,
+            value: solution.parameter,
+            property: solution.value,
+            evaluations: solution
+                .evaluations
+                .iter()
+                .map(EvaluationReport::new)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct EvaluationReport {
+    pub value: f64,
+    pub property: f64,
+}
+
+impl EvaluationReport {
+    pub fn new(evaluation: &Evaluation) -> Self {
+        Self {
+            value: evaluation.parameter,
+            property: evaluation.value,
+        }
+    }
+}
+
+/// The result of the `orient` command
+#[derive(Serialize)]
+pub struct OrientReport {
+    /// The maximum overhang angle, in degrees from the vertical
+    pub max_angle: f64,
+
+    /// The suggested orientations, best first
+    pub orientations: Vec<OrientationReport>,
+}
+
+impl OrientReport {
+    pub fn new(max_angle: f64, orientations: &[Orientation]) -> Self {
+        Self {
+            max_angle,
+            orientations: orientations
+                .iter()
+                .map(OrientationReport::new)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct OrientationReport {
+    /// The direction in model space that points towards the print bed
+    pub down: [f64; 3],
+
+    pub overhang_area: f64,
+    pub support_volume: f64,
+    pub height: f64,
+    pub center_of_mass_height: f64,
+}
+
+impl OrientationReport {
+    pub fn new(orientation: &Orientation) -> Self {
+        Self {
+            down: orientation.down.into(),
+            overhang_area: orientation.overhang_area,
+            support_volume: orientation.support_volume,
+            height: orientation.height,
+            center_of_mass_height: orientation.center_of_mass_height,
+        }
+    }
+}
+
+/// The result of the `stack-up` command
+#[derive(Serialize)]
+pub struct StackUpReport {
+    /// The names of the parameters that were varied
+    pub parameters: Vec<String>,
+
+    /// The parameter values of each sample, in the order of `parameters`
+    pub samples: Vec<Vec<f64>>,
+
+    pub measurements: Vec<MeasurementReport>,
+}
+
+impl StackUpReport {
+    pub fn new(
+        stack_up: &StackUp,
+        samples: &[Vec<f64>],
+        measurements: &[Measurement],
+        summaries: &[Summary],
+        values: &[Vec<f64>],
+    ) -> Self {
+        Self {
+            parameters: stack_up
+                .variations
+                .iter()
+                .map(|variation| variation.parameter.clone())
+                .collect(),
+            samples: samples.to_vec(),
+            measurements: measurements
+                .iter()
+                .zip(summaries)
+                .zip(values)
+                .map(|((measurement, summary), values)| MeasurementReport {
+                    measurement: measurement.to_string(),
+                    min: summary.min,
+                    max: summary.max,
+                    mean: summary.mean,
+                    std_dev: summary.std_dev,
+                    values: values.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct MeasurementReport {
+    pub measurement: String,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+
+    /// The measured value of each sample
+    pub values: Vec<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        kernel::{shape::ValidationError, topology::vertices::Vertex},
+        math::Point,
+    };
+
+    use super::DiagnosticReport;
+
+    #[test]
+    fn diagnostic_report_should_serialize_message_and_positions() {
+        let error = ValidationError::<Vertex>::NotClosed(vec![Point::from([
+            1., 2., 3.,
+        ])]);
+
+        let report =
+            serde_json::to_value(DiagnosticReport::new(&error)).unwrap();
+        assert_eq!(
+            report,
+            serde_json::json!({
+                "message": "Edges don't form a closed loop",
+                "positions": [[1., 2., 3.]],
+            })
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Solving for model parameters
+//!
+//! Sometimes, a model needs to have a certain property, like a given volume or
+//! mass, and it's not obvious which parameter values result in that. A
+//! [`Solver`] finds the value of a parameter, that makes a measured
+//! [`Property`] hit a target value, by evaluating the model repeatedly.
+//!
+//! Only one parameter is adjusted at a time. The others keep the values they
+//! are given, so multiple parameters can be solved for one after another.
+
+use std::{fmt, str::FromStr};
+
+use crate::{convention::ParseError, evaluation::ProcessedShape, math::Scalar};
+
+/// A property of a shape, that can be measured after it was processed
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Property {
+    /// The volume that the shape encloses (`volume`)
+    Volume,
+
+    /// The area of the shape's surface (`area`)
+    Area,
+
+    /// The mass of the shape, in grams (`mass`)
+    ///
+    /// Computed from the volume, assuming the shape is modeled in
+    /// millimeters, and a density in grams per cubic centimeter.
+    Mass,
+
+    /// The size of the shape along the x-axis (`size-x`)
+    SizeX,
+
+    /// The size of the shape along the y-axis (`size-y`)
+    SizeY,
+
+    /// The size of the shape along the z-axis (`size-z`)
+    SizeZ,
+}
+
+impl Property {
+    /// Measure the property of a shape
+    ///
+    /// `density` is only used for [`Property::Mass`], and required for it.
+    pub fn measure(
+        &self,
+        shape: &ProcessedShape,
+        density: Option<f64>,
+    ) -> Result<f64, MeasureError> {
+        let size = shape.aabb.size();
+
+        let value = match self {
+            Self::Volume => volume(shape)?,
+            Self::Area => {
+                shape.stats().ok_or(MeasureError::Empty)?.area.into_f64()
+            }
+            Self::Mass => {
+                let density = density.ok_or(MeasureError::MissingDensity)?;
+
+                // Cubic millimeters to cubic centimeters
+                volume(shape)? / 1000. * density
+            }
+            Self::SizeX => size.x.into_f64(),
+            Self::SizeY => size.y.into_f64(),
+            Self::SizeZ => size.z.into_f64(),
+        };
+
+        Ok(value)
+    }
+}
+
+fn volume(shape: &ProcessedShape) -> Result<f64, MeasureError> {
+    if shape.triangles.is_empty() {
+        return Err(MeasureError::Empty);
+    }
+
+    shape
+        .volume()
+        .map(Scalar::into_f64)
+        .ok_or(MeasureError::NotClosed)
+}
+
+impl FromStr for Property {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "volume" => Ok(Self::Volume),
+            "area" => Ok(Self::Area),
+            "mass" => Ok(Self::Mass),
+            "size-x" => Ok(Self::SizeX),
+            "size-y" => Ok(Self::SizeY),
+            "size-z" => Ok(Self::SizeZ),
+            _ => Err(ParseError(
+                "property",
+                "volume, area, mass, size-x, size-y, size-z",
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Property {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Volume => "volume",
+            Self::Area => "area",
+            Self::Mass => "mass",
+            Self::SizeX => "size-x",
+            Self::SizeY => "size-y",
+            Self::SizeZ => "size-z",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Returned by [`Property::measure`], if the property can't be measured
+#[derive(Debug, thiserror::Error)]
+pub enum MeasureError {
+    #[error("Shape is empty")]
+    Empty,
+
+    #[error("Shape doesn't enclose a volume (mesh is not closed)")]
+    NotClosed,
+
+    #[error("Measuring the mass requires a density")]
+    MissingDensity,
+}
+
+/// Finds the parameter value, for which a function hits a target value
+///
+/// The solver searches the range of the parameter, using the Illinois variant
+/// of the regula falsi method. This requires the target value to be between
+/// the values of the function at both ends of the range, and the function to
+/// be continuous. If the function is monotonic within the range, the solution
+/// is unique.
+#[derive(Clone, Debug)]
+pub struct Solver {
+    /// The range of values that the parameter is searched in
+    pub range: [f64; 2],
+
+    /// How far the value of the function may be from the target
+    pub tolerance: f64,
+
+    /// The maximum number of times the function is evaluated
+    pub max_evaluations: usize,
+}
+
+impl Solver {
+    /// Create a solver for the given range
+    ///
+    /// The tolerance is initialized to `0.`, which means the target value is
+    /// hit as accurately as the evaluations allow, and the maximum number of
+    /// evaluations to `20`.
+    pub fn new(range: [f64; 2]) -> Self {
+        Self {
+            range,
+            tolerance: 0.,
+            max_evaluations: 20,
+        }
+    }
+
+    /// Find the parameter value, for which `f` returns `target`
+    ///
+    /// Errors returned by `f` are passed on to the caller.
+    pub fn solve<E>(
+        &self,
+        target: f64,
+        mut f: impl FnMut(f64) -> Result<f64, E>,
+    ) -> Result<Solution, Error<E>>
+    where
+        E: fmt::Debug + fmt::Display,
+    {
+        let mut evaluations = Vec::new();
+        let mut evaluate = |evaluations: &mut Vec<_>, parameter: f64| {
+            let value = f(parameter).map_err(Error::Evaluation)?;
+            evaluations.push(Evaluation { parameter, value });
+            Ok((parameter, value - target))
+        };
+
+        let [min, max] = self.range;
+        let mut a = evaluate(&mut evaluations, min)?;
+        let mut b = evaluate(&mut evaluations, max)?;
+
+        if a.1.signum() == b.1.signum() && a.1 != 0. && b.1 != 0. {
+            return Err(Error::NotBracketed {
+                target,
+                at_min: a.1 + target,
+                at_max: b.1 + target,
+            });
+        }
+
+        // The values that are used to interpolate between the ends of the
+        // interval. Whenever the same end is kept twice in a row, its value is
+        // halved, to prevent it from being stuck. This is what distinguishes
+        // the Illinois method from the plain regula falsi.
+        let [mut f_a, mut f_b] = [a.1, b.1];
+
+        // The end of the interval that was kept in the previous iteration
+        let mut kept_a = None;
+
+        loop {
+            let best = if a.1.abs() <= b.1.abs() { a } else { b };
+            if best.1.abs() <= self.tolerance || a.0 == b.0 {
+                return Ok(Solution {
+                    parameter: best.0,
+                    value: best.1 + target,
+                    evaluations,
+                });
+            }
+            if evaluations.len() >= self.max_evaluations {
+                return Err(Error::NotConverged {
+                    parameter: best.0,
+                    value: best.1 + target,
+                    evaluations: evaluations.len(),
+                });
+            }
+
+            let parameter = (a.0 * f_b - b.0 * f_a) / (f_b - f_a);
+            let c = evaluate(&mut evaluations, parameter)?;
+
+            // Replace the end of the interval, whose value has the same sign
+            // as the new one.
+            if c.1.signum() == a.1.signum() {
+                a = c;
+                f_a = c.1;
+                if kept_a == Some(false) {
+                    f_b /= 2.;
+                }
+                kept_a = Some(false);
+            } else {
+                b = c;
+                f_b = c.1;
+                if kept_a == Some(true) {
+                    f_a /= 2.;
+                }
+                kept_a = Some(true);
+            }
+        }
+    }
+}
+
+/// The result of a successful [`Solver::solve`]
+#[derive(Clone, Debug)]
+pub struct Solution {
+    /// The parameter value that was found
+    pub parameter: f64,
+
+    /// The value of the function for that parameter
+    pub value: f64,
+
+    /// All evaluations of the function, in order
+    pub evaluations: Vec<Evaluation>,
+}
+
+/// An evaluation of the function, while solving
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Evaluation {
+    /// The parameter value that the function was evaluated for
+    pub parameter: f64,
+
+    /// The value that the function returned
+    pub value: f64,
+}
+
+/// Returned by [`Solver::solve`], if no solution was found
+#[derive(Debug, thiserror::Error)]
+pub enum Error<E: fmt::Debug + fmt::Display> {
+    #[error("Error evaluating model: {0}")]
+    Evaluation(E),
+
+    #[error(
+        "Target value {target} is not between the values at the ends of the \
+        range ({at_min} and {at_max})"
+    )]
+    NotBracketed {
+        target: f64,
+        at_min: f64,
+        at_max: f64,
+    },
+
+    #[error(
+        "No solution found after {evaluations} evaluations; closest value was \
+        {value}, for the parameter value {parameter}"
+    )]
+    NotConverged {
+        parameter: f64,
+        value: f64,
+        evaluations: usize,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::{Error, Solver};
+
+    #[test]
+    fn solve_should_find_parameter() {
+        // The volume of a cube, as a function of its side length
+        let mut solver = Solver::new([1., 10.]);
+        solver.tolerance = 1e-6;
+
+        let solution = solver
+            .solve(50., |side| Ok::<_, Infallible>(side * side * side))
+            .unwrap();
+
+        assert!((solution.parameter - 50f64.cbrt()).abs() < 1e-6);
+        assert!((solution.value - 50.).abs() <= 1e-6);
+        assert!(solution.evaluations.len() < solver.max_evaluations);
+
+        // Works just as well for decreasing functions.
+        let solution =
+            solver.solve(0.5, |x| Ok::<_, Infallible>(1. / x)).unwrap();
+        assert!((solution.parameter - 2.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn solve_should_report_failures() {
+        let solver = Solver::new([1., 10.]);
+
+        assert!(matches!(
+            solver
+                .solve(1000.1, |side| Ok::<_, Infallible>(side * side * side)),
+            Err(Error::NotBracketed { .. })
+        ));
+        assert!(matches!(
+            solver.solve(50., |_| Err::<f64, _>("invalid")),
+            Err(Error::Evaluation("invalid"))
+        ));
+
+        // A step function can't be solved to arbitrary accuracy.
+        let mut solver = Solver::new([0., 1.]);
+        solver.tolerance = 0.1;
+        assert!(matches!(
+            solver.solve(0.5, |x| {
+                Ok::<_, Infallible>(if x < 0.5 { 0. } else { 1. })
+            }),
+            Err(Error::NotConverged {
+                evaluations: 20,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,381 @@
+//! Statistical tolerance stack-up analysis
+//!
+//! A manufactured part never matches its model exactly. Every dimension is
+//! somewhere within a tolerance band around its nominal value, and when parts
+//! are assembled, their deviations add up. Whether the parts still fit
+//! together, can be checked by evaluating the model many times, with each
+//! toleranced parameter set to a random value within its band, and measuring
+//! the result. This is known as a Monte Carlo analysis.
+//!
+//! A [`StackUp`] generates the random parameter values for each evaluation,
+//! from a list of [`Variation`]s. Each evaluation is then measured, using one
+//! or more [`Measurement`]s, and the distribution of each measurement is
+//! summarized as a [`Summary`].
+
+use std::{fmt, str::FromStr};
+
+use crate::{
+    convention::ParseError,
+    evaluation::ProcessedShape,
+    kernel::shapes::anchor::Anchor,
+    random::Rng,
+    solve::{MeasureError, Property},
+};
+
+/// A parameter that varies within a tolerance band
+#[derive(Clone, Debug, PartialEq)]
+pub struct Variation {
+    /// The name of the parameter
+    pub parameter: String,
+
+    /// The nominal value of the parameter
+    pub nominal: f64,
+
+    /// How far the parameter may deviate from its nominal value, either way
+    pub deviation: f64,
+}
+
+impl Variation {
+    /// Pick a random value for the parameter
+    fn sample(&self, distribution: Distribution, rng: &mut Rng) -> f64 {
+        let offset = match distribution {
+            Distribution::Uniform => rng.next_f64() * 2. - 1.,
+            Distribution::Normal => loop {
+                // Box-Muller transform. `1 - x` is in `(0, 1]`, so the
+                // logarithm is always finite.
+                let radius = (-2. * (1. - rng.next_f64()).ln()).sqrt();
+                let angle = 2. * std::f64::consts::PI * rng.next_f64();
+
+                // Values outside of the band are rejected, which happens for
+                // about 0.3% of them. A part that is outside of its tolerance
+                // band would not have passed inspection.
+                let offset = radius * angle.cos() / 3.;
+                if offset.abs() <= 1. {
+                    break offset;
+                }
+            },
+        };
+
+        self.nominal + self.deviation * offset
+    }
+}
+
+impl FromStr for Variation {
+    type Err = InvalidVariation;
+
+    /// Parse a variation in the form `name=nominal+-deviation`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidVariation(s.to_owned());
+
+        let (parameter, band) = s.split_once('=').ok_or_else(invalid)?;
+        let (nominal, deviation) = band.split_once("+-").ok_or_else(invalid)?;
+
+        let nominal: f64 = nominal.trim().parse().map_err(|_| invalid())?;
+        let deviation: f64 = deviation.trim().parse().map_err(|_| invalid())?;
+        if parameter.is_empty() || deviation.is_nan() || deviation < 0. {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            parameter: parameter.to_owned(),
+            nominal,
+            deviation,
+        })
+    }
+}
+
+/// Returned by [`Variation::from_str`], if the variation is invalid
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid variation `{0}`; expected `NAME=NOMINAL+-DEVIATION`")]
+pub struct InvalidVariation(pub String);
+
+/// How the values of a parameter are distributed within its tolerance band
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Distribution {
+    /// All values within the band are equally likely (`uniform`)
+    ///
+    /// This is the pessimistic choice, if nothing is known about the
+    /// manufacturing process.
+    Uniform,
+
+    /// Values are normally distributed around the nominal value (`normal`)
+    ///
+    /// The band covers three standard deviations either way, so 99.7% of the
+    /// values are within it. This is typical for a process that is under
+    /// control. The other values are discarded, so every value is within the
+    /// band.
+    Normal,
+}
+
+impl FromStr for Distribution {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(Self::Uniform),
+            "normal" => Ok(Self::Normal),
+            _ => Err(ParseError("distribution", "uniform, normal")),
+        }
+    }
+}
+
+/// A measurement of an evaluated model
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Measurement {
+    /// A property of the shape, like its size along an axis
+    Property(Property),
+
+    /// The width of a gap between two anchors (`gap:FROM:TO`)
+    ///
+    /// This is the distance from the origin of the anchor `from` to the
+    /// origin of the anchor `to`, along the z-axis of `from`. Place `from` on
+    /// one side of the gap, with its z-axis pointing across it, and `to` on
+    /// the other side. A negative width means that the parts interfere.
+    Gap {
+        /// The name of the anchor on the first side of the gap
+        from: String,
+
+        /// The name of the anchor on the second side of the gap
+        to: String,
+    },
+}
+
+impl Measurement {
+    /// Measure an evaluated model
+    ///
+    /// `anchors` are the anchors of the shape, and `density` is passed on to
+    /// [`Property::measure`].
+    pub fn measure(
+        &self,
+        shape: &ProcessedShape,
+        anchors: &[Anchor],
+        density: Option<f64>,
+    ) -> Result<f64, MeasurementError> {
+        match self {
+            Self::Property(property) => Ok(property.measure(shape, density)?),
+            Self::Gap { from, to } => {
+                let find = |name: &String| {
+                    anchors
+                        .iter()
+                        .find(|anchor| &anchor.name == name)
+                        .map(|anchor| anchor.frame)
+                        .ok_or_else(|| {
+                            MeasurementError::MissingAnchor(name.clone())
+                        })
+                };
+                let [from, to] = [find(from)?, find(to)?];
+
+                Ok((to.origin - from.origin).dot(&from.z_axis).into_f64())
+            }
+        }
+    }
+}
+
+impl FromStr for Measurement {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expected = "volume, area, mass, size-x, size-y, size-z, \
+            gap:FROM:TO";
+
+        if let Some(anchors) = s.strip_prefix("gap:") {
+            return match anchors.split_once(':') {
+                Some((from, to)) if !from.is_empty() && !to.is_empty() => {
+                    Ok(Self::Gap {
+                        from: from.to_owned(),
+                        to: to.to_owned(),
+                    })
+                }
+                _ => Err(ParseError("measurement", expected)),
+            };
+        }
+
+        s.parse()
+            .map(Self::Property)
+            .map_err(|_| ParseError("measurement", expected))
+    }
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Property(property) => write!(f, "{}", property),
+            Self::Gap { from, to } => write!(f, "gap:{}:{}", from, to),
+        }
+    }
+}
+
+/// Returned by [`Measurement::measure`], if the model can't be measured
+#[derive(Debug, thiserror::Error)]
+pub enum MeasurementError {
+    #[error(transparent)]
+    Property(#[from] MeasureError),
+
+    #[error("Model has no anchor named `{0}`")]
+    MissingAnchor(String),
+}
+
+/// Generates the parameter values for a stack-up analysis
+#[derive(Clone, Debug)]
+pub struct StackUp {
+    /// The parameters that vary
+    pub variations: Vec<Variation>,
+
+    /// How the parameter values are distributed within their bands
+    pub distribution: Distribution,
+
+    /// The number of times the model is evaluated
+    pub samples: usize,
+
+    /// The seed for the random number generator
+    ///
+    /// The same seed results in the same parameter values, so an analysis can
+    /// be repeated.
+    pub seed: u64,
+}
+
+impl StackUp {
+    /// Create a stack-up analysis for the given variations
+    ///
+    /// The distribution is initialized to [`Distribution::Normal`], the
+    /// number of samples to `100`, and the seed to `0`.
+    pub fn new(variations: Vec<Variation>) -> Self {
+        Self {
+            variations,
+            distribution: Distribution::Normal,
+            samples: 100,
+            seed: 0,
+        }
+    }
+
+    /// Generate the parameter values for each evaluation
+    ///
+    /// Returns one list of values per sample, in the order of
+    /// [`StackUp::variations`].
+    pub fn parameters(&self) -> Vec<Vec<f64>> {
+        let mut rng = Rng::new(self.seed);
+
+        (0..self.samples)
+            .map(|_| {
+                self.variations
+                    .iter()
+                    .map(|variation| {
+                        variation.sample(self.distribution, &mut rng)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// A summary of the distribution of a measurement
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Summary {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+
+    /// The sample standard deviation
+    pub std_dev: f64,
+}
+
+impl Summary {
+    /// Summarize a list of measured values
+    ///
+    /// Returns `None`, if the list is empty.
+    pub fn new(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let squares: f64 = values.iter().map(|v| (v - mean).powi(2)).sum();
+        let std_dev = if values.len() > 1 {
+            (squares / (n - 1.)).sqrt()
+        } else {
+            0.
+        };
+
+        Some(Self {
+            min: values.iter().cloned().fold(f64::INFINITY, f64::min),
+            max: values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+            mean,
+            std_dev,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solve::Property;
+
+    use super::{Distribution, Measurement, StackUp, Summary, Variation};
+
+    #[test]
+    fn variation_should_parse() {
+        assert_eq!(
+            "width=10+-0.1".parse::<Variation>().unwrap(),
+            Variation {
+                parameter: "width".to_owned(),
+                nominal: 10.,
+                deviation: 0.1,
+            }
+        );
+
+        assert!("width=10".parse::<Variation>().is_err());
+        assert!("=10+-0.1".parse::<Variation>().is_err());
+        assert!("width=10+--0.1".parse::<Variation>().is_err());
+    }
+
+    #[test]
+    fn measurement_should_parse() {
+        assert_eq!(
+            "size-x".parse::<Measurement>().unwrap(),
+            Measurement::Property(Property::SizeX)
+        );
+        assert_eq!(
+            "gap:shaft:bore".parse::<Measurement>().unwrap(),
+            Measurement::Gap {
+                from: "shaft".to_owned(),
+                to: "bore".to_owned(),
+            }
+        );
+
+        assert!("gap:shaft".parse::<Measurement>().is_err());
+        assert!("length".parse::<Measurement>().is_err());
+    }
+
+    #[test]
+    fn parameters_should_stay_within_band() {
+        let variation = Variation {
+            parameter: "width".to_owned(),
+            nominal: 10.,
+            deviation: 0.1,
+        };
+
+        for distribution in [Distribution::Uniform, Distribution::Normal] {
+            let stack_up = StackUp {
+                distribution,
+                samples: 1000,
+                ..StackUp::new(vec![variation.clone(), variation.clone()])
+            };
+
+            let parameters = stack_up.parameters();
+            assert_eq!(parameters.len(), 1000);
+            assert_eq!(parameters, stack_up.parameters());
+
+            let values: Vec<_> = parameters.into_iter().flatten().collect();
+            let summary = Summary::new(&values).unwrap();
+            assert!((summary.mean - 10.).abs() < 0.01);
+
+            assert!(summary.min >= 9.9 && summary.max <= 10.1);
+
+            let expected_std_dev = match distribution {
+                Distribution::Uniform => 0.1 / 3f64.sqrt(),
+                Distribution::Normal => 0.1 / 3.,
+            };
+            assert!((summary.std_dev / expected_std_dev - 1.).abs() < 0.1);
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Support for testing models
+//!
+//! Models are Rust libraries, so they can have unit tests like any other Rust
+//! code. This module provides the means to evaluate a shape from such a test,
+//! without involving the viewer, and to make assertions about the result.
+//!
+//! To use it, add the host application as a development dependency of the
+//! model:
+//!
+//! ``` toml
+//! [dev-dependencies.fj-host]
+//! path = "../.."
+//! ```
+//!
+//! Then call the model function from a test, and check the shape it returns:
+//!
+//! ``` rust
+//! use std::collections::HashMap;
+//!
+//! use fj_host::testing::Evaluated;
+//!
+//! # fn model(_: &HashMap<String, String>) -> fj::Shape {
+//! #     let rectangle = fj::Sketch::from_points(vec![
+//! #         [-1.5, -1.], [1.5, -1.], [1.5, 1.], [-1.5, 1.],
+//! #     ]);
+//! #     fj::Sweep { shape: rectangle.into(), length: 1. }.into()
+//! # }
+//! let shape = model(&HashMap::new());
+//! let evaluated = Evaluated::new(&shape);
+//!
+//! evaluated.assert_aabb([-1.5, -1., 0.], [1.5, 1., 1.]);
+//! evaluated.assert_watertight();
+//! evaluated.assert_volume(6., 0.01);
+//! evaluated.assert_face_area(6., 1e-9);
+//! ```
+
+use crate::{
+    analysis::{
+        diff::Diff,
+        manifold::{self, ProblemKind},
+        stats::Stats,
+    },
+    evaluation::{Evaluation, ShapeProcessor},
+    kernel::algorithms::tolerance::Tolerance,
+    kernel::shapes::ToShape as _,
+    kernel::topology::faces::Face,
+    math::{Aabb, Point, Scalar, Triangle},
+};
+
+/// An evaluated shape
+pub struct Evaluated {
+    evaluation: Evaluation,
+}
+
+impl Evaluated {
+    /// Evaluate a shape, using the default tolerance
+    ///
+    /// This is the same tolerance that the host application uses.
+    pub fn new(shape: &fj::Shape) -> Self {
+        let tolerance = Tolerance::from_bounding_box(&shape.bounding_volume());
+        Self::with_tolerance(shape, tolerance.inner().into_f64())
+    }
+
+    /// Evaluate a shape, using the given tolerance
+    ///
+    /// The tolerance defines how far the triangulation of the shape may
+    /// deviate from its actual geometry. It must be larger than zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the tolerance is invalid, if the shape is degenerate (see
+    /// [`fj::Shape::check`]), or if the kernel can't build it.
+    pub fn with_tolerance(shape: &fj::Shape, tolerance: f64) -> Self {
+        let tolerance = Tolerance::from_scalar(tolerance)
+            .unwrap_or_else(|err| panic!("{}", err));
+        if let Err(err) = shape.check() {
+            panic!("Invalid shape: {}", err);
+        }
+
+        let evaluation = ShapeProcessor::with_tolerance(tolerance)
+            .evaluate(shape)
+            .unwrap_or_else(|err| panic!("Error building shape: {}", err));
+
+        Self { evaluation }
+    }
+
+    /// Access the evaluation of the shape
+    ///
+    /// This allows running analyses that aren't covered by the methods of
+    /// `Evaluated`, without evaluating the shape again.
+    pub fn evaluation(&self) -> &Evaluation {
+        &self.evaluation
+    }
+
+    /// Access the triangles of the evaluated shape
+    pub fn triangles(&self) -> &[Triangle<3>] {
+        &self.evaluation.triangles
+    }
+
+    /// Compute the axis-aligned bounding box of the triangulated shape
+    ///
+    /// Returns `None`, if the shape is empty.
+    pub fn aabb(&self) -> Option<Aabb<3>> {
+        if self.triangles().is_empty() {
+            return None;
+        }
+
+        Some(Aabb::<3>::from_points(
+            self.triangles()
+                .iter()
+                .flat_map(|triangle| triangle.points()),
+        ))
+    }
+
+    /// Indicate whether the triangulated shape is watertight
+    ///
+    /// A shape is watertight, if its triangle mesh has no holes, and every
+    /// edge is shared by exactly two triangles.
+    pub fn is_watertight(&self) -> bool {
+        manifold::check(self.triangles())
+            .iter()
+            .all(|problem| problem.kind == ProblemKind::InconsistentOrientation)
+    }
+
+    /// Compute the volume of the triangulated shape
+    ///
+    /// Returns `None`, if the shape is not watertight, or its triangles can't
+    /// be oriented consistently.
+    pub fn volume(&self) -> Option<f64> {
+        let oriented = manifold::orient(self.triangles())?;
+        Some(Stats::compute(&oriented).volume.into_f64())
+    }
+
+    /// Compute the area of each face of the shape
+    ///
+    /// The areas of plane faces, that are bounded by lines, circles, and
+    /// ellipses, are exact. All others are computed from the triangulation of
+    /// the face (see [`Face::area`]).
+    ///
+    /// [`Face::area`]: crate::kernel::topology::faces::Face::area
+    pub fn face_areas(&self) -> Vec<f64> {
+        self.faces(|face, tolerance| face.area(tolerance))
+    }
+
+    /// Compute the perimeter of each face of the shape
+    ///
+    /// Like [`Evaluated::face_areas`], this is exact for plane faces that are
+    /// bounded by lines, circles, and ellipses.
+    pub fn face_perimeters(&self) -> Vec<f64> {
+        self.faces(|face, tolerance| face.perimeter(tolerance))
+    }
+
+    fn faces(&self, f: impl Fn(&Face, Tolerance) -> Scalar) -> Vec<f64> {
+        self.evaluation
+            .brep()
+            .faces()
+            .map(|face| f(face.get(), self.evaluation.tolerance).into_f64())
+            .collect()
+    }
+
+    /// Compare the shape to another version of it
+    ///
+    /// This is useful to make sure that a change to shared model code, or to
+    /// the tolerance value, doesn't change a shape more than expected. See
+    /// [`Diff`] for what is compared.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if either shape is empty, or has too many vertices to compare.
+    pub fn diff(&self, other: &Evaluated) -> Diff {
+        let tolerance = self
+            .evaluation
+            .tolerance
+            .min(other.evaluation.tolerance)
+            .inner();
+        Diff::compute(self.triangles(), other.triangles(), tolerance)
+            .expect("Too many vertices to compare")
+    }
+
+    /// Assert that the shape has the given bounding box
+    ///
+    /// The bounding box is allowed to deviate by the tolerance that was used
+    /// to evaluate the shape, since the triangulation of curved surfaces
+    /// doesn't exactly reach their extent.
+    #[track_caller]
+    pub fn assert_aabb(&self, min: [f64; 3], max: [f64; 3]) {
+        let aabb = self.aabb().expect("Shape is empty");
+
+        let expected = [Point::from(min), Point::from(max)];
+        let actual = [aabb.min, aabb.max];
+
+        let within_tolerance =
+            expected.into_iter().zip(actual).all(|(expected, actual)| {
+                (actual - expected).magnitude()
+                    <= self.evaluation.tolerance.inner()
+            });
+
+        assert!(
+            within_tolerance,
+            "Unexpected bounding box: expected {:?} - {:?}, got {:?} - {:?}",
+            expected[0], expected[1], actual[0], actual[1],
+        );
+    }
+
+    /// Assert that the shape is watertight
+    ///
+    /// See [`Evaluated::is_watertight`].
+    #[track_caller]
+    pub fn assert_watertight(&self) {
+        assert!(self.is_watertight(), "Shape is not watertight");
+    }
+
+    /// Assert that the shape deviates from another version by no more than
+    /// `max_distance`
+    ///
+    /// The deviation is measured as the Hausdorff distance between both
+    /// shapes. See [`Evaluated::diff`].
+    #[track_caller]
+    pub fn assert_close_to(&self, other: &Evaluated, max_distance: f64) {
+        let distance = self.diff(other).hausdorff_distance.into_f64();
+
+        assert!(
+            distance <= max_distance,
+            "Shapes deviate by {}, which is more than {}",
+            distance,
+            max_distance,
+        );
+    }
+
+    /// Assert that the shape's volume is close to the expected value
+    ///
+    /// `relative_tolerance` is the maximum allowed deviation, relative to
+    /// `expected`. For example, `0.01` means the volume must be within 1% of
+    /// `expected`.
+    #[track_caller]
+    pub fn assert_volume(&self, expected: f64, relative_tolerance: f64) {
+        let volume = self
+            .volume()
+            .expect("Can't compute volume of shape that is not watertight");
+
+        assert!(
+            (volume - expected).abs() <= (expected * relative_tolerance).abs(),
+            "Unexpected volume: expected {} (within {}%), got {}",
+            expected,
+            relative_tolerance * 100.,
+            volume,
+        );
+    }
+
+    /// Assert that the shape has a face with the given area
+    ///
+    /// `relative_tolerance` works like for [`Evaluated::assert_volume`].
+    #[track_caller]
+    pub fn assert_face_area(&self, expected: f64, relative_tolerance: f64) {
+        let areas = self.face_areas();
+
+        assert!(
+            areas.iter().any(|area| {
+                (area - expected).abs() <= (expected * relative_tolerance).abs()
+            }),
+            "No face with area {} (within {}%); face areas are {:?}",
+            expected,
+            relative_tolerance * 100.,
+            areas,
+        );
+    }
+}
+
+/// A cuboid with one corner at the origin, for use in tests
+///
+/// `size` is the extent of the cuboid along the x, y, and z axes.
+#[cfg(test)]
+pub(crate) fn cuboid([x, y, z]: [f64; 3]) -> fj::Sweep {
+    fj::Sweep {
+        shape: fj::Sketch::from_points(vec![
+            [0., 0.],
+            [x, 0.],
+            [x, y],
+            [0., y],
+        ])
+        .into(),
+        length: z,
+    }
+}
@@ -1,29 +1,130 @@
-use winit::{event_loop::EventLoop, window::WindowBuilder};
+use std::f64::consts::PI;
 
-pub struct Window(winit::window::Window);
+use winit::{
+    event_loop::EventLoop,
+    window::{Icon, WindowBuilder},
+};
+
+pub struct Window {
+    inner: winit::window::Window,
+    model_name: String,
+}
 
 impl Window {
-    pub fn new(event_loop: &EventLoop<()>) -> Self {
+    pub fn new(event_loop: &EventLoop<()>, model_name: &str) -> Self {
         let window = WindowBuilder::new()
-            .with_title("Fornjot")
+            .with_title(title(model_name, ModelState::Current))
+            .with_window_icon(Some(icon()))
             .with_maximized(true)
             .with_decorations(true)
             .with_transparent(false)
             .build(event_loop)
             .unwrap();
 
-        Self(window)
+        Self {
+            inner: window,
+            model_name: model_name.to_owned(),
+        }
     }
 
     pub fn inner(&self) -> &winit::window::Window {
-        &self.0
+        &self.inner
     }
 
     pub fn width(&self) -> u32 {
-        self.0.inner_size().width
+        self.inner.inner_size().width
     }
 
     pub fn height(&self) -> u32 {
-        self.0.inner_size().height
+        self.inner.inner_size().height
+    }
+
+    /// Update the window to reflect the state of the displayed model
+    pub fn set_model_state(&self, state: ModelState) {
+        self.inner.set_title(&title(&self.model_name, state));
     }
 }
+
+/// The state of the model that is being displayed
+///
+/// If the model's source code changes, the displayed geometry is stale until
+/// the model has been rebuilt successfully.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ModelState {
+    /// The displayed geometry reflects the current model
+    Current,
+
+    /// The model is being rebuilt
+    Rebuilding,
+
+    /// Rebuilding the model failed
+    Failed,
+}
+
+fn title(model_name: &str, state: ModelState) -> String {
+    let state = match state {
+        ModelState::Current => "",
+        ModelState::Rebuilding => " (rebuilding...)",
+        ModelState::Failed => " (build failed; showing last good version)",
+    };
+
+    format!("Fornjot - {}{}", model_name, state)
+}
+
+/// Create the application icon
+///
+/// The icon is a five-pointed star, like the one in the star model, which is
+/// also used in the screenshot in the README. It is generated here instead of
+/// being loaded from an image file, to save us from having to pull in an image
+/// decoder.
+fn icon() -> Icon {
+    const SIZE: u32 = 64;
+
+    let center = SIZE as f64 / 2.;
+    let r_outer = center * 0.95;
+    let r_inner = r_outer * 0.45;
+
+    let star: Vec<_> = (0..10)
+        .map(|i| {
+            // Start at the top, so the star is pointing upwards.
+            let angle = PI / 5. * i as f64 - PI / 2.;
+            let radius = if i % 2 == 0 { r_outer } else { r_inner };
+
+            let (sin, cos) = angle.sin_cos();
+            [center + cos * radius, center + sin * radius]
+        })
+        .collect();
+
+    let mut rgba = Vec::new();
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let point = [x as f64 + 0.5, y as f64 + 0.5];
+
+            let pixel = if contains(&star, point) {
+                [200, 0, 0, 255]
+            } else {
+                [0, 0, 0, 0]
+            };
+
+            rgba.extend(pixel);
+        }
+    }
+
+    // Can't panic. We generated a buffer of exactly the right size.
+    Icon::from_rgba(rgba, SIZE, SIZE).unwrap()
+}
+
+/// Determine whether a polygon contains a point, using the even-odd rule
+fn contains(polygon: &[[f64; 2]], [x, y]: [f64; 2]) -> bool {
+    let mut inside = false;
+
+    for (i, &[ax, ay]) in polygon.iter().enumerate() {
+        let [bx, by] = polygon[(i + 1) % polygon.len()];
+
+        if (ay > y) != (by > y) && x < (bx - ax) * (y - ay) / (by - ay) + ax {
+            inside = !inside;
+        }
+    }
+
+    inside
+}